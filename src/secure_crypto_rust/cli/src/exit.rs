@@ -0,0 +1,68 @@
+// ## 📂 File: `cli/src/exit.rs`
+//! Exit codes of `rse1`.
+//!
+//! | Code | Meaning                                                     |
+//! |------|-------------------------------------------------------------|
+//! | `0`  | success                                                     |
+//! | `1`  | any other failure (pipeline, compression, cancellation)     |
+//! | `2`  | usage error (reported by the argument parser)               |
+//! | `3`  | validation: malformed header, segment, frame or parameters  |
+//! | `4`  | authentication: wrong key, AEAD tag, digest or plaintext check failed |
+//! | `5`  | I/O error                                                   |
+
+use crypto_core::{
+    crypto::CryptoError,
+    stream_v2::{frame_worker::FrameWorkerError, segment_worker::SegmentWorkerError},
+    types::StreamError,
+};
+
+pub const FAILURE: u8 = 1;
+pub const VALIDATION: u8 = 3;
+pub const AUTHENTICATION: u8 = 4;
+pub const IO: u8 = 5;
+
+/// Exit code for `error`.
+pub fn code(error: &StreamError) -> u8 {
+    match error {
+        StreamError::Io(_) => IO,
+        StreamError::At { source, .. } => code(source),
+        StreamError::ChecksumMismatch { .. }
+        | StreamError::TruncatedStream { .. }
+        | StreamError::StreamDigestMismatch => AUTHENTICATION,
+        StreamError::Crypto(CryptoError::TagMismatch | CryptoError::KeyConfirmationFailed) => AUTHENTICATION,
+        StreamError::SegmentWorker(e) => segment_worker_code(e),
+        StreamError::Crypto(_)
+        | StreamError::Aad(_)
+        | StreamError::Header(_)
+        | StreamError::Segment(_)
+        | StreamError::Frame(_)
+        | StreamError::Nonce(_)
+        | StreamError::UnknownKeyId { .. }
+        | StreamError::Validation(_) => VALIDATION,
+        StreamError::CompressionWorker(_)
+        | StreamError::Compression(_)
+        | StreamError::ReorderBufferOverflow { .. }
+        | StreamError::PipelineError(_)
+        | StreamError::Cancelled => FAILURE,
+    }
+}
+
+fn segment_worker_code(error: &SegmentWorkerError) -> u8 {
+    match error {
+        SegmentWorkerError::DigestError(_) | SegmentWorkerError::CryptoError(CryptoError::TagMismatch) => AUTHENTICATION,
+        SegmentWorkerError::FrameWorkerError(e) => frame_worker_code(e),
+        SegmentWorkerError::StateError(_) | SegmentWorkerError::WorkerPanicked(_) => FAILURE,
+        _ => VALIDATION,
+    }
+}
+
+fn frame_worker_code(error: &FrameWorkerError) -> u8 {
+    match error {
+        FrameWorkerError::Crypto(CryptoError::TagMismatch)
+        | FrameWorkerError::CryptoFailure(_)
+        | FrameWorkerError::AuthenticationFailed { .. } => AUTHENTICATION,
+        FrameWorkerError::AtFrame { source, .. } => frame_worker_code(source),
+        FrameWorkerError::WorkerDisconnected | FrameWorkerError::WorkerMissing | FrameWorkerError::WorkerPanicked(_) => FAILURE,
+        _ => VALIDATION,
+    }
+}
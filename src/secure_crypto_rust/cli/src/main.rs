@@ -0,0 +1,323 @@
+// # 📂 `cli/src/main.rs`
+//! rse1: command line front-end for segmented encrypted streams.
+//!
+//! ```text
+//! rse1 encrypt <in> <out> --key-file <path> [--codec zstd|lz4|deflate] [--chunk-size N] [--profile parallel|sequential|auto]
+//! rse1 decrypt <in> <out> --key-file <path>
+//! rse1 inspect <in>
+//! rse1 verify  <in> --key-file <path>
+//! ```
+//!
+//! - Key files hold the raw master key bytes (16, 24 or 32 bytes).
+//! - `--json` switches every subcommand to one line of JSON on stdout.
+//! - Exit codes separate validation, authentication and I/O failures (see `exit`).
+
+mod exit;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use serde_json::json;
+
+use crypto_core::{
+    compression::CompressionCodec,
+    headers::{AlgProfile, CipherSuite, HeaderV1, HkdfPrf, Strategy},
+    stream_v2::{
+        ApiConfig, DecryptParams, EncryptParams, InputSource, OutputSink, VerifyReport,
+        decrypt_stream_v2, encrypt_stream_v2,
+        io::{SegmentInfo, iter_segment_headers},
+        verify_stream_v2,
+    },
+    telemetry::TelemetrySnapshot,
+    types::StreamError,
+    utils::enum_name_or_hex,
+};
+
+#[derive(Debug, Parser)]
+#[command(name = "rse1", version, about = "Encrypt, decrypt, inspect and verify segmented encrypted streams")]
+struct Cli {
+    /// Print machine-readable JSON instead of the one-line summary
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Encrypt <INPUT> into <OUTPUT>
+    Encrypt {
+        input: PathBuf,
+        output: PathBuf,
+        #[arg(long)]
+        key_file: PathBuf,
+        /// Compression codec (default: chosen per stream)
+        #[arg(long, value_enum)]
+        codec: Option<Codec>,
+        /// Segment size in bytes, rounded up to a supported size
+        #[arg(long)]
+        chunk_size: Option<u32>,
+        /// Worker strategy recorded in the stream header
+        #[arg(long, value_enum)]
+        profile: Option<Profile>,
+    },
+    /// Decrypt <INPUT> into <OUTPUT>
+    Decrypt {
+        input: PathBuf,
+        output: PathBuf,
+        #[arg(long)]
+        key_file: PathBuf,
+    },
+    /// Print the stream header and segment table (no key needed)
+    Inspect { input: PathBuf },
+    /// Authenticate every segment without writing plaintext
+    Verify {
+        input: PathBuf,
+        #[arg(long)]
+        key_file: PathBuf,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Codec {
+    Zstd,
+    Lz4,
+    Deflate,
+}
+
+impl From<Codec> for CompressionCodec {
+    fn from(codec: Codec) -> Self {
+        match codec {
+            Codec::Zstd => CompressionCodec::Zstd,
+            Codec::Lz4 => CompressionCodec::Lz4,
+            Codec::Deflate => CompressionCodec::Deflate,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Profile {
+    Parallel,
+    Sequential,
+    Auto,
+}
+
+impl From<Profile> for Strategy {
+    fn from(profile: Profile) -> Self {
+        match profile {
+            Profile::Parallel => Strategy::Parallel,
+            Profile::Sequential => Strategy::Sequential,
+            Profile::Auto => Strategy::Auto,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("rse1: {e}");
+            ExitCode::from(exit::code(&e))
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<(), StreamError> {
+    match cli.command {
+        Command::Encrypt { input, output, key_file, codec, chunk_size, profile } => {
+            let mut builder = HeaderV1::builder();
+            if let Some(codec) = codec {
+                builder = builder.compression(codec.into());
+            }
+            if let Some(chunk_size) = chunk_size {
+                builder = builder.chunk_size(chunk_size);
+            }
+            if let Some(profile) = profile {
+                builder = builder.strategy(profile.into());
+            }
+            let header = builder.build().map_err(StreamError::Header)?;
+
+            let snapshot = encrypt_stream_v2(
+                InputSource::File(input),
+                OutputSink::File(output),
+                &read_key(&key_file)?,
+                EncryptParams::new(header),
+                ApiConfig::default(),
+            )?;
+            print_telemetry("encrypt", &snapshot, cli.json);
+            Ok(())
+        }
+        Command::Decrypt { input, output, key_file } => {
+            let snapshot = decrypt_stream_v2(
+                InputSource::File(input),
+                OutputSink::File(output),
+                &read_key(&key_file)?,
+                DecryptParams::new(),
+                ApiConfig::default(),
+            )?;
+            print_telemetry("decrypt", &snapshot, cli.json);
+            Ok(())
+        }
+        Command::Inspect { input } => inspect(&input, cli.json),
+        Command::Verify { input, key_file } => {
+            let report = verify_stream_v2(InputSource::File(input), &read_key(&key_file)?)?;
+            print_verify(&report, cli.json);
+            match report.failure {
+                Some(failure) => Err(failure.error),
+                None => Ok(()),
+            }
+        }
+    }
+}
+
+fn read_key(path: &Path) -> Result<Vec<u8>, StreamError> {
+    Ok(fs::read(path)?)
+}
+
+fn print_telemetry(operation: &str, snapshot: &TelemetrySnapshot, as_json: bool) {
+    if as_json {
+        println!("{}", snapshot.to_json());
+        return;
+    }
+    let or_na = |v: Option<f64>, fmt: &dyn Fn(f64) -> String| v.map(fmt).unwrap_or_else(|| "n/a".into());
+    println!(
+        "{operation}: segments={} plaintext={}B ciphertext={}B ratio={} throughput={}",
+        snapshot.segments_processed,
+        snapshot.bytes_plaintext,
+        snapshot.bytes_ciphertext + snapshot.bytes_overhead,
+        or_na(snapshot.compression_ratio(), &|r| format!("{r:.3}")),
+        or_na(snapshot.throughput_mbps(), &|t| format!("{t:.2}MB/s")),
+    );
+}
+
+fn print_verify(report: &VerifyReport, as_json: bool) {
+    let failure = report.failure.as_ref();
+    if as_json {
+        let failure = failure.map(|f| {
+            json!({ "segment_index": f.segment_index, "frame_index": f.frame_index, "error": f.error.to_string() })
+        });
+        let line = json!({
+            "ok": report.is_ok(),
+            "segments_verified": report.segments_verified,
+            "frames_verified": report.frames_verified,
+            "bytes_plaintext": report.bytes_plaintext,
+            "failure": failure,
+        });
+        println!("{line}");
+        return;
+    }
+    match failure {
+        None => println!(
+            "verify: ok segments={} frames={} plaintext={}B",
+            report.segments_verified, report.frames_verified, report.bytes_plaintext
+        ),
+        Some(f) => println!(
+            "verify: FAILED at segment {}{} after {} good segments",
+            f.segment_index,
+            f.frame_index.map(|i| format!(" frame {i}")).unwrap_or_default(),
+            report.segments_verified
+        ),
+    }
+}
+
+/// Header and segment table, read without the key.
+///
+/// Segments listed before a malformed one are still printed.
+fn inspect(input: &Path, as_json: bool) -> Result<(), StreamError> {
+    let mut iter = iter_segment_headers(fs::File::open(input)?);
+    let mut segments = Vec::new();
+    let mut failure = None;
+    for item in iter.by_ref() {
+        match item {
+            Ok(info) => segments.push(info),
+            Err(e) => {
+                failure = Some(e);
+                break;
+            }
+        }
+    }
+    let Some(header) = iter.header().copied() else {
+        return Err(failure.unwrap_or(StreamError::Validation("missing stream header".into())));
+    };
+
+    if as_json {
+        let line = json!({ "header": header_json(&header), "segments": segments.iter().map(segment_json).collect::<Vec<_>>() });
+        println!("{line}");
+    } else {
+        println!(
+            "header: version={} profile={} cipher={} prf={} codec={} strategy={} chunk_size={} flags=0x{:04x} key_id={} plaintext_size={} created={} salt={}",
+            header.version,
+            enum_name_or_hex::<AlgProfile>(header.alg_profile),
+            enum_name_or_hex::<CipherSuite>(header.cipher),
+            enum_name_or_hex::<HkdfPrf>(header.hkdf_prf),
+            enum_name_or_hex::<CompressionCodec>(header.compression),
+            enum_name_or_hex::<Strategy>(header.strategy),
+            header.chunk_size,
+            header.flags,
+            header.key_id,
+            header.plaintext_size,
+            created_at(&header),
+            hex(&header.salt),
+        );
+        println!("{:>8} {:>12} {:>10} {:>10} {:>7}  flags", "segment", "offset", "bytes", "wire", "frames");
+        for info in &segments {
+            println!(
+                "{:>8} {:>12} {:>10} {:>10} {:>7}  {}",
+                info.segment_index, info.offset, info.bytes_len, info.wire_len, info.frame_count, info.flags
+            );
+        }
+    }
+
+    match failure {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+fn header_json(header: &HeaderV1) -> serde_json::Value {
+    json!({
+        "version": header.version,
+        "alg_profile": enum_name_or_hex::<AlgProfile>(header.alg_profile),
+        "cipher": enum_name_or_hex::<CipherSuite>(header.cipher),
+        "hkdf_prf": enum_name_or_hex::<HkdfPrf>(header.hkdf_prf),
+        "compression": enum_name_or_hex::<CompressionCodec>(header.compression),
+        "strategy": enum_name_or_hex::<Strategy>(header.strategy),
+        "chunk_size": header.chunk_size,
+        "flags": header.flags,
+        "key_id": header.key_id,
+        "plaintext_size": header.plaintext_size,
+        "created_at": header.created_at_rfc3339(),
+        "created_in_future": header.created_in_future(),
+        "salt": hex(&header.salt),
+    })
+}
+
+/// RFC 3339 encryption time, `unset` for a zero `enc_time_ns`; flagged when
+/// it lies after the local clock.
+fn created_at(header: &HeaderV1) -> String {
+    match header.created_at_rfc3339() {
+        Some(time) if header.created_in_future() => format!("{time}(future)"),
+        Some(time) => time,
+        None => "unset".into(),
+    }
+}
+
+fn segment_json(info: &SegmentInfo) -> serde_json::Value {
+    json!({
+        "segment_index": info.segment_index,
+        "offset": info.offset,
+        "bytes_len": info.bytes_len,
+        "wire_len": info.wire_len,
+        "frame_count": info.frame_count,
+        "digest_alg": info.digest_alg,
+        "flags": info.flags.to_string(),
+    })
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
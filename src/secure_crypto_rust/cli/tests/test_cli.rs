@@ -0,0 +1,172 @@
+// # 📂 `cli/tests/test_cli.rs`
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use assert_cmd::Command;
+
+    /// Scratch directory holding the key, plaintext, ciphertext and output of one test.
+    struct Scratch {
+        dir: PathBuf,
+    }
+
+    impl Scratch {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("rse1_{name}_{}", std::process::id()));
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("key"), [0x11u8; 32]).unwrap();
+            fs::write(dir.join("wrong.key"), [0x22u8; 32]).unwrap();
+            let plaintext: Vec<u8> = (0..300_000u32).map(|i| (i % 251) as u8).collect();
+            fs::write(dir.join("plain"), plaintext).unwrap();
+            Self { dir }
+        }
+
+        fn path(&self, name: &str) -> String {
+            self.dir.join(name).to_string_lossy().into_owned()
+        }
+
+        fn encrypt(&self, extra: &[&str]) {
+            rse1()
+                .args(["encrypt", &self.path("plain"), &self.path("enc"), "--key-file", &self.path("key")])
+                .args(extra)
+                .assert()
+                .success();
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    fn rse1() -> Command {
+        Command::cargo_bin("rse1").unwrap()
+    }
+
+    fn stdout_of(assert: &assert_cmd::assert::Assert) -> String {
+        String::from_utf8_lossy(&assert.get_output().stdout).into_owned()
+    }
+
+    #[test]
+    fn encrypt_decrypt_roundtrip() {
+        let scratch = Scratch::new("roundtrip");
+        let assert = rse1()
+            .args(["encrypt", &scratch.path("plain"), &scratch.path("enc"), "--key-file", &scratch.path("key")])
+            .args(["--codec", "zstd", "--chunk-size", "65536", "--profile", "parallel"])
+            .assert()
+            .success();
+        let summary = stdout_of(&assert);
+        assert!(summary.starts_with("encrypt: segments="), "{summary}");
+        assert!(summary.contains("ratio=") && summary.contains("throughput="), "{summary}");
+
+        rse1()
+            .args(["decrypt", &scratch.path("enc"), &scratch.path("dec"), "--key-file", &scratch.path("key")])
+            .assert()
+            .success();
+        assert_eq!(fs::read(scratch.path("dec")).unwrap(), fs::read(scratch.path("plain")).unwrap());
+    }
+
+    #[test]
+    fn json_telemetry_is_machine_readable() {
+        let scratch = Scratch::new("json");
+        let assert = rse1()
+            .args(["--json", "encrypt", &scratch.path("plain"), &scratch.path("enc"), "--key-file", &scratch.path("key")])
+            .assert()
+            .success();
+        let report: serde_json::Value = serde_json::from_str(stdout_of(&assert).trim()).unwrap();
+        assert_eq!(report["bytes_plaintext"], 300_000);
+    }
+
+    #[test]
+    fn inspect_needs_no_key() {
+        let scratch = Scratch::new("inspect");
+        scratch.encrypt(&["--chunk-size", "65536"]);
+
+        let text = stdout_of(&rse1().args(["inspect", &scratch.path("enc")]).assert().success());
+        assert!(text.contains("chunk_size=65536"), "{text}");
+        assert!(text.contains("FINAL_SEGMENT"), "{text}");
+
+        let json = stdout_of(&rse1().args(["inspect", &scratch.path("enc"), "--json"]).assert().success());
+        let report: serde_json::Value = serde_json::from_str(json.trim()).unwrap();
+        assert_eq!(report["header"]["chunk_size"], 65536);
+        assert!(report["header"]["created_at"].as_str().unwrap().ends_with('Z'));
+        assert_eq!(report["header"]["created_in_future"], false);
+        // Five data segments and the final one
+        assert_eq!(report["segments"].as_array().unwrap().len(), 6);
+    }
+
+    #[test]
+    fn verify_accepts_an_intact_stream() {
+        let scratch = Scratch::new("verify_ok");
+        scratch.encrypt(&[]);
+        let text = stdout_of(
+            &rse1().args(["verify", &scratch.path("enc"), "--key-file", &scratch.path("key")]).assert().success(),
+        );
+        assert!(text.starts_with("verify: ok"), "{text}");
+    }
+
+    #[test]
+    fn wrong_key_is_an_authentication_failure() {
+        let scratch = Scratch::new("wrong_key");
+        scratch.encrypt(&[]);
+        rse1()
+            .args(["verify", &scratch.path("enc"), "--key-file", &scratch.path("wrong.key")])
+            .assert()
+            .code(4);
+        rse1()
+            .args(["decrypt", &scratch.path("enc"), &scratch.path("dec"), "--key-file", &scratch.path("wrong.key")])
+            .assert()
+            .code(4);
+    }
+
+    #[test]
+    fn tampered_stream_fails_verification() {
+        let scratch = Scratch::new("tampered");
+        scratch.encrypt(&[]);
+        let mut ciphertext = fs::read(scratch.path("enc")).unwrap();
+        let last = ciphertext.len() / 2;
+        ciphertext[last] ^= 0x01;
+        fs::write(scratch.path("enc"), ciphertext).unwrap();
+
+        let assert = rse1()
+            .args(["--json", "verify", &scratch.path("enc"), "--key-file", &scratch.path("key")])
+            .assert()
+            .failure();
+        let report: serde_json::Value = serde_json::from_str(stdout_of(&assert).trim()).unwrap();
+        assert_eq!(report["ok"], false);
+    }
+
+    #[test]
+    fn malformed_input_is_a_validation_error() {
+        let scratch = Scratch::new("malformed");
+        fs::write(scratch.path("enc"), b"definitely not an RSE1 stream, but long enough for a header......................").unwrap();
+        rse1().args(["inspect", &scratch.path("enc")]).assert().code(3);
+        rse1()
+            .args(["decrypt", &scratch.path("enc"), &scratch.path("dec"), "--key-file", &scratch.path("key")])
+            .assert()
+            .code(3);
+    }
+
+    #[test]
+    fn missing_files_are_io_errors() {
+        let scratch = Scratch::new("missing");
+        rse1().args(["inspect", &scratch.path("nope")]).assert().code(5);
+        rse1()
+            .args(["encrypt", &scratch.path("plain"), &scratch.path("enc"), "--key-file", &scratch.path("nope")])
+            .assert()
+            .code(5);
+    }
+
+    #[test]
+    fn invalid_chunk_size_is_a_validation_error() {
+        let scratch = Scratch::new("chunk");
+        rse1()
+            .args(["encrypt", &scratch.path("plain"), &scratch.path("enc"), "--key-file", &scratch.path("key")])
+            .args(["--chunk-size", "0"])
+            .assert()
+            .code(3);
+    }
+}
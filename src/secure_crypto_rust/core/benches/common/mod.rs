@@ -0,0 +1,258 @@
+// # 📂 `benches/common/mod.rs`
+//
+// Shared harness for the stream benchmarks: workloads, headers and the
+// in-memory encrypt/decrypt calls. Nothing here touches the disk: the audit
+// log stays `LogConfig::Disabled` and both ends use `InputSource::Memory` /
+// `OutputSink::Memory`, or a [`SlowDisk`] over memory for I/O-bound cases.
+
+#![allow(dead_code)]
+
+use std::io::{Cursor, Read};
+use std::time::Duration;
+
+use criterion::{BenchmarkGroup, Criterion, Throughput, measurement::WallTime};
+use crypto_core::{
+    compression::CompressionCodec,
+    headers::{AlgProfile, CipherSuite, HeaderV1, HkdfPrf},
+    recovery::LogConfig,
+    stream_v2::{
+        InputSource, OutputSink,
+        core::{ApiConfig, DecryptParams, EncryptParams, decrypt_stream_v2, encrypt_stream_v2},
+        parallelism::HybridParallelismProfile,
+    },
+};
+
+pub const MASTER_KEY: [u8; 32] = [0x42u8; 32];
+pub const CHUNK_SIZE: u32 = 1024 * 1024;
+
+/// Full runs push 64 MiB through every case.
+const FULL_LEN: usize = 64 * 1024 * 1024;
+/// `--quick` (CI smoke runs) shrinks inputs and samples.
+const QUICK_LEN: usize = 4 * 1024 * 1024;
+
+/// `cargo bench -- --quick`, or `CRYPTO_BENCH_QUICK=1` in the environment.
+pub fn quick() -> bool {
+    std::env::args().any(|arg| arg == "--quick") || std::env::var_os("CRYPTO_BENCH_QUICK").is_some()
+}
+
+/// Input size of every case.
+pub fn input_len() -> usize {
+    if quick() { QUICK_LEN } else { FULL_LEN }
+}
+
+/// Criterion defaults, trimmed for multi-MiB iterations.
+pub fn criterion() -> Criterion {
+    let c = Criterion::default().configure_from_args();
+    if quick() {
+        c.sample_size(10).warm_up_time(Duration::from_millis(200)).measurement_time(Duration::from_secs(1))
+    } else {
+        c.sample_size(10).measurement_time(Duration::from_secs(20))
+    }
+}
+
+/// Group reporting throughput in bytes/sec of plaintext.
+pub fn group<'a>(c: &'a mut Criterion, name: &str) -> BenchmarkGroup<'a, WallTime> {
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Bytes(input_len() as u64));
+    group
+}
+
+/// Plaintext shapes: best case, worst case and something in between for the codecs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Workload {
+    Zeros,
+    Random,
+    Text,
+}
+
+impl Workload {
+    pub const ALL: [Workload; 3] = [Workload::Zeros, Workload::Random, Workload::Text];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Workload::Zeros => "zeros",
+            Workload::Random => "random",
+            Workload::Text => "text",
+        }
+    }
+
+    /// `len` bytes of this workload, the same on every run.
+    pub fn generate(self, len: usize) -> Vec<u8> {
+        match self {
+            Workload::Zeros => vec![0u8; len],
+            Workload::Random => {
+                // xorshift64*: incompressible, reproducible, no rand dependency in the hot path
+                let mut state = 0x9E37_79B9_7F4A_7C15u64;
+                let mut out = Vec::with_capacity(len + 8);
+                while out.len() < len {
+                    state ^= state >> 12;
+                    state ^= state << 25;
+                    state ^= state >> 27;
+                    out.extend_from_slice(&state.wrapping_mul(0x2545_F491_4F6C_DD1D).to_le_bytes());
+                }
+                out.truncate(len);
+                out
+            }
+            Workload::Text => {
+                const WORDS: [&str; 16] = [
+                    "segment", "frame", "digest", "stream", "header", "cipher", "nonce", "chunk",
+                    "worker", "codec", "key", "tag", "the", "of", "and", "a",
+                ];
+                let mut out = Vec::with_capacity(len + 16);
+                let mut i = 0usize;
+                while out.len() < len {
+                    let word = WORDS[(i.wrapping_mul(2_654_435_761) >> 7) % WORDS.len()];
+                    out.extend_from_slice(word.as_bytes());
+                    out.push(if i % 13 == 12 { b'\n' } else { b' ' });
+                    i += 1;
+                }
+                out.truncate(len);
+                out
+            }
+        }
+    }
+}
+
+/// AEAD ciphers to compare.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cipher {
+    ChaCha,
+    Aes,
+}
+
+impl Cipher {
+    pub const ALL: [Cipher; 2] = [Cipher::ChaCha, Cipher::Aes];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Cipher::ChaCha => "chacha20poly1305",
+            Cipher::Aes => "aes256gcm",
+        }
+    }
+
+    fn profile(self) -> (AlgProfile, CipherSuite) {
+        match self {
+            Cipher::ChaCha => (AlgProfile::Chacha20Poly1305HkdfSha256, CipherSuite::Chacha20Poly1305),
+            Cipher::Aes => (AlgProfile::Aes256GcmHkdfSha256, CipherSuite::Aes256Gcm),
+        }
+    }
+}
+
+pub const CODECS: [CompressionCodec; 3] = [CompressionCodec::Auto, CompressionCodec::Zstd, CompressionCodec::Lz4];
+pub const FRAME_SIZES: [usize; 3] = [4 * 1024, 16 * 1024, 64 * 1024];
+/// Small segments stress the per-segment hand-offs between pipeline stages.
+pub const SEGMENT_SIZES: [u32; 3] = [16 * 1024, 64 * 1024, CHUNK_SIZE];
+
+/// Segments read ahead on decrypt: serial, double buffered, two ahead.
+pub const READ_AHEADS: [usize; 3] = [0, 1, 2];
+/// Bandwidth of the simulated disk behind [`Case::decrypt_from_disk`], bytes/sec.
+pub const DISK_BYTES_PER_SEC: f64 = 2.0 * 1024.0 * 1024.0 * 1024.0;
+
+/// Reader that takes as long as a disk of `bytes_per_sec` would.
+///
+/// In-memory input never blocks, so read-ahead has nothing to overlap with;
+/// this puts the read latency back.
+pub struct SlowDisk<R> {
+    inner: R,
+    bytes_per_sec: f64,
+}
+
+impl<R> SlowDisk<R> {
+    pub fn new(inner: R, bytes_per_sec: f64) -> Self {
+        Self { inner, bytes_per_sec }
+    }
+}
+
+impl<R: Read> Read for SlowDisk<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        std::thread::sleep(Duration::from_secs_f64(n as f64 / self.bytes_per_sec));
+        Ok(n)
+    }
+}
+
+/// Worker counts: one, four and every core (the profile caps it).
+pub fn worker_counts() -> Vec<usize> {
+    let mut counts = vec![1, 4, num_cpus::get()];
+    counts.dedup();
+    counts
+}
+
+/// One benchmark case; every knob not under test keeps its default.
+#[derive(Debug, Clone, Copy)]
+pub struct Case {
+    pub codec: CompressionCodec,
+    pub cipher: Cipher,
+    pub frame_size: Option<usize>,
+    pub workers: Option<usize>,
+    pub chunk_size: u32,
+    pub read_ahead: usize,
+}
+
+impl Default for Case {
+    fn default() -> Self {
+        Self {
+            codec: CompressionCodec::Auto,
+            cipher: Cipher::ChaCha,
+            frame_size: None,
+            workers: None,
+            chunk_size: CHUNK_SIZE,
+            read_ahead: 0,
+        }
+    }
+}
+
+impl Case {
+    pub fn header(&self) -> HeaderV1 {
+        let (alg_profile, cipher) = self.cipher.profile();
+        HeaderV1 {
+            alg_profile: alg_profile as u16,
+            cipher: cipher as u16,
+            hkdf_prf: HkdfPrf::Sha256 as u16,
+            compression: self.codec as u16,
+            chunk_size: self.chunk_size,
+            ..HeaderV1::test_header()
+        }
+    }
+
+    pub fn params(&self) -> EncryptParams<'_> {
+        let params = EncryptParams::new(self.header());
+        match self.frame_size {
+            Some(frame_size) => params.with_frame_size(frame_size),
+            None => params,
+        }
+    }
+
+    /// Output kept in memory, no audit log.
+    pub fn config(&self) -> ApiConfig {
+        let config = ApiConfig::new(Some(true), None).with_log(LogConfig::Disabled).with_read_ahead(self.read_ahead);
+        match self.workers {
+            Some(workers) => config.with_profile(HybridParallelismProfile::new(workers, 0, workers * 2)),
+            None => config,
+        }
+    }
+
+    /// Inputs are taken by value so benchmarks can clone them outside the timed section.
+    pub fn encrypt(&self, plaintext: Vec<u8>) -> Vec<u8> {
+        encrypt_stream_v2(InputSource::Memory(plaintext), OutputSink::Memory, &MASTER_KEY, self.params(), self.config())
+            .expect("benchmark encryption failed")
+            .output
+            .expect("captured ciphertext")
+    }
+
+    pub fn decrypt(&self, ciphertext: Vec<u8>) -> Vec<u8> {
+        decrypt_stream_v2(InputSource::Memory(ciphertext), OutputSink::Memory, &MASTER_KEY, DecryptParams::new(), self.config())
+            .expect("benchmark decryption failed")
+            .output
+            .expect("captured plaintext")
+    }
+
+    /// [`Case::decrypt`], reading the ciphertext at [`DISK_BYTES_PER_SEC`].
+    pub fn decrypt_from_disk(&self, ciphertext: Vec<u8>) -> Vec<u8> {
+        let input = InputSource::Reader(Box::new(SlowDisk::new(Cursor::new(ciphertext), DISK_BYTES_PER_SEC)));
+        decrypt_stream_v2(input, OutputSink::Memory, &MASTER_KEY, DecryptParams::new(), self.config())
+            .expect("benchmark decryption failed")
+            .output
+            .expect("captured plaintext")
+    }
+}
@@ -0,0 +1,109 @@
+// # 📂 `benches/stream_throughput.rs`
+//
+// Encrypt and decrypt throughput of the v2 stream, one group per knob:
+//
+// - `codec/<workload>`      Auto, Zstd, Lz4
+// - `cipher/<workload>`     ChaCha20-Poly1305, AES-256-GCM
+// - `frame_size/<workload>` 4K, 16K, 64K
+// - `workers/<workload>`    1, 4, every core
+// - `segment_size/<workload>` 16K, 64K, 1M
+// - `read_ahead/random`     decrypt only, 0 / 1 / 2 segments, from a 2 GiB/s simulated disk
+//
+// Each case runs over 64 MiB of zeros, random bytes and text and reports
+// bytes/sec of plaintext. Full run:
+//
+//     cargo bench -p crypto-core --bench stream_throughput
+//
+// CI smoke run (4 MiB inputs, short measurement):
+//
+//     cargo bench -p crypto-core --bench stream_throughput -- --quick
+
+mod common;
+
+use criterion::{BatchSize, BenchmarkId, Criterion, criterion_group, criterion_main};
+
+use common::{Case, Cipher, CODECS, FRAME_SIZES, READ_AHEADS, SEGMENT_SIZES, Workload, group, input_len, worker_counts};
+
+/// `encrypt/<label>` and `decrypt/<label>` for every workload in `name/<workload>`.
+fn bench_cases(c: &mut Criterion, name: &str, cases: &[(String, Case)]) {
+    for workload in Workload::ALL {
+        let plaintext = workload.generate(input_len());
+        let mut group = group(c, &format!("{name}/{}", workload.name()));
+
+        for (label, case) in cases {
+            group.bench_with_input(BenchmarkId::new("encrypt", label), case, |b, case| {
+                b.iter_batched(|| plaintext.clone(), |input| case.encrypt(input), BatchSize::LargeInput)
+            });
+
+            let ciphertext = case.encrypt(plaintext.clone());
+            assert_eq!(case.decrypt(ciphertext.clone()), plaintext, "{name}/{label}: roundtrip mismatch");
+            group.bench_with_input(BenchmarkId::new("decrypt", label), case, |b, case| {
+                b.iter_batched(|| ciphertext.clone(), |input| case.decrypt(input), BatchSize::LargeInput)
+            });
+        }
+        group.finish();
+    }
+}
+
+fn codecs(c: &mut Criterion) {
+    let cases: Vec<_> = CODECS
+        .into_iter()
+        .map(|codec| (format!("{codec:?}"), Case { codec, ..Case::default() }))
+        .collect();
+    bench_cases(c, "codec", &cases);
+}
+
+fn ciphers(c: &mut Criterion) {
+    let cases: Vec<_> = Cipher::ALL
+        .into_iter()
+        .map(|cipher| (cipher.name().to_string(), Case { cipher, ..Case::default() }))
+        .collect();
+    bench_cases(c, "cipher", &cases);
+}
+
+fn frame_sizes(c: &mut Criterion) {
+    let cases: Vec<_> = FRAME_SIZES
+        .into_iter()
+        .map(|frame_size| (format!("{}K", frame_size / 1024), Case { frame_size: Some(frame_size), ..Case::default() }))
+        .collect();
+    bench_cases(c, "frame_size", &cases);
+}
+
+fn workers(c: &mut Criterion) {
+    let cases: Vec<_> = worker_counts()
+        .into_iter()
+        .map(|workers| (workers.to_string(), Case { workers: Some(workers), ..Case::default() }))
+        .collect();
+    bench_cases(c, "workers", &cases);
+}
+
+fn segment_sizes(c: &mut Criterion) {
+    let cases: Vec<_> = SEGMENT_SIZES
+        .into_iter()
+        .map(|chunk_size| (format!("{}K", chunk_size / 1024), Case { chunk_size, ..Case::default() }))
+        .collect();
+    bench_cases(c, "segment_size", &cases);
+}
+
+/// Decrypt only: read-ahead pays off once reading the input takes time.
+fn read_ahead(c: &mut Criterion) {
+    let plaintext = Workload::Random.generate(input_len());
+    let mut group = group(c, "read_ahead/random");
+
+    for read_ahead in READ_AHEADS {
+        let case = Case { read_ahead, ..Case::default() };
+        let ciphertext = case.encrypt(plaintext.clone());
+        assert_eq!(case.decrypt_from_disk(ciphertext.clone()), plaintext, "read_ahead/{read_ahead}: roundtrip mismatch");
+        group.bench_with_input(BenchmarkId::new("decrypt", read_ahead), &case, |b, case| {
+            b.iter_batched(|| ciphertext.clone(), |input| case.decrypt_from_disk(input), BatchSize::LargeInput)
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = common::criterion();
+    targets = codecs, ciphers, frame_sizes, workers, segment_sizes, read_ahead
+}
+criterion_main!(benches);
@@ -3,16 +3,22 @@
 //! codecs/auto.rs
 //! Pass-through codec.
 
-use crate::compression::{compute_checksum, types::{CompressionError, Compressor, Decompressor}, verify_checksum};
+use crate::compression::{Checksum, ChecksumAlg, compute_checksum, types::{CompressionError, Compressor, Decompressor, MAX_DECOMPRESSED_LEN, check_output_len}, verify_checksum};
 
+#[derive(Default)]
 pub struct AutoCompressor;
-pub struct AutoDecompressor;
+pub struct AutoDecompressor {
+    max_output: usize,
+}
 
 impl AutoCompressor {
     pub fn new() -> Self { Self }
 }
 impl AutoDecompressor {
-    pub fn new() -> Self { Self }
+    pub fn new() -> Self { Self { max_output: MAX_DECOMPRESSED_LEN } }
+}
+impl Default for AutoDecompressor {
+    fn default() -> Self { Self::new() }
 }
 
 impl Compressor for AutoCompressor {
@@ -25,8 +31,8 @@ impl Compressor for AutoCompressor {
         out.extend_from_slice(&compressed);
 
         // Append CRC32 of original plaintext
-        let checksum = compute_checksum(&input, None);
-        out.extend_from_slice(&checksum.to_le_bytes());
+        let checksum = compute_checksum(input, ChecksumAlg::Crc32);
+        out.extend_from_slice(&checksum.to_bytes());
 
         Ok(())
     }
@@ -50,8 +56,9 @@ impl Decompressor for AutoDecompressor {
         // compressed data is everything except the last 4 bytes
         let compressed = &input[4..input.len() - 4];
         let checksum_bytes = &input[input.len() - 4..];
-        let expected_crc = u32::from_le_bytes(checksum_bytes.try_into().unwrap());
+        let expected_crc = Checksum::Crc32(u32::from_le_bytes(checksum_bytes.try_into().unwrap()));
 
+        check_output_len(orig_len, self.max_output)?;
         let decompressed = compressed;
 
         // Optional sanity check: verify decoded size matches prefix
@@ -63,10 +70,14 @@ impl Decompressor for AutoDecompressor {
         }
 
         // Verify checksum
-        let actual_crc = compute_checksum(&decompressed, None);
-        verify_checksum(expected_crc, actual_crc, "auto".into())?;
+        let actual_crc = compute_checksum(decompressed, ChecksumAlg::Crc32);
+        verify_checksum(&expected_crc, &actual_crc, "auto".into())?;
         
         out.extend_from_slice(&decompressed);
         Ok(())
     }
+
+    fn set_max_output(&mut self, max: usize) {
+        self.max_output = max;
+    }
 }
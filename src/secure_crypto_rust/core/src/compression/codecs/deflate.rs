@@ -4,8 +4,8 @@ use std::io::Read;
 use std::io::Write;
 use flate2::{Compression, write::ZlibEncoder, read::ZlibDecoder};
 
-use crate::compression::compute_checksum;
-use crate::compression::types::{Compressor, Decompressor, CompressionError};
+use crate::compression::{Checksum, ChecksumAlg, compute_checksum};
+use crate::compression::types::{Compressor, Decompressor, CompressionError, MAX_DECOMPRESSED_LEN, check_output_len};
 use crate::compression::verify_checksum;
 
 pub struct DeflateCompressor {
@@ -37,8 +37,8 @@ impl Compressor for DeflateCompressor {
         out.extend_from_slice(&compressed);
 
         // Append CRC32 of original plaintext
-        let checksum = compute_checksum(&input, None);
-        out.extend_from_slice(&checksum.to_le_bytes());
+        let checksum = compute_checksum(input, ChecksumAlg::Crc32);
+        out.extend_from_slice(&checksum.to_bytes());
 
         Ok(())
     }
@@ -49,11 +49,13 @@ impl Compressor for DeflateCompressor {
     }
 }
 
-pub struct DeflateDecompressor;
+pub struct DeflateDecompressor {
+    max_output: usize,
+}
 
 impl DeflateDecompressor {
     pub fn new() -> Result<Box<dyn Decompressor + Send>, CompressionError> {
-        Ok(Box::new(Self))
+        Ok(Box::new(Self { max_output: MAX_DECOMPRESSED_LEN }))
     }
 }
 
@@ -72,13 +74,23 @@ impl Decompressor for DeflateDecompressor {
         // compressed data is everything except the last 4 bytes
         let compressed = &input[4..input.len() - 4];
         let checksum_bytes = &input[input.len() - 4..];
-        let expected_crc = u32::from_le_bytes(checksum_bytes.try_into().unwrap());
+        let expected_crc = Checksum::Crc32(u32::from_le_bytes(checksum_bytes.try_into().unwrap()));
+
+        check_output_len(orig_len, self.max_output)?;
 
-        // Decode an entire zlib stream for this frame
+        // Decode an entire zlib stream for this frame, never past the cap:
+        // the prefix is unauthenticated until the checksum matches
         let mut dec = ZlibDecoder::new(compressed);
         let mut decompressed = Vec::new();
-        dec.read_to_end(&mut decompressed)
+        (&mut dec).take(self.max_output as u64 + 1).read_to_end(&mut decompressed)
             .map_err(|e| CompressionError::CodecProcessFailed { codec: "deflate".into(), msg: e.to_string() })?;
+        check_output_len(decompressed.len(), self.max_output)?;
+
+        // The zlib stream must end the frame; the decoder stops quietly before extra bytes
+        let consumed = dec.total_in() as usize;
+        if consumed != compressed.len() {
+            return Err(CompressionError::TrailingData { codec: "deflate".into(), extra: compressed.len() - consumed });
+        }
 
         // Optional sanity check: verify decoded size matches prefix
         if decompressed.len() != orig_len {
@@ -89,10 +101,14 @@ impl Decompressor for DeflateDecompressor {
         }
 
         // Verify checksum
-        let actual_crc = compute_checksum(&decompressed, None);
-        verify_checksum(expected_crc, actual_crc, "deflate".into())?;
+        let actual_crc = compute_checksum(&decompressed, ChecksumAlg::Crc32);
+        verify_checksum(&expected_crc, &actual_crc, "deflate".into())?;
         
         out.extend_from_slice(&decompressed);
         Ok(())
     }
+
+    fn set_max_output(&mut self, max: usize) {
+        self.max_output = max;
+    }
 }
@@ -1,31 +1,43 @@
 //! codecs/lz4.rs
 //! LZ4 block streaming compressor/decompressor (deterministic, dictionary optional).
-use lz4_flex::block::{compress_prepend_size, decompress_size_prepended};
+use lz4_flex::block::{
+    compress_prepend_size, compress_prepend_size_with_dict,
+    decompress_size_prepended, decompress_size_prepended_with_dict,
+};
 
-use crate::compression::{compute_checksum, types::{CompressionError, Compressor, Decompressor}, verify_checksum};
+use crate::compression::{Checksum, ChecksumAlg, compute_checksum, types::{CompressionError, Compressor, Decompressor, MAX_DECOMPRESSED_LEN, check_output_len}, verify_checksum};
 
 /// LZ4 compressor using lz4 block API.
-/// Note: lz4 does not expose streaming encoder with dictionary/level,
+/// Note: lz4 does not expose streaming encoder with levels,
 /// so we emulate streaming by compressing each chunk independently.
-pub struct Lz4Compressor;
+/// An optional dictionary primes every block.
+pub struct Lz4Compressor {
+    dict: Option<Vec<u8>>,
+}
 
-pub struct Lz4Decompressor;
+pub struct Lz4Decompressor {
+    dict: Option<Vec<u8>>,
+    max_output: usize,
+}
 
 impl Lz4Compressor {
-    pub fn new(_level: i32, _dict: Option<&[u8]>) -> Result<Box<dyn Compressor + Send>, CompressionError> {
-        // lz4 does not support level/dict in block mode.
-        Ok(Box::new(Self))
+    pub fn new(_level: i32, dict: Option<&[u8]>) -> Result<Box<dyn Compressor + Send>, CompressionError> {
+        // lz4_flex block mode has no levels; only the dictionary is honored.
+        Ok(Box::new(Self { dict: dict.map(|d| d.to_vec()) }))
     }
 }
 
 impl Compressor for Lz4Compressor {
     fn compress_chunk(&mut self, input: &[u8], out: &mut Vec<u8>) -> Result<(), CompressionError> {
-        let compressed = compress_prepend_size(input);
+        let compressed = match &self.dict {
+            Some(d) => compress_prepend_size_with_dict(input, d),
+            None => compress_prepend_size(input),
+        };
         out.extend_from_slice(&compressed);
 
         // Append CRC32 of original input
-        let checksum = compute_checksum(&input, None);
-        out.extend_from_slice(&checksum.to_le_bytes());
+        let checksum = compute_checksum(input, ChecksumAlg::Crc32);
+        out.extend_from_slice(&checksum.to_bytes());
 
         Ok(())
     }
@@ -37,8 +49,8 @@ impl Compressor for Lz4Compressor {
 
 
 impl Lz4Decompressor {
-    pub fn new(_dict: Option<&[u8]>) -> Result<Box<dyn Decompressor + Send>, CompressionError> {
-        Ok(Box::new(Self))
+    pub fn new(dict: Option<&[u8]>) -> Result<Box<dyn Decompressor + Send>, CompressionError> {
+        Ok(Box::new(Self { dict: dict.map(|d| d.to_vec()), max_output: MAX_DECOMPRESSED_LEN }))
     }
 }
 
@@ -53,19 +65,31 @@ impl Decompressor for Lz4Decompressor {
         
         // Split compressed data and checksum
         let (compressed, checksum_bytes) = input.split_at(input.len() - 4);
-        let expected_crc = u32::from_le_bytes(checksum_bytes.try_into().unwrap());
+        let expected_crc = Checksum::Crc32(u32::from_le_bytes(checksum_bytes.try_into().unwrap()));
+
+        // lz4_flex allocates the prepended size up front: reject it first
+        if let Some(size) = compressed.get(..4) {
+            check_output_len(u32::from_le_bytes(size.try_into().unwrap()) as usize, self.max_output)?;
+        }
 
-        let decompressed = decompress_size_prepended(compressed)
+        let decompressed = match &self.dict {
+            Some(d) => decompress_size_prepended_with_dict(compressed, d),
+            None => decompress_size_prepended(compressed),
+        }
             .map_err(|e| CompressionError::CodecProcessFailed {
                 codec: "lz4".into(),
                 msg: e.to_string(),
             })?;
         
         // Verify checksum
-        let actual_crc = compute_checksum(&decompressed, None);
-        verify_checksum(expected_crc, actual_crc, "lz4".into())?;
+        let actual_crc = compute_checksum(&decompressed, ChecksumAlg::Crc32);
+        verify_checksum(&expected_crc, &actual_crc, "lz4".into())?;
         
         out.extend_from_slice(&decompressed);
         Ok(())
     }
+
+    fn set_max_output(&mut self, max: usize) {
+        self.max_output = max;
+    }
 }
@@ -4,6 +4,7 @@ use bincode::serde::encode_to_vec;
 use bincode::config::standard;
 use wgpu::{util::DeviceExt, wgt::PollType};
 use bytemuck::{cast_slice, Pod, Zeroable};
+use tracing::debug;
 
 use crate::compression::types::{Compressor, CompressionError};
 
@@ -33,11 +34,11 @@ impl Lz4GpuCompressor {
             })?;
         
         let info = adapter.get_info();
-        println!("Adapter info: {:?}", info);
+        debug!(?info, "lz4_gpu adapter");
 
         let features = adapter.features();
         let limits = adapter.limits();
-        println!("Features: {:?}, Limits: {:?}", features, limits);
+        debug!(?features, ?limits, "lz4_gpu adapter capabilities");
 
         let (device, queue) = adapter
             .request_device(
@@ -189,7 +190,7 @@ impl Lz4OclCompressor {
                 msg: format!("Failed to get OpenCL device: {e:?}"),
             })?;
 
-        println!("OpenCL Device: {:?}", device.name().unwrap());
+        debug!(device = ?device.name().ok(), "lz4_ocl device");
 
         // Create context and queue
         let context = Context::builder()
@@ -18,29 +18,60 @@ pub use deflate::*;
 pub use lz4::*;
 pub use zstd::*;
 
-// Convert enum ID → compressor struct.
-// pub fn codec_struct_from_id_compressor(
-//     id: CompressionCodec,
-//     level: Option<i32>,
-//     dict: Option<&[u8]>,
-// ) -> Result<Box<dyn Compressor + Send>, CompressionError> {
-//     match id {
-//         CompressionCodec::Auto    => Ok(Box::new(AutoCompressor::new())),
-//         CompressionCodec::Zstd    => ZstdCompressor::new(level.unwrap_or(DEFAULT_LEVEL_ZSTD), dict),
-//         CompressionCodec::Lz4     => Lz4Compressor::new(level.unwrap_or(DEFAULT_LEVEL_LZ4), dict),
-//         CompressionCodec::Deflate => DeflateCompressor::new(level.unwrap_or(DEFAULT_LEVEL_DEFLATE)),
-//     }
-// }
+use crate::compression::types::{
+    CompressionCodec, CompressionError, Compressor, Decompressor,
+    DEFAULT_LEVEL_DEFLATE, DEFAULT_LEVEL_LZ4, DEFAULT_LEVEL_ZSTD,
+};
 
-// Convert enum ID → decompressor struct.
-// pub fn codec_struct_from_id_decompressor(
-//     id: CompressionCodec,
-//     dict: Option<&[u8]>,
-// ) -> Result<Box<dyn Decompressor + Send>, CompressionError> {
-//     match id {
-//         CompressionCodec::Auto    => Ok(Box::new(AutoDecompressor::new())),
-//         CompressionCodec::Zstd    => ZstdDecompressor::new(dict),
-//         CompressionCodec::Lz4     => Lz4Decompressor::new(dict),
-//         CompressionCodec::Deflate => DeflateDecompressor::new(),
-//     }
-// }
+/// Convert enum ID → compressor struct.
+///
+/// - `level` falls back to the codec default when `None`.
+/// - `dict` is threaded into Zstd/LZ4; Deflate rejects a dictionary.
+///
+/// # Errors
+/// - `CompressionError::CodecInitFailed` if the codec cannot be initialized with the given options.
+pub fn codec_struct_from_id_compressor(
+    id: CompressionCodec,
+    level: Option<i32>,
+    dict: Option<&[u8]>,
+) -> Result<Box<dyn Compressor + Send>, CompressionError> {
+    match id {
+        CompressionCodec::Auto    => Ok(Box::new(AutoCompressor::new())),
+        CompressionCodec::Zstd    => ZstdCompressor::new(level.unwrap_or(DEFAULT_LEVEL_ZSTD), dict),
+        CompressionCodec::Lz4     => Lz4Compressor::new(level.unwrap_or(DEFAULT_LEVEL_LZ4), dict),
+        CompressionCodec::Deflate => {
+            reject_dict("deflate", dict)?;
+            DeflateCompressor::new(level.unwrap_or(DEFAULT_LEVEL_DEFLATE))
+        }
+    }
+}
+
+/// Convert enum ID → decompressor struct.
+///
+/// # Errors
+/// - `CompressionError::CodecInitFailed` if the codec cannot be initialized with the given dictionary.
+pub fn codec_struct_from_id_decompressor(
+    id: CompressionCodec,
+    dict: Option<&[u8]>,
+) -> Result<Box<dyn Decompressor + Send>, CompressionError> {
+    match id {
+        CompressionCodec::Auto    => Ok(Box::new(AutoDecompressor::new())),
+        CompressionCodec::Zstd    => ZstdDecompressor::new(dict),
+        CompressionCodec::Lz4     => Lz4Decompressor::new(dict),
+        CompressionCodec::Deflate => {
+            reject_dict("deflate", dict)?;
+            DeflateDecompressor::new()
+        }
+    }
+}
+
+/// Codecs without preset dictionary support must not silently ignore one.
+fn reject_dict(codec: &str, dict: Option<&[u8]>) -> Result<(), CompressionError> {
+    match dict {
+        Some(_) => Err(CompressionError::CodecInitFailed {
+            codec: codec.into(),
+            msg: "preset dictionaries are not supported".into(),
+        }),
+        None => Ok(()),
+    }
+}
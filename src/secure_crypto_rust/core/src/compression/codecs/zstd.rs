@@ -1,62 +1,54 @@
 //! src/compression/codecs/zstd.rs
 //!
-//! Zstd streaming compressor/decompressor.
+//! Zstd block compressor/decompressor.
 //!
 //! Design notes:
-//! - Wraps zstd streaming encoder/decoder with trait objects for uniform pipeline use.
+//! - Wraps zstd bulk contexts with trait objects for uniform pipeline use.
 //! - Errors are mapped into `CompressionError` variants with codec context.
-//! - Compressor accumulates into an internal Vec; finish consumes encoder safely.
-//! - Decompressor buffers input and reconstructs decoder per chunk for simplicity.
+//! - Level and dictionary are bound once at construction; every chunk is a standalone frame,
+//!   and a chunk holding more than that one frame is rejected.
 
 // #### Option 1: Use Zstd block API
 // Zstd has a block compression API (`zstd::bulk::compress` / `decompress`) that produces standalone compressed blocks. Each block can be decompressed independently.
-use std::io::{Cursor, BufReader};
+use crate::compression::{Checksum, ChecksumAlg, compute_checksum, types::{CompressionError, Compressor, Decompressor, MAX_DECOMPRESSED_LEN, check_output_len}, verify_checksum};
 
-use crate::compression::{compute_checksum, types::{CompressionError, Compressor, Decompressor}, verify_checksum};
-
-/// Zstd streaming compressor.
-/// - Holds an encoder writing into an internal Vec.
+/// Zstd block compressor.
+/// - Holds a bulk compression context (level + optional dictionary loaded once).
 /// - Implements `Compressor` trait for chunked compression.
 pub struct ZstdCompressor {
-    _encoder: Option<zstd::stream::Encoder<'static, Vec<u8>>>, // wrapped in Option to allow finish()
+    ctx: zstd::bulk::Compressor<'static>,
 }
 
-/// Zstd streaming decompressor.
-/// - Buffers compressed input.
-/// - Reconstructs decoder per chunk for simplicity.
+/// Zstd block decompressor.
+/// - Holds a bulk decompression context (optional dictionary loaded once).
 /// - Implements `Decompressor` trait.
 pub struct ZstdDecompressor {
-    _buffer: Vec<u8>,
-    _decoder: Option<zstd::stream::Decoder<'static, BufReader<Cursor<Vec<u8>>>>>,
+    ctx: zstd::bulk::Decompressor<'static>,
+    max_output: usize,
 }
 
 impl ZstdCompressor {
     /// Create a new Zstd compressor with given level and optional dictionary.
     ///
     /// # Errors
-    /// - Returns `CompressionError::CodecInitFailed` if encoder initialization fails.
+    /// - Returns `CompressionError::CodecInitFailed` if context initialization fails.
     pub fn new(level: i32, dict: Option<&[u8]>) -> Result<Box<dyn Compressor + Send>, CompressionError> {
-        let encoder = if let Some(d) = dict {
-            zstd::stream::Encoder::with_dictionary(Vec::new(), level, d)
-                .map_err(|e| CompressionError::CodecInitFailed {
-                    codec: "zstd".into(),
-                    msg: e.to_string(),
-                })?
-        } else {
-            zstd::stream::Encoder::new(Vec::new(), level)
-                .map_err(|e| CompressionError::CodecInitFailed {
-                    codec: "zstd".into(),
-                    msg: e.to_string(),
-                })?
-        };
-        Ok(Box::new(Self { _encoder: Some(encoder) }))
+        let ctx = match dict {
+            Some(d) => zstd::bulk::Compressor::with_dictionary(level, d),
+            None => zstd::bulk::Compressor::new(level),
+        }
+        .map_err(|e| CompressionError::CodecInitFailed {
+            codec: "zstd".into(),
+            msg: e.to_string(),
+        })?;
+        Ok(Box::new(Self { ctx }))
     }
 }
 
 impl Compressor for ZstdCompressor {
     fn compress_chunk(&mut self, input: &[u8], out: &mut Vec<u8>) -> Result<(), CompressionError> {
         // Compress the input
-        let compressed = zstd::bulk::compress(input, 0)
+        let compressed = self.ctx.compress(input)
             .map_err(|e| CompressionError::CodecProcessFailed { codec: "zstd".into(), msg: e.to_string() })?;
 
         // Prefix with original plaintext length (like lz4_flex does)
@@ -65,41 +57,33 @@ impl Compressor for ZstdCompressor {
         out.extend_from_slice(&compressed);
 
         // Append CRC32 of original plaintext
-        let checksum = compute_checksum(input, None);
-        out.extend_from_slice(&checksum.to_le_bytes());
+        let checksum = compute_checksum(input, ChecksumAlg::Crc32);
+        out.extend_from_slice(&checksum.to_bytes());
         
         Ok(())
     }
 
     fn finish(&mut self, _out: &mut Vec<u8>) -> Result<(), CompressionError> {
+        // No-op: `compress_chunk` already wrote a complete frame
         Ok(())
     }
 }
 
 impl ZstdDecompressor {
+    /// Create a new Zstd decompressor with an optional dictionary.
+    ///
+    /// # Errors
+    /// - Returns `CompressionError::CodecInitFailed` if context initialization fails.
     pub fn new(dict: Option<&[u8]>) -> Result<Box<dyn Decompressor + Send>, CompressionError> {
-        let cursor = Cursor::new(Vec::new());
-        let result: Result<zstd::stream::Decoder<'_, BufReader<Cursor<Vec<u8>>>>, std::io::Error> =
-            if let Some(d) = dict {
-                zstd::stream::Decoder::with_dictionary(BufReader::new(cursor), d)
-            } else {
-                zstd::stream::Decoder::new(cursor)
-            };
-
-        let decoder = match result {
-            Ok(dec) => Some(dec),
-            Err(e) => {
-                return Err(CompressionError::CodecInitFailed {
-                    codec: "zstd".into(),
-                    msg: e.to_string(),
-                });
-            }
-        };
-
-        Ok(Box::new(Self {
-            _buffer: Vec::new(),
-            _decoder: decoder,
-        }))
+        let ctx = match dict {
+            Some(d) => zstd::bulk::Decompressor::with_dictionary(d),
+            None => zstd::bulk::Decompressor::new(),
+        }
+        .map_err(|e| CompressionError::CodecInitFailed {
+            codec: "zstd".into(),
+            msg: e.to_string(),
+        })?;
+        Ok(Box::new(Self { ctx, max_output: MAX_DECOMPRESSED_LEN }))
     }
 }
 
@@ -118,10 +102,23 @@ impl Decompressor for ZstdDecompressor {
         // compressed data is everything except the last 4 bytes
         let compressed = &input[4..input.len() - 4];
         let checksum_bytes = &input[input.len() - 4..];
-        let expected_crc = u32::from_le_bytes(checksum_bytes.try_into().unwrap());
+        let expected_crc = Checksum::Crc32(u32::from_le_bytes(checksum_bytes.try_into().unwrap()));
+
+        // The prefix sizes the output buffer: reject it before allocating
+        check_output_len(orig_len, self.max_output)?;
+
+        // Exactly one frame: bulk decompression would also accept concatenated frames
+        let frame_len = zstd_safe::find_frame_compressed_size(compressed)
+            .map_err(|code| CompressionError::CodecProcessFailed {
+                codec: "zstd".into(),
+                msg: zstd_safe::get_error_name(code).to_string(),
+            })?;
+        if frame_len != compressed.len() {
+            return Err(CompressionError::TrailingData { codec: "zstd".into(), extra: compressed.len() - frame_len });
+        }
 
         // Decompress with known output size
-        let decompressed = zstd::bulk::decompress(compressed, orig_len)
+        let decompressed = self.ctx.decompress(compressed, orig_len)
             .map_err(|e| CompressionError::CodecProcessFailed { codec: "zstd".into(), msg: e.to_string() })?;
 
         // Optional sanity check: verify decoded size matches prefix
@@ -133,10 +130,14 @@ impl Decompressor for ZstdDecompressor {
         }
 
         // Verify checksum
-        let actual_crc = compute_checksum(&decompressed, None);
-        verify_checksum(expected_crc, actual_crc, "zstd".into())?;
+        let actual_crc = compute_checksum(&decompressed, ChecksumAlg::Crc32);
+        verify_checksum(&expected_crc, &actual_crc, "zstd".into())?;
 
         out.extend_from_slice(&decompressed);
         Ok(())
     }
+
+    fn set_max_output(&mut self, max: usize) {
+        self.max_output = max;
+    }
 }
@@ -0,0 +1,151 @@
+// ## src/compression/dictionary.rs
+
+//! compression/dictionary.rs
+//! Dictionary registry and zstd dictionary training.
+//!
+//! Design notes:
+//! - Dictionaries are exchanged in a framed form: `MAGIC_DICT || codec dictionary bytes`.
+//! - Framing is validated once, at registration time; lookups return the codec payload only.
+//! - Streams reference a dictionary through `header.dict_id` (with `DICT_USED` set); id 0 is reserved for "no dictionary".
+//! - Under `SEGMENT_DICTS`, a segment may name its own dictionary instead (picked by a [`DictSelector`]).
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::compression::types::CompressionError;
+use crate::constants::{MAGIC_DICT, MAX_DICT_LEN, MIN_DICT_LEN};
+
+/// Returns true if `dict` is a well-formed framed dictionary
+/// (length within `MIN_DICT_LEN..=MAX_DICT_LEN` and `MAGIC_DICT` prefix).
+pub fn is_valid_dictionary(dict: &[u8]) -> bool {
+    if dict.len() < MIN_DICT_LEN || dict.len() > MAX_DICT_LEN {
+        return false;
+    }
+
+    // First 4 bytes to be a magic number
+    dict.starts_with(MAGIC_DICT)
+}
+
+/// Train a zstd dictionary from representative samples.
+///
+/// Returns the framed form (`MAGIC_DICT` prefix), ready for `DictionaryRegistry::register`.
+/// `max_size` bounds the whole framed dictionary and is clamped to `MAX_DICT_LEN`.
+///
+/// # Errors
+/// - `CompressionError::CodecInitFailed` if zstd cannot train on the samples
+///   (typically too few or too small samples).
+pub fn train_dictionary(samples: &[&[u8]], max_size: usize) -> Result<Vec<u8>, CompressionError> {
+    let max_size = max_size.min(MAX_DICT_LEN);
+    if max_size < MIN_DICT_LEN {
+        return Err(CompressionError::CodecInitFailed {
+            codec: "zstd".into(),
+            msg: format!("dictionary max_size {} below minimum {}", max_size, MIN_DICT_LEN),
+        });
+    }
+
+    let trained = zstd::dict::from_samples(samples, max_size - MAGIC_DICT.len())
+        .map_err(|e| CompressionError::CodecInitFailed {
+            codec: "zstd".into(),
+            msg: format!("dictionary training failed: {}", e),
+        })?;
+
+    let mut framed = Vec::with_capacity(MAGIC_DICT.len() + trained.len());
+    framed.extend_from_slice(MAGIC_DICT);
+    framed.extend_from_slice(&trained);
+    Ok(framed)
+}
+
+/// Segment index and plaintext in, dictionary id out.
+type SelectFn = Arc<dyn Fn(u32, &[u8]) -> Option<u32> + Send + Sync>;
+
+/// Picks a segment's dictionary from its index and plaintext; `None` keeps
+/// the stream's (`header.dict_id`, or none).
+///
+/// - `ids` lists every id the closure may return; `EncryptParams::validate`
+///   checks them against the registry before anything is written.
+/// - Called from the encrypt reader thread, once per data segment.
+#[derive(Clone)]
+pub struct DictSelector {
+    ids: Vec<u32>,
+    select: SelectFn,
+}
+
+impl DictSelector {
+    pub fn new<F>(ids: impl IntoIterator<Item = u32>, select: F) -> Self
+    where
+        F: Fn(u32, &[u8]) -> Option<u32> + Send + Sync + 'static,
+    {
+        Self { ids: ids.into_iter().collect(), select: Arc::new(select) }
+    }
+
+    /// Every dictionary id the selector may pick.
+    pub fn ids(&self) -> &[u32] {
+        &self.ids
+    }
+
+    /// Dictionary id for segment `segment_index` holding `bytes`.
+    pub fn select(&self, segment_index: u32, bytes: &[u8]) -> Option<u32> {
+        (self.select)(segment_index, bytes)
+    }
+}
+
+impl std::fmt::Debug for DictSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DictSelector").field("ids", &self.ids).finish_non_exhaustive()
+    }
+}
+
+/// Maps `header.dict_id` values to dictionary bytes.
+///
+/// - Dictionaries are validated once on `register`.
+/// - `get` returns the codec payload (framing stripped).
+#[derive(Debug, Clone, Default)]
+pub struct DictionaryRegistry {
+    dicts: HashMap<u32, Vec<u8>>,
+}
+
+impl DictionaryRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a framed dictionary under `dict_id`.
+    ///
+    /// # Errors
+    /// - `CompressionError::InvalidDictionary` if `dict_id` is 0, the framing is
+    ///   invalid, or the id is already registered.
+    pub fn register(&mut self, dict_id: u32, dict: Vec<u8>) -> Result<(), CompressionError> {
+        if dict_id == 0 || !is_valid_dictionary(&dict) || self.dicts.contains_key(&dict_id) {
+            return Err(CompressionError::InvalidDictionary { dict_id });
+        }
+        self.dicts.insert(dict_id, dict[MAGIC_DICT.len()..].to_vec());
+        Ok(())
+    }
+
+    /// Codec dictionary bytes for `dict_id`, if registered.
+    pub fn get(&self, dict_id: u32) -> Option<&[u8]> {
+        self.dicts.get(&dict_id).map(|d| d.as_slice())
+    }
+
+    /// Like `get`, but an unknown id is `CompressionError::InvalidDictionary`.
+    pub fn resolve(&self, dict_id: u32) -> Result<&[u8], CompressionError> {
+        self.get(dict_id).ok_or(CompressionError::InvalidDictionary { dict_id })
+    }
+
+    pub fn contains(&self, dict_id: u32) -> bool {
+        self.dicts.contains_key(&dict_id)
+    }
+
+    /// Every registered id, in no particular order.
+    pub fn ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.dicts.keys().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.dicts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dicts.is_empty()
+    }
+}
@@ -32,10 +32,13 @@
 pub mod types;
 pub mod registry;
 pub mod codecs;
+pub mod dictionary;
 pub mod stream;
 
 pub use types::*;
 pub use registry::*;
+pub use dictionary::*;
+pub use stream::{CompressStream, DecompressStream};
 
 
 // Notes:
@@ -1,78 +1,189 @@
 // compression/stream.rs
 //! compression/stream.rs
-//! Streaming helpers that respect chunk_size discipline.
-use std::io::Read;
-
-use crate::compression::types::{Compressor, Decompressor, CompressionError};
-
-/// Summary: Compress data read from R in chunk_size blocks, yielding compressed chunks.
-/// - Respects MAX_CHUNK_SIZE sanity.
-/// - Calls compressor.finish() after EOF to flush pending state.
-#[inline]
-pub fn compress_stream<R: Read>(
-    mut r: R,
-    chunk_size: usize,
-    mut compressor: Box<dyn Compressor>
-) -> impl Iterator<Item = Result<Vec<u8>, CompressionError>> {
-    // assert!(chunk_size > 0 && chunk_size <= MAX_CHUNK_SIZE);
-    let mut buf = vec![0u8; chunk_size];
-    let mut eof = false;
-
-    std::iter::from_fn(move || {
-        if eof {
-            // Flush and end stream (once).
-            let mut out = Vec::new();
-            if let Err(e) = compressor.finish(&mut out) {
-                return Some(Err(e));
-            }
-            if out.is_empty() {
-                return None;
-            } else {
-                return Some(Ok(out));
-            }
+//! Chunked compression independent of the crypto pipeline.
+//!
+//! Design notes:
+//! - Input is cut into `chunk_size` blocks (short reads are refilled), so the
+//!   chunk boundaries, and therefore the output, depend only on the bytes.
+//! - Each compressed chunk is framed as `[len u32 LE][crc32 u32 LE][payload]`;
+//!   the CRC covers the payload and is checked before decompression.
+//! - Any registered codec works; each chunk is closed with `Compressor::finish`,
+//!   so every frame decodes on its own.
+use std::io::{self, Read};
+
+use crate::compression::registry::{create_compressor, create_decompressor};
+use crate::compression::types::{
+    Checksum, ChecksumAlg, CodecOptions, CompressionCodec, CompressionError, Compressor, Decompressor,
+    MAX_DECOMPRESSED_LEN, compute_checksum, enum_name_or_hex, verify_checksum,
+};
+use crate::constants::MAX_CHUNK_SIZE;
+
+/// Bytes before each payload: `len` + `crc32`.
+pub const CHUNK_PREFIX_LEN: usize = 8;
+
+/// Largest framed payload accepted: a maximal chunk plus codec expansion.
+pub const MAX_FRAMED_PAYLOAD: usize = MAX_DECOMPRESSED_LEN + MAX_DECOMPRESSED_LEN / 16;
+
+/// Fill `buf` from `r` unless EOF comes first; returns the bytes read.
+fn read_full<R: Read>(r: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match r.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
         }
+    }
+    Ok(filled)
+}
 
-        match r.read(&mut buf) {
-            Ok(0) => {
-                eof = true;
-                // Next iteration will flush.
-                return None;
-            }
-            Ok(n) => {
-                let mut out = Vec::new();
-                if let Err(e) = compressor.compress_chunk(&buf[..n], &mut out) {
-                    return Some(Err(e));
-                }
-                Some(Ok(out))
-            }
-            Err(_) => Some(Err(CompressionError::StateError("read error".into()))),
+/// Frame one payload as `[len][crc32][payload]`.
+fn frame(payload: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(CHUNK_PREFIX_LEN + payload.len());
+    out.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    out.extend_from_slice(&compute_checksum(payload, ChecksumAlg::Crc32).to_bytes());
+    out.extend_from_slice(payload);
+    out
+}
+
+/// Iterator over framed compressed chunks of `R`.
+///
+/// Stops after the first error.
+pub struct CompressStream<R: Read> {
+    reader: R,
+    compressor: Box<dyn Compressor + Send>,
+    buf: Vec<u8>,
+    done: bool,
+}
+
+impl<R: Read> CompressStream<R> {
+    /// Compress `reader` with the registered codec `codec_id` in `chunk_size` blocks.
+    ///
+    /// `chunk_size` must be in `1..=MAX_CHUNK_SIZE`.
+    pub fn new(
+        reader: R,
+        codec_id: u16,
+        chunk_size: usize,
+        options: Option<CodecOptions>,
+    ) -> Result<Self, CompressionError> {
+        let compressor = create_compressor(codec_id, options)?;
+        Self::with_compressor(reader, compressor, chunk_size)
+    }
+
+    /// Compress with an already configured compressor.
+    pub fn with_compressor(
+        reader: R,
+        compressor: Box<dyn Compressor + Send>,
+        chunk_size: usize,
+    ) -> Result<Self, CompressionError> {
+        if chunk_size == 0 || chunk_size > MAX_CHUNK_SIZE {
+            return Err(CompressionError::ChunkTooLarge { have: chunk_size, max: MAX_CHUNK_SIZE });
         }
-    })
+        Ok(Self { reader, compressor, buf: vec![0u8; chunk_size], done: false })
+    }
+
+    fn next_frame(&mut self) -> Result<Option<Vec<u8>>, CompressionError> {
+        let n = read_full(&mut self.reader, &mut self.buf)?;
+        if n == 0 {
+            self.done = true;
+            return Ok(None);
+        }
+        let mut payload = Vec::new();
+        self.compressor.compress_chunk(&self.buf[..n], &mut payload)?;
+        self.compressor.finish(&mut payload)?;
+        Ok(Some(frame(&payload)))
+    }
 }
 
-/// Summary: Decompress data read from R in chunk_size blocks, yielding decompressed chunks.
-/// - Respects MAX_CHUNK_SIZE sanity.
-/// - Stateless with respect to frame boundaries (caller controls boundaries).
-#[inline]
-pub fn decompress_stream<R: Read>(
-    mut r: R,
-    chunk_size: usize,
-    mut decompressor: Box<dyn Decompressor>
-) -> impl Iterator<Item = Result<Vec<u8>, CompressionError>> {
-    // assert!(chunk_size > 0 && chunk_size <= MAX_CHUNK_SIZE);
-    let mut buf = vec![0u8; chunk_size];
-
-    std::iter::from_fn(move || {
-        match r.read(&mut buf) {
-            Ok(0) => None,
-            Ok(n) => {
-                let mut out = Vec::new();
-                if let Err(e) = decompressor.decompress_chunk(&buf[..n], &mut out) {
-                    return Some(Err(e));
-                }
-                Some(Ok(out))
+impl<R: Read> Iterator for CompressStream<R> {
+    type Item = Result<Vec<u8>, CompressionError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.next_frame();
+        if result.is_err() {
+            self.done = true;
+        }
+        result.transpose()
+    }
+}
+
+/// Iterator over the decompressed chunks of a `CompressStream` output.
+///
+/// Stops after the first error.
+pub struct DecompressStream<R: Read> {
+    reader: R,
+    decompressor: Box<dyn Decompressor + Send>,
+    codec: String,
+    done: bool,
+}
+
+impl<R: Read> DecompressStream<R> {
+    /// Decompress framed chunks from `reader` with the registered codec `codec_id`.
+    pub fn new(reader: R, codec_id: u16, options: Option<CodecOptions>) -> Result<Self, CompressionError> {
+        let decompressor = create_decompressor(codec_id, options)?;
+        Ok(Self::with_decompressor(reader, decompressor, enum_name_or_hex::<CompressionCodec>(codec_id)))
+    }
+
+    /// Decompress with an already configured decompressor; `codec` names it in errors.
+    pub fn with_decompressor(reader: R, decompressor: Box<dyn Decompressor + Send>, codec: String) -> Self {
+        Self { reader, decompressor, codec, done: false }
+    }
+
+    /// Cap the decompressed size of each chunk (default `MAX_DECOMPRESSED_LEN`).
+    pub fn with_max_output(mut self, max: usize) -> Self {
+        self.decompressor.set_max_output(max);
+        self
+    }
+
+    fn next_chunk(&mut self) -> Result<Option<Vec<u8>>, CompressionError> {
+        let mut prefix = [0u8; CHUNK_PREFIX_LEN];
+        match read_full(&mut self.reader, &mut prefix)? {
+            0 => return Ok(None),
+            CHUNK_PREFIX_LEN => {}
+            n => {
+                return Err(CompressionError::StateError(format!(
+                    "truncated chunk prefix: {n} of {CHUNK_PREFIX_LEN} bytes"
+                )));
             }
-            Err(_) => Some(Err(CompressionError::StateError("read error".into()))),
         }
-    })
+        let len = u32::from_le_bytes(prefix[..4].try_into().unwrap()) as usize;
+        let expected = Checksum::from_bytes(ChecksumAlg::Crc32, &prefix[4..]).expect("4-byte crc32 prefix");
+        if len > MAX_FRAMED_PAYLOAD {
+            return Err(CompressionError::ChunkTooLarge { have: len, max: MAX_FRAMED_PAYLOAD });
+        }
+
+        // `take` grows the buffer with the data actually present
+        let mut payload = Vec::new();
+        (&mut self.reader).take(len as u64).read_to_end(&mut payload)?;
+        if payload.len() != len {
+            return Err(CompressionError::StateError(format!(
+                "truncated chunk payload: {} of {len} bytes",
+                payload.len()
+            )));
+        }
+        verify_checksum(&expected, &compute_checksum(&payload, expected.alg()), self.codec.clone())?;
+
+        let mut out = Vec::new();
+        self.decompressor.decompress_chunk(&payload, &mut out)?;
+        Ok(Some(out))
+    }
+}
+
+impl<R: Read> Iterator for DecompressStream<R> {
+    type Item = Result<Vec<u8>, CompressionError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.next_chunk();
+        if !matches!(result, Ok(Some(_))) {
+            self.done = true;
+        }
+        result.transpose()
+    }
 }
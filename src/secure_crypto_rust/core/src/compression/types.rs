@@ -1,10 +1,12 @@
 //! compression/constants.rs
 //! Stable codec IDs and defaults, plus FFI-safe enum mapping.
 use std::fmt;
-use std::net::{TcpStream};
+use std::net::{SocketAddr, TcpStream};
 use std::time::{Instant, Duration};
 use num_enum::TryFromPrimitive;
 
+use crate::constants::MAX_CHUNK_SIZE;
+
 /// Stable codec IDs (u16) for headers and wire format.
 pub mod codec_ids {
     pub const AUTO: u16    = 0x0000;
@@ -13,6 +15,12 @@ pub mod codec_ids {
     pub const DEFLATE: u16 = 0x0003;
 }
 
+/// Decompressed bytes allowed beyond a stream's `chunk_size` per segment.
+pub const DECOMPRESS_SLACK: usize = 1024;
+
+/// Output cap of a decompressor not bound to a stream header.
+pub const MAX_DECOMPRESSED_LEN: usize = MAX_CHUNK_SIZE + DECOMPRESS_SLACK;
+
 /// Default compression levels (balanced).
 pub const DEFAULT_LEVEL_ZSTD: i32 = 6;
 pub const DEFAULT_LEVEL_LZ4: i32 = 0; // fast mode
@@ -54,7 +62,7 @@ impl CompressionCodec {
 
 // ## 🎯 Enum for Codec Levels
 #[repr(u16)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CodecLevel {
     // Zstd presets (zstd = "0.13")
     ZstdFast,        // level 1
@@ -75,65 +83,88 @@ pub enum CodecLevel {
     Custom(i32),
 }
 
-impl<'a> CodecLevel {
-    /// Automatically select optimal CodecLevel based on codec type and multiple inferred factors.
-    pub fn auto_select(codec_id: u16, stream_size: usize, dict: Option<&'a [u8]>) -> CodecLevel {
-        let codec: CompressionCodec = CompressionCodec::from(codec_id).unwrap_or(CompressionCodec::Auto);
+/// Inputs to [`CodecLevel::auto_select`].
+///
+/// Everything is supplied by the caller; nothing is probed. Unset fields
+/// fall back to offline defaults: no GPU, no bandwidth constraint, and
+/// `favor_decompression` / `archival` inferred from the codec and stream size.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AutoSelectHints {
+    /// Expected number of bytes to compress.
+    pub stream_size: usize,
+    /// A compression dictionary is in use.
+    pub has_dict: bool,
+    /// Prefer fast decompression over ratio; `None` infers it from codec and size.
+    pub favor_decompression: Option<bool>,
+    /// Long-term storage, ratio matters most; `None` means streams over 1 GB.
+    pub archival: Option<bool>,
+    /// Caller's bandwidth estimate in Mbit/s (see [`estimate_bandwidth`]).
+    pub bandwidth_mbps: Option<u32>,
+    /// A GPU compressor is available.
+    pub gpu_available: Option<bool>,
+}
 
-        // 1. Dictionary presence
-        let has_dict = dict.is_some();
-
-        // 2. GPU availability (simple probe using wgpu)
-        let gpu_available = {
-            // TODO:
-            // Try to create a wgpu instance and request an adapter
-            // let instance = wgpu::Instance::default();
-            // let adapter = futures::executor::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
-            //     power_preference: wgpu::PowerPreference::HighPerformance,
-            //     compatible_surface: None,
-            //     force_fallback_adapter: false,
-            // }));
-            // adapter.is_some()
-            false
-        };
-
-        // 3. Favor decompression (inferred)
-        let favor_decompression = match codec {
+impl AutoSelectHints {
+    /// Below this many Mbit/s the link, not the CPU, is the bottleneck.
+    pub const LOW_BANDWIDTH_MBPS: u32 = 10;
+
+    pub fn new(stream_size: usize) -> Self {
+        Self { stream_size, ..Self::default() }
+    }
+
+    fn favor_decompression(&self, codec: CompressionCodec) -> bool {
+        self.favor_decompression.unwrap_or(match codec {
             CompressionCodec::Lz4 => true, // LZ4 is decompression‑friendly
-            CompressionCodec::Zstd => stream_size < 1_000_000, // small payloads → favor decompression
+            CompressionCodec::Zstd => self.stream_size < 1_000_000, // small payloads → favor decompression
             CompressionCodec::Deflate => false,
             CompressionCodec::Auto => false,
-        };
-
-        // 4. Archival (inferred from very large streams)
-        let archival = stream_size > 1_000_000_000; // >1 GB considered archival
-
-        // 5. Network bandwidth measurement (simple probe)
-        let network_bandwidth_mbps = {
-            // crude estimation: attempt to connect to a fast host and measure RTT
-            let start = Instant::now();
-            let result = TcpStream::connect_timeout(
-                &"1.1.1.1:53".parse().unwrap(), // Cloudflare DNS
-                Duration::from_millis(200),
-            );
-            if result.is_ok() {
-                let elapsed = start.elapsed().as_millis() as u32;
-                if elapsed < 20 {
-                    100 // assume high bandwidth
-                } else if elapsed < 100 {
-                    50
-                } else {
-                    5 // low bandwidth
-                }
-            } else {
-                10 // fallback default
-            }
-        };
+        })
+    }
+
+    fn archival(&self) -> bool {
+        self.archival.unwrap_or(self.stream_size > 1_000_000_000) // >1 GB considered archival
+    }
+
+    fn low_bandwidth(&self) -> bool {
+        self.bandwidth_mbps.is_some_and(|mbps| mbps < Self::LOW_BANDWIDTH_MBPS)
+    }
+}
+
+/// Crude bandwidth estimate from the TCP connect time to `addr`.
+///
+/// Opens a real connection; never called by the library itself. Returns
+/// `None` if the connection fails within `timeout`. Feed the result into
+/// [`AutoSelectHints::bandwidth_mbps`].
+pub fn estimate_bandwidth(addr: SocketAddr, timeout: Duration) -> Option<u32> {
+    let start = Instant::now();
+    TcpStream::connect_timeout(&addr, timeout).ok()?;
+    let elapsed = start.elapsed().as_millis();
+    Some(if elapsed < 20 {
+        100 // assume high bandwidth
+    } else if elapsed < 100 {
+        50
+    } else {
+        5 // low bandwidth
+    })
+}
+
+impl CodecLevel {
+    /// Select a CodecLevel for `codec_id` from the caller's `hints`.
+    ///
+    /// Pure: the same inputs always give the same level.
+    pub fn auto_select(codec_id: u16, hints: &AutoSelectHints) -> CodecLevel {
+        let codec: CompressionCodec = CompressionCodec::from(codec_id).unwrap_or(CompressionCodec::Auto);
+        let stream_size = hints.stream_size;
+        let has_dict = hints.has_dict;
+        let gpu_available = hints.gpu_available.unwrap_or(false);
+        let favor_decompression = hints.favor_decompression(codec);
+        let archival = hints.archival();
+        let low_bandwidth = hints.low_bandwidth();
 
         // Decision logic
         match codec {
             CompressionCodec::Zstd => {
-                if archival || network_bandwidth_mbps < 10 {
+                if archival || low_bandwidth {
                     CodecLevel::ZstdMax
                 } else if favor_decompression || has_dict {
                     CodecLevel::ZstdFast
@@ -150,7 +181,7 @@ impl<'a> CodecLevel {
             CompressionCodec::Lz4 => {
                 if favor_decompression {
                     CodecLevel::Lz4DecSpeed
-                } else if network_bandwidth_mbps < 10 {
+                } else if low_bandwidth {
                     CodecLevel::Lz4HighAccel
                 } else if stream_size < 1_000_000 {
                     CodecLevel::Lz4Fast
@@ -161,7 +192,7 @@ impl<'a> CodecLevel {
                 }
             }
             CompressionCodec::Deflate => {
-                if archival || network_bandwidth_mbps < 10 {
+                if archival || low_bandwidth {
                     CodecLevel::FlateBest
                 } else if stream_size < 1_000_000 {
                     CodecLevel::FlateFast
@@ -174,7 +205,7 @@ impl<'a> CodecLevel {
             CompressionCodec::Auto => {
                 if stream_size < 1_000_000 {
                     CodecLevel::Lz4Fast
-                } else if network_bandwidth_mbps < 10 {
+                } else if low_bandwidth {
                     CodecLevel::ZstdMax
                 } else {
                     CodecLevel::ZstdBalanced
@@ -182,29 +213,17 @@ impl<'a> CodecLevel {
             }
         }
 
-        // ## 🧩 What’s Automatic Here
-        // - **`dict.is_some()`** → checked directly.  
-        // - **`gpu_available`** → probed via `wgpu::Instance::request_adapter`.  
-        // - **`favor_decompression`** → inferred from codec type and stream size.  
-        // - **`archival`** → inferred if stream size > 1 GB.  
-        // - **`network_bandwidth_mbps`** → measured at runtime using a quick TCP probe.  
-
         // ## 🚀 Example Usage
 
         // ```rust
-        // fn main() {
-        //     let codec = CompressionCodec::Zstd;
-        //     let stream_size = 200_000_000; // 200 MB
-        //     let dict: Option<&[u8]> = None;
-
-        //     let level = CodecInfo::auto_select(codec, stream_size, dict);
-        //     println!("Selected level: {:?}", level);
-        // }
+        // let hints = AutoSelectHints { stream_size: 200_000_000, ..Default::default() }; // 200 MB
+        // let level = CodecLevel::auto_select(codec_ids::ZSTD, &hints);
+        // println!("Selected level: {:?}", level);
         // ```
 
-        // Output (example):
+        // Output:
         // ```
-        // Selected level: ZstdBalanced
+        // Selected level: ZstdMax
         // ```
     }
 
@@ -239,10 +258,8 @@ pub struct CodecOptions<'a> {
 
 impl<'a> CodecOptions<'a> {
     pub fn resolve_auto(codec_id: u16, stream_size: usize, dict: Option<&'a [u8]>) -> Self {
-        // 1. Dictionary presence
-        // let has_dict = dict.is_some();
-
-        let level: CodecLevel = CodecLevel::auto_select(codec_id, stream_size, dict);
+        let hints = AutoSelectHints { has_dict: dict.is_some(), ..AutoSelectHints::new(stream_size) };
+        let level: CodecLevel = CodecLevel::auto_select(codec_id, &hints);
         Self::resolve(level, dict)
     }
     pub fn resolve(level: CodecLevel, dict: Option<&'a [u8]>) -> Self {
@@ -491,6 +508,8 @@ pub enum CompressionError {
     CodecInitFailed { codec: String, msg: String },
     CodecProcessFailed { codec: String, msg: String },
     ChunkTooLarge { have: usize, max: usize },
+    /// Bytes left over after the codec frame of a chunk.
+    TrailingData { codec: String, extra: usize },
     StateError(String),
 }
 
@@ -509,6 +528,7 @@ impl fmt::Display for CompressionError {
             CodecInitFailed { codec, msg } => write!(f, "codec {} init failed: {}", codec, msg),
             CodecProcessFailed { codec, msg } => write!(f, "codec {} process failed: {}", codec, msg),
             ChunkTooLarge { have, max } => write!(f, "chunk too large: {} > {}", have, max),
+            TrailingData { codec, extra } => write!(f, "codec {} frame followed by {} trailing bytes", codec, extra),
             StateError(msg) => write!(f, "compression state error: {}", msg),
         }
     }
@@ -516,53 +536,53 @@ impl fmt::Display for CompressionError {
 
 impl std::error::Error for CompressionError {}
 
-#[repr(u16)]
-#[derive(Copy, Clone, Debug, PartialEq, Eq, TryFromPrimitive)]
-pub enum ChecksumAlg {
-    Crc32   = 0x0001,
-    Blake3   = 0x0201, // UN-KEYED Blake3
-}
-pub fn compute_checksum(data: &[u8], alg: Option<ChecksumAlg>) -> u32 {
-    match alg {
-        Some(ChecksumAlg::Crc32)  => compute_crc32(data),
-        // Some(ChecksumAlg::Blake3) => compute_blake3(data), // Its return 32-bytes
-        _                         => compute_crc32(data)
-    }
-}
+pub use crate::utils::{Checksum, ChecksumAlg, compute_checksum};
 
-pub fn verify_checksum(expected_crc: u32, actual_crc: u32, codec: String) -> Result<(), CompressionError> {
-    if actual_crc != expected_crc {
+/// Compare a checksum read from the wire with one computed locally.
+pub fn verify_checksum(expected: &Checksum, actual: &Checksum, codec: String) -> Result<(), CompressionError> {
+    if expected.alg() != actual.alg() {
         return Err(CompressionError::CodecProcessFailed {
-            codec: codec.into(),
+            codec,
+            msg: format!("checksum algorithm mismatch: {:?} vs {:?}", expected.alg(), actual.alg()),
+        });
+    }
+    if actual != expected {
+        return Err(CompressionError::CodecProcessFailed {
+            codec,
             msg: "checksum mismatch".into(),
         });
     }
     Ok(())
 }
 
-fn compute_crc32(data: &[u8]) -> u32 {
-    use crc32fast::Hasher;
-    let mut hasher = Hasher::new();
-    hasher.update(data);
-    hasher.finalize()
-}
-
-// fn compute_blake3(data: &[u8]) -> [u8; 32] {
-//     use blake3::Hasher;
-//     let mut hasher = Hasher::new();
-//     hasher.update(data);
-//     *hasher.finalize().as_bytes()
-// }
-
 // Require Send so trait objects can cross thread boundaries.
+///
+/// Per-chunk contract: every chunk (one segment in the pipeline) is an
+/// independent codec frame. Callers run `compress_chunk` then `finish` for
+/// each chunk, and the bytes of both form the chunk's complete frame; no
+/// state carries over to the next chunk.
 pub trait Compressor: Send {
     /// Compress a single chunk into out buffer.
     fn compress_chunk(&mut self, input: &[u8], out: &mut Vec<u8>) -> Result<(), CompressionError>;
-    /// Flush any pending state.
+    /// Flush pending state of the current chunk, completing its frame.
     fn finish(&mut self, out: &mut Vec<u8>) -> Result<(), CompressionError>;
 }
 
+/// Counterpart of [`Compressor`]: each input is one complete frame; bytes
+/// left after it fail with `TrailingData`.
 pub trait Decompressor: Send {
     /// Decompress a single chunk into out buffer.
     fn decompress_chunk(&mut self, input: &[u8], out: &mut Vec<u8>) -> Result<(), CompressionError>;
+    /// Cap the decompressed size of a chunk (default `MAX_DECOMPRESSED_LEN`).
+    ///
+    /// Larger chunks fail with `ChunkTooLarge` before their output is materialized.
+    fn set_max_output(&mut self, max: usize);
+}
+
+/// `ChunkTooLarge` when `have` exceeds `max`.
+pub fn check_output_len(have: usize, max: usize) -> Result<(), CompressionError> {
+    if have > max {
+        return Err(CompressionError::ChunkTooLarge { have, max });
+    }
+    Ok(())
 }
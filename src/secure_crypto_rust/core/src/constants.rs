@@ -1,19 +1,39 @@
 
 
-/// Magic number for this envelope version.
+//! Protocol identifiers and tuning defaults.
+//!
+//! Two stability classes:
+//! - **Wire**: written into streams (header fields, flag bits, ids). A value
+//!   never changes meaning; new values are only added, and older builds reject
+//!   them (or, for flags, may tolerate them; see [`flags::validate`]).
+//! - **Tuning**: defaults and sanity bounds of this build. They may change
+//!   between releases without affecting streams already written.
+
+use crate::headers::HeaderError;
+
+/// Magic number for this envelope version. Wire.
 /// "RSE1" = Rust Streaming Envelope v1
 // - If the constant represents a **protocol magic field** (like `"RSE1"` in a header), use `[u8; 4]`. That way the type itself enforces “exactly 4 bytes” and matches our struct field type (`[u8; 4]`).
 pub const MAGIC_RSE1: [u8; 4] = *b"RSE1";
+/// `HeaderV1::version` of the 80-byte v1 layout. Wire.
 pub const HEADER_V1: u16 = 1;
+/// Header versions this build can decode; `headers::decode_any` dispatches on these.
+/// Grows as layouts are added; a version is never removed while streams use it.
+pub const SUPPORTED_HEADER_VERSIONS: &[u16] = &[HEADER_V1];
 
 // Basic sanity: minimum length and maybe a magic prefix
 // require first 4 bytes to be a magic number
 // - If the constant is more of a **prefix marker** we’ll check against slices (like `"DICT"` at the start of a dictionary payload), then `&[u8]` is fine:
+/// Prefix of a serialized dictionary payload. Wire: dictionaries stored next
+/// to streams keep it.
 pub const MAGIC_DICT: &[u8] = b"DICT";
+/// Shortest dictionary payload accepted, prefix included. Tuning.
 pub const MIN_DICT_LEN: usize = 8;
+/// Longest dictionary payload accepted. Tuning.
 pub const MAX_DICT_LEN: usize = 1 << 20; // 1 MiB cap for sanity
 
-/// Industry-standard master key lengths (AES-128, AES-192, AES-256)
+/// Industry-standard master key lengths (AES-128, AES-192, AES-256).
+/// Every length derives a 32-byte session key, so adding one never changes the wire.
 pub const MASTER_KEY_LENGTHS: &[usize] = &[16, 24, 32];
 
 // ### 📊 Comparison table
@@ -28,19 +48,28 @@ pub const MASTER_KEY_LENGTHS: &[usize] = &[16, 24, 32];
 // - **Match physical cores**: Each worker is CPU‑bound (AES, compression, HKDF). Running more workers than cores just adds context‑switch overhead.  
 // - **Typical range**: 4–16 workers for server‑class CPUs; 2–8 for laptops.  
 // - **Scaling**: Beyond 16 workers, diminishing returns set in unless we’re on a many‑core server (32+ cores).  
-pub const WORKERS_COUNT: &[usize] = &[2, 4, 8, 16];
+// - **Validation**: any count in `1..=num_cpus::get()` is accepted; there is no whitelist.
 
 // ### 🧩 Why queue cap matters
 // - **Small queue (2–16)**: Keeps latency low, avoids excessive buffering, and ensures back‑pressure works correctly.  
 // - **Large queue (>32)**: Can cause memory bloat, uneven scheduling, and delayed error propagation. Most cryptographic pipelines (AES, VPNs, TLS offload) deliberately cap queues at small powers of two.  
 // - **Industry practice**: VPN engines, GPU crypto libraries, and parallel AES implementations typically use **queue caps of 4–16**.
-pub const QUEUE_CAPS: &[usize] = &[2, 4, 8, 16];
+// - **Validation**: any cap in `1..=MAX_QUEUE_CAP` is accepted; larger values are allowed for deep pipelines but rarely help.
+/// Largest accepted queue cap. Tuning.
+pub const MAX_QUEUE_CAP: usize = 1024;
+/// Worker count when none is configured. Tuning.
 pub const DEFAULT_WORKERS: usize = 2;            // or num_cpus::get()
+/// Queue cap when none is configured. Tuning.
 pub const DEFAULT_QUEUE_CAP: usize = 4;          // or workers * 2
 
-/// Defaults when Option<T> is None
+/// Chunk size when none is configured. Tuning: the chosen size is recorded
+/// in each header, so changing the default never affects existing streams.
 pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024; // 64 KB
 /// Industry-standard chunk sizes (in bytes) ✅
+///
+/// The sizes [`ChunkSize`] accepts and chunk size rounding snaps to. Tuning:
+/// decoders accept any `chunk_size` up to [`MAX_CHUNK_SIZE`], so adding a
+/// size here needs no stream change.
 pub const ALLOWED_CHUNK_SIZES: &[usize] = &[
     16 * 1024,    // 16 KiB  - IoT/embedded, constrained memory
     32 * 1024,    // 32 KiB  - Mobile devices, network packets
@@ -51,9 +80,67 @@ pub const ALLOWED_CHUNK_SIZES: &[usize] = &[
     2048 * 1024,  // 2 MiB   - Large file transfers
     4096 * 1024,  // 4 MiB   - High-throughput systems
 ];
-/// Max chunk size sanity bound (32 MiB).
+/// Max chunk size sanity bound (32 MiB). Wire: decoders reject larger
+/// `chunk_size` values, so it can only grow.
 pub const MAX_CHUNK_SIZE: usize = 32 * 1024 * 1024;
 
+/// A chunk size from [`ALLOWED_CHUNK_SIZES`]; no other value can be built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ChunkSize(u32);
+
+impl ChunkSize {
+    /// [`DEFAULT_CHUNK_SIZE`].
+    pub const DEFAULT: ChunkSize = ChunkSize(DEFAULT_CHUNK_SIZE as u32);
+
+    /// `bytes` as a chunk size, if it is one of [`ALLOWED_CHUNK_SIZES`].
+    pub fn new(bytes: usize) -> Option<Self> {
+        ALLOWED_CHUNK_SIZES.contains(&bytes).then_some(ChunkSize(bytes as u32))
+    }
+
+    /// Every allowed size, smallest first.
+    pub fn all() -> impl Iterator<Item = ChunkSize> {
+        ALLOWED_CHUNK_SIZES.iter().map(|&bytes| ChunkSize(bytes as u32))
+    }
+
+    /// Size in bytes, as stored in `HeaderV1::chunk_size`.
+    pub fn get(self) -> u32 {
+        self.0
+    }
+}
+
+impl Default for ChunkSize {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl TryFrom<usize> for ChunkSize {
+    type Error = HeaderError;
+
+    fn try_from(bytes: usize) -> Result<Self, HeaderError> {
+        Self::new(bytes).ok_or_else(|| {
+            HeaderError::Validation(format!("chunk size {bytes} is not one of {ALLOWED_CHUNK_SIZES:?}"))
+        })
+    }
+}
+
+impl From<ChunkSize> for u32 {
+    fn from(size: ChunkSize) -> u32 {
+        size.0
+    }
+}
+
+impl From<ChunkSize> for usize {
+    fn from(size: ChunkSize) -> usize {
+        size.0 as usize
+    }
+}
+
+/// Minimum size reduction (percent) for a compressed segment to be kept;
+/// otherwise the segment is stored raw with `SegmentFlags::UNCOMPRESSED`.
+/// Tuning: the segment flag records the outcome.
+pub const MIN_COMPRESSION_SAVINGS_PCT: usize = 5;
+
 #[derive(Debug, Clone, Copy)]
 pub enum ChunkPolicy {
     RoundUp,
@@ -86,13 +173,13 @@ impl RoundingBase {
     }
 }
 
-/// Cipher suite identifiers (mirrored in headers).
+/// Cipher suite identifiers (mirrored in headers, `HeaderV1::cipher`). Wire.
 pub mod cipher_ids {
     pub const AES256_GCM: u16        = 0x0001;
     pub const CHACHA20_POLY1305: u16 = 0x0002;
 }
 
-/// HKDF PRF identifiers (mirrored in headers).
+/// HKDF PRF identifiers (mirrored in headers, `HeaderV1::hkdf_prf`). Wire.
 pub mod prf_ids {
     pub const SHA256: u16  = 0x0001;
     pub const SHA512: u16  = 0x0002;
@@ -101,12 +188,76 @@ pub mod prf_ids {
     pub const BLAKE3K: u16 = 0x0005; // keyed BLAKE3 (avoid unless policy requires)
 }
 
-/// Flag bitmask for optional features and metadata presence.
+/// Flag bitmask for optional features and metadata presence (`HeaderV1::flags`). Wire.
+///
+/// A bit keeps its meaning forever; new features take unused bits and go in
+/// [`all`]. Decoders reject bits outside [`KNOWN_MASK`] unless told to
+/// tolerate them (`UnknownFlagPolicy::Tolerate`).
 pub mod flags {
+    use crate::headers::HeaderError;
+
     pub const HAS_TOTAL_LEN: u16    = 0x0001;
     pub const HAS_CRC32: u16        = 0x0002;
     pub const HAS_TERMINATOR: u16   = 0x0004;
     pub const HAS_FINAL_DIGEST: u16 = 0x0008;
     pub const DICT_USED: u16        = 0x0010;
     pub const AAD_STRICT: u16       = 0x0020;
+    pub const HAS_SEGMENT_INDEX: u16 = 0x0040; // authenticated index segment + footer after the final segment
+    pub const HAS_KEY_CHECK: u16    = 0x0080; // key check value in reserved[0..4] (see crypto::derive_key_check)
+    pub const STRICT_EXTENSIONS: u16 = 0x0100; // reject ext_flags bits outside KNOWN_EXT_FLAGS and malformed extension blocks
+    pub const SEGMENT_HEADER_CRC: u16 = 0x0200; // segment headers are layout v2, carrying a CRC-8 of their own fields
+    pub const EXPLICIT_FRAMES: u16  = 0x0400; // segment closing frames follow HAS_TERMINATOR / HAS_FINAL_DIGEST (see headers::FrameLayout)
+    pub const PER_SEGMENT_KEYS: u16 = 0x0800; // frames of each segment are sealed under crypto::derive_segment_key
+    pub const SEGMENT_DICTS: u16    = 0x1000; // segments may select their own dictionary (SegmentFlags::DICT_OVERRIDE)
+    pub const SEGMENT_FLAGS_AAD: u16 = 0x2000; // frame AAD is v3, binding the segment flags (see crypto::aad_version)
+
+    /// Every bit this build understands.
+    pub const KNOWN_MASK: u16 = HAS_TOTAL_LEN
+        | HAS_CRC32
+        | HAS_TERMINATOR
+        | HAS_FINAL_DIGEST
+        | DICT_USED
+        | AAD_STRICT
+        | HAS_SEGMENT_INDEX
+        | HAS_KEY_CHECK
+        | STRICT_EXTENSIONS
+        | SEGMENT_HEADER_CRC
+        | EXPLICIT_FRAMES
+        | PER_SEGMENT_KEYS
+        | SEGMENT_DICTS
+        | SEGMENT_FLAGS_AAD;
+
+    /// Every known bit and its name, lowest bit first.
+    pub fn all() -> &'static [(u16, &'static str)] {
+        &[
+            (HAS_TOTAL_LEN, "HAS_TOTAL_LEN"),
+            (HAS_CRC32, "HAS_CRC32"),
+            (HAS_TERMINATOR, "HAS_TERMINATOR"),
+            (HAS_FINAL_DIGEST, "HAS_FINAL_DIGEST"),
+            (DICT_USED, "DICT_USED"),
+            (AAD_STRICT, "AAD_STRICT"),
+            (HAS_SEGMENT_INDEX, "HAS_SEGMENT_INDEX"),
+            (HAS_KEY_CHECK, "HAS_KEY_CHECK"),
+            (STRICT_EXTENSIONS, "STRICT_EXTENSIONS"),
+            (SEGMENT_HEADER_CRC, "SEGMENT_HEADER_CRC"),
+            (EXPLICIT_FRAMES, "EXPLICIT_FRAMES"),
+            (PER_SEGMENT_KEYS, "PER_SEGMENT_KEYS"),
+            (SEGMENT_DICTS, "SEGMENT_DICTS"),
+            (SEGMENT_FLAGS_AAD, "SEGMENT_FLAGS_AAD"),
+        ]
+    }
+
+    /// Fail with `HeaderError::UnknownFlags` if `raw` has bits outside [`KNOWN_MASK`].
+    pub fn validate(raw: u16) -> Result<(), HeaderError> {
+        match raw & !KNOWN_MASK {
+            0 => Ok(()),
+            bits => Err(HeaderError::UnknownFlags { bits }),
+        }
+    }
 }
+
+/// Extension flag bits (`HeaderV1::ext_flags`, reserved[4..6]) this build understands. Wire.
+///
+/// No extension flag is defined yet; unknown bits are tolerated unless
+/// `flags::STRICT_EXTENSIONS` is set.
+pub const KNOWN_EXT_FLAGS: u16 = 0x0000;
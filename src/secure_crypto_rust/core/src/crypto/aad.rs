@@ -2,14 +2,52 @@
 
 // ## 📂 File: `src/crypto/aad.rs`
 
-use crate::headers::encode_header_le;
-use crate::headers::types::{HeaderV1, AadDomain};
-use crate::crypto::types::{AadError, AadHeader};
+use crate::constants::flags;
+use crate::headers::encode_header_le_with;
+use crate::headers::types::{HeaderV1, AadDomain, UnknownFlagPolicy};
+use crate::crypto::types::{AadError, AadHeader, AAD_V2, AAD_V3};
+
+/// Frame AAD revision of a stream: [`AAD_V3`] when its header sets
+/// `SEGMENT_FLAGS_AAD`, [`AAD_V2`] otherwise.
+///
+/// The flag is part of the HKDF 'info', so it cannot be cleared to open a
+/// stream under the weaker revision: the session key changes with it.
+#[inline]
+pub fn aad_version(header: &HeaderV1) -> u8 {
+    if header.flags & flags::SEGMENT_FLAGS_AAD != 0 { AAD_V3 } else { AAD_V2 }
+}
 
+/// Build the AAD of one frame.
+///
+/// ```text
+/// [ aad_version (1) = aad_version(header) ]
+/// [ aad_domain  (2) ][ compression (2) ][ dict_id (4) ][ chunk_size (4) ]
+/// [ encoded HeaderV1 (80) ][ extension block ]  only with AAD_STRICT
+/// [ frame_type (1) ][ segment_index (4) ][ frame_index (4) ][ plaintext_len (4) ]
+/// [ segment_flags (2) ]                          only from AAD_V3 on
+/// ```
+///
+/// Everything is little-endian. The stream fields stop a captured header from
+/// being replayed with another codec or dictionary: frames fail to open
+/// instead of decompressing to garbage. The segment flags do the same for a
+/// segment header whose `UNCOMPRESSED` or trailer bits were flipped.
 #[inline]
 pub fn build_aad(
     header: &HeaderV1,
     aad_header: &AadHeader,
+) -> Result<Vec<u8>, AadError> {
+    build_aad_with_extensions(header, &[], aad_header)
+}
+
+/// [`build_aad`] for streams carrying a TLV extension block (`header.ext_len > 0`).
+///
+/// Under `AAD_STRICT` the raw block follows the encoded header, so editing an
+/// extension fails every frame; otherwise `extensions` is ignored.
+#[inline]
+pub fn build_aad_with_extensions(
+    header: &HeaderV1,
+    extensions: &[u8],
+    aad_header: &AadHeader,
 ) -> Result<Vec<u8>, AadError> {
     // Validate domain early (domain separation safety)
     if AadDomain::try_from(header.aad_domain).is_err() {
@@ -18,27 +56,50 @@ pub fn build_aad(
         });
     }
 
-    let mut out = Vec::with_capacity(AadHeader::LEN_V1);
-
-    // 1️⃣ Authenticate EXACT encoded header bytes (stream-level invariants)
-    let header_bytes = encode_header_le(header)?;
-    out.extend_from_slice(&header_bytes);
+    let version = aad_version(header);
+    let strict = header.flags & flags::AAD_STRICT != 0;
+    let len = match (version, strict) {
+        (AAD_V3, true) => AadHeader::LEN_V3_STRICT + extensions.len(),
+        (AAD_V3, false) => AadHeader::LEN_V3,
+        (_, true) => AadHeader::LEN_V2_STRICT + extensions.len(),
+        (_, false) => AadHeader::LEN_V2,
+    };
+    let mut out = Vec::with_capacity(len);
+
+    // 1️⃣ Version, then the stream fields every frame depends on
+    out.push(version);
+    out.extend_from_slice(&header.aad_domain.to_le_bytes());
+    out.extend_from_slice(&header.compression.to_le_bytes());
+    out.extend_from_slice(&header.dict_id.to_le_bytes());
+    out.extend_from_slice(&header.chunk_size.to_le_bytes());
+
+    // 2️⃣ Strict mode: EXACT encoded header bytes (all stream-level invariants)
+    if strict {
+        // Writers rejected unknown flags, readers applied their policy on decode
+        let header_bytes = encode_header_le_with(header, UnknownFlagPolicy::Tolerate)?;
+        out.extend_from_slice(&header_bytes);
+        out.extend_from_slice(extensions);
+    }
 
-    // 2️⃣ Authenticate ONLY immutable frame invariants
+    // 3️⃣ Authenticate ONLY immutable frame invariants
     out.extend_from_slice(&aad_header.frame_type.to_le_bytes());
     out.extend_from_slice(&aad_header.segment_index.to_le_bytes());
     out.extend_from_slice(&aad_header.frame_index.to_le_bytes());
     out.extend_from_slice(&aad_header.plaintext_len.to_le_bytes());
+    if version == AAD_V3 {
+        out.extend_from_slice(&aad_header.segment_flags.to_le_bytes());
+    }
 
-    debug_assert_eq!(out.len(), AadHeader::LEN_V1);
+    debug_assert_eq!(out.len(), len);
     Ok(out)
 }
 // ### ✅ AAD is now deterministic and parallel-safe
 
 // AAD is derived **only** from:
 
-// * encoded `HeaderV1` (80 bytes)
-// * immutable per-frame metadata
+// * the AAD version byte and codec-related header fields
+// * encoded `HeaderV1` (80 bytes) and its extension block, under `AAD_STRICT`
+// * immutable per-frame metadata, and the flags of its segment from v3 on
 
 // This guarantees:
 
@@ -56,6 +117,7 @@ pub fn build_aad(
 // | segment_index  | ✅       | ordering              |
 // | frame_index    | ✅       | nonce binding         |
 // | plaintext_len  | ✅       | truncation protection |
+// | segment_flags  | ✅ (v3)  | payload semantics     |
 // | compressed_len | ❌       | mutable               |
 // | ciphertext_len | ❌       | post-encryption       |
 
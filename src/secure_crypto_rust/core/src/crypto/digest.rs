@@ -2,10 +2,14 @@ use std::fmt;
 use std::convert::TryFrom;
 use num_enum::TryFromPrimitive;
 
+use digest::array::{typenum::Unsigned, Array};
+use digest::crypto_common::hazmat::SerializableState;
 use sha2::{Digest as _, Sha256, Sha512};
 use sha3::{Sha3_256, Sha3_512};
 use blake3;
 
+use crate::crypto::kdf::derive_digest_key;
+use crate::crypto::types::KEY_LEN_32;
 use crate::utils::enum_name_or_hex;
 
 /// Digest-related errors.
@@ -15,6 +19,12 @@ pub enum DigestError {
     DigestMismatch,
     InvalidFormat,
     InvalidLength { have: usize, need: usize },
+    /// The algorithm's hash state cannot be exported (see `DigestAlg::can_resume`).
+    NotResumable { alg: DigestAlg },
+    /// A keyed algorithm was requested without the session key.
+    KeyRequired { alg: DigestAlg },
+    /// DATA frames must be fed once each, in ascending `frame_index` order.
+    FrameOrderViolation { expected: u32, got: u32 },
 }
 impl fmt::Display for DigestError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -27,9 +37,17 @@ impl fmt::Display for DigestError {
             InvalidFormat => write!(f, "invalid header: {}", "Invalid frame header"),
             InvalidLength { have, need } =>
                 write!(f, "digest buffer too short: {} < {}", have, need),
+            NotResumable { alg } =>
+                write!(f, "digest state of {} cannot be checkpointed", alg),
+            KeyRequired { alg } =>
+                write!(f, "{} digests need the session key", alg),
+            FrameOrderViolation { expected, got } =>
+                write!(f, "frame order violation: expected frame {}, got {}", expected, got),
         }
     }
 }
+
+impl std::error::Error for DigestError {}
 /// Supported digest algorithms (extensible).
 #[repr(u16)]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, TryFromPrimitive)]
@@ -43,15 +61,29 @@ pub enum DigestAlg {
     // Sha3_384 = 0x0103,
     Sha3_512 = 0x0104,
     Blake3   = 0x0201, // UN-KEYED Blake3
+    Blake3Keyed = 0x0202, // Blake3 keyed with `derive_digest_key(session_key)`
 }
 impl DigestAlg {
     pub fn can_resume(&self) -> bool {
         let state = match self {
-            DigestAlg::Blake3       => false,
+            DigestAlg::Blake3 | DigestAlg::Blake3Keyed => false,
             _       => true,
         };
         state
     }
+
+    /// Whether the digest is keyed by the session key (acts as a MAC).
+    pub fn is_keyed(&self) -> bool {
+        matches!(self, DigestAlg::Blake3Keyed)
+    }
+
+    /// Digest output length in bytes.
+    pub fn digest_len(&self) -> usize {
+        match self {
+            DigestAlg::Sha256 | DigestAlg::Sha3_256 | DigestAlg::Blake3 | DigestAlg::Blake3Keyed => 32,
+            DigestAlg::Sha512 | DigestAlg::Sha3_512 => 64,
+        }
+    }
 }
 
 // impl TryFrom<u16> for DigestAlg {
@@ -81,6 +113,7 @@ impl fmt::Display for DigestAlg {
             DigestAlg::Sha3_256       => "Sha3_256",
             DigestAlg::Sha3_512       => "Sha3_512",
             DigestAlg::Blake3       => "Blake3",
+            DigestAlg::Blake3Keyed  => "Blake3Keyed",
         };
         f.write_str(name)
     }
@@ -98,10 +131,14 @@ pub enum DigestState {
     // Sha3_384(Sha3_384),
     Sha3_512(Sha3_512),
     Blake3(blake3::Hasher),
+    Blake3Keyed(blake3::Hasher),
 }
 
 impl DigestState {
     /// Create a new digest state.
+    ///
+    /// # Panics
+    /// For `Blake3Keyed`, which needs a key: use [`DigestState::new_keyed`].
     #[inline]
     pub fn new(alg: DigestAlg) -> Self {
         match alg {
@@ -110,6 +147,23 @@ impl DigestState {
             DigestAlg::Sha3_256 => DigestState::Sha3_256(Sha3_256::new()),
             DigestAlg::Sha3_512 => DigestState::Sha3_512(Sha3_512::new()),
             DigestAlg::Blake3   => DigestState::Blake3(blake3::Hasher::new()),
+            DigestAlg::Blake3Keyed => panic!("Blake3Keyed digests need a key, use DigestState::new_keyed"),
+        }
+    }
+
+    /// Create a `Blake3Keyed` state, keyed by the digest key derived from `session_key`.
+    #[inline]
+    pub fn new_keyed(session_key: &[u8; KEY_LEN_32]) -> Self {
+        let key = derive_digest_key(session_key);
+        DigestState::Blake3Keyed(blake3::Hasher::new_keyed(&key))
+    }
+
+    /// Create a state for `alg`; keyed algorithms need `session_key`.
+    pub fn for_alg(alg: DigestAlg, session_key: Option<&[u8; KEY_LEN_32]>) -> Result<Self, DigestError> {
+        match (alg, session_key) {
+            (DigestAlg::Blake3Keyed, Some(key)) => Ok(DigestState::new_keyed(key)),
+            (DigestAlg::Blake3Keyed, None) => Err(DigestError::KeyRequired { alg }),
+            (alg, _) => Ok(DigestState::new(alg)),
         }
     }
 
@@ -121,6 +175,7 @@ impl DigestState {
             DigestState::Sha3_256(_) => DigestAlg::Sha3_256,
             DigestState::Sha3_512(_) => DigestAlg::Sha3_512,
             DigestState::Blake3(_)   => DigestAlg::Blake3,
+            DigestState::Blake3Keyed(_) => DigestAlg::Blake3Keyed,
         }
     }
 
@@ -135,7 +190,7 @@ impl DigestState {
             DigestState::Sha3_256(h) => h.update(data),
             // DigestState::Sha3_384(h) => h.update(data),
             DigestState::Sha3_512(h) => h.update(data),
-            DigestState::Blake3(h)   => { h.update(data); },
+            DigestState::Blake3(h) | DigestState::Blake3Keyed(h) => { h.update(data); },
         }
     }
 
@@ -150,9 +205,40 @@ impl DigestState {
             DigestState::Sha3_256(h) => h.finalize().to_vec(),
             // DigestState::Sha3_384(h) => h.finalize().to_vec(),
             DigestState::Sha3_512(h) => h.finalize().to_vec(),
-            DigestState::Blake3(h)   => h.finalize().as_bytes().to_vec(),
+            DigestState::Blake3(h) | DigestState::Blake3Keyed(h) => h.finalize().as_bytes().to_vec(),
         }
     }
+
+    /// Export the hash state for a checkpoint.
+    ///
+    /// Fails with `NotResumable` for algorithms where `can_resume()` is false.
+    pub fn serialize(&self) -> Result<Vec<u8>, DigestError> {
+        match self {
+            DigestState::Sha256(h)   => Ok(h.serialize().to_vec()),
+            DigestState::Sha512(h)   => Ok(h.serialize().to_vec()),
+            DigestState::Sha3_256(h) => Ok(h.serialize().to_vec()),
+            DigestState::Sha3_512(h) => Ok(h.serialize().to_vec()),
+            DigestState::Blake3(_) | DigestState::Blake3Keyed(_) => Err(DigestError::NotResumable { alg: self.alg() }),
+        }
+    }
+
+    /// Rebuild a hash state exported by [`DigestState::serialize`].
+    pub fn deserialize(alg: DigestAlg, bytes: &[u8]) -> Result<Self, DigestError> {
+        match alg {
+            DigestAlg::Sha256   => restore_state(bytes).map(DigestState::Sha256),
+            DigestAlg::Sha512   => restore_state(bytes).map(DigestState::Sha512),
+            DigestAlg::Sha3_256 => restore_state(bytes).map(DigestState::Sha3_256),
+            DigestAlg::Sha3_512 => restore_state(bytes).map(DigestState::Sha3_512),
+            DigestAlg::Blake3 | DigestAlg::Blake3Keyed => Err(DigestError::NotResumable { alg }),
+        }
+    }
+}
+
+fn restore_state<H: SerializableState>(bytes: &[u8]) -> Result<H, DigestError> {
+    let need = H::SerializedStateSize::USIZE;
+    let state = Array::try_from(bytes)
+        .map_err(|_| DigestError::InvalidLength { have: bytes.len(), need })?;
+    H::deserialize(&state).map_err(|_| DigestError::InvalidFormat)
 }
 
 /// Digest frame decoded from plaintext.
@@ -246,33 +332,61 @@ pub struct SegmentDigestBuilder {
     pub state: DigestState,
     pub segment_index: u32,
     pub frame_count: u32,
+    /// `frame_index` the next `update_frame` must carry.
+    pub next_frame_index: u32,
     pub finalized: bool,
 }
 impl SegmentDigestBuilder {
     /// Create a new digest builder.
+    ///
+    /// # Panics
+    /// For `Blake3Keyed`: use [`SegmentDigestBuilder::new_keyed`].
     #[inline]
     pub fn new(alg: DigestAlg, segment_index: u32, frame_count: u32) -> Self {
-        let mut state = DigestState::new(alg);
+        Self::fresh(DigestState::new(alg), segment_index, frame_count)
+    }
+
+    /// Create a `Blake3Keyed` builder; `key` is the session key, the digest
+    /// key is derived from it.
+    #[inline]
+    pub fn new_keyed(key: &[u8; KEY_LEN_32], segment_index: u32, frame_count: u32) -> Self {
+        Self::fresh(DigestState::new_keyed(key), segment_index, frame_count)
+    }
 
+    /// Create a builder for `alg`; keyed algorithms need `session_key`.
+    pub fn for_alg(
+        alg: DigestAlg,
+        session_key: Option<&[u8; KEY_LEN_32]>,
+        segment_index: u32,
+        frame_count: u32,
+    ) -> Result<Self, DigestError> {
+        Ok(Self::fresh(DigestState::for_alg(alg, session_key)?, segment_index, frame_count))
+    }
+
+    fn fresh(mut state: DigestState, segment_index: u32, frame_count: u32) -> Self {
         // Feed segment header: MUST be done for a fresh segment
         state.update(&segment_index.to_le_bytes());
         state.update(&frame_count.to_le_bytes());
 
         Self {
-            alg,
+            alg: state.alg(),
             state,
             segment_index,
             frame_count,
+            next_frame_index: 0,
             finalized: false,
         }
     }
 
     /// Create a verifier by resuming from an existing hydrated state.
     /// Used for frame-level resume within a single segment.
+    ///
+    /// `next_frame_index` is the first frame not yet fed to `state`.
     pub fn with_state(
         state: DigestState,
         segment_index: u32,
         frame_count: u32,
+        next_frame_index: u32,
     ) -> Self {
         // FIX: Extract the algorithm from the existing state
         let alg = state.alg();
@@ -286,6 +400,7 @@ impl SegmentDigestBuilder {
             state,
             segment_index,
             frame_count,
+            next_frame_index,
             finalized: false,
         }
     }
@@ -301,13 +416,18 @@ impl SegmentDigestBuilder {
     }
 
     /// Feed one DATA frame (strictly ascending `frame_index`).
+    ///
+    /// Fails with `FrameOrderViolation`, hashing nothing, unless `frame_index`
+    /// is `next_frame_index`: frames out of order or repeated would yield a
+    /// digest of some other segment.
     #[inline]
-    pub fn update_frame(&mut self, frame_index: u32, ciphertext: &[u8]) {
+    pub fn update_frame(&mut self, frame_index: u32, ciphertext: &[u8]) -> Result<(), DigestError> {
+        check_frame_order(self.next_frame_index, frame_index)?;
         self.update(&frame_index.to_le_bytes());
         self.update(&(ciphertext.len() as u32).to_le_bytes());
         self.update(ciphertext);
-        println!("builder input: seg={} frame_count={} frame_index={} ct_len={}",
-            self.segment_index, self.frame_count, frame_index, ciphertext.len());
+        self.next_frame_index = frame_index.saturating_add(1);
+        Ok(())
     }
 
     /// Finalize and return digest bytes.
@@ -322,46 +442,77 @@ impl SegmentDigestBuilder {
 }
 
 /// Streaming verifier (bit-exact with `DigestBuilder`).
+///
+/// [`finalize`](Self::finalize) compares the computed digest with the expected
+/// one in constant time (see [`ct_eq`]): a mismatch takes as long whichever
+/// byte differs.
 pub struct SegmentDigestVerifier {
     _alg: DigestAlg,
     state: DigestState,
     expected: Vec<u8>,
     segment_index: u32,
     frame_count: u32,
+    next_frame_index: u32,
     finalized: bool,
 }
 
 impl SegmentDigestVerifier {
     /// Create a fresh verifier for a new segment.
     /// This hashes the segment header (index and frame count) immediately.
+    ///
+    /// # Panics
+    /// For `Blake3Keyed`: use [`SegmentDigestVerifier::new_keyed`].
     pub fn new(
         alg: DigestAlg,
         segment_index: u32,
         frame_count: u32,
         expected: Vec<u8>,
     ) -> Self {
-        let mut state = DigestState::new(alg);
+        Self::fresh(DigestState::new(alg), segment_index, frame_count, expected)
+    }
+
+    /// Create a `Blake3Keyed` verifier; `key` is the session key the
+    /// segment was digested under.
+    pub fn new_keyed(key: &[u8; KEY_LEN_32], segment_index: u32, frame_count: u32, expected: Vec<u8>) -> Self {
+        Self::fresh(DigestState::new_keyed(key), segment_index, frame_count, expected)
+    }
+
+    /// Create a verifier for `alg`; keyed algorithms need `session_key`.
+    pub fn for_alg(
+        alg: DigestAlg,
+        session_key: Option<&[u8; KEY_LEN_32]>,
+        segment_index: u32,
+        frame_count: u32,
+        expected: Vec<u8>,
+    ) -> Result<Self, DigestError> {
+        Ok(Self::fresh(DigestState::for_alg(alg, session_key)?, segment_index, frame_count, expected))
+    }
 
+    fn fresh(mut state: DigestState, segment_index: u32, frame_count: u32, expected: Vec<u8>) -> Self {
         // Feed segment header: MUST be done for a fresh segment
         state.update(&segment_index.to_le_bytes());
         state.update(&frame_count.to_le_bytes());
 
         Self {
-            _alg: alg,
+            _alg: state.alg(),
             state,
             expected,
             segment_index,
             frame_count,
+            next_frame_index: 0,
             finalized: false,
         }
     }
 
     /// Create a verifier by resuming from an existing hydrated state.
     /// Used for frame-level resume within a single segment.
+    ///
+    /// `next_frame_index` is the first frame not yet fed to `state`.
     pub fn with_state(
         state: DigestState,
         segment_index: u32,
         frame_count: u32,
+        next_frame_index: u32,
         expected: Vec<u8>,
     ) -> Self {
         // FIX: Extract the algorithm from the existing state
@@ -377,6 +528,7 @@ impl SegmentDigestVerifier {
             expected,
             segment_index,
             frame_count,
+            next_frame_index,
             finalized: false,
         }
     }
@@ -385,6 +537,21 @@ impl SegmentDigestVerifier {
         self.state.clone() // DigestState must implement Clone
     }
 
+    /// Segment this verifier was created for.
+    pub fn segment_index(&self) -> u32 {
+        self.segment_index
+    }
+
+    /// Number of DATA frames bound into the digest.
+    pub fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    /// `frame_index` the next `update_frame` must carry.
+    pub fn next_frame_index(&self) -> u32 {
+        self.next_frame_index
+    }
+
     #[inline]
     fn update(&mut self, data: &[u8]) {
         debug_assert!(!self.finalized);
@@ -392,20 +559,23 @@ impl SegmentDigestVerifier {
     }
 
     /// Feed one DATA frame (strictly ascending `frame_index`).
+    ///
+    /// Fails with `FrameOrderViolation`, like [`SegmentDigestBuilder::update_frame`].
     #[inline]
-    pub fn update_frame(&mut self, frame_index: u32, ciphertext: &[u8]) {
+    pub fn update_frame(&mut self, frame_index: u32, ciphertext: &[u8]) -> Result<(), DigestError> {
+        check_frame_order(self.next_frame_index, frame_index)?;
         self.update(&frame_index.to_le_bytes());
         self.update(&(ciphertext.len() as u32).to_le_bytes());
         self.update(ciphertext);
-        println!("verifier input: seg={} frame_count={} frame_index={} ct_len={}",
-            self.segment_index, self.frame_count, frame_index, ciphertext.len());
+        self.next_frame_index = frame_index.saturating_add(1);
+        Ok(())
     }
 
     /// Finalize and compare against expected digest.
     pub fn finalize(mut self) -> Result<(), DigestError> {
         self.finalized = true;
         let actual = self.state.finalize();
-        if actual == self.expected {
+        if ct_eq(&actual, &self.expected) {
             Ok(())
         } else {
             Err(DigestError::DigestMismatch)
@@ -413,3 +583,27 @@ impl SegmentDigestVerifier {
     }
 
 }
+
+/// Constant-time equality for authenticator values (digests, key check values).
+///
+/// Every byte is inspected whatever the contents; only the lengths, which are
+/// public, may end the comparison early.
+#[inline(never)]
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    // Keep the optimiser from turning the fold back into an early-exit compare
+    std::hint::black_box(diff) == 0
+}
+
+/// Canonical order: DATA frames `0, 1, 2, ...`, each exactly once.
+#[inline]
+fn check_frame_order(expected: u32, got: u32) -> Result<(), DigestError> {
+    if got == expected {
+        Ok(())
+    } else {
+        Err(DigestError::FrameOrderViolation { expected, got })
+    }
+}
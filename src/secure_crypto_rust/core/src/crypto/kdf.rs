@@ -3,13 +3,14 @@
 //! crypto/kdf.rs
 //! HKDF-based session key derivation from master key and header salt.
 //!
-//! Supported PRFs: SHA-256, SHA-512, SHA3-256, SHA3-512, Blake3 derive_key.
+//! Supported PRFs: SHA-256, SHA-512, SHA3-256, SHA3-512, keyed BLAKE3.
 //!
 //! Design:
 //! - HKDF-Extract(master_key, salt) -> PRK
 //! - HKDF-Expand(PRK, info) -> session key (32 bytes)
 //! - HKDF-based session key derivation from master key and header salt.
-//! - Supports SHA-256, SHA-512, SHA3-256, SHA3-512, and Blake3 derive_key.
+//! - Supports SHA-256, SHA-512, SHA3-256, SHA3-512, and keyed BLAKE3
+//!   (derive_key extract, keyed_hash expand).
 //!
 //! Security notes:
 //! - Salt must be random per stream.
@@ -20,18 +21,30 @@
 //! - Mirrors TLS 1.3/QUIC key schedules: derive traffic keys via HKDF.
 //! - Salt must be random per stream. Info binds protocol identity.
 
-use crate::constants::prf_ids;
-use crate::headers::types::HeaderV1;
+use crate::headers::types::{HeaderV1, HkdfPrf};
 use crate::crypto::types::{KEY_LEN_32, CryptoError};
 
 use hkdf::Hkdf;
 use sha2::{Sha256, Sha512};
 use sha3::{Sha3_256, Sha3_512};
-use blake3::derive_key;
+use blake3::{derive_key, keyed_hash};
+use zeroize::Zeroizing;
+
+/// Length of the key check value carried in `HeaderV1::reserved` (`HAS_KEY_CHECK`).
+pub const KEY_CHECK_LEN: usize = 4;
+
+/// HKDF 'info' label of the key check value; never used for any other output.
+const KEY_CHECK_INFO: &[u8] = b"RSE1|KEY-CHECK";
+
+/// HKDF 'info' label of the keyed segment digest key (`DigestAlg::Blake3Keyed`).
+const DIGEST_KEY_INFO: &[u8] = b"RSE1|SEGMENT-DIGEST";
+
+/// HKDF 'info' label of per-segment AEAD subkeys (`PER_SEGMENT_KEYS`), followed by the segment index.
+const SEGMENT_KEY_INFO: &[u8] = b"RSE1|SEGMENT-KEY";
 
 /// Summary: Build HKDF 'info' from header fields to bind protocol identity.
 /// Included fields: magic, version, alg_profile, cipher, hkdf_prf, compression,
-/// strategy, aad_domain, flags, chunk_size, key_id.
+/// strategy, aad_domain, flags, chunk_size, key_id, salt.
 /// Excludes reserved/telemetry.
 #[inline]
 fn build_info_from_header(header: &HeaderV1) -> Vec<u8> {
@@ -47,16 +60,18 @@ fn build_info_from_header(header: &HeaderV1) -> Vec<u8> {
     info.extend_from_slice(&header.flags.to_le_bytes());
     info.extend_from_slice(&header.chunk_size.to_le_bytes());
     info.extend_from_slice(&header.key_id.to_le_bytes());
+    info.extend_from_slice(&header.salt);
     info
 }
 
 /// Summary: Derive a 32-byte per-stream session key via HKDF from master_key + header.salt.
-/// - PRF chosen from header.hkdf_prf (SHA-256, SHA-512, optionally keyed BLAKE3).
+/// - PRF chosen from header.hkdf_prf (HKDF with SHA-256/512, SHA3-256/512, or keyed BLAKE3).
 /// - 'info' binds protocol identity and configuration.
-/// Returns [u8;32] session key.
+///
+/// Returns the [u8;32] session key, wiped when dropped.
 ///
 /// Errors:
-/// - Unsupported PRF selection returns CryptoError::UnsupportedPrf.
+/// - Unknown PRF id returns CryptoError::UnsupportedPrf.
 ///
 /// Security notes:
 /// - Never use master_key directly for AEAD; always derive.
@@ -65,53 +80,131 @@ fn build_info_from_header(header: &HeaderV1) -> Vec<u8> {
 pub fn derive_session_key_32(
     master_key: &[u8],
     header: &HeaderV1,
-) -> Result<[u8; KEY_LEN_32], CryptoError> {
+) -> Result<Zeroizing<[u8; KEY_LEN_32]>, CryptoError> {
+    let prf = HkdfPrf::try_from(header.hkdf_prf)
+        .map_err(|_| CryptoError::UnsupportedPrf { prf_id: header.hkdf_prf })?;
+    derive_session_key_32_with(prf, master_key, header)
+}
+
+/// Summary: Derive the session key with an explicitly chosen PRF.
+///
+/// `prf` must be the one recorded in `header.hkdf_prf` (the id is part of
+/// 'info', so any other choice could never match the decrypting side).
+///
+/// Errors:
+/// - `prf` differs from `header.hkdf_prf`: CryptoError::PrfMismatch.
+pub fn derive_session_key_32_with(
+    prf: HkdfPrf,
+    master_key: &[u8],
+    header: &HeaderV1,
+) -> Result<Zeroizing<[u8; KEY_LEN_32]>, CryptoError> {
+    if prf as u16 != header.hkdf_prf {
+        return Err(CryptoError::PrfMismatch { requested: prf as u16, header: header.hkdf_prf });
+    }
     if header.salt.iter().all(|&b| b == 0) {
         return Err(CryptoError::Failure("salt must not be all-zero".into()));
     }
 
     let info = build_info_from_header(header);
+    let mut key = Zeroizing::new([0u8; KEY_LEN_32]);
 
-    match header.hkdf_prf {
-        x if x == prf_ids::SHA256 => {
-            let hk = Hkdf::<Sha256>::new(Some(&header.salt), master_key);
-            let mut key = [0u8; KEY_LEN_32];
-            hk.expand(&info, &mut key)
+    match prf {
+        HkdfPrf::Sha256 => {
+            Hkdf::<Sha256>::new(Some(&header.salt), master_key)
+                .expand(&info, &mut key[..])
                 .map_err(|_| CryptoError::Failure("HKDF expand failed (SHA-256)".into()))?;
-            Ok(key)
         }
 
-        x if x == prf_ids::SHA512 => {
-            let hk = Hkdf::<Sha512>::new(Some(&header.salt), master_key);
-            let mut key = [0u8; KEY_LEN_32];
-            hk.expand(&info, &mut key)
+        HkdfPrf::Sha512 => {
+            Hkdf::<Sha512>::new(Some(&header.salt), master_key)
+                .expand(&info, &mut key[..])
                 .map_err(|_| CryptoError::Failure("HKDF expand failed (SHA-512)".into()))?;
-            Ok(key)
         }
 
-        x if x == prf_ids::SHA3_256 => {
-            let hk = Hkdf::<Sha3_256>::new(Some(&header.salt), master_key);
-            let mut key = [0u8; KEY_LEN_32];
-            hk.expand(&info, &mut key)
+        HkdfPrf::Sha3_256 => {
+            Hkdf::<Sha3_256>::new(Some(&header.salt), master_key)
+                .expand(&info, &mut key[..])
                 .map_err(|_| CryptoError::Failure("HKDF expand failed (SHA3-256)".into()))?;
-            Ok(key)
         }
 
-        x if x == prf_ids::SHA3_512 => {
-            let hk = Hkdf::<Sha3_512>::new(Some(&header.salt), master_key);
-            let mut key = [0u8; KEY_LEN_32];
-            hk.expand(&info, &mut key)
+        HkdfPrf::Sha3_512 => {
+            Hkdf::<Sha3_512>::new(Some(&header.salt), master_key)
+                .expand(&info, &mut key[..])
                 .map_err(|_| CryptoError::Failure("HKDF expand failed (SHA3-512)".into()))?;
-            Ok(key)
         }
 
-        x if x == prf_ids::BLAKE3K => {
-            let material = [master_key, &header.salt, &info].concat();
-            let key = derive_key("RSE1|HKDF|SESSION", &material);
-            Ok(key)
+        HkdfPrf::Blake3K => {
+            // Extract: PRK from the master key and salt (salt has fixed length)
+            let material = Zeroizing::new([master_key, &header.salt].concat());
+            let prk = Zeroizing::new(derive_key("RSE1|BLAKE3K|EXTRACT", &material));
+            // Expand: keyed BLAKE3 over 'info'
+            key.copy_from_slice(keyed_hash(&prk, &info).as_bytes());
         }
+    }
 
-        other => Err(CryptoError::UnsupportedPrf { prf_id: other }),
+    Ok(key)
+}
+
+/// Summary: Derive the 4-byte key check value of a session key.
+/// - KCV = first 4 bytes of HKDF-Expand(PRK = session_key, info = "RSE1|KEY-CHECK") with SHA-256.
+/// - Lets decryption reject a wrong master key before any segment is read.
+///
+/// Errors:
+/// - Session key shorter than 32 bytes returns CryptoError::InvalidKeyLen.
+///
+/// Security notes:
+/// - HKDF-Expand is a PRF keyed by the session key and the label is distinct
+///   from every other derivation, so the KCV reveals nothing about the key or
+///   about AEAD outputs under it.
+/// - 32 bits only let a guesser discard wrong keys early; any frame tag already
+///   confirms a guess, so the key is not weakened.
+pub fn derive_key_check(session_key: &[u8]) -> Result<[u8; KEY_CHECK_LEN], CryptoError> {
+    if session_key.len() != KEY_LEN_32 {
+        return Err(CryptoError::InvalidKeyLen { expected: KEY_LEN_32, actual: session_key.len() });
     }
+    let mut okm = [0u8; KEY_CHECK_LEN];
+    Hkdf::<Sha256>::from_prk(session_key)
+        .map_err(|_| CryptoError::Failure("HKDF PRK rejected (key check)".into()))?
+        .expand(KEY_CHECK_INFO, &mut okm)
+        .map_err(|_| CryptoError::Failure("HKDF expand failed (key check)".into()))?;
+    Ok(okm)
+}
+
+/// Summary: Derive the 32-byte key of keyed segment digests from a session key.
+/// - Key = HKDF-Expand(PRK = session_key, info = "RSE1|SEGMENT-DIGEST") with SHA-256.
+/// - Used by `DigestAlg::Blake3Keyed`: only holders of the session key can
+///   compute or check such a digest.
+///
+/// Security notes:
+/// - The label is distinct from every other derivation, so the digest key is
+///   independent of the AEAD key and of the key check value.
+pub fn derive_digest_key(session_key: &[u8; KEY_LEN_32]) -> Zeroizing<[u8; KEY_LEN_32]> {
+    let mut key = Zeroizing::new([0u8; KEY_LEN_32]);
+    Hkdf::<Sha256>::from_prk(session_key)
+        .expect("a 32-byte PRK is valid for HKDF-SHA256")
+        .expand(DIGEST_KEY_INFO, &mut key[..])
+        .expect("32 bytes are within the HKDF-SHA256 output limit");
+    key
 }
 
+/// Summary: Derive the AEAD subkey of one segment from a session key.
+/// - Key = HKDF-Expand(PRK = session_key, info = "RSE1|SEGMENT-KEY" || segment_index (u32 LE)) with SHA-256.
+/// - Used under `PER_SEGMENT_KEYS`: each segment's frames are sealed under
+///   their own key, so a leaked subkey exposes one segment only.
+///
+/// Security notes:
+/// - Distinct labels and indices give independent keys; frame nonces stay
+///   unique per stream as well, so no (key, nonce) pair can repeat.
+/// - One HKDF-Expand per segment: negligible next to sealing its frames.
+pub fn derive_segment_key(session_key: &[u8; KEY_LEN_32], segment_index: u32) -> Zeroizing<[u8; KEY_LEN_32]> {
+    let mut info = [0u8; SEGMENT_KEY_INFO.len() + 4];
+    info[..SEGMENT_KEY_INFO.len()].copy_from_slice(SEGMENT_KEY_INFO);
+    info[SEGMENT_KEY_INFO.len()..].copy_from_slice(&segment_index.to_le_bytes());
+
+    let mut key = Zeroizing::new([0u8; KEY_LEN_32]);
+    Hkdf::<Sha256>::from_prk(session_key)
+        .expect("a 32-byte PRK is valid for HKDF-SHA256")
+        .expand(&info, &mut key[..])
+        .expect("32 bytes are within the HKDF-SHA256 output limit");
+    key
+}
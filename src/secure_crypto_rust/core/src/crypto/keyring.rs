@@ -0,0 +1,103 @@
+// ## 📂 File: `src/crypto/keyring.rs`
+
+//! crypto/keyring.rs
+//! Master key registry keyed by `header.key_id`.
+//!
+//! Design notes:
+//! - Decrypt selects the master key from the parsed stream header, enabling key rotation.
+//! - Key lengths are validated on insert, so selection never hands out an unusable key.
+//! - `Debug` lists key ids only; key bytes never reach logs and are wiped on drop.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use zeroize::Zeroizing;
+
+use crate::constants::MASTER_KEY_LENGTHS;
+use crate::crypto::CryptoError;
+
+/// Map from `u32` key id to master key bytes, with an optional default key.
+#[derive(Clone, Default)]
+pub struct KeyRing {
+    keys: HashMap<u32, Zeroizing<Vec<u8>>>,
+    default: Option<Zeroizing<Vec<u8>>>,
+}
+
+impl KeyRing {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register (or replace) the master key for `key_id`.
+    ///
+    /// # Errors
+    /// - `CryptoError::InvalidKeyLen` if the key is not an accepted master key length.
+    pub fn insert(&mut self, key_id: u32, master_key: &[u8]) -> Result<(), CryptoError> {
+        validate_master_key(master_key)?;
+        self.keys.insert(key_id, Zeroizing::new(master_key.to_vec()));
+        Ok(())
+    }
+
+    /// Builder form of [`KeyRing::insert`].
+    pub fn with_key(mut self, key_id: u32, master_key: &[u8]) -> Result<Self, CryptoError> {
+        self.insert(key_id, master_key)?;
+        Ok(self)
+    }
+
+    /// Key used for streams whose `key_id` has no registered key.
+    ///
+    /// # Errors
+    /// - `CryptoError::InvalidKeyLen` if the key is not an accepted master key length.
+    pub fn set_default(&mut self, master_key: &[u8]) -> Result<(), CryptoError> {
+        validate_master_key(master_key)?;
+        self.default = Some(Zeroizing::new(master_key.to_vec()));
+        Ok(())
+    }
+
+    /// Master key for `key_id`, falling back to the default key.
+    pub fn select(&self, key_id: u32) -> Option<&[u8]> {
+        self.keys
+            .get(&key_id)
+            .or(self.default.as_ref())
+            .map(|k| k.as_slice())
+    }
+
+    pub fn contains(&self, key_id: u32) -> bool {
+        self.keys.contains_key(&key_id)
+    }
+
+    pub fn remove(&mut self, key_id: u32) -> bool {
+        self.keys.remove(&key_id).is_some()
+    }
+
+    /// Registered key ids (unordered).
+    pub fn key_ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.keys.keys().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+impl fmt::Debug for KeyRing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut ids: Vec<u32> = self.key_ids().collect();
+        ids.sort_unstable();
+        f.debug_struct("KeyRing")
+            .field("key_ids", &ids)
+            .field("has_default", &self.default.is_some())
+            .finish()
+    }
+}
+
+fn validate_master_key(master_key: &[u8]) -> Result<(), CryptoError> {
+    if !MASTER_KEY_LENGTHS.contains(&master_key.len()) {
+        return Err(CryptoError::InvalidKeyLen { expected: 32, actual: master_key.len() });
+    }
+    Ok(())
+}
@@ -5,10 +5,12 @@ pub mod aead;
 pub mod kdf;
 pub mod nonce;
 pub mod digest;
+pub mod keyring;
 
 pub use types::*;
 pub use aad::*;
 pub use aead::*;
 pub use kdf::*;
 pub use nonce::*;
-pub use digest::*;
\ No newline at end of file
+pub use digest::*;
+pub use keyring::*;
\ No newline at end of file
@@ -5,7 +5,8 @@
 //!
 //! Design:
 //! - TLS-like scheme: take a 12-byte base IV from the stream salt, then XOR the
-//!   low 8 bytes with the little-endian frame_index.
+//!   low 8 bytes with the little-endian frame counter
+//!   (`segment_index << 32 | frame_index`, see `frame_nonce_counter`).
 //!
 //! Why:
 //! - Deterministic, stateless derivation enables parallel workers to compute nonces
@@ -15,9 +16,60 @@
 //! Security notes:
 //! - Never reuse the same (salt, frame_index) pair. The salt must be random per stream.
 //! - Do not use all-zero salts. Validate before deriving.
+//! - Segment and frame indices are u32 halves of the counter: encryption stops
+//!   at `MAX_SEGMENTS_PER_STREAM` / `MAX_FRAMES_PER_SEGMENT`, far below wraparound.
+
+use rand::{RngCore, rngs::OsRng};
 
 use crate::crypto::types::{NONCE_LEN_12};
-use crate::crypto::types::{NonceError};
+use crate::crypto::types::{CryptoError, NonceError};
+
+/// Most segments one stream may hold (data, trailer and final segments).
+///
+/// Half the u32 segment index space: the remainder is headroom, never used.
+pub const MAX_SEGMENTS_PER_STREAM: u32 = 1 << 31;
+
+/// Most frames one segment may hold, digest and terminator included.
+///
+/// Half the u32 frame index space. Real segments stay far below it
+/// (`MAX_CHUNK_SIZE` / smallest frame size = 8192 frames).
+pub const MAX_FRAMES_PER_SEGMENT: u32 = 1 << 31;
+
+/// Summary: Nonce counter of a frame: `segment_index` in the high 32 bits,
+/// `frame_index` in the low 32 bits.
+///
+/// Frame indices restart in every segment; the segment half keeps the
+/// counters, and so the nonces, of different segments apart.
+#[inline]
+pub fn frame_nonce_counter(segment_index: u32, frame_index: u32) -> u64 {
+    (u64::from(segment_index) << 32) | u64::from(frame_index)
+}
+
+/// Summary: Fail if a stream would need segment `segment_index`.
+#[inline]
+pub fn check_segment_budget(segment_index: u32) -> Result<(), CryptoError> {
+    if segment_index >= MAX_SEGMENTS_PER_STREAM {
+        return Err(CryptoError::NonceSpaceExhausted {
+            counter: "segment",
+            requested: u64::from(segment_index) + 1,
+            limit: u64::from(MAX_SEGMENTS_PER_STREAM),
+        });
+    }
+    Ok(())
+}
+
+/// Summary: Fail if one segment would need `frame_count` frames.
+#[inline]
+pub fn check_frame_budget(frame_count: u64) -> Result<(), CryptoError> {
+    if frame_count > u64::from(MAX_FRAMES_PER_SEGMENT) {
+        return Err(CryptoError::NonceSpaceExhausted {
+            counter: "frame",
+            requested: frame_count,
+            limit: u64::from(MAX_FRAMES_PER_SEGMENT),
+        });
+    }
+    Ok(())
+}
 
 /// Derive a 12-byte AEAD nonce in a TLS-style pattern from a 16-byte salt and frame index.
 ///
@@ -98,6 +150,23 @@ pub fn validate_salt(salt: &[u8; 16]) -> Result<(), NonceError> {
     Ok(())
 }
 
+/// Summary: Fresh 16-byte stream salt from the OS RNG.
+#[inline]
+pub fn generate_salt() -> [u8; 16] {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    salt
+}
+
+/// Summary: True if every byte of `salt` is the same (`[0u8; 16]`, `[1u8; 16]`, ...).
+///
+/// Such salts are placeholders, not random values: two streams built from
+/// the same placeholder under one key reuse every nonce.
+#[inline]
+pub fn is_degenerate_salt(salt: &[u8; 16]) -> bool {
+    salt.iter().all(|&b| b == salt[0])
+}
+
 /// Summary: Validate requested nonce length.
 /// Currently only 12-byte nonces are supported.
 ///
@@ -24,6 +24,9 @@ pub struct AadHeader {
     pub frame_index: u32,
     /// Plaintext length in this frame (DATA only; last frame may be < chunk_size).
     pub plaintext_len: u32,
+    /// Raw `SegmentFlags` of the segment carrying the frame: they decide how
+    /// the decrypted bytes are read (compressed or not, trailer, index).
+    pub segment_flags: u16,
 }
 
 impl AadHeader {
@@ -31,10 +34,37 @@ impl AadHeader {
         + 4                  // segment_index
         + 4                  // frame_index 
         + 4;                 // plaintext_len
+
+    /// Segment flags, appended after the frame fields from v3 on.
+    pub const SEGMENT_FLAGS_LEN: usize = 2;
         
-    pub const LEN_V1: usize = AadHeader::FRAME_LEN  // FRAME_LEN
-        + HeaderV1::LEN;                  // HeaderV1 len
+    /// Stream fields bound into every frame's AAD.
+    pub const STREAM_LEN: usize = 1 // aad_version
+        + 2                  // aad_domain
+        + 2                  // compression
+        + 4                  // dict_id
+        + 4;                 // chunk_size
+
+    pub const LEN_V2: usize = AadHeader::STREAM_LEN + AadHeader::FRAME_LEN;
+
+    pub const LEN_V3: usize = AadHeader::LEN_V2 + AadHeader::SEGMENT_FLAGS_LEN;
+
+    /// With `AAD_STRICT`: the full canonical header encoding is bound too.
+    pub const LEN_V2_STRICT: usize = AadHeader::LEN_V2 + HeaderV1::LEN;
+
+    pub const LEN_V3_STRICT: usize = AadHeader::LEN_V3 + HeaderV1::LEN;
 }
+
+/// Version byte leading every frame AAD.
+///
+/// v1 (no version byte) authenticated the encoded header followed by the
+/// frame fields; v2 binds the codec-related stream fields explicitly and the
+/// full header only under `AAD_STRICT`. Frames of v1 streams fail to open.
+pub const AAD_V2: u8 = 0x02;
+
+/// v2 plus the flags of the segment header. Announced by the stream header
+/// flag `SEGMENT_FLAGS_AAD` (see `crypto::aad_version`).
+pub const AAD_V3: u8 = 0x03;
 #[derive(Debug)]
 pub enum AadError {
     /// Unknown or unsupported AAD domain.
@@ -98,6 +128,9 @@ pub enum CryptoError {
     /// Unsupported HKDF PRF selection from header.
     UnsupportedPrf { prf_id: u16 },
 
+    /// Explicitly requested PRF differs from `header.hkdf_prf`.
+    PrfMismatch { requested: u16, header: u16 },
+
     /// Invalid key length provided to cipher.
     InvalidKeyLen { expected: usize, actual: usize },
 
@@ -107,6 +140,12 @@ pub enum CryptoError {
     /// AEAD tag mismatch (authentication failure).
     TagMismatch,
 
+    /// The header's key check value does not match the derived session key (wrong master key).
+    KeyConfirmationFailed,
+
+    /// A segment or frame counter reached its limit in `crypto::nonce`.
+    NonceSpaceExhausted { counter: &'static str, requested: u64, limit: u64 },
+
     /// General derivation or runtime error with context.
     Failure(String),
     /// Formatted runtime error with context.
@@ -123,12 +162,20 @@ impl fmt::Display for CryptoError {
             UnsupportedPrf { prf_id } =>
                 write!(f, "unsupported HKDF PRF: {}",
                        enum_name_or_hex::<HkdfPrf>(*prf_id)),
+            PrfMismatch { requested, header } =>
+                write!(f, "HKDF PRF mismatch: requested {}, header declares {}",
+                       enum_name_or_hex::<HkdfPrf>(*requested),
+                       enum_name_or_hex::<HkdfPrf>(*header)),
             InvalidKeyLen { expected, actual } =>
                 write!(f, "invalid key length: expected={}, actual={}", expected, actual),
             InvalidNonceLen { expected, actual } =>
                 write!(f, "invalid nonce length: expected={}, actual={}", expected, actual),
             TagMismatch =>
                 write!(f, "AEAD tag mismatch"),
+            KeyConfirmationFailed =>
+                write!(f, "key confirmation failed: wrong master key"),
+            NonceSpaceExhausted { counter, requested, limit } =>
+                write!(f, "nonce space exhausted: {} {} requested, limit is {}", requested, counter, limit),
             Failure(msg) =>
                 write!(f, "crypto failure: {}", msg),
             Format(msg) =>
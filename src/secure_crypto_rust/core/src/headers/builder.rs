@@ -0,0 +1,181 @@
+// ## 📂 File: `src/headers/builder.rs`
+//! src/headers/builder.rs
+//!
+//! Fluent construction of `HeaderV1`.
+//!
+//! Design notes:
+//! - `cipher` and `hkdf_prf` follow from the algorithm profile; explicit values
+//!   must agree with it.
+//! - A fresh random salt is generated unless one is supplied.
+//! - `chunk_size` is rounded through `best_chunk_size`, after rejecting zero
+//!   and sizes above `MAX_CHUNK_SIZE`.
+//! - `build` runs `HeaderV1::validate`, so a built header always encodes.
+
+use crate::compression::CompressionCodec;
+use crate::constants::{ChunkPolicy, DEFAULT_CHUNK_SIZE, MAX_CHUNK_SIZE, RoundingBase};
+use crate::crypto::generate_salt;
+use crate::headers::types::{AadDomain, AlgProfile, CipherSuite, HeaderError, HeaderV1, HkdfPrf, Strategy};
+use crate::utils::best_chunk_size;
+
+/// Builder for [`HeaderV1`]; see [`HeaderV1::builder`].
+///
+/// Unset fields take the values of `HeaderV1::default()`.
+#[derive(Debug, Clone, Default)]
+pub struct HeaderBuilder {
+    alg_profile: Option<AlgProfile>,
+    cipher: Option<CipherSuite>,
+    hkdf_prf: Option<HkdfPrf>,
+    compression: Option<CompressionCodec>,
+    strategy: Option<Strategy>,
+    aad_domain: Option<AadDomain>,
+    chunk_size: Option<u32>,
+    salt: Option<[u8; 16]>,
+    key_id: u32,
+    dict_id: Option<u32>,
+    plaintext_size: Option<u64>,
+    parallel_hint: u32,
+    flags: u16,
+}
+
+impl HeaderV1 {
+    /// Start a [`HeaderBuilder`].
+    pub fn builder() -> HeaderBuilder {
+        HeaderBuilder::default()
+    }
+}
+
+impl HeaderBuilder {
+    pub fn alg_profile(mut self, profile: AlgProfile) -> Self {
+        self.alg_profile = Some(profile);
+        self
+    }
+
+    /// Must match the profile's cipher; normally left to the profile.
+    pub fn cipher(mut self, cipher: CipherSuite) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    /// Must match the profile's PRF; normally left to the profile.
+    pub fn hkdf_prf(mut self, prf: HkdfPrf) -> Self {
+        self.hkdf_prf = Some(prf);
+        self
+    }
+
+    pub fn compression(mut self, codec: CompressionCodec) -> Self {
+        self.compression = Some(codec);
+        self
+    }
+
+    pub fn strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = Some(strategy);
+        self
+    }
+
+    pub fn aad_domain(mut self, domain: AadDomain) -> Self {
+        self.aad_domain = Some(domain);
+        self
+    }
+
+    /// Requested chunk size, rounded to a supported size by `build`.
+    pub fn chunk_size(mut self, chunk_size: u32) -> Self {
+        self.chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// Use `salt` instead of a fresh random one.
+    pub fn salt(mut self, salt: [u8; 16]) -> Self {
+        self.salt = Some(salt);
+        self
+    }
+
+    pub fn key_id(mut self, key_id: u32) -> Self {
+        self.key_id = key_id;
+        self
+    }
+
+    /// Sets `dict_id` and `DICT_USED`.
+    pub fn dict_id(mut self, dict_id: u32) -> Self {
+        self.dict_id = Some(dict_id);
+        self
+    }
+
+    /// Sets `plaintext_size` and `HAS_TOTAL_LEN`.
+    pub fn plaintext_size(mut self, size: u64) -> Self {
+        self.plaintext_size = Some(size);
+        self
+    }
+
+    pub fn parallel_hint(mut self, workers: u32) -> Self {
+        self.parallel_hint = workers;
+        self
+    }
+
+    /// Additional `constants::flags` bits, OR-ed into the header flags.
+    pub fn flags(mut self, bits: u16) -> Self {
+        self.flags |= bits;
+        self
+    }
+
+    /// Assemble and validate the header.
+    ///
+    /// # Errors
+    /// - `AlgProfileCipherMismatch` / `AlgProfilePrfMismatch` for explicit
+    ///   settings contradicting the profile
+    /// - `InvalidChunkSizeZero` / `InvalidChunkSizeTooLarge` for the requested chunk size
+    /// - anything `HeaderV1::validate` rejects (all-zero salt, `DICT_USED` without a dict id, ...)
+    pub fn build(self) -> Result<HeaderV1, HeaderError> {
+        let defaults = HeaderV1::default();
+        let profile = match self.alg_profile {
+            Some(profile) => profile,
+            None => AlgProfile::try_from(defaults.alg_profile)
+                .map_err(|_| HeaderError::UnknownAlgProfile { raw: defaults.alg_profile })?,
+        };
+
+        if let Some(cipher) = self.cipher {
+            if cipher != profile.cipher() {
+                return Err(HeaderError::AlgProfileCipherMismatch { profile: profile as u16, cipher: cipher as u16 });
+            }
+        }
+        if let Some(prf) = self.hkdf_prf {
+            if prf != profile.hkdf_prf() {
+                return Err(HeaderError::AlgProfilePrfMismatch { profile: profile as u16, prf: prf as u16 });
+            }
+        }
+
+        let chunk_size = match self.chunk_size {
+            None => DEFAULT_CHUNK_SIZE as u32,
+            Some(0) => return Err(HeaderError::InvalidChunkSizeZero),
+            Some(have) if have as usize > MAX_CHUNK_SIZE => {
+                return Err(HeaderError::InvalidChunkSizeTooLarge { have, max: MAX_CHUNK_SIZE as u32 });
+            }
+            Some(requested) => {
+                best_chunk_size(Some(requested as usize), ChunkPolicy::RoundUp, RoundingBase::KiB { max_exp: 20 }) as u32
+            }
+        };
+
+        let mut header = HeaderV1 {
+            alg_profile: profile as u16,
+            cipher: profile.cipher() as u16,
+            hkdf_prf: profile.hkdf_prf() as u16,
+            compression: self.compression.map_or(defaults.compression, |codec| codec as u16),
+            strategy: self.strategy.map_or(defaults.strategy, |strategy| strategy as u16),
+            aad_domain: self.aad_domain.map_or(defaults.aad_domain, |domain| domain as u16),
+            flags: defaults.flags | self.flags,
+            chunk_size,
+            salt: self.salt.unwrap_or_else(generate_salt),
+            key_id: self.key_id,
+            parallel_hint: self.parallel_hint,
+            ..defaults
+        };
+        if let Some(dict_id) = self.dict_id {
+            header.set_dict_id(dict_id);
+        }
+        if let Some(size) = self.plaintext_size {
+            header.set_plaintext_size(size);
+        }
+
+        header.validate()?;
+        Ok(header)
+    }
+}
@@ -9,7 +9,38 @@
 //! - Validation is performed after decoding to reject malformed or incompatible streams.
 //! - Treat header as authoritative source for strategy and chunk sizing.
 
-use crate::headers::types::{HeaderV1, HeaderError};
+use crate::constants::{HEADER_V1, MAGIC_RSE1, SUPPORTED_HEADER_VERSIONS};
+use crate::headers::types::{HeaderV1, HeaderError, UnknownFlagPolicy, VersionedHeader};
+
+/// Bytes needed to identify a header: magic (4) + version (2).
+pub const HEADER_PREFIX_LEN: usize = 6;
+
+/// Decode a stream header of any supported version.
+///
+/// Inspects magic + version first and dispatches to the matching decoder, so a
+/// stream written by a newer encoder is rejected with
+/// `HeaderError::UnsupportedVersion` instead of being misparsed.
+pub fn decode_any(buf: &[u8]) -> Result<VersionedHeader, HeaderError> {
+    decode_any_with(buf, UnknownFlagPolicy::Reject)
+}
+
+/// [`decode_any`], treating unknown `flags` bits as `policy` says.
+pub fn decode_any_with(buf: &[u8], policy: UnknownFlagPolicy) -> Result<VersionedHeader, HeaderError> {
+    if buf.len() < HEADER_PREFIX_LEN {
+        return Err(HeaderError::BufferTooShort { have: buf.len(), need: HEADER_PREFIX_LEN });
+    }
+
+    let magic: [u8; 4] = buf[0..4].try_into().unwrap();
+    if magic != MAGIC_RSE1 {
+        return Err(HeaderError::InvalidMagic { have: magic, need: MAGIC_RSE1 });
+    }
+
+    match u16::from_le_bytes([buf[4], buf[5]]) {
+        0 => Err(HeaderError::InvalidVersion { have: 0 }),
+        HEADER_V1 => decode_header_le_with(buf, policy).map(VersionedHeader::V1),
+        have => Err(HeaderError::UnsupportedVersion { have, supported: SUPPORTED_HEADER_VERSIONS }),
+    }
+}
 
 /// Deserialize an 80‑byte little‑endian header into `HeaderV1`.
 ///
@@ -23,6 +54,12 @@ use crate::headers::types::{HeaderV1, HeaderError};
 /// - Debug assertion ensures exactly 80 bytes are consumed.
 #[inline]
 pub fn decode_header_le(buf: &[u8]) -> Result<HeaderV1, HeaderError> {
+    decode_header_le_with(buf, UnknownFlagPolicy::Reject)
+}
+
+/// [`decode_header_le`], treating unknown `flags` bits as `policy` says
+/// (see [`HeaderV1::validate_with`]).
+pub fn decode_header_le_with(buf: &[u8], policy: UnknownFlagPolicy) -> Result<HeaderV1, HeaderError> {
     // Ensure buffer has at least HEADER_LEN_V1 bytes.
     if buf.len() < HeaderV1::LEN {
         return Err(HeaderError::BufferTooShort { have: buf.len(), need: HeaderV1::LEN });
@@ -72,7 +109,7 @@ pub fn decode_header_le(buf: &[u8]) -> Result<HeaderV1, HeaderError> {
     }
 
     // Validate decoded header fields.
-    h.validate()?;
+    h.validate_with(policy)?;
 
     Ok(h)
 }
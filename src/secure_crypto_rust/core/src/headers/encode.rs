@@ -9,7 +9,7 @@
 //! - Validation is performed before encoding to fail fast on invalid headers.
 //! - Returns a fixed `[u8; HEADER_LEN_V1]` buffer on success.
 
-use crate::headers::types::{HeaderV1, HeaderError};
+use crate::headers::types::{HeaderV1, HeaderError, UnknownFlagPolicy};
 
 /// Serialize a `HeaderV1` into an 80‑byte buffer in little‑endian order.
 ///
@@ -23,8 +23,14 @@ use crate::headers::types::{HeaderV1, HeaderError};
 /// - Debug assertion ensures exactly 80 bytes are written.
 #[inline]
 pub fn encode_header_le(h: &HeaderV1) -> Result<[u8; HeaderV1::LEN], HeaderError> {
+    encode_header_le_with(h, UnknownFlagPolicy::Reject)
+}
+
+/// [`encode_header_le`], treating unknown `flags` bits as `policy` says
+/// (see [`HeaderV1::validate_with`]).
+pub fn encode_header_le_with(h: &HeaderV1, policy: UnknownFlagPolicy) -> Result<[u8; HeaderV1::LEN], HeaderError> {
     // Validate header fields before encoding.
-    h.validate()?;
+    h.validate_with(policy)?;
 
     // Fixed output buffer of 80 bytes.
     let mut out = [0u8; HeaderV1::LEN];
@@ -0,0 +1,128 @@
+// ## 📂 File: `src/headers/extensions.rs`
+//! src/headers/extensions.rs
+//!
+//! TLV extension block following the fixed header.
+//!
+//! Design notes:
+//! - `HeaderV1::ext_len` (reserved[6..8]) gives the block length; zero means no block.
+//! - Entries are `[ tag (2, LE) ][ len (2, LE) ][ value (len) ]`, back to back.
+//! - Decoders that do not care skip `ext_len` bytes; tags are never interpreted here.
+//! - Under `AAD_STRICT` the raw block is bound into every frame's AAD.
+
+use crate::constants::flags;
+use crate::headers::types::{HeaderError, HeaderV1};
+
+/// One TLV entry of the extension block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderExtension {
+    pub tag: u16,
+    pub value: Vec<u8>,
+}
+
+/// Ordered list of extension entries; duplicate tags are kept as written.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeaderExtensions {
+    entries: Vec<HeaderExtension>,
+}
+
+impl HeaderExtensions {
+    /// Per-entry overhead: tag + length.
+    pub const ENTRY_HEADER_LEN: usize = 2 + 2;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an entry.
+    pub fn push(&mut self, tag: u16, value: impl Into<Vec<u8>>) -> &mut Self {
+        self.entries.push(HeaderExtension { tag, value: value.into() });
+        self
+    }
+
+    /// Value of the first entry with `tag`.
+    pub fn get(&self, tag: u16) -> Option<&[u8]> {
+        self.entries.iter().find(|e| e.tag == tag).map(|e| e.value.as_slice())
+    }
+
+    pub fn entries(&self) -> &[HeaderExtension] {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Encoded block length.
+    pub fn encoded_len(&self) -> usize {
+        self.entries.iter().map(|e| Self::ENTRY_HEADER_LEN + e.value.len()).sum()
+    }
+
+    /// Serialize the block; fails if it does not fit `ext_len` (u16).
+    pub fn encode(&self) -> Result<Vec<u8>, HeaderError> {
+        let total = self.encoded_len();
+        if total > u16::MAX as usize {
+            return Err(HeaderError::MalformedExtensions(format!(
+                "extension block of {total} bytes exceeds {}",
+                u16::MAX
+            )));
+        }
+        let mut out = Vec::with_capacity(total);
+        for entry in &self.entries {
+            out.extend_from_slice(&entry.tag.to_le_bytes());
+            // Cannot overflow: the whole block fits a u16
+            out.extend_from_slice(&(entry.value.len() as u16).to_le_bytes());
+            out.extend_from_slice(&entry.value);
+        }
+        Ok(out)
+    }
+
+    /// Parse a block; every byte must belong to a complete entry.
+    pub fn decode(buf: &[u8]) -> Result<Self, HeaderError> {
+        let mut entries = Vec::new();
+        let mut off = 0usize;
+        while off < buf.len() {
+            if buf.len() - off < Self::ENTRY_HEADER_LEN {
+                return Err(HeaderError::MalformedExtensions(format!(
+                    "truncated entry header at offset {off}"
+                )));
+            }
+            let tag = u16::from_le_bytes([buf[off], buf[off + 1]]);
+            let len = u16::from_le_bytes([buf[off + 2], buf[off + 3]]) as usize;
+            off += Self::ENTRY_HEADER_LEN;
+            if buf.len() - off < len {
+                return Err(HeaderError::MalformedExtensions(format!(
+                    "entry 0x{tag:04x} claims {len} bytes, {} left",
+                    buf.len() - off
+                )));
+            }
+            entries.push(HeaderExtension { tag, value: buf[off..off + len].to_vec() });
+            off += len;
+        }
+        Ok(Self { entries })
+    }
+
+    /// Encode the block and record its length in `header.ext_len`.
+    pub fn attach(&self, header: &mut HeaderV1) -> Result<Vec<u8>, HeaderError> {
+        let block = self.encode()?;
+        header.set_ext_len(block.len() as u16);
+        Ok(block)
+    }
+}
+
+/// Check a raw extension block read after `header`.
+///
+/// The length must match `ext_len`; the TLV structure is only checked under
+/// `STRICT_EXTENSIONS`, so lenient decoders can skip blocks they do not parse.
+pub fn validate_extension_block(header: &HeaderV1, block: &[u8]) -> Result<(), HeaderError> {
+    if block.len() != header.ext_len() as usize {
+        return Err(HeaderError::MalformedExtensions(format!(
+            "block is {} bytes, header ext_len is {}",
+            block.len(),
+            header.ext_len()
+        )));
+    }
+    if header.flags & flags::STRICT_EXTENSIONS != 0 {
+        HeaderExtensions::decode(block)?;
+    }
+    Ok(())
+}
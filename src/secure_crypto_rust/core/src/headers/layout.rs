@@ -0,0 +1,101 @@
+// ## 📂 File: `src/headers/layout.rs`
+//! src/headers/layout.rs
+//!
+//! Programmatic description of the wire format.
+//!
+//! Design notes:
+//! - `layout()` lists every `HeaderV1` field in wire order; `segmenting::layout()`
+//!   and `framing::layout()` do the same for segment and frame headers.
+//! - Offsets and lengths are tested against the encoders, so the description
+//!   cannot drift from the bytes actually written.
+//! - `describe()` renders all three as text for other implementations (Python mirror).
+
+use std::fmt::Write as _;
+
+use crate::headers::types::HeaderV1;
+use crate::stream_v2::{framing, framing::FrameHeader, segmenting, segmenting::SegmentHeader};
+
+/// Byte order of a field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    /// Little-endian unsigned integer.
+    Little,
+    /// Raw bytes, copied as-is.
+    Bytes,
+}
+
+/// One fixed-size field of an encoded header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldSpec {
+    pub name: &'static str,
+    pub offset: usize,
+    pub len: usize,
+    pub endianness: Endianness,
+    pub semantics: &'static str,
+}
+
+/// Lay out `(name, len, endianness, semantics)` back to back from offset 0.
+pub(crate) fn contiguous(fields: &[(&'static str, usize, Endianness, &'static str)]) -> Vec<FieldSpec> {
+    let mut offset = 0;
+    fields
+        .iter()
+        .map(|&(name, len, endianness, semantics)| {
+            let spec = FieldSpec { name, offset, len, endianness, semantics };
+            offset += len;
+            spec
+        })
+        .collect()
+}
+
+/// Fields of the encoded `HeaderV1` (`HeaderV1::LEN` bytes).
+pub fn layout() -> Vec<FieldSpec> {
+    use Endianness::*;
+    contiguous(&[
+        ("magic", 4, Bytes, "\"RSE1\""),
+        ("version", 2, Little, "header version (1)"),
+        ("alg_profile", 2, Little, "algorithm profile id (cipher + PRF bundle)"),
+        ("cipher", 2, Little, "AEAD cipher suite id"),
+        ("hkdf_prf", 2, Little, "HKDF PRF id"),
+        ("compression", 2, Little, "compression codec id"),
+        ("strategy", 2, Little, "worker strategy id"),
+        ("aad_domain", 2, Little, "AAD domain id"),
+        ("flags", 2, Little, "constants::flags bitmask"),
+        ("chunk_size", 4, Little, "plaintext bytes per segment"),
+        ("plaintext_size", 8, Little, "total plaintext length if HAS_TOTAL_LEN, else 0"),
+        ("crc32", 4, Little, "CRC32 of bytes 0..32"),
+        ("dict_id", 4, Little, "compression dictionary id if DICT_USED, else 0"),
+        ("salt", 16, Bytes, "random per-stream HKDF salt and nonce base"),
+        ("key_id", 4, Little, "master key id"),
+        ("parallel_hint", 4, Little, "CPU workers used by the encryptor"),
+        ("enc_time_ns", 8, Little, "encryption wall-clock time, UNIX epoch ns; 0 if unset"),
+        ("key_check", 4, Bytes, "key check value if HAS_KEY_CHECK; otherwise zero"),
+        ("ext_flags", 2, Little, "extension flag bits (unknown bits rejected under STRICT_EXTENSIONS)"),
+        ("ext_len", 2, Little, "length of the TLV extension block after the header, 0 if none"),
+    ])
+}
+
+/// Text tables of the stream, segment and frame header layouts.
+pub fn describe() -> String {
+    let mut out = String::new();
+    for (title, len, fields) in [
+        ("HeaderV1", HeaderV1::LEN, layout()),
+        ("SegmentHeader", SegmentHeader::LEN, segmenting::layout()),
+        ("FrameHeader", FrameHeader::LEN, framing::layout()),
+    ] {
+        let _ = writeln!(out, "{title} ({len} bytes)");
+        let _ = writeln!(out, "{:>6} {:>4}  {:<6}  {:<16} semantics", "offset", "len", "endian", "field");
+        for field in fields {
+            let endian = match field.endianness {
+                Endianness::Little => "LE",
+                Endianness::Bytes => "bytes",
+            };
+            let _ = writeln!(
+                out,
+                "{:>6} {:>4}  {:<6}  {:<16} {}",
+                field.offset, field.len, endian, field.name, field.semantics
+            );
+        }
+        out.push('\n');
+    }
+    out
+}
@@ -19,14 +19,20 @@
 pub mod types;
 pub mod encode;
 pub mod decode;
+pub mod builder;
+pub mod layout;
+pub mod extensions;
 
 pub use types::*;
 pub use encode::*;
 pub use decode::*;
+pub use builder::*;
+pub use extensions::*;
+pub use layout::{Endianness, FieldSpec, describe, layout};
 
 // ## Implementation notes
 
 // - Endianness: Little-endian across all multi-byte integers; document this in our Python mirror when we clone the Rust project.
 // - Security: The header is authenticated indirectly via AAD in each frame; never trust header fields without AEAD verification. Flags only guide optional behavior.
-// - Extensibility: Use the reserved field for future additions (e.g., signing policy IDs, attestation markers). Bump version when semantics change.
+// - Extensibility: New optional metadata goes in the TLV extension block (`ext_len`) or an `ext_flags` bit; bump version only when the fixed layout changes.
 // - Parity: Keep these files authoritative. Bindings (Python, etc.) should import the constants and replicate binary layouts exactly.
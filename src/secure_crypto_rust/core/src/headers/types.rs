@@ -6,17 +6,22 @@
 //! Industry notes:
 //! - #[repr(C)] + fixed field sizes ensures binary stability across Rust and FFI.
 //! - Use little-endian when writing/reading multi-byte integers for cross-language parity.
-//! - Reserved bytes allow future fields without changing size; always zero them.
+//! - Reserved bytes carry the key check value and the extension fields
+//!   (`ext_flags`, `ext_len`); the TLV extension block follows the 80 bytes.
 //! - This header is 80 bytes, fixed length, designed for reproducibility and forward compatibility.
 
 use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use chrono::{DateTime, SecondsFormat, Utc};
 use num_enum::TryFromPrimitive;
 
 use crate::compression::CodecError;
 use crate::compression::CompressionCodec;
-use crate::constants::HEADER_V1;
+use crate::constants::{HEADER_V1, SUPPORTED_HEADER_VERSIONS};
 use crate::constants::{MAGIC_RSE1, DEFAULT_CHUNK_SIZE, MAX_CHUNK_SIZE};
-use crate::constants::{cipher_ids, prf_ids, flags};
+use crate::constants::{cipher_ids, prf_ids, flags, KNOWN_EXT_FLAGS};
+use crate::crypto::{KEY_CHECK_LEN, generate_salt};
+use crate::stream_v2::framing::FrameType;
 
 /// Fixed header size in bytes.
 // pub const HEADER_LEN_V1: usize = 80;
@@ -101,14 +106,43 @@ impl AlgProfile {
             _ => Err(HeaderError::UnknownAlgProfile { raw }),
         }
     }
+
+    /// AEAD cipher bundled in this profile.
+    pub fn cipher(self) -> CipherSuite {
+        match self {
+            AlgProfile::Aes256GcmHkdfSha256 | AlgProfile::Aes256GcmHkdfSha512 => CipherSuite::Aes256Gcm,
+            AlgProfile::Chacha20Poly1305HkdfSha256
+            | AlgProfile::Chacha20Poly1305HkdfSha512
+            | AlgProfile::Chacha20Poly1305HkdfBlake3K => CipherSuite::Chacha20Poly1305,
+        }
+    }
+
+    /// HKDF PRF bundled in this profile.
+    pub fn hkdf_prf(self) -> HkdfPrf {
+        match self {
+            AlgProfile::Aes256GcmHkdfSha256 | AlgProfile::Chacha20Poly1305HkdfSha256 => HkdfPrf::Sha256,
+            AlgProfile::Aes256GcmHkdfSha512 | AlgProfile::Chacha20Poly1305HkdfSha512 => HkdfPrf::Sha512,
+            AlgProfile::Chacha20Poly1305HkdfBlake3K => HkdfPrf::Blake3K,
+        }
+    }
 }
 
-/// AAD domain identifiers.
+/// AAD domain identifiers: the context a stream was sealed for.
+///
+/// The domain is bound into every frame's AAD, so relabelling a stream means
+/// re-encrypting it. Select it with `EncryptParams::with_aad_domain` (or
+/// `HeaderBuilder::aad_domain`) and pin it on decrypt with
+/// `DecryptParams::expect_aad_domain`; `AAD_STRICT` streams require the pin.
 #[repr(u16)]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, TryFromPrimitive)]
 pub enum AadDomain {
+    /// No particular context (the default). Use when the same stream may be
+    /// stored or sent.
     Generic      = 0x0001,
+    /// A stream stored at rest, e.g. an encrypted file or blob.
     FileEnvelope = 0x0002,
+    /// A stream consumed as it arrives over a pipe, socket or other protocol
+    /// channel; keeps a ciphertext lifted from a file out of that channel.
     PipeEnvelope = 0x0003,
 }
 impl AadDomain {
@@ -145,8 +179,8 @@ pub struct HeaderV1 {
     pub salt: [u8; 16],        // nonce base (random per stream)
     pub key_id: u32,           // master key registry reference
     pub parallel_hint: u32,    // optional suggested worker count
-    pub enc_time_ns: u64,      // optional monotonic encoding timestamp
-    pub reserved: [u8; 8],     // future fields; must be zero
+    pub enc_time_ns: u64,      // optional wall-clock encryption time (UNIX epoch ns); 0 if unset
+    pub reserved: [u8; 8],     // [0..4] key check value if HAS_KEY_CHECK, else zero; [4..6] ext_flags; [6..8] ext_len
 }
 
 impl Default for HeaderV1 {
@@ -157,6 +191,9 @@ impl Default for HeaderV1 {
     /// - Default compression: Auto
     /// - Default strategy: Sequential
     /// - Default chunk size: 64 KiB
+    /// - Fresh random salt (OS RNG)
+    /// - Segment header CRCs on (`SEGMENT_HEADER_CRC`)
+    /// - Segment flags bound into the frame AAD (`SEGMENT_FLAGS_AAD`)
     /// - Optional fields zeroed
     fn default() -> Self {
         Self {
@@ -168,12 +205,12 @@ impl Default for HeaderV1 {
             compression: CompressionCodec::Auto as u16,
             strategy: Strategy::Sequential as u16,
             aad_domain: AadDomain::Generic as u16,
-            flags: 0,
+            flags: flags::SEGMENT_HEADER_CRC | flags::SEGMENT_FLAGS_AAD,
             chunk_size: DEFAULT_CHUNK_SIZE as u32,        // 64 KiB default
             plaintext_size: 0,
             crc32: 0,
             dict_id: 0,
-            salt: [1u8; 16],
+            salt: generate_salt(),
             key_id: 0,
             parallel_hint: 0,
             enc_time_ns: 0,
@@ -212,7 +249,7 @@ impl HeaderV1 {
             plaintext_size: 0,
             crc32: 0,
             dict_id: 0,
-            salt: [0xA5; 16],
+            salt: *b"RSE1-test-salt-1",
             key_id: 1,
             parallel_hint: 0,
             enc_time_ns: 0,
@@ -220,7 +257,13 @@ impl HeaderV1 {
         }
     }
 
+    /// Check every field; unknown `flags` bits are rejected.
     pub fn validate(&self) -> Result<(), HeaderError> {
+        self.validate_with(UnknownFlagPolicy::Reject)
+    }
+
+    /// [`HeaderV1::validate`], treating unknown `flags` bits as `policy` says.
+    pub fn validate_with(&self, policy: UnknownFlagPolicy) -> Result<(), HeaderError> {
         // Magic
         if self.magic != MAGIC_RSE1 {
             return Err(HeaderError::InvalidMagic {
@@ -229,10 +272,16 @@ impl HeaderV1 {
             });
         }
 
-        // Version
+        // Version: exactly the layouts this struct implements
         if self.version == 0 {
             return Err(HeaderError::InvalidVersion { have: self.version });
         }
+        if self.version != HEADER_V1 {
+            return Err(HeaderError::UnsupportedVersion {
+                have: self.version,
+                supported: SUPPORTED_HEADER_VERSIONS,
+            });
+        }
 
         // Chunk size
         if self.chunk_size == 0 {
@@ -255,18 +304,40 @@ impl HeaderV1 {
         // Compression codec
         CompressionCodec::verify(self.compression)?;
 
+        // Profile ↔ cipher consistency: frame workers select the AEAD from
+        // `cipher`, so the profile must not advertise a different one.
+        if let Ok(profile) = AlgProfile::try_from(self.alg_profile) {
+            if profile.cipher() as u16 != self.cipher {
+                return Err(HeaderError::AlgProfileCipherMismatch {
+                    profile: self.alg_profile,
+                    cipher: self.cipher,
+                });
+            }
+        }
+
         // Salt must not be all zero
         if self.salt.iter().all(|&b| b == 0) {
             return Err(HeaderError::InvalidSalt { salt: self.salt });
         }
 
-        // Reserved bytes must be zero
-        if self.reserved.iter().any(|&b| b != 0) {
+        // Key check slot must be zero unless flagged; bytes 4..8 are the extension fields
+        if self.flags & flags::HAS_KEY_CHECK == 0 && self.reserved[..KEY_CHECK_LEN].iter().any(|&b| b != 0) {
             return Err(HeaderError::ReservedBytesNonZero {
                 reserved: self.reserved,
             });
         }
 
+        // Unknown flags: a newer writer's feature this build would silently ignore
+        if policy == UnknownFlagPolicy::Reject {
+            flags::validate(self.flags)?;
+        }
+
+        // Unknown extension flags are tolerated unless the writer asked for strictness
+        let unknown = self.ext_flags() & !KNOWN_EXT_FLAGS;
+        if self.flags & flags::STRICT_EXTENSIONS != 0 && unknown != 0 {
+            return Err(HeaderError::UnknownExtensionFlags { bits: unknown });
+        }
+
         // Dict flag consistency
         if (self.flags & flags::DICT_USED) != 0 && self.dict_id == 0 {
             return Err(HeaderError::DictUsedButMissingId);
@@ -275,6 +346,12 @@ impl HeaderV1 {
         Ok(())
     }
 
+    /// Initialize a header with mandatory fields and a fresh random salt.
+    /// Same as `HeaderV1::default()`.
+    pub fn new_random() -> Self {
+        Self::default()
+    }
+
     /// Initialize a header with mandatory fields and caller-provided random salt.
     /// - master_key linkage via key_id is set by caller.
     /// - Optional fields (plaintext_size, crc32) are left zero unless flags are set.
@@ -285,17 +362,39 @@ impl HeaderV1 {
     }
 
     /// Marks plaintext_size as present, sets value and flag.
+    ///
+    /// The header field is informational; decryption checks the length
+    /// recorded in the authenticated plaintext trailer.
     pub fn set_plaintext_size(&mut self, size: u64) {
         self.plaintext_size = size;
         self.flags |= flags::HAS_TOTAL_LEN;
     }
 
     /// Marks crc32 as present, sets value and flag.
+    ///
+    /// Note: on the wire, bytes 32..36 carry the checksum of the header itself
+    /// (`encode_header_le` overwrites this value). The plaintext CRC32 travels
+    /// in the authenticated plaintext trailer, see [`HeaderV1::enable_crc32`].
+    #[deprecated(note = "the value is overwritten by the header checksum; use `enable_crc32`")]
     pub fn set_crc32(&mut self, crc32: u32) {
         self.crc32 = crc32;
         self.flags |= flags::HAS_CRC32;
     }
 
+    /// Record a CRC32 of the whole plaintext in the plaintext trailer, verified on decrypt.
+    pub fn enable_crc32(&mut self) {
+        self.flags |= flags::HAS_CRC32;
+    }
+
+    /// Record the total plaintext length in the plaintext trailer, verified on decrypt.
+    ///
+    /// Use [`HeaderV1::set_plaintext_size`] when the size is known up front.
+    /// Encrypting to a seekable sink fills in `plaintext_size` afterwards,
+    /// unless `AAD_STRICT` binds the header as written.
+    pub fn enable_total_len(&mut self) {
+        self.flags |= flags::HAS_TOTAL_LEN;
+    }
+
     /// Marks dict_id as used.
     pub fn set_dict_id(&mut self, dict_id: u32) {
         self.dict_id = dict_id;
@@ -303,19 +402,271 @@ impl HeaderV1 {
     }
 
     /// Enables authenticated terminator frame expectation.
+    ///
+    /// Only read under `EXPLICIT_FRAMES`; see [`HeaderV1::set_frame_layout`].
     pub fn enable_terminator(&mut self) {
         self.flags |= flags::HAS_TERMINATOR;
     }
 
     /// Enables authenticated final digest frame expectation.
+    ///
+    /// Only read under `EXPLICIT_FRAMES`; see [`HeaderV1::set_frame_layout`].
     pub fn enable_final_digest(&mut self) {
         self.flags |= flags::HAS_FINAL_DIGEST;
     }
 
-    /// Strict AAD domain enforcement (decoder must match).
+    /// Closing frames of every segment, as the flags declare them.
+    pub fn frame_layout(&self) -> FrameLayout {
+        if self.flags & flags::EXPLICIT_FRAMES == 0 {
+            return FrameLayout::LEGACY;
+        }
+        FrameLayout {
+            segment_digest: self.flags & flags::HAS_FINAL_DIGEST == 0,
+            terminator: self.flags & flags::HAS_TERMINATOR != 0,
+        }
+    }
+
+    /// Set `EXPLICIT_FRAMES` and the `HAS_TERMINATOR` / `HAS_FINAL_DIGEST`
+    /// bits for `layout`. Set before deriving the session key: flags are
+    /// part of the HKDF 'info'.
+    pub fn set_frame_layout(&mut self, layout: FrameLayout) {
+        self.flags |= flags::EXPLICIT_FRAMES;
+        self.flags &= !(flags::HAS_TERMINATOR | flags::HAS_FINAL_DIGEST);
+        if layout.terminator {
+            self.flags |= flags::HAS_TERMINATOR;
+        }
+        if layout.final_digest() {
+            self.flags |= flags::HAS_FINAL_DIGEST;
+        }
+    }
+
+    /// Strict AAD: bind the full encoded header into every frame's AAD,
+    /// so any header edit (even hints like `parallel_hint`) fails decryption.
     pub fn enable_aad_strict(&mut self) {
         self.flags |= flags::AAD_STRICT;
     }
+
+    /// Append a segment index trailer after the final segment.
+    pub fn enable_segment_index(&mut self) {
+        self.flags |= flags::HAS_SEGMENT_INDEX;
+    }
+
+    /// Announce a key check value. Set before deriving the session key:
+    /// the flag is part of the HKDF 'info'.
+    pub fn enable_key_check(&mut self) {
+        self.flags |= flags::HAS_KEY_CHECK;
+    }
+
+    /// Seal each segment's frames under its own subkey (`crypto::derive_segment_key`).
+    /// Set before deriving the session key: the flag is part of the HKDF 'info'.
+    pub fn enable_per_segment_keys(&mut self) {
+        self.flags |= flags::PER_SEGMENT_KEYS;
+    }
+
+    pub fn per_segment_keys(&self) -> bool {
+        self.flags & flags::PER_SEGMENT_KEYS != 0
+    }
+
+    /// Let segments select their own dictionary (`SegmentFlags::DICT_OVERRIDE`).
+    pub fn enable_segment_dicts(&mut self) {
+        self.flags |= flags::SEGMENT_DICTS;
+    }
+
+    pub fn segment_dicts(&self) -> bool {
+        self.flags & flags::SEGMENT_DICTS != 0
+    }
+
+    /// Bind the segment flags into every frame's AAD (`crypto::AAD_V3`).
+    /// Set before deriving the session key: the flag is part of the HKDF 'info'.
+    pub fn enable_segment_flags_aad(&mut self) {
+        self.flags |= flags::SEGMENT_FLAGS_AAD;
+    }
+
+    /// Stores the key check value (`crypto::derive_key_check`) and sets `HAS_KEY_CHECK`.
+    pub fn set_key_check(&mut self, kcv: [u8; KEY_CHECK_LEN]) {
+        self.reserved[..KEY_CHECK_LEN].copy_from_slice(&kcv);
+        self.flags |= flags::HAS_KEY_CHECK;
+    }
+
+    /// Record the current wall-clock time in `enc_time_ns`.
+    ///
+    /// UNIX epoch nanoseconds rather than a monotonic clock: the value is read
+    /// on other machines. A clock before 1970 leaves the field unset (zero).
+    pub fn stamp_enc_time(&mut self) {
+        self.enc_time_ns = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| u64::try_from(d.as_nanos()).unwrap_or(u64::MAX));
+    }
+
+    /// Encryption time from `enc_time_ns`; `None` when unset (zero).
+    pub fn created_at(&self) -> Option<SystemTime> {
+        (self.enc_time_ns != 0).then(|| UNIX_EPOCH + Duration::from_nanos(self.enc_time_ns))
+    }
+
+    /// Encryption time as RFC 3339 (UTC, nanoseconds); `None` when unset.
+    pub fn created_at_rfc3339(&self) -> Option<String> {
+        self.created_at()
+            .map(|t| DateTime::<Utc>::from(t).to_rfc3339_opts(SecondsFormat::Nanos, true))
+    }
+
+    /// The recorded encryption time lies after the local clock (skew or a bogus writer).
+    pub fn created_in_future(&self) -> bool {
+        self.created_at().is_some_and(|t| t > SystemTime::now())
+    }
+
+    /// Extension flag bits (reserved[4..6]); see `constants::KNOWN_EXT_FLAGS`.
+    pub fn ext_flags(&self) -> u16 {
+        u16::from_le_bytes([self.reserved[4], self.reserved[5]])
+    }
+
+    pub fn set_ext_flags(&mut self, bits: u16) {
+        self.reserved[4..6].copy_from_slice(&bits.to_le_bytes());
+    }
+
+    /// Length of the TLV extension block following the encoded header (reserved[6..8]).
+    pub fn ext_len(&self) -> u16 {
+        u16::from_le_bytes([self.reserved[6], self.reserved[7]])
+    }
+
+    /// Records the extension block length; written by `io::write_header_with_extensions`.
+    pub fn set_ext_len(&mut self, len: u16) {
+        self.reserved[6..8].copy_from_slice(&len.to_le_bytes());
+    }
+
+    /// Reject unknown `ext_flags` bits and malformed extension blocks.
+    pub fn enable_strict_extensions(&mut self) {
+        self.flags |= flags::STRICT_EXTENSIONS;
+    }
+
+    /// Bytes before the first segment: the fixed header plus the extension block.
+    pub fn encoded_len(&self) -> usize {
+        Self::LEN + self.ext_len() as usize
+    }
+
+    /// The key check value, if the header carries one.
+    pub fn key_check(&self) -> Option<[u8; KEY_CHECK_LEN]> {
+        (self.flags & flags::HAS_KEY_CHECK != 0).then(|| {
+            let mut kcv = [0u8; KEY_CHECK_LEN];
+            kcv.copy_from_slice(&self.reserved[..KEY_CHECK_LEN]);
+            kcv
+        })
+    }
+
+    /// Names of the `constants::flags` bits set in `flags`, in bit order.
+    ///
+    /// Unknown bits are left out; `Display` shows them as hex.
+    pub fn flags_names(&self) -> Vec<&'static str> {
+        flags::all().iter().filter(|(bit, _)| self.flags & bit != 0).map(|&(_, name)| name).collect()
+    }
+
+    /// One-line description safe for logs: enum names, sizes with units and
+    /// flag names; the salt is cut to its first two bytes. Same as `Display`.
+    pub fn summary(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// How header validation treats `flags` bits outside `flags::KNOWN_MASK`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnknownFlagPolicy {
+    /// Fail with `HeaderError::UnknownFlags` (default): the stream may rely
+    /// on a feature this build does not implement.
+    #[default]
+    Reject,
+    /// Accept them, e.g. to inspect a stream written by a newer build.
+    Tolerate,
+}
+
+/// Frames closing each non-empty segment, after its data and padding frames.
+///
+/// Without `EXPLICIT_FRAMES` every stream uses [`FrameLayout::LEGACY`], the
+/// layout written before these flags were honored. Under it:
+/// - `HAS_TERMINATOR` adds a terminator frame after the digest frame;
+/// - `HAS_FINAL_DIGEST` drops the per-segment digest frames; a BLAKE3 of the
+///   whole plaintext is authenticated in the plaintext trailer segment instead.
+///
+/// Crash recovery (`recovery::bootstrap_from_log`) marks a segment complete
+/// at its terminator, so streams without terminators resume from the start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameLayout {
+    /// Each segment carries a digest frame over its data frames.
+    pub segment_digest: bool,
+    /// Each segment ends with a terminator frame.
+    pub terminator: bool,
+}
+
+impl FrameLayout {
+    /// Digest and terminator in every segment.
+    pub const LEGACY: Self = Self { segment_digest: true, terminator: true };
+
+    /// A whole-stream digest replaces the per-segment ones (`HAS_FINAL_DIGEST`).
+    pub fn final_digest(&self) -> bool {
+        !self.segment_digest
+    }
+
+    /// Number of digest and terminator frames per non-empty segment.
+    pub fn closing_frames(&self) -> u32 {
+        u32::from(self.segment_digest) + u32::from(self.terminator)
+    }
+
+    /// Whether segments under this layout may carry a frame of `frame_type`.
+    pub fn has(&self, frame_type: FrameType) -> bool {
+        match frame_type {
+            FrameType::Data | FrameType::Padding => true,
+            FrameType::Digest => self.segment_digest,
+            FrameType::Terminator => self.terminator,
+        }
+    }
+}
+
+impl Default for FrameLayout {
+    fn default() -> Self {
+        Self::LEGACY
+    }
+}
+
+impl fmt::Display for HeaderV1 {
+    /// `RSE1 v1 profile=… cipher=… prf=… codec=… strategy=… aad=… chunk=64 KiB flags=[…] key_id=1 salt=5253…`
+    ///
+    /// Never prints the full salt: it is the nonce base of the stream.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names = self.flags_names().join(", ");
+        let unknown = self.flags & !flags::KNOWN_MASK;
+        if unknown != 0 {
+            if !names.is_empty() {
+                names.push_str(", ");
+            }
+            names.push_str(&format!("0x{:04x}", unknown));
+        }
+        write!(
+            f,
+            "{} v{} profile={} cipher={} prf={} codec={} strategy={} aad={} chunk={} flags=[{}] key_id={} salt={}…",
+            String::from_utf8_lossy(&self.magic),
+            self.version,
+            enum_name_or_hex::<AlgProfile>(self.alg_profile),
+            enum_name_or_hex::<CipherSuite>(self.cipher),
+            enum_name_or_hex::<HkdfPrf>(self.hkdf_prf),
+            enum_name_or_hex::<CompressionCodec>(self.compression),
+            enum_name_or_hex::<Strategy>(self.strategy),
+            enum_name_or_hex::<AadDomain>(self.aad_domain),
+            fmt_size(self.chunk_size),
+            names,
+            self.key_id,
+            hex::encode(&self.salt[..2]),
+        )
+    }
+}
+
+/// `64 KiB`, `1 MiB`, or plain bytes when not a whole number of KiB.
+fn fmt_size(bytes: u32) -> String {
+    const KIB: u32 = 1024;
+    const MIB: u32 = 1024 * 1024;
+    match bytes {
+        0 => "0 B".into(),
+        b if b % MIB == 0 => format!("{} MiB", b / MIB),
+        b if b % KIB == 0 => format!("{} KiB", b / KIB),
+        b => format!("{} B", b),
+    }
 }
 
 pub fn enum_name_or_hex<T>(raw: T::Primitive) -> String
@@ -337,6 +688,30 @@ pub fn fmt_bytes(b: &[u8]) -> String {
     }
 }
 
+/// A decoded stream header of any supported version (see `decode_any`).
+///
+/// New header layouts are added as variants; callers match on the versions
+/// they implement.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VersionedHeader {
+    V1(HeaderV1),
+}
+
+impl VersionedHeader {
+    pub fn version(&self) -> u16 {
+        match self {
+            VersionedHeader::V1(h) => h.version,
+        }
+    }
+
+    /// Encoded size of this header on the wire, extension block included.
+    pub fn encoded_len(&self) -> usize {
+        match self {
+            VersionedHeader::V1(h) => h.encoded_len(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum HeaderError {
     /// Buffer too short to contain a minimal header.
@@ -348,9 +723,12 @@ pub enum HeaderError {
     /// Invalid crc32 marker (expected "RSE1").
     InvalidCrc32 { have: usize, need: usize },
 
-    /// Invalid version (e.g., zero or unsupported).
+    /// Invalid version (zero is never a valid version).
     InvalidVersion { have: u16 },
 
+    /// Well-formed version this build cannot decode (e.g. a newer stream).
+    UnsupportedVersion { have: u16, supported: &'static [u16] },
+
     /// Unknown or unsupported cipher suite.
     UnknownCipherSuite { raw: u16 },
 
@@ -369,6 +747,12 @@ pub enum HeaderError {
     /// Unknown or unsupported AAD domain.
     UnknownAadDomain { raw: u16 },
 
+    /// Algorithm profile bundles a different cipher than `cipher`.
+    AlgProfileCipherMismatch { profile: u16, cipher: u16 },
+
+    /// Algorithm profile bundles a different PRF than `hkdf_prf`.
+    AlgProfilePrfMismatch { profile: u16, prf: u16 },
+
     /// Salt is invalid (e.g., all zeros).
     InvalidSalt { salt: [u8; 16] },
 
@@ -383,6 +767,15 @@ pub enum HeaderError {
 
     /// Flags indicate dictionary used but dict_id is zero.
     DictUsedButMissingId,
+
+    /// `flags` bits this build does not know (see `UnknownFlagPolicy`).
+    UnknownFlags { bits: u16 },
+
+    /// `ext_flags` bits this build does not know, under `STRICT_EXTENSIONS`.
+    UnknownExtensionFlags { bits: u16 },
+
+    /// Extension block does not match `ext_len` or is not well-formed TLV.
+    MalformedExtensions(String),
     
     /// Generic validation error with context.
     Validation(String),
@@ -401,6 +794,8 @@ impl fmt::Display for HeaderError {
 
             InvalidVersion { have } =>
                 write!(f, "invalid version: {}", have),
+            UnsupportedVersion { have, supported } =>
+                write!(f, "unsupported header version: {} (supported: {:?})", have, supported),
 
             UnknownCipherSuite { raw } =>
                 write!(f, "unknown cipher suite: {}",
@@ -420,9 +815,18 @@ impl fmt::Display for HeaderError {
             UnknownAadDomain { raw } =>
                 write!(f, "unknown AAD domain: {}",
                     enum_name_or_hex::<AadDomain>(*raw)),
-
-            InvalidSalt { salt } =>
-                write!(f, "invalid salt: all zeros ({})", fmt_bytes(salt)),
+            AlgProfileCipherMismatch { profile, cipher } =>
+                write!(f, "algorithm profile {} does not match cipher {}",
+                    enum_name_or_hex::<AlgProfile>(*profile),
+                    enum_name_or_hex::<CipherSuite>(*cipher)),
+            AlgProfilePrfMismatch { profile, prf } =>
+                write!(f, "algorithm profile {} does not match HKDF PRF {}",
+                    enum_name_or_hex::<AlgProfile>(*profile),
+                    enum_name_or_hex::<HkdfPrf>(*prf)),
+
+            // Never print salt bytes
+            InvalidSalt { .. } =>
+                write!(f, "invalid salt: all zeros"),
             InvalidChunkSizeZero =>
                 write!(f, "invalid chunk_size: zero"),
             InvalidChunkSizeTooLarge { have, max } =>
@@ -432,6 +836,12 @@ impl fmt::Display for HeaderError {
                 write!(f, "reserved bytes must be zero, got {}", fmt_bytes(reserved)),
             DictUsedButMissingId =>
                 write!(f, "DICT_USED flag set but dict_id is zero"),
+            UnknownFlags { bits } =>
+                write!(f, "unknown header flags 0x{:04x}", bits),
+            UnknownExtensionFlags { bits } =>
+                write!(f, "unknown extension flags 0x{:04x} with STRICT_EXTENSIONS set", bits),
+            MalformedExtensions(msg) =>
+                write!(f, "malformed header extensions: {}", msg),
             Validation(msg) =>
                 write!(f, "header validation error: {}", msg),
         }
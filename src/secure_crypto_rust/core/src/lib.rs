@@ -22,9 +22,54 @@ pub mod scheduler;
 // Stream layers
 pub mod stream_v2;
 
+// Conformance vectors
+#[cfg(feature = "testvectors")]
+pub mod testvectors;
+
 // -----------------------------------------------------------------------------
 // Prelude (Rust users)
 // -----------------------------------------------------------------------------
+/// Curated re-exports for typical encrypt/decrypt use.
+///
+/// ```
+/// use crypto_core::prelude::*;
+///
+/// fn main() -> Result<(), StreamError> {
+///     let master_key = [0x11u8; 32];
+///     let plaintext = b"hello, segmented stream".repeat(1024);
+///
+///     let header = HeaderV1::new_random();
+///     let params = EncryptParams::new(header).with_compression(CompressionCodec::Zstd, None);
+///
+///     let encrypted: TelemetrySnapshot = encrypt_stream_v2(
+///         InputSource::Memory(plaintext.clone()),
+///         OutputSink::Memory,
+///         &master_key,
+///         params,
+///         ApiConfig::with_buf_enabled(),
+///     )?;
+///     let ciphertext = encrypted.output.clone().expect("memory sink captures output");
+///
+///     let decrypted = decrypt_stream_v2(
+///         InputSource::Memory(ciphertext),
+///         OutputSink::Memory,
+///         &master_key,
+///         DecryptParams::new(),
+///         ApiConfig::with_buf_enabled(),
+///     )?;
+///     assert_eq!(decrypted.output.unwrap(), plaintext);
+///     Ok(())
+/// }
+/// ```
 pub mod prelude {
-
+    pub use crate::compression::CompressionCodec;
+    pub use crate::crypto::DigestAlg;
+    pub use crate::headers::{CipherSuite, HeaderV1};
+    pub use crate::stream_v2::{
+        ApiConfig, DecryptParams, DecryptingReader, EncryptParams, EncryptingWriter, InputSource, OutputSink,
+        decrypt_stream_v2, encrypt_stream_v2,
+    };
+    pub use crate::stream_v2::parallelism::HybridParallelismProfile;
+    pub use crate::telemetry::TelemetrySnapshot;
+    pub use crate::types::StreamError;
 }
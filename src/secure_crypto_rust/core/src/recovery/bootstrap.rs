@@ -1,58 +1,176 @@
 // ## 📦 `src/recovery/bootstrap.rs`
 // Purpose: Stream-based replay of unified log and restoration of hashing/decryption states.
 
+use std::collections::BTreeMap;
 use std::io;
-use crate::recovery::persist::{LogManager};
-use crate::recovery::checkpoint::{Checkpointable, SegmentCheckpoint, DecryptCheckpoint};
-use crate::recovery::resume::parse_resume_line;
+use crate::headers::HeaderV1;
+use crate::recovery::persist::{Direction, FrameRecord, UnifiedEntry, read_log_entries};
+use crate::recovery::checkpoint::{Checkpoint, Checkpointable, SegmentCheckpoint, DecryptCheckpoint};
+use crate::recovery::resume::{ResumePoint, parse_resume_line};
 use crate::crypto::digest::{DigestState};
+use crate::stream_v2::framing::FrameType;
+use crate::stream_v2::segmenting::SegmentHeader;
+use tracing::{debug, info, warn};
 
-// Internal macro for production-grade tracing in 2026
-macro_rules! trace {
-    ($($arg:tt)*) => {
-        #[cfg(debug_assertions)]
-        println!("[TRACE] {}", format_args!($($arg)*));
-    };
+/// Pipeline position reconstructed from a unified log by [`bootstrap_from_log`].
+///
+/// Frame records carry the frame wire length (header included), so a segment
+/// counts as done once its terminator frame was logged. The resume
+/// boundary is the end of the longest run of done segments starting at 0;
+/// workers finish out of order, so later segments may be done as well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecoveryState {
+    /// Highest segment index with a logged frame or scheduler position (`None` for an empty log).
+    pub last_segment_index: Option<u32>,
+    /// Highest frame index logged within `last_segment_index`.
+    pub last_frame_index: Option<u32>,
+    /// Direction of the logged frames; `None` when the log holds no frame entry.
+    pub direction: Option<Direction>,
+    /// Frame wire bytes covered by frame records.
+    pub bytes_processed: u64,
+    /// First segment not done; every segment before it is.
+    pub next_segment_index: u32,
+    /// Absolute stream offset (stream header included) of `next_segment_index`.
+    pub resume_offset: u64,
 }
 
-/// The primary entry point for system recovery.
-/// Streams the log from disk to rebuild the pipeline state without OOM risks.
-pub fn run_recovery(log_path: &str) -> io::Result<()> {
-    println!("--- RECOVERY START (2026.01) ---");
-    
-    // 2026 Best Practice: Stream log entries to handle multi-GB log files
-    let log_stream = LogManager::stream_log(log_path)?;
-
-    for entry_result in log_stream {
-        let line = entry_result?;
-        
-        // 1. Detect Scheduler Resume Points
-        if let Some((seg, frame, _)) = parse_resume_line(&line) {
-            println!("[REPLAY] Found Resume Point: Segment {}, Next Frame {}", seg, frame);
-            continue;
+impl From<RecoveryState> for ResumePoint {
+    fn from(state: RecoveryState) -> Self {
+        ResumePoint::new(state.resume_offset, state.next_segment_index)
+    }
+}
+
+/// Logged wire of one segment.
+#[derive(Debug, Default)]
+struct SegmentProgress {
+    frame_bytes: u64,
+    terminated: bool,
+}
+
+#[derive(Debug, Default)]
+struct LogReplay {
+    position: Option<(u32, u32)>,
+    direction: Option<Direction>,
+    bytes_processed: u64,
+    segments: BTreeMap<u32, SegmentProgress>,
+}
+
+impl LogReplay {
+    fn note_position(&mut self, segment_index: u32, frame_index: u32) {
+        if self.position.is_none_or(|last| (segment_index, frame_index) > last) {
+            self.position = Some((segment_index, frame_index));
         }
+    }
 
-        // 2. High-level replay of data frames (Optional: dispatch to verification engine)
-        if line.starts_with("ENCRYPT:") {
-            trace!("Replaying encryption frame entry from log");
-        } else if line.starts_with("DECRYPT:") {
-            trace!("Replaying decryption frame entry from log");
+    fn apply(&mut self, entry: UnifiedEntry) -> io::Result<()> {
+        match entry {
+            UnifiedEntry::Scheduler(msg) => {
+                // Positions are "next frame"; other scheduler lines (and torn ones) carry none
+                let line = format!("SCHEDULER: {msg}");
+                let next = Checkpoint::from_log_line(&line)
+                    .map(|cp| (cp.segment_index, cp.frame_index))
+                    .or_else(|| parse_resume_line(&line).map(|(seg, next_frame, _)| (seg, next_frame)));
+                if let Some((seg, next_frame)) = next {
+                    if let Some(frame) = next_frame.checked_sub(1) {
+                        self.note_position(seg, frame);
+                    }
+                }
+                Ok(())
+            }
+            UnifiedEntry::Frame(record) => self.apply_frame(&record),
         }
     }
-    
-    println!("--- RECOVERY COMPLETE ---");
+
+    fn apply_frame(&mut self, record: &FrameRecord) -> io::Result<()> {
+        if self.direction.is_some_and(|d| d != record.direction) {
+            return Err(invalid_log("log mixes encrypt and decrypt entries"));
+        }
+
+        self.direction = Some(record.direction);
+        self.bytes_processed += u64::from(record.wire_len);
+        self.note_position(record.segment_index, record.frame_index);
+        let segment = self.segments.entry(record.segment_index).or_default();
+        segment.frame_bytes += u64::from(record.wire_len);
+        segment.terminated |= record.frame_type == FrameType::Terminator;
+        Ok(())
+    }
+
+    fn finish(self) -> RecoveryState {
+        let mut next_segment_index = 0u32;
+        let mut resume_offset = HeaderV1::LEN as u64;
+        while let Some(segment) = self.segments.get(&next_segment_index).filter(|s| s.terminated) {
+            resume_offset += SegmentHeader::LEN as u64 + segment.frame_bytes;
+            next_segment_index += 1;
+        }
+
+        RecoveryState {
+            last_segment_index: self.position.map(|(seg, _)| seg),
+            last_frame_index: self.position.map(|(_, frame)| frame),
+            direction: self.direction,
+            bytes_processed: self.bytes_processed,
+            next_segment_index,
+            resume_offset,
+        }
+    }
+}
+
+fn invalid_log(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+/// 🧭 Reconstruct the pipeline position from a unified log after a crash
+///
+/// Streams the entries of `log_path` (text or binary, see [`read_log_entries`]).
+/// A final entry that does not parse is the torn tail of an interrupted
+/// append and is dropped; any other malformed entry is `InvalidData`. The result converts into a [`ResumePoint`] for
+/// `decrypt_stream_v2_from`.
+pub fn bootstrap_from_log(log_path: &str) -> io::Result<RecoveryState> {
+    let mut replay = LogReplay::default();
+    let mut entries = read_log_entries(log_path)?.peekable();
+
+    while let Some(entry) = entries.next() {
+        let is_last = entries.peek().is_none();
+        match entry.and_then(|entry| replay.apply(entry)) {
+            Ok(()) => {}
+            Err(e) if is_last => {
+                warn!(log_path, error = %e, "dropping torn final log entry");
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(replay.finish())
+}
+
+/// The primary entry point for system recovery.
+/// Streams the log from disk to rebuild the pipeline state without OOM risks.
+pub fn run_recovery(log_path: &str) -> io::Result<()> {
+    info!(log_path, "recovery start");
+
+    let state = bootstrap_from_log(log_path)?;
+    debug!(
+        last_segment_index = ?state.last_segment_index,
+        last_frame_index = ?state.last_frame_index,
+        bytes_processed = state.bytes_processed,
+        "log replayed"
+    );
+
+    info!(
+        log_path,
+        next_segment_index = state.next_segment_index,
+        resume_offset = state.resume_offset,
+        "recovery complete"
+    );
     Ok(())
 }
 
 /// Dispatches the actual state restoration for Encryption.
-/// Strategy: Automatically handles SHA resume or Blake3 restart via checkpoint logic.
+/// SHA-2/SHA-3 states are restored; Blake3 fails with `DigestError::NotResumable`.
 pub fn resume_encrypt_from_checkpoint(checkpoint: SegmentCheckpoint) -> Result<DigestState, crate::crypto::DigestError> {
-    // Note: Blake3 logic is handled internally by checkpoint.resume_from_checkpoint()
-    // It will return a fresh Hasher for Blake3, and a restored one for SHA.
-    println!(
-        "[BOOT] Restoring {} state for segment {}...", 
-        checkpoint.alg, 
-        checkpoint.segment_index
+    debug!(
+        alg = %checkpoint.alg,
+        segment_index = checkpoint.segment_index,
+        "restoring digest state"
     );
     
     checkpoint.resume_from_checkpoint()
@@ -60,29 +178,28 @@ pub fn resume_encrypt_from_checkpoint(checkpoint: SegmentCheckpoint) -> Result<D
 
 /// Restores the decryption primitive state (AES-CTR/ChaCha20).
 pub fn resume_decrypt_from_checkpoint(checkpoint: &DecryptCheckpoint) {
-    println!(
-        "[BOOT] Resuming decryption: Seg {}, Frame {}, Alg: {:?}",
-        checkpoint.segment_index,
-        checkpoint.frame_index,
-        checkpoint.state
+    debug!(
+        segment_index = checkpoint.segment_index,
+        frame_index = checkpoint.frame_index,
+        "resuming decryption"
     );
     // Real-world: Cipher::new_from_slices(&key, &checkpoint.state.to_bytes())
 }
 
 /// Uniform recovery handler for processing a batch of in-memory checkpoints.
 pub fn run_recovery_cycle(checkpoints: Vec<Box<dyn Checkpointable>>) {
-    println!("--- PROCESSING CHECKPOINT BATCH ---");
+    debug!(count = checkpoints.len(), "processing checkpoint batch");
 
     for cp in checkpoints {
-        println!("[RECOVERY] {}", cp.summary());
+        debug!(checkpoint = %cp.summary(), "recovering checkpoint");
 
         let any_ref = cp.as_any();
 
         if let Some(seg_cp) = any_ref.downcast_ref::<SegmentCheckpoint>() {
             // Reconstruct hashing state
             match resume_encrypt_from_checkpoint(seg_cp.clone()) {
-                Ok(_) => println!("  -> Encryption state ready: Seg {}", seg_cp.segment_index),
-                Err(e) => eprintln!("  -> ERROR: Hash state recovery failed: {:?}", e),
+                Ok(_) => debug!(segment_index = seg_cp.segment_index, "encryption state ready"),
+                Err(e) => warn!(segment_index = seg_cp.segment_index, error = ?e, "digest state recovery failed"),
             }
         } 
         else if let Some(dec_cp) = any_ref.downcast_ref::<DecryptCheckpoint>() {
@@ -5,12 +5,14 @@
 //! recovery/checkpoint.rs
 //! Defines checkpoint structures for resumable hashing and decryption.
 use std::any::Any;
+use std::io;
+use base64::{engine::general_purpose::STANDARD, Engine};
 // trait from RustCrypto digest 0.11 (Standard in 2026)
 use digest::{array::Array, crypto_common::hazmat::SerializableState}; 
 use sha2::{Sha256, Sha512};
 use sha3::{Sha3_256, Sha3_512};
-use blake3::{Hasher as Blake3Hasher};
-use crate::crypto::{DigestError, digest::{DigestAlg, DigestState}};
+use crate::crypto::{DigestError, digest::{DigestAlg, DigestState, SegmentDigestBuilder, SegmentDigestVerifier}};
+use crate::recovery::persist::{AsyncLogManager, UnifiedEntry};
 
 pub trait Checkpointable: Send + Sync {
     fn export(&self) -> Vec<u8>;
@@ -57,7 +59,7 @@ impl SegmentCheckpoint {
             DigestState::Sha3_256(h) => SerializedState::Sha3_256(h.serialize()),
             DigestState::Sha3_512(h) => SerializedState::Sha3_512(h.serialize()),
             // This effectively "drops" resume support by restarting the hash for this alg.
-            DigestState::Blake3(_)   => SerializedState::Blake3NoState, 
+            DigestState::Blake3(_) | DigestState::Blake3Keyed(_) => SerializedState::Blake3NoState, 
         };
         Self { alg, segment_index, next_frame_index, state }
     }
@@ -67,9 +69,8 @@ impl SegmentCheckpoint {
             (DigestAlg::Sha512, SerializedState::Sha512(arr)) => Sha512::deserialize(&arr).map(DigestState::Sha512).map_err(|_| DigestError::InvalidFormat),
             (DigestAlg::Sha3_256, SerializedState::Sha3_256(arr)) => Sha3_256::deserialize(&arr).map(DigestState::Sha3_256).map_err(|_| DigestError::InvalidFormat),
             (DigestAlg::Sha3_512, SerializedState::Sha3_512(arr)) => Sha3_512::deserialize(&arr).map(DigestState::Sha3_512).map_err(|_| DigestError::InvalidFormat),
-            // Blake3 Refactor: Instead of an error, we return a fresh Hasher.
-            // This effectively "drops" resume support by restarting the hash for this alg.
-            (DigestAlg::Blake3, _) => Ok(DigestState::Blake3(Blake3Hasher::new())),
+            // A fresh hasher would silently produce a different digest
+            (alg @ (DigestAlg::Blake3 | DigestAlg::Blake3Keyed), _) => Err(DigestError::NotResumable { alg }),
             _ => Err(DigestError::InvalidFormat),
         }
     }
@@ -83,6 +84,116 @@ impl Checkpointable for SegmentCheckpoint {
     fn as_any(&self) -> &dyn Any { self }
 }
 
+/// Mid-segment position of a digest worker, enough to continue after a restart.
+///
+/// Captured after frame `frame_index - 1` was fed to the digest; the worker
+/// resumes by feeding frame `frame_index`, read from `stream_offset`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub segment_index: u32,
+    /// Next DATA frame to feed to the digest.
+    pub frame_index: u32,
+    /// Frame count the digest was started with.
+    pub frame_count: u32,
+    /// Absolute stream offset where frame `frame_index` starts.
+    pub stream_offset: u64,
+    pub alg: DigestAlg,
+    /// Output of `DigestState::serialize`.
+    pub digest_state: Vec<u8>,
+}
+
+impl Checkpoint {
+    /// Log line prefix (after `SCHEDULER: `).
+    pub const LOG_TAG: &'static str = "CHECKPOINT";
+
+    /// Snapshot an encrypt-side digest builder.
+    pub fn from_builder(builder: &SegmentDigestBuilder, frame_index: u32, stream_offset: u64) -> Result<Self, DigestError> {
+        Ok(Self {
+            segment_index: builder.segment_index,
+            frame_index,
+            frame_count: builder.frame_count,
+            stream_offset,
+            alg: builder.alg,
+            digest_state: builder.state.serialize()?,
+        })
+    }
+
+    /// Snapshot a decrypt-side digest verifier.
+    pub fn from_verifier(verifier: &SegmentDigestVerifier, frame_index: u32, stream_offset: u64) -> Result<Self, DigestError> {
+        let state = verifier.state();
+        Ok(Self {
+            segment_index: verifier.segment_index(),
+            frame_index,
+            frame_count: verifier.frame_count(),
+            stream_offset,
+            alg: state.alg(),
+            digest_state: state.serialize()?,
+        })
+    }
+
+    /// Builder positioned right before frame `frame_index`.
+    pub fn to_builder(&self) -> Result<SegmentDigestBuilder, DigestError> {
+        let state = DigestState::deserialize(self.alg, &self.digest_state)?;
+        Ok(SegmentDigestBuilder::with_state(state, self.segment_index, self.frame_count, self.frame_index))
+    }
+
+    /// Verifier positioned right before frame `frame_index`.
+    pub fn to_verifier(&self, expected: Vec<u8>) -> Result<SegmentDigestVerifier, DigestError> {
+        let state = DigestState::deserialize(self.alg, &self.digest_state)?;
+        Ok(SegmentDigestVerifier::with_state(state, self.segment_index, self.frame_count, self.frame_index, expected))
+    }
+
+    /// `CHECKPOINT|SEG|FRAME|FRAME_COUNT|OFFSET|ALG_ID|STATE_BASE64|CHECKSUM`
+    ///
+    /// The checksum (first 8 hex chars of BLAKE3 over the rest) lets recovery
+    /// skip torn lines, like resume points.
+    pub fn to_log_line(&self) -> String {
+        let raw_msg = format!(
+            "{}|{}|{}|{}|{}|{}|{}",
+            Self::LOG_TAG,
+            self.segment_index,
+            self.frame_index,
+            self.frame_count,
+            self.stream_offset,
+            self.alg as u16,
+            STANDARD.encode(&self.digest_state)
+        );
+        let checksum = blake3::hash(raw_msg.as_bytes()).to_hex();
+        format!("{}|{}", raw_msg, &checksum[..8])
+    }
+
+    /// Parse a line written by [`Checkpoint::persist`], with or without the
+    /// `SCHEDULER: ` log prefix. `None` for other lines and torn/corrupt ones.
+    pub fn from_log_line(line: &str) -> Option<Self> {
+        let content = line.strip_prefix("SCHEDULER: ").unwrap_or(line).trim_end();
+        let (raw_msg, checksum) = content.rsplit_once('|')?;
+        if &blake3::hash(raw_msg.as_bytes()).to_hex()[..8] != checksum {
+            return None;
+        }
+
+        let parts: Vec<&str> = raw_msg.split('|').collect();
+        let [tag, seg, frame, count, offset, alg, state] = parts.as_slice() else {
+            return None;
+        };
+        if *tag != Self::LOG_TAG {
+            return None;
+        }
+        Some(Self {
+            segment_index: seg.parse().ok()?,
+            frame_index: frame.parse().ok()?,
+            frame_count: count.parse().ok()?,
+            stream_offset: offset.parse().ok()?,
+            alg: DigestAlg::try_from(alg.parse::<u16>().ok()?).ok()?,
+            digest_state: STANDARD.decode(state).ok()?,
+        })
+    }
+
+    /// Append this checkpoint to the audit log.
+    pub fn persist(&self, log_manager: &AsyncLogManager) -> io::Result<()> {
+        log_manager.append(UnifiedEntry::Scheduler(self.to_log_line()))
+    }
+}
+
 // DecryptState remains unchanged as it typically relies on simple counters (CTR/ChaCha)
 // which are natively resumable by just storing the counter [u8] or [u32].
 #[derive(Debug, Clone)]
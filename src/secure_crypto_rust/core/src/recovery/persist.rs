@@ -6,21 +6,109 @@
 // - **Unit tests**: validate append, rotate, replay, compaction.  
 
 //! Unified log manager for append, rotation, replay, compaction.
+use std::collections::HashSet;
 use std::fs::{self, File, OpenOptions};
-use std::io::{self, Write, BufRead, BufReader, BufWriter};
-use std::sync::mpsc::{channel, Sender};
-use std::thread;
+use std::io::{self, Write, BufRead, BufReader, BufWriter, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{SystemTime, UNIX_EPOCH};
 use base64::{engine::general_purpose::STANDARD, Engine};
+use tracing::warn;
 
-#[derive(Debug, Clone)]
+use crate::stream_v2::framing::{FrameType, decode::parse_frame_header};
+
+/// Pipeline direction of a frame record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Encrypt = 0,
+    Decrypt = 1,
+}
+
+impl Direction {
+    fn tag(self) -> &'static str {
+        match self {
+            Direction::Encrypt => "ENC",
+            Direction::Decrypt => "DEC",
+        }
+    }
+}
+
+/// How much of each frame the audit log keeps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogDetail {
+    /// Indices, length, CRC32 and time of each frame (default).
+    #[default]
+    Metadata,
+    /// Metadata plus the whole frame wire; for debugging only (log ≈ stream size).
+    FullPayload,
+}
+
+/// On-disk encoding of log entries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// One line per entry (default).
+    #[default]
+    Text,
+    /// Length-prefixed records, see [`encode_binary_entry`].
+    Binary,
+}
+
+/// Format and detail of an audit log.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LogOptions {
+    pub format: LogFormat,
+    pub detail: LogDetail,
+}
+
+/// One frame handled by a segment worker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameRecord {
+    pub direction: Direction,
+    pub segment_index: u32,
+    pub frame_index: u32,
+    pub frame_type: FrameType,
+    /// Frame header plus ciphertext.
+    pub wire_len: u32,
+    /// CRC32 of the frame wire.
+    pub wire_crc32: u32,
+    /// Nanoseconds since the Unix epoch when the record was made (0 when unknown).
+    pub timestamp_ns: u64,
+    /// Frame wire, kept with `LogDetail::FullPayload` only.
+    pub payload: Option<Vec<u8>>,
+}
+
+impl FrameRecord {
+    /// Record the frame at the start of `wire`; returns it with its wire length.
+    pub fn from_wire(direction: Direction, wire: &[u8], detail: LogDetail) -> io::Result<(Self, usize)> {
+        let header = parse_frame_header(wire).map_err(|e| invalid_log(format!("frame entry: {e}")))?;
+        let len = header.total_len().map_err(|e| invalid_log(format!("frame entry: {e}")))?;
+        let frame = wire.get(..len).ok_or_else(|| invalid_log("frame entry is truncated"))?;
+        let record = Self {
+            direction,
+            segment_index: header.segment_index,
+            frame_index: header.frame_index,
+            frame_type: header.frame_type,
+            wire_len: u32::try_from(len).map_err(|_| invalid_log("frame entry exceeds u32"))?,
+            wire_crc32: crc32fast::hash(frame),
+            timestamp_ns: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos() as u64),
+            payload: (detail == LogDetail::FullPayload).then(|| frame.to_vec()),
+        };
+        Ok((record, len))
+    }
+
+    /// Identity of the frame (direction, indices, wire); time and payload are ignored.
+    fn key(&self) -> (Direction, u32, u32, u32, u32) {
+        (self.direction, self.segment_index, self.frame_index, self.wire_len, self.wire_crc32)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum UnifiedEntry {
     Scheduler(String), // e.g. compaction marker
-    Encrypt(Vec<u8>),  // encrypted frame
-    Decrypt(Vec<u8>),  // decrypted frame
-}
-enum LogCommand {
-    Append(UnifiedEntry),
-    Rotate,
+    Frame(FrameRecord),
 }
 #[derive(Debug)]
 pub struct LogManager {
@@ -42,13 +130,8 @@ impl LogManager {
 
     /// Append a new entry.
     pub fn append(&mut self, entry: UnifiedEntry) -> io::Result<()> {
-        let line = match &entry {
-            UnifiedEntry::Scheduler(msg) => format!("SCHEDULER: {}\n", msg),
-            UnifiedEntry::Encrypt(data) => format!("ENCRYPT: {}\n", STANDARD.encode(data)),
-            UnifiedEntry::Decrypt(data) => format!("DECRYPT: {}\n", STANDARD.encode(data)),
-        };
         // Write to the already-open BufWriter
-        self.writer.write_all(line.as_bytes())?;
+        self.writer.write_all(format_entry(&entry).as_bytes())?;
         // 🔥 CRASH-CONSISTENCY:
         // It forces the OS to write the bytes to disk immediately.
         self.writer.flush()?; 
@@ -70,7 +153,7 @@ impl LogManager {
     pub fn rotate(&mut self) {
         // For simplicity, write to a file named "unified.log"
         if let Err(e) = self.persist_to_file("unified.log") {
-            eprintln!("Log rotation failed: {}", e);
+            warn!(error = %e, "log rotation failed");
         }
         self.entries.clear();
     }
@@ -84,85 +167,231 @@ impl LogManager {
     pub fn persist_to_file(&self, path: &str) -> io::Result<()> {
         let mut file = OpenOptions::new().create(true).append(true).open(path)?;
         for entry in &self.entries {
-            let line = match entry {
-                UnifiedEntry::Scheduler(msg) => format!("SCHEDULER: {}\n", msg),
-                UnifiedEntry::Encrypt(data) => format!("ENCRYPT: {} bytes\n", data.len()),
-                UnifiedEntry::Decrypt(data) => format!("DECRYPT: {} bytes\n", data.len()),
-            };
-            file.write_all(line.as_bytes())?;
+            file.write_all(format_entry(entry).as_bytes())?;
         }
         Ok(())
     }
 
 }
 
+/// Where the stream API sends its audit log.
+///
+/// The default is `Disabled`: nothing is written unless the caller asks for it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum LogConfig {
+    /// Append to this exact file.
+    Path(PathBuf),
+    /// Append to a file in this directory, named after the stream direction
+    /// (`stream_v2_enc.log` / `stream_v2_dec.log`).
+    Directory(PathBuf),
+    /// Keep entries in memory, see [`AsyncLogManager::entries`].
+    Memory,
+    /// No audit log at all.
+    #[default]
+    Disabled,
+}
+
+enum LogSink {
+    File(FileSink),
+    Memory(Arc<Mutex<Vec<UnifiedEntry>>>),
+    Disabled,
+}
+
+/// Handle on the background writer thread.
+///
+/// The thread stops at the first write, flush or rotation error and parks it
+/// in `error`; `failed` lets `append` notice without taking the lock.
+struct FileSink {
+    tx: Mutex<Option<Sender<UnifiedEntry>>>,
+    handle: Mutex<Option<JoinHandle<()>>>,
+    failed: Arc<AtomicBool>,
+    error: Arc<Mutex<Option<io::Error>>>,
+}
+
 pub struct AsyncLogManager {
-    tx: Sender<LogCommand>,
+    sink: LogSink,
+    detail: LogDetail,
 }
 
 impl AsyncLogManager {
-    /// Initialize the background logger thread.
-    pub fn new(path: &str, rotation_limit: usize) -> io::Result<Self> {
-        let (tx, rx) = channel::<LogCommand>();
-        let path_owned = path.to_string();
-        
-        // Clone sender so the background thread can trigger its own rotation
-        let tx_internal = tx.clone(); 
+    /// Build the manager described by `config`; `file_name` is used in
+    /// `LogConfig::Directory` mode.
+    pub fn from_config(config: &LogConfig, file_name: &str, rotation_limit: usize) -> io::Result<Self> {
+        Self::from_config_with(config, LogOptions::default(), file_name, rotation_limit)
+    }
 
-        thread::spawn(move || {
-            let file = OpenOptions::new().create(true).append(true).open(&path_owned)
-                .expect("Failed to open log file in background thread");
-            let mut writer = BufWriter::new(file);
-            let mut count = 0;
-
-            while let Ok(cmd) = rx.recv() {
-                match cmd {
-                    LogCommand::Append(entry) => {
-                        // FIX: Use the defined helper function below
-                        let line = format_entry(&entry);
-                        
-                        if let Err(e) = writer.write_all(line.as_bytes()) {
-                            eprintln!("Log Write Error: {}", e);
-                            continue;
-                        }
-                        let _ = writer.flush();
-
-                        count += 1;
-                        if count >= rotation_limit {
-                            // FIX: Successfully use tx_internal to trigger rotation
-                            let _ = tx_internal.send(LogCommand::Rotate);
-                            count = 0;
-                        }
-                    }
-                    LogCommand::Rotate => {
-                        let _ = writer.flush();
-                        drop(writer); // Close file handle
-
-                        let timestamp = chrono::Utc::now().format("%Y%m%dT%H%M%S");
-                        let archived_path = format!("{}.{}", path_owned, timestamp);
-                        
-                        if fs::rename(&path_owned, &archived_path).is_ok() {
-                            let archive_to_compress = archived_path.clone();
-                            // Background Zstd compression (New for 2.3.0)
-                            thread::spawn(move || {
-                                compress_log_file(&archive_to_compress);
-                            });
-                        }
-
-                        let new_file = OpenOptions::new().create(true).append(true).open(&path_owned).unwrap();
-                        writer = BufWriter::new(new_file);
+    /// [`from_config`](Self::from_config) with an explicit format and detail.
+    pub fn from_config_with(config: &LogConfig, options: LogOptions, file_name: &str, rotation_limit: usize) -> io::Result<Self> {
+        let manager = match config {
+            LogConfig::Path(path) => Self::open(path, rotation_limit, options.format)?,
+            LogConfig::Directory(dir) => {
+                fs::create_dir_all(dir)?;
+                Self::open(dir.join(file_name), rotation_limit, options.format)?
+            }
+            LogConfig::Memory => Self::memory(),
+            LogConfig::Disabled => Self::disabled(),
+        };
+        Ok(manager.with_detail(options.detail))
+    }
+
+    /// Initialize the background logger thread, writing `options.format`.
+    pub fn new(path: &str, rotation_limit: usize, options: LogOptions) -> io::Result<Self> {
+        Ok(Self::open(path, rotation_limit, options.format)?.with_detail(options.detail))
+    }
+
+    /// Log to `dir/file_name`, creating `dir` if needed.
+    pub fn in_dir(dir: impl AsRef<Path>, file_name: &str, rotation_limit: usize) -> io::Result<Self> {
+        fs::create_dir_all(dir.as_ref())?;
+        Self::with_path(dir.as_ref().join(file_name), rotation_limit)
+    }
+
+    /// In-memory backend: entries are kept (never rotated) for inspection.
+    pub fn memory() -> Self {
+        Self { sink: LogSink::Memory(Arc::new(Mutex::new(Vec::new()))), detail: LogDetail::Metadata }
+    }
+
+    /// No-op backend: `append` drops entries, no thread is spawned.
+    pub fn disabled() -> Self {
+        Self { sink: LogSink::Disabled, detail: LogDetail::Metadata }
+    }
+
+    /// Keep frame payloads (`FullPayload`) or metadata only.
+    pub fn with_detail(mut self, detail: LogDetail) -> Self {
+        self.detail = detail;
+        self
+    }
+
+    pub fn detail(&self) -> LogDetail {
+        self.detail
+    }
+
+    /// Whether appended entries go anywhere.
+    pub fn is_enabled(&self) -> bool {
+        !matches!(self.sink, LogSink::Disabled)
+    }
+
+    /// Entries recorded by the in-memory backend; `None` for other backends.
+    pub fn entries(&self) -> Option<Vec<UnifiedEntry>> {
+        match &self.sink {
+            LogSink::Memory(entries) => Some(entries.lock().map(|e| e.clone()).unwrap_or_default()),
+            _ => None,
+        }
+    }
+
+    /// Log to `path` from a background thread.
+    ///
+    /// The file is opened here so an unwritable location fails the caller
+    /// instead of the logger thread.
+    pub fn with_path(path: impl AsRef<Path>, rotation_limit: usize) -> io::Result<Self> {
+        Self::open(path, rotation_limit, LogFormat::Text)
+    }
+
+    /// Log to an arbitrary writer from a background thread (no rotation).
+    pub fn with_writer(writer: impl Write + Send + 'static) -> Self {
+        Self::spawn(Box::new(writer), None, LogFormat::Text)
+    }
+
+    fn open(path: impl AsRef<Path>, rotation_limit: usize, format: LogFormat) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self::spawn(Box::new(file), Some((path, rotation_limit)), format))
+    }
+
+    fn spawn(writer: Box<dyn Write + Send>, rotation: Option<(PathBuf, usize)>, format: LogFormat) -> Self {
+        let (tx, rx) = channel::<UnifiedEntry>();
+        let failed = Arc::new(AtomicBool::new(false));
+        let error = Arc::new(Mutex::new(None));
+
+        let handle = thread::spawn({
+            let failed = Arc::clone(&failed);
+            let error = Arc::clone(&error);
+            move || {
+                if let Err(e) = write_entries(rx, writer, rotation, format) {
+                    warn!(error = %e, "audit log stopped");
+                    if let Ok(mut slot) = error.lock() {
+                        *slot = Some(e);
                     }
+                    failed.store(true, Ordering::Release);
                 }
             }
         });
 
-        Ok(Self { tx })
+        Self {
+            sink: LogSink::File(FileSink {
+                tx: Mutex::new(Some(tx)),
+                handle: Mutex::new(Some(handle)),
+                failed,
+                error,
+            }),
+            detail: LogDetail::Metadata,
+        }
+    }
+
+    /// Append a [`FrameRecord`] for every frame in a segment `wire`.
+    ///
+    /// Does nothing (not even parsing) when the log is disabled.
+    pub fn log_frames(&self, direction: Direction, wire: &[u8]) -> io::Result<()> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+        let mut offset = 0;
+        while offset < wire.len() {
+            let (record, len) = FrameRecord::from_wire(direction, &wire[offset..], self.detail)?;
+            self.append(UnifiedEntry::Frame(record))?;
+            offset += len;
+        }
+        Ok(())
     }
 
     /// Non-blocking append. Sends entry to background thread.
-    pub fn append(&self, entry: UnifiedEntry) {
-        if let Err(e) = self.tx.send(LogCommand::Append(entry)) {
-            eprintln!("Failed to send log entry to background thread: {}", e);
+    ///
+    /// Fails once the background thread has stopped on an error (the error
+    /// itself is returned by [`shutdown`](Self::shutdown)) or after shutdown.
+    pub fn append(&self, entry: UnifiedEntry) -> io::Result<()> {
+        match &self.sink {
+            LogSink::File(sink) => {
+                if sink.failed.load(Ordering::Acquire) {
+                    return Err(io::Error::other("audit log writer failed"));
+                }
+                let tx = sink.tx.lock().map_err(|_| io::Error::other("audit log poisoned"))?;
+                match tx.as_ref() {
+                    Some(tx) => tx
+                        .send(entry)
+                        .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "audit log writer stopped")),
+                    None => Err(io::Error::new(io::ErrorKind::BrokenPipe, "audit log closed")),
+                }
+            }
+            LogSink::Memory(entries) => {
+                entries.lock().map_err(|_| io::Error::other("audit log poisoned"))?.push(entry);
+                Ok(())
+            }
+            LogSink::Disabled => Ok(()),
+        }
+    }
+
+    /// Flush and stop the background thread, returning any deferred error.
+    pub fn shutdown(self) -> io::Result<()> {
+        self.close()
+    }
+
+    /// [`shutdown`](Self::shutdown) through a shared handle.
+    ///
+    /// Later appends fail; calling it again returns `Ok(())`.
+    pub fn close(&self) -> io::Result<()> {
+        let LogSink::File(sink) = &self.sink else {
+            return Ok(());
+        };
+
+        // Dropping the only sender ends the thread's receive loop
+        drop(sink.tx.lock().map_err(|_| io::Error::other("audit log poisoned"))?.take());
+        let handle = sink.handle.lock().map_err(|_| io::Error::other("audit log poisoned"))?.take();
+        if let Some(handle) = handle {
+            handle.join().map_err(|_| io::Error::other("audit log thread panicked"))?;
+        }
+
+        match sink.error.lock().map_err(|_| io::Error::other("audit log poisoned"))?.take() {
+            Some(e) => Err(e),
+            None => Ok(()),
         }
     }
 
@@ -174,12 +403,252 @@ impl AsyncLogManager {
 
 }
 
-/// FIX: Helper function to handle string formatting for log entries
+/// 📖 Stream the entries of a text or binary log
+///
+/// The format is detected from the first byte ([`BINARY_RECORD_MARKER`] for
+/// binary logs). Text logs may also hold `ENCRYPT:` / `DECRYPT:` lines of
+/// older versions, which carried the frame wire only. A malformed entry is
+/// `InvalidData`; the caller decides whether it is a torn tail.
+pub fn read_log_entries(path: &str) -> io::Result<Box<dyn Iterator<Item = io::Result<UnifiedEntry>>>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let binary = reader.fill_buf()?.first() == Some(&BINARY_RECORD_MARKER);
+    if binary {
+        Ok(Box::new(std::iter::from_fn(move || read_binary_entry(&mut reader).transpose())))
+    } else {
+        Ok(Box::new(reader.lines().map(|line| line.and_then(|line| parse_text_entry(&line)))))
+    }
+}
+
+/// First byte of every binary record (never the first byte of a text line).
+pub const BINARY_RECORD_MARKER: u8 = 0xB1;
+
+const TAG_SCHEDULER: u8 = 0x01;
+const TAG_FRAME: u8 = 0x02;
+
+/// Fixed part of a binary frame body: direction, segment, frame, type, wire_len, crc32, timestamp.
+const FRAME_BODY_LEN: usize = 1 + 4 + 4 + 2 + 4 + 4 + 8;
+
+/// `MARKER u8 | TAG u8 | BODY_LEN u32 | BODY | CRC32(TAG..BODY) u32`, little endian.
+///
+/// A scheduler body is the UTF-8 message; a frame body is the fixed fields
+/// then the payload (empty for `LogDetail::Metadata`).
+pub fn encode_binary_entry(entry: &UnifiedEntry) -> Vec<u8> {
+    let (tag, body) = match entry {
+        UnifiedEntry::Scheduler(msg) => (TAG_SCHEDULER, msg.as_bytes().to_vec()),
+        UnifiedEntry::Frame(record) => {
+            let payload = record.payload.as_deref().unwrap_or_default();
+            let mut body = Vec::with_capacity(FRAME_BODY_LEN + payload.len());
+            body.push(record.direction as u8);
+            body.extend_from_slice(&record.segment_index.to_le_bytes());
+            body.extend_from_slice(&record.frame_index.to_le_bytes());
+            body.extend_from_slice(&(record.frame_type as u16).to_le_bytes());
+            body.extend_from_slice(&record.wire_len.to_le_bytes());
+            body.extend_from_slice(&record.wire_crc32.to_le_bytes());
+            body.extend_from_slice(&record.timestamp_ns.to_le_bytes());
+            body.extend_from_slice(payload);
+            (TAG_FRAME, body)
+        }
+    };
+    let mut out = Vec::with_capacity(2 + 4 + body.len() + 4);
+    out.push(BINARY_RECORD_MARKER);
+    out.push(tag);
+    out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    out.extend_from_slice(&body);
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(&[tag]);
+    crc.update(&body);
+    out.extend_from_slice(&crc.finalize().to_le_bytes());
+    out
+}
+
+/// Next binary record; `None` at a clean end of file.
+fn read_binary_entry(reader: &mut impl Read) -> io::Result<Option<UnifiedEntry>> {
+    let mut head = [0u8; 6];
+    match reader.read(&mut head[..1])? {
+        0 => return Ok(None),
+        _ => reader.read_exact(&mut head[1..]).map_err(|_| invalid_log("binary record is truncated"))?,
+    }
+    if head[0] != BINARY_RECORD_MARKER {
+        return Err(invalid_log("binary record marker missing"));
+    }
+    let tag = head[1];
+    let body_len = u32::from_le_bytes([head[2], head[3], head[4], head[5]]) as usize;
+    let mut body = vec![0u8; body_len];
+    let mut crc = [0u8; 4];
+    reader
+        .read_exact(&mut body)
+        .and_then(|()| reader.read_exact(&mut crc))
+        .map_err(|_| invalid_log("binary record is truncated"))?;
+
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(&[tag]);
+    hasher.update(&body);
+    if hasher.finalize() != u32::from_le_bytes(crc) {
+        return Err(invalid_log("binary record checksum mismatch"));
+    }
+
+    match tag {
+        TAG_SCHEDULER => String::from_utf8(body)
+            .map(|msg| Some(UnifiedEntry::Scheduler(msg)))
+            .map_err(|_| invalid_log("scheduler record is not UTF-8")),
+        TAG_FRAME if body.len() >= FRAME_BODY_LEN => {
+            let u32_at = |at: usize| u32::from_le_bytes(body[at..at + 4].try_into().expect("4 bytes"));
+            let direction = match body[0] {
+                0 => Direction::Encrypt,
+                1 => Direction::Decrypt,
+                _ => return Err(invalid_log("unknown frame record direction")),
+            };
+            let frame_type = FrameType::try_from(u16::from_le_bytes([body[9], body[10]]))
+                .map_err(|_| invalid_log("unknown frame record type"))?;
+            let payload = body[FRAME_BODY_LEN..].to_vec();
+            Ok(Some(UnifiedEntry::Frame(FrameRecord {
+                direction,
+                segment_index: u32_at(1),
+                frame_index: u32_at(5),
+                frame_type,
+                wire_len: u32_at(11),
+                wire_crc32: u32_at(15),
+                timestamp_ns: u64::from_le_bytes(body[19..27].try_into().expect("8 bytes")),
+                payload: (!payload.is_empty()).then_some(payload),
+            })))
+        }
+        _ => Err(invalid_log("unknown binary record")),
+    }
+}
+
+/// One text line (without its newline) back into an entry.
+fn parse_text_entry(line: &str) -> io::Result<UnifiedEntry> {
+    if let Some(msg) = line.strip_prefix("SCHEDULER: ") {
+        return Ok(UnifiedEntry::Scheduler(msg.to_string()));
+    }
+    if let Some(content) = line.strip_prefix("FRAME: ") {
+        let (raw_msg, checksum) = content.rsplit_once('|').ok_or_else(|| invalid_log("frame line has no checksum"))?;
+        if &blake3::hash(raw_msg.as_bytes()).to_hex()[..8] != checksum {
+            return Err(invalid_log("frame line checksum mismatch"));
+        }
+        let parts: Vec<&str> = raw_msg.split('|').collect();
+        let (fields, payload) = match parts.as_slice() {
+            [fields @ .., payload] if fields.len() == 7 => (fields, Some(*payload)),
+            fields if fields.len() == 7 => (fields, None),
+            _ => return Err(invalid_log("frame line has the wrong field count")),
+        };
+        let number = |s: &str| s.parse::<u64>().map_err(|_| invalid_log("frame line field is not a number"));
+        let direction = match fields[0] {
+            "ENC" => Direction::Encrypt,
+            "DEC" => Direction::Decrypt,
+            _ => return Err(invalid_log("unknown frame line direction")),
+        };
+        let frame_type = u16::try_from(number(fields[3])?)
+            .ok()
+            .and_then(|raw| FrameType::try_from(raw).ok())
+            .ok_or_else(|| invalid_log("unknown frame line type"))?;
+        let narrow = |s: &str| number(s).and_then(|n| u32::try_from(n).map_err(|_| invalid_log("frame line field exceeds u32")));
+        return Ok(UnifiedEntry::Frame(FrameRecord {
+            direction,
+            segment_index: narrow(fields[1])?,
+            frame_index: narrow(fields[2])?,
+            frame_type,
+            wire_len: narrow(fields[4])?,
+            wire_crc32: u32::from_str_radix(fields[5], 16).map_err(|_| invalid_log("frame line CRC is not hex"))?,
+            timestamp_ns: number(fields[6])?,
+            payload: payload
+                .map(|p| STANDARD.decode(p).map_err(|e| invalid_log(format!("frame line payload is not base64: {e}"))))
+                .transpose()?,
+        }));
+    }
+
+    // Older logs: the frame wire alone
+    let (direction, payload) = if let Some(payload) = line.strip_prefix("ENCRYPT: ") {
+        (Direction::Encrypt, payload)
+    } else if let Some(payload) = line.strip_prefix("DECRYPT: ") {
+        (Direction::Decrypt, payload)
+    } else {
+        return Err(invalid_log("unrecognized log line"));
+    };
+    let wire = STANDARD.decode(payload.trim_end()).map_err(|e| invalid_log(format!("frame entry is not base64: {e}")))?;
+    let (mut record, len) = FrameRecord::from_wire(direction, &wire, LogDetail::FullPayload)?;
+    if len != wire.len() {
+        return Err(invalid_log("frame entry has trailing bytes"));
+    }
+    record.timestamp_ns = 0;
+    Ok(UnifiedEntry::Frame(record))
+}
+
+fn invalid_log(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+/// Background thread body: write entries until every sender is gone.
+fn write_entries(
+    rx: Receiver<UnifiedEntry>,
+    writer: Box<dyn Write + Send>,
+    rotation: Option<(PathBuf, usize)>,
+    format: LogFormat,
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(writer);
+    let mut count = 0;
+
+    while let Ok(entry) = rx.recv() {
+        match format {
+            LogFormat::Text => writer.write_all(format_entry(&entry).as_bytes())?,
+            LogFormat::Binary => writer.write_all(&encode_binary_entry(&entry))?,
+        }
+        writer.flush()?;
+
+        count += 1;
+        if let Some((path, rotation_limit)) = &rotation {
+            if count >= *rotation_limit {
+                writer = rotate_log_file(path, writer)?;
+                count = 0;
+            }
+        }
+    }
+    writer.flush()
+}
+
+/// Archive `path` (compressed in the background) and reopen it empty.
+fn rotate_log_file(path: &Path, writer: BufWriter<Box<dyn Write + Send>>) -> io::Result<BufWriter<Box<dyn Write + Send>>> {
+    writer.into_inner().map_err(|e| e.into_error())?.flush()?; // Close file handle
+
+    let timestamp = chrono::Utc::now().format("%Y%m%dT%H%M%S");
+    let archived_path = format!("{}.{}", path.display(), timestamp);
+
+    if fs::rename(path, &archived_path).is_ok() {
+        // Background Zstd compression (New for 2.3.0)
+        thread::spawn(move || {
+            compress_log_file(&archived_path);
+        });
+    }
+
+    let new_file = OpenOptions::new().create(true).append(true).open(path)?;
+    Ok(BufWriter::new(Box::new(new_file)))
+}
+
+/// Text line of an entry, newline included.
+///
+/// `FRAME: DIR|SEG|FRAME|TYPE|WIRE_LEN|CRC32_HEX|TIMESTAMP_NS[|PAYLOAD_BASE64]|CHECKSUM`,
+/// the checksum (first 8 hex chars of BLAKE3 over the rest) as for resume points.
 fn format_entry(entry: &UnifiedEntry) -> String {
     match entry {
         UnifiedEntry::Scheduler(msg) => format!("SCHEDULER: {}\n", msg),
-        UnifiedEntry::Encrypt(data) => format!("ENCRYPT: {}\n", STANDARD.encode(data)),
-        UnifiedEntry::Decrypt(data) => format!("DECRYPT: {}\n", STANDARD.encode(data)),
+        UnifiedEntry::Frame(record) => {
+            let mut raw_msg = format!(
+                "{}|{}|{}|{}|{}|{:08x}|{}",
+                record.direction.tag(),
+                record.segment_index,
+                record.frame_index,
+                record.frame_type as u16,
+                record.wire_len,
+                record.wire_crc32,
+                record.timestamp_ns
+            );
+            if let Some(payload) = &record.payload {
+                raw_msg.push('|');
+                raw_msg.push_str(&STANDARD.encode(payload));
+            }
+            let checksum = blake3::hash(raw_msg.as_bytes()).to_hex();
+            format!("FRAME: {}|{}\n", raw_msg, &checksum[..8])
+        }
     }
 }
 
@@ -194,10 +663,14 @@ fn compress_log_file(src_path: &str) {
     }
 }
 
-/// Compaction logic: remove redundant scheduler markers.
+/// Compaction logic: remove redundant scheduler markers and repeated frame records.
+///
+/// A frame record is repeated when an earlier record has the same direction,
+/// indices, length and CRC (e.g. a retried write); the first one is kept.
 pub fn compact_unified_log(entries: &mut Vec<UnifiedEntry>) {
-    let mut compacted = Vec::new();
+    let mut compacted: Vec<UnifiedEntry> = Vec::new();
     let mut last_scheduler: Option<String> = None;
+    let mut seen_frames = HashSet::new();
 
     for entry in entries.drain(..) {
         match &entry {
@@ -207,7 +680,11 @@ pub fn compact_unified_log(entries: &mut Vec<UnifiedEntry>) {
                     last_scheduler = Some(msg.clone());
                 }
             }
-            _ => compacted.push(entry),
+            UnifiedEntry::Frame(record) => {
+                if seen_frames.insert(record.key()) {
+                    compacted.push(entry);
+                }
+            }
         }
     }
 
@@ -5,6 +5,22 @@ use crate::recovery::checkpoint::SegmentCheckpoint;
 use crate::recovery::persist::{AsyncLogManager, UnifiedEntry};
 use std::io;
 
+/// Resume point for stream decryption.
+/// Marks a whole-segment boundary inside an encrypted stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResumePoint {
+    /// Absolute byte offset (stream header included) of the next `SegmentHeader`.
+    pub offset: u64,
+    /// Segment index expected in the header found at `offset`.
+    pub segment_index: u32,
+}
+
+impl ResumePoint {
+    pub fn new(offset: u64, segment_index: u32) -> Self {
+        Self { offset, segment_index }
+    }
+}
+
 /// Resume point for digest workers.
 /// Represents the exact boundary where a worker can pick up processing.
 #[derive(Debug, Clone)]
@@ -66,7 +82,7 @@ impl SegmentResumePoint {
         log_manager.append(UnifiedEntry::Scheduler(final_msg))
     }
 
-    pub fn persist(&self, log_manager: &AsyncLogManager) {
+    pub fn persist(&self, log_manager: &AsyncLogManager) -> io::Result<()> {
         // 1. Create a specialized log entry string.
         let final_msg = self.format_log_line();
         
@@ -95,7 +111,7 @@ pub fn parse_resume_line(line: &str) -> Option<(u32, u32, String)> {
     
     let actual_hash = blake3::hash(data_to_verify.as_bytes());
     if &actual_hash.to_hex()[..8] != provided_checksum {
-        eprintln!("CRITICAL: Corrupt log line detected (Checksum mismatch). Skipping.");
+        tracing::warn!("corrupt log line (checksum mismatch), skipping");
         return None;
     }
 
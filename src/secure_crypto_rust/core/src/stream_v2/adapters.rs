@@ -0,0 +1,475 @@
+// # 📂 src/stream_v2/adapters.rs
+
+// ## 📂 File: `src/stream_v2/adapters.rs`
+// ## `std::io::Write` / `std::io::Read` adapters over the segment pipeline
+//
+// `EncryptingWriter` and `DecryptingReader` drive the same segment workers as
+// `run_encrypt_pipeline` / `run_decrypt_pipeline`, so a stream produced by one
+// side can always be consumed by the other:
+//
+// - full `chunk_size` segments are dispatched as soon as they are buffered
+// - the stream is closed by an empty `FINAL_SEGMENT`, exactly like the pipeline reader
+// - compression is applied per segment with the codec recorded in the header
+
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::sync::Arc;
+use bytes::{Buf, Bytes, BytesMut};
+use zeroize::Zeroizing;
+use crossbeam::channel::{bounded, Receiver, Sender};
+
+use crate::compression::{DictSelector, DictionaryRegistry};
+use crate::constants::flags;
+use crate::crypto::{DigestAlg, check_segment_budget};
+use crate::headers::HeaderV1;
+use crate::stream_v2::compression_worker::{
+    compress_segment, decompress_frames, make_backend, select_segment_dict, CodecInfo, CompressionBackend,
+};
+use crate::stream_v2::core::{
+    setup_dec_context, setup_enc_context, validate_decrypt_params, validate_encrypt_params,
+    ApiConfig, DecryptParams, EncryptParams,
+};
+use crate::stream_v2::io::{read_segment, write_header_with_extensions, write_index_trailer, PayloadReader, PlaintextTrailer};
+use crate::stream_v2::parallelism::WorkerTarget;
+use crate::stream_v2::pipeline::encrypt_index_segment;
+use crate::stream_v2::segment_worker::{
+    DecryptSegmentInput, DecryptSegmentWorker, DecryptedSegment, EncryptContext, EncryptSegmentInput,
+    EncryptSegmentWorker, EncryptedSegment, SegmentWorkerError,
+};
+use crate::stream_v2::segmenting::{encode_segment, SegmentIndexMap};
+use crate::stream_v2::segmenting::types::SegmentFlags;
+use crate::recovery::AsyncLogManager;
+use crate::telemetry::StageTimes;
+use crate::types::StreamError;
+
+// ============================================================
+// Encrypting writer
+// ============================================================
+
+/// 🔐 Streaming encryptor implementing [`std::io::Write`].
+///
+/// Plaintext is buffered into `chunk_size` segments which are compressed and
+/// handed to an [`EncryptSegmentWorker`]; encrypted segments are written to the
+/// wrapped writer in order. Call [`EncryptingWriter::finish`] to emit the
+/// final segment marker — dropping the writer leaves the stream truncated.
+pub struct EncryptingWriter<W: Write> {
+    inner: W,
+    chunk_size: usize,
+    pending: BytesMut,
+    backend: Box<dyn CompressionBackend>,
+    /// `EncryptParams::dict_selector`, with the registry it picks from.
+    dict_selector: Option<(DictSelector, Arc<DictionaryRegistry>)>,
+    seg_tx: Sender<EncryptSegmentInput>,
+    out_rx: Receiver<Result<EncryptedSegment, SegmentWorkerError>>,
+    next_index: u32,
+    next_write: u32,
+    in_flight: usize,
+    max_in_flight: usize,
+    final_written: bool,
+    index: SegmentIndexMap,
+    /// Context for the index trailer, when the header sets `HAS_SEGMENT_INDEX`.
+    trailer: Option<(EncryptContext, Arc<AsyncLogManager>)>,
+    plaintext: PlaintextTrailer,
+    /// `header.plaintext_size`; 0 when not declared.
+    declared_len: u64,
+}
+
+impl<W: Write> EncryptingWriter<W> {
+    /// Validate params, write the stream header and start the segment worker.
+    ///
+    /// The input size is never known here, so `Strategy::Auto` gets the
+    /// dynamic profile without a size-based plan.
+    pub fn new(mut inner: W, master_key: &[u8], params: EncryptParams) -> Result<Self, StreamError> {
+        validate_encrypt_params(master_key, &params, None, None)?;
+
+        let (crypto, profile, log_manager) = setup_enc_context(
+            master_key,
+            &params.stamped_header(),
+            params.digest_alg.unwrap_or(DigestAlg::Blake3),
+            params.frame_size,
+            &ApiConfig::default(),
+            None,
+        )?;
+        let crypto = crypto.with_padding(params.pad_to).with_extensions(params.extension_block()?);
+        write_header_with_extensions(&mut inner, &crypto.header, &crypto.extensions)?;
+
+        let mut codec_info = CodecInfo::from_header(&crypto.header, params.dictionaries.as_deref())
+            .map_err(StreamError::Compression)?;
+        codec_info.gpu = profile.gpu();
+        if let Some(level) = params.level {
+            codec_info.level = level;
+        }
+        let backend = make_backend(WorkerTarget::Cpu(0), codec_info).map_err(StreamError::Compression)?;
+        let dict_selector = params.dict_selector.clone().zip(params.dictionaries.clone());
+
+        // One worker keeps segments in submission order; frames still fan out
+        // across the worker's frame pool.
+        let max_in_flight = profile.inflight_segments().max(1);
+        let (seg_tx, seg_rx) = bounded::<EncryptSegmentInput>(max_in_flight);
+        let (out_tx, out_rx) = bounded::<Result<EncryptedSegment, SegmentWorkerError>>(max_in_flight);
+        let chunk_size = crypto.base.segment_size;
+        let trailer = (crypto.header.flags & flags::HAS_SEGMENT_INDEX != 0)
+            .then(|| (crypto.clone(), log_manager.clone()));
+        let plaintext = PlaintextTrailer::for_header(&crypto.header);
+        let declared_len = crypto.header.plaintext_size;
+        let payload_offset = crypto.header.encoded_len() as u64;
+
+        // The worker lives as long as `seg_tx`; only a failed start is reported here
+        EncryptSegmentWorker::new(crypto, log_manager).run_v2(seg_rx, out_tx).wait_ready()?;
+
+        Ok(Self {
+            inner,
+            chunk_size,
+            pending: BytesMut::with_capacity(chunk_size),
+            backend,
+            dict_selector,
+            seg_tx,
+            out_rx,
+            next_index: 0,
+            next_write: 0,
+            in_flight: 0,
+            max_in_flight,
+            final_written: false,
+            index: SegmentIndexMap::starting_at(payload_offset),
+            trailer,
+            plaintext,
+            declared_len,
+        })
+    }
+
+    /// Encrypt any buffered tail, emit the final segment and return the inner writer.
+    pub fn finish(self) -> Result<W, StreamError> {
+        self.finish_with_index().map(|(inner, _)| inner)
+    }
+
+    /// Like [`EncryptingWriter::finish`], also returning the offsets of every
+    /// written segment (for [`crate::stream_v2::RandomAccessDecryptor::with_index`]).
+    pub fn finish_with_index(mut self) -> Result<(W, SegmentIndexMap), StreamError> {
+        if !self.pending.is_empty() {
+            let chunk = self.pending.split().freeze();
+            self.dispatch(chunk, SegmentFlags::empty())?;
+        }
+        if self.declared_len != 0 && self.declared_len != self.plaintext.total_len() {
+            return Err(StreamError::Validation(format!(
+                "header declares {} plaintext bytes, input has {}",
+                self.declared_len,
+                self.plaintext.total_len()
+            )));
+        }
+        if self.plaintext.is_enabled() {
+            let (payload, flags) = (self.plaintext.payload(), self.plaintext.flags());
+            self.dispatch(payload, flags)?;
+        }
+
+        // Same rule as the pipeline reader: close the stream with an empty final segment
+        self.dispatch(Bytes::new(), SegmentFlags::FINAL_SEGMENT)?;
+        self.drain()?;
+
+        if !self.final_written {
+            return Err(StreamError::Validation("Missing final segment".into()));
+        }
+
+        if let Some((crypto, log_manager)) = self.trailer.take() {
+            let segment = encrypt_index_segment(&crypto, log_manager, &self.index)?;
+            write_index_trailer(&mut self.inner, &segment, &self.index)?;
+        }
+
+        self.inner.flush()?;
+        Ok((self.inner, self.index))
+    }
+
+    /// Borrow the wrapped writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    fn dispatch_full_segments(&mut self) -> Result<(), StreamError> {
+        while self.pending.len() >= self.chunk_size {
+            let chunk = self.pending.split_to(self.chunk_size).freeze();
+            self.dispatch(chunk, SegmentFlags::empty())?;
+        }
+        Ok(())
+    }
+
+    fn dispatch(&mut self, bytes: Bytes, flags: SegmentFlags) -> Result<(), StreamError> {
+        // Backpressure: never keep more than `max_in_flight` segments in the worker
+        if self.in_flight >= self.max_in_flight {
+            self.collect_one()?;
+        }
+
+        check_segment_budget(self.next_index).map_err(StreamError::Crypto)?;
+
+        // Final empty segment bypasses compression, as in the compression workers
+        let (bytes, flags) = if bytes.is_empty() {
+            (bytes, flags)
+        } else {
+            // Only data segments (no flags yet) pick their own dictionary, as in the pipeline reader
+            let (bytes, flags) = match &self.dict_selector {
+                Some((selector, registry)) if flags.is_empty() => {
+                    select_segment_dict(selector, registry, self.next_index, bytes, flags).map_err(StreamError::Compression)?
+                }
+                _ => (bytes, flags),
+            };
+            compress_segment(self.backend.as_mut(), &bytes, flags).map_err(StreamError::Compression)?
+        };
+
+        self.seg_tx
+            .send(EncryptSegmentInput {
+                segment_index: self.next_index,
+                bytes,
+                flags,
+                stage_times: StageTimes::default(),
+            })
+            .map_err(|_| StreamError::PipelineError("encrypt segment channel closed"))?;
+
+        self.next_index += 1;
+        self.in_flight += 1;
+        Ok(())
+    }
+
+    fn collect_one(&mut self) -> Result<(), StreamError> {
+        let encrypted = self
+            .out_rx
+            .recv()
+            .map_err(|_| StreamError::PipelineError("encrypt segment worker disconnected"))?
+            .map_err(StreamError::SegmentWorker)?;
+        self.in_flight -= 1;
+
+        if encrypted.header.segment_index != self.next_write {
+            return Err(StreamError::Validation(format!(
+                "out-of-order segment: expected {}, got {}",
+                self.next_write, encrypted.header.segment_index
+            )));
+        }
+
+        let segment_enc = encode_segment(&encrypted.header, &encrypted.wire).map_err(StreamError::Segment)?;
+        self.inner.write_all(&segment_enc)?;
+        self.index.push(&encrypted.header).map_err(StreamError::Segment)?;
+
+        if encrypted.header.flags.contains(SegmentFlags::FINAL_SEGMENT) {
+            self.final_written = true;
+        }
+        self.next_write += 1;
+        Ok(())
+    }
+
+    fn drain(&mut self) -> Result<(), StreamError> {
+        while self.in_flight > 0 {
+            self.collect_one()?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for EncryptingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        self.plaintext.update(buf);
+        self.dispatch_full_segments().map_err(io::Error::other)?;
+        Ok(buf.len())
+    }
+
+    /// Writes every dispatched segment to the inner writer.
+    ///
+    /// A partially filled segment stays buffered: segment boundaries must
+    /// match the pipeline's `chunk_size` chunking.
+    fn flush(&mut self) -> io::Result<()> {
+        self.drain().map_err(io::Error::other)?;
+        self.inner.flush()
+    }
+}
+
+// ============================================================
+// Decrypting reader
+// ============================================================
+
+/// Live decrypt state, created once the stream header has been parsed.
+struct DecryptSession<R: Read> {
+    payload: PayloadReader<R>,
+    header: HeaderV1,
+    backend: Box<dyn CompressionBackend>,
+    /// `None` once the input reached EOF (closes the worker input).
+    seg_tx: Option<Sender<DecryptSegmentInput>>,
+    out_rx: Receiver<Result<DecryptedSegment, SegmentWorkerError>>,
+    next_index: u32,
+    in_flight: usize,
+    max_in_flight: usize,
+    final_seen: bool,
+    trailer: PlaintextTrailer,
+}
+
+impl<R: Read> DecryptSession<R> {
+    fn open(reader: R, master_key: &[u8], params: &DecryptParams) -> Result<Self, StreamError> {
+        let (header, payload) = PayloadReader::with_header_policy(reader, params.unknown_flags)?;
+        params.check_header(&header)?;
+        let (crypto, profile, log_manager) = setup_dec_context(master_key, &header, DigestAlg::Blake3, &ApiConfig::default(), None)?;
+        let crypto = crypto.with_extensions(payload.extensions().clone());
+        crypto.header.validate_with(params.unknown_flags).map_err(StreamError::Header)?;
+
+        let mut codec_info = CodecInfo::from_header(&header, params.dictionaries.as_deref())
+            .map_err(StreamError::Compression)?;
+        codec_info.gpu = profile.gpu();
+        let backend = make_backend(WorkerTarget::Cpu(0), codec_info).map_err(StreamError::Compression)?;
+
+        let max_in_flight = profile.inflight_segments().max(1);
+        let (seg_tx, seg_rx) = bounded::<DecryptSegmentInput>(max_in_flight);
+        let (out_tx, out_rx) = bounded::<Result<DecryptedSegment, SegmentWorkerError>>(max_in_flight);
+
+        // The worker lives as long as `seg_tx`; only a failed start is reported here
+        DecryptSegmentWorker::new(crypto, log_manager).run_v2(seg_rx, out_tx).wait_ready()?;
+
+        Ok(Self {
+            trailer: PlaintextTrailer::for_header(&header),
+            payload,
+            header,
+            backend,
+            seg_tx: Some(seg_tx),
+            out_rx,
+            next_index: 0,
+            in_flight: 0,
+            max_in_flight,
+            final_seen: false,
+        })
+    }
+
+    /// Plaintext frames of the next segment, or `None` once the final segment was consumed.
+    fn next_plaintext(&mut self) -> Result<Option<Vec<Bytes>>, StreamError> {
+        loop {
+            // Read ahead up to `max_in_flight` segments
+            while self.in_flight < self.max_in_flight {
+                let Some(tx) = self.seg_tx.as_ref() else { break };
+                match read_segment(&mut self.payload)? {
+                    Some((header, wire)) => {
+                        let is_final = header.flags.contains(SegmentFlags::FINAL_SEGMENT);
+                        tx.send(DecryptSegmentInput { header, wire })
+                            .map_err(|_| StreamError::PipelineError("decrypt segment channel closed"))?;
+                        self.in_flight += 1;
+                        // Trailing data (segment index) is not read
+                        if is_final {
+                            self.seg_tx = None;
+                        }
+                    }
+                    None => self.seg_tx = None,
+                }
+            }
+
+            if self.in_flight == 0 {
+                if !self.final_seen {
+                    return Err(StreamError::Validation("Missing final segment".into()));
+                }
+                return Ok(None);
+            }
+
+            let segment = self
+                .out_rx
+                .recv()
+                .map_err(|_| StreamError::PipelineError("decrypt segment worker disconnected"))?
+                .map_err(StreamError::SegmentWorker)?;
+            self.in_flight -= 1;
+
+            if segment.header.segment_index != self.next_index {
+                return Err(StreamError::Validation(format!(
+                    "out-of-order segment: expected {}, got {}",
+                    self.next_index, segment.header.segment_index
+                )));
+            }
+            self.next_index += 1;
+
+            if segment.header.flags.contains(SegmentFlags::FINAL_SEGMENT) && segment.is_empty() {
+                self.trailer.finish()?;
+                self.final_seen = true;
+                continue;
+            }
+
+            let is_trailer = PlaintextTrailer::is_trailer(segment.header.flags);
+            let frames = decompress_frames(self.backend.as_mut(), segment.frames, segment.header.flags)
+                .map_err(StreamError::Compression)?;
+            if is_trailer {
+                self.trailer.verify(&frames.concat())?;
+                continue;
+            }
+            for frame in &frames {
+                self.trailer.update(frame);
+            }
+            return Ok(Some(frames));
+        }
+    }
+}
+
+/// 🔓 Streaming decryptor implementing [`std::io::Read`].
+///
+/// The stream header is parsed lazily on the first read (or on
+/// [`DecryptingReader::header`]); segments are then read ahead, decrypted by a
+/// [`DecryptSegmentWorker`], decompressed and served in order. Reaching EOF
+/// without a final segment marker surfaces as an `io::Error`.
+pub struct DecryptingReader<R: Read> {
+    source: Option<R>,
+    master_key: Zeroizing<Vec<u8>>,
+    params: DecryptParams,
+    session: Option<DecryptSession<R>>,
+    buf: VecDeque<Bytes>,
+}
+
+impl<R: Read> DecryptingReader<R> {
+    /// Validate params; nothing is read from `reader` until the first read.
+    pub fn new(reader: R, master_key: &[u8], params: DecryptParams) -> Result<Self, StreamError> {
+        validate_decrypt_params(master_key, &params, None, None)?;
+        Ok(Self {
+            source: Some(reader),
+            master_key: Zeroizing::new(master_key.to_vec()),
+            params,
+            session: None,
+            buf: VecDeque::new(),
+        })
+    }
+
+    /// Parse (if needed) and return the stream header.
+    pub fn header(&mut self) -> Result<&HeaderV1, StreamError> {
+        Ok(&self.session()?.header)
+    }
+
+    fn session(&mut self) -> Result<&mut DecryptSession<R>, StreamError> {
+        if self.session.is_none() {
+            let reader = self
+                .source
+                .take()
+                .ok_or(StreamError::PipelineError("decrypting reader failed to open stream"))?;
+            let session = DecryptSession::open(reader, &self.master_key, &self.params)?;
+            // The session key is derived; the master key is no longer needed
+            self.master_key = Zeroizing::new(Vec::new());
+            self.session = Some(session);
+        }
+        self.session
+            .as_mut()
+            .ok_or(StreamError::PipelineError("decrypting reader failed to open stream"))
+    }
+}
+
+impl<R: Read> Read for DecryptingReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if out.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            match self.buf.front_mut() {
+                Some(frame) if !frame.is_empty() => {
+                    let n = out.len().min(frame.len());
+                    out[..n].copy_from_slice(&frame[..n]);
+                    frame.advance(n);
+                    return Ok(n);
+                }
+                Some(_) => {
+                    self.buf.pop_front();
+                }
+                None => {
+                    let next = self.session().and_then(|s| s.next_plaintext());
+                    match next.map_err(io::Error::other)? {
+                        Some(frames) => self.buf.extend(frames),
+                        None => return Ok(0),
+                    }
+                }
+            }
+        }
+    }
+}
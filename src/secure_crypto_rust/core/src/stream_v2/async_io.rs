@@ -0,0 +1,173 @@
+// ## 📂 File: `src/stream_v2/async_io.rs`
+// ## tokio `AsyncRead` / `AsyncWrite` front-end for the v2 pipelines (feature `async`)
+//
+// The segment/frame workers stay on their own threads; only the endpoints are async:
+//
+// - an async reader fills a bounded `tokio::sync::mpsc` channel with input chunks
+// - the sync pipeline runs under `spawn_blocking`, reading/writing through channel bridges
+// - an async writer drains a second bounded channel into the output, in order
+//
+// Both channels are bounded, so a slow consumer stalls the pipeline and in turn
+// the reader, exactly like the crossbeam queues of the sync path.
+
+use std::io::{self, Read, Write};
+
+use bytes::{Buf, Bytes};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+use zeroize::Zeroizing;
+
+use crate::constants::DEFAULT_QUEUE_CAP;
+use crate::stream_v2::core::{decrypt_stream_v2, encrypt_stream_v2, ApiConfig, DecryptParams, EncryptParams};
+use crate::stream_v2::io::{InputSource, OutputSink};
+use crate::telemetry::TelemetrySnapshot;
+use crate::types::StreamError;
+
+/// Size of each read issued against the async input.
+const ASYNC_READ_CHUNK: usize = 64 * 1024;
+
+/// 🔐 Encrypt stream (v2) between tokio endpoints
+///
+/// Same stream format and telemetry as [`encrypt_stream_v2`]. `output` is
+/// shut down once the final segment has been written, and left open on error.
+pub async fn encrypt_stream_v2_async<R, W>(
+    input: R,
+    output: W,
+    master_key: &[u8],
+    params: EncryptParams<'static>,
+    config: ApiConfig,
+) -> Result<TelemetrySnapshot, StreamError>
+where
+    R: AsyncRead + Unpin + Send,
+    W: AsyncWrite + Unpin + Send,
+{
+    let master_key = Zeroizing::new(master_key.to_vec());
+    run_bridged(input, output, move |reader, writer| {
+        encrypt_stream_v2(reader, writer, &master_key, params, config)
+    })
+    .await
+}
+
+/// 🔓 Decrypt stream (v2) between tokio endpoints
+///
+/// Same checks and telemetry as [`decrypt_stream_v2`]. `output` is shut down
+/// once the final segment has been verified, and left open on error.
+pub async fn decrypt_stream_v2_async<R, W>(
+    input: R,
+    output: W,
+    master_key: &[u8],
+    params: DecryptParams,
+    config: ApiConfig,
+) -> Result<TelemetrySnapshot, StreamError>
+where
+    R: AsyncRead + Unpin + Send,
+    W: AsyncWrite + Unpin + Send,
+{
+    let master_key = Zeroizing::new(master_key.to_vec());
+    run_bridged(input, output, move |reader, writer| {
+        decrypt_stream_v2(reader, writer, &master_key, params, config)
+    })
+    .await
+}
+
+/// Drive `pipeline` on the blocking pool with async reader/writer tasks on either side.
+async fn run_bridged<R, W, F>(mut input: R, mut output: W, pipeline: F) -> Result<TelemetrySnapshot, StreamError>
+where
+    R: AsyncRead + Unpin + Send,
+    W: AsyncWrite + Unpin + Send,
+    F: FnOnce(InputSource, OutputSink) -> Result<TelemetrySnapshot, StreamError> + Send + 'static,
+{
+    let (in_tx, in_rx) = channel::<io::Result<Bytes>>(DEFAULT_QUEUE_CAP);
+    let (out_tx, mut out_rx) = channel::<Bytes>(DEFAULT_QUEUE_CAP);
+
+    let blocking = tokio::task::spawn_blocking(move || {
+        let reader = ChannelReader { rx: in_rx, current: Bytes::new() };
+        let writer = ChannelWriter { tx: out_tx };
+        pipeline(InputSource::Reader(Box::new(reader)), OutputSink::Writer(Box::new(writer)))
+    });
+
+    let read_task = async move {
+        let mut buf = vec![0u8; ASYNC_READ_CHUNK];
+        loop {
+            let read = tokio::select! {
+                read = input.read(&mut buf) => read,
+                // Pipeline stopped (or failed) without draining the input
+                _ = in_tx.closed() => break,
+            };
+            match read {
+                Ok(0) => break,
+                Ok(n) => {
+                    if in_tx.send(Ok(Bytes::copy_from_slice(&buf[..n]))).await.is_err() {
+                        break; // pipeline stopped; its own error is reported
+                    }
+                }
+                Err(e) => {
+                    let _ = in_tx.send(Err(e)).await;
+                    break;
+                }
+            }
+        }
+        // Dropping `in_tx` signals EOF to the pipeline reader
+    };
+
+    let write_task = async {
+        let mut result = Ok(());
+        while let Some(chunk) = out_rx.recv().await {
+            if let Err(e) = output.write_all(&chunk).await {
+                result = Err(e);
+                break;
+            }
+        }
+        // Closing the receiver makes further pipeline writes fail with BrokenPipe
+        drop(out_rx);
+        result
+    };
+
+    let (_, pipeline_result, write_result) = tokio::join!(read_task, blocking, write_task);
+    // An output failure is the root cause of the pipeline's BrokenPipe
+    write_result?;
+    let snapshot = pipeline_result.unwrap_or(Err(StreamError::PipelineError("async pipeline task panicked")))?;
+
+    // Only a complete stream is closed: a failed run must not look like EOF downstream
+    output.shutdown().await?;
+    Ok(snapshot)
+}
+
+/// Blocking `Read` over the chunks produced by the async reader.
+struct ChannelReader {
+    rx: Receiver<io::Result<Bytes>>,
+    current: Bytes,
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.current.is_empty() {
+            match self.rx.blocking_recv() {
+                Some(chunk) => self.current = chunk?,
+                None => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.current.len());
+        buf[..n].copy_from_slice(&self.current[..n]);
+        self.current.advance(n);
+        Ok(n)
+    }
+}
+
+/// Blocking `Write` feeding the async writer.
+struct ChannelWriter {
+    tx: Sender<Bytes>,
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.tx
+            .blocking_send(Bytes::copy_from_slice(buf))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "async output closed"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -1,71 +1,205 @@
 use std::sync::{Arc, Mutex};
-use crossbeam::channel::{Receiver, Sender};
+use crossbeam::channel::{Receiver, Sender, bounded};
+use tracing::trace;
 
-use crate::stream_v2::{ 
-    compression_worker::{CodecInfo, CompressionWorkerError, make_backend, run_compression_worker, run_decompression_worker},
-    parallelism::{HybridParallelismProfile, Scheduler, WorkerTarget}, 
-    segment_worker::{DecryptedSegment, EncryptSegmentInput}
+use crate::headers::HeaderV1;
+use crate::stream_v2::{
+    compression_worker::{CodecInfo, CompressionBackend, CompressionWorkerError, BackendOrigin, make_backend, make_backend_checked, run_compression_worker_into, run_decompression_worker_into},
+    parallelism::{HybridParallelismProfile, Scheduler, WorkerTarget},
+    segment_worker::{DecryptedSegment, EncryptSegmentInput},
+    sink::StageSink,
 };
 
+/// Running compression workers, as returned by [`spawn_compression_workers`].
+#[derive(Debug)]
+pub struct CompressionPool {
+    /// Routes segments to workers; call `release(segment_index)` once a
+    /// segment has been consumed downstream so load tracking stays accurate.
+    pub scheduler: Arc<Mutex<Scheduler>>,
+    /// GPU backends that failed their conformance check and run on the CPU.
+    pub gpu_fallbacks: usize,
+}
+
+/// CPU backends, GPU backends and the number of GPU devices demoted to the CPU side.
+type Backends = (Vec<Box<dyn CompressionBackend>>, Vec<Box<dyn CompressionBackend>>, usize);
+
+/// Build one backend per CPU worker and one per GPU device.
+///
+/// GPU backends failing the conformance check are returned among the CPU
+/// backends, so they are never routed GPU-sized work under a GPU label.
+fn build_backends(
+    profile: &HybridParallelismProfile,
+    codec_info: &CodecInfo,
+) -> Result<Backends, CompressionWorkerError> {
+    let mut cpu = Vec::with_capacity(profile.cpu_workers().max(1));
+    let mut gpu = Vec::with_capacity(profile.gpu_workers());
+    let mut gpu_fallbacks = 0;
+
+    // At least one CPU worker, so small segments always have somewhere to go
+    for i in 0..profile.cpu_workers().max(1) {
+        cpu.push(make_backend(WorkerTarget::Cpu(i), codec_info.clone())?);
+    }
+    for i in 0..profile.gpu_workers() {
+        let (backend, origin) = make_backend_checked(WorkerTarget::Gpu(i), codec_info.clone())?;
+        if origin == BackendOrigin::CpuFallback {
+            gpu_fallbacks += 1;
+            cpu.push(backend);
+        } else {
+            gpu.push(backend);
+        }
+    }
+    Ok((cpu, gpu, gpu_fallbacks))
+}
+
+/// Compression worker entry point
+///
+/// Fails before any thread starts on a dictionary/`DICT_USED` mismatch or an
+/// unknown codec. Segments are routed by [`spawn_routed_compression_workers`].
 pub fn spawn_compression_workers(
     profile: HybridParallelismProfile,
     codec_info: CodecInfo,
     comp_rx: Receiver<EncryptSegmentInput>,
     out_tx: Sender<Result<EncryptSegmentInput, CompressionWorkerError>>,
-) {
-    let scheduler = Arc::new(Mutex::new(Scheduler::new(
-        profile.cpu_workers(),
-        profile.gpu_workers(),
+    header: &HeaderV1,
+) -> Result<CompressionPool, CompressionWorkerError> {
+    spawn_compression_workers_into(profile, codec_info, comp_rx, out_tx, header)
+}
+
+/// [`spawn_compression_workers`], each worker delivering to its own clone of `sink`.
+pub fn spawn_compression_workers_into<S>(
+    profile: HybridParallelismProfile,
+    codec_info: CodecInfo,
+    comp_rx: Receiver<EncryptSegmentInput>,
+    sink: S,
+    header: &HeaderV1,
+) -> Result<CompressionPool, CompressionWorkerError>
+where
+    S: StageSink<EncryptSegmentInput, CompressionWorkerError> + Clone + 'static,
+{
+    // A dictionary mismatch would otherwise surface as an opaque codec failure mid-stream
+    codec_info.check_dict_binding(header)?;
+
+    // Build every backend first so a bad codec fails before any thread starts
+    let (cpu, gpu, gpu_fallbacks) = build_backends(&profile, &codec_info)?;
+
+    let scheduler = spawn_routed_compression_workers_into(
+        cpu,
+        gpu,
         profile.gpu_threshold(),
-    )));
-
-    // spawn CPU workers
-    for i in 0..profile.cpu_workers() {
-        let backend = make_backend(WorkerTarget::Cpu(i), codec_info.clone());
-        let sched = scheduler.clone();
-        let rx = comp_rx.clone();
-        let tx = out_tx.clone();
-        std::thread::spawn(move || run_compression_worker(rx, tx, backend, sched));
-    }
+        profile.inflight_segments(),
+        comp_rx,
+        sink,
+    );
+    Ok(CompressionPool { scheduler, gpu_fallbacks })
+}
 
-    // spawn GPU workers
-    for i in 0..profile.gpu_workers() {
-        let backend = make_backend(WorkerTarget::Gpu(i), codec_info.clone());
-        let sched = scheduler.clone();
-        let rx = comp_rx.clone();
-        let tx = out_tx.clone();
-        std::thread::spawn(move || run_compression_worker(rx, tx, backend, sched));
+/// Start one worker per backend behind a router thread.
+///
+/// The router takes segments from `comp_rx` and asks the returned scheduler
+/// where each goes: segments of at least `gpu_threshold` bytes to the least
+/// loaded GPU worker when there is one, everything else to the least loaded
+/// CPU worker. Each worker has its own queue of `inflight` segments.
+pub fn spawn_routed_compression_workers(
+    cpu: Vec<Box<dyn CompressionBackend>>,
+    gpu: Vec<Box<dyn CompressionBackend>>,
+    gpu_threshold: usize,
+    inflight: usize,
+    comp_rx: Receiver<EncryptSegmentInput>,
+    out_tx: Sender<Result<EncryptSegmentInput, CompressionWorkerError>>,
+) -> Arc<Mutex<Scheduler>> {
+    spawn_routed_compression_workers_into(cpu, gpu, gpu_threshold, inflight, comp_rx, out_tx)
+}
+
+/// [`spawn_routed_compression_workers`], each worker delivering to its own clone of `sink`.
+pub fn spawn_routed_compression_workers_into<S>(
+    cpu: Vec<Box<dyn CompressionBackend>>,
+    gpu: Vec<Box<dyn CompressionBackend>>,
+    gpu_threshold: usize,
+    inflight: usize,
+    comp_rx: Receiver<EncryptSegmentInput>,
+    sink: S,
+) -> Arc<Mutex<Scheduler>>
+where
+    S: StageSink<EncryptSegmentInput, CompressionWorkerError> + Clone + 'static,
+{
+    let cpu_slots = cpu.len();
+    let scheduler = Arc::new(Mutex::new(Scheduler::new(cpu_slots, gpu.len(), gpu_threshold)));
+
+    // CPU workers first, then GPU workers: slot = index in this list
+    let mut worker_txs = Vec::with_capacity(cpu_slots + gpu.len());
+    for backend in cpu.into_iter().chain(gpu) {
+        let (tx, rx) = bounded::<EncryptSegmentInput>(inflight.max(1));
+        let out = sink.clone();
+        std::thread::spawn(move || run_compression_worker_into(rx, out, backend));
+        worker_txs.push(tx);
     }
+
+    let router_scheduler = scheduler.clone();
+    std::thread::spawn(move || {
+        for seg in comp_rx.iter() {
+            let target = router_scheduler.lock().unwrap().assign(seg.segment_index, seg.bytes.len());
+            trace!(segment_index = seg.segment_index, ?target, "routing segment");
+            let slot = match target {
+                WorkerTarget::Cpu(idx) => idx,
+                WorkerTarget::Gpu(idx) => cpu_slots + idx,
+            };
+            if worker_txs[slot].send(seg).is_err() {
+                break; // worker exited after an error
+            }
+        }
+        // Dropping `worker_txs` lets the workers drain and exit
+    });
+
+    scheduler
+}
+
+/// Backend for decompressing inside the ordered writer (`PipelineConfig::decompress_in_order`).
+///
+/// Same startup checks as [`spawn_decompression_workers`], always on the CPU.
+pub fn inline_decompressor(
+    codec_info: CodecInfo,
+    header: &HeaderV1,
+) -> Result<Box<dyn CompressionBackend>, CompressionWorkerError> {
+    codec_info.check_dict_binding(header)?;
+    Ok(make_backend(WorkerTarget::Cpu(0), codec_info)?)
 }
 
 /// Decompression worker entry point
+///
+/// Same startup checks as [`spawn_compression_workers`]. Segments go to
+/// whichever worker is free; returns how many GPU backends fell back to the CPU.
 pub fn spawn_decompression_workers(
     profile: HybridParallelismProfile,
     codec_info: CodecInfo,
     decomp_rx: Receiver<DecryptedSegment>,
     out_tx: Sender<Result<DecryptedSegment, CompressionWorkerError>>,
-) {
-    let scheduler = Arc::new(Mutex::new(Scheduler::new(
-        profile.cpu_workers(),
-        profile.gpu_workers(),
-        profile.gpu_threshold(),
-    )));
+    header: &HeaderV1,
+) -> Result<usize, CompressionWorkerError> {
+    spawn_decompression_workers_into(profile, codec_info, decomp_rx, out_tx, header)
+}
 
-    // spawn CPU workers
-    for i in 0..profile.cpu_workers() {
-        let backend = make_backend(WorkerTarget::Cpu(i), codec_info.clone());
-        let sched = scheduler.clone();
-        let rx = decomp_rx.clone();
-        let tx = out_tx.clone();
-        std::thread::spawn(move || run_decompression_worker(rx, tx, backend, sched));
-    }
+/// [`spawn_decompression_workers`], each worker delivering to its own clone of `sink`.
+pub fn spawn_decompression_workers_into<S>(
+    profile: HybridParallelismProfile,
+    codec_info: CodecInfo,
+    decomp_rx: Receiver<DecryptedSegment>,
+    sink: S,
+    header: &HeaderV1,
+) -> Result<usize, CompressionWorkerError>
+where
+    S: StageSink<DecryptedSegment, CompressionWorkerError> + Clone + 'static,
+{
+    // A dictionary mismatch would otherwise surface as an opaque codec failure mid-stream
+    codec_info.check_dict_binding(header)?;
 
-    // spawn GPU workers
-    for i in 0..profile.gpu_workers() {
-        let backend = make_backend(WorkerTarget::Gpu(i), codec_info.clone());
-        let sched = scheduler.clone();
+    // Build every backend first so a bad codec fails before any thread starts
+    let (cpu, gpu, gpu_fallbacks) = build_backends(&profile, &codec_info)?;
+    let max_output = codec_info.output_limit();
+
+    for backend in cpu.into_iter().chain(gpu) {
         let rx = decomp_rx.clone();
-        let tx = out_tx.clone();
-        std::thread::spawn(move || run_decompression_worker(rx, tx, backend, sched));
+        let tx = sink.clone();
+        std::thread::spawn(move || run_decompression_worker_into(rx, tx, backend, max_output));
     }
+    Ok(gpu_fallbacks)
 }
@@ -21,8 +21,21 @@ pub use types::{
 };
 pub use worker::{
     make_backend,
+    make_backend_checked,
+    verify_or_fallback,
+    check_conformance,
+    conformance_corpus,
+    BackendOrigin,
+    SegmentDictBackend,
+    select_segment_dict,
+    compress_segment,
+    decompress_segment,
+    decompress_frames,
+    decompress_decrypted,
     run_compression_worker,
+    run_compression_worker_into,
     run_decompression_worker,
+    run_decompression_worker_into,
 };
 pub use worker_cpu::{
     CpuCompressionBackend
@@ -1,6 +1,6 @@
 use std::fmt;
 
-use crate::{compression::{CodecLevel, CompressionError}, headers::HeaderV1, stream_v2::parallelism::GpuInfo};
+use crate::{compression::{AutoSelectHints, CodecLevel, CompressionError, DECOMPRESS_SLACK, DictionaryRegistry, MAX_DECOMPRESSED_LEN}, constants::flags, headers::HeaderV1, stream_v2::parallelism::GpuInfo};
 
 #[derive(Debug, Clone)]
 pub struct CodecInfo<'a> {
@@ -8,34 +8,66 @@ pub struct CodecInfo<'a> {
     pub level: CodecLevel,     // now uses enum instead of raw i32
     pub dict: Option<&'a [u8]>,
     pub gpu: Option<GpuInfo>,
+    /// Largest decompressed segment accepted; `None` for `MAX_DECOMPRESSED_LEN`.
+    pub max_output: Option<usize>,
+    /// Dictionaries `DICT_OVERRIDE` segments may select (`SEGMENT_DICTS` streams);
+    /// `make_backend` builds a backend for each.
+    pub segment_dicts: Option<&'a DictionaryRegistry>,
 }
 
 impl<'a> CodecInfo<'a> {
+    /// Build codec info from the stream header.
+    ///
+    /// - If `DICT_USED` is set, `header.dict_id` must resolve through `dict_registry`.
+    /// - Otherwise no dictionary is used.
+    /// - If `SEGMENT_DICTS` is set, every dictionary in `dict_registry` is
+    ///   available to `DICT_OVERRIDE` segments.
+    /// - Decompressed segments are capped at `chunk_size + DECOMPRESS_SLACK`.
+    ///
+    /// # Errors
+    /// - `CompressionError::InvalidDictionary` if the dictionary id is unknown
+    ///   or no registry was provided.
     pub fn from_header(
         header: &HeaderV1,
-        dict_registry: Option<&'a std::collections::HashMap<u32, Vec<u8>>>,
-    ) -> Self {
-        // Resolve dictionary only if registry is provided and dict_id != 0
-        let dict = dict_registry
-            .and_then(|registry| {
-                header
-                    .dict_id
-                    .checked_sub(1) // treat 0 as "no dict"
-                    .and_then(|id| registry.get(&id))
-            })
-            .map(|buf| buf.as_slice());
-
-        let level = CodecLevel::auto_select(
-            header.compression, 
-            header.chunk_size as usize, 
-            dict,
-        );
+        dict_registry: Option<&'a DictionaryRegistry>,
+    ) -> Result<Self, CompressionError> {
+        let dict = if header.flags & flags::DICT_USED != 0 {
+            let registry = dict_registry
+                .ok_or(CompressionError::InvalidDictionary { dict_id: header.dict_id })?;
+            Some(registry.resolve(header.dict_id)?)
+        } else {
+            None
+        };
+
+        let hints = AutoSelectHints { has_dict: dict.is_some(), ..AutoSelectHints::new(header.chunk_size as usize) };
+        let level = CodecLevel::auto_select(header.compression, &hints);
 
-        Self {
+        Ok(Self {
             codec_id: header.compression,
             level: level,
             dict,
             gpu: None, // detect at runtime
+            max_output: Some((header.chunk_size as usize).saturating_add(DECOMPRESS_SLACK)),
+            segment_dicts: if header.segment_dicts() { dict_registry } else { None },
+        })
+    }
+
+    /// Decompressed size cap per segment.
+    pub fn output_limit(&self) -> usize {
+        self.max_output.unwrap_or(MAX_DECOMPRESSED_LEN)
+    }
+
+    /// Dictionary presence must agree with the header's `DICT_USED` flag.
+    ///
+    /// # Errors
+    /// - `CompressionWorkerError::DictionaryRequired` if the flag is set but `dict` is `None`.
+    /// - `CompressionWorkerError::UnexpectedDictionary` if `dict` is set but the flag is not.
+    pub fn check_dict_binding(&self, header: &HeaderV1) -> Result<(), CompressionWorkerError> {
+        let dict_used = header.flags & flags::DICT_USED != 0;
+        match (dict_used, self.dict.is_some()) {
+            (true, false) => Err(CompressionWorkerError::DictionaryRequired { dict_id: header.dict_id }),
+            (false, true) => Err(CompressionWorkerError::UnexpectedDictionary),
+            _ => Ok(()),
         }
     }
 }
@@ -68,6 +100,12 @@ impl<'a> CodecInfo<'a> {
 pub trait CompressionBackend: Send {
     fn compress_chunk(&mut self, input: &[u8]) -> Result<Vec<u8>, CompressionError>;
     fn decompress_chunk(&mut self, input: &[u8]) -> Result<Vec<u8>, CompressionError>;
+
+    /// Backend for `DICT_OVERRIDE` segments naming `dict_id`; `None` when
+    /// this backend has no such dictionary.
+    fn dict_backend(&mut self, _dict_id: u32) -> Option<&mut dyn CompressionBackend> {
+        None
+    }
 }
 
 
@@ -75,6 +113,10 @@ pub trait CompressionBackend: Send {
 pub enum CompressionWorkerError {
     Compression(CompressionError),
     StateError(String),
+    /// Header sets `DICT_USED` but the worker got no dictionary.
+    DictionaryRequired { dict_id: u32 },
+    /// Worker got a dictionary for a header without `DICT_USED`.
+    UnexpectedDictionary,
 }
 
 impl From<std::io::Error> for CompressionWorkerError {
@@ -94,8 +136,17 @@ impl fmt::Display for CompressionWorkerError {
         match self {
             CompressionWorkerError::Compression(e) => write!(f, "compression error: {}", e),
             StateError(msg) => write!(f, "compression worker error: {}", msg),
+            DictionaryRequired { dict_id } => write!(f, "dictionary {} required by header but not provided", dict_id),
+            UnexpectedDictionary => write!(f, "dictionary provided but header does not set DICT_USED"),
         }
     }
 }
 
-impl std::error::Error for CompressionWorkerError {}
\ No newline at end of file
+impl std::error::Error for CompressionWorkerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CompressionWorkerError::Compression(e) => Some(e),
+            _ => None,
+        }
+    }
+}
\ No newline at end of file
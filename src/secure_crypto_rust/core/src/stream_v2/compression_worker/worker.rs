@@ -1,36 +1,295 @@
-use std::{sync::{Arc, Mutex}, time::Instant};
+use std::collections::HashMap;
+use std::time::Instant;
 use bytes::Bytes;
 use crossbeam::channel::{Receiver, Sender};
+use tracing::{debug, trace, warn};
 
-use crate::{stream_v2::{
+use crate::{compression::{CompressionError, DictSelector, DictionaryRegistry, check_output_len}, constants::MIN_COMPRESSION_SAVINGS_PCT, stream_v2::{
     compression_worker::{CodecInfo, CompressionBackend, CpuCompressionBackend, GpuCompressionBackend, types::CompressionWorkerError}, 
-    parallelism::{Scheduler, WorkerTarget}, segment_worker::{DecryptedSegment, EncryptSegmentInput}, 
-    segmenting::types::SegmentFlags
+    parallelism::WorkerTarget, segment_worker::{DecryptedSegment, EncryptSegmentInput}, sink::StageSink, 
+    segmenting::types::{DICT_ID_LEN, SegmentFlags}
 }, telemetry::{Stage, StageTimes}};
 
 /// Factory: choose backend based on codec + target
-pub fn make_backend(target: WorkerTarget, codec_info: CodecInfo) -> Box<dyn CompressionBackend> {
-    match target {
-        WorkerTarget::Cpu(_) => {
-            let backend = CpuCompressionBackend::new(codec_info)
-                .expect("failed to create CPU compressor/decompressor");
-            Box::new(backend)
-        }
+///
+/// Unknown codec ids surface as `CompressionError::UnsupportedCodec`.
+/// GPU targets go through [`make_backend_checked`] and may come back as CPU backends.
+pub fn make_backend(target: WorkerTarget, codec_info: CodecInfo) -> Result<Box<dyn CompressionBackend>, CompressionError> {
+    make_backend_checked(target, codec_info).map(|(backend, _)| backend)
+}
+
+/// Which implementation a backend factory handed out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendOrigin {
+    Cpu,
+    Gpu,
+    /// GPU requested, but init or the conformance check failed.
+    CpuFallback,
+}
+
+/// Like [`make_backend`], also reporting whether a GPU target fell back to the CPU.
+///
+/// With `codec_info.segment_dicts`, the backend is wrapped in a [`SegmentDictBackend`].
+pub fn make_backend_checked(
+    target: WorkerTarget,
+    codec_info: CodecInfo,
+) -> Result<(Box<dyn CompressionBackend>, BackendOrigin), CompressionError> {
+    let (backend, origin) = match target {
+        WorkerTarget::Cpu(_) => (Box::new(CpuCompressionBackend::new(codec_info.clone())?) as Box<dyn CompressionBackend>, BackendOrigin::Cpu),
         WorkerTarget::Gpu(_) => {
-            let backend = GpuCompressionBackend::new(codec_info)
-                .expect("failed to create GPU compressor/decompressor");
-            Box::new(backend)
+            let candidate = GpuCompressionBackend::new(codec_info.clone())
+                .map(|backend| Box::new(backend) as Box<dyn CompressionBackend>);
+            verify_or_fallback(candidate, codec_info.clone())?
+        }
+    };
+    match codec_info.segment_dicts {
+        Some(registry) => Ok((Box::new(SegmentDictBackend::new(backend, &codec_info, registry)?), origin)),
+        None => Ok((backend, origin)),
+    }
+}
+
+/// Backend of a `SEGMENT_DICTS` stream: the stream backend, plus one CPU
+/// backend per registered dictionary for `DICT_OVERRIDE` segments.
+///
+/// Every dictionary backend is built up front, so a dictionary the codec
+/// rejects fails before the first segment.
+pub struct SegmentDictBackend {
+    stream: Box<dyn CompressionBackend>,
+    dicts: HashMap<u32, Box<dyn CompressionBackend>>,
+}
+
+impl SegmentDictBackend {
+    pub fn new(
+        stream: Box<dyn CompressionBackend>,
+        codec_info: &CodecInfo,
+        registry: &DictionaryRegistry,
+    ) -> Result<Self, CompressionError> {
+        let mut dicts = HashMap::with_capacity(registry.len());
+        for dict_id in registry.ids() {
+            let info = CodecInfo { dict: registry.get(dict_id), gpu: None, segment_dicts: None, ..codec_info.clone() };
+            dicts.insert(dict_id, Box::new(CpuCompressionBackend::new(info)?) as Box<dyn CompressionBackend>);
+        }
+        Ok(Self { stream, dicts })
+    }
+}
+
+impl CompressionBackend for SegmentDictBackend {
+    fn compress_chunk(&mut self, input: &[u8]) -> Result<Vec<u8>, CompressionError> {
+        self.stream.compress_chunk(input)
+    }
+
+    fn decompress_chunk(&mut self, input: &[u8]) -> Result<Vec<u8>, CompressionError> {
+        self.stream.decompress_chunk(input)
+    }
+
+    fn dict_backend(&mut self, dict_id: u32) -> Option<&mut dyn CompressionBackend> {
+        self.dicts.get_mut(&dict_id).map(|backend| backend.as_mut() as &mut dyn CompressionBackend)
+    }
+}
+
+/// Use `candidate` only if it passes [`check_conformance`]; otherwise warn and
+/// return a `CpuCompressionBackend` for the same codec.
+///
+/// Codec errors from the CPU fallback itself (unknown codec, bad dictionary)
+/// are returned as-is.
+pub fn verify_or_fallback(
+    candidate: Result<Box<dyn CompressionBackend>, CompressionError>,
+    codec_info: CodecInfo,
+) -> Result<(Box<dyn CompressionBackend>, BackendOrigin), CompressionError> {
+    let mut reference = CpuCompressionBackend::new(codec_info)?;
+    let failure = match candidate {
+        Ok(mut backend) => match check_conformance(backend.as_mut(), &mut reference) {
+            Ok(()) => return Ok((backend, BackendOrigin::Gpu)),
+            Err(e) => e,
+        },
+        Err(e) => e,
+    };
+    warn!(error = %failure, "GPU compression backend rejected, falling back to CPU");
+    Ok((Box::new(reference), BackendOrigin::CpuFallback))
+}
+
+/// Samples every non-reference backend must round-trip (see [`check_conformance`]).
+pub fn conformance_corpus() -> Vec<Vec<u8>> {
+    vec![
+        b"a".to_vec(),
+        b"The quick brown fox jumps over the lazy dog.\n".repeat(64),
+        vec![0u8; 64 * 1024],
+        (0..64 * 1024u32).map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8).collect(),
+    ]
+}
+
+/// Check `backend` against the corpus: its output must decompress back to the
+/// input through both itself and `reference` (the backend a decryptor would use).
+pub fn check_conformance(
+    backend: &mut dyn CompressionBackend,
+    reference: &mut dyn CompressionBackend,
+) -> Result<(), CompressionError> {
+    for (i, sample) in conformance_corpus().iter().enumerate() {
+        let compressed = backend.compress_chunk(sample)?;
+        let decoders: [&mut dyn CompressionBackend; 2] = [&mut *backend, &mut *reference];
+        for decoder in decoders {
+            if decoder.decompress_chunk(&compressed)? != *sample {
+                return Err(CompressionError::CodecProcessFailed {
+                    codec: "conformance".into(),
+                    msg: format!("corpus sample {i} did not round-trip"),
+                });
+            }
         }
     }
+    Ok(())
+}
+
+/// Ask `selector` for the dictionary of a data segment.
+///
+/// A selected id must be one of `selector.ids()` and in `registry`: checked
+/// here, before the segment is compressed or encrypted. The payload comes back led by the id, with
+/// `DICT_OVERRIDE` added to `flags`; [`compress_segment`] takes it from there.
+///
+/// # Errors
+/// - `CompressionError::InvalidDictionary` for an undeclared id or one `registry` lacks.
+pub fn select_segment_dict(
+    selector: &DictSelector,
+    registry: &DictionaryRegistry,
+    segment_index: u32,
+    bytes: Bytes,
+    flags: SegmentFlags,
+) -> Result<(Bytes, SegmentFlags), CompressionError> {
+    let Some(dict_id) = selector.select(segment_index, &bytes) else {
+        return Ok((bytes, flags));
+    };
+    if !selector.ids().contains(&dict_id) || !registry.contains(dict_id) {
+        return Err(CompressionError::InvalidDictionary { dict_id });
+    }
+    let mut prefixed = Vec::with_capacity(DICT_ID_LEN + bytes.len());
+    prefixed.extend_from_slice(&dict_id.to_le_bytes());
+    prefixed.extend_from_slice(&bytes);
+    Ok((Bytes::from(prefixed), flags | SegmentFlags::DICT_OVERRIDE))
 }
 
+/// Split a `DICT_OVERRIDE` payload into its dictionary id and the rest.
+fn split_dict_id(bytes: &Bytes) -> Result<(u32, Bytes), CompressionError> {
+    let Some(id) = bytes.get(..DICT_ID_LEN) else {
+        return Err(CompressionError::StateError(format!(
+            "DICT_OVERRIDE payload of {} bytes has no dictionary id",
+            bytes.len()
+        )));
+    };
+    let dict_id = u32::from_le_bytes(id.try_into().expect("DICT_ID_LEN bytes"));
+    Ok((dict_id, bytes.slice(DICT_ID_LEN..)))
+}
+
+/// The backend a `DICT_OVERRIDE` segment naming `dict_id` goes through.
+fn override_backend(backend: &mut dyn CompressionBackend, dict_id: u32) -> Result<&mut dyn CompressionBackend, CompressionError> {
+    backend.dict_backend(dict_id).ok_or(CompressionError::InvalidDictionary { dict_id })
+}
+
+/// Compress one segment payload, falling back to raw storage.
+///
+/// When the compressed form is not at least `MIN_COMPRESSION_SAVINGS_PCT`
+/// smaller than the input, the input is returned unchanged with
+/// `SegmentFlags::UNCOMPRESSED` added to `flags`.
+///
+/// A `DICT_OVERRIDE` payload (see [`select_segment_dict`]) is compressed with
+/// its own dictionary and keeps its id in front; stored raw, it drops both
+/// the id and the flag.
+pub fn compress_segment(
+    backend: &mut dyn CompressionBackend,
+    bytes: &Bytes,
+    flags: SegmentFlags,
+) -> Result<(Bytes, SegmentFlags), CompressionError> {
+    if flags.contains(SegmentFlags::DICT_OVERRIDE) {
+        let (dict_id, payload) = split_dict_id(bytes)?;
+        let compressed = override_backend(backend, dict_id)?.compress_chunk(&payload)?;
+        if compressed.len() * 100 > payload.len() * (100 - MIN_COMPRESSION_SAVINGS_PCT) {
+            return Ok((payload, (flags - SegmentFlags::DICT_OVERRIDE) | SegmentFlags::UNCOMPRESSED));
+        }
+        let mut out = Vec::with_capacity(DICT_ID_LEN + compressed.len());
+        out.extend_from_slice(&dict_id.to_le_bytes());
+        out.extend_from_slice(&compressed);
+        return Ok((Bytes::from(out), flags));
+    }
+    let compressed = backend.compress_chunk(bytes)?;
+    if compressed.len() * 100 > bytes.len() * (100 - MIN_COMPRESSION_SAVINGS_PCT) {
+        return Ok((bytes.clone(), flags | SegmentFlags::UNCOMPRESSED));
+    }
+    Ok((Bytes::from(compressed), flags))
+}
+
+/// Decompress one segment payload; raw-stored segments pass through.
+///
+/// `DICT_OVERRIDE` payloads go through the backend's dictionary for their id
+/// (`CompressionError::InvalidDictionary` when it has none).
+pub fn decompress_segment(
+    backend: &mut dyn CompressionBackend,
+    bytes: &Bytes,
+    flags: SegmentFlags,
+) -> Result<Bytes, CompressionError> {
+    if flags.contains(SegmentFlags::UNCOMPRESSED) {
+        return Ok(bytes.clone());
+    }
+    if flags.contains(SegmentFlags::DICT_OVERRIDE) {
+        let (dict_id, payload) = split_dict_id(bytes)?;
+        return Ok(Bytes::from(override_backend(backend, dict_id)?.decompress_chunk(&payload)?));
+    }
+    Ok(Bytes::from(backend.decompress_chunk(bytes)?))
+}
+
+/// Decompress an ordered list of plaintext frames.
+///
+/// Raw (`UNCOMPRESSED`) segments are passed through frame by frame without
+/// copying; compressed segments are joined, since codecs need the whole segment.
+pub fn decompress_frames(
+    backend: &mut dyn CompressionBackend,
+    frames: Vec<Bytes>,
+    flags: SegmentFlags,
+) -> Result<Vec<Bytes>, CompressionError> {
+    if flags.contains(SegmentFlags::UNCOMPRESSED) {
+        return Ok(frames);
+    }
+    let joined = match frames.as_slice() {
+        [single] => single.clone(),
+        _ => Bytes::from(frames.concat()),
+    };
+    Ok(vec![decompress_segment(backend, &joined, flags)?])
+}
+
+/// Decompress a decrypted segment in place; the empty final segment passes through.
+///
+/// Fails with `CompressionError::ChunkTooLarge` past `max_output` plaintext bytes.
+pub fn decompress_decrypted(
+    backend: &mut dyn CompressionBackend,
+    seg: &mut DecryptedSegment,
+    max_output: usize,
+) -> Result<(), CompressionError> {
+    if seg.header.flags.contains(SegmentFlags::FINAL_SEGMENT) && seg.is_empty() {
+        trace!(segment_index = seg.header.segment_index, "final empty segment bypasses decompression");
+        return Ok(());
+    }
+    let frames = decompress_frames(backend, std::mem::take(&mut seg.frames), seg.header.flags)?;
+    check_output_len(frames.iter().map(Bytes::len).sum(), max_output)?;
+    seg.frames = frames;
+    Ok(())
+}
 
 /// Single compression worker loop
+///
+/// Compresses whatever arrives on `rx`; routing to CPU or GPU workers happens
+/// upstream (see `spawn_routed_compression_workers`).
 pub fn run_compression_worker(
     rx: Receiver<EncryptSegmentInput>,
     tx: Sender<Result<EncryptSegmentInput, CompressionWorkerError>>,
+    backend: Box<dyn super::CompressionBackend>,
+) {
+    run_compression_worker_into(rx, tx, backend)
+}
+
+/// [`run_compression_worker`] delivering to any [`StageSink`].
+///
+/// Stops on the first error, or once `sink` reports nobody is listening.
+pub fn run_compression_worker_into<S: StageSink<EncryptSegmentInput, CompressionWorkerError>>(
+    rx: Receiver<EncryptSegmentInput>,
+    mut sink: S,
     mut backend: Box<dyn super::CompressionBackend>,
-    scheduler: Arc<Mutex<Scheduler>>,
 ) {
 
     while let Ok(mut seg) = rx.recv() {
@@ -38,88 +297,78 @@ pub fn run_compression_worker(
         // Compression / segment
         let start = Instant::now();
 
-        let target = {
-            let mut sched = scheduler.lock().unwrap();
-            sched.dispatch(seg.bytes.len())
-        };
-
         // ✅ Catch final empty segment before compression
         if seg.flags.contains(SegmentFlags::FINAL_SEGMENT) && seg.bytes.is_empty() {
-            eprintln!("[COMPRESSION] final empty segment {} bypassed", seg.segment_index);
+            trace!(segment_index = seg.segment_index, "final empty segment bypasses compression");
             stage_times.add(Stage::Compress, start.elapsed());
             seg.stage_times = stage_times;
 
-            let _ = tx.send(Ok(seg));
-            let mut sched = scheduler.lock().unwrap();
-            sched.complete(target);
+            if !sink.send(Ok(seg)) {
+                break;
+            }
             continue;
         }
 
-        match backend.compress_chunk(&seg.bytes) {
-            Ok(buf) => {
-                seg.bytes = Bytes::from(buf);
+        match compress_segment(backend.as_mut(), &seg.bytes, seg.flags) {
+            Ok((buf, flags)) => {
+                seg.bytes = buf;
+                seg.flags = flags;
                 stage_times.add(Stage::Compress, start.elapsed());
                 seg.stage_times = stage_times;
 
-                let _ = tx.send(Ok(seg));
+                if !sink.send(Ok(seg)) {
+                    debug!("compressed segment receiver gone, compression worker exiting");
+                    break;
+                }
             }
             Err(e) => {
-                eprintln!("[COMPRESSION] failed: {e}");
-                let _ = tx.send(Err(CompressionWorkerError::Compression(e)));
+                debug!(segment_index = seg.segment_index, error = %e, "compression failed");
+                sink.send(Err(CompressionWorkerError::Compression(e)));
                 break; // exit on error so pipeline can terminate
             }
         }
-
-        let mut sched = scheduler.lock().unwrap();
-        sched.complete(target);
     }
 }
 
 /// Single decompression worker loop
+///
+/// Segments whose plaintext exceeds `max_output` bytes abort the worker with
+/// `CompressionError::ChunkTooLarge` (see [`CodecInfo::output_limit`]).
 pub fn run_decompression_worker(
     rx: Receiver<DecryptedSegment>,
     tx: Sender<Result<DecryptedSegment, CompressionWorkerError>>,
+    backend: Box<dyn super::CompressionBackend>,
+    max_output: usize,
+) {
+    run_decompression_worker_into(rx, tx, backend, max_output)
+}
+
+/// [`run_decompression_worker`] delivering to any [`StageSink`].
+pub fn run_decompression_worker_into<S: StageSink<DecryptedSegment, CompressionWorkerError>>(
+    rx: Receiver<DecryptedSegment>,
+    mut sink: S,
     mut backend: Box<dyn super::CompressionBackend>,
-    scheduler: Arc<Mutex<Scheduler>>,
+    max_output: usize,
 ) {
     while let Ok(mut seg) = rx.recv() {
-        let mut stage_times = StageTimes::default();
         // Decompression / segment
         let start = Instant::now();
 
-        let target = {
-            let mut sched = scheduler.lock().unwrap();
-            sched.dispatch(seg.bytes.len())
-        };
-
-        // ✅ Catch final empty segment before decompression
-        if seg.header.flags.contains(SegmentFlags::FINAL_SEGMENT) && seg.bytes.is_empty() {
-            eprintln!("[DECOMPRESSION] final empty segment {} bypassed", seg.header.segment_index);
-            stage_times.add(Stage::Decompress, start.elapsed());
-            seg.stage_times = stage_times;
-
-            let _ = tx.send(Ok(seg));
-            let mut sched = scheduler.lock().unwrap();
-            sched.complete(target);
-            continue;
-        }
-        
-        match backend.decompress_chunk(&seg.bytes) {
-            Ok(buf) => {
-                seg.bytes = Bytes::from(buf);
-                stage_times.add(Stage::Decompress, start.elapsed());
-                seg.stage_times = stage_times;
+        match decompress_decrypted(backend.as_mut(), &mut seg, max_output) {
+            Ok(()) => {
+                // Added to the decryption times already on the segment: the writer merges both
+                seg.stage_times.add(Stage::Decompress, start.elapsed());
 
-                let _ = tx.send(Ok(seg));
+                if !sink.send(Ok(seg)) {
+                    debug!("decompressed segment receiver gone, decompression worker exiting");
+                    break;
+                }
             }
             Err(e) => {
-                eprintln!("[DECOMPRESSION] failed: {e}");
-                let _ = tx.send(Err(CompressionWorkerError::Compression(e)));
+                debug!(segment_index = seg.header.segment_index, error = %e, "decompression failed");
+                sink.send(Err(CompressionWorkerError::Compression(e)));
                 break; // exit on error so pipeline can terminate
             }
         }
-
-        let mut sched = scheduler.lock().unwrap();
-        sched.complete(target);
     }
 }
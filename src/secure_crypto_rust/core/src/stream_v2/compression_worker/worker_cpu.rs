@@ -10,9 +10,11 @@ pub struct CpuCompressionBackend {
 
 impl CpuCompressionBackend {
     pub fn new(codec_info: CodecInfo) -> Result<Self, CompressionError> {
+        let mut decompressor = create_decompressor(codec_info.codec_id, Some(CodecOptions::resolve(codec_info.level, codec_info.dict)))?;
+        decompressor.set_max_output(codec_info.output_limit());
         Ok(Self {
             compressor: create_compressor(codec_info.codec_id, Some(CodecOptions::resolve(codec_info.level, codec_info.dict)))?,
-            decompressor: create_decompressor(codec_info.codec_id, Some(CodecOptions::resolve(codec_info.level, codec_info.dict)))?,
+            decompressor,
         })
     }
 }
@@ -20,7 +22,9 @@ impl CpuCompressionBackend {
 impl super::types::CompressionBackend for CpuCompressionBackend {
     fn compress_chunk(&mut self, input: &[u8]) -> Result<Vec<u8>, CompressionError> {
         let mut out = Vec::new();
+        // One self-contained codec frame per segment
         self.compressor.compress_chunk(input, &mut out)?;
+        self.compressor.finish(&mut out)?;
         Ok(out)
     }
 
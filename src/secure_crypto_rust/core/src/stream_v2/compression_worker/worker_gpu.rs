@@ -17,9 +17,11 @@ impl GpuCompressionBackend {
     /// Initialize a GPU backend for compression/decompression.
     /// Uses codec registry to create compressor/decompressor, and attaches GPU info.
     pub fn new(codec_info: CodecInfo) -> Result<Self, CompressionError> {
+        let mut decompressor = create_decompressor(codec_info.codec_id, Some(CodecOptions::resolve(codec_info.level, codec_info.dict)))?;
+        decompressor.set_max_output(codec_info.output_limit());
         Ok(Self {
             compressor: create_compressor(codec_info.codec_id, Some(CodecOptions::resolve(codec_info.level, codec_info.dict)))?,
-            decompressor: create_decompressor(codec_info.codec_id, Some(CodecOptions::resolve(codec_info.level, codec_info.dict)))?,
+            decompressor,
             _gpu: codec_info.gpu,
         })
     }
@@ -34,6 +36,7 @@ impl super::types::CompressionBackend for GpuCompressionBackend {
         let mut out = Vec::new();
         // let mut compressor = pollster::block_on(Lz4GpuCompressor::new())?;
         self.compressor.compress_chunk(input, &mut out)?;
+        self.compressor.finish(&mut out)?;
 
         Ok(out)
     }
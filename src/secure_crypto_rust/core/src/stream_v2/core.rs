@@ -1,41 +1,436 @@
 
 // ## 2️⃣ `core.rs` — stable public API
 
-use std::sync::Arc;
+use std::io::{Cursor, Read, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use bytes::Bytes;
 
 use crate::{
-    constants::{DEFAULT_QUEUE_CAP, DEFAULT_WORKERS, MAGIC_DICT, MASTER_KEY_LENGTHS, MAX_DICT_LEN, MIN_DICT_LEN, QUEUE_CAPS, WORKERS_COUNT}, 
-    crypto::{CryptoError, DigestAlg, derive_session_key_32}, 
-    headers::HeaderV1, recovery::AsyncLogManager, 
-    stream_v2::{io::{InputSource, OutputSink, PayloadReader, open_input, open_output}, 
-    parallelism::HybridParallelismProfile, pipeline::{PipelineConfig, run_decrypt_pipeline, run_encrypt_pipeline}, 
-    segment_worker::{DecryptContext, EncryptContext}}, 
+    constants::{ChunkPolicy, MASTER_KEY_LENGTHS, MAX_QUEUE_CAP, RoundingBase, flags}, 
+    compression::{CodecLevel, CompressionCodec, CompressionError, DictSelector, DictionaryRegistry},
+    crypto::{CryptoError, DigestAlg, KeyRing, derive_session_key_32, is_degenerate_salt}, 
+    headers::{AadDomain, CipherSuite, HeaderExtensions, HeaderV1, Strategy, UnknownFlagPolicy}, recovery::{AsyncLogManager, LogConfig, LogOptions, ResumePoint}, 
+    stream_v2::{io::{InputSource, OutputSink, PayloadReader, open_input, open_input_at_with, open_output_counted, read_segment, rewrite_header}, 
+    parallelism::{AUTO_PARALLEL_THRESHOLD, AutoPlan, HybridParallelismProfile}, progress::ProgressCallback, pipeline::{PipelineConfig, run_decrypt_pipeline_with_outcome, run_encrypt_pipeline_with_outcome}, 
+    overhead::min_segment_pad_target, segment_worker::{DecryptContext, EncryptContext, SegmentWorkerError, types::{ALLOWED_FRAME_SIZES, get_frame_size, is_allowed_frame_size}}, segmenting::{SegmentLimits, encode_segment}}, 
     telemetry::TelemetrySnapshot, 
-    types::StreamError
+    types::StreamError,
+    utils::{best_chunk_size, enum_name_or_hex},
 };
+use tracing::warn;
 
-#[derive(Clone, Debug)]
+/// How `EncryptParams::validate` treats `header.salt`.
+///
+/// Nonces derive from the salt, so two streams with the same salt and key
+/// reuse every nonce. Headers from `HeaderV1::default()` / `new_random()`
+/// always carry a fresh random salt.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SaltPolicy {
+    /// Reject degenerate salts (every byte equal), the telltale of a placeholder.
+    #[default]
+    Checked,
+    /// Use `header.salt` exactly as given. Only for reproducible test vectors.
+    Deterministic,
+}
+
+#[derive(Clone)]
 pub struct EncryptParams<'a> {
     pub header: HeaderV1,
     pub dict: Option<&'a [u8]>,
+    /// Explicit codec level; `None` lets `CodecLevel::auto_select` decide.
+    pub level: Option<CodecLevel>,
+    /// Registry resolving `header.dict_id` when `DICT_USED` is set.
+    pub dictionaries: Option<Arc<DictionaryRegistry>>,
+    /// Explicit frame size, one of `ALLOWED_FRAME_SIZES`.
+    ///
+    /// - 4 KiB frames maximize parallelism; 64 KiB frames minimize per-frame overhead.
+    /// - `None` picks a size with `optimal_frame_size` based on `header.chunk_size`.
+    /// - Not recorded in the header: decrypt reads each frame's length from its frame header.
+    pub frame_size: Option<usize>,
+    /// Salt checks; see [`SaltPolicy`].
+    pub salt_policy: SaltPolicy,
+    /// Segment digest algorithm, recorded in every segment header.
+    ///
+    /// - `None` uses unkeyed `Blake3`.
+    /// - `Blake3Keyed` binds each digest to the session key, so it acts as a MAC.
+    pub digest_alg: Option<DigestAlg>,
+    /// Wire length (frames only) every non-empty segment is padded to.
+    ///
+    /// - `None` → no padding (default).
+    /// - Padding frames are authenticated and discarded on decrypt.
+    /// - Must be at least [`min_segment_pad_target`] for the chunk size, frame size and digest.
+    pub pad_to: Option<usize>,
+    /// TLV extension block written right after the header.
+    ///
+    /// - Its length is recorded in `header.ext_len`; decoders that do not parse it skip it.
+    /// - Bound into every frame's AAD under `AAD_STRICT`.
+    pub extensions: Option<HeaderExtensions>,
+    /// Record the encryption time in `header.enc_time_ns` (default `true`).
+    ///
+    /// - `false` writes `header.enc_time_ns` as given (zero means unset).
+    /// - Turned off by [`EncryptParams::with_deterministic_salt`] so output is reproducible.
+    pub include_timestamp: bool,
+    /// Seal each segment's frames under its own subkey (default `false`).
+    ///
+    /// - Recorded as `PER_SEGMENT_KEYS` in the header; decrypt follows the flag.
+    /// - Subkey = HKDF(session key, segment index), see `crypto::derive_segment_key`.
+    pub per_segment_keys: bool,
+    /// Per-segment dictionary choice, resolved through `dictionaries`.
+    ///
+    /// - `None` → every segment uses the stream dictionary, if any (default).
+    /// - Recorded as `SEGMENT_DICTS` in the header; segments using their own
+    ///   dictionary carry `SegmentFlags::DICT_OVERRIDE`.
+    /// - Every id in `DictSelector::ids` must be in `dictionaries`, checked
+    ///   before anything is written; an undeclared id picked at run time
+    ///   fails the stream before that segment is compressed.
+    pub dict_selector: Option<DictSelector>,
+}
+
+impl std::fmt::Debug for EncryptParams<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncryptParams")
+            .field("header", &self.header)
+            .field("dict", &self.dict)
+            .field("level", &self.level)
+            .field("dictionaries", &self.dictionaries)
+            .field("frame_size", &self.frame_size)
+            .field("salt_policy", &self.salt_policy)
+            .field("digest_alg", &self.digest_alg)
+            .field("pad_to", &self.pad_to)
+            .field("extensions", &self.extensions)
+            .field("include_timestamp", &self.include_timestamp)
+            .field("per_segment_keys", &self.per_segment_keys)
+            .field("dict_selector", &self.dict_selector)
+            .finish()
+    }
+}
+
+impl Default for EncryptParams<'_> {
+    /// `HeaderV1::default()` (fresh random salt), with the encryption timestamp on.
+    fn default() -> Self {
+        Self::new(HeaderV1::default())
+    }
 }
+
 impl<'a> EncryptParams<'a> {
+    pub fn new(header: HeaderV1) -> Self {
+        Self {
+            header,
+            dict: None,
+            level: None,
+            dictionaries: None,
+            frame_size: None,
+            salt_policy: SaltPolicy::Checked,
+            digest_alg: None,
+            pad_to: None,
+            extensions: None,
+            include_timestamp: true,
+            per_segment_keys: false,
+            dict_selector: None,
+        }
+    }
+
+    /// Select the segment digest algorithm.
+    pub fn with_digest_alg(mut self, digest_alg: DigestAlg) -> Self {
+        self.digest_alg = Some(digest_alg);
+        self
+    }
+
+    /// Encrypt with exactly `header.salt`, skipping the degenerate-salt check.
+    ///
+    /// For deterministic test vectors; never reuse such a salt with a real key.
+    /// Also disables the encryption timestamp, which would change every run.
+    pub fn with_deterministic_salt(mut self) -> Self {
+        self.salt_policy = SaltPolicy::Deterministic;
+        self.include_timestamp = false;
+        self
+    }
+
+    /// Keep `header.enc_time_ns` as given instead of recording the encryption time.
+    pub fn without_timestamp(mut self) -> Self {
+        self.include_timestamp = false;
+        self
+    }
+
+    /// The header as written: `header`, stamped with the current time when `include_timestamp` is set,
+    /// flagged `PER_SEGMENT_KEYS` when `per_segment_keys` is and `SEGMENT_DICTS` with a `dict_selector`.
+    pub fn stamped_header(&self) -> HeaderV1 {
+        let mut header = self.header;
+        if self.include_timestamp {
+            header.stamp_enc_time();
+        }
+        if self.per_segment_keys {
+            header.enable_per_segment_keys();
+        }
+        if self.dict_selector.is_some() {
+            header.enable_segment_dicts();
+        }
+        header
+    }
+
+    /// Pad every data segment to `target` wire bytes, so segment sizes leak
+    /// nothing about the plaintext or how well it compressed.
+    ///
+    /// The segment index (`with_segment_index`) is not padded.
+    pub fn pad_segments_to(mut self, target: Option<usize>) -> Self {
+        self.pad_to = target;
+        self
+    }
+
+    /// Request a specific frame size (validated against `ALLOWED_FRAME_SIZES`).
+    pub fn with_frame_size(mut self, frame_size: usize) -> Self {
+        self.frame_size = Some(frame_size);
+        self
+    }
+
+    /// Select the compression codec (recorded in `header.compression`) and
+    /// optionally pin its level.
+    pub fn with_compression(mut self, codec: CompressionCodec, level: Option<CodecLevel>) -> Self {
+        self.header.compression = codec as u16;
+        self.level = level;
+        self
+    }
+
+    /// Record the master key id in the header (`KeyRing` lookup on decrypt).
+    pub fn with_key_id(mut self, key_id: u32) -> Self {
+        self.header.key_id = key_id;
+        self
+    }
+
+    /// Bind the stream to an AAD domain (recorded in `header.aad_domain`).
+    pub fn with_aad_domain(mut self, domain: AadDomain) -> Self {
+        self.header.aad_domain = domain as u16;
+        self
+    }
+
+    /// Declare the plaintext size (sets `plaintext_size` and `HAS_TOTAL_LEN`).
+    ///
+    /// Encryption fails if the input length differs; decryption checks the
+    /// authenticated length and fails with `StreamError::TruncatedStream`.
+    pub fn with_plaintext_size(mut self, size: u64) -> Self {
+        self.header.set_plaintext_size(size);
+        self
+    }
+
+    /// Record the plaintext length in the authenticated trailer (sets `HAS_TOTAL_LEN`)
+    /// without declaring it up front, e.g. for readers or `EncryptingWriter`.
+    ///
+    /// Decryption fails with `StreamError::TruncatedStream` when trailing data
+    /// segments were dropped.
+    pub fn with_length_trailer(mut self) -> Self {
+        self.header.enable_total_len();
+        self
+    }
+
+    /// Seal each segment under its own subkey (see [`EncryptParams::per_segment_keys`]).
+    pub fn with_per_segment_keys(mut self) -> Self {
+        self.per_segment_keys = true;
+        self
+    }
+
+    /// Append an authenticated segment index after the final segment
+    /// (sets `HAS_SEGMENT_INDEX` in the header).
+    pub fn with_segment_index(mut self) -> Self {
+        self.header.enable_segment_index();
+        self
+    }
+
+    /// Write `extensions` as a TLV block after the header (see [`EncryptParams::extensions`]).
+    pub fn with_extensions(mut self, extensions: HeaderExtensions) -> Self {
+        self.extensions = Some(extensions);
+        self
+    }
+
+    /// The encoded extension block (empty without extensions).
+    pub fn extension_block(&self) -> Result<Bytes, StreamError> {
+        match &self.extensions {
+            Some(extensions) => Ok(Bytes::from(extensions.encode().map_err(StreamError::Header)?)),
+            None => Ok(Bytes::new()),
+        }
+    }
+
+    /// Compress with the registered dictionary `dict_id` (sets `DICT_USED` in the header).
+    pub fn with_dictionary(mut self, dictionaries: Arc<DictionaryRegistry>, dict_id: u32) -> Self {
+        self.header.set_dict_id(dict_id);
+        self.dictionaries = Some(dictionaries);
+        self
+    }
+
+    /// Let `selector` pick each segment's dictionary from `dictionaries`
+    /// (see [`EncryptParams::dict_selector`]).
+    ///
+    /// Combines with [`EncryptParams::with_dictionary`]: segments the selector
+    /// passes on keep the stream dictionary. Pass the same registry to both.
+    pub fn with_dict_selector(mut self, dictionaries: Arc<DictionaryRegistry>, selector: DictSelector) -> Self {
+        self.dictionaries = Some(dictionaries);
+        self.dict_selector = Some(selector);
+        self
+    }
+
     pub fn validate(&self) -> Result<(), StreamError> {
         validate_dictionary(self.dict.as_deref())?;
+        validate_dict_binding(&self.header, self.dictionaries.as_deref())?;
+        if let Some(selector) = &self.dict_selector {
+            let Some(registry) = self.dictionaries.as_deref().filter(|registry| !registry.is_empty()) else {
+                return Err(StreamError::Validation("dict_selector needs a non-empty dictionary registry".into()));
+            };
+            if selector.ids().is_empty() {
+                return Err(StreamError::Validation("dict_selector declares no dictionary ids".into()));
+            }
+            if let Some(&dict_id) = selector.ids().iter().find(|&&id| !registry.contains(id)) {
+                return Err(StreamError::Compression(CompressionError::InvalidDictionary { dict_id }));
+            }
+        }
+        if let Some(size) = self.frame_size {
+            if !is_allowed_frame_size(size) {
+                return Err(StreamError::Validation(format!(
+                    "invalid frame size: {size}, must be one of {:?}",
+                    ALLOWED_FRAME_SIZES
+                )));
+            }
+        }
+        if let Some(target) = self.pad_to {
+            let frame_size = self.frame_size.unwrap_or_else(|| get_frame_size(self.header.chunk_size as usize));
+            let min = min_segment_pad_target(self.header.chunk_size, frame_size, self.digest_alg.unwrap_or(DigestAlg::Blake3));
+            // Decryptors refuse segments beyond the limits of the chunk size
+            let max = SegmentLimits::for_chunk_size(self.header.chunk_size).max_wire_len;
+            if (target as u64) < min || target > max {
+                return Err(StreamError::Validation(format!(
+                    "invalid segment padding target: {target}, must be in {min}..={max}"
+                )));
+            }
+        }
+        self.extension_block()?;
+        if self.salt_policy == SaltPolicy::Checked && is_degenerate_salt(&self.header.salt) {
+            return Err(StreamError::Validation(format!(
+                "degenerate salt {:02x?}: use HeaderV1::new_random() or SaltPolicy::Deterministic",
+                self.header.salt
+            )));
+        }
         // If HeaderV1 has validation logic, we can enable it here:
         // self.header.validate_header()?;
         Ok(())
     }
 }
-#[derive(Clone, Debug)]
-pub struct DecryptParams;
+#[derive(Clone, Debug, Default)]
+pub struct DecryptParams {
+    /// Registry resolving `header.dict_id` for streams with `DICT_USED` set.
+    pub dictionaries: Option<Arc<DictionaryRegistry>>,
+    /// Pinned header fields; `None` accepts whatever the header claims.
+    /// - Checked by [`DecryptParams::check_header`] before any key derivation.
+    pub expected_cipher: Option<CipherSuite>,
+    pub expected_compression: Option<CompressionCodec>,
+    pub expected_key_id: Option<u32>,
+    pub expected_aad_domain: Option<AadDomain>,
+    /// Header `flags` bits this build does not know: rejected by default.
+    /// - `Tolerate` decrypts streams from a newer writer whose extra flags
+    ///   only add optional data this build can skip.
+    pub unknown_flags: UnknownFlagPolicy,
+}
 impl DecryptParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Dictionaries available to streams that reference a `dict_id`.
+    pub fn with_dictionaries(mut self, dictionaries: Arc<DictionaryRegistry>) -> Self {
+        self.dictionaries = Some(dictionaries);
+        self
+    }
+
+    /// Reject streams whose header names a different cipher suite.
+    pub fn expect_cipher(mut self, cipher: CipherSuite) -> Self {
+        self.expected_cipher = Some(cipher);
+        self
+    }
+
+    /// Reject streams whose header names a different compression codec.
+    pub fn expect_compression(mut self, codec: CompressionCodec) -> Self {
+        self.expected_compression = Some(codec);
+        self
+    }
+
+    /// Reject streams encrypted under a different master key id.
+    pub fn expect_key_id(mut self, key_id: u32) -> Self {
+        self.expected_key_id = Some(key_id);
+        self
+    }
+
+    /// State the AAD domain the stream should be bound to.
+    ///
+    /// Required for `AAD_STRICT` streams, which fail on a mismatch; any other
+    /// stream in another domain decrypts with a warning (see [`DecryptParams::aad_domain_warning`]).
+    pub fn expect_aad_domain(mut self, domain: AadDomain) -> Self {
+        self.expected_aad_domain = Some(domain);
+        self
+    }
+
+    /// Treat unknown header `flags` bits as `policy` says (see [`DecryptParams::unknown_flags`]).
+    pub fn with_unknown_flags(mut self, policy: UnknownFlagPolicy) -> Self {
+        self.unknown_flags = policy;
+        self
+    }
+
     pub fn validate(&self) -> Result<(), StreamError> {
         Ok(())
     }
+
+    /// Compare the parsed stream header against the pinned fields.
+    ///
+    /// Fails with `StreamError::Validation` naming the first mismatched field.
+    pub fn check_header(&self, header: &HeaderV1) -> Result<(), StreamError> {
+        fn pinned(field: &str, expected: Option<(String, u64)>, found: u64) -> Result<(), StreamError> {
+            match expected {
+                Some((name, raw)) if raw != found => Err(StreamError::Validation(format!(
+                    "header {field} mismatch: expected {name} ({raw:#x}), found {found:#x}"
+                ))),
+                _ => Ok(()),
+            }
+        }
+
+        pinned("cipher", self.expected_cipher.map(|c| (format!("{c:?}"), c as u64)), header.cipher as u64)?;
+        pinned(
+            "compression",
+            self.expected_compression.map(|c| (format!("{c:?}"), c as u64)),
+            header.compression as u64,
+        )?;
+        pinned("key_id", self.expected_key_id.map(|id| (id.to_string(), id as u64)), header.key_id as u64)?;
+
+        // Strict streams bind the domain for real: the caller has to name it
+        if header.flags & flags::AAD_STRICT != 0 {
+            let Some(expected) = self.expected_aad_domain else {
+                return Err(StreamError::Validation(format!(
+                    "AAD_STRICT stream bound to {}: state the expected domain with DecryptParams::expect_aad_domain",
+                    enum_name_or_hex::<AadDomain>(header.aad_domain)
+                )));
+            };
+            pinned("aad_domain", Some((format!("{expected:?}"), expected as u64)), header.aad_domain as u64)?;
+        } else if let Some(warning) = self.aad_domain_warning(header) {
+            warn!("{warning}");
+        }
+        Ok(())
+    }
+
+    /// Why a non-strict stream's AAD domain differs from the expected one, if it does.
+    ///
+    /// Decryption still goes ahead; the decrypt entry points add this to
+    /// `TelemetrySnapshot::warnings`. `None` for `AAD_STRICT` streams, which
+    /// fail in [`DecryptParams::check_header`] instead.
+    pub fn aad_domain_warning(&self, header: &HeaderV1) -> Option<String> {
+        let expected = self.expected_aad_domain?;
+        if header.flags & flags::AAD_STRICT != 0 || header.aad_domain == expected as u16 {
+            return None;
+        }
+        Some(format!(
+            "stream AAD domain is {}, expected {expected:?}",
+            enum_name_or_hex::<AadDomain>(header.aad_domain)
+        ))
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct ApiConfig {
     /// Whether to capture the output buffer in memory.
     /// - `None` or `Some(false)` → no buffer capture (production default).
@@ -43,8 +438,67 @@ pub struct ApiConfig {
     pub with_buf: Option<bool>,
 
     /// Whether to collect detailed metrics during pipeline execution.
-    /// Currently unused, reserved for future expansion.
+    ///
+    /// `Some(true)` records per-segment timings and crypto worker load
+    /// (`TelemetrySnapshot::segment_records`, `worker_usage`, `segment_stats()`).
     pub collect_metrics: Option<bool>,
+
+    /// Worker profile to use instead of the one the stream header asks for.
+    /// - `None` → [`HybridParallelismProfile::from_header`] (default).
+    pub profile: Option<HybridParallelismProfile>,
+
+    /// Input size below which a `Strategy::Auto` stream runs single-threaded.
+    /// - Defaults to [`AUTO_PARALLEL_THRESHOLD`] (4 MiB).
+    /// - Inputs of unknown size run sequentially for this many bytes, then go parallel.
+    pub auto_parallel_threshold: u64,
+
+    /// Audit log destination.
+    /// - `LogConfig::Disabled` → no log, no logger thread (default).
+    pub log: LogConfig,
+
+    /// Audit log format and frame detail.
+    /// - Defaults to text lines with frame metadata only.
+    pub log_options: LogOptions,
+
+    /// Upper bound on the captured output buffer (`with_buf`).
+    /// - `None` → unbounded (default).
+    /// - Writing past the bound fails the stream with an I/O error.
+    pub max_captured_bytes: Option<usize>,
+
+    /// Called after each segment is read and after each ordered write.
+    /// - `None` → no reporting (default).
+    /// - Returning `ControlFlow::Break` stops the run with `StreamError::Cancelled`.
+    pub progress: Option<ProgressCallback>,
+
+    /// Decrypt only: decompress each segment in the ordered writer, one at a time.
+    /// - `false` → decompression workers run in parallel (default).
+    /// - `true` → segments waiting for their turn stay compressed, so at most
+    ///   one segment of plaintext is resident, at the cost of serial decompression.
+    pub decompress_in_order: bool,
+
+    /// Decrypt only: segments read ahead on a separate thread, so the next
+    /// segment's wire is read while the current one is dispatched.
+    /// - `0` → read serially (default).
+    /// - [`DEFAULT_READ_AHEAD`](crate::stream_v2::io::DEFAULT_READ_AHEAD) (1) → double buffering.
+    /// - Each extra segment holds one more wire in memory.
+    pub read_ahead: usize,
+}
+
+impl std::fmt::Debug for ApiConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiConfig")
+            .field("with_buf", &self.with_buf)
+            .field("collect_metrics", &self.collect_metrics)
+            .field("profile", &self.profile)
+            .field("auto_parallel_threshold", &self.auto_parallel_threshold)
+            .field("log", &self.log)
+            .field("log_options", &self.log_options)
+            .field("max_captured_bytes", &self.max_captured_bytes)
+            .field("progress", &self.progress.is_some())
+            .field("decompress_in_order", &self.decompress_in_order)
+            .field("read_ahead", &self.read_ahead)
+            .finish()
+    }
 }
 
 impl Default for ApiConfig {
@@ -52,6 +506,14 @@ impl Default for ApiConfig {
         Self {
             with_buf: Some(false),      // default: no buffer
             collect_metrics: Some(false), // default: no metrics
+            profile: None,
+            auto_parallel_threshold: AUTO_PARALLEL_THRESHOLD,
+            log: LogConfig::Disabled,
+            log_options: LogOptions::default(),
+            max_captured_bytes: None,
+            progress: None,
+            decompress_in_order: false,
+            read_ahead: 0,
         }
     }
 }
@@ -61,38 +523,194 @@ impl ApiConfig {
         Self {
             with_buf: with_buf.or(Some(false)),
             collect_metrics: collect_metrics.or(Some(false)),
+            profile: None,
+            auto_parallel_threshold: AUTO_PARALLEL_THRESHOLD,
+            log: LogConfig::Disabled,
+            log_options: LogOptions::default(),
+            max_captured_bytes: None,
+            progress: None,
+            decompress_in_order: false,
+            read_ahead: 0,
         }
     }
 
     pub fn with_buf_enabled() -> Self {
-        Self { with_buf: Some(true), collect_metrics: Some(false) }
+        Self {
+            with_buf: Some(true),
+            collect_metrics: Some(false),
+            profile: None,
+            auto_parallel_threshold: AUTO_PARALLEL_THRESHOLD,
+            log: LogConfig::Disabled,
+            log_options: LogOptions::default(),
+            max_captured_bytes: None,
+            progress: None,
+            decompress_in_order: false,
+            read_ahead: 0,
+        }
+    }
+
+    /// Override the header's strategy with an explicit worker profile.
+    pub fn with_profile(mut self, profile: HybridParallelismProfile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    /// Change the size at which `Strategy::Auto` switches to parallel workers.
+    pub fn with_auto_parallel_threshold(mut self, bytes: u64) -> Self {
+        self.auto_parallel_threshold = bytes;
+        self
+    }
+
+    /// Send the audit log to `log` instead of dropping it.
+    pub fn with_log(mut self, log: LogConfig) -> Self {
+        self.log = log;
+        self
+    }
+
+    /// Pick the audit log format (text / binary) and frame detail.
+    pub fn with_log_options(mut self, options: LogOptions) -> Self {
+        self.log_options = options;
+        self
+    }
+
+    /// Cap the captured output buffer at `bytes`.
+    pub fn with_max_captured_bytes(mut self, bytes: usize) -> Self {
+        self.max_captured_bytes = Some(bytes);
+        self
+    }
+
+    /// Report progress to `progress`; returning `Break` cancels the run.
+    pub fn with_progress(mut self, progress: ProgressCallback) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Decompress in the ordered writer instead of in parallel workers (decrypt only).
+    pub fn with_decompress_in_order(mut self, enabled: bool) -> Self {
+        self.decompress_in_order = enabled;
+        self
+    }
+
+    /// Read up to `segments` segments ahead of the decrypt workers (decrypt only).
+    pub fn with_read_ahead(mut self, segments: usize) -> Self {
+        self.read_ahead = segments;
+        self
     }
 }
 
-fn setup_enc_context(master_key: &[u8], header: &HeaderV1, alg: DigestAlg)
-    -> Result<(EncryptContext, HybridParallelismProfile, Arc<AsyncLogManager>), StreamError> 
-{
-    let session_key = derive_session_key_32(master_key, header).map_err(StreamError::Crypto)?;
-    let profile = HybridParallelismProfile::dynamic(header.chunk_size as u32, 0.50, 64);
-    let context = EncryptContext::new(header.clone(), profile.clone(), &session_key, alg)
+/// Move the captured output buffer into the snapshot (no copy).
+fn attach_captured(snapshot: &mut TelemetrySnapshot, maybe_buf: Option<Arc<Mutex<Vec<u8>>>>) {
+    if let Some(arc_buf) = maybe_buf {
+        snapshot.attach_output(std::mem::take(&mut *arc_buf.lock().unwrap()));
+    }
+}
+
+/// Report what a `Discard` sink received: plaintext when decrypting, the
+/// stream (ciphertext + overhead) when encrypting.
+fn attach_discarded(snapshot: &mut TelemetrySnapshot, discarded: Option<Arc<AtomicU64>>, encrypting: bool) {
+    if let Some(written) = discarded {
+        let written = written.load(Ordering::Relaxed);
+        if encrypting {
+            snapshot.bytes_ciphertext = written.saturating_sub(snapshot.bytes_overhead);
+        } else {
+            snapshot.bytes_plaintext = written;
+        }
+    }
+}
+
+/// Seekable sinks (`File`, captured `Memory`): write the final length into a
+/// `HAS_TOTAL_LEN` header that did not declare it up front.
+///
+/// The authenticated trailer stays the reference decryption checks against.
+/// `AAD_STRICT` frames authenticate the header as written, so it is kept.
+fn patch_header_size(header: &HeaderV1, snapshot: &mut TelemetrySnapshot, file: Option<&Path>) -> Result<(), StreamError> {
+    if header.flags & flags::HAS_TOTAL_LEN == 0 || header.flags & flags::AAD_STRICT != 0 || header.plaintext_size != 0 {
+        return Ok(());
+    }
+    let patched = HeaderV1 { plaintext_size: snapshot.bytes_plaintext, ..*header };
+    if let Some(path) = file {
+        rewrite_header(&mut std::fs::OpenOptions::new().write(true).open(path)?, &patched)?;
+    }
+    if let Some(output) = snapshot.output.as_mut() {
+        rewrite_header(&mut Cursor::new(output), &patched)?;
+    }
+    Ok(())
+}
+
+/// Size-based plan for a `Strategy::Auto` header.
+///
+/// `None` when `config.profile` is set or the header pins a strategy.
+pub(crate) fn auto_plan(header: &HeaderV1, config: &ApiConfig, size_hint: Option<u64>) -> Option<AutoPlan> {
+    if config.profile.is_some() || !matches!(Strategy::try_from(header.strategy), Ok(Strategy::Auto)) {
+        return None;
+    }
+    Some(AutoPlan::for_size(size_hint, config.auto_parallel_threshold, header.chunk_size))
+}
+
+/// `config.profile`, else the `Auto` plan's profile, else the header strategy.
+fn select_profile(header: &HeaderV1, config: &ApiConfig, plan: Option<AutoPlan>) -> HybridParallelismProfile {
+    match (&config.profile, plan) {
+        (Some(profile), _) => profile.clone(),
+        (None, Some(plan)) => HybridParallelismProfile::for_auto_plan(header, plan),
+        (None, None) => HybridParallelismProfile::from_header(header),
+    }
+}
+
+/// Derive the session key and pick the worker profile for encryption.
+///
+/// `config.profile` overrides the header strategy, then `plan` (see [`auto_plan`]);
+/// the chosen CPU worker count is recorded in `parallel_hint` of the header
+/// that gets written, along with a key check value (`HAS_KEY_CHECK`).
+pub(crate) fn setup_enc_context(
+    master_key: &[u8],
+    header: &HeaderV1,
+    alg: DigestAlg,
+    frame_size: Option<usize>,
+    config: &ApiConfig,
+    plan: Option<AutoPlan>,
+) -> Result<(EncryptContext, HybridParallelismProfile, Arc<AsyncLogManager>), StreamError> {
+    let profile = select_profile(header, config, plan);
+    let mut header = *header;
+    header.parallel_hint = profile.cpu_workers() as u32;
+    // The flag is bound into the session key; the value is filled in by the context
+    header.enable_key_check();
+    let session_key = derive_session_key_32(master_key, &header).map_err(StreamError::Crypto)?;
+    let context = EncryptContext::with_frame_size(header, profile.clone(), session_key.as_slice(), alg, frame_size)
         .map_err(StreamError::SegmentWorker)?;
-    let log_manager = Arc::new(AsyncLogManager::new("stream_v2_enc.log", 100)?);
+    let log_manager = Arc::new(AsyncLogManager::from_config_with(&config.log, config.log_options, "stream_v2_enc.log", 100)?);
 
     Ok((context, profile, log_manager))
 }
 
-fn setup_dec_context(master_key: &[u8], header: &HeaderV1, alg: DigestAlg)
-    -> Result<(DecryptContext, HybridParallelismProfile, Arc<AsyncLogManager>), StreamError> 
-{
+/// Derive the session key and pick the worker profile for decryption.
+///
+/// `config.profile` overrides the strategy recorded in the stream header, then `plan`.
+/// A wrong master key fails here with `CryptoError::KeyConfirmationFailed`
+/// when the stream carries a key check value.
+pub(crate) fn setup_dec_context(
+    master_key: &[u8],
+    header: &HeaderV1,
+    alg: DigestAlg,
+    config: &ApiConfig,
+    plan: Option<AutoPlan>,
+) -> Result<(DecryptContext, HybridParallelismProfile, Arc<AsyncLogManager>), StreamError> {
     let session_key = derive_session_key_32(master_key, header).map_err(StreamError::Crypto)?;
-    let profile = HybridParallelismProfile::dynamic(header.chunk_size as u32, 0.50, 64);
-    let context = DecryptContext::from_stream_header(header.clone(), profile.clone(), &session_key, alg)
-        .map_err(StreamError::SegmentWorker)?;
-    let log_manager = Arc::new(AsyncLogManager::new("stream_v2_dec.log", 100)?);
+    let profile = select_profile(header, config, plan);
+    let context = DecryptContext::from_stream_header(*header, profile.clone(), session_key.as_slice(), alg)
+        .map_err(dec_context_error)?;
+    let log_manager = Arc::new(AsyncLogManager::from_config_with(&config.log, config.log_options, "stream_v2_dec.log", 100)?);
 
     Ok((context, profile, log_manager))
 }
 
+/// Failed key confirmation is a crypto error; other context failures stay segment worker errors.
+pub(crate) fn dec_context_error(error: SegmentWorkerError) -> StreamError {
+    match error {
+        SegmentWorkerError::CryptoError(e @ CryptoError::KeyConfirmationFailed) => StreamError::Crypto(e),
+        e => StreamError::SegmentWorker(e),
+    }
+}
+
 /// 🔐 Encrypt stream (v2)
 pub fn encrypt_stream_v2(
     input: InputSource,
@@ -103,29 +721,39 @@ pub fn encrypt_stream_v2(
 ) -> Result<TelemetrySnapshot, StreamError> {
     validate_encrypt_params(master_key, &params, None, None)?;
 
+    let plan = auto_plan(&params.header, &config, input.size_hint());
     let reader = open_input(input)?;
-    let (writer, maybe_buf) = open_output(output, config.with_buf)?;
+    let file = match &output {
+        OutputSink::File(path) => Some(path.clone()),
+        _ => None,
+    };
+    let (writer, maybe_buf, discarded) = open_output_counted(output, config.with_buf, config.max_captured_bytes)?;
 
     // ---- Read stream header ----
     let mut payload_reader = PayloadReader::new(reader);
 
-    let (mut crypto, profile, log_manager) = setup_enc_context(master_key, &params.header, DigestAlg::Blake3)?;
-    let config_pipe = PipelineConfig::new(profile, maybe_buf.clone());
+    let (crypto, profile, log_manager) = setup_enc_context(master_key, &params.stamped_header(), params.digest_alg.unwrap_or(DigestAlg::Blake3), params.frame_size, &config, plan)?;
+    let mut crypto = crypto.with_padding(params.pad_to).with_extensions(params.extension_block()?);
+    let config_pipe = PipelineConfig::new(profile, maybe_buf.clone())
+        .with_codec_level(params.level)
+        .with_dictionaries(params.dictionaries.clone())
+        .with_dict_selector(params.dict_selector.clone())
+        .with_warmup(plan.map_or(0, |plan| plan.warmup_segments()))
+        .with_progress(config.progress.clone())
+        .with_segment_metrics(config.collect_metrics == Some(true));
 
-    let mut snapshot = run_encrypt_pipeline(
+    let mut snapshot = run_encrypt_pipeline_with_outcome(
         &mut payload_reader,
         writer,
         &mut crypto,
         &config_pipe,
         log_manager,
-    )?;
+    )
+    .into_result()?;
 
-    // --- Telemetry buffer extraction for tests --- 
-    if let Some(ref arc_buf) = maybe_buf { 
-        let buf = arc_buf.lock().unwrap(); 
-        snapshot.attach_output(buf.clone()); 
-        // clone Vec<u8> into snapshot.output 
-    }
+    attach_captured(&mut snapshot, maybe_buf);
+    attach_discarded(&mut snapshot, discarded, true);
+    patch_header_size(&crypto.header, &mut snapshot, file.as_deref())?;
 
     Ok(snapshot)
 }
@@ -141,34 +769,194 @@ pub fn decrypt_stream_v2(
     //
     validate_decrypt_params(master_key, &params, None, None)?;
 
+    let size_hint = input.size_hint();
     let reader = open_input(input)?;
-    let (writer, maybe_buf) = open_output(output, config.with_buf)?;
+    let (writer, maybe_buf, discarded) = open_output_counted(output, config.with_buf, config.max_captured_bytes)?;
 
     // ---- Read stream header ----
     // Assert reader is positioned correctly
-    let (header, mut payload_reader) = PayloadReader::with_header(reader)?;
+    let (header, mut payload_reader) = PayloadReader::with_header_policy(reader, params.unknown_flags)?;
+    params.check_header(&header)?;
 
-    let (mut crypto, profile, log_manager) = setup_dec_context(master_key, &header, DigestAlg::Blake3)?;
-    let config_pipe = PipelineConfig::new(profile, maybe_buf.clone());
+    let mut snapshot =
+        decrypt_payload(&header, &mut payload_reader, writer, maybe_buf, master_key, &params, &config, size_hint)?;
+    attach_discarded(&mut snapshot, discarded, false);
+    snapshot.warnings.extend(params.aad_domain_warning(&header));
+    Ok(snapshot)
+}
 
-    let mut snapshot = run_decrypt_pipeline(
-        &mut payload_reader,
+/// 🔓 Decrypt stream (v2), selecting the master key by `header.key_id`
+///
+/// Fails with `StreamError::UnknownKeyId` when `keyring` has neither a key for
+/// the stream's `key_id` nor a default key.
+pub fn decrypt_stream_v2_with_keyring(
+    input: InputSource,
+    output: OutputSink,
+    keyring: &KeyRing,
+    params: DecryptParams,
+    config: ApiConfig,
+) -> Result<TelemetrySnapshot, StreamError> {
+    let size_hint = input.size_hint();
+    let reader = open_input(input)?;
+
+    // ---- Read stream header ----
+    let (header, mut payload_reader) = PayloadReader::with_header_policy(reader, params.unknown_flags)?;
+    params.check_header(&header)?;
+    let master_key = keyring
+        .select(header.key_id)
+        .ok_or(StreamError::UnknownKeyId { key_id: header.key_id })?;
+
+    validate_decrypt_params(master_key, &params, None, None)?;
+    let (writer, maybe_buf, discarded) = open_output_counted(output, config.with_buf, config.max_captured_bytes)?;
+
+    let mut snapshot =
+        decrypt_payload(&header, &mut payload_reader, writer, maybe_buf, master_key, &params, &config, size_hint)?;
+    attach_discarded(&mut snapshot, discarded, false);
+    snapshot.warnings.extend(params.aad_domain_warning(&header));
+    Ok(snapshot)
+}
+
+/// Shared decrypt tail once the stream header has been parsed.
+///
+/// `size_hint` is the ciphertext size; an unknown size decrypts with the
+/// full `Auto` profile, since there is no read-ahead to gate.
+#[allow(clippy::too_many_arguments)]
+fn decrypt_payload<R: Read + Send>(
+    header: &HeaderV1,
+    payload_reader: &mut PayloadReader<R>,
+    writer: Box<dyn Write + Send>,
+    maybe_buf: Option<Arc<Mutex<Vec<u8>>>>,
+    master_key: &[u8],
+    params: &DecryptParams,
+    config: &ApiConfig,
+    size_hint: Option<u64>,
+) -> Result<TelemetrySnapshot, StreamError> {
+    let plan = auto_plan(header, config, size_hint).map(|plan| match plan {
+        AutoPlan::Warmup { .. } => AutoPlan::Parallel,
+        plan => plan,
+    });
+    let (crypto, profile, log_manager) = setup_dec_context(master_key, header, DigestAlg::Blake3, config, plan)?;
+    let mut crypto = crypto.with_extensions(payload_reader.extensions().clone());
+    let config_pipe = PipelineConfig::new(profile, maybe_buf.clone())
+        .with_dictionaries(params.dictionaries.clone())
+        .with_unknown_flags(params.unknown_flags)
+        .with_progress(config.progress.clone())
+        .with_segment_metrics(config.collect_metrics == Some(true))
+        .with_decompress_in_order(config.decompress_in_order)
+        .with_read_ahead(config.read_ahead);
+
+    let mut snapshot = run_decrypt_pipeline_with_outcome(
+        payload_reader,
         writer,
         &mut crypto,
         &config_pipe,
         log_manager,
-    )?;
+    )
+    .into_result()?;
+
+    attach_captured(&mut snapshot, maybe_buf);
+
+    Ok(snapshot)
+}
+
+/// 🔓 Decrypt stream (v2), resuming at a segment boundary
+///
+/// The stream header is still read from the start of `input` (key derivation),
+/// then the reader is positioned at `resume_point.offset`. The segment found
+/// there must carry `resume_point.segment_index`; only that segment and its
+/// successors are decrypted, and the stream must still end with a final segment.
+pub fn decrypt_stream_v2_from(
+    input: InputSource,
+    output: OutputSink,
+    master_key: &[u8],
+    params: DecryptParams,
+    config: ApiConfig,
+    resume_point: ResumePoint,
+) -> Result<TelemetrySnapshot, StreamError> {
+    validate_decrypt_params(master_key, &params, None, None)?;
+
+    let (header, mut reader) = open_input_at_with(input, resume_point.offset, params.unknown_flags)?;
+    params.check_header(&header)?;
+    let (writer, maybe_buf, discarded) = open_output_counted(output, config.with_buf, config.max_captured_bytes)?;
 
-    // --- Telemetry buffer extraction for tests --- 
-    if let Some(ref arc_buf) = maybe_buf { 
-        let buf = arc_buf.lock().unwrap(); 
-        snapshot.attach_output(buf.clone()); 
-        // clone Vec<u8> into snapshot.output 
+    // ---- Validate resume boundary ----
+    let (seg_header, wire) = read_segment(&mut reader)?.ok_or_else(|| {
+        StreamError::Validation(format!("no segment at resume offset {}", resume_point.offset))
+    })?;
+    if seg_header.segment_index != resume_point.segment_index {
+        return Err(StreamError::Validation(format!(
+            "resume point mismatch: expected segment {}, found {} at offset {}",
+            resume_point.segment_index, seg_header.segment_index, resume_point.offset
+        )));
     }
 
+    // Put the peeked segment back in front of the remaining payload
+    let first = encode_segment(&seg_header, &wire).map_err(StreamError::Segment)?;
+    let limits = reader.limits();
+    let extensions = reader.extensions().clone();
+    let mut payload_reader = PayloadReader::new(Cursor::new(first).chain(reader))
+        .with_position(resume_point.offset)
+        .with_limits(limits)
+        .with_stream_flags(header.flags);
+
+    let (crypto, profile, log_manager) = setup_dec_context(master_key, &header, DigestAlg::Blake3, &config, None)?;
+    let mut crypto = crypto.with_extensions(extensions);
+    let config_pipe = PipelineConfig::new(profile, maybe_buf.clone())
+        .with_start_segment(resume_point.segment_index)
+        .with_dictionaries(params.dictionaries.clone())
+        .with_unknown_flags(params.unknown_flags)
+        .with_progress(config.progress.clone())
+        .with_segment_metrics(config.collect_metrics == Some(true))
+        .with_decompress_in_order(config.decompress_in_order)
+        .with_read_ahead(config.read_ahead);
+
+    let mut snapshot = run_decrypt_pipeline_with_outcome(
+        &mut payload_reader,
+        writer,
+        &mut crypto,
+        &config_pipe,
+        log_manager,
+    )
+    .into_result()?;
+
+    attach_captured(&mut snapshot, maybe_buf);
+    attach_discarded(&mut snapshot, discarded, false);
+    snapshot.warnings.extend(params.aad_domain_warning(&header));
+
     Ok(snapshot)
 }
 
+/// 🔐 Encrypt a small in-memory payload, returning the ciphertext.
+///
+/// `params.header.chunk_size` is lowered to the smallest allowed chunk size
+/// holding `plaintext` (see [`best_chunk_size`]), so a short payload is not
+/// framed for 64 KiB segments.
+pub fn encrypt_bytes(plaintext: &[u8], master_key: &[u8], mut params: EncryptParams) -> Result<Vec<u8>, StreamError> {
+    let fitted = best_chunk_size(Some(plaintext.len()), ChunkPolicy::RoundUp, RoundingBase::KiB { max_exp: 20 });
+    params.header.chunk_size = params.header.chunk_size.min(fitted as u32);
+
+    let snapshot = encrypt_stream_v2(
+        InputSource::Memory(plaintext.to_vec()),
+        OutputSink::Memory,
+        master_key,
+        params,
+        ApiConfig::new(Some(true), None),
+    )?;
+    snapshot.output.ok_or(StreamError::PipelineError("encrypt_bytes: no output buffer"))
+}
+
+/// 🔓 Decrypt a stream held in memory, returning the plaintext.
+pub fn decrypt_bytes(ciphertext: &[u8], master_key: &[u8], params: DecryptParams) -> Result<Vec<u8>, StreamError> {
+    let snapshot = decrypt_stream_v2(
+        InputSource::Memory(ciphertext.to_vec()),
+        OutputSink::Memory,
+        master_key,
+        params,
+        ApiConfig::new(Some(true), None),
+    )?;
+    snapshot.output.ok_or(StreamError::PipelineError("decrypt_bytes: no output buffer"))
+}
+
 
 pub fn validate_encrypt_params(
     master_key: &[u8],
@@ -185,22 +973,7 @@ pub fn validate_encrypt_params(
         }));
     }
 
-    // --- Resolve defaults ---
-    let w  = workers.unwrap_or(DEFAULT_WORKERS);
-    let q  = queue_cap.unwrap_or(DEFAULT_QUEUE_CAP);
-
-    if !WORKERS_COUNT.contains(&w) {
-        return Err(StreamError::Validation(format!(
-            "invalid workers count: {w}, must be one of {:?}",
-            WORKERS_COUNT
-        )));
-    }
-    if !QUEUE_CAPS.contains(&q) {
-        return Err(StreamError::Validation(format!(
-            "invalid queue capacity: {q}, must be one of {:?}",
-            QUEUE_CAPS
-        )));
-    }
+    validate_worker_settings(workers, queue_cap)?;
 
     params.validate()?;
     Ok(())
@@ -219,24 +992,31 @@ pub fn validate_decrypt_params(
         }));
     }
 
-    // --- Resolve defaults ---
-    let w  = workers.unwrap_or(DEFAULT_WORKERS);
-    let q  = queue_cap.unwrap_or(DEFAULT_QUEUE_CAP);
+    validate_worker_settings(workers, queue_cap)?;
 
-    if !WORKERS_COUNT.contains(&w) {
-        return Err(StreamError::Validation(format!(
-            "invalid workers count: {w}, must be one of {:?}",
-            WORKERS_COUNT
-        )));
+    params.validate()?;
+    Ok(())
+}
+
+/// Range checks for explicit worker/queue settings; `None` keeps the defaults.
+///
+/// Workers must be in `1..=num_cpus::get()`, queue caps in `1..=MAX_QUEUE_CAP`.
+pub fn validate_worker_settings(workers: Option<usize>, queue_cap: Option<usize>) -> Result<(), StreamError> {
+    let max_workers = num_cpus::get().max(1);
+    if let Some(w) = workers {
+        if !(1..=max_workers).contains(&w) {
+            return Err(StreamError::Validation(format!(
+                "invalid workers count: {w}, must be in 1..={max_workers}"
+            )));
+        }
     }
-    if !QUEUE_CAPS.contains(&q) {
-        return Err(StreamError::Validation(format!(
-            "invalid queue capacity: {q}, must be one of {:?}",
-            QUEUE_CAPS
-        )));
+    if let Some(q) = queue_cap {
+        if !(1..=MAX_QUEUE_CAP).contains(&q) {
+            return Err(StreamError::Validation(format!(
+                "invalid queue capacity: {q}, must be in 1..={MAX_QUEUE_CAP}"
+            )));
+        }
     }
-
-    params.validate()?;
     Ok(())
 }
 
@@ -255,14 +1035,15 @@ pub fn validate_dictionary(dict: Option<&[u8]>) -> Result<(), StreamError> {
     }
 }
 
-pub fn is_valid_dictionary(dict: &[u8]) -> bool {
-    // Replace with the actual validation logic:
-    // e.g. check header bytes, length constraints, codec id, etc.
-    if dict.len() < MIN_DICT_LEN || dict.len() > MAX_DICT_LEN {
-        return false;
-    }
+pub use crate::compression::is_valid_dictionary;
 
-    // First 4 bytes to be a magic number
-    let magic = MAGIC_DICT;
-    dict.len() >= magic.len() && &dict[..magic.len()] == magic
+/// A header with `DICT_USED` must reference a dictionary present in `dictionaries`.
+pub fn validate_dict_binding(header: &HeaderV1, dictionaries: Option<&DictionaryRegistry>) -> Result<(), StreamError> {
+    if header.flags & flags::DICT_USED == 0 {
+        return Ok(());
+    }
+    match dictionaries {
+        Some(registry) if registry.contains(header.dict_id) => Ok(()),
+        _ => Err(StreamError::Compression(CompressionError::InvalidDictionary { dict_id: header.dict_id })),
+    }
 }
@@ -5,62 +5,72 @@ use std::time::Instant;
 use bytes::Bytes;
 use crossbeam::channel::{Receiver, Sender};
 
-use crate::crypto::AadHeader;
+use crate::crypto::{AadHeader, CryptoError, TAG_LEN};
 use crate::crypto::{
-    aad::build_aad,
-    aead::AeadImpl,
-    nonce::derive_nonce_12_tls_style,
+    aad::build_aad_with_extensions,
+    nonce::{derive_nonce_12_tls_style, frame_nonce_counter},
 };
 use crate::headers::types::HeaderV1;
-use crate::stream_v2::framing::{FrameHeader, FrameType};
-use crate::stream_v2::framing::decode::{decode_frame};
+use crate::stream_v2::framing::{FrameError, FrameHeader, FrameType};
+use crate::stream_v2::framing::decode::{decode_frame, parse_frame_header};
 use crate::telemetry::{Stage, StageTimes};
-use super::types::{FrameWorkerError, DecryptedFrame};
+use super::pool::catch_frame_panic;
+use super::types::{DecryptFrameInput, FrameKeys, FrameWorkerError, DecryptedFrame};
 
 pub struct DecryptFrameWorker {
     header: HeaderV1,
-    aead: AeadImpl,
+    /// Raw header extension block, bound into the AAD under `AAD_STRICT`.
+    extensions: Bytes,
+    keys: FrameKeys,
 }
 
 impl DecryptFrameWorker {
     pub fn new(header: HeaderV1, session_key: &[u8]) -> Result<Self, FrameWorkerError> {
-        let aead = AeadImpl::from_header_and_key(&header, session_key)?;
-        Ok(Self { header, aead })
+        let keys = FrameKeys::for_header(&header, session_key)?;
+        Ok(Self { header, extensions: Bytes::new(), keys })
+    }
+
+    /// Bind the stream's header extension block (see `build_aad_with_extensions`).
+    pub fn with_extensions(mut self, extensions: Bytes) -> Self {
+        self.extensions = extensions;
+        self
     }
     pub fn decrypt_frame(
         &self,
-        wire: Bytes,
+        input: DecryptFrameInput,
     ) -> Result<DecryptedFrame, FrameWorkerError> {
+        let DecryptFrameInput { wire, segment_flags } = input;
         let mut stage_times = StageTimes::default();
         
         // 1️⃣ Parse header
         let start = Instant::now();
-        let view = decode_frame(&wire)?;
+        let view = decode_frame(&wire).map_err(|e| FrameWorkerError::from_decode(e, wire.len()))?;
         // Decoding
         stage_times.add(Stage::Decode, start.elapsed());
 
         // Validation
         let start = Instant::now();
         let ct_start = FrameHeader::LEN;
-        let ct_end = ct_start + view.header.ciphertext_len as usize;
+        let ct_end = view.header.total_len().map_err(FrameWorkerError::MalformedFrameHeader)?;
 
         if ct_end > wire.len() {
-            return Err(FrameWorkerError::InvalidInput("Wire length mismatch detected".into()));
+            return Err(FrameWorkerError::Truncated { needed: ct_end, have: wire.len() });
         }
 
         let aad_header = AadHeader {
-            frame_type: view.header.frame_type.try_to_u8()?,
+            frame_type: view.header.frame_type.try_to_u8().map_err(FrameWorkerError::MalformedFrameHeader)?,
             segment_index: view.header.segment_index,
             frame_index: view.header.frame_index,
             plaintext_len: view.header.plaintext_len,
+            segment_flags: segment_flags.bits(),
         };
         // rebuild AAD
-        let aad = build_aad(&self.header, &aad_header)?;
+        let aad = build_aad_with_extensions(&self.header, &self.extensions, &aad_header)?;
 
         // derive nonce
         let nonce = derive_nonce_12_tls_style(
             &self.header.salt,
-            view.header.frame_index as u64,
+            frame_nonce_counter(view.header.segment_index, view.header.frame_index),
         )?;
         stage_times.add(Stage::Validate, start.elapsed());
 
@@ -68,9 +78,23 @@ impl DecryptFrameWorker {
         // Decryption
         let start = Instant::now();
         let plaintext: Vec<u8> = match view.header.frame_type {
-            FrameType::Data | FrameType::Digest => {
-                // Normal AEAD decryption
-                self.aead.open(&nonce, &aad, view.ciphertext)?
+            FrameType::Data | FrameType::Digest | FrameType::Padding => {
+                if view.ciphertext.len() < TAG_LEN {
+                    return Err(FrameWorkerError::MalformedFrameHeader(FrameError::Malformed(
+                        format!("ciphertext_len {} shorter than the AEAD tag", view.ciphertext.len()),
+                    )));
+                }
+                // Normal AEAD decryption; a tag mismatch is an authentication
+                // failure of this very frame
+                self.keys.with_aead(view.header.segment_index, |aead| {
+                    aead.open(&nonce, &aad, view.ciphertext).map_err(|e| match e {
+                        CryptoError::TagMismatch => FrameWorkerError::AuthenticationFailed {
+                            segment_index: view.header.segment_index,
+                            frame_index: view.header.frame_index,
+                        },
+                        e => FrameWorkerError::from(e),
+                    })
+                })?
                 // return FrameOutput with plaintext
             }
             FrameType::Terminator => {
@@ -98,19 +122,30 @@ impl DecryptFrameWorker {
         // * plaintext **must be owned** (crypto output)
     }
 
+    /// [`decrypt_frame`](Self::decrypt_frame) with failures tagged by frame
+    /// when the frame header is readable, so the segment worker can route
+    /// them like frames.
+    pub fn decrypt_tagged(&self, input: DecryptFrameInput) -> Result<DecryptedFrame, FrameWorkerError> {
+        let wire = input.wire.clone();
+        self.decrypt_frame(input).map_err(|e| match parse_frame_header(&wire) {
+            Ok(h) => e.at(h.segment_index, h.frame_index),
+            Err(_) => e,
+        })
+    }
+
     /// ## Step 1: Turn `DecryptFrameWorker` into a real worker
     /// ### 1. **Frame Workers**: Return Results (No Panics)
     /// Frame workers should **never panic** - they should always return `Result`:
     pub fn run(
         self,
-        rx: Receiver<Bytes>,
+        rx: Receiver<DecryptFrameInput>,
         tx: Sender<Result<DecryptedFrame, FrameWorkerError>>,
     ) {
         std::thread::spawn(move || {
             // We use a reference to the sender 'tx' inside the loop 
             // to ensure it's only dropped when this thread exits.
             while let Ok(input) = rx.recv() {
-                let result = self.decrypt_frame(input);
+                let result = catch_frame_panic(|| self.decrypt_tagged(input));
                 // Always send result (Ok or Err)
                 if tx.send(result).is_err() {
                     // Segment worker dropped rx, exit cleanly
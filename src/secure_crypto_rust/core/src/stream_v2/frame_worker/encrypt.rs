@@ -6,25 +6,34 @@ use bytes::Bytes;
 use crossbeam::channel::{Receiver, Sender};
 use crate::crypto::types::AadHeader;
 use crate::crypto::{
-    aad::{build_aad},
-    aead::AeadImpl,
-    nonce::derive_nonce_12_tls_style,
+    aad::build_aad_with_extensions,
+    nonce::{derive_nonce_12_tls_style, frame_nonce_counter},
 };
 use crate::headers::types::{HeaderV1};
 use crate::stream_v2::framing::{FrameHeader, FrameType};
 use crate::stream_v2::framing::encode::encode_frame;
+use crate::stream_v2::framing::types::len_to_u32;
 use crate::telemetry::{Stage, StageTimes};
-use super::types::{FrameInput, FrameWorkerError, EncryptedFrame};
+use super::pool::catch_frame_panic;
+use super::types::{FrameInput, FrameKeys, FrameWorkerError, EncryptedFrame};
 
 pub struct EncryptFrameWorker {
     header: HeaderV1,
-    aead: AeadImpl,
+    /// Raw header extension block, bound into the AAD under `AAD_STRICT`.
+    extensions: Bytes,
+    keys: FrameKeys,
 }
 
 impl EncryptFrameWorker {
     pub fn new(header: HeaderV1, session_key: &[u8]) -> Result<Self, FrameWorkerError> {
-        let aead = AeadImpl::from_header_and_key(&header, session_key)?;
-        Ok(Self { header, aead })
+        let keys = FrameKeys::for_header(&header, session_key)?;
+        Ok(Self { header, extensions: Bytes::new(), keys })
+    }
+
+    /// Bind the stream's header extension block (see `build_aad_with_extensions`).
+    pub fn with_extensions(mut self, extensions: Bytes) -> Self {
+        self.extensions = extensions;
+        self
     }
 
     pub fn encrypt_frame(
@@ -37,21 +46,22 @@ impl EncryptFrameWorker {
         let start = Instant::now();
         input.validate()?;
 
-        let plaintext_len = input.plaintext.len() as u32;
+        let plaintext_len = len_to_u32("plaintext_len", input.plaintext.len())?;
         let aad_header = AadHeader {
             frame_type: input.frame_type.try_to_u8()?,
             segment_index: input.segment_index,
             frame_index: input.frame_index,
             plaintext_len: plaintext_len,
+            segment_flags: input.segment_flags.bits(),
         };
 
         // 1️⃣ Build AAD from immutable fields only
-        let aad = build_aad(&self.header, &aad_header)?;
+        let aad = build_aad_with_extensions(&self.header, &self.extensions, &aad_header)?;
 
-        // 2️⃣ Derive nonce (frame_index-based)
+        // 2️⃣ Derive nonce (segment_index and frame_index based)
         let nonce = derive_nonce_12_tls_style(
             &self.header.salt,
-            input.frame_index as u64,
+            frame_nonce_counter(input.segment_index, input.frame_index),
         )?;
         stage_times.add(Stage::Validate, start.elapsed());
 
@@ -59,9 +69,9 @@ impl EncryptFrameWorker {
         // Encryption
         let start = Instant::now();
         let ciphertext: Vec<u8> = match input.frame_type {
-            FrameType::Data | FrameType::Digest => {
+            FrameType::Data | FrameType::Digest | FrameType::Padding => {
                 // normal encryption path
-                self.aead.seal(&nonce, &aad, &input.plaintext)?
+                self.keys.with_aead(input.segment_index, |aead| aead.seal(&nonce, &aad, &input.plaintext).map_err(FrameWorkerError::from))?
                 // build frame with ciphertext
             }
             FrameType::Terminator => {
@@ -78,7 +88,7 @@ impl EncryptFrameWorker {
             frame_index: input.frame_index,
             plaintext_len: plaintext_len,
             // 4️⃣ Fill mutable fields
-            ciphertext_len: ciphertext.len() as u32,
+            ciphertext_len: len_to_u32("ciphertext_len", ciphertext.len())?,
         };
 
         // Encoding
@@ -104,6 +114,13 @@ impl EncryptFrameWorker {
         // ✔ range tracked
     }
 
+    /// [`encrypt_frame`](Self::encrypt_frame) with failures tagged by frame,
+    /// so the segment worker can route them like frames.
+    pub fn encrypt_tagged(&self, input: &FrameInput) -> Result<EncryptedFrame, FrameWorkerError> {
+        self.encrypt_frame(input)
+            .map_err(|e| e.at(input.segment_index, input.frame_index))
+    }
+
     /// ## Step 1: Turn `EncryptFrameWorker` into a real worker
     /// ### 1. **Frame Workers**: Return Results (No Panics)
     /// Frame workers should **never panic** - they should always return `Result`:
@@ -114,7 +131,7 @@ impl EncryptFrameWorker {
     ) {
         std::thread::spawn(move || {
             while let Ok(input) = rx.recv() {
-                let result = self.encrypt_frame(&input);
+                let result = catch_frame_panic(|| self.encrypt_tagged(&input));
             
                 // Always send the result (Ok or Err)
                 if tx.send(result).is_err() {
@@ -16,10 +16,23 @@
 pub mod types;
 pub mod encrypt;
 pub mod decrypt;
+pub mod pool;
 
 pub use types::{
     FrameInput,
+    DecryptFrameInput,
     EncryptedFrame,
     DecryptedFrame,
     FrameWorkerError,
-};
\ No newline at end of file
+};
+
+pub use pool::{
+    FrameDispatch,
+    recv_frame,
+    FrameWorkerPool,
+    FramePoolHandle,
+    EncryptFramePool,
+    DecryptFramePool,
+    EncryptFramePoolHandle,
+    DecryptFramePoolHandle,
+};
@@ -0,0 +1,253 @@
+// # 📂 `src/stream_v2/frame_worker/pool.rs`
+
+//! Frame worker pools shared by every segment worker of a pipeline run.
+//!
+//! Jobs carry the reply channel of the segment worker that sent them, and
+//! responses keep their `(segment_index, frame_index)`, so segments from
+//! different segment workers can interleave on the same frame workers.
+//!
+//! A panic while processing a job is caught and answered with
+//! `FrameWorkerError::WorkerPanicked`, so the segment worker waiting on that
+//! frame gets an error instead of waiting forever.
+
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use bytes::Bytes;
+use crossbeam::channel::{Receiver, RecvTimeoutError, Sender, bounded, unbounded};
+use tracing::debug;
+
+use crate::headers::types::HeaderV1;
+use crate::utils::panic_message;
+use super::decrypt::DecryptFrameWorker;
+use super::encrypt::EncryptFrameWorker;
+use super::types::{DecryptFrameInput, DecryptedFrame, EncryptedFrame, FrameInput, FrameWorkerError};
+
+/// Where a frame worker sends one response.
+pub type FrameReply<R> = Sender<Result<R, FrameWorkerError>>;
+
+/// One frame to process, plus the channel its response goes back on.
+pub struct FrameJob<T, R> {
+    pub input: T,
+    pub reply: FrameReply<R>,
+}
+
+/// Sends frames to frame workers.
+///
+/// Implemented for a plain `Sender` (a private pool with one response
+/// channel) and for [`FrameJobSender`] (a shared pool).
+pub trait FrameDispatch<T> {
+    fn dispatch(&self, input: T) -> Result<(), FrameWorkerError>;
+
+    /// Whether any frame worker is left to answer dispatched frames.
+    ///
+    /// A plain `Sender` reports `true`: its response channel disconnects on
+    /// its own once the workers holding it are gone.
+    fn workers_alive(&self) -> bool {
+        true
+    }
+}
+
+impl<T> FrameDispatch<T> for Sender<T> {
+    fn dispatch(&self, input: T) -> Result<(), FrameWorkerError> {
+        self.send(input).map_err(|_| FrameWorkerError::WorkerDisconnected)
+    }
+}
+
+/// A segment worker's connection to a shared pool; see [`FramePoolHandle::connect`].
+pub struct FrameJobSender<T, R> {
+    jobs: Sender<FrameJob<T, R>>,
+    reply: FrameReply<R>,
+    alive: Arc<AtomicUsize>,
+}
+
+impl<T, R> FrameDispatch<T> for FrameJobSender<T, R> {
+    fn dispatch(&self, input: T) -> Result<(), FrameWorkerError> {
+        if !self.workers_alive() {
+            return Err(FrameWorkerError::WorkerDisconnected);
+        }
+        self.jobs
+            .send(FrameJob { input, reply: self.reply.clone() })
+            .map_err(|_| FrameWorkerError::WorkerDisconnected)
+    }
+
+    // The sender keeps its own reply channel open, so a dead pool never
+    // disconnects it: count the worker threads instead
+    fn workers_alive(&self) -> bool {
+        self.alive.load(Ordering::Acquire) > 0
+    }
+}
+
+/// Cloneable handle on a [`FrameWorkerPool`], handed to each segment worker.
+pub struct FramePoolHandle<T, R> {
+    jobs: Sender<FrameJob<T, R>>,
+    alive: Arc<AtomicUsize>,
+}
+
+impl<T, R> Clone for FramePoolHandle<T, R> {
+    fn clone(&self) -> Self {
+        Self { jobs: self.jobs.clone(), alive: self.alive.clone() }
+    }
+}
+
+impl<T, R> FramePoolHandle<T, R> {
+    /// A dispatcher and the receiver its responses arrive on.
+    pub fn connect(&self) -> (FrameJobSender<T, R>, Receiver<Result<R, FrameWorkerError>>) {
+        let (reply, responses) = unbounded();
+        (FrameJobSender { jobs: self.jobs.clone(), reply, alive: self.alive.clone() }, responses)
+    }
+}
+
+/// How often a waiting segment worker re-checks [`FrameDispatch::workers_alive`].
+pub const FRAME_LIVENESS_POLL: Duration = Duration::from_millis(100);
+
+/// Next frame response on `out_rx`.
+///
+/// Fails with `WorkerDisconnected` when the channel closes, or when no
+/// response is pending and `dispatch` reports its workers gone.
+pub fn recv_frame<T, R>(
+    out_rx: &Receiver<Result<R, FrameWorkerError>>,
+    dispatch: &impl FrameDispatch<T>,
+) -> Result<Result<R, FrameWorkerError>, FrameWorkerError> {
+    loop {
+        match out_rx.recv_timeout(FRAME_LIVENESS_POLL) {
+            Ok(response) => return Ok(response),
+            Err(RecvTimeoutError::Timeout) if dispatch.workers_alive() => {}
+            Err(_) => return Err(FrameWorkerError::WorkerDisconnected),
+        }
+    }
+}
+
+/// Process one job, turning a panic into `WorkerPanicked`.
+pub(crate) fn catch_frame_panic<R>(
+    process: impl FnOnce() -> Result<R, FrameWorkerError>,
+) -> Result<R, FrameWorkerError> {
+    catch_unwind(AssertUnwindSafe(process)).unwrap_or_else(|payload| {
+        let msg = panic_message(&*payload);
+        debug!(panic = %msg, "frame worker panicked");
+        Err(FrameWorkerError::WorkerPanicked(msg))
+    })
+}
+
+/// Decrements the live worker count when a worker thread ends, unwinding included.
+struct AliveGuard(Arc<AtomicUsize>);
+
+impl Drop for AliveGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// A fixed set of frame worker threads.
+///
+/// Workers exit once the pool and every [`FramePoolHandle`] are dropped;
+/// [`shutdown`](Self::shutdown) additionally waits for them.
+pub struct FrameWorkerPool<T, R> {
+    jobs: Sender<FrameJob<T, R>>,
+    workers: Vec<JoinHandle<()>>,
+    alive: Arc<AtomicUsize>,
+}
+
+pub type EncryptFramePool = FrameWorkerPool<FrameInput, EncryptedFrame>;
+pub type DecryptFramePool = FrameWorkerPool<DecryptFrameInput, DecryptedFrame>;
+pub type EncryptFramePoolHandle = FramePoolHandle<FrameInput, EncryptedFrame>;
+pub type DecryptFramePoolHandle = FramePoolHandle<DecryptFrameInput, DecryptedFrame>;
+
+impl EncryptFramePool {
+    /// `workers` encrypt frame workers (at least one) for one stream.
+    pub fn encrypt(header: &HeaderV1, session_key: &[u8], workers: usize) -> Result<Self, FrameWorkerError> {
+        Self::encrypt_with_extensions(header, &Bytes::new(), session_key, workers)
+    }
+
+    /// Like [`EncryptFramePool::encrypt`], for a stream with a header extension block.
+    pub fn encrypt_with_extensions(
+        header: &HeaderV1,
+        extensions: &Bytes,
+        session_key: &[u8],
+        workers: usize,
+    ) -> Result<Self, FrameWorkerError> {
+        let frame_workers = (0..workers.max(1))
+            .map(|_| EncryptFrameWorker::new(*header, session_key).map(|w| w.with_extensions(extensions.clone())))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::from_workers(frame_workers, |worker, input| worker.encrypt_tagged(&input)))
+    }
+}
+
+impl DecryptFramePool {
+    /// `workers` decrypt frame workers (at least one) for one stream.
+    pub fn decrypt(header: &HeaderV1, session_key: &[u8], workers: usize) -> Result<Self, FrameWorkerError> {
+        Self::decrypt_with_extensions(header, &Bytes::new(), session_key, workers)
+    }
+
+    /// Like [`DecryptFramePool::decrypt`], for a stream with a header extension block.
+    pub fn decrypt_with_extensions(
+        header: &HeaderV1,
+        extensions: &Bytes,
+        session_key: &[u8],
+        workers: usize,
+    ) -> Result<Self, FrameWorkerError> {
+        let frame_workers = (0..workers.max(1))
+            .map(|_| DecryptFrameWorker::new(*header, session_key).map(|w| w.with_extensions(extensions.clone())))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::from_workers(frame_workers, |worker, input| worker.decrypt_tagged(input)))
+    }
+}
+
+impl<T: Send + 'static, R: Send + 'static> FrameWorkerPool<T, R> {
+    /// One thread per entry of `frame_workers`, each answering jobs with `process`.
+    ///
+    /// The stock pools are built by [`EncryptFramePool::encrypt`] and
+    /// [`DecryptFramePool::decrypt`]; this is the seam for custom workers.
+    pub fn from_workers<W, F>(frame_workers: Vec<W>, process: F) -> Self
+    where
+        W: Send + 'static,
+        F: Fn(&W, T) -> Result<R, FrameWorkerError> + Send + Clone + 'static,
+    {
+        let (jobs, rx) = bounded::<FrameJob<T, R>>(frame_workers.len() * 4);
+        let alive = Arc::new(AtomicUsize::new(frame_workers.len()));
+        let workers = frame_workers
+            .into_iter()
+            .map(|worker| {
+                let rx = rx.clone();
+                let process = process.clone();
+                let guard = AliveGuard(alive.clone());
+                std::thread::spawn(move || {
+                    let _guard = guard;
+                    while let Ok(job) = rx.recv() {
+                        let FrameJob { input, reply } = job;
+                        // A gone segment worker only affects its own jobs
+                        let _ = reply.send(catch_frame_panic(|| process(&worker, input)));
+                    }
+                })
+            })
+            .collect();
+        Self { jobs, workers, alive }
+    }
+}
+
+impl<T, R> FrameWorkerPool<T, R> {
+    pub fn handle(&self) -> FramePoolHandle<T, R> {
+        FramePoolHandle { jobs: self.jobs.clone(), alive: self.alive.clone() }
+    }
+
+    /// Number of frame worker threads in the pool.
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Close the pool and wait for its workers.
+    ///
+    /// Returns once every handle has been dropped as well.
+    pub fn shutdown(self) {
+        let FrameWorkerPool { jobs, workers, .. } = self;
+        drop(jobs);
+        for worker in workers {
+            if worker.join().is_err() {
+                debug!("frame worker panicked");
+            }
+        }
+    }
+}
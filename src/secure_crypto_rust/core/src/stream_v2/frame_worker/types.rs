@@ -1,8 +1,14 @@
+use std::collections::VecDeque;
 use std::fmt;
+use std::sync::Mutex;
 use bytes::Bytes;
+use zeroize::Zeroizing;
 
-use crate::stream_v2::framing::types::{FrameError, FrameType};
-use crate::crypto::types::{CryptoError, NonceError, AadError};
+use crate::stream_v2::framing::types::{FrameError, FrameHeader, FrameType};
+use crate::stream_v2::segmenting::types::SegmentFlags;
+use crate::crypto::types::{CryptoError, NonceError, AadError, KEY_LEN_32};
+use crate::crypto::{aead::AeadImpl, kdf::derive_segment_key};
+use crate::headers::types::HeaderV1;
 use crate::telemetry::StageTimes;
 
 #[derive(Debug)]
@@ -12,11 +18,24 @@ pub enum FrameWorkerError {
     InvalidHeader,
     WorkerDisconnected,
     WorkerMissing,
+    /// A frame worker panicked while processing a frame; carries the panic message.
+    WorkerPanicked(String),
     
     Crypto(CryptoError),
     Nonce(NonceError),
     Aad(AadError),
     Framing(FrameError),
+
+    /// The frame parsed but its AEAD tag did not verify: wrong key, or tampered
+    /// ciphertext, header or AAD.
+    AuthenticationFailed { segment_index: u32, frame_index: u32 },
+    /// The frame header is not a valid frame header.
+    MalformedFrameHeader(FrameError),
+    /// The frame claims `needed` bytes but only `have` are present.
+    Truncated { needed: usize, have: usize },
+
+    /// Failure of a specific frame, as reported by a pooled worker.
+    AtFrame { segment_index: u32, frame_index: u32, source: Box<FrameWorkerError> },
 }
 // #[derive(Debug, Error)]
 // pub enum FrameWorkerError {
@@ -44,17 +63,71 @@ impl fmt::Display for FrameWorkerError {
             CryptoFailure(msg) => write!(f, "crypto failure: {}", msg),
             WorkerDisconnected => write!(f, "fatal error: {}", "Frame worker disconnected unexpectedly"),
             WorkerMissing => write!(f, "fatal error: {}", "Frame worker is not allocated"),
+            WorkerPanicked(msg) => write!(f, "fatal error: frame worker panicked: {}", msg),
             InvalidHeader => write!(f, "invalid header: {}", "Invalid frame header"),
 
             Crypto(e) => write!(f, "crypto error: {}", e),
             Nonce(e) => write!(f, "nonce error: {}", e),
             Aad(e) => write!(f, "aad error: {}", e),
             Framing(e) => write!(f, "framing error: {}", e),
+            AuthenticationFailed { segment_index, frame_index } =>
+                write!(f, "authentication failed for segment {} frame {}", segment_index, frame_index),
+            MalformedFrameHeader(e) => write!(f, "malformed frame header: {}", e),
+            Truncated { needed, have } => write!(f, "truncated frame: need {} bytes, have {}", needed, have),
+            AtFrame { segment_index, frame_index, source } =>
+                write!(f, "segment {} frame {}: {}", segment_index, frame_index, source),
         }
     }
 }
 
-impl std::error::Error for FrameWorkerError {}
+impl FrameWorkerError {
+    /// Tag the error with the frame it was raised for.
+    pub fn at(self, segment_index: u32, frame_index: u32) -> Self {
+        FrameWorkerError::AtFrame { segment_index, frame_index, source: Box::new(self) }
+    }
+
+    /// `(segment_index, frame_index)` of a tagged error.
+    pub fn frame_tag(&self) -> Option<(u32, u32)> {
+        match self {
+            FrameWorkerError::AtFrame { segment_index, frame_index, .. } => Some((*segment_index, *frame_index)),
+            _ => None,
+        }
+    }
+
+    /// Classify a frame decode failure over a buffer of `have` bytes: short
+    /// input is [`FrameWorkerError::Truncated`], anything else a
+    /// [`FrameWorkerError::MalformedFrameHeader`].
+    pub fn from_decode(e: FrameError, have: usize) -> Self {
+        match e {
+            FrameError::Truncated => FrameWorkerError::Truncated { needed: FrameHeader::LEN, have },
+            FrameError::LengthMismatch { expected, actual } if actual < expected =>
+                FrameWorkerError::Truncated { needed: expected, have: actual },
+            e => FrameWorkerError::MalformedFrameHeader(e),
+        }
+    }
+
+    /// The underlying error, without the frame tag.
+    pub fn untagged(&self) -> &FrameWorkerError {
+        match self {
+            FrameWorkerError::AtFrame { source, .. } => source.untagged(),
+            other => other,
+        }
+    }
+}
+
+impl std::error::Error for FrameWorkerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FrameWorkerError::Crypto(e) => Some(e),
+            FrameWorkerError::Nonce(e) => Some(e),
+            FrameWorkerError::Aad(e) => Some(e),
+            FrameWorkerError::Framing(e) => Some(e),
+            FrameWorkerError::MalformedFrameHeader(e) => Some(e),
+            FrameWorkerError::AtFrame { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
 
 impl From<CryptoError> for FrameWorkerError {
     fn from(e: CryptoError) -> Self {
@@ -87,6 +160,16 @@ pub struct FrameInput {
     pub frame_index: u32,
     pub frame_type: FrameType,
     pub plaintext: Bytes, // 🔥 instead of Arc<[u8]>
+    /// Flags of the segment header; bound into the frame AAD.
+    pub segment_flags: SegmentFlags,
+}
+
+/// One sealed frame to decrypt, with the flags of the segment header it came
+/// under (they are part of the frame AAD).
+#[derive(Debug, Clone)]
+pub struct DecryptFrameInput {
+    pub wire: Bytes,
+    pub segment_flags: SegmentFlags,
 }
 
 // ## ✅ Policy for `FrameType::Digest`
@@ -115,6 +198,8 @@ impl FrameInput {
                     ));
                 }
             }
+            // Any length, empty included: padding is discarded on decrypt
+            FrameType::Padding => {}
             FrameType::Digest => {
                 if self.plaintext.len() < 4 {
                     return Err(FrameWorkerError::InvalidInput(
@@ -185,3 +270,70 @@ impl DecryptedFrame {
 // ✔ zero-copy ciphertext
 // ✔ reorderable
 // ✔ lifetime-safe
+
+/// Per-segment ciphers a frame worker keeps (see [`FrameKeys`]).
+const SEGMENT_CIPHER_CACHE: usize = 16;
+
+/// AEAD key of a frame worker.
+///
+/// Without `PER_SEGMENT_KEYS` every frame is sealed under the session key.
+/// With it, each segment has its own subkey (`derive_segment_key`), derived
+/// on the first frame of that segment a worker sees. Segment workers share
+/// the frame pool, so frames of the segments in flight interleave: the
+/// ciphers of the last [`SEGMENT_CIPHER_CACHE`] segments are kept, oldest
+/// evicted first.
+pub(crate) enum FrameKeys {
+    Session(Box<AeadImpl>),
+    PerSegment {
+        header: HeaderV1,
+        session_key: Zeroizing<[u8; KEY_LEN_32]>,
+        ciphers: Mutex<VecDeque<(u32, AeadImpl)>>,
+    },
+}
+
+impl FrameKeys {
+    pub(crate) fn for_header(header: &HeaderV1, session_key: &[u8]) -> Result<Self, FrameWorkerError> {
+        if !header.per_segment_keys() {
+            return Ok(Self::Session(Box::new(AeadImpl::from_header_and_key(header, session_key)?)));
+        }
+        if session_key.len() != KEY_LEN_32 {
+            return Err(CryptoError::InvalidKeyLen { expected: KEY_LEN_32, actual: session_key.len() }.into());
+        }
+        // Copied straight into the wiped buffer: no stack copy left behind
+        let mut key = Zeroizing::new([0u8; KEY_LEN_32]);
+        key.copy_from_slice(session_key);
+        Ok(Self::PerSegment {
+            header: *header,
+            session_key: key,
+            ciphers: Mutex::new(VecDeque::with_capacity(SEGMENT_CIPHER_CACHE)),
+        })
+    }
+
+    /// Run `f` with the cipher of `segment_index`.
+    pub(crate) fn with_aead<T>(
+        &self,
+        segment_index: u32,
+        f: impl FnOnce(&AeadImpl) -> Result<T, FrameWorkerError>,
+    ) -> Result<T, FrameWorkerError> {
+        match self {
+            Self::Session(aead) => f(aead),
+            Self::PerSegment { header, session_key, ciphers } => {
+                // A panic while holding the lock leaves valid ciphers behind
+                let mut ciphers = ciphers.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                let slot = match ciphers.iter().position(|(index, _)| *index == segment_index) {
+                    Some(slot) => slot,
+                    None => {
+                        let subkey = derive_segment_key(session_key, segment_index);
+                        let aead = AeadImpl::from_header_and_key(header, &subkey[..])?;
+                        if ciphers.len() == SEGMENT_CIPHER_CACHE {
+                            ciphers.pop_front();
+                        }
+                        ciphers.push_back((segment_index, aead));
+                        ciphers.len() - 1
+                    }
+                };
+                f(&ciphers[slot].1)
+            }
+        }
+    }
+}
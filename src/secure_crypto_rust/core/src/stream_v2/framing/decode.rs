@@ -1,7 +1,7 @@
 use byteorder::{LittleEndian, ByteOrder};
 
-use crate::stream_v2::framing::types::{FRAME_MAGIC, FRAME_VERSION, FrameView};
-use crate::stream_v2::framing::types::{FrameType, FrameHeader, FrameError};
+use crate::stream_v2::framing::types::{FRAME_MAGIC, FRAME_VERSION, FrameView, MAX_FRAME_CIPHERTEXT_LEN};
+use crate::stream_v2::framing::types::{FrameType, FrameHeader, FrameError, u32_to_len};
 
 #[inline]
 pub fn parse_frame_header(wire: &[u8]) -> Result<FrameHeader, FrameError> {
@@ -42,6 +42,10 @@ pub fn parse_frame_header(wire: &[u8]) -> Result<FrameHeader, FrameError> {
     // off += 4;
 
     let ciphertext_len = LittleEndian::read_u32(&wire[off..off + 4]);
+    // Reject absurd lengths before anyone slices or allocates by them
+    if !u32_to_len("ciphertext_len", ciphertext_len).is_ok_and(|len| len <= MAX_FRAME_CIPHERTEXT_LEN) {
+        return Err(FrameError::CiphertextTooLarge { have: ciphertext_len, max: MAX_FRAME_CIPHERTEXT_LEN });
+    }
 
     Ok(FrameHeader {
         frame_type,
@@ -67,7 +71,7 @@ pub fn decode_frame_header(buf: &[u8]) -> Result<FrameHeader, FrameError> {
 pub fn decode_frame(wire: &[u8]) -> Result<FrameView<'_>, FrameError> {
     let header = parse_frame_header(wire)?;
 
-    let expected_len = FrameHeader::LEN + header.ciphertext_len as usize;
+    let expected_len = header.total_len()?;
     if wire.len() != expected_len {
         return Err(FrameError::LengthMismatch {
             expected: expected_len,
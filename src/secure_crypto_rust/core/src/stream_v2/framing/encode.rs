@@ -2,6 +2,21 @@ use byteorder::{LittleEndian, WriteBytesExt};
 
 use crate::stream_v2::framing::types::{FRAME_VERSION, FRAME_MAGIC};
 use crate::stream_v2::framing::types::{FrameHeader, FrameError};
+use crate::headers::layout::{Endianness, FieldSpec, contiguous};
+
+/// Fields of an encoded `FrameHeader` (`FrameHeader::LEN` bytes), in wire order.
+pub fn layout() -> Vec<FieldSpec> {
+    use Endianness::*;
+    contiguous(&[
+        ("magic", 4, Bytes, "\"SV2F\""),
+        ("version", 1, Little, "frame version (1)"),
+        ("frame_type", 1, Little, "1 data, 2 terminator, 3 digest, 4 padding"),
+        ("segment_index", 4, Little, "owning segment"),
+        ("frame_index", 4, Little, "position within the segment, from 0"),
+        ("plaintext_len", 4, Little, "plaintext bytes sealed in this frame"),
+        ("ciphertext_len", 4, Little, "ciphertext bytes (including the AEAD tag) following this header"),
+    ])
+}
 /// Encode a frame record into canonical wire format.
 ///
 /// Layout:
@@ -20,9 +35,9 @@ pub fn encode_frame(
     header: &FrameHeader,
     ciphertext: &[u8],
 ) -> Result<Vec<u8>, FrameError> {
-    let expected = FrameHeader::LEN + header.ciphertext_len as usize;
+    let expected = header.total_len()?;
 
-    if ciphertext.len() != header.ciphertext_len as usize {
+    if ciphertext.len() != header.ciphertext_len_usize()? {
         return Err(FrameError::LengthMismatch {
             expected,
             actual: ciphertext.len(),
@@ -20,4 +20,6 @@ pub use types::{
     FrameView,
     FrameType,
     FrameError,
-};
\ No newline at end of file
+    MAX_FRAME_CIPHERTEXT_LEN,
+};
+pub use encode::layout;
\ No newline at end of file
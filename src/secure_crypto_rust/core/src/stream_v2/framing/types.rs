@@ -1,9 +1,24 @@
 use std::fmt;
 use num_enum::TryFromPrimitive;
 
+use crate::crypto::TAG_LEN;
+use crate::stream_v2::segment_worker::types::MAX_FRAME_SIZE;
+
 pub const FRAME_MAGIC: [u8; 4] = *b"SV2F";
 pub const FRAME_VERSION: u8 = 1;
 
+/// Room above `MAX_FRAME_SIZE + TAG_LEN` for non-data frame payloads.
+pub const FRAME_LEN_SLACK: usize = 1024;
+
+/// Largest `ciphertext_len` accepted when parsing a frame header.
+///
+/// Data frames never exceed the frame size plus the AEAD tag; digest and
+/// terminator payloads are far smaller.
+pub const MAX_FRAME_CIPHERTEXT_LEN: usize = MAX_FRAME_SIZE + TAG_LEN + FRAME_LEN_SLACK;
+
+/// Wire bytes of a padding frame beyond its plaintext: header and AEAD tag.
+pub const PADDING_FRAME_OVERHEAD: usize = FrameHeader::LEN + TAG_LEN;
+
 /// Frame type identifiers for the envelope.
 #[repr(u16)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive)]
@@ -11,6 +26,8 @@ pub enum FrameType {
     Data       = 0x0001,
     Terminator = 0x0002,
     Digest     = 0x0003,
+    /// Authenticated filler between the data and digest frames; its plaintext is discarded.
+    Padding    = 0x0004,
 }
 
 impl FrameType {
@@ -26,15 +43,10 @@ impl FrameType {
 
         // Reject non-canonical encodings (future-proof & strict)
         if hi != 0 {
-            return Err(FrameError::InvalidFrameType(lo));
+            return Err(FrameError::UnknownFrameType { raw: lo });
         }
 
-        match lo {
-            0x01 => Ok(FrameType::Data),
-            0x02 => Ok(FrameType::Terminator),
-            0x03 => Ok(FrameType::Digest),
-            _ => Err(FrameError::InvalidFrameType(lo)),
-        }
+        Self::try_from_u8(lo)
     }
 
     #[inline(always)]
@@ -43,7 +55,8 @@ impl FrameType {
             0x01 => Ok(FrameType::Data),
             0x02 => Ok(FrameType::Terminator),
             0x03 => Ok(FrameType::Digest),
-            _ => Err(FrameError::InvalidFrameType(v)),
+            0x04 => Ok(FrameType::Padding),
+            _ => Err(FrameError::UnknownFrameType { raw: v }),
         }
     }
     
@@ -56,7 +69,7 @@ impl FrameType {
 
         // Enforce canonical single-byte encoding
         if hi != 0 {
-            return Err(FrameError::InvalidFrameType(lo));
+            return Err(FrameError::UnknownFrameType { raw: lo });
         }
 
         Ok(lo)
@@ -98,6 +111,16 @@ impl FrameHeader {
         }
     }
 
+    /// `ciphertext_len` as an in-memory length.
+    pub fn ciphertext_len_usize(&self) -> Result<usize, FrameError> {
+        u32_to_len("ciphertext_len", self.ciphertext_len)
+    }
+
+    /// Frame header plus ciphertext, checked against `usize` overflow.
+    pub fn total_len(&self) -> Result<usize, FrameError> {
+        checked_len_add("frame length", FrameHeader::LEN, self.ciphertext_len_usize()?)
+    }
+
     /// Canonical header for tests.
     /// Guaranteed to pass `validate()` unless a regression is introduced.
     pub fn test_header(frame_type: FrameType, segment_index: u32) -> Self {
@@ -128,6 +151,7 @@ impl FrameHeader {
             Some(FrameType::Data) => "data",
             Some(FrameType::Terminator) => "terminator",
             Some(FrameType::Digest) => "digest",
+            Some(FrameType::Padding) => "padding",
             None => "unknown",
         }
     }
@@ -151,13 +175,18 @@ pub struct FrameView<'a> {
 pub enum FrameError {
     InvalidMagic([u8; 4]),
     UnsupportedVersion(u8),
-    InvalidFrameType(u8),
+    /// Frame type byte outside [`FrameType`]
+    UnknownFrameType { raw: u8 },
     LengthMismatch {
         expected: usize,
         actual: usize,
     },
     Truncated,
     Malformed(String),
+    /// `ciphertext_len` beyond `MAX_FRAME_CIPHERTEXT_LEN`
+    CiphertextTooLarge { have: u32, max: usize },
+    /// A length does not fit the integer type it is converted to
+    LengthOverflow { field: &'static str, value: u64 },
 }
 
 impl fmt::Display for FrameError {
@@ -168,16 +197,36 @@ impl fmt::Display for FrameError {
                 write!(f, "invalid frame magic: {:?}", m),
             UnsupportedVersion(v) =>
                 write!(f, "unsupported frame version: {}", v),
-            InvalidFrameType(v) =>
-                write!(f, "invalid frame type: {}", v),
+            UnknownFrameType { raw } =>
+                write!(f, "unknown frame type: 0x{:02x}", raw),
             LengthMismatch { expected, actual } =>
                 write!(f, "length mismatch: expected {}, got {}", expected, actual),
             Truncated =>
                 write!(f, "truncated frame"),
             Malformed(msg) =>
                 write!(f, "malformed frame: {}", msg),
+            CiphertextTooLarge { have, max } =>
+                write!(f, "frame ciphertext too large: {} bytes (max {})", have, max),
+            LengthOverflow { field, value } =>
+                write!(f, "frame {} overflows: {}", field, value),
         }
     }
 }
 
 impl std::error::Error for FrameError {}
+
+/// `len` as a `u32` wire field; `LengthOverflow` beyond `u32::MAX`.
+pub fn len_to_u32(field: &'static str, len: usize) -> Result<u32, FrameError> {
+    u32::try_from(len).map_err(|_| FrameError::LengthOverflow { field, value: len as u64 })
+}
+
+/// A `u32` wire field as an in-memory length; `LengthOverflow` where `usize` is narrower.
+pub fn u32_to_len(field: &'static str, value: u32) -> Result<usize, FrameError> {
+    usize::try_from(value).map_err(|_| FrameError::LengthOverflow { field, value: u64::from(value) })
+}
+
+/// `a + b`; `LengthOverflow` instead of wrapping.
+pub fn checked_len_add(field: &'static str, a: usize, b: usize) -> Result<usize, FrameError> {
+    a.checked_add(b)
+        .ok_or(FrameError::LengthOverflow { field, value: (a as u64).saturating_add(b as u64) })
+}
@@ -3,30 +3,66 @@
 // ## 📂 File: `src/stream_v2/io.rs`
 // ## Normalized I/O + ordered encrypted writer (production-ready)
 
-use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom, Write};
 use std::path::PathBuf;
 use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
-use bytes::Bytes;
+use std::thread;
+use std::time::{Duration, Instant};
+use bytes::{Bytes, BytesMut};
+use crossbeam::channel::{Receiver, bounded};
+use tracing::{debug, trace};
 
-use crate::headers::{HeaderV1};
+use crate::constants::flags;
+use crate::crypto::ct_eq;
+use crate::headers::{HeaderV1, UnknownFlagPolicy, VersionedHeader, validate_extension_block};
+use crate::stream_v2::compression_worker::{CompressionBackend, CompressionWorkerError, decompress_decrypted};
 use crate::stream_v2::segment_worker::{DecryptedSegment, EncryptedSegment};
-use crate::stream_v2::segmenting::types::SegmentFlags;
-use crate::stream_v2::segmenting::{SegmentHeader, decode_segment_header, encode_segment};
+use crate::stream_v2::segmenting::types::{SegmentError, SegmentFlags};
+use crate::stream_v2::segmenting::{
+    SegmentHeader, SegmentIndexMap, SegmentLimits, decode_index_footer, decode_segment_header, encode_index_footer, encode_segment,
+};
 use crate::types::StreamError;
 
 /// Canonical input abstraction
+///
+/// New variants may be added, so matches need a wildcard arm.
+#[non_exhaustive]
 pub enum InputSource {
     Reader(Box<dyn Read + Send>),
     File(PathBuf),
     Memory(Vec<u8>),
+    /// Process standard input.
+    Stdin,
+}
+
+impl InputSource {
+    /// Input length in bytes, when it can be known without reading.
+    ///
+    /// `Memory` → buffer length, `File` → metadata length (`None` if the
+    /// metadata is unavailable), `Reader` and `Stdin` → `None`.
+    pub fn size_hint(&self) -> Option<u64> {
+        match self {
+            InputSource::Reader(_) | InputSource::Stdin => None,
+            InputSource::File(p) => std::fs::metadata(p).ok().map(|m| m.len()),
+            InputSource::Memory(b) => Some(b.len() as u64),
+        }
+    }
 }
 
 /// Canonical output abstraction
+///
+/// New variants may be added, so matches need a wildcard arm.
+#[non_exhaustive]
 pub enum OutputSink {
     Writer(Box<dyn Write + Send>),
     File(PathBuf),
     Memory,
+    /// Process standard output (buffered; flushed by the ordered writer).
+    Stdout,
+    /// Drop the output; the bytes written are reported through telemetry.
+    Discard,
 }
 
 /// Normalize input source into a boxed reader
@@ -35,29 +71,55 @@ pub fn open_input(src: InputSource) -> Result<Box<dyn Read + Send>, StreamError>
         InputSource::Reader(r) => r,
         InputSource::File(p) => Box::new(std::fs::File::open(p)?),
         InputSource::Memory(b) => Box::new(std::io::Cursor::new(b)),
+        // `StdinLock` is not `Send`; the handle locks per read instead
+        InputSource::Stdin => Box::new(std::io::stdin()),
     };
     Ok(reader)
 }
 
 /// Normalize output sink into a boxed writer
+///
+/// A captured `Memory` buffer holds at most `max_captured` bytes; writing
+/// past it fails the stream instead of growing the buffer. `Discard` returns
+/// no buffer; use [`open_output_counted`] to read its byte count.
 pub fn open_output(
     sink: OutputSink,
     with_buf: Option<bool>,
+    max_captured: Option<usize>,
 ) -> Result<(Box<dyn Write + Send>, Option<Arc<Mutex<Vec<u8>>>>), StreamError> {
+    let (writer, maybe_buf, _) = open_output_counted(sink, with_buf, max_captured)?;
+    Ok((writer, maybe_buf))
+}
+
+/// Writer, captured buffer and `Discard` byte counter of an opened sink.
+pub type CountedOutput = (Box<dyn Write + Send>, Option<Arc<Mutex<Vec<u8>>>>, Option<Arc<AtomicU64>>);
+
+/// [`open_output`], plus the byte counter of a `Discard` sink (`None` otherwise).
+pub fn open_output_counted(
+    sink: OutputSink,
+    with_buf: Option<bool>,
+    max_captured: Option<usize>,
+) -> Result<CountedOutput, StreamError> {
     match sink {
-        OutputSink::Writer(w) => Ok((w, None)),
-        OutputSink::File(p) => Ok((Box::new(std::fs::File::create(p)?), None)),
+        OutputSink::Writer(w) => Ok((w, None, None)),
+        OutputSink::File(p) => Ok((Box::new(std::fs::File::create(p)?), None, None)),
+        // `StdoutLock` is not `Send`; buffer so small frames don't hit the line-buffered handle
+        OutputSink::Stdout => Ok((Box::new(std::io::BufWriter::new(std::io::stdout())), None, None)),
+        OutputSink::Discard => {
+            let written = Arc::new(AtomicU64::new(0));
+            Ok((Box::new(DiscardWriter { written: written.clone() }), None, Some(written)))
+        }
         OutputSink::Memory => {
             match with_buf {
                 Some(true) => {
                     let buf = Arc::new(Mutex::new(Vec::new()));
-                    let writer = SharedBufferWriter { buf: buf.clone() };
-                    Ok((Box::new(writer), Some(buf)))
+                    let writer = SharedBufferWriter { buf: buf.clone(), limit: max_captured };
+                    Ok((Box::new(writer), Some(buf), None))
                 },
                 _ => {
                     // If we don’t need concurrent access, we can just use Cursor<Vec<u8>> directly:
                     let cursor = Cursor::new(Vec::new());
-                    Ok((Box::new(cursor), None))
+                    Ok((Box::new(cursor), None, None))
                 }
             }
         }
@@ -66,10 +128,19 @@ pub fn open_output(
 
 pub struct SharedBufferWriter {
     buf: Arc<Mutex<Vec<u8>>>,
+    limit: Option<usize>,
 }
 impl Write for SharedBufferWriter {
     fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
         let mut guard = self.buf.lock().unwrap();
+        if let Some(limit) = self.limit {
+            if guard.len().saturating_add(data.len()) > limit {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::OutOfMemory,
+                    format!("captured output exceeds max_captured_bytes ({limit})"),
+                ));
+            }
+        }
         guard.extend_from_slice(data);
         Ok(data.len())
     }
@@ -90,51 +161,464 @@ impl Write for SharedBufferWriter {
     // }
 }
 
+/// Writer behind `OutputSink::Discard`: drops the bytes, counts them.
+pub struct DiscardWriter {
+    written: Arc<AtomicU64>,
+}
+
+impl Write for DiscardWriter {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        self.written.fetch_add(data.len() as u64, Ordering::Relaxed);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
 // ================= Header =================
 
 pub fn write_header<W: Write>(w: &mut W, h: &HeaderV1) -> Result<(), StreamError> {
+    write_header_with_extensions(w, h, &[])
+}
+
+/// Write the stream header followed by its TLV extension block.
+///
+/// `extensions` must be exactly `h.ext_len()` bytes (see `HeaderExtensions::attach`).
+pub fn write_header_with_extensions<W: Write>(w: &mut W, h: &HeaderV1, extensions: &[u8]) -> Result<(), StreamError> {
+    validate_extension_block(h, extensions).map_err(StreamError::Header)?;
     let buf = crate::headers::encode_header_le(h).map_err(|e| StreamError::Header(e))?;
     w.write_all(&buf)?;
+    w.write_all(extensions)?;
     Ok(())
 }
 
-fn read_header<R: Read>(r: &mut R) -> Result<HeaderV1, StreamError> {
+/// Overwrite the fixed header at the start of a seekable stream, leaving the
+/// write position where it was.
+///
+/// Only fields outside the AAD may change (e.g. `plaintext_size` of a
+/// non-`AAD_STRICT` stream); the extension block is not touched.
+pub fn rewrite_header<W: Write + Seek>(w: &mut W, h: &HeaderV1) -> Result<(), StreamError> {
+    let buf = crate::headers::encode_header_le(h).map_err(StreamError::Header)?;
+    let end = w.stream_position()?;
+    w.seek(SeekFrom::Start(0))?;
+    w.write_all(&buf)?;
+    w.seek(SeekFrom::Start(end))?;
+    Ok(())
+}
+
+/// Read the fixed header and the extension block following it.
+fn read_header<R: Read>(r: &mut R) -> Result<(HeaderV1, Bytes), StreamError> {
+    read_header_with(r, UnknownFlagPolicy::Reject)
+}
+
+/// [`read_header`], treating unknown header `flags` bits as `policy` says.
+fn read_header_with<R: Read>(r: &mut R, policy: UnknownFlagPolicy) -> Result<(HeaderV1, Bytes), StreamError> {
     let mut buf = [0u8; HeaderV1::LEN];
     r.read_exact(&mut buf)?;
-    Ok(crate::headers::decode_header_le(&buf).map_err(|e| StreamError::Header(e))?)
+    // Dispatch on magic + version: newer streams fail with `UnsupportedVersion`
+    let VersionedHeader::V1(header) = crate::headers::decode_any_with(&buf, policy).map_err(StreamError::Header)?;
+
+    let mut extensions = vec![0u8; header.ext_len() as usize];
+    r.read_exact(&mut extensions)?;
+    validate_extension_block(&header, &extensions).map_err(StreamError::Header)?;
+    Ok((header, Bytes::from(extensions)))
 }
 
+/// Default read buffer of a [`PayloadReader`].
+pub const PAYLOAD_READ_BUF: usize = 256 * 1024;
+
+/// Buffered reader over an encrypted stream.
+///
+/// Segment and frame headers are small: reading them through a
+/// [`PAYLOAD_READ_BUF`] buffer keeps them from hitting the underlying
+/// file or socket one `read` at a time. Errors from [`read_segment`] and
+/// [`PayloadReader::with_header`] carry the [`PayloadReader::position`] of
+/// the header being read (`StreamError::At`).
 #[derive(Debug)]
 pub struct PayloadReader<R: Read> {
-    inner: R,
+    inner: BufReader<R>,
+    /// Stream offset of the next byte handed out
+    position: u64,
+    /// Bounds checked on every segment header before its wire is allocated
+    limits: SegmentLimits,
+    /// Raw TLV extension block read after the stream header (empty if none)
+    extensions: Bytes,
+    /// Stream header flags segment headers are checked against (`SEGMENT_HEADER_CRC`)
+    stream_flags: u16,
 }
 
 impl<R: Read> PayloadReader<R> {
     /// Construct without consuming header (rarely used)
+    ///
+    /// Without a stream header, segments are bounded by the largest supported chunk size.
     pub fn new(reader: R) -> Self {
-        PayloadReader { inner: reader }
+        Self::with_capacity(PAYLOAD_READ_BUF, reader)
+    }
+
+    /// [`PayloadReader::new`] with a `capacity`-byte read buffer.
+    pub fn with_capacity(capacity: usize, reader: R) -> Self {
+        PayloadReader {
+            inner: BufReader::with_capacity(capacity, reader),
+            position: 0,
+            limits: SegmentLimits::default(),
+            extensions: Bytes::new(),
+            stream_flags: 0,
+        }
     }
 
     /// Consume header and return both parsed header and payload reader
-    pub fn with_header(mut reader: R) -> Result<(HeaderV1, Self), StreamError> {
-        let header = read_header(&mut reader)?;
-        Ok((header, PayloadReader { inner: reader }))
+    ///
+    /// The extension block (`header.ext_len` bytes) is consumed as well; see
+    /// [`PayloadReader::extensions`].
+    pub fn with_header(reader: R) -> Result<(HeaderV1, Self), StreamError> {
+        Self::new(reader).read_stream_header(UnknownFlagPolicy::Reject)
+    }
+
+    /// [`PayloadReader::with_header`], treating unknown header `flags` bits as `policy` says.
+    pub fn with_header_policy(reader: R, policy: UnknownFlagPolicy) -> Result<(HeaderV1, Self), StreamError> {
+        Self::new(reader).read_stream_header(policy)
+    }
+
+    /// [`PayloadReader::with_header`] with a `capacity`-byte read buffer.
+    pub fn with_header_and_capacity(capacity: usize, reader: R) -> Result<(HeaderV1, Self), StreamError> {
+        Self::with_capacity(capacity, reader).read_stream_header(UnknownFlagPolicy::Reject)
+    }
+
+    fn read_stream_header(mut self, policy: UnknownFlagPolicy) -> Result<(HeaderV1, Self), StreamError> {
+        let offset = self.position;
+        let (header, extensions) = read_header_with(&mut self, policy).map_err(|e| e.at(offset))?;
+        self.limits = SegmentLimits::for_chunk_size(header.chunk_size);
+        self.extensions = extensions;
+        self.stream_flags = header.flags;
+        Ok((header, self))
+    }
+
+    /// Stream offset of the next byte read: bytes consumed so far, plus the
+    /// starting offset for readers that do not start at the stream header
+    /// ([`open_input_at`], [`PayloadReader::with_position`]). After a seek,
+    /// the offset the inner reader reported.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Count positions from `offset`: the reader starts `offset` bytes into the stream.
+    pub fn with_position(mut self, offset: u64) -> Self {
+        self.position = offset;
+        self
+    }
+
+    /// Size of the read buffer.
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// Raw extension block that followed the stream header
+    /// (parse with `HeaderExtensions::decode`).
+    pub fn extensions(&self) -> &Bytes {
+        &self.extensions
+    }
+
+    /// Replace the segment length bounds.
+    pub fn with_limits(mut self, limits: SegmentLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn limits(&self) -> SegmentLimits {
+        self.limits
+    }
+
+    /// Check segment headers against these stream header flags; only
+    /// `SEGMENT_HEADER_CRC` matters (see `SegmentHeader::require_header_crc`).
+    pub fn with_stream_flags(mut self, flags: u16) -> Self {
+        self.stream_flags = flags;
+        self
+    }
+
+    pub fn stream_flags(&self) -> u16 {
+        self.stream_flags
     }
 }
 
 impl<R: Read> Read for PayloadReader<R> {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        self.inner.read(buf)
+        let n = self.inner.read(buf)?;
+        self.position += n as u64;
+        Ok(n)
     }
 }
 
+/// Seeks go through the buffer (`SeekFrom::Current` accounts for the bytes
+/// buffered but not yet read) and reset [`PayloadReader::position`].
 impl<R: Read + Seek> Seek for PayloadReader<R> {
     fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
-        self.inner.seek(pos)
+        self.position = self.inner.seek(pos)?;
+        Ok(self.position)
+    }
+
+    fn stream_position(&mut self) -> std::io::Result<u64> {
+        // Without discarding the buffer, unlike `seek(SeekFrom::Current(0))`
+        self.inner.stream_position()
     }
 }
 
 
+// ================= Resume =================
+
+/// Open `src`, parse the stream header and position the payload reader at `offset`.
+///
+/// `offset` is absolute (stream header included). File and memory inputs seek
+/// directly; plain readers are skipped forward.
+pub fn open_input_at(
+    src: InputSource,
+    offset: u64,
+) -> Result<(HeaderV1, PayloadReader<Box<dyn Read + Send>>), StreamError> {
+    open_input_at_with(src, offset, UnknownFlagPolicy::Reject)
+}
+
+/// [`open_input_at`], treating unknown header `flags` bits as `policy` says.
+pub fn open_input_at_with(
+    src: InputSource,
+    offset: u64,
+    policy: UnknownFlagPolicy,
+) -> Result<(HeaderV1, PayloadReader<Box<dyn Read + Send>>), StreamError> {
+    if offset < HeaderV1::LEN as u64 {
+        return Err(StreamError::Validation(format!(
+            "resume offset {offset} points inside the stream header"
+        )));
+    }
+
+    let check_offset = |header: &HeaderV1| {
+        if offset < header.encoded_len() as u64 {
+            return Err(StreamError::Validation(format!(
+                "resume offset {offset} points inside the header extension block"
+            )));
+        }
+        Ok(())
+    };
+
+    let (header, extensions, reader): (HeaderV1, Bytes, Box<dyn Read + Send>) = match src {
+        InputSource::File(p) => {
+            let mut file = std::fs::File::open(p)?;
+            let (header, extensions) = read_header_with(&mut file, policy)?;
+            check_offset(&header)?;
+            file.seek(SeekFrom::Start(offset))?;
+            (header, extensions, Box::new(file))
+        }
+        InputSource::Memory(b) => {
+            let mut cursor = Cursor::new(b);
+            let (header, extensions) = read_header_with(&mut cursor, policy)?;
+            check_offset(&header)?;
+            cursor.seek(SeekFrom::Start(offset))?;
+            (header, extensions, Box::new(cursor))
+        }
+        other => {
+            let mut r = open_input(other)?;
+            let (header, extensions) = read_header_with(&mut r, policy)?;
+            check_offset(&header)?;
+            let skip = offset - header.encoded_len() as u64;
+            let skipped = std::io::copy(&mut r.by_ref().take(skip), &mut std::io::sink())?;
+            if skipped != skip {
+                return Err(StreamError::Validation(format!(
+                    "resume offset {offset} is past the end of the stream"
+                )));
+            }
+            (header, extensions, r)
+        }
+    };
+
+    let mut payload = PayloadReader::new(reader).with_position(offset).with_stream_flags(header.flags);
+    payload.limits = SegmentLimits::for_chunk_size(header.chunk_size);
+    payload.extensions = extensions;
+    Ok((header, payload))
+}
+
+/// Build a [`SegmentIndexMap`] by walking the segment headers of a stream.
+///
+/// Only headers are read; wires are skipped with `seek`. The scan stops at the
+/// final segment and fails if the stream ends before it or a wire is truncated.
+/// The stream header is re-read to skip its extension block.
+pub fn scan_segment_index<R: Read + Seek>(r: &mut R) -> Result<SegmentIndexMap, StreamError> {
+    let end = r.seek(SeekFrom::End(0))?;
+    r.seek(SeekFrom::Start(0))?;
+    let (header, _) = read_header(r)?;
+    let mut index = SegmentIndexMap::starting_at(header.encoded_len() as u64);
+    r.seek(SeekFrom::Start(index.end_offset()))?;
+
+    while !index.is_complete() {
+        let offset = index.end_offset();
+        if offset + SegmentHeader::LEN as u64 > end {
+            return Err(StreamError::Validation(format!(
+                "Missing final segment: stream ends at offset {end} after {} segments",
+                index.len()
+            )));
+        }
+
+        let mut hdr_buf = [0u8; SegmentHeader::LEN];
+        r.read_exact(&mut hdr_buf)?;
+        let segment = decode_segment_header(&hdr_buf).map_err(StreamError::Segment)?;
+        segment.require_header_crc(header.flags).map_err(StreamError::Segment)?;
+        index.push(&segment).map_err(StreamError::Segment)?;
+        if index.end_offset() > end {
+            return Err(StreamError::Segment(SegmentError::Truncated));
+        }
+        r.seek(SeekFrom::Start(index.end_offset()))?;
+    }
+
+    trace!(segments = index.len(), "segment index scanned");
+    Ok(index)
+}
+
+/// Metadata of one segment, read without the key (see [`iter_segment_headers`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SegmentInfo {
+    /// Absolute offset of the segment header (stream header included).
+    pub offset: u64,
+    pub segment_index: u32,
+    /// Payload bytes before encryption (compressed size for compressed segments).
+    pub bytes_len: u32,
+    /// Encrypted frame bytes following the segment header.
+    pub wire_len: u32,
+    pub frame_count: u32,
+    /// Raw digest algorithm id (`DigestAlg` registry).
+    pub digest_alg: u16,
+    pub flags: SegmentFlags,
+}
+
+impl SegmentInfo {
+    fn new(offset: u64, header: &SegmentHeader) -> Self {
+        Self {
+            offset,
+            segment_index: header.segment_index,
+            bytes_len: header.bytes_len,
+            wire_len: header.wire_len,
+            frame_count: header.frame_count,
+            digest_alg: header.digest_alg,
+            flags: header.flags,
+        }
+    }
+
+    /// Bytes taken on the wire, segment header included.
+    pub fn encoded_len(&self) -> u64 {
+        SegmentHeader::LEN as u64 + self.wire_len as u64
+    }
+
+    pub fn is_final(&self) -> bool {
+        self.flags.contains(SegmentFlags::FINAL_SEGMENT)
+    }
+}
+
+/// Walk the segment headers of an encrypted stream without the key.
+///
+/// `r` must be positioned at the start of the stream: the stream header is
+/// parsed first, then each segment header is decoded and its wire skipped.
+/// Iteration ends after the final segment (trailers such as the segment index
+/// are not visited) or after the first error. Wire CRCs are not checked.
+pub fn iter_segment_headers<R: Read>(r: R) -> SegmentHeaders<R> {
+    SegmentHeaders { reader: r, header: None, offset: 0, done: false }
+}
+
+/// Iterator returned by [`iter_segment_headers`].
+pub struct SegmentHeaders<R: Read> {
+    reader: R,
+    header: Option<HeaderV1>,
+    offset: u64,
+    done: bool,
+}
+
+impl<R: Read> SegmentHeaders<R> {
+    /// The stream header, once the first item has been read.
+    pub fn header(&self) -> Option<&HeaderV1> {
+        self.header.as_ref()
+    }
+
+    fn read_next(&mut self) -> Result<SegmentInfo, StreamError> {
+        if self.header.is_none() {
+            let (header, _) = read_header(&mut self.reader)?;
+            self.offset = header.encoded_len() as u64;
+            self.header = Some(header);
+        }
+
+        let mut hdr_buf = [0u8; SegmentHeader::LEN];
+        match self.reader.read_exact(&mut hdr_buf) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                return Err(StreamError::Validation(format!(
+                    "Missing final segment: stream ends at offset {}",
+                    self.offset
+                )));
+            }
+            Err(e) => return Err(e.into()),
+        }
+        let header = decode_segment_header(&hdr_buf).map_err(StreamError::Segment)?;
+        if let Some(stream) = &self.header {
+            header.require_header_crc(stream.flags).map_err(StreamError::Segment)?;
+        }
+        let info = SegmentInfo::new(self.offset, &header);
+
+        let skipped = std::io::copy(&mut self.reader.by_ref().take(info.wire_len as u64), &mut std::io::sink())?;
+        if skipped != info.wire_len as u64 {
+            return Err(StreamError::Segment(SegmentError::Truncated));
+        }
+        self.offset += info.encoded_len();
+        Ok(info)
+    }
+}
+
+impl<R: Read> Iterator for SegmentHeaders<R> {
+    type Item = Result<SegmentInfo, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let item = self.read_next();
+        self.done = !matches!(&item, Ok(info) if !info.is_final());
+        Some(item)
+    }
+}
+
+/// Write the encrypted index segment and the footer pointing at it.
+///
+/// `index` must describe every segment already written, so that the index
+/// segment starts at `index.end_offset()`. Returns the number of bytes written.
+pub fn write_index_trailer<W: Write>(
+    out: &mut W,
+    segment: &EncryptedSegment,
+    index: &SegmentIndexMap,
+) -> Result<u64, StreamError> {
+    if !index.is_complete() {
+        return Err(StreamError::Validation("segment index written before the final segment".into()));
+    }
+    let segment_enc = encode_segment(&segment.header, &segment.wire).map_err(StreamError::Segment)?;
+    out.write_all(&segment_enc)?;
+    out.write_all(&encode_index_footer(index.end_offset()))?;
+    trace!(offset = index.end_offset(), segments = index.len(), "segment index written");
+    Ok((segment_enc.len() + SegmentIndexMap::FOOTER_LEN) as u64)
+}
+
+/// Offset of the trailing index segment, read from the stream footer.
+///
+/// `None` when the stream does not end with an index footer (flag
+/// `HAS_SEGMENT_INDEX` unset). The footer itself is not authenticated: the
+/// index segment it points at is, and must be decrypted before use.
+pub fn locate_segment_index<R: Read + Seek>(r: &mut R) -> Result<Option<u64>, StreamError> {
+    let end = r.seek(SeekFrom::End(0))?;
+    if end < (HeaderV1::LEN + SegmentIndexMap::FOOTER_LEN) as u64 {
+        return Ok(None);
+    }
+
+    let mut footer = [0u8; SegmentIndexMap::FOOTER_LEN];
+    r.seek(SeekFrom::Start(end - SegmentIndexMap::FOOTER_LEN as u64))?;
+    r.read_exact(&mut footer)?;
+
+    Ok(decode_index_footer(&footer).filter(|&offset| {
+        offset >= HeaderV1::LEN as u64 && offset + SegmentHeader::LEN as u64 <= end - SegmentIndexMap::FOOTER_LEN as u64
+    }))
+}
+
 // ================= Utilities =================
 /// Ensure the reader has advanced past the header (default 80 bytes).
 pub fn assert_reader_after_header<R: Read + Seek + Send>(reader: &mut R, header_len: usize) -> Result<(), StreamError> {
@@ -152,19 +636,76 @@ pub fn read_exact_or_eof<R: Read>(
     r: &mut R,
     len: usize,
 ) -> Result<Bytes, StreamError> {
-    let mut buf = vec![0u8; len];
-    let mut off = 0;
+    ChunkReader::new(r, len).read_chunk()
+}
+
+/// First buffer size of a [`ChunkReader`] read; doubles up to `chunk_size`.
+pub const INITIAL_READ_BUF: usize = 64 * 1024;
 
-    while off < len {
-        let n = r.read(&mut buf[off..])?;
-        if n == 0 {
-            break;
+/// Allocation stats of a [`ChunkReader`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChunkReaderStats {
+    /// Non-empty chunks returned.
+    pub chunks: u64,
+    /// Largest buffer capacity held while reading a chunk, in bytes.
+    pub peak_capacity: usize,
+}
+
+/// Splits an input into canonical `chunk_size` chunks without reserving a
+/// whole chunk up front.
+///
+/// The buffer starts at [`INITIAL_READ_BUF`] and doubles only while input
+/// keeps arriving, so a short input never costs a full `chunk_size`
+/// allocation. Chunks are split off one `BytesMut` and frozen, so they stay
+/// zero-copy `Bytes` downstream; once every chunk sharing an allocation is
+/// dropped (segment written), the next read reclaims it instead of allocating.
+pub struct ChunkReader<R> {
+    inner: R,
+    chunk_size: usize,
+    buf: BytesMut,
+    stats: ChunkReaderStats,
+}
+
+impl<R: Read> ChunkReader<R> {
+    pub fn new(inner: R, chunk_size: usize) -> Self {
+        Self { inner, chunk_size, buf: BytesMut::new(), stats: ChunkReaderStats::default() }
+    }
+
+    /// Next chunk: exactly `chunk_size` bytes, shorter only at EOF, empty once exhausted.
+    pub fn read_chunk(&mut self) -> Result<Bytes, StreamError> {
+        let mut want = self.chunk_size.min(INITIAL_READ_BUF);
+        let mut filled = 0;
+
+        while filled < self.chunk_size {
+            if filled == want {
+                want = want.saturating_mul(2).min(self.chunk_size);
+            }
+            if self.buf.len() < want {
+                self.buf.resize(want, 0);
+            }
+            self.stats.peak_capacity = self.stats.peak_capacity.max(self.buf.capacity());
+
+            let n = self.inner.read(&mut self.buf[filled..want])?;
+            if n == 0 {
+                break;
+            }
+            filled += n;
+        }
+
+        self.buf.truncate(filled);
+        if filled > 0 {
+            self.stats.chunks += 1;
         }
-        off += n;
+        Ok(self.buf.split().freeze())
+    }
+
+    pub fn stats(&self) -> ChunkReaderStats {
+        self.stats
     }
 
-    buf.truncate(off);
-    Ok(Bytes::from(buf))
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
 }
 // pub fn read_exact_or_eof_1<R: Read>(
 //     r: &mut R,
@@ -197,8 +738,22 @@ pub fn read_exact_or_eof<R: Read>(
 
 //     Ok(Some((header, Bytes::from(wire))))
 // }
+/// Read the next segment header and its wire.
+///
+/// The header's lengths are checked against the reader's [`SegmentLimits`]
+/// before the wire buffer is allocated: a forged header fails with a
+/// `SegmentError` instead of a multi-GiB allocation.
+///
+/// Errors are located at the segment header's offset (`StreamError::At`).
 pub fn read_segment<R: Read>(
-    r: &mut R,
+    r: &mut PayloadReader<R>,
+) -> Result<Option<(SegmentHeader, Bytes)>, StreamError> {
+    let offset = r.position;
+    read_segment_at(r).map_err(|e| e.at(offset))
+}
+
+fn read_segment_at<R: Read>(
+    r: &mut PayloadReader<R>,
 ) -> Result<Option<(SegmentHeader, Bytes)>, StreamError> {
     let mut hdr_buf = [0u8; SegmentHeader::LEN];
 
@@ -208,11 +763,12 @@ pub fn read_segment<R: Read>(
     }
 
     let header = decode_segment_header(&hdr_buf).map_err(StreamError::Segment)?;
-    // 🔍 Debug header summary
-    // eprintln!("[IO:DECRYPT] Parsed header: {}", header.summary());
+    trace!(header = %header, "parsed segment header");
+    header.require_header_crc(r.stream_flags).map_err(StreamError::Segment)?;
+    r.limits.check(&header).map_err(StreamError::Segment)?;
 
     // Allocate wire buffer according to header
-    let mut wire = vec![0u8; header.wire_len as usize];
+    let mut wire = vec![0u8; header.wire_len_usize().map_err(StreamError::Segment)?];
     if header.wire_len > 0 {
         r.read_exact(&mut wire)?;
     }
@@ -226,6 +782,50 @@ pub fn read_segment<R: Read>(
     Ok(Some((header, Bytes::from(wire))))
 }
 
+/// Segments a [`SegmentPrefetcher`] reads ahead by default: the next wire is
+/// read while the current one is dispatched (double buffering).
+pub const DEFAULT_READ_AHEAD: usize = 1;
+
+/// Reads segments on a scoped thread, ahead of the consumer.
+///
+/// Yields exactly what repeated [`read_segment`] calls would, errors
+/// included and in the same place; it stops after the `FINAL_SEGMENT` (so
+/// an index trailer is never read as a segment) or the first error. At most
+/// `read_ahead` segments are read and not yet taken.
+pub struct SegmentPrefetcher {
+    rx: Receiver<Result<(SegmentHeader, Bytes), StreamError>>,
+}
+
+impl SegmentPrefetcher {
+    /// Start reading `reader` on a thread of `scope`, `read_ahead` (at least one) segments ahead.
+    pub fn spawn<'scope, R: Read + Send>(
+        scope: &'scope thread::Scope<'scope, '_>,
+        reader: &'scope mut PayloadReader<R>,
+        read_ahead: usize,
+    ) -> Self {
+        // The reader thread blocks on a full channel with one more segment in hand
+        let (tx, rx) = bounded(read_ahead.max(1) - 1);
+        scope.spawn(move || {
+            while let Some(result) = read_segment(reader).transpose() {
+                let last = match &result {
+                    Ok((header, _)) => header.flags.contains(SegmentFlags::FINAL_SEGMENT),
+                    Err(_) => true,
+                };
+                // A closed channel means the consumer is done
+                if tx.send(result).is_err() || last {
+                    break;
+                }
+            }
+        });
+        Self { rx }
+    }
+
+    /// The next segment, `None` once the stream (or the final segment) has been read.
+    pub fn next_segment(&self) -> Result<Option<(SegmentHeader, Bytes)>, StreamError> {
+        self.rx.recv().ok().transpose()
+    }
+}
+
 // pub fn read_segment<R: Read>(
 //     r: &mut R,
 // ) -> Result<Option<(SegmentHeader, Bytes)>, StreamError> {
@@ -251,13 +851,210 @@ pub fn read_segment<R: Read>(
 //     }
 // }
 
+// ================= Plaintext trailer =================
+
+/// Running CRC32, length and digest of the plaintext, for streams with
+/// `HAS_CRC32` / `HAS_TOTAL_LEN` / a whole-stream digest (`FrameLayout::final_digest`).
+///
+/// The encrypt side feeds every plaintext chunk and emits [`PlaintextTrailer::payload`]
+/// as an authenticated trailer segment right before the final segment. The
+/// decrypt side feeds the reconstructed plaintext and checks that segment when
+/// it arrives. The payload layout follows the header flags (bound into the
+/// session key), not the segment flags:
+///
+/// ```text
+/// [ crc32     (4, LE) ]  HAS_CRC32
+/// [ total_len (8, LE) ]  HAS_TOTAL_LEN
+/// [ blake3    (32)    ]  HAS_FINAL_DIGEST under EXPLICIT_FRAMES
+/// ```
+///
+/// A disabled trailer ignores everything.
+#[derive(Debug, Clone, Default)]
+pub struct PlaintextTrailer {
+    crc: Option<crc32fast::Hasher>,
+    track_len: bool,
+    total_len: u64,
+    digest: Option<Box<blake3::Hasher>>,
+    verified: bool,
+}
+
+impl PlaintextTrailer {
+    pub fn new(crc32: bool, total_len: bool) -> Self {
+        Self { crc: crc32.then(crc32fast::Hasher::new), track_len: total_len, total_len: 0, digest: None, verified: false }
+    }
+
+    /// Also carry a BLAKE3 of the whole plaintext.
+    pub fn with_stream_digest(mut self) -> Self {
+        self.digest = Some(Box::default());
+        self
+    }
+
+    /// Trailer for `header`: enabled by `HAS_CRC32`, `HAS_TOTAL_LEN` and/or a
+    /// whole-stream digest (`HeaderV1::frame_layout`).
+    pub fn for_header(header: &HeaderV1) -> Self {
+        let trailer = Self::new(header.flags & flags::HAS_CRC32 != 0, header.flags & flags::HAS_TOTAL_LEN != 0);
+        if header.frame_layout().final_digest() {
+            trailer.with_stream_digest()
+        } else {
+            trailer
+        }
+    }
+
+    /// Whether `flags` mark a trailer segment.
+    pub fn is_trailer(flags: SegmentFlags) -> bool {
+        flags.intersects(SegmentFlags::CHECKSUM | SegmentFlags::TOTAL_LEN | SegmentFlags::STREAM_DIGEST)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.crc.is_some() || self.track_len || self.digest.is_some()
+    }
+
+    /// Segment flags of the trailer segment.
+    pub fn flags(&self) -> SegmentFlags {
+        let mut flags = SegmentFlags::empty();
+        flags.set(SegmentFlags::CHECKSUM, self.crc.is_some());
+        flags.set(SegmentFlags::TOTAL_LEN, self.track_len);
+        flags.set(SegmentFlags::STREAM_DIGEST, self.digest.is_some());
+        flags
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.total_len += data.len() as u64;
+        if let Some(crc) = self.crc.as_mut() {
+            crc.update(data);
+        }
+        if let Some(digest) = self.digest.as_mut() {
+            digest.update(data);
+        }
+    }
+
+    /// Plaintext bytes fed so far.
+    pub fn total_len(&self) -> u64 {
+        self.total_len
+    }
+
+    /// CRC32 of everything fed so far.
+    pub fn crc32(&self) -> u32 {
+        self.crc.clone().map_or(0, crc32fast::Hasher::finalize)
+    }
+
+    /// BLAKE3 of everything fed so far, if the trailer carries one.
+    pub fn stream_digest(&self) -> Option<[u8; 32]> {
+        self.digest.as_ref().map(|digest| *digest.finalize().as_bytes())
+    }
+
+    /// Payload of the trailer segment.
+    pub fn payload(&self) -> Bytes {
+        let mut payload = Vec::with_capacity(self.payload_len());
+        if self.crc.is_some() {
+            payload.extend_from_slice(&self.crc32().to_le_bytes());
+        }
+        if self.track_len {
+            payload.extend_from_slice(&self.total_len.to_le_bytes());
+        }
+        if let Some(digest) = self.stream_digest() {
+            payload.extend_from_slice(&digest);
+        }
+        Bytes::from(payload)
+    }
+
+    fn payload_len(&self) -> usize {
+        4 * usize::from(self.crc.is_some()) + 8 * usize::from(self.track_len) + 32 * usize::from(self.digest.is_some())
+    }
+
+    /// Compare the decrypted trailer payload with the plaintext seen so far.
+    ///
+    /// The length is checked first: a truncated stream also fails its CRC,
+    /// and `TruncatedStream` is the more useful error.
+    pub fn verify(&mut self, payload: &[u8]) -> Result<(), StreamError> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+        if self.verified {
+            return Err(StreamError::Validation("duplicate plaintext trailer segment".into()));
+        }
+        if payload.len() != self.payload_len() {
+            return Err(StreamError::Validation(format!(
+                "plaintext trailer carries {} bytes, expected {}",
+                payload.len(),
+                self.payload_len()
+            )));
+        }
+
+        let (crc, rest) = payload.split_at(if self.crc.is_some() { 4 } else { 0 });
+        let (len, digest) = rest.split_at(if self.track_len { 8 } else { 0 });
+        if self.track_len {
+            let expected = u64::from_le_bytes(len.try_into().expect("length checked above"));
+            if expected != self.total_len {
+                return Err(StreamError::TruncatedStream { expected, got: self.total_len });
+            }
+        }
+        if self.crc.is_some() {
+            let expected = u32::from_le_bytes(crc.try_into().expect("length checked above"));
+            let actual = self.crc32();
+            if expected != actual {
+                return Err(StreamError::ChecksumMismatch { expected, actual });
+            }
+        }
+        if let Some(actual) = self.stream_digest() {
+            if !ct_eq(digest, &actual) {
+                return Err(StreamError::StreamDigestMismatch);
+            }
+        }
+        self.verified = true;
+        Ok(())
+    }
+
+    /// At the end of the stream: an enabled trailer must have been verified.
+    pub fn finish(&self) -> Result<(), StreamError> {
+        if self.is_enabled() && !self.verified {
+            return Err(StreamError::Validation(format!(
+                "Missing plaintext trailer segment after {} bytes",
+                self.total_len
+            )));
+        }
+        Ok(())
+    }
+}
+
 // ================= Ordered writers =================
 
+/// Fail with `ReorderBufferOverflow` once more than `limit` segments wait in `pending`.
+fn check_pending<T>(pending: &BTreeMap<u32, T>, next: u32, limit: usize) -> Result<(), StreamError> {
+    if pending.len() > limit {
+        debug!(waiting_for = next, pending = pending.len(), limit, "reorder buffer overflow");
+        return Err(StreamError::ReorderBufferOverflow { waiting_for: next, pending: pending.len(), limit });
+    }
+    Ok(())
+}
+
+/// At `finish`, once every contiguous segment is written: nothing may wait
+/// behind a gap, and the final segment must be the last one written.
+fn check_drained<T>(pending: &BTreeMap<u32, T>, next: u32, final_index: Option<u32>) -> Result<(), StreamError> {
+    if !pending.is_empty() {
+        debug!(missing = next, stranded = pending.len(), "segments stranded behind a gap");
+        return Err(StreamError::Validation(format!("missing segment {next}, {} segments stranded", pending.len())));
+    }
+    let Some(final_index) = final_index else {
+        return Err(StreamError::Validation("Missing final segment".into()));
+    };
+    let last = next.checked_sub(1);
+    if last != Some(final_index) {
+        return Err(StreamError::Validation(format!(
+            "final segment {final_index} is not the last segment written ({})",
+            last.map_or_else(|| "none".to_string(), |last| last.to_string())
+        )));
+    }
+    Ok(())
+}
+
 pub struct OrderedEncryptedWriter<'a, W: Write> {
     out: &'a mut W,
     next: u32,
     pending: BTreeMap<u32, EncryptedSegment>,
+    max_pending: usize,
     final_index: Option<u32>,
+    index: SegmentIndexMap,
 }
 
 impl<'a, W: Write> OrderedEncryptedWriter<'a, W> {
@@ -266,21 +1063,54 @@ impl<'a, W: Write> OrderedEncryptedWriter<'a, W> {
             out,
             next: 0,
             pending: BTreeMap::new(),
+            max_pending: usize::MAX,
             final_index: None,
+            index: SegmentIndexMap::new(),
         }
     }
 
+    /// Out-of-order segments buffered before `push` fails (unbounded by default).
+    pub fn with_max_pending(mut self, limit: usize) -> Self {
+        self.max_pending = limit;
+        self
+    }
+
+    /// Absolute offset of the first segment (default: right after a header without extensions).
+    pub fn with_payload_offset(mut self, offset: u64) -> Self {
+        self.index = SegmentIndexMap::starting_at(offset);
+        self
+    }
+
+    /// Offsets of every segment written so far.
+    pub fn index(&self) -> &SegmentIndexMap {
+        &self.index
+    }
+
+    /// Append the index trailer once [`OrderedEncryptedWriter::finish`] succeeded.
+    pub fn write_index_trailer(&mut self, segment: &EncryptedSegment) -> Result<u64, StreamError> {
+        write_index_trailer(self.out, segment, &self.index)
+    }
+
+    /// Flush the sink once everything (index trailer included) is written.
+    pub fn flush(&mut self) -> Result<(), StreamError> {
+        self.out.flush()?;
+        Ok(())
+    }
+
     pub fn push(&mut self, segment: EncryptedSegment) -> Result<(), StreamError> {
         // Accept empty wire if FINAL_SEGMENT is set
         if segment.header.flags.contains(SegmentFlags::FINAL_SEGMENT) && segment.wire.is_empty() {
-            eprintln!("[ENCRYPT WRITER] Final empty segment {} detected", segment.header.segment_index);
+            trace!(segment_index = segment.header.segment_index, "final segment queued");
             self.final_index = Some(segment.header.segment_index);
         }
         // Don’t write immediately — enqueue it
         self.pending.insert(segment.header.segment_index, segment);
-        self.flush_ready()
+        self.flush_ready()?;
+        check_pending(&self.pending, self.next, self.max_pending)
     }
 
+    /// Write what is left in order, then fail if a segment never arrived
+    /// (see `check_drained`) or the final marker is missing.
     pub fn finish(&mut self) -> Result<(), StreamError> {
         // Flush any pending segments in order
         while let Some(seg) = self.pending.remove(&self.next) {
@@ -288,12 +1118,8 @@ impl<'a, W: Write> OrderedEncryptedWriter<'a, W> {
             self.next += 1;
         }
 
-        // Validation: final marker must have been seen
-        if self.final_index.is_none() {
-            return Err(StreamError::Validation("Missing final segment".into()));
-        }
-
-        Ok(())
+        // Validation: no gap, and the final marker closes the stream
+        check_drained(&self.pending, self.next, self.final_index)
     }
     
     fn flush_ready(&mut self) -> Result<(), StreamError> {
@@ -306,8 +1132,9 @@ impl<'a, W: Write> OrderedEncryptedWriter<'a, W> {
 
     fn write(&mut self, segment: EncryptedSegment) -> Result<(), StreamError> {
         let segment_enc = encode_segment(&segment.header, &segment.wire).map_err(|e| StreamError::Segment(e))?;
-            eprintln!("[ENCRYPT WRITER] Final writing segment {}", segment.header.segment_index);
+        trace!(segment_index = segment.header.segment_index, "writing encrypted segment");
         self.out.write_all(&segment_enc)?;
+        self.index.push(&segment.header).map_err(StreamError::Segment)?;
         Ok(())
     }
 }
@@ -316,33 +1143,94 @@ pub struct OrderedPlaintextWriter<'a, W: Write> {
     out: &'a mut W,
     next: u32,
     pending: BTreeMap<u32, DecryptedSegment>,
+    max_pending: usize,
     final_index: Option<u32>,
+    trailer: PlaintextTrailer,
+    bytes_written: u64,
+    segments_written: u64,
+    /// Backend and output limit when segments arrive still compressed.
+    decompressor: Option<(Box<dyn CompressionBackend>, usize)>,
+    decompress_time: Duration,
 }
 
 impl<'a, W: Write> OrderedPlaintextWriter<'a, W> {
     pub fn new(out: &'a mut W) -> Self {
+        Self::starting_at(out, 0)
+    }
+
+    /// Writer for a resumed stream: segment `next` is the first one written.
+    pub fn starting_at(out: &'a mut W, next: u32) -> Self {
         Self {
             out,
-            next: 0,
+            next,
             pending: BTreeMap::new(),
+            max_pending: usize::MAX,
             final_index: None,
+            trailer: PlaintextTrailer::default(),
+            bytes_written: 0,
+            segments_written: 0,
+            decompressor: None,
+            decompress_time: Duration::ZERO,
         }
     }
-    pub fn push(&mut self, segment: &DecryptedSegment) -> Result<(), StreamError> {
+
+    /// Out-of-order segments buffered before `push` fails (unbounded by default).
+    pub fn with_max_pending(mut self, limit: usize) -> Self {
+        self.max_pending = limit;
+        self
+    }
+
+    /// Verify the plaintext against the stream's trailer segment.
+    ///
+    /// Only meaningful from segment 0: a resumed writer never sees the whole plaintext.
+    pub fn with_trailer(mut self, trailer: PlaintextTrailer) -> Self {
+        self.trailer = trailer;
+        self
+    }
+
+    /// Accept compressed segments and decompress each one as it is written.
+    ///
+    /// Buffered out-of-order segments stay compressed, so at most one segment of
+    /// plaintext is resident. Output past `max_output` bytes per segment fails
+    /// with `CompressionError::ChunkTooLarge`.
+    pub fn with_decompressor(mut self, backend: Box<dyn CompressionBackend>, max_output: usize) -> Self {
+        self.decompressor = Some((backend, max_output));
+        self
+    }
+
+    /// Plaintext bytes written so far.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Time spent decompressing (see [`with_decompressor`](Self::with_decompressor)).
+    pub fn decompress_time(&self) -> Duration {
+        self.decompress_time
+    }
+
+    /// Segments written so far, trailer segment included.
+    pub fn segments_written(&self) -> u64 {
+        self.segments_written
+    }
+
+    pub fn push(&mut self, segment: DecryptedSegment) -> Result<(), StreamError> {
         // Accept empty wire if FINAL_SEGMENT is set
-        if segment.header.flags.contains(SegmentFlags::FINAL_SEGMENT) && segment.bytes.is_empty() {
-            eprintln!("[PLAINTEXT WRITER] Final empty segment {} detected", segment.header.segment_index);
+        if segment.header.flags.contains(SegmentFlags::FINAL_SEGMENT) && segment.is_empty() {
+            trace!(segment_index = segment.header.segment_index, "final segment queued");
             self.final_index = Some(segment.header.segment_index);
 
             // Enqueue the final marker like any other segment
         }
 
         // Normal push logic
-        eprintln!("[PLAINTEXT WRITER] Queuing segment {}", segment.header.segment_index);
-        self.pending.insert(segment.header.segment_index, segment.clone());
-        self.flush_ready()
+        self.pending.insert(segment.header.segment_index, segment);
+        self.flush_ready()?;
+        check_pending(&self.pending, self.next, self.max_pending)
     }
 
+    /// Write what is left in order, then fail if a segment never arrived
+    /// (see `check_drained`), the final marker is missing or the trailer
+    /// was not verified.
     pub fn finish(&mut self) -> Result<(), StreamError> {
         // Flush any pending segments in order
         while let Some(segment) = self.pending.remove(&self.next) {
@@ -350,12 +1238,13 @@ impl<'a, W: Write> OrderedPlaintextWriter<'a, W> {
             self.next += 1;
         }
 
-        // Validation: final marker must have been seen
-        if self.final_index.is_none() {
-            return Err(StreamError::Validation("Missing final segment".into()));
-        }
+        // Validation: no gap, and the final marker closes the stream
+        check_drained(&self.pending, self.next, self.final_index)?;
+        self.trailer.finish()?;
+        // A buffered sink (stdout) must not lose its tail to an ignored drop-time error
+        self.out.flush()?;
 
-        eprintln!("[PLAINTEXT WRITER] Finished, final marker index {:?}", self.final_index);
+        trace!(final_index = ?self.final_index, "plaintext writer finished");
         Ok(())
     }
 
@@ -367,9 +1256,26 @@ impl<'a, W: Write> OrderedPlaintextWriter<'a, W> {
         Ok(())
     }
 
-    fn write(&mut self, segment: DecryptedSegment) -> Result<(), StreamError> {
-        eprintln!("[PLAINTEXT WRITER] Writing segment {}", segment.header.segment_index);
-        self.out.write_all(&segment.bytes)?;
+    fn write(&mut self, mut segment: DecryptedSegment) -> Result<(), StreamError> {
+        if let Some((backend, max_output)) = &mut self.decompressor {
+            let start = Instant::now();
+            decompress_decrypted(backend.as_mut(), &mut segment, *max_output)
+                .map_err(|e| StreamError::CompressionWorker(CompressionWorkerError::Compression(e)))?;
+            self.decompress_time += start.elapsed();
+        }
+        self.segments_written += 1;
+        // The trailer segment is metadata, never plaintext
+        if PlaintextTrailer::is_trailer(segment.header.flags) {
+            trace!(segment_index = segment.header.segment_index, "verifying plaintext trailer");
+            return self.trailer.verify(&segment.to_bytes());
+        }
+
+        trace!(segment_index = segment.header.segment_index, "writing plaintext segment");
+        for frame in &segment.frames {
+            self.trailer.update(frame);
+            self.out.write_all(frame)?;
+            self.bytes_written += frame.len() as u64;
+        }
         Ok(())
     }
 }
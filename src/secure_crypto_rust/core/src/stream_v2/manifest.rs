@@ -0,0 +1,235 @@
+// # 📂 src/stream_v2/manifest.rs
+
+// ## 📂 File: `src/stream_v2/manifest.rs`
+// ## Detached segment digest manifests
+//
+// Segment digests are computed over frame ciphertext, so they can be
+// recomputed from the encrypted stream alone, without the key:
+//
+// - `export_manifest` walks the segments up to the final one and records
+//   `(segment_index, frame_count, digest_alg, digest)` for each
+// - `verify_against_manifest` recomputes them and reports every segment as
+//   matching, mismatching, malformed, missing or unexpected
+// - manifests serialize as JSON (digests as hex) or as compact bincode
+//
+// A manifest proves the ciphertext is unchanged; it does not authenticate it.
+// Only decryption (or `verify_stream_v2`) checks the digests against the key.
+// Keyed digests (`Blake3Keyed`) cannot be recomputed without the session
+// key: such segments fail export and report as malformed.
+
+use std::collections::BTreeMap;
+
+use bincode::config::standard;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::crypto::{DigestAlg, SegmentDigestBuilder, ct_eq};
+use crate::stream_v2::framing::{FrameHeader, FrameType, decode::parse_frame_header};
+use crate::stream_v2::io::{InputSource, PayloadReader, open_input, read_segment};
+use crate::stream_v2::segmenting::SegmentHeader;
+use crate::stream_v2::segmenting::types::SegmentFlags;
+use crate::types::StreamError;
+
+/// Manifest format version.
+pub const MANIFEST_VERSION: u16 = 1;
+
+/// Recorded digest of one segment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SegmentDigestEntry {
+    pub segment_index: u32,
+    /// Frames in the segment (data + digest + terminator), as in its header.
+    pub frame_count: u32,
+    /// Raw digest algorithm id (`DigestAlg` registry).
+    pub digest_alg: u16,
+    /// Digest over the data frame ciphertext; empty for an empty final segment.
+    #[serde(serialize_with = "serialize_digest", deserialize_with = "deserialize_digest")]
+    pub digest: Vec<u8>,
+}
+
+/// Per-segment ciphertext digests of one stream.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DigestManifest {
+    pub version: u16,
+    pub segments: Vec<SegmentDigestEntry>,
+}
+
+impl DigestManifest {
+    pub fn to_json(&self) -> Result<String, StreamError> {
+        serde_json::to_string_pretty(self).map_err(|e| StreamError::Validation(format!("manifest encode failed: {}", e)))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, StreamError> {
+        let manifest: Self =
+            serde_json::from_str(json).map_err(|e| StreamError::Validation(format!("manifest decode failed: {}", e)))?;
+        manifest.check_version()
+    }
+
+    /// Compact binary form (bincode, standard config).
+    pub fn to_bytes(&self) -> Result<Vec<u8>, StreamError> {
+        bincode::serde::encode_to_vec(self, standard())
+            .map_err(|e| StreamError::Validation(format!("manifest encode failed: {}", e)))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, StreamError> {
+        let (manifest, used): (Self, usize) = bincode::serde::decode_from_slice(bytes, standard())
+            .map_err(|e| StreamError::Validation(format!("manifest decode failed: {}", e)))?;
+        if used != bytes.len() {
+            return Err(StreamError::Validation(format!("manifest has {} trailing bytes", bytes.len() - used)));
+        }
+        manifest.check_version()
+    }
+
+    fn check_version(self) -> Result<Self, StreamError> {
+        if self.version != MANIFEST_VERSION {
+            return Err(StreamError::Validation(format!("unsupported manifest version {}", self.version)));
+        }
+        Ok(self)
+    }
+}
+
+/// Outcome for one segment of [`verify_against_manifest`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SegmentDigestStatus {
+    Match,
+    /// Frame count or digest differs from the manifest.
+    Mismatch,
+    /// Wire CRC or framing is broken: no digest could be computed.
+    Malformed(String),
+    /// In the manifest, but not in the stream.
+    Missing,
+    /// In the stream, but not in the manifest.
+    Unexpected,
+}
+
+/// Per-segment result of [`verify_against_manifest`], ordered by segment index.
+#[derive(Debug, Clone, Default)]
+pub struct ManifestReport {
+    pub segments: Vec<(u32, SegmentDigestStatus)>,
+}
+
+impl ManifestReport {
+    pub fn is_ok(&self) -> bool {
+        self.segments.iter().all(|(_, status)| *status == SegmentDigestStatus::Match)
+    }
+
+    /// Indices of every segment that did not match.
+    pub fn failed_segments(&self) -> Vec<u32> {
+        self.segments
+            .iter()
+            .filter(|(_, status)| *status != SegmentDigestStatus::Match)
+            .map(|(segment_index, _)| *segment_index)
+            .collect()
+    }
+}
+
+/// 📜 Recompute the digest of every segment, without the key
+///
+/// Fails on an unreadable stream or a segment whose wire CRC or framing is
+/// broken: a manifest is only exported from an intact stream.
+pub fn export_manifest(input: InputSource) -> Result<DigestManifest, StreamError> {
+    let (_, mut reader) = PayloadReader::with_header(open_input(input)?)?;
+    let mut segments = Vec::new();
+
+    while let Some((header, wire)) = read_segment(&mut reader)? {
+        header.validate(&wire).map_err(StreamError::Segment)?;
+        segments.push(SegmentDigestEntry {
+            segment_index: header.segment_index,
+            frame_count: header.frame_count,
+            digest_alg: header.digest_alg,
+            digest: segment_digest(&header, &wire)?,
+        });
+        if header.flags.contains(SegmentFlags::FINAL_SEGMENT) {
+            return Ok(DigestManifest { version: MANIFEST_VERSION, segments });
+        }
+    }
+    Err(StreamError::Validation(format!(
+        "Missing final segment: stream ends after {} segments",
+        segments.len()
+    )))
+}
+
+/// 📜 Compare a stream with a manifest, segment by segment
+///
+/// A stream that cannot be read to its final segment reports the remaining
+/// manifest entries as `Missing`; only an unreadable stream header is an error.
+pub fn verify_against_manifest(input: InputSource, manifest: &DigestManifest) -> Result<ManifestReport, StreamError> {
+    let (_, mut reader) = PayloadReader::with_header(open_input(input)?)?;
+    let mut expected: BTreeMap<u32, &SegmentDigestEntry> =
+        manifest.segments.iter().map(|entry| (entry.segment_index, entry)).collect();
+    let mut statuses = BTreeMap::new();
+
+    while let Ok(Some((header, wire))) = read_segment(&mut reader) {
+        let status = match expected.remove(&header.segment_index) {
+            None => SegmentDigestStatus::Unexpected,
+            Some(entry) => match header.validate(&wire).map_err(StreamError::Segment).and_then(|()| segment_digest(&header, &wire)) {
+                Err(e) => SegmentDigestStatus::Malformed(e.to_string()),
+                Ok(digest) if entry.frame_count == header.frame_count
+                    && entry.digest_alg == header.digest_alg
+                    && ct_eq(&entry.digest, &digest) => SegmentDigestStatus::Match,
+                Ok(_) => SegmentDigestStatus::Mismatch,
+            },
+        };
+        statuses.insert(header.segment_index, status);
+        if header.flags.contains(SegmentFlags::FINAL_SEGMENT) {
+            break;
+        }
+    }
+    statuses.extend(expected.into_keys().map(|segment_index| (segment_index, SegmentDigestStatus::Missing)));
+
+    Ok(ManifestReport { segments: statuses.into_iter().collect() })
+}
+
+/// Digest of a segment's data frames, exactly as the encrypt side computes it.
+fn segment_digest(header: &SegmentHeader, wire: &[u8]) -> Result<Vec<u8>, StreamError> {
+    if wire.is_empty() {
+        return Ok(Vec::new());
+    }
+    let alg = DigestAlg::try_from(header.digest_alg)
+        .map_err(|_| StreamError::Validation(format!("unknown digest algorithm {:#06x}", header.digest_alg)))?;
+
+    // Data frames, located without decrypting
+    let mut data_frames = Vec::new();
+    let mut offset = 0;
+    while offset < wire.len() {
+        let frame = parse_frame_header(&wire[offset..]).map_err(StreamError::Frame)?;
+        let ct_start = offset + FrameHeader::LEN;
+        let ct_end = ct_start + frame.ciphertext_len as usize;
+        if ct_end > wire.len() {
+            return Err(StreamError::Validation(format!(
+                "frame {} of segment {} is truncated",
+                frame.frame_index, header.segment_index
+            )));
+        }
+        if frame.frame_type == FrameType::Data {
+            data_frames.push((frame.frame_index, &wire[ct_start..ct_end]));
+        }
+        offset = ct_end;
+    }
+    data_frames.sort_unstable_by_key(|(frame_index, _)| *frame_index);
+
+    let mut builder = SegmentDigestBuilder::for_alg(alg, None, header.segment_index, data_frames.len() as u32)
+        .map_err(|e| StreamError::Validation(format!("segment {}: {}", header.segment_index, e)))?;
+    for (frame_index, ciphertext) in data_frames {
+        builder
+            .update_frame(frame_index, ciphertext)
+            .map_err(|e| StreamError::Validation(format!("segment {}: {}", header.segment_index, e)))?;
+    }
+    Ok(builder.finalize())
+}
+
+/// Digests are hex strings in human-readable formats, raw bytes otherwise.
+fn serialize_digest<S: Serializer>(digest: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&hex::encode(digest))
+    } else {
+        serializer.serialize_bytes(digest)
+    }
+}
+
+fn deserialize_digest<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    if deserializer.is_human_readable() {
+        let hex_digest = String::deserialize(deserializer)?;
+        hex::decode(hex_digest).map_err(serde::de::Error::custom)
+    } else {
+        Vec::<u8>::deserialize(deserializer)
+    }
+}
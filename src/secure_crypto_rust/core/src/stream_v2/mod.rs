@@ -12,6 +12,16 @@ pub mod parallelism;
 pub mod pipeline;
 pub mod io;
 pub mod core;
+pub mod adapters;
+pub mod random_access;
+pub mod verify;
+pub mod reencrypt;
+pub mod manifest;
+pub mod progress;
+pub mod sink;
+pub mod overhead;
+#[cfg(feature = "async")]
+pub mod async_io;
 
 pub mod segmenting;
 pub mod segment_worker;
@@ -24,8 +34,70 @@ pub use io::{
 };
 
 pub use core::{
+    ApiConfig,
+    EncryptParams,
+    DecryptParams,
+    SaltPolicy,
     encrypt_stream_v2,
     decrypt_stream_v2,
+    decrypt_stream_v2_from,
+    decrypt_stream_v2_with_keyring,
+    encrypt_bytes,
+    decrypt_bytes,
+};
+
+pub use adapters::{
+    EncryptingWriter,
+    DecryptingReader,
+};
+
+pub use random_access::RandomAccessDecryptor;
+
+pub use overhead::{
+    estimate_ciphertext_len,
+    estimate_ciphertext_len_with,
+    estimate_max_plaintext_for,
+    max_segment_wire_len,
+    min_segment_pad_target,
+};
+
+pub use progress::{
+    ProgressCallback,
+    ProgressEvent,
+};
+
+pub use verify::{
+    VerifyFailure,
+    VerifyReport,
+    verify_stream_v2,
+};
+
+pub use reencrypt::{
+    REENCRYPT_PIPE_DEPTH,
+    ReencryptReport,
+    reencrypt_stream_v2,
+};
+
+pub use manifest::{
+    DigestManifest,
+    ManifestReport,
+    SegmentDigestEntry,
+    SegmentDigestStatus,
+    export_manifest,
+    verify_against_manifest,
+};
+
+pub use parallelism::{
+    HybridParallelismProfile,
+    ParallelismProfile,
+    ParallelismProfileBuilder,
+    ProfileWarning,
+};
+
+#[cfg(feature = "async")]
+pub use async_io::{
+    encrypt_stream_v2_async,
+    decrypt_stream_v2_async,
 };
 
 
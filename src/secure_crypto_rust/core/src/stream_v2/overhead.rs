@@ -0,0 +1,133 @@
+// ## 📂 File: `src/stream_v2/overhead.rs`
+//! Encrypted stream size, computed from the layout instead of by encrypting.
+//!
+//! Exact when the payload is stored uncompressed (`CompressionCodec::Auto`
+//! on incompressible input, or any codec falling back to raw); an upper bound
+//! otherwise, since a segment is never stored larger than its plaintext.
+//!
+//! ```text
+//! [ HeaderV1 ]
+//! per data segment (one per chunk):
+//!   [ SegmentHeader ][ data frames ][ digest frame ][ terminator frame ]
+//!   (closing frames as `HeaderV1::frame_layout` has them)
+//! [ trailer segment ]          HAS_CRC32 | HAS_TOTAL_LEN | whole-stream digest
+//! [ final segment header ]     empty
+//! [ index segment ][ footer ]  HAS_SEGMENT_INDEX
+//! ```
+
+use crate::constants::flags;
+use crate::crypto::{DigestAlg, TAG_LEN};
+use crate::headers::{FrameLayout, HeaderV1};
+use crate::stream_v2::framing::types::{FrameHeader, PADDING_FRAME_OVERHEAD};
+use crate::stream_v2::segment_worker::types::get_frame_size;
+use crate::stream_v2::segmenting::{SegmentHeader, SegmentIndexMap};
+
+/// Ciphertext length of `plaintext_len` bytes encrypted under `header`,
+/// with the default frame size and digest algorithm.
+pub fn estimate_ciphertext_len(plaintext_len: u64, header: &HeaderV1) -> u64 {
+    let frame_size = get_frame_size(header.chunk_size as usize);
+    estimate_ciphertext_len_with(plaintext_len, header, frame_size, DigestAlg::Blake3)
+}
+
+/// [`estimate_ciphertext_len`] for an explicit frame size and digest algorithm
+/// (`EncryptParams::frame_size` / `EncryptParams::digest_alg`).
+pub fn estimate_ciphertext_len_with(
+    plaintext_len: u64,
+    header: &HeaderV1,
+    frame_size: usize,
+    digest_alg: DigestAlg,
+) -> u64 {
+    let layout = Layout {
+        frame_size: (frame_size as u64).max(1),
+        digest_len: digest_alg.digest_len() as u64,
+        frames: header.frame_layout(),
+    };
+    let chunk_size = u64::from(header.chunk_size).max(1);
+
+    let full_chunks = plaintext_len / chunk_size;
+    let rest = plaintext_len % chunk_size;
+    let mut segments = full_chunks + u64::from(rest > 0);
+    let mut total = (HeaderV1::LEN as u64)
+        .saturating_add(full_chunks.saturating_mul(layout.segment_len(chunk_size)))
+        .saturating_add(if rest > 0 { layout.segment_len(rest) } else { 0 });
+
+    let trailer_len = if header.flags & flags::HAS_CRC32 != 0 { 4 } else { 0 }
+        + if header.flags & flags::HAS_TOTAL_LEN != 0 { 8 } else { 0 }
+        + if layout.frames.final_digest() { 32 } else { 0 };
+    if trailer_len > 0 {
+        total = total.saturating_add(layout.segment_len(trailer_len));
+        segments += 1;
+    }
+
+    // Empty final segment: header only
+    total = total.saturating_add(SegmentHeader::LEN as u64);
+    segments += 1;
+
+    if header.flags & flags::HAS_SEGMENT_INDEX != 0 {
+        let index_len = 4 + segments.saturating_mul(SegmentIndexMap::ENTRY_LEN as u64);
+        total = total
+            .saturating_add(layout.segment_len(index_len))
+            .saturating_add(SegmentIndexMap::FOOTER_LEN as u64);
+    }
+    total
+}
+
+/// Wire bytes (frames only, `SegmentHeader` excluded) of the largest segment
+/// `chunk_size` allows: a full chunk stored uncompressed, with both closing
+/// frames ([`FrameLayout::LEGACY`], the largest layout).
+pub fn max_segment_wire_len(chunk_size: u32, frame_size: usize, digest_alg: DigestAlg) -> u64 {
+    let layout = Layout {
+        frame_size: (frame_size as u64).max(1),
+        digest_len: digest_alg.digest_len() as u64,
+        frames: FrameLayout::LEGACY,
+    };
+    layout.segment_len(u64::from(chunk_size).max(1)) - SegmentHeader::LEN as u64
+}
+
+/// Smallest `EncryptParams::pad_segments_to` target every segment can be padded
+/// to exactly: the largest segment plus a one-byte padding frame.
+pub fn min_segment_pad_target(chunk_size: u32, frame_size: usize, digest_alg: DigestAlg) -> u64 {
+    max_segment_wire_len(chunk_size, frame_size, digest_alg) + PADDING_FRAME_OVERHEAD as u64 + 1
+}
+
+/// Largest plaintext whose [`estimate_ciphertext_len`] fits `ciphertext_budget`.
+///
+/// `None` when not even an empty stream fits.
+pub fn estimate_max_plaintext_for(ciphertext_budget: u64, header: &HeaderV1) -> Option<u64> {
+    if estimate_ciphertext_len(0, header) > ciphertext_budget {
+        return None;
+    }
+    // Ciphertext always exceeds plaintext, so the answer is below the budget
+    let (mut lo, mut hi) = (0u64, ciphertext_budget);
+    while lo < hi {
+        let mid = lo + (hi - lo).div_ceil(2);
+        if estimate_ciphertext_len(mid, header) <= ciphertext_budget {
+            lo = mid;
+        } else {
+            hi = mid - 1;
+        }
+    }
+    Some(lo)
+}
+
+struct Layout {
+    frame_size: u64,
+    digest_len: u64,
+    frames: FrameLayout,
+}
+
+impl Layout {
+    /// Encoded length of a non-empty segment carrying `payload_len` bytes.
+    fn segment_len(&self, payload_len: u64) -> u64 {
+        let frame_overhead = (FrameHeader::LEN + TAG_LEN) as u64;
+        let data_frames = payload_len.div_ceil(self.frame_size);
+        // Digest frame: [ alg_id: u16 ][ digest_len: u16 ][ digest ]
+        let digest_frame = if self.frames.segment_digest { frame_overhead + 4 + self.digest_len } else { 0 };
+        // Terminator frame: header only, nothing is sealed
+        let terminator_frame = if self.frames.terminator { FrameHeader::LEN as u64 } else { 0 };
+        (SegmentHeader::LEN as u64)
+            .saturating_add(payload_len)
+            .saturating_add(data_frames.saturating_mul(frame_overhead))
+            .saturating_add(digest_frame + terminator_frame)
+    }
+}
@@ -1,6 +1,54 @@
 
+use std::collections::HashMap;
+
+use tracing::debug;
+
+use crate::constants::{DEFAULT_QUEUE_CAP, DEFAULT_WORKERS, MAX_QUEUE_CAP};
+use crate::headers::{HeaderV1, Strategy};
+use crate::types::StreamError;
+
 pub const GPU_THRESHOLD: usize = 4 * 1024 * 1024; // 4 MB
 
+/// Default input size below which a `Strategy::Auto` stream runs single-threaded.
+pub const AUTO_PARALLEL_THRESHOLD: u64 = 4 * 1024 * 1024; // 4 MiB
+
+/// How a `Strategy::Auto` stream is run, decided from the input size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoPlan {
+    /// Known size below the threshold: one worker, one segment in flight.
+    Sequential,
+    /// Known size at or above the threshold: [`HybridParallelismProfile::dynamic`].
+    Parallel,
+    /// Unknown size: the dynamic profile, but each of the first `segments`
+    /// segments waits for its predecessor before being read.
+    Warmup { segments: u32 },
+}
+
+impl AutoPlan {
+    /// Plan for an input of `size_hint` bytes (`None` when unknowable).
+    ///
+    /// The warm-up covers `threshold` bytes of `chunk_size` segments, so a
+    /// stream that turns out small never spins up the worker pool.
+    pub fn for_size(size_hint: Option<u64>, threshold: u64, chunk_size: u32) -> Self {
+        match size_hint {
+            Some(size) if size < threshold => AutoPlan::Sequential,
+            Some(_) => AutoPlan::Parallel,
+            None => {
+                let segments = threshold.div_ceil(u64::from(chunk_size.max(1)));
+                AutoPlan::Warmup { segments: segments.clamp(1, u64::from(u32::MAX)) as u32 }
+            }
+        }
+    }
+
+    /// Segments to run one at a time before promoting (0 = none).
+    pub fn warmup_segments(&self) -> u32 {
+        match self {
+            AutoPlan::Warmup { segments } => *segments,
+            _ => 0,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub enum GpuBackend {
     None,
@@ -20,7 +68,7 @@ async fn detect_wgpu_count() -> usize {
     let instance = wgpu::Instance::default();
     let adapters = instance.enumerate_adapters(wgpu::Backends::all()).await;
     if !adapters.is_empty() {
-        eprintln!("[GPU DETECT] wgpu adapters found: {}", adapters.len());
+        debug!(count = adapters.len(), "wgpu adapters found");
         return adapters.len();
     }
     0
@@ -49,7 +97,7 @@ pub fn detect_gpu_info() -> GpuInfo {
     {
         if let Ok(count) = cust::device::Device::num_devices() {
             if count > 0 {
-                eprintln!("[GPU DETECT] CUDA devices found: {}", count);
+                debug!(count, "CUDA devices found");
                 let names = (0..count)
                     .filter_map(|i| cust::device::Device::get(i).ok())
                     .map(|d| d.name().unwrap_or_else(|_| "Unknown CUDA device".into()))
@@ -77,7 +125,7 @@ pub fn detect_gpu_info() -> GpuInfo {
         cl_count
     };
     if cl_count > 0 {
-        eprintln!("[GPU DETECT] OpenCL devices found: {}", cl_count);
+        debug!(count = cl_count, "OpenCL devices found");
         return GpuInfo {
             count: cl_count,
             backend: GpuBackend::OpenCL,
@@ -88,7 +136,6 @@ pub fn detect_gpu_info() -> GpuInfo {
     // Vulkan/Metal/DX via wgpu
     let adapters = pollster::block_on(detect_wgpu_count());
     if adapters > 0 {
-        eprintln!("[GPU DETECT] wgpu adapters found: {}", adapters);
         // wgpu::Adapter doesn’t expose names directly without async device creation,
         // so we can leave names empty or fill with placeholders.
         return GpuInfo {
@@ -98,7 +145,7 @@ pub fn detect_gpu_info() -> GpuInfo {
         };
     }
 
-    eprintln!("[GPU DETECT] No GPU devices found");
+    debug!("no GPU devices found");
     GpuInfo {
         count: 0,
         backend: GpuBackend::None,
@@ -135,6 +182,141 @@ pub fn detect_gpu_info() -> GpuInfo {
 //     0
 // }
 
+/// Memory that [`HybridParallelismProfile::dynamic_with_budget`] sizes the
+/// in-flight window against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryBudget {
+    /// Available memory as reported by the OS.
+    Detect,
+    /// Explicit byte count, e.g. a container's cgroup limit.
+    Bytes(u64),
+}
+
+impl MemoryBudget {
+    /// Available bytes; `Detect` queries sysinfo, which reports bytes.
+    pub fn available_bytes(&self) -> u64 {
+        match self {
+            MemoryBudget::Detect => {
+                let mut sys = sysinfo::System::new();
+                sys.refresh_memory();
+                sys.available_memory()
+            }
+            MemoryBudget::Bytes(bytes) => *bytes,
+        }
+    }
+}
+
+/// Segments that fit in `mem_fraction` of `available_bytes`, in `1..=hard_cap`.
+///
+/// A `max_segment_size` of zero counts as one byte; `mem_fraction` is clamped to `0.0..=1.0`.
+pub fn inflight_for_budget(available_bytes: u64, max_segment_size: u32, mem_fraction: f64, hard_cap: usize) -> usize {
+    let budget = (available_bytes as f64 * mem_fraction.clamp(0.0, 1.0)) as u64;
+    let max_segments = budget / u64::from(max_segment_size.max(1));
+    max_segments.min(hard_cap as u64).max(1) as usize
+}
+
+/// A setting the [`ParallelismProfileBuilder`] had to adjust.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProfileWarning {
+    /// `requested` exceeded the limit for `field`; `applied` was used instead.
+    Clamped { field: &'static str, requested: usize, applied: usize },
+}
+
+impl std::fmt::Display for ProfileWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProfileWarning::Clamped { field, requested, applied } => {
+                write!(f, "{field} clamped from {requested} to {applied}")
+            }
+        }
+    }
+}
+
+/// CPU-only parallelism configuration.
+///
+/// Built through [`ParallelismProfile::builder`], which clamps out-of-range
+/// values and reports every adjustment in [`warnings`](Self::warnings).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParallelismProfile {
+    cpu_workers: usize,
+    inflight_segments: usize,
+    warnings: Vec<ProfileWarning>,
+}
+
+impl ParallelismProfile {
+    pub fn builder() -> ParallelismProfileBuilder {
+        ParallelismProfileBuilder::default()
+    }
+
+    pub fn cpu_workers(&self) -> usize {
+        self.cpu_workers
+    }
+
+    pub fn inflight_segments(&self) -> usize {
+        self.inflight_segments
+    }
+
+    /// What the builder clamped; empty when every value was taken as given.
+    pub fn warnings(&self) -> &[ProfileWarning] {
+        &self.warnings
+    }
+}
+
+/// Builder for [`ParallelismProfile`].
+///
+/// Unset values default to `DEFAULT_WORKERS` / `DEFAULT_QUEUE_CAP`.
+#[derive(Debug, Clone, Default)]
+pub struct ParallelismProfileBuilder {
+    cpu_workers: Option<usize>,
+    inflight_segments: Option<usize>,
+}
+
+impl ParallelismProfileBuilder {
+    pub fn cpu_workers(mut self, workers: usize) -> Self {
+        self.cpu_workers = Some(workers);
+        self
+    }
+
+    pub fn inflight_segments(mut self, segments: usize) -> Self {
+        self.inflight_segments = Some(segments);
+        self
+    }
+
+    /// Zero for either value is an error; values above `num_cpus::get()`
+    /// workers or `MAX_QUEUE_CAP` segments are clamped with a warning.
+    pub fn build(self) -> Result<ParallelismProfile, StreamError> {
+        let max_workers = num_cpus::get().max(1);
+        let mut warnings = Vec::new();
+
+        let cpu_workers = match self.cpu_workers {
+            Some(requested) => clamp_setting("cpu_workers", requested, max_workers, &mut warnings)?,
+            None => DEFAULT_WORKERS.min(max_workers),
+        };
+        let inflight_segments = match self.inflight_segments {
+            Some(requested) => clamp_setting("inflight_segments", requested, MAX_QUEUE_CAP, &mut warnings)?,
+            None => DEFAULT_QUEUE_CAP,
+        };
+
+        Ok(ParallelismProfile { cpu_workers, inflight_segments, warnings })
+    }
+}
+
+fn clamp_setting(
+    field: &'static str,
+    requested: usize,
+    max: usize,
+    warnings: &mut Vec<ProfileWarning>,
+) -> Result<usize, StreamError> {
+    if requested == 0 {
+        return Err(StreamError::Validation(format!("{field} must be at least 1")));
+    }
+    if requested > max {
+        warnings.push(ProfileWarning::Clamped { field, requested, applied: max });
+        return Ok(max);
+    }
+    Ok(requested)
+}
+
 /// Parallelism configuration
 #[derive(Debug, Clone)]
 pub struct HybridParallelismProfile {
@@ -201,37 +383,81 @@ impl HybridParallelismProfile {
     // * `max_segments = 8192 MB / 32 MB = 256`
     // * With `hard_cap = 64`, we get `inflight_segments = 64`.
     pub fn dynamic(max_segment_size: u32, mem_fraction: f64, hard_cap: usize) -> Self {
+        Self::dynamic_with_budget(max_segment_size, MemoryBudget::Detect, mem_fraction, hard_cap)
+    }
+
+    /// [`dynamic`](Self::dynamic) against an explicit memory budget.
+    ///
+    /// `mem_fraction` of `budget` bounds the in-flight segments (see [`inflight_for_budget`]).
+    pub fn dynamic_with_budget(max_segment_size: u32, budget: MemoryBudget, mem_fraction: f64, hard_cap: usize) -> Self {
         let cores = num_cpus::get();
         let cpu_workers = cores.saturating_sub(1);
 
-        let mut sys = sysinfo::System::new_all();
-        sys.refresh_memory();
-        let avail_bytes = sys.available_memory() * 1024;
-        let budget = (avail_bytes as f64 * mem_fraction) as u32;
-        let max_segments = budget / max_segment_size;
+        let avail_bytes = budget.available_bytes();
+        let inflight_segments = inflight_for_budget(avail_bytes, max_segment_size, mem_fraction, hard_cap);
 
         let gpu = detect_gpu_info();
         let gpu_workers = gpu.count;
 
-        eprintln!(
-            "[PROFILE] cpu_workers={}, gpu_workers={}, inflight_segments={}",
+        debug!(
             cpu_workers,
             gpu_workers,
-            max_segments.min(hard_cap as u32)
+            avail_bytes,
+            inflight_segments,
+            "dynamic parallelism profile"
         );
 
         Self {
             cpu_workers,
             gpu_workers,
-            inflight_segments: max_segments.min(hard_cap as u32) as usize,
+            inflight_segments,
             gpu_threshold: GPU_THRESHOLD,
             gpu: Some(gpu),
         }
     }
 
+    /// Profile requested by a stream header.
+    ///
+    /// - `Sequential` → [`single_threaded`](Self::single_threaded)
+    /// - `Parallel` → `parallel_hint` CPU workers (capped at the core count);
+    ///   a zero hint falls back to `Auto`
+    /// - `Auto` → [`dynamic`](Self::dynamic) sizing from `chunk_size`
+    pub fn from_header(header: &HeaderV1) -> Self {
+        let dynamic = || Self::dynamic(header.chunk_size, 0.50, 64);
+        match Strategy::try_from(header.strategy) {
+            Ok(Strategy::Sequential) => Self::single_threaded(),
+            Ok(Strategy::Parallel) if header.parallel_hint > 0 => {
+                let mut profile = dynamic();
+                profile.cpu_workers = (header.parallel_hint as usize).clamp(1, num_cpus::get().max(1));
+                profile
+            }
+            _ => dynamic(),
+        }
+    }
+
+    /// Profile for a `Strategy::Auto` stream following `plan`.
+    pub fn for_auto_plan(header: &HeaderV1, plan: AutoPlan) -> Self {
+        match plan {
+            AutoPlan::Sequential => Self::single_threaded(),
+            AutoPlan::Parallel | AutoPlan::Warmup { .. } => Self::dynamic(header.chunk_size, 0.50, 64),
+        }
+    }
 }
 
 
+impl From<ParallelismProfile> for HybridParallelismProfile {
+    /// CPU-only: no GPU workers and no GPU probe.
+    fn from(profile: ParallelismProfile) -> Self {
+        Self {
+            cpu_workers: profile.cpu_workers,
+            gpu_workers: 0,
+            inflight_segments: profile.inflight_segments,
+            gpu_threshold: GPU_THRESHOLD,
+            gpu: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum WorkerTarget {
     Cpu(usize), // index of CPU worker
@@ -270,10 +496,14 @@ pub fn dispatch_segment(
     }
 }
 
+#[derive(Debug)]
 pub struct Scheduler {
     cpu_load: Vec<usize>, // queue depth per CPU worker
     gpu_load: Vec<usize>, // queue depth per GPU device
     gpu_threshold: usize, // segment size threshold for GPU dispatch
+    assigned: HashMap<u32, WorkerTarget>, // segments between `assign` and `release`
+    segments_cpu: u64,
+    segments_gpu: u64,
 }
 
 impl Scheduler {
@@ -282,12 +512,18 @@ impl Scheduler {
             cpu_load: vec![0; cpu_workers],
             gpu_load: vec![0; gpu_workers],
             gpu_threshold,
+            assigned: HashMap::new(),
+            segments_cpu: 0,
+            segments_gpu: 0,
         }
     }
 
     /// Dispatch a segment to CPU or GPU based on size and current load
+    ///
+    /// Without CPU workers every segment goes to a GPU.
     pub fn dispatch(&mut self, segment_size: usize) -> WorkerTarget {
-        if !self.gpu_load.is_empty() && segment_size >= self.gpu_threshold {
+        let wants_gpu = segment_size >= self.gpu_threshold || self.cpu_load.is_empty();
+        if !self.gpu_load.is_empty() && wants_gpu {
             // Choose GPU with lowest load
             let (idx, _) = self.gpu_load
                 .iter()
@@ -323,4 +559,41 @@ impl Scheduler {
             }
         }
     }
+
+    /// [`dispatch`](Self::dispatch) `segment_index` and remember the target
+    /// until [`release`](Self::release).
+    pub fn assign(&mut self, segment_index: u32, segment_size: usize) -> WorkerTarget {
+        let target = self.dispatch(segment_size);
+        match target {
+            WorkerTarget::Cpu(_) => self.segments_cpu += 1,
+            WorkerTarget::Gpu(_) => self.segments_gpu += 1,
+        }
+        self.assigned.insert(segment_index, target.clone());
+        target
+    }
+
+    /// The segment left the pipeline: [`complete`](Self::complete) its target.
+    pub fn release(&mut self, segment_index: u32) -> Option<WorkerTarget> {
+        let target = self.assigned.remove(&segment_index)?;
+        self.complete(target.clone());
+        Some(target)
+    }
+
+    /// Segments assigned to CPU workers so far.
+    pub fn segments_cpu(&self) -> u64 {
+        self.segments_cpu
+    }
+
+    /// Segments assigned to GPU workers so far.
+    pub fn segments_gpu(&self) -> u64 {
+        self.segments_gpu
+    }
+
+    /// Queue depth of `target`; `None` if the worker does not exist.
+    pub fn load(&self, target: &WorkerTarget) -> Option<usize> {
+        match target {
+            WorkerTarget::Cpu(idx) => self.cpu_load.get(*idx).copied(),
+            WorkerTarget::Gpu(idx) => self.gpu_load.get(*idx).copied(),
+        }
+    }
 }
@@ -4,26 +4,35 @@
 // ## Pure pipeline wiring (no crypto logic)
 
 use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Instant;
 use bytes::Bytes;
-use crossbeam::channel::{bounded};
-
-use crate::headers::HeaderV1;
-use crate::stream_v2::compression_pipeline::{spawn_compression_workers, spawn_decompression_workers};
-use crate::stream_v2::compression_worker::{CodecInfo, CompressionWorkerError};
-use crate::stream_v2::io::{self, PayloadReader};
+use crossbeam::channel::{Receiver, bounded, unbounded};
+use tracing::{debug, trace};
+
+use crate::compression::{CodecLevel, DictSelector, DictionaryRegistry};
+use crate::constants::flags;
+use crate::headers::UnknownFlagPolicy;
+use crate::crypto::check_segment_budget;
+use crate::stream_v2::compression_pipeline::{inline_decompressor, spawn_compression_workers_into, spawn_decompression_workers_into};
+use crate::stream_v2::compression_worker::{CodecInfo, CompressionWorkerError, select_segment_dict};
+use crate::stream_v2::frame_worker::{DecryptFramePool, EncryptFramePool};
+use crate::stream_v2::io::{self, PayloadReader, PlaintextTrailer, SegmentPrefetcher};
 use crate::stream_v2::parallelism::HybridParallelismProfile;
+use crate::stream_v2::progress::{Progress, ProgressCallback};
 use crate::stream_v2::segment_worker::{
-    DecryptSegmentInput, DecryptSegmentWorker, DecryptedSegment, EncryptSegmentInput, EncryptSegmentWorker, EncryptedSegment, EncryptContext, DecryptContext, SegmentWorkerError
+    DecryptSegmentInput, DecryptSegmentWorker, DecryptedSegment, EncryptSegmentInput, EncryptSegmentWorker, EncryptedSegment, EncryptContext, DecryptContext, SegmentHook, SegmentWorkerError, SegmentWorkerHandle
 };
-use crate::stream_v2::segmenting::types::SegmentFlags;
-use crate::telemetry::{Stage, StageTimes, TelemetryCounters, TelemetrySnapshot, TelemetryTimer};
+use crate::stream_v2::sink::{ForwardSink, RouteSink, StageTelemetry};
+use crate::stream_v2::segmenting::{SegmentHeader, SegmentIndexMap, encode_segment_index};
+use crate::stream_v2::segmenting::types::{SegmentFlags, len_to_u32};
+use crate::telemetry::{EffectiveStrategy, SegmentRecorder, Stage, StageTimes, TelemetryCounters, TelemetrySnapshot, TelemetryTimer};
 use crate::types::StreamError;
 use crate::recovery::persist::AsyncLogManager;
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct PipelineConfig {
     pub profile: HybridParallelismProfile,
     /// The final encrypted stream bytes, if the output sink was memory-backed.
@@ -35,6 +44,58 @@ pub struct PipelineConfig {
     /// where we want to inspect the produced ciphertext alongside telemetry
     /// counters and stage timings.
     pub buf: Option<Arc<Mutex<Vec<u8>>>>,
+    /// First segment index to decrypt (resumed streams).
+    ///
+    /// Segments below this index are skipped and telemetry only counts the
+    /// resumed portion. `0` for a full stream.
+    pub start_segment: u32,
+    /// Explicit compression level for the encrypt side.
+    ///
+    /// `None` keeps the level chosen by `CodecInfo::from_header`.
+    pub codec_level: Option<CodecLevel>,
+    /// Registry resolving `header.dict_id` for both pipeline directions.
+    pub dictionaries: Option<Arc<DictionaryRegistry>>,
+    /// Encrypt only: picks each data segment's dictionary from `dictionaries`
+    /// (`SEGMENT_DICTS` headers; see [`EncryptParams::dict_selector`](crate::stream_v2::core::EncryptParams::dict_selector)).
+    pub dict_selector: Option<DictSelector>,
+    /// Encrypt segments to run one at a time before the worker pool takes over.
+    ///
+    /// Used by `Strategy::Auto` when the input size is unknown. `0` for none.
+    pub warmup_segments: u32,
+    /// Progress hook; `ControlFlow::Break` cancels the run (see [`ApiConfig::progress`](crate::stream_v2::core::ApiConfig::progress)).
+    pub progress: Option<ProgressCallback>,
+    /// Record per-segment timings into `TelemetrySnapshot::segment_records`.
+    pub segment_metrics: bool,
+    /// Decrypt only: decompress serially in the ordered writer instead of in
+    /// the decompression workers (see [`ApiConfig::decompress_in_order`](crate::stream_v2::core::ApiConfig::decompress_in_order)).
+    pub decompress_in_order: bool,
+    /// Decrypt only: called by the segment workers before each segment.
+    pub segment_hook: Option<SegmentHook>,
+    /// Decrypt only: segments read ahead of the one being dispatched; `0`
+    /// reads serially (see [`ApiConfig::read_ahead`](crate::stream_v2::core::ApiConfig::read_ahead)).
+    pub read_ahead: usize,
+    /// Decrypt only: how stream header validation treats unknown `flags` bits.
+    pub unknown_flags: UnknownFlagPolicy,
+}
+
+impl std::fmt::Debug for PipelineConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PipelineConfig")
+            .field("profile", &self.profile)
+            .field("buf", &self.buf.is_some())
+            .field("start_segment", &self.start_segment)
+            .field("codec_level", &self.codec_level)
+            .field("dictionaries", &self.dictionaries)
+            .field("dict_selector", &self.dict_selector)
+            .field("warmup_segments", &self.warmup_segments)
+            .field("progress", &self.progress.is_some())
+            .field("segment_metrics", &self.segment_metrics)
+            .field("decompress_in_order", &self.decompress_in_order)
+            .field("segment_hook", &self.segment_hook.is_some())
+            .field("read_ahead", &self.read_ahead)
+            .field("unknown_flags", &self.unknown_flags)
+            .finish()
+    }
 }
 
 impl PipelineConfig {
@@ -42,25 +103,205 @@ impl PipelineConfig {
         Self {
             profile,
             buf,
+            start_segment: 0,
+            codec_level: None,
+            dictionaries: None,
+            dict_selector: None,
+            warmup_segments: 0,
+            progress: None,
+            segment_metrics: false,
+            decompress_in_order: false,
+            segment_hook: None,
+            read_ahead: 0,
+            unknown_flags: UnknownFlagPolicy::Reject,
         }
     }
     pub fn with_buf(profile: HybridParallelismProfile) -> (Self, Arc<Mutex<Vec<u8>>>) {
         let buf = Arc::new(Mutex::new(Vec::new()));
-        (Self { profile, buf: Some(buf.clone()) }, buf)
+        (Self { buf: Some(buf.clone()), ..Self::new(profile, None) }, buf)
+    }
+    /// Resume decryption at `segment_index`.
+    pub fn with_start_segment(mut self, segment_index: u32) -> Self {
+        self.start_segment = segment_index;
+        self
+    }
+    /// Pin the compression level used by the compression workers.
+    pub fn with_codec_level(mut self, level: Option<CodecLevel>) -> Self {
+        self.codec_level = level;
+        self
+    }
+    /// Dictionaries for streams with `DICT_USED` set.
+    pub fn with_dictionaries(mut self, dictionaries: Option<Arc<DictionaryRegistry>>) -> Self {
+        self.dictionaries = dictionaries;
+        self
+    }
+    /// Let `selector` pick the dictionary of each encrypt data segment.
+    pub fn with_dict_selector(mut self, selector: Option<DictSelector>) -> Self {
+        self.dict_selector = selector;
+        self
+    }
+    /// Serialize the first `segments` encrypt segments (see [`AutoPlan::Warmup`](crate::stream_v2::parallelism::AutoPlan::Warmup)).
+    pub fn with_warmup(mut self, segments: u32) -> Self {
+        self.warmup_segments = segments;
+        self
+    }
+    /// Report progress to `progress`, which may cancel the run.
+    pub fn with_progress(mut self, progress: Option<ProgressCallback>) -> Self {
+        self.progress = progress;
+        self
+    }
+    /// Collect per-segment timings and worker load (`ApiConfig::collect_metrics`).
+    pub fn with_segment_metrics(mut self, enabled: bool) -> Self {
+        self.segment_metrics = enabled;
+        self
+    }
+    /// Decompress in the ordered writer, bounding plaintext residency to one segment.
+    pub fn with_decompress_in_order(mut self, enabled: bool) -> Self {
+        self.decompress_in_order = enabled;
+        self
+    }
+    /// Run `hook` in the decrypt segment workers before each segment.
+    pub fn with_segment_hook(mut self, hook: Option<SegmentHook>) -> Self {
+        self.segment_hook = hook;
+        self
+    }
+    /// Read up to `segments` segments ahead on a separate thread (decrypt only).
+    pub fn with_read_ahead(mut self, segments: usize) -> Self {
+        self.read_ahead = segments;
+        self
+    }
+    /// How the decrypt pipeline validates unknown stream header `flags` bits.
+    pub fn with_unknown_flags(mut self, policy: UnknownFlagPolicy) -> Self {
+        self.unknown_flags = policy;
+        self
+    }
+}
+
+/// Out-of-order segments an ordered writer buffers before failing.
+///
+/// At least one per worker, so ordinary reordering between workers never trips it.
+fn reorder_limit(profile: &HybridParallelismProfile) -> usize {
+    profile.inflight_segments().max(profile.cpu_workers() + profile.gpu_workers())
+}
+
+/// Reader side of the writer's progress: one token per segment written in order.
+///
+/// Keeping at most `window` segments dispatched but unwritten bounds what the
+/// ordered writer can have pending, however the workers reorder them.
+struct WriteWindow {
+    written: Receiver<()>,
+    seen: u32,
+}
+
+impl WriteWindow {
+    fn new(written: Receiver<()>) -> Self {
+        Self { written, seen: 0 }
+    }
+
+    /// Block until `dispatched` segments leave room for one more within `window`.
+    fn wait(&mut self, dispatched: u32, window: usize) -> Result<(), StreamError> {
+        while (dispatched - self.seen) as usize >= window {
+            self.written
+                .recv()
+                .map_err(|_| StreamError::PipelineError("writer stopped before the reader finished"))?;
+            self.seen += 1;
+        }
+        Ok(())
+    }
+}
+
+/// What actually ran: the warm-up outcome, otherwise the worker pool size.
+fn effective_strategy(config: &PipelineConfig, promoted: bool) -> EffectiveStrategy {
+    if config.warmup_segments > 0 {
+        if promoted {
+            return EffectiveStrategy::Promoted { after_segments: config.warmup_segments };
+        }
+        return EffectiveStrategy::Sequential;
+    }
+    if config.profile.cpu_workers() > 1 {
+        EffectiveStrategy::Parallel
+    } else {
+        EffectiveStrategy::Sequential
+    }
+}
+
+
+/// Result of a pipeline run, with its telemetry either way.
+///
+/// On failure the snapshot covers the work completed before the error:
+/// counters and stage times gathered so far, and `segments_processed`
+/// counting the segments written in order.
+#[derive(Debug)]
+pub struct PipelineOutcome {
+    pub result: Result<(), StreamError>,
+    pub snapshot: TelemetrySnapshot,
+}
+
+impl PipelineOutcome {
+    /// Failure before any segment went through: setup telemetry only.
+    fn failed(error: StreamError, counters: &TelemetryCounters, timer: &mut TelemetryTimer) -> Self {
+        timer.finish();
+        Self { result: Err(error), snapshot: TelemetrySnapshot::from(counters, timer, Some(0)) }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.result.is_ok()
+    }
+
+    /// The snapshot on success, the error otherwise; the partial snapshot is
+    /// logged at debug level, then dropped.
+    pub fn into_result(self) -> Result<TelemetrySnapshot, StreamError> {
+        match self.result {
+            Ok(()) => Ok(self.snapshot),
+            Err(e) => {
+                debug!(
+                    error = %e,
+                    segments = self.snapshot.segments_processed,
+                    bytes_plaintext = self.snapshot.bytes_plaintext,
+                    elapsed = ?self.snapshot.elapsed,
+                    "pipeline failed"
+                );
+                Err(e)
+            }
+        }
     }
 }
 
+/// `?` for pipeline setup: a failure still carries the telemetry gathered so far.
+macro_rules! try_setup {
+    ($expr:expr, $counters:expr, $timer:expr) => {
+        match $expr {
+            Ok(value) => value,
+            Err(e) => return PipelineOutcome::failed(e, &$counters, &mut $timer),
+        }
+    };
+}
 
 // ============================================================
 // Encrypt pipeline
 // ============================================================
 pub fn run_encrypt_pipeline<R, W>(
-    mut reader: &mut PayloadReader<R>,
-    mut writer: W,
+    reader: &mut PayloadReader<R>,
+    writer: W,
     crypto: &mut EncryptContext, // borrow mutably
     config: &PipelineConfig, // borrow instead of move
     log_manager: Arc<AsyncLogManager>,
 ) -> Result<TelemetrySnapshot, StreamError>
+where
+    R: Read + Send,
+    W: Write + Send,
+{
+    run_encrypt_pipeline_with_outcome(reader, writer, crypto, config, log_manager).into_result()
+}
+
+/// [`run_encrypt_pipeline`], keeping the telemetry of a failed run.
+pub fn run_encrypt_pipeline_with_outcome<R, W>(
+    mut reader: &mut PayloadReader<R>,
+    mut writer: W,
+    crypto: &mut EncryptContext,
+    config: &PipelineConfig,
+    log_manager: Arc<AsyncLogManager>,
+) -> PipelineOutcome
 where
     R: Read + Send,
     W: Write + Send,
@@ -69,160 +310,260 @@ where
     let mut timer = TelemetryTimer::new();
     let mut segment_index = 0u32;
 
-    eprintln!("[PIPELINE] Start encrypt pipeline");
+    debug!("start encrypt pipeline");
 
     // ---- Write stream header ----
     let start = Instant::now();
-    io::write_header(&mut writer, &crypto.header)?;
+    try_setup!(io::write_header_with_extensions(&mut writer, &crypto.header, &crypto.extensions), counters, timer);
     timer.stage_times.add(Stage::Write, start.elapsed());
-    counters.bytes_overhead += HeaderV1::LEN as u64; // record stream header overhead
-    eprintln!("[PIPELINE] Header written");
+    counters.bytes_overhead += crypto.header.encoded_len() as u64; // record stream header overhead
 
     // ---- Channels ----
+    // Compression workers feed the crypto workers directly; every stage's errors go to the writer
     let (comp_tx, comp_rx) = bounded::<EncryptSegmentInput>(config.profile.inflight_segments());
-    let (seg_tx, seg_rx_raw) = bounded::<Result<EncryptSegmentInput, CompressionWorkerError>>(config.profile.inflight_segments());
-    let (seg_tx_clean, seg_rx_clean) = bounded::<EncryptSegmentInput>(config.profile.inflight_segments());
-    let (out_tx, out_rx) = bounded::<Result<EncryptedSegment, SegmentWorkerError>>(config.profile.inflight_segments());
+    let (seg_tx, seg_rx) = bounded::<EncryptSegmentInput>(config.profile.inflight_segments());
+    let (out_tx, out_rx) = bounded::<Result<EncryptedSegment, StreamError>>(config.profile.inflight_segments());
 
     // ---- Spawn compression workers ----
-    let mut codec_info = CodecInfo::from_header(&crypto.header, None);
+    // Decoders only look for per-segment dictionaries under SEGMENT_DICTS
+    let segment_dicts = try_setup!(
+        match (&config.dict_selector, &config.dictionaries) {
+            (None, _) => Ok(None),
+            (Some(selector), Some(registry)) if crypto.header.segment_dicts() => Ok(Some((selector, registry))),
+            (Some(_), _) => Err(StreamError::Validation(
+                "dict_selector needs a dictionary registry and a SEGMENT_DICTS header".into(),
+            )),
+        },
+        counters,
+        timer
+    );
+    let mut codec_info = try_setup!(
+        CodecInfo::from_header(&crypto.header, config.dictionaries.as_deref()).map_err(StreamError::Compression),
+        counters,
+        timer
+    );
     codec_info.gpu = config.profile.gpu();
+    if let Some(level) = config.codec_level {
+        codec_info.level = level;
+    }
 
-    // Compression / segment
-    spawn_compression_workers(config.profile.clone(), codec_info, comp_rx, seg_tx.clone());
-
-    drop(seg_tx); // Important: drop seg_tx here so seg_rx_raw eventually closes
+    // Compression / segment: each worker merges its stage times and bytes_compressed here once
+    let compression_telemetry = Arc::new(Mutex::new(StageTelemetry::default()));
+    let pool = try_setup!(
+        spawn_compression_workers_into(
+            config.profile.clone(),
+            codec_info,
+            comp_rx,
+            ForwardSink::new(seg_tx, out_tx.clone(), Arc::clone(&compression_telemetry)),
+            &crypto.header,
+        )
+        .map_err(spawn_error),
+        counters,
+        timer
+    );
+
+    // One frame worker pool for the whole run, shared by every segment worker
+    let frame_pool = try_setup!(
+        EncryptFramePool::encrypt_with_extensions(&crypto.header, &crypto.extensions, crypto.base.session_key.as_slice(), config.profile.cpu_workers())
+            .map_err(|e| StreamError::SegmentWorker(e.into())),
+        counters,
+        timer
+    );
 
     let counters_read = Arc::new(Mutex::new(TelemetryCounters::default()));
     let read_stage_times = Arc::new(Mutex::new(StageTimes::default()));
-    let compression_stage_times = Arc::new(Mutex::new(StageTimes::default()));
     let mut encryption_stage_times = StageTimes::default();
+    let promoted = AtomicBool::new(false);
+    let read_buffer_peak = AtomicUsize::new(0);
+    let progress = Progress::new(config.progress.clone());
+    let recorder = config.segment_metrics.then(|| Arc::new(SegmentRecorder::new()));
+    let mut segments_written = 0usize;
 
-    thread::scope(|scope| {
+    // Writer → reader: one token per segment written
+    let (done_tx, done_rx) = unbounded::<()>();
+    let window = reorder_limit(&config.profile);
+
+    let result = thread::scope(|scope| {
         // ---- Reader thread ----
-        scope.spawn(|| -> Result<(), StreamError> {
-            let chunk_size = crypto.base.segment_size;
+        let reader_handle = scope.spawn(|| -> Result<(), StreamError> {
+            let mut chunks = io::ChunkReader::new(&mut reader, crypto.base.segment_size);
             let read_stage_times = Arc::clone(&read_stage_times);
             let counters_read = Arc::clone(&counters_read);
+            let mut trailer = PlaintextTrailer::for_header(&crypto.header);
+            let mut written = WriteWindow::new(done_rx);
+            // Warm-up: wait for the previous segment before reading the next
+            let window_at = |segment_index: u32| if segment_index <= config.warmup_segments { 1 } else { window };
 
             loop {
+                if progress.is_cancelled() {
+                    return Err(StreamError::Cancelled);
+                }
+                written.wait(segment_index, window_at(segment_index))?;
                 let mut times = read_stage_times.lock().unwrap();
                 // Read / chunking / before compress
                 let start = Instant::now();
-                let buf = io::read_exact_or_eof(&mut reader, chunk_size)?;
+                let buf = chunks.read_chunk()?;
+                read_buffer_peak.fetch_max(chunks.stats().peak_capacity, Ordering::Relaxed);
                 
                 if buf.is_empty() {
-                    eprintln!("[READER] EOF reached, dispatching final empty segment {}", segment_index);
-                    if segment_index > 0 {
+                    trace!(segment_index, "reader reached EOF");
+                    // A declared size must match what was actually read
+                    if crypto.header.plaintext_size != 0 && crypto.header.plaintext_size != trailer.total_len() {
+                        return Err(StreamError::Validation(format!(
+                            "header declares {} plaintext bytes, input has {}",
+                            crypto.header.plaintext_size,
+                            trailer.total_len()
+                        )));
+                    }
+                    // CRC and length are only known now: authenticate them as their own segment
+                    if trailer.is_enabled() {
+                        check_segment_budget(segment_index).map_err(StreamError::Crypto)?;
                         comp_tx.send(EncryptSegmentInput {
                             segment_index,
-                            bytes: Bytes::new(),
-                            flags: SegmentFlags::FINAL_SEGMENT,
+                            bytes: trailer.payload(),
+                            flags: trailer.flags(),
                             stage_times: StageTimes::default(),
-                        }).map_err(|_| StreamError::PipelineError("encrypt segment channel closed".into()))?;
+                        }).map_err(|_| StreamError::PipelineError("encrypt segment channel closed"))?;
+                        segment_index += 1;
+                        written.wait(segment_index, window_at(segment_index))?;
                     }
+                    // Every stream ends with an empty final segment, including empty input
+                    check_segment_budget(segment_index).map_err(StreamError::Crypto)?;
+                    comp_tx.send(EncryptSegmentInput {
+                        segment_index,
+                        bytes: Bytes::new(),
+                        flags: SegmentFlags::FINAL_SEGMENT,
+                        stage_times: StageTimes::default(),
+                    }).map_err(|_| StreamError::PipelineError("encrypt segment channel closed"))?;
                     times.add(Stage::Read, start.elapsed());
                     break;
                 }
-                eprintln!("[READER] Dispatching segment {}", segment_index);
+                trace!(segment_index, len = buf.len(), "dispatching segment");
+                check_segment_budget(segment_index).map_err(StreamError::Crypto)?;
                 // counters bytes_plaintext
-                counters_read.lock().unwrap().bytes_plaintext += buf.len() as u64;
+                let len = buf.len() as u64;
+                counters_read.lock().unwrap().bytes_plaintext += len;
+                trailer.update(&buf);
+                if config.warmup_segments > 0 && segment_index == config.warmup_segments {
+                    debug!(segment_index, "warm-up done, promoting to parallel");
+                    promoted.store(true, Ordering::Relaxed);
+                }
+                let (bytes, flags) = match segment_dicts {
+                    Some((selector, registry)) => select_segment_dict(selector, registry, segment_index, buf, SegmentFlags::empty())
+                        .map_err(StreamError::Compression)?,
+                    None => (buf, SegmentFlags::empty()),
+                };
 
                 comp_tx.send(EncryptSegmentInput {
                     segment_index,
-                    bytes: buf,
-                    flags: SegmentFlags::empty(),
+                    bytes,
+                    flags,
                     stage_times: StageTimes::default(),
                 }).map_err(|_| StreamError::PipelineError("encrypt segment channel closed".into()))?;
                 
                 times.add(Stage::Read, start.elapsed());
                 segment_index += 1;
+                drop(times);
+                progress.on_read(len)?;
 
             }
             
-            eprintln!("[READER] Finished, dropping comp_tx");
             drop(comp_tx);
 
             Ok(())
         });
 
-        // Adapter thread: unwrap compression results
-        scope.spawn({
-            let seg_rx_raw = seg_rx_raw.clone();
-            let seg_tx_clean = seg_tx_clean.clone();
-            let out_tx = out_tx.clone();
-            let compression_stage_times = Arc::clone(&compression_stage_times);
-            let counters_read = Arc::clone(&counters_read);
-
-            move || {
-                for res in seg_rx_raw.iter() {
-                    match res {
-                        Ok(seg) => {
-                            // merge compression stage_times 
-                            let mut times = compression_stage_times.lock().unwrap(); 
-                            for (stage, dur) in seg.stage_times.iter() { times.add(*stage, *dur); }
-
-                            // counters bytes_compressed
-                            counters_read.lock().unwrap().bytes_compressed += seg.bytes.len() as u64;
-
-                            let _ = seg_tx_clean.send(seg);
-
-                        }
-                        Err(e) => {
-                            eprintln!("[PIPELINE] compression worker error: {e}");
-                            let _ = out_tx.send(Err(SegmentWorkerError::StateError(e.to_string())));
-                            break;
-                        }
-                    }
-                }
-            }
-        });
-
-        drop(seg_tx_clean); // Drop seg_tx_clean when adapter finishes
-
         // ---- Crypto workers ----
-        for _ in 0..config.profile.cpu_workers() {
-            let worker = EncryptSegmentWorker::new(crypto.clone(), log_manager.clone());
-            let rx = seg_rx_clean.clone();
-            let tx = out_tx.clone();
-
-            scope.spawn(move || worker.run_v2(rx, tx));
+        let mut segment_workers = Vec::with_capacity(config.profile.cpu_workers());
+        for worker_id in 0..config.profile.cpu_workers() {
+            let mut worker = EncryptSegmentWorker::new(crypto.clone(), log_manager.clone())
+                .with_frame_pool(frame_pool.handle());
+            if let Some(recorder) = &recorder {
+                worker = worker.with_recorder(Arc::clone(recorder), worker_id);
+            }
+            segment_workers.push(worker.run_v2_into(seg_rx.clone(), out_tx.clone()));
         }
+        // Workers hold their own receivers; once they exit, compression workers stop instead of blocking
+        drop(seg_rx);
 
         drop(out_tx); // drop out_tx in main thread
-        eprintln!("[PIPELINE] dropped out_tx in main thread");
 
         // ---- Ordered writer ----
-        let mut ordered_writer = io::OrderedEncryptedWriter::new(&mut writer);
-
-        for res in out_rx.iter() {
-            eprintln!("[WRITER] receiving segment result");
-            match res {
-                Ok(encrypted) => {
-                    eprintln!("[WRITER] received segment {}", encrypted.header.segment_index);
-                    // merge encryption stage_times
-                    encryption_stage_times.merge(&encrypted.stage_times);
-
-                    // 🔥 Merge telemetry from this segment worker
-                    counters.merge(&encrypted.counters);
-
-                    // Writing / wiring
-                    let start = Instant::now();
-                    ordered_writer.push(encrypted)?;
-                    encryption_stage_times.add(Stage::Write, start.elapsed());
-                }
-                Err(e) => {
-                    eprintln!("[PIPELINE] crypto/compression worker error: {e}");
-                    return Err(StreamError::SegmentWorker(e));
+        let payload_offset = crypto.header.encoded_len() as u64;
+        let mut ordered_writer = io::OrderedEncryptedWriter::new(&mut writer)
+            .with_max_pending(reorder_limit(&config.profile))
+            .with_payload_offset(payload_offset);
+
+        let writer_result = (|| -> Result<(), StreamError> {
+            wait_segment_workers(&mut segment_workers)?;
+            let mut segments_reported = 0;
+            for res in out_rx.iter() {
+                match res {
+                    Ok(encrypted) => {
+                        pool.scheduler.lock().unwrap().release(encrypted.header.segment_index);
+                        // merge encryption stage_times
+                        encryption_stage_times.merge(&encrypted.stage_times);
+
+                        // 🔥 Merge telemetry from this segment worker
+                        counters.merge(&encrypted.counters);
+
+                        // Writing / wiring
+                        let start = Instant::now();
+                        ordered_writer.push(encrypted)?;
+                        encryption_stage_times.add(Stage::Write, start.elapsed());
+
+                        let index = ordered_writer.index();
+                        if index.len() > segments_reported {
+                            for _ in segments_reported..index.len() {
+                                let _ = done_tx.send(());
+                            }
+                            segments_reported = index.len();
+                            progress.on_written(index.end_offset() - payload_offset, segments_reported as u64)?;
+                        }
+                    }
+                    Err(e) => {
+                        debug!(error = %e, "crypto/compression worker error");
+                        return Err(e);
+                    }
                 }
             }
-        }
-        
-        eprintln!("[WRITER] out_rx closed, finishing writer");
-        ordered_writer.finish()?;
-        
-        Ok::<(), StreamError>(())
-    })?;
+            ordered_writer.finish()?;
+
+            if crypto.header.flags & flags::HAS_SEGMENT_INDEX != 0 {
+                let start = Instant::now();
+                let trailer = encrypt_index_segment(crypto, log_manager.clone(), ordered_writer.index())?;
+                counters.bytes_overhead += ordered_writer.write_index_trailer(&trailer)?;
+                encryption_stage_times.add(Stage::Write, start.elapsed());
+            }
+            ordered_writer.flush()
+        })();
+        segments_written = ordered_writer.index().len();
+        // Unblock workers still sending when the writer bailed out early
+        drop(out_rx);
+        // ...and a reader waiting for room in the window
+        drop(done_tx);
+
+        let reader_result = reader_handle
+            .join()
+            .unwrap_or(Err(StreamError::PipelineError("encrypt reader thread panicked")));
+        // Their input is closed and `out_rx` is gone: every segment worker is on its way out
+        let workers_result = join_segment_workers(segment_workers);
+
+        first_pipeline_error(reader_result, writer_result).and(workers_result)
+    });
+
+    // Segment workers are done once their inputs close; wait for the frame workers too
+    let frame_workers = frame_pool.threads();
+    frame_pool.shutdown();
+
+    // Workers are done: flush the audit log so a write failure is not lost
+    let log_closed = log_manager.close();
+    // Cancellation surfaces downstream as assorted channel/final-segment errors
+    let result = if progress.is_cancelled() {
+        Err(StreamError::Cancelled)
+    } else {
+        result.and(log_closed.map_err(StreamError::from))
+    };
 
     timer.finish();
 
@@ -233,45 +574,159 @@ where
     // merge read stage_times
     // let final_times = read_stage_times.lock().unwrap(); 
     // for (stage, dur) in final_times.iter() { timer.add_stage_time(*stage, *dur); }
-    for (stage, dur) in read_stage_times.lock().unwrap().iter() {
-        timer.add_stage_time(*stage, *dur);
-    }
+    timer.merge(&read_stage_times.lock().unwrap());
     // merge compression stage_times
-    for (stage, dur) in compression_stage_times.lock().unwrap().iter() {
-        timer.add_stage_time(*stage, *dur);
-    }
+    let compressed = compression_telemetry.lock().unwrap();
+    timer.merge(&compressed.stage_times);
     // merge encryption stage_times
-    for (stage, dur) in encryption_stage_times.iter() {
-        timer.add_stage_time(*stage, *dur);
+    timer.merge(&encryption_stage_times);
+    // Reader and compression workers own bytes_plaintext and bytes_compressed
+    counters.merge(&counters_read.lock().unwrap());
+    counters.merge(&compressed.counters);
+
+    // A failed run only counts what reached the output
+    let segments = if result.is_ok() { segment_index + 1 } else { segments_written as u32 };
+    let mut snapshot = TelemetrySnapshot::from(
+        &counters, 
+        &timer, 
+        Some(segments)
+    );
+    snapshot.strategy_effective = Some(effective_strategy(config, promoted.into_inner()));
+    snapshot.cpu_workers = config.profile.cpu_workers();
+    snapshot.frame_workers = frame_workers;
+    if let Some(recorder) = &recorder {
+        (snapshot.segment_records, snapshot.worker_usage) = recorder.drain();
     }
-    // update bytes_plaintext len
-    counters.bytes_plaintext = counters_read.lock().unwrap().bytes_plaintext;
-    // update bytes_compressed len
-    counters.bytes_compressed = counters_read.lock().unwrap().bytes_compressed;
-
-    // After pipeline finishes: 
-    if let Some(ref arc_buf) = config.buf { 
-        let buf = arc_buf.lock().unwrap(); 
-        println!("Captured output: {:?}", String::from_utf8_lossy(&buf)); 
+    snapshot.read_buffer_peak = read_buffer_peak.into_inner() as u64;
+    snapshot.gpu_fallbacks = pool.gpu_fallbacks as u64;
+    {
+        let scheduler = pool.scheduler.lock().unwrap();
+        snapshot.segments_cpu = scheduler.segments_cpu();
+        snapshot.segments_gpu = scheduler.segments_gpu();
     }
+    PipelineOutcome { result, snapshot }
+}
 
-    Ok(TelemetrySnapshot::from(
-        &counters, 
-        &timer, 
-        Some(segment_index + 1)
-    ))
+/// Encrypt the payload of the trailing index segment (`HAS_SEGMENT_INDEX`).
+///
+/// The index is a regular segment numbered after the final one, so it gets the
+/// same per-frame AEAD, digest and terminator as the data segments. It is not
+/// compressed.
+pub(crate) fn encrypt_index_segment(
+    crypto: &EncryptContext,
+    log_manager: Arc<AsyncLogManager>,
+    index: &SegmentIndexMap,
+) -> Result<EncryptedSegment, StreamError> {
+    let (seg_tx, seg_rx) = bounded::<EncryptSegmentInput>(1);
+    let (out_tx, out_rx) = bounded::<Result<EncryptedSegment, SegmentWorkerError>>(1);
+    // The index grows with the stream: never padded
+    let mut worker = EncryptSegmentWorker::new(crypto.clone().with_padding(None), log_manager).run_v2(seg_rx, out_tx);
+    worker.wait_ready()?;
+
+    seg_tx
+        .send(EncryptSegmentInput {
+            segment_index: len_to_u32("segment_index", index.len()).map_err(StreamError::Segment)?,
+            bytes: encode_segment_index(index),
+            flags: SegmentFlags::SEGMENT_INDEX,
+            stage_times: StageTimes::default(),
+        })
+        .map_err(|_| StreamError::PipelineError("index segment channel closed"))?;
+    drop(seg_tx);
+
+    let segment = out_rx
+        .recv()
+        .map_err(|_| StreamError::PipelineError("index segment worker disconnected"))?
+        .map_err(StreamError::SegmentWorker)?;
+    worker.join()?;
+    Ok(segment)
+}
+
+/// Wait until every segment worker has started.
+///
+/// A frame pool that failed to come up ends the run before anything is written.
+fn wait_segment_workers(workers: &mut [SegmentWorkerHandle]) -> Result<(), StreamError> {
+    for worker in workers {
+        worker.wait_ready().map_err(|e| {
+            debug!(error = %e, "segment worker failed to start");
+            StreamError::PipelineError("segment worker failed to start")
+        })?;
+    }
+    Ok(())
+}
+
+/// Join the segment workers once their channels are closed.
+///
+/// Segment failures already went through the output channel; what is left is
+/// a worker thread that panicked or never started.
+fn join_segment_workers(workers: Vec<SegmentWorkerHandle>) -> Result<(), StreamError> {
+    let mut result = Ok(());
+    for worker in workers {
+        let error = match worker.join() {
+            Ok(()) => continue,
+            Err(SegmentWorkerError::WorkerPanicked(msg)) => {
+                debug!(panic = %msg, "segment worker thread panicked");
+                StreamError::PipelineError("segment worker thread panicked")
+            }
+            Err(e) => {
+                debug!(error = %e, "segment worker failed to start");
+                StreamError::PipelineError("segment worker failed to start")
+            }
+        };
+        result = result.and(Err(error));
+    }
+    result
+}
+
+/// Combine the reader thread's result with the writer's.
+///
+/// A reader failure (e.g. an I/O error on the plaintext source) is the root
+/// cause and wins — except a closed channel, which only means a downstream
+/// stage stopped first and reported the more specific error itself.
+/// Codec errors from worker startup keep their `StreamError::Compression` shape.
+fn spawn_error(e: CompressionWorkerError) -> StreamError {
+    match e {
+        CompressionWorkerError::Compression(e) => StreamError::Compression(e),
+        e => StreamError::CompressionWorker(e),
+    }
+}
+
+fn first_pipeline_error(reader: Result<(), StreamError>, writer: Result<(), StreamError>) -> Result<(), StreamError> {
+    match (reader, writer) {
+        (Err(e), _) if !matches!(e, StreamError::PipelineError(_)) => Err(e),
+        (_, Err(e)) => Err(e),
+        (Err(e), Ok(())) => Err(e),
+        (Ok(()), Ok(())) => Ok(()),
+    }
 }
 
 // ============================================================
 // Decrypt pipeline
 // ============================================================
 pub fn run_decrypt_pipeline<R, W>(
-    mut reader: &mut PayloadReader<R>,
-    mut writer: W,
+    reader: &mut PayloadReader<R>,
+    writer: W,
     crypto: &mut DecryptContext, // borrow mutably
     config: &PipelineConfig, // borrow instead of move
     log_manager: Arc<AsyncLogManager>,
 ) -> Result<TelemetrySnapshot, StreamError>
+where
+    R: Read + Send,
+    W: Write + Send,
+{
+    run_decrypt_pipeline_with_outcome(reader, writer, crypto, config, log_manager).into_result()
+}
+
+/// What the decrypt reader thread reads next (see [`io::read_segment`]).
+type NextSegment = Result<Option<(SegmentHeader, Bytes)>, StreamError>;
+
+/// [`run_decrypt_pipeline`], keeping the telemetry of a failed run.
+pub fn run_decrypt_pipeline_with_outcome<R, W>(
+    reader: &mut PayloadReader<R>,
+    mut writer: W,
+    crypto: &mut DecryptContext,
+    config: &PipelineConfig,
+    log_manager: Arc<AsyncLogManager>,
+) -> PipelineOutcome
 where
     R: Read + Send,
     W: Write + Send,
@@ -280,148 +735,230 @@ where
     let mut timer = TelemetryTimer::new();
     let mut last_segment_index = 0;
 
-    eprintln!("[PIPELINE] Start decrypt pipeline");
+    debug!("start decrypt pipeline");
 
     // ---- Read stream header ----
     // Validation / stream header
     let start = Instant::now();
-    crypto.header.validate().map_err(StreamError::Header)?;
+    try_setup!(crypto.header.validate_with(config.unknown_flags).map_err(StreamError::Header), counters, timer);
     timer.stage_times.add(Stage::Validate, start.elapsed());
     // Calculate len of overhead bytes / stream header
-    counters.bytes_overhead += HeaderV1::LEN as u64;
-    eprintln!("[PIPELINE] Header validated");
+    counters.bytes_overhead += crypto.header.encoded_len() as u64;
 
     // ---- Channels ----
+    // Crypto workers feed the decompression workers directly; every stage's errors go to the writer
     let (seg_tx, seg_rx) = bounded::<DecryptSegmentInput>(config.profile.inflight_segments());
-    let (crypto_out_tx, crypto_out_rx) = bounded::<Result<DecryptedSegment, SegmentWorkerError>>(config.profile.inflight_segments());
-    let (decomp_out_tx, decomp_out_rx) = bounded::<Result<DecryptedSegment, CompressionWorkerError>>(config.profile.inflight_segments());
-    let (decomp_in_tx, decomp_in_rx) = bounded::<DecryptedSegment>(config.profile.inflight_segments());
-
-    // ---- Spawn decompression workers ----
-    let mut codec_info = CodecInfo::from_header(&crypto.header, None);
+    let (decomp_tx, decomp_rx) = bounded::<DecryptedSegment>(config.profile.inflight_segments());
+    let (out_tx, out_rx) = bounded::<Result<DecryptedSegment, StreamError>>(config.profile.inflight_segments());
+
+    // ---- Spawn decompression workers (or build the writer's decompressor) ----
+    let mut codec_info = try_setup!(
+        CodecInfo::from_header(&crypto.header, config.dictionaries.as_deref()).map_err(StreamError::Compression),
+        counters,
+        timer
+    );
     codec_info.gpu = config.profile.gpu();
-
-    let counters_read = Arc::new(Mutex::new(TelemetryCounters::default()));
-    let counters_segment = Arc::new(Mutex::new(TelemetryCounters::default()));
+    let max_output = codec_info.output_limit();
+
+    // Fails with `CompressionError::UnsupportedCodec` or a dictionary mismatch before any thread starts
+    let (gpu_fallbacks, inline_backend) = if config.decompress_in_order {
+        (0, Some(try_setup!(inline_decompressor(codec_info, &crypto.header).map_err(spawn_error), counters, timer)))
+    } else {
+        let fallbacks = try_setup!(
+            spawn_decompression_workers_into(config.profile.clone(), codec_info, decomp_rx, out_tx.clone(), &crypto.header)
+                .map_err(spawn_error),
+            counters,
+            timer
+        );
+        (fallbacks, None)
+    };
+
+    // One frame worker pool for the whole run, shared by every segment worker
+    let frame_pool = try_setup!(
+        DecryptFramePool::decrypt_with_extensions(&crypto.header, &crypto.extensions, crypto.base.session_key.as_slice(), config.profile.cpu_workers())
+            .map_err(|e| StreamError::SegmentWorker(e.into())),
+        counters,
+        timer
+    );
+
+    // Segments carry their decryption / decompression telemetry to the writer, which merges it lock-free
     let read_stage_times = Arc::new(Mutex::new(StageTimes::default()));
-    let decryption_stage_times = Arc::new(Mutex::new(StageTimes::default()));
-    let mut decompression_stage_times = StageTimes::default();
+    let mut segment_stage_times = StageTimes::default();
+    let progress = Progress::new(config.progress.clone());
+    let recorder = config.segment_metrics.then(|| Arc::new(SegmentRecorder::new()));
+    let mut segments_written = 0u64;
+
+    // Writer → reader: one token per segment written
+    let (done_tx, done_rx) = unbounded::<()>();
+    let window = reorder_limit(&config.profile);
+
+    let result = thread::scope(|scope| {
+        // ---- Read-ahead ----
+        // The prefetcher reads segment N+1 while the reader thread hands segment N on
+        let next_segment: Box<dyn FnMut() -> NextSegment + Send + '_> = if config.read_ahead > 0 {
+            let prefetcher = SegmentPrefetcher::spawn(scope, &mut *reader, config.read_ahead);
+            Box::new(move || prefetcher.next_segment())
+        } else {
+            let reader = &mut *reader;
+            Box::new(move || io::read_segment(reader))
+        };
 
-    thread::scope(|scope| {
         // ---- Reader thread ----
-        scope.spawn(|| -> Result<(), StreamError> {
-            eprintln!("[READER] Thread started");
+        let reader_handle = scope.spawn(|| -> Result<(), StreamError> {
             let read_stage_times = Arc::clone(&read_stage_times);
-            let counters_read = Arc::clone(&counters_read);
+            // Owned by this thread: dropping it on exit stops the prefetcher
+            let mut next_segment = next_segment;
+            let mut written = WriteWindow::new(done_rx);
+            let mut dispatched = 0u32;
 
             // Read / chunking / before decompress
             let mut start = Instant::now();            
-            while let Some((header, wire)) = io::read_segment(&mut reader)? {
-                eprintln!("[READER] Dispatching segment {}", header.segment_index);
+            while let Some((header, wire)) = next_segment()? {
+                if progress.is_cancelled() {
+                    return Err(StreamError::Cancelled);
+                }
+                if header.segment_index < config.start_segment {
+                    trace!(segment_index = header.segment_index, "skipping segment before resume point");
+                    start = Instant::now();
+                    continue;
+                }
+                trace!(segment_index = header.segment_index, wire_len = wire.len(), "dispatching segment");
                 let mut times = read_stage_times.lock().unwrap();
-                
-                counters_read.lock().unwrap().bytes_ciphertext += wire.len() as u64;
 
+                // Anything after the final segment (e.g. a segment index trailer) is not payload
+                let is_final = header.flags.contains(SegmentFlags::FINAL_SEGMENT);
+                let len = (SegmentHeader::LEN + wire.len()) as u64;
+                written.wait(dispatched, window)?;
                 seg_tx.send(DecryptSegmentInput { header, wire })
                     .map_err(|_| StreamError::PipelineError("decrypt segment channel closed".into()))?;
+                dispatched += 1;
 
                 times.add(Stage::Read, start.elapsed());
+                drop(times);
+                progress.on_read(len)?;
+                if is_final {
+                    break;
+                }
                 start = Instant::now();
             }
 
-            eprintln!("[READER] Finished, dropping seg_tx");
             drop(seg_tx);
 
             Ok(())
         });
 
         // ---- Crypto workers ----
-        for _ in 0..config.profile.cpu_workers() {
-            let worker = DecryptSegmentWorker::new(crypto.clone(), log_manager.clone());
+        let mut segment_workers = Vec::with_capacity(config.profile.cpu_workers());
+        for worker_id in 0..config.profile.cpu_workers() {
+            let mut worker = DecryptSegmentWorker::new(crypto.clone(), log_manager.clone())
+                .with_frame_pool(frame_pool.handle())
+                .with_segment_hook(config.segment_hook.clone());
+            if let Some(recorder) = &recorder {
+                worker = worker.with_recorder(Arc::clone(recorder), worker_id);
+            }
             let rx = seg_rx.clone();
-            let tx = crypto_out_tx.clone();
-
-            scope.spawn(move || worker.run_v2(rx, tx));
+            let handle = if config.decompress_in_order {
+                // Still compressed: the ordered writer decompresses it
+                worker.run_v2_into(rx, out_tx.clone())
+            } else {
+                worker.run_v2_into(rx, RouteSink::new(decomp_tx.clone(), out_tx.clone()))
+            };
+            segment_workers.push(handle);
         }
+        // Workers hold their own receivers; once they exit, the reader stops instead of blocking
+        drop(seg_rx);
 
-        drop(crypto_out_tx);
-        eprintln!("[PIPELINE] dropped out_tx in main thread");
+        // Only the workers' clones may keep the downstream channels open
+        drop(decomp_tx);
+        drop(out_tx);
 
-        // Adapter: forward successful segments, propagate errors
-        scope.spawn({
-            let decomp_in_tx = decomp_in_tx.clone();
-            let decomp_out_tx = decomp_out_tx.clone();
-            let decryption_stage_times = Arc::clone(&decryption_stage_times);
-            let counters_segment = Arc::clone(&counters_segment);
+        // ---- Ordered plaintext writer ----
+        // A resumed stream only yields a suffix of the plaintext: nothing to check the trailer against
+        let trailer = if config.start_segment == 0 {
+            PlaintextTrailer::for_header(&crypto.header)
+        } else {
+            PlaintextTrailer::default()
+        };
+        let mut ordered_writer = io::OrderedPlaintextWriter::starting_at(&mut writer, config.start_segment)
+            .with_trailer(trailer)
+            .with_max_pending(reorder_limit(&config.profile));
+        if let Some(backend) = inline_backend {
+            ordered_writer = ordered_writer.with_decompressor(backend, max_output);
+        }
+
+        let writer_result = (|| -> Result<(), StreamError> {
+            wait_segment_workers(&mut segment_workers)?;
+            let mut segments_reported = 0;
+            for res in out_rx.iter() {
 
-            move || {
-                for res in crypto_out_rx.iter() {
-                    match res {
-                        Ok(seg) => {
-                            let mut times = decryption_stage_times.lock().unwrap();
-                            for (stage, dur) in seg.stage_times.iter() { times.add(*stage, *dur); }
+                match res {
+                    Ok(mut segment) => {
+                        // Merged once the plaintext is out: bookkeeping never delays a write
+                        let stage_times = std::mem::take(&mut segment.stage_times);
+                        let segment_counters = std::mem::take(&mut segment.counters);
+                        // Writing / wiring
+                        let start = Instant::now();
+                        let decompressed_before = ordered_writer.decompress_time();
 
-                            // 🔥 Merge telemetry from this segment
-                            counters_segment.lock().unwrap().merge(&seg.counters);
+                        if segment.header.flags.contains(SegmentFlags::FINAL_SEGMENT) && segment.is_empty() {
+                            last_segment_index = segment.header.segment_index;
 
-                            let _ = decomp_in_tx.send(seg);
+                            // ✅ Push the final marker so OrderedPlaintextWriter sees it
                         }
-                        Err(e) => {
-                            eprintln!("[PIPELINE] crypto worker error: {e}");
-                            let _ = decomp_out_tx.send(Err(CompressionWorkerError::StateError(e.to_string())));
-                            break;
+                        // Push plaintext
+                        ordered_writer.push(segment)?;
+                        // Inline decompression runs inside `push`: keep it out of the Write stage
+                        let decompressed = ordered_writer.decompress_time() - decompressed_before;
+                        segment_stage_times.add(Stage::Write, start.elapsed().saturating_sub(decompressed));
+                        // Decryption and decompression times, and the segment worker's counters
+                        segment_stage_times.merge(&stage_times);
+                        counters.merge(&segment_counters);
+
+                        if ordered_writer.segments_written() > segments_reported {
+                            for _ in segments_reported..ordered_writer.segments_written() {
+                                let _ = done_tx.send(());
+                            }
+                            segments_reported = ordered_writer.segments_written();
+                            progress.on_written(ordered_writer.bytes_written(), segments_reported)?;
                         }
                     }
-                }
-            }
-        });
-
-        // Drop decomp_in_tx when adapter finishes
-        drop(decomp_in_tx);
-
-        // Now spawn decompression workers on decomp_in_rx
-        spawn_decompression_workers(config.profile.clone(), codec_info, decomp_in_rx, decomp_out_tx.clone());
-        
-        drop(decomp_out_tx); // Drop decomp_out_tx here locally for main thread
-
-        // ---- Ordered plaintext writer ----
-        let mut ordered_writer = io::OrderedPlaintextWriter::new(&mut writer);
-
-        for res in decomp_out_rx.iter() {
-
-            match res {
-                Ok(segment) => {
-                    eprintln!("[WRITER] receiving segment {}", segment.header.segment_index);
-                    // merge decompression stage_times
-                    decompression_stage_times.merge(&segment.stage_times);
-                    // Writing / wiring
-                    let start = Instant::now();
-
-                    if segment.header.flags.contains(SegmentFlags::FINAL_SEGMENT) && segment.bytes.is_empty() {
-                        eprintln!("[WRITER] final empty segment {}", segment.header.segment_index);
-                        last_segment_index = segment.header.segment_index;
-
-                        // ✅ Push the final marker so OrderedPlaintextWriter sees it
+                    Err(e) => {
+                        debug!(error = %e, "crypto/decompression worker error");
+                        return Err(e);
                     }
-                    // update bytes_plaintext
-                    counters.bytes_plaintext += segment.bytes.len() as u64;
-
-                    // Push plaintext
-                    ordered_writer.push(&segment)?;
-                    decompression_stage_times.add(Stage::Write, start.elapsed());
-                }
-                Err(e) => {
-                    eprintln!("[PIPELINE] decompression worker error: {e}");
-                    return Err(StreamError::CompressionWorker(e));
                 }
             }
-        }
-
-        eprintln!("[WRITER] out_rx closed, finishing writer");
-        ordered_writer.finish()?;
-        
-        Ok::<(), StreamError>(())
-    })?;
+            ordered_writer.finish()
+        })();
+        drop(out_rx);
+        // Unblock a reader waiting for room in the window
+        drop(done_tx);
+        // Segments may reach the writer compressed: count plaintext as written (trailer excluded)
+        counters.bytes_plaintext = ordered_writer.bytes_written();
+        segments_written = ordered_writer.segments_written();
+        segment_stage_times.add(Stage::Decompress, ordered_writer.decompress_time());
+
+        let reader_result = reader_handle
+            .join()
+            .unwrap_or(Err(StreamError::PipelineError("decrypt reader thread panicked")));
+        // Their input is closed and `out_rx` is gone: every segment worker is on its way out
+        let workers_result = join_segment_workers(segment_workers);
+
+        first_pipeline_error(reader_result, writer_result).and(workers_result)
+    });
+
+    // Segment workers are done once their inputs close; wait for the frame workers too
+    let frame_workers = frame_pool.threads();
+    frame_pool.shutdown();
+
+    // Workers are done: flush the audit log so a write failure is not lost
+    let log_closed = log_manager.close();
+    // Cancellation surfaces downstream as assorted channel/final-segment errors
+    let result = if progress.is_cancelled() {
+        Err(StreamError::Cancelled)
+    } else {
+        result.and(log_closed.map_err(StreamError::from))
+    };
 
     timer.finish();
 
@@ -432,33 +969,25 @@ where
     // merge read stage_times
     // let final_times = read_stage_times.lock().unwrap(); 
     // for (stage, dur) in final_times.iter() { timer.add_stage_time(*stage, *dur); }
-    for (stage, dur) in read_stage_times.lock().unwrap().iter() {
-        timer.add_stage_time(*stage, *dur);
-    }
-    // merge decryption stage_times
-    for (stage, dur) in decryption_stage_times.lock().unwrap().iter() {
-        timer.add_stage_time(*stage, *dur);
-    }
-    // merge decompression stage_times
-    for (stage, dur) in decompression_stage_times.iter() {
-        timer.add_stage_time(*stage, *dur);
+    timer.merge(&read_stage_times.lock().unwrap());
+    // merge decryption, decompression and write stage_times
+    timer.merge(&segment_stage_times);
+
+    // A failed run only counts what reached the output
+    let segments = if result.is_ok() {
+        (last_segment_index + 1).saturating_sub(config.start_segment)
+    } else {
+        segments_written as u32
+    };
+    let mut snapshot = TelemetrySnapshot::from(&counters, &timer, Some(segments));
+    snapshot.strategy_effective = Some(effective_strategy(config, false));
+    snapshot.cpu_workers = config.profile.cpu_workers();
+    snapshot.frame_workers = frame_workers;
+    snapshot.stream_created_at = crypto.header.created_at();
+    if let Some(recorder) = &recorder {
+        (snapshot.segment_records, snapshot.worker_usage) = recorder.drain();
     }
-
-    // update bytes_ciphertext len
-    counters.bytes_ciphertext = counters_read.lock().unwrap().bytes_ciphertext;
-    // 🔥 Merge telemetry from this segment worker
-    counters.merge(&counters_segment.lock().unwrap());
-
-    // After pipeline finishes: 
-    if let Some(ref arc_buf) = config.buf { 
-        let buf = arc_buf.lock().unwrap(); 
-        println!("Captured output: {:?}", String::from_utf8_lossy(&buf)); 
-    }
-
-    Ok(TelemetrySnapshot::from(
-        &counters,
-        &timer,
-        Some(last_segment_index + 1),
-    ))
+    snapshot.gpu_fallbacks = gpu_fallbacks as u64;
+    PipelineOutcome { result, snapshot }
 }
 
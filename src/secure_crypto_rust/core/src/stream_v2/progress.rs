@@ -0,0 +1,94 @@
+// ## 📂 File: `src/stream_v2/progress.rs`
+//! Progress reporting and cooperative cancellation for the pipelines.
+//!
+//! The reader thread reports after each segment it dispatches, the ordered
+//! writer after each push that wrote segments out. Either report may run the
+//! caller's callback; `ControlFlow::Break` latches a cancel flag that every
+//! later report (and the reader's loop) sees, so the pipeline winds down and
+//! returns `StreamError::Cancelled`.
+
+use std::ops::ControlFlow;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::types::StreamError;
+
+/// Caller hook invoked with each [`ProgressEvent`]; `Break` cancels the run.
+///
+/// Called from the reader and writer threads, possibly concurrently.
+pub type ProgressCallback = Arc<dyn Fn(ProgressEvent) -> ControlFlow<()> + Send + Sync>;
+
+/// Pipeline position at the time of a report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgressEvent {
+    /// Input bytes dispatched by the reader (plaintext when encrypting, segment wire when decrypting).
+    pub bytes_read: u64,
+    /// Bytes the ordered writer has written out (stream header excluded).
+    pub bytes_written: u64,
+    /// Segments the ordered writer has written out.
+    pub segments_completed: u64,
+    /// Time since the pipeline started.
+    pub elapsed: Duration,
+}
+
+/// Shared progress state of one pipeline run.
+pub(crate) struct Progress {
+    callback: Option<ProgressCallback>,
+    start: Instant,
+    bytes_read: AtomicU64,
+    bytes_written: AtomicU64,
+    segments_completed: AtomicU64,
+    cancelled: AtomicBool,
+}
+
+impl Progress {
+    pub(crate) fn new(callback: Option<ProgressCallback>) -> Self {
+        Self {
+            callback,
+            start: Instant::now(),
+            bytes_read: AtomicU64::new(0),
+            bytes_written: AtomicU64::new(0),
+            segments_completed: AtomicU64::new(0),
+            cancelled: AtomicBool::new(false),
+        }
+    }
+
+    /// Whether a callback has returned `Break`.
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Reader side: `len` more input bytes were dispatched.
+    pub(crate) fn on_read(&self, len: u64) -> Result<(), StreamError> {
+        self.bytes_read.fetch_add(len, Ordering::Relaxed);
+        self.report()
+    }
+
+    /// Writer side: totals after a push that wrote segments out.
+    pub(crate) fn on_written(&self, bytes_written: u64, segments_completed: u64) -> Result<(), StreamError> {
+        self.bytes_written.store(bytes_written, Ordering::Relaxed);
+        self.segments_completed.store(segments_completed, Ordering::Relaxed);
+        self.report()
+    }
+
+    fn report(&self) -> Result<(), StreamError> {
+        if self.is_cancelled() {
+            return Err(StreamError::Cancelled);
+        }
+        let Some(callback) = &self.callback else {
+            return Ok(());
+        };
+        let event = ProgressEvent {
+            bytes_read: self.bytes_read.load(Ordering::Relaxed),
+            bytes_written: self.bytes_written.load(Ordering::Relaxed),
+            segments_completed: self.segments_completed.load(Ordering::Relaxed),
+            elapsed: self.start.elapsed(),
+        };
+        if callback(event).is_break() {
+            self.cancelled.store(true, Ordering::Release);
+            return Err(StreamError::Cancelled);
+        }
+        Ok(())
+    }
+}
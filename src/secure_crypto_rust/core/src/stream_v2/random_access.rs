@@ -0,0 +1,179 @@
+// # 📂 src/stream_v2/random_access.rs
+
+// ## 📂 File: `src/stream_v2/random_access.rs`
+// ## Decrypt single segments of a seekable stream
+//
+// Segments are independently framed and digest-protected, so segment N can be
+// decrypted without touching the others:
+//
+// - a `SegmentIndexMap` locates the segment: given, read from the authenticated
+//   index trailer (`HAS_SEGMENT_INDEX`), or scanned on first use
+// - the segment is read with one seek and checked by a `DecryptSegmentWorker`
+//   (CRC, per-frame AEAD, digest, terminator), exactly as in the pipeline
+// - the plaintext is decompressed with the codec recorded in the stream header
+
+use std::io::{Read, Seek, SeekFrom};
+use bytes::Bytes;
+use crossbeam::channel::{bounded, Receiver, Sender};
+
+use crate::constants::flags;
+use crate::crypto::DigestAlg;
+use crate::headers::HeaderV1;
+use crate::stream_v2::compression_worker::{decompress_frames, make_backend, CodecInfo, CompressionBackend};
+use crate::stream_v2::core::{setup_dec_context, validate_decrypt_params, ApiConfig, DecryptParams};
+use crate::stream_v2::io::{locate_segment_index, read_segment, scan_segment_index, PayloadReader, PlaintextTrailer};
+use crate::stream_v2::parallelism::WorkerTarget;
+use crate::stream_v2::segment_worker::{DecryptSegmentInput, DecryptSegmentWorker, DecryptedSegment, SegmentWorkerError};
+use crate::stream_v2::segmenting::{decode_segment_index, SegmentHeader, SegmentIndexMap};
+use crate::stream_v2::segmenting::types::SegmentFlags;
+use crate::types::StreamError;
+
+/// 🔓 Random-access decryptor over a `Read + Seek` stream.
+///
+/// Without an index, the first lookup loads the stream's index trailer if the
+/// header announces one, and otherwise scans the segment headers once (wires
+/// are skipped with `seek`); pass one with [`RandomAccessDecryptor::with_index`]
+/// to avoid both.
+pub struct RandomAccessDecryptor<R: Read + Seek> {
+    reader: PayloadReader<R>,
+    header: HeaderV1,
+    index: Option<SegmentIndexMap>,
+    backend: Box<dyn CompressionBackend>,
+    seg_tx: Sender<DecryptSegmentInput>,
+    out_rx: Receiver<Result<DecryptedSegment, SegmentWorkerError>>,
+}
+
+impl<R: Read + Seek> RandomAccessDecryptor<R> {
+    /// Parse the stream header and start the segment worker.
+    pub fn new(mut reader: R, master_key: &[u8], params: DecryptParams) -> Result<Self, StreamError> {
+        validate_decrypt_params(master_key, &params, None, None)?;
+
+        reader.seek(SeekFrom::Start(0))?;
+        let (header, reader) = PayloadReader::with_header_policy(reader, params.unknown_flags)?;
+        params.check_header(&header)?;
+        let (crypto, profile, log_manager) = setup_dec_context(master_key, &header, DigestAlg::Blake3, &ApiConfig::default(), None)?;
+        let crypto = crypto.with_extensions(reader.extensions().clone());
+        crypto.header.validate_with(params.unknown_flags).map_err(StreamError::Header)?;
+
+        let mut codec_info = CodecInfo::from_header(&header, params.dictionaries.as_deref())
+            .map_err(StreamError::Compression)?;
+        codec_info.gpu = profile.gpu();
+        let backend = make_backend(WorkerTarget::Cpu(0), codec_info).map_err(StreamError::Compression)?;
+
+        // One segment at a time: lookups are synchronous
+        let (seg_tx, seg_rx) = bounded::<DecryptSegmentInput>(1);
+        let (out_tx, out_rx) = bounded::<Result<DecryptedSegment, SegmentWorkerError>>(1);
+        DecryptSegmentWorker::new(crypto, log_manager).run_v2(seg_rx, out_tx).wait_ready()?;
+
+        Ok(Self { reader, header, index: None, backend, seg_tx, out_rx })
+    }
+
+    /// Use a known index instead of scanning the stream.
+    pub fn with_index(mut self, index: SegmentIndexMap) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    pub fn header(&self) -> &HeaderV1 {
+        &self.header
+    }
+
+    /// The segment index, loaded or scanned on first use.
+    pub fn index(&mut self) -> Result<&SegmentIndexMap, StreamError> {
+        if self.index.is_none() {
+            let trailing = if self.header.flags & flags::HAS_SEGMENT_INDEX != 0 {
+                self.load_trailing_index()?
+            } else {
+                None
+            };
+            let index = match trailing {
+                Some(index) => index,
+                None => scan_segment_index(&mut self.reader)?,
+            };
+            self.index = Some(index);
+        }
+        self.index
+            .as_ref()
+            .ok_or(StreamError::PipelineError("segment index unavailable"))
+    }
+
+    /// Number of segments, empty final segment included.
+    pub fn segment_count(&mut self) -> Result<usize, StreamError> {
+        Ok(self.index()?.len())
+    }
+
+    /// Decrypt, verify and decompress segment `segment_index`.
+    ///
+    /// The empty final segment and the plaintext trailer segment yield
+    /// empty plaintext; indexes past the final segment fail
+    /// with `StreamError::Validation`.
+    pub fn read_segment(&mut self, segment_index: u32) -> Result<Bytes, StreamError> {
+        let index = self.index()?;
+        let entry = *index.get(segment_index).ok_or_else(|| {
+            StreamError::Validation(format!(
+                "segment {segment_index} out of range: stream has {} segments",
+                index.len()
+            ))
+        })?;
+
+        self.reader.seek(SeekFrom::Start(entry.offset))?;
+        let (header, wire) = read_segment(&mut self.reader)?
+            .ok_or_else(|| StreamError::Validation(format!("no segment at offset {}", entry.offset)))?;
+        if header.segment_index != segment_index || header.wire_len != entry.wire_len {
+            return Err(StreamError::Validation(format!(
+                "segment index mismatch: expected segment {segment_index} at offset {}, found {}",
+                entry.offset, header.segment_index
+            )));
+        }
+
+        let segment = self.decrypt(header, wire)?;
+        if (segment.header.flags.contains(SegmentFlags::FINAL_SEGMENT) && segment.is_empty())
+            || PlaintextTrailer::is_trailer(segment.header.flags)
+        {
+            return Ok(Bytes::new());
+        }
+
+        let frames = decompress_frames(self.backend.as_mut(), segment.frames, segment.header.flags)
+            .map_err(StreamError::Compression)?;
+        Ok(match frames.as_slice() {
+            [single] => single.clone(),
+            frames => Bytes::from(frames.concat()),
+        })
+    }
+
+    /// Decrypt and check the index segment the stream footer points at.
+    ///
+    /// `None` when there is no footer (e.g. a truncated stream): callers fall
+    /// back to a scan.
+    fn load_trailing_index(&mut self) -> Result<Option<SegmentIndexMap>, StreamError> {
+        let Some(offset) = locate_segment_index(&mut self.reader)? else {
+            return Ok(None);
+        };
+        self.reader.seek(SeekFrom::Start(offset))?;
+        let (header, wire) = read_segment(&mut self.reader)?
+            .ok_or_else(|| StreamError::Validation(format!("no index segment at offset {offset}")))?;
+        if !header.flags.contains(SegmentFlags::SEGMENT_INDEX) {
+            return Err(StreamError::Validation(format!(
+                "segment at offset {offset} is not a segment index"
+            )));
+        }
+
+        let index_segment = header.segment_index;
+        let payload = self.decrypt(header, wire)?.to_bytes();
+        let index = decode_segment_index(&payload, self.header.encoded_len() as u64).map_err(StreamError::Segment)?;
+        if !index.is_complete() || index.end_offset() != offset || index.len() != index_segment as usize {
+            return Err(StreamError::Validation("segment index does not match the stream layout".into()));
+        }
+        Ok(Some(index))
+    }
+
+    fn decrypt(&mut self, header: SegmentHeader, wire: Bytes) -> Result<DecryptedSegment, StreamError> {
+        self.seg_tx
+            .send(DecryptSegmentInput { header, wire })
+            .map_err(|_| StreamError::PipelineError("decrypt segment channel closed"))?;
+        self.out_rx
+            .recv()
+            .map_err(|_| StreamError::PipelineError("decrypt segment worker disconnected"))?
+            .map_err(StreamError::SegmentWorker)
+    }
+}
@@ -0,0 +1,171 @@
+// # 📂 src/stream_v2/reencrypt.rs
+
+// ## 📂 File: `src/stream_v2/reencrypt.rs`
+// ## Rotate the key of an encrypted stream without a plaintext copy on disk
+//
+// Re-encryption runs both pipelines at once, joined by an in-memory pipe:
+//
+// - the decrypt leg runs on its own thread; its ordered writer feeds the pipe
+// - the encrypt leg reads the pipe like any other plaintext source, so its
+//   chunk reader re-segments across the old segment boundaries
+// - the pipe is a bounded channel of ordered writes, and the decrypt leg
+//   decompresses in order, so only a few segments of plaintext are resident
+// - a failed decrypt leg poisons the pipe: the encrypt leg fails instead of
+//   sealing a truncated stream, and the decrypt error is reported
+
+use std::io::{self, Read, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use crossbeam::channel::{Receiver, Sender, bounded};
+
+use crate::crypto::KeyRing;
+use crate::stream_v2::core::{
+    ApiConfig, DecryptParams, EncryptParams, decrypt_stream_v2_with_keyring, encrypt_stream_v2, validate_encrypt_params,
+};
+use crate::stream_v2::io::{InputSource, OutputSink};
+use crate::telemetry::TelemetrySnapshot;
+use crate::types::StreamError;
+
+/// Ordered writes the pipe holds before the decrypt leg blocks.
+pub const REENCRYPT_PIPE_DEPTH: usize = 4;
+
+/// Outcome of [`reencrypt_stream_v2`], one snapshot per leg.
+#[derive(Debug)]
+pub struct ReencryptReport {
+    /// Decryption of the old stream (its output went to the pipe).
+    pub decrypt: TelemetrySnapshot,
+    /// Encryption of the new stream; `output` holds it for `OutputSink::Memory`.
+    pub encrypt: TelemetrySnapshot,
+}
+
+/// A chunk of plaintext, or why the decrypt leg stopped.
+type PipeMessage = Result<Vec<u8>, String>;
+
+/// Decrypt side of the pipe: every ordered write becomes one message.
+struct PipeWriter {
+    tx: Sender<PipeMessage>,
+}
+
+impl Write for PipeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        self.tx
+            .send(Ok(buf.to_vec()))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "re-encryption stopped reading plaintext"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Encrypt side of the pipe; end of input once the decrypt leg is done.
+struct PipeReader {
+    rx: Receiver<PipeMessage>,
+    current: Vec<u8>,
+    pos: usize,
+    /// Set once the decrypt leg's failure reached the encrypt leg.
+    poisoned: Arc<AtomicBool>,
+}
+
+impl Read for PipeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.current.len() {
+            match self.rx.recv() {
+                Ok(Ok(chunk)) => {
+                    self.current = chunk;
+                    self.pos = 0;
+                }
+                Ok(Err(reason)) => {
+                    self.poisoned.store(true, Ordering::Relaxed);
+                    return Err(io::Error::other(format!("re-encryption source failed: {reason}")));
+                }
+                // Every sender gone without an error: the old stream ended cleanly
+                Err(_) => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.current.len() - self.pos);
+        buf[..n].copy_from_slice(&self.current[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// 🔁 Re-encrypt a stream under a new master key and header
+///
+/// The old stream's key is selected from `old_keys` by its `key_id`; the new
+/// stream is written with `new_master_key` and `new_params`, whose header may
+/// change `key_id`, salt, codec and chunk size. Plaintext never reaches
+/// `output` or the disk: it flows through a pipe of [`REENCRYPT_PIPE_DEPTH`]
+/// writes.
+///
+/// `config` applies to the encrypt leg (output capture, audit log, progress);
+/// the decrypt leg shares its worker profile and metrics setting.
+///
+/// # Errors
+/// - the decrypt leg's error when the old stream fails to authenticate
+/// - otherwise the encrypt leg's error
+pub fn reencrypt_stream_v2(
+    input: InputSource,
+    output: OutputSink,
+    old_keys: &KeyRing,
+    new_master_key: &[u8],
+    new_params: EncryptParams,
+    config: ApiConfig,
+) -> Result<ReencryptReport, StreamError> {
+    // Fail before anything is decrypted
+    validate_encrypt_params(new_master_key, &new_params, None, None)?;
+
+    let decrypt_config = ApiConfig {
+        with_buf: Some(false),
+        collect_metrics: config.collect_metrics,
+        profile: config.profile.clone(),
+        auto_parallel_threshold: config.auto_parallel_threshold,
+        read_ahead: config.read_ahead,
+        ..ApiConfig::default()
+    }
+    // Segments waiting for their turn stay compressed
+    .with_decompress_in_order(true);
+
+    let (tx, rx) = bounded::<PipeMessage>(REENCRYPT_PIPE_DEPTH);
+    let poison_tx = tx.clone();
+    let writer = PipeWriter { tx };
+    let poisoned = Arc::new(AtomicBool::new(false));
+    let reader = PipeReader { rx, current: Vec::new(), pos: 0, poisoned: poisoned.clone() };
+
+    thread::scope(|scope| {
+        let decrypt_leg = scope.spawn(move || {
+            let result = decrypt_stream_v2_with_keyring(
+                input,
+                OutputSink::Writer(Box::new(writer)),
+                old_keys,
+                DecryptParams::new(),
+                decrypt_config,
+            );
+            if let Err(e) = &result {
+                // The encrypt leg may already be gone
+                let _ = poison_tx.send(Err(e.to_string()));
+            }
+            result
+        });
+
+        let encrypt = encrypt_stream_v2(InputSource::Reader(Box::new(reader)), output, new_master_key, new_params, config);
+        let decrypt = decrypt_leg
+            .join()
+            .unwrap_or(Err(StreamError::PipelineError("re-encryption decrypt leg panicked")));
+
+        // Report the leg that failed first: a poisoned pipe means the decrypt
+        // leg did, a broken pipe on the decrypt side means the encrypt leg did
+        let decrypt = match decrypt {
+            Err(e) if poisoned.load(Ordering::Relaxed) => return Err(e),
+            decrypt => decrypt,
+        };
+        let encrypt = encrypt?;
+        Ok(ReencryptReport { decrypt: decrypt?, encrypt })
+    })
+}
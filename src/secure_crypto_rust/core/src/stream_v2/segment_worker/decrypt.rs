@@ -1,21 +1,29 @@
 // # 📂 `src/stream_v2/segment_worker/decrypt.rs`
 
 use bytes::Bytes;
-use crossbeam::channel::{Receiver, Sender, bounded, unbounded};
-use std::{thread, time::Instant};
+use crossbeam::channel::{Receiver, Sender};
+use std::{collections::HashMap, time::Instant};
+use tracing::{debug, trace};
 
 use crate::{
-    crypto::{DigestAlg, DigestFrame, SegmentDigestVerifier}, 
+    crypto::{DigestFrame, KEY_LEN_32, SegmentDigestVerifier}, 
+    headers::FrameLayout,
+    recovery::Direction,
     stream_v2::{
-        frame_worker::{DecryptedFrame, FrameWorkerError, decrypt::DecryptFrameWorker}, 
-        framing::{FrameError, FrameHeader, FrameType, decode::parse_frame_header}, 
-        segment_worker::{DecryptContext, DecryptedSegment, SegmentWorkerError, types::DecryptSegmentInput}, segmenting::{SegmentHeader, types::SegmentFlags}
-    }, telemetry::{Stage, StageTimes, counters::TelemetryCounters}
+        frame_worker::{DecryptFrameInput, DecryptFramePool, DecryptFramePoolHandle, DecryptedFrame, FrameDispatch, FrameWorkerError, recv_frame}, 
+        framing::{FrameError, FrameHeader, FrameType, decode::parse_frame_header, types::checked_len_add}, 
+        segment_worker::{DecryptContext, DecryptedSegment, SegmentHook, SegmentWorkerError, SegmentWorkerHandle, types::{DecryptSegmentInput, catch_segment_panic}}, segmenting::{SegmentHeader, types::SegmentFlags}, sink::StageSink,
+    }, telemetry::{SegmentRecord, SegmentRecorder, Stage, StageTimes, counters::TelemetryCounters}
 };
 
 pub struct DecryptSegmentWorker {
     crypto: DecryptContext,
     pub log_manager: std::sync::Arc<crate::recovery::persist::AsyncLogManager>,
+    /// Shared frame workers; `None` starts a private pool in `run_v2`.
+    frames: Option<DecryptFramePoolHandle>,
+    /// Per-segment timing sink and this worker's id in the pool.
+    recorder: Option<(std::sync::Arc<SegmentRecorder>, usize)>,
+    hook: Option<SegmentHook>,
 }
 
 impl DecryptSegmentWorker {
@@ -23,103 +31,195 @@ impl DecryptSegmentWorker {
         crypto: crate::stream_v2::segment_worker::DecryptContext,
         log_manager: std::sync::Arc<crate::recovery::persist::AsyncLogManager>,
     ) -> Self {
-        Self { crypto, log_manager }
+        Self { crypto, log_manager, frames: None, recorder: None, hook: None }
+    }
+
+    /// Decrypt frames on a pool shared with other segment workers.
+    pub fn with_frame_pool(mut self, frames: DecryptFramePoolHandle) -> Self {
+        self.frames = Some(frames);
+        self
+    }
+
+    /// Report every decrypted segment to `recorder` as worker `worker_id`.
+    pub fn with_recorder(mut self, recorder: std::sync::Arc<SegmentRecorder>, worker_id: usize) -> Self {
+        self.recorder = Some((recorder, worker_id));
+        self
+    }
+
+    /// Call `hook` with each segment header before the segment is processed.
+    pub fn with_segment_hook(mut self, hook: Option<SegmentHook>) -> Self {
+        self.hook = hook;
+        self
     }
 
     /// Run decrypt loop.
     ///
     /// Receives segment wire bytes from `rx`, processes frames in parallel,
     /// reorders decrypted frames, verifies Digest, streams out ordered plaintext frames.
+    ///
+    /// The returned handle reports whether the frame pool started and joins
+    /// the worker thread once `rx` is closed.
     pub fn run_v2(
         self,
         rx: Receiver<DecryptSegmentInput>,
         tx: Sender<Result<DecryptedSegment, SegmentWorkerError>>,
-    ) {
-        let crypto = self.crypto.clone();
-
-        thread::spawn(move || {
-            eprintln!("[WORKER] thread spawned");
-            let worker_count = crypto.base.profile.cpu_workers();
-            let digest_alg = crypto.base.digest_alg;
-
-            // Frame worker pool channels
-            let (frame_tx, frame_rx) = bounded::<Bytes>(worker_count * 4);
-            let (out_tx, out_rx) = unbounded::<Result<DecryptedFrame, FrameWorkerError>>();
+    ) -> SegmentWorkerHandle {
+        self.run_v2_into(rx, tx)
+    }
 
-            for _ in 0..worker_count {
-                let fw = DecryptFrameWorker::new(crypto.header, &crypto.base.session_key)
-                    .expect("DecryptFrameWorker pool init failed");
-               
-                fw.run(frame_rx.clone(), out_tx.clone());
-            }
-            drop(frame_rx);
-            drop(out_tx);
+    /// [`run_v2`](Self::run_v2) delivering to any [`StageSink`].
+    pub fn run_v2_into<S>(self, rx: Receiver<DecryptSegmentInput>, mut tx: S) -> SegmentWorkerHandle
+    where
+        S: StageSink<DecryptedSegment, SegmentWorkerError> + 'static,
+    {
+        let crypto = self.crypto.clone();
+        let frames = self.frames;
+        let log_manager = self.log_manager;
+        let recorder = self.recorder;
+        let hook = self.hook;
+
+        SegmentWorkerHandle::spawn(move |ready| {
+            // Frame workers: the shared pool, or a private one that lives as long as this connection
+            let (frame_tx, out_rx) = match frames {
+                Some(frames) => frames.connect(),
+                None => {
+                    let worker_count = crypto.base.profile.cpu_workers();
+                    match DecryptFramePool::decrypt_with_extensions(&crypto.header, &crypto.extensions, crypto.base.session_key.as_slice(), worker_count) {
+                        Ok(pool) => pool.handle().connect(),
+                        Err(e) => {
+                            let _ = ready.send(Err(e.into()));
+                            return;
+                        }
+                    }
+                }
+            };
+            // Started: later failures are per segment, through `tx`
+            let _ = ready.send(Ok(()));
+            drop(ready);
 
             // Main loop: process encrypted segments
             while let Ok(segment) = rx.recv() {
-                eprintln!("[WORKER] processing segment {}", segment.header.segment_index);
-                // verify segment wire
+                let span = tracing::debug_span!("segment", segment_index = segment.header.segment_index);
+                let _enter = span.enter();
+                if let Some(hook) = &hook {
+                    hook(&segment.header);
+                }
+                // verify segment wire: the only CRC pass over it
+                let start = Instant::now();
                 match segment.header.validate(&segment.wire) {
                     Ok(()) => {
-                        let result = process_decrypt_segment_v2(
-                            &segment,
-                            &digest_alg,
-                            &frame_tx,
-                            &out_rx,
-                        );
+                        let validated = start.elapsed();
+                        // A panic fails this segment instead of silently closing `tx`
+                        let result = catch_segment_panic(|| {
+                            process_decrypt_segment_with_layout(
+                                &segment,
+                                Some(&*crypto.base.session_key),
+                                crypto.header.frame_layout(),
+                                &frame_tx,
+                                &out_rx,
+                            )
+                        })
+                        .map(|mut decrypted| {
+                            decrypted.stage_times.add(Stage::Validate, validated);
+                            decrypted
+                        });
+                        if result.is_ok() {
+                            if let Some((recorder, worker_id)) = &recorder {
+                                recorder.record(SegmentRecord {
+                                    segment_index: segment.header.segment_index,
+                                    wire_len: segment.wire.len() as u64,
+                                    duration: start.elapsed(),
+                                    worker_id: *worker_id,
+                                });
+                            }
+                            // A failed append resurfaces when the pipeline closes the log
+                            if let Err(e) = log_manager.log_frames(Direction::Decrypt, &segment.wire) {
+                                debug!(error = %e, "audit log append failed");
+                            }
+                        }
 
                         // Send result (Ok or Err) - let caller decide how to handle errors
-                        if tx.send(result).is_err() {
-                            eprintln!("[WORKER] tx send failed, receiver gone");
+                        if !tx.send(result) {
+                            debug!("segment result receiver gone, decrypt worker exiting");
                             // Receiver dropped, exit cleanly
                             return;
                         }
                     }
                     Err(e) => {
-                        if tx.send(Err(SegmentWorkerError::SegmentError(e))).is_err() {
-                            eprintln!("[WORKER] tx send failed, receiver gone");
+                        if !tx.send(Err(SegmentWorkerError::SegmentError(e))) {
+                            debug!("segment result receiver gone, decrypt worker exiting");
                             // Receiver dropped, exit cleanly
                             return;
                         }
                     }
                 }
             }
-            eprintln!("[WORKER] rx closed, dropping frame_tx and exiting");
+            debug!("segment input closed, decrypt worker exiting");
             drop(frame_tx);
             drop(tx);
-        });
+        })
     }
 
 }
 
 /// Process a single encrypted segment into plaintext
+///
+/// `input.header` must already have passed `SegmentHeader::validate` against
+/// `input.wire`; the worker loop does that once per segment.
+///
+/// Unkeyed digests only: a `Blake3Keyed` segment fails with
+/// `DigestError::KeyRequired`, see [`process_decrypt_segment_with_key`].
 pub fn process_decrypt_segment_v2(
     input: &DecryptSegmentInput,
-    digest_alg: &DigestAlg,
-    frame_tx: &Sender<Bytes>,
+    frame_tx: &impl FrameDispatch<DecryptFrameInput>,
+    out_rx: &Receiver<Result<DecryptedFrame, FrameWorkerError>>,
+) -> Result<DecryptedSegment, SegmentWorkerError> {
+    process_decrypt_segment_with_key(input, None, frame_tx, out_rx)
+}
+
+/// [`process_decrypt_segment_v2`] with the session key.
+///
+/// Digests are verified with the algorithm the segment header records
+/// (`SegmentHeader::digest_algorithm`), so `Blake3Keyed` segments need the key.
+pub fn process_decrypt_segment_with_key(
+    input: &DecryptSegmentInput,
+    session_key: Option<&[u8; KEY_LEN_32]>,
+    frame_tx: &impl FrameDispatch<DecryptFrameInput>,
+    out_rx: &Receiver<Result<DecryptedFrame, FrameWorkerError>>,
+) -> Result<DecryptedSegment, SegmentWorkerError> {
+    process_decrypt_segment_with_layout(input, session_key, FrameLayout::LEGACY, frame_tx, out_rx)
+}
+
+/// [`process_decrypt_segment_with_key`] for a stream whose header declares
+/// its closing frames (`HeaderV1::frame_layout`).
+///
+/// A closing frame the layout promises but the segment lacks fails with
+/// `MissingDigestFrame` / `MissingTerminatorFrame`; one it does not promise
+/// fails with `UnexpectedFrame`.
+pub fn process_decrypt_segment_with_layout(
+    input: &DecryptSegmentInput,
+    session_key: Option<&[u8; KEY_LEN_32]>,
+    layout: FrameLayout,
+    frame_tx: &impl FrameDispatch<DecryptFrameInput>,
     out_rx: &Receiver<Result<DecryptedFrame, FrameWorkerError>>,
 ) -> Result<DecryptedSegment, SegmentWorkerError> {
     let mut counters = TelemetryCounters::default();
     let mut stage_times = StageTimes::default();
 
-    eprintln!("[DECRYPT] Entering process_decrypt_segment_v2 for segment {}", input.header.segment_index);
+    trace!(wire_len = input.wire.len(), "decrypting segment");
 
-    // Validation
-    let start = Instant::now();
-    // ✅ Empty final segment case
+    // ✅ Empty final segment case (its header was validated like any other)
     if input.wire.is_empty() && input.header.flags.contains(SegmentFlags::FINAL_SEGMENT) {
-        eprintln!("[DECRYPT] Empty FINAL_SEGMENT detected at index {}", input.header.segment_index);
+        debug!("empty final segment");
+        // Its header was still read
+        counters.add_header(SegmentHeader::LEN);
         return Ok(DecryptedSegment {
             header: input.header.clone(),
-            bytes: Bytes::new(), // no plaintext frames
+            frames: Vec::new(), // no plaintext frames
             counters,
             stage_times,
         });
     }
-    // verify crc32 of segment wire
-    input.header.validate(&input.wire).map_err(SegmentWorkerError::SegmentError)?;
-    stage_times.add(Stage::Validate, start.elapsed());
-
     // One frame for each segment, the SegmentHeader
     counters.add_header(SegmentHeader::LEN);
 
@@ -128,23 +228,28 @@ pub fn process_decrypt_segment_v2(
     let start = Instant::now();
     let mut offset = 0;
     let mut frame_count: usize = 0;
+    // (segment_index, frame_index) of every dispatched frame, to route responses
+    let mut dispatched: HashMap<(u32, u32), usize> = HashMap::new();
     while offset < input.wire.len() {
-        eprintln!("[DECRYPT] Parsing frame headers from wire, len={}", input.wire.len());
-        let header = parse_frame_header(&input.wire[offset..])?;
-        let frame_len = FrameHeader::LEN + header.ciphertext_len as usize;
-        let end = offset + frame_len;
+        let remaining = input.wire.len() - offset;
+        let header = parse_frame_header(&input.wire[offset..])
+            .map_err(|e| FrameWorkerError::from_decode(e, remaining))?;
+        let frame_len = header.total_len().map_err(FrameWorkerError::MalformedFrameHeader)?;
+        // Lengths are untrusted until the frame authenticates: never wrap
+        let end = checked_len_add("frame end offset", offset, frame_len)?;
 
         if end > input.wire.len() {
-            eprintln!("[DECRYPT] Frame truncated at offset {}", offset);
-            return Err(FrameError::Truncated.into());
+            debug!(offset, "frame truncated");
+            return Err(FrameWorkerError::Truncated { needed: frame_len, have: remaining }
+                .at(header.segment_index, header.frame_index)
+                .into());
         }
 
-        eprintln!("[DECRYPT] Dispatching frame {} (segment {}, len={})",
-                  frame_count, input.header.segment_index, frame_len);
+        trace!(frame_count, frame_len, "dispatching frame");
         // 2️⃣ Dispatch all frames for parallel decryption
         // 🔥 O(1) slice
-        frame_tx.send(input.wire.slice(offset..end))
-            .map_err(|_| SegmentWorkerError::FrameWorkerError(FrameWorkerError::WorkerDisconnected))?;
+        frame_tx.dispatch(DecryptFrameInput { wire: input.wire.slice(offset..end), segment_flags: input.header.flags })?;
+        *dispatched.entry((header.segment_index, header.frame_index)).or_default() += 1;
 
         offset = end;
         frame_count += 1;
@@ -153,84 +258,103 @@ pub fn process_decrypt_segment_v2(
     stage_times.add(Stage::Read, start.elapsed());
 
     if frame_count == 0 {
-        eprintln!("[DECRYPT] No frames found in non-final segment {}", input.header.segment_index);
+        debug!("no frames in non-final segment");
         return Err(SegmentWorkerError::InvalidSegment("Empty segment".into()));
     }
 
     // 3️⃣ Collect decrypted frames (unordered)
-    let mut data_frames = Vec::with_capacity(frame_count.saturating_sub(2));
+    let mut data_frames = Vec::with_capacity(frame_count.saturating_sub(layout.closing_frames() as usize));
+    let mut padding_frames: Vec<DecryptedFrame> = Vec::new();
     let mut digest_frame: Option<DecryptedFrame> = None;
     let mut terminator_frame: Option<DecryptedFrame> = None;
-    let mut received = 0;
-    eprintln!("[DECRYPT] Collecting {} decrypted frames", frame_count);
-
-    while received < frame_count {
-        match out_rx.recv() {
-            Ok(Ok(frame)) => {
-                received += 1;
-                eprintln!("[DECRYPT] Received frame type {:?}, index {}", frame.frame_type, frame.frame_index);
-                // Decryption
-                println!("{}", &frame.stage_times.summary());
-                stage_times.merge(&frame.stage_times);
-
-                match frame.frame_type {
-                    FrameType::Data => data_frames.push(frame),
-                    FrameType::Digest => {
-                        if digest_frame.is_some() {
-                            return Err(SegmentWorkerError::InvalidSegment("Multiple digest frames".into()));
-                        }
-                        digest_frame = Some(frame);
-                    }
-                    FrameType::Terminator => {
-                        if terminator_frame.is_some() {
-                            return Err(SegmentWorkerError::InvalidSegment("Multiple terminator frames".into()));
-                        }
-                        terminator_frame = Some(frame);
-                    }
-                }
+    trace!(frame_count, "collecting decrypted frames");
+
+    for _ in 0..frame_count {
+        let frame = recv_dispatched_frame(frame_tx, out_rx, &mut dispatched)?;
+        trace!(frame_index = frame.frame_index, frame_type = ?frame.frame_type, "frame decrypted");
+        // Decryption
+        stage_times.merge(&frame.stage_times);
+
+        match frame.frame_type {
+            FrameType::Data => data_frames.push(frame),
+            // Authenticated like any other frame, then discarded
+            FrameType::Padding => padding_frames.push(frame),
+            FrameType::Digest | FrameType::Terminator if !layout.has(frame.frame_type) => {
+                debug!(frame_index = frame.frame_index, frame_type = ?frame.frame_type, "frame not in the stream's layout");
+                return Err(SegmentWorkerError::UnexpectedFrame { frame_type: frame.frame_type, frame_index: frame.frame_index });
             }
-            Ok(Err(e)) => {
-                eprintln!("[DECRYPT] Frame worker error: {:?}", e);
-                return Err(e.into());
+            FrameType::Digest => {
+                if digest_frame.is_some() {
+                    return Err(SegmentWorkerError::InvalidSegment("Multiple digest frames".into()));
+                }
+                digest_frame = Some(frame);
             }
-            Err(_) => {
-                eprintln!("[DECRYPT] Frame worker channel disconnected");
-                return Err(SegmentWorkerError::FrameWorkerError(FrameWorkerError::WorkerDisconnected));
+            FrameType::Terminator => {
+                if terminator_frame.is_some() {
+                    return Err(SegmentWorkerError::InvalidSegment("Multiple terminator frames".into()));
+                }
+                terminator_frame = Some(frame);
             }
         }
     }
 
-    // Validate frame counts, data_frames + digest_frame + terminator_frame
-    if (data_frames.len() + 2) != frame_count as usize {
-        eprintln!("[DECRYPT] Invalid frame count: data={} total={}", data_frames.len(), frame_count);
-        return Err(SegmentWorkerError::InvalidSegment("Invalid number of frames received".into()));
+    // Validate frame counts: every closing frame the layout promises is present
+    if layout.segment_digest && digest_frame.is_none() {
+        debug!(data = data_frames.len(), padding = padding_frames.len(), total = frame_count, "digest frame missing");
+        return Err(SegmentWorkerError::MissingDigestFrame);
+    }
+    if layout.terminator && terminator_frame.is_none() {
+        debug!(data = data_frames.len(), padding = padding_frames.len(), total = frame_count, "terminator frame missing");
+        return Err(SegmentWorkerError::MissingTerminatorFrame);
     }
 
     // 4️⃣ Sort decrypted DATA frames by frame_index
     data_frames.sort_unstable_by_key(|f| f.frame_index);
-    eprintln!("[DECRYPT] Sorted {} data frames", data_frames.len());
 
-    let data_frame_count = data_frames.len() as u32;
+    let data_frame_count = u32::try_from(data_frames.len())
+        .map_err(|_| FrameError::LengthOverflow { field: "data frame count", value: data_frames.len() as u64 })?;
     let segment_index = data_frames.first().map(|f| f.segment_index).unwrap_or(0);
 
-    // 5️⃣ Authenticated digest Logic
+    // Padding frames sit between the data and the digest, with no gaps
+    padding_frames.sort_unstable_by_key(|f| f.frame_index);
+    let body_frame_count = data_frame_count
+        .checked_add(padding_frames.len() as u32)
+        .ok_or(FrameError::LengthOverflow { field: "padding frame count", value: padding_frames.len() as u64 })?;
+    if padding_frames.iter().zip(data_frame_count..body_frame_count).any(|(f, i)| f.frame_index != i) {
+        debug!(data = data_frame_count, padding = padding_frames.len(), "padding frame index mismatch");
+        return Err(SegmentWorkerError::InvalidSegment("Padding frames must follow the data frames".into()));
+    }
+    for frame in &padding_frames {
+        // Padding is pure overhead: header, zeros and tag
+        counters.bytes_overhead += frame.wire.len() as u64;
+    }
+
+    // 5️⃣ Authenticated digest Logic (only when the layout has segment digests)
     // Digesting
     let start = Instant::now();
-    let digest_frame_data = digest_frame.ok_or(SegmentWorkerError::MissingDigestFrame)?;
-    if digest_frame_data.frame_index != data_frame_count {
-        eprintln!("[DECRYPT] Digest frame index mismatch: expected {}, got {}",
-                  data_frame_count, digest_frame_data.frame_index);
-        return Err(SegmentWorkerError::InvalidSegment("Invalid digest frame index".into()));
-    }
-    let digest_frame_payload = DigestFrame::decode(&digest_frame_data.plaintext)?;
-    eprintln!("[DECRYPT] Digest frame decoded, verifying segment {}", segment_index);
+    let mut verifier = match &digest_frame {
+        Some(digest_frame_data) => {
+            if digest_frame_data.frame_index != body_frame_count {
+                debug!(expected = body_frame_count, actual = digest_frame_data.frame_index, "digest frame index mismatch");
+                return Err(SegmentWorkerError::InvalidSegment("Invalid digest frame index".into()));
+            }
+            let digest_frame_payload = DigestFrame::decode(&digest_frame_data.plaintext)?;
 
-    let mut verifier = SegmentDigestVerifier::new(
-        digest_alg.clone(),
-        segment_index,
-        data_frame_count,
-        digest_frame_payload.digest,
-    );
+            let digest_alg = input.header.digest_algorithm().map_err(SegmentWorkerError::SegmentError)?;
+            if digest_frame_payload.algorithm != digest_alg {
+                debug!(header = ?digest_alg, frame = ?digest_frame_payload.algorithm, "digest algorithm mismatch");
+                return Err(SegmentWorkerError::InvalidSegment("Digest frame algorithm differs from the segment header".into()));
+            }
+            Some(SegmentDigestVerifier::for_alg(
+                digest_alg,
+                session_key,
+                segment_index,
+                data_frame_count,
+                digest_frame_payload.digest,
+            )?)
+        }
+        None => None,
+    };
 
     // 6️⃣ Update Verifier   
     for frame in &data_frames {
@@ -238,55 +362,91 @@ pub fn process_decrypt_segment_v2(
         counters.bytes_overhead += FrameHeader::LEN as u64;
         // Calculate len of plaintext (may be compressed)
         counters.bytes_compressed += frame.plaintext.len() as u64;
+        // Calculate len of ciphertext, as the encryptor does
+        counters.bytes_ciphertext += frame.ciphertext().len() as u64;
         //
-        verifier.update_frame(frame.frame_index, frame.ciphertext());
+        if let Some(verifier) = verifier.as_mut() {
+            verifier.update_frame(frame.frame_index, frame.ciphertext())?;
+        }
     }
     // Many frames for each segment data
     counters.frames_data = data_frame_count as u64;
 
     // 7️⃣ Cryptographic finalization
-    verifier.finalize()?; // may fail if digest mismatch
-    // One frame for each segment, the SegmentDigest of segment data
-    counters.add_digest(digest_frame_data.plaintext.len());
-
+    if let (Some(verifier), Some(digest_frame_data)) = (verifier, &digest_frame) {
+        verifier.finalize()?; // may fail if digest mismatch
+        // One frame for each segment, the SegmentDigest of segment data
+        counters.add_digest(digest_frame_data.wire.len());
+        trace!("digest verified");
+    }
     stage_times.add(Stage::Digest, start.elapsed());
-    eprintln!("[DECRYPT] Digest verified for segment {}", segment_index);
 
     // 8️⃣ Terminator
     let start = Instant::now();
-    let terminator_frame_data = terminator_frame.ok_or(SegmentWorkerError::MissingTerminatorFrame)?;
-    if terminator_frame_data.frame_index != data_frame_count + 1 {
-        eprintln!("[DECRYPT] Terminator frame index mismatch: expected {}, got {}",
-                  data_frame_count + 1, terminator_frame_data.frame_index);
-        return Err(SegmentWorkerError::InvalidSegment("Terminator frame should be the last frame of a segment".into()));
+    if let Some(terminator_frame_data) = &terminator_frame {
+        let expected = body_frame_count + u32::from(layout.segment_digest);
+        if terminator_frame_data.frame_index != expected {
+            debug!(expected, actual = terminator_frame_data.frame_index, "terminator frame index mismatch");
+            return Err(SegmentWorkerError::InvalidSegment("Terminator frame should be the last frame of a segment".into()));
+        }
+        // One frame for each segment, the SegmentTerminator
+        counters.add_terminator(terminator_frame_data.wire.len());
     }
-    // One frame for each segment, the SegmentTerminator
-    counters.add_terminator(terminator_frame_data.plaintext.len());
 
-    eprintln!("[DECRYPT] Terminator frame validated for segment {}", segment_index);
     stage_times.add(Stage::Validate, start.elapsed());
 
     // 9️⃣ collect plaintext
     // Writing / wiring
+    // Digest and terminator are verified: frames may now be released, in order and uncopied
     let start = Instant::now();
-    let mut plaintext_out = Vec::with_capacity(data_frames.iter().map(|f| f.plaintext.len()).sum());
-
-    for frame in data_frames {
-        // Append frame plaintext into one contiguous buffer 
-        plaintext_out.extend_from_slice(&frame.plaintext);
-    }
-    let bytes = Bytes::from(plaintext_out); // single Bytes
+    let frames: Vec<Bytes> = data_frames.into_iter().map(|frame| frame.plaintext).collect();
     let header = input.header;
     // We can compare the header.bytes_len against plaintext.len(), must be equal
     // header.bytes_len == plaintext.len() as u32
     stage_times.add(Stage::Write, start.elapsed());
 
     // 🔟 Return decrypted segment
-    eprintln!("[DECRYPT] Returning decrypted segment {}", segment_index);
+    trace!(frames = frames.len(), "segment decrypted");
     Ok(DecryptedSegment {
         header,
-        bytes, // single Bytes
+        frames,
         counters,
         stage_times,
     })
 }
+
+/// Next response for one of the `dispatched` frames.
+///
+/// Responses for frames this segment did not dispatch (left over from a
+/// segment that failed half-way) are dropped; untagged errors are assumed
+/// to be ours. Fails instead of blocking once the frame workers are gone.
+fn recv_dispatched_frame(
+    frame_tx: &impl FrameDispatch<DecryptFrameInput>,
+    out_rx: &Receiver<Result<DecryptedFrame, FrameWorkerError>>,
+    dispatched: &mut HashMap<(u32, u32), usize>,
+) -> Result<DecryptedFrame, SegmentWorkerError> {
+    loop {
+        let response = recv_frame(out_rx, frame_tx).map_err(|e| {
+            debug!("frame worker channel disconnected");
+            SegmentWorkerError::FrameWorkerError(e)
+        })?;
+        match response {
+            Ok(frame) => match dispatched.get_mut(&(frame.segment_index, frame.frame_index)) {
+                Some(pending) if *pending > 0 => {
+                    *pending -= 1;
+                    return Ok(frame);
+                }
+                _ => debug!(stray_segment = frame.segment_index, frame_index = frame.frame_index, "dropping frame not dispatched by this segment"),
+            },
+            Err(e) => match e.frame_tag() {
+                Some(tag) if dispatched.get(&tag).is_none_or(|pending| *pending == 0) => {
+                    debug!(error = %e, "dropping frame error not dispatched by this segment");
+                }
+                _ => {
+                    debug!(error = ?e, "frame worker error");
+                    return Err(e.into());
+                }
+            },
+        }
+    }
+}
@@ -2,21 +2,30 @@
 
 use std::time::Instant;
 use bytes::Bytes;
-use crossbeam::channel::{Receiver, Sender, bounded, unbounded};
+use crossbeam::channel::{Receiver, Sender};
+use tracing::{debug, trace};
 
 use crate::{
-    crypto::{DigestAlg, DigestFrame, SegmentDigestBuilder}, 
+    constants::flags,
+    headers::FrameLayout,
+    crypto::{DigestAlg, DigestFrame, KEY_LEN_32, SegmentDigestBuilder, TAG_LEN, check_frame_budget}, 
+    recovery::Direction,
     stream_v2::{
-        frame_worker::{EncryptedFrame, FrameInput, FrameWorkerError, encrypt::EncryptFrameWorker},
-        framing::{FrameHeader, types::FrameType}, segment_worker::SegmentWorkerError, segmenting::{SegmentHeader, types::SegmentFlags},
-    }, telemetry::{Stage, StageTimes, counters::TelemetryCounters}
+        frame_worker::{EncryptFramePool, EncryptFramePoolHandle, EncryptedFrame, FrameDispatch, FrameInput, FrameWorkerError, recv_frame},
+        framing::{FrameHeader, types::{FrameType, PADDING_FRAME_OVERHEAD}}, segment_worker::SegmentWorkerError,
+        segmenting::{SegmentHeader, types::{SegmentFlags, len_to_u32}}, sink::StageSink,
+    }, telemetry::{SegmentRecord, SegmentRecorder, Stage, StageTimes, counters::TelemetryCounters}
 };
-use super::types::{EncryptSegmentInput, EncryptedSegment};
+use super::types::{EncryptContext, EncryptSegmentInput, EncryptedSegment, SegmentWorkerHandle, catch_segment_panic, frame_ranges};
 
 pub struct EncryptSegmentWorker {
     pub crypto: crate::stream_v2::segment_worker::EncryptContext,
     // Production requirement: Access to the background logger
     pub log_manager: std::sync::Arc<crate::recovery::persist::AsyncLogManager>,
+    /// Shared frame workers; `None` starts a private pool in `run_v2`.
+    frames: Option<EncryptFramePoolHandle>,
+    /// Per-segment timing sink and this worker's id in the pool.
+    recorder: Option<(std::sync::Arc<SegmentRecorder>, usize)>,
 }
 
 impl EncryptSegmentWorker {
@@ -24,7 +33,19 @@ impl EncryptSegmentWorker {
         crypto: crate::stream_v2::segment_worker::EncryptContext,
         log_manager: std::sync::Arc<crate::recovery::persist::AsyncLogManager>,
     ) -> Self {
-        Self { crypto, log_manager }
+        Self { crypto, log_manager, frames: None, recorder: None }
+    }
+
+    /// Encrypt frames on a pool shared with other segment workers.
+    pub fn with_frame_pool(mut self, frames: EncryptFramePoolHandle) -> Self {
+        self.frames = Some(frames);
+        self
+    }
+
+    /// Report every encrypted segment to `recorder` as worker `worker_id`.
+    pub fn with_recorder(mut self, recorder: std::sync::Arc<SegmentRecorder>, worker_id: usize) -> Self {
+        self.recorder = Some((recorder, worker_id));
+        self
     }
 
     /// Run loop: consumes plaintext segments, emits encrypted segments.
@@ -34,73 +55,179 @@ impl EncryptSegmentWorker {
     /// [ Digest frame ]
     /// [ Terminator frame ]
     /// Run encryption loop - processes plaintext segments and outputs encrypted segments
+    ///
+    /// The returned handle reports whether the frame pool started and joins
+    /// the worker thread once `rx` is closed.
     pub fn run_v2(
         self,
         rx: Receiver<EncryptSegmentInput>,
         tx: Sender<Result<EncryptedSegment, SegmentWorkerError>>,
-    ) {
-        let crypto = self.crypto.clone();
+    ) -> SegmentWorkerHandle {
+        self.run_v2_into(rx, tx)
+    }
 
-        std::thread::spawn(move || {
-            // Spawn frame workers
-            let worker_count = crypto.base.profile.cpu_workers();
-            let digest_alg = crypto.base.digest_alg;
-            let frame_size = crypto.base.frame_size;
+    /// [`run_v2`](Self::run_v2) delivering to any [`StageSink`].
+    pub fn run_v2_into<S>(self, rx: Receiver<EncryptSegmentInput>, mut tx: S) -> SegmentWorkerHandle
+    where
+        S: StageSink<EncryptedSegment, SegmentWorkerError> + 'static,
+    {
+        let crypto = self.crypto.clone();
+        let frames = self.frames;
+        let log_manager = self.log_manager;
+        let recorder = self.recorder;
 
-            let (frame_tx, frame_rx) = bounded::<FrameInput>(worker_count * 4);
-            let (out_tx, out_rx) = unbounded::<Result<EncryptedFrame, FrameWorkerError>>();
+        SegmentWorkerHandle::spawn(move |ready| {
+            let header_crc = crypto.header.flags & flags::SEGMENT_HEADER_CRC != 0;
 
-            for _ in 0..worker_count {
-                let fw = EncryptFrameWorker::new(crypto.header.clone(), &crypto.base.session_key)
-                    .expect("EncryptFrameWorker pool init failed");
-                fw.run(frame_rx.clone(), out_tx.clone());
-            }
-            drop(frame_rx);
-            drop(out_tx);
+            // Frame workers: the shared pool, or a private one that lives as long as this connection
+            let (frame_tx, out_rx) = match frames {
+                Some(frames) => frames.connect(),
+                None => {
+                    let worker_count = crypto.base.profile.cpu_workers();
+                    match EncryptFramePool::encrypt_with_extensions(&crypto.header, &crypto.extensions, crypto.base.session_key.as_slice(), worker_count) {
+                        Ok(pool) => pool.handle().connect(),
+                        Err(e) => {
+                            let _ = ready.send(Err(e.into()));
+                            return;
+                        }
+                    }
+                }
+            };
+            // Started: later failures are per segment, through `tx`
+            let _ = ready.send(Ok(()));
+            drop(ready);
 
             while let Ok(segment) = rx.recv() {
-                eprintln!("[WORKER] processing segment {}", segment.segment_index);
-                let result = process_encrypt_segment_2(
-                    &segment,
-                    frame_size,
-                    digest_alg,
-                    &frame_tx,
-                    &out_rx,
-                );
+                let span = tracing::debug_span!("segment", segment_index = segment.segment_index);
+                let _enter = span.enter();
+                let start = Instant::now();
+                // A panic fails this segment instead of silently closing `tx`
+                let mut result = catch_segment_panic(|| {
+                    process_encrypt_segment_with_context(&segment, &crypto, &frame_tx, &out_rx)
+                });
+                if header_crc {
+                    if let Ok(encrypted) = &mut result {
+                        encrypted.header = encrypted.header.with_header_crc();
+                    }
+                }
+                if let Ok(encrypted) = &result {
+                    if let Some((recorder, worker_id)) = &recorder {
+                        recorder.record(SegmentRecord {
+                            segment_index: encrypted.header.segment_index,
+                            wire_len: encrypted.wire.len() as u64,
+                            duration: start.elapsed(),
+                            worker_id: *worker_id,
+                        });
+                    }
+                    // A failed append resurfaces when the pipeline closes the log
+                    if let Err(e) = log_manager.log_frames(Direction::Encrypt, &encrypted.wire) {
+                        debug!(error = %e, "audit log append failed");
+                    }
+                }
 
                 // Send result (Ok or Err) - let caller decide how to handle errors
-                if tx.send(result).is_err() {
-                    eprintln!("[WORKER] tx send failed, receiver gone");
+                if !tx.send(result) {
+                    debug!("segment result receiver gone, encrypt worker exiting");
                     // Receiver dropped, exit cleanly
                     return;
                 }
             }
-            eprintln!("[WORKER] rx closed, exiting loop");
             drop(frame_tx);
             drop(tx); // critical: close output channel
-            eprintln!("[WORKER] dropped tx, worker exiting");
-        });
+            debug!("segment input closed, encrypt worker exiting");
+        })
     }
 }
 
 /// Process a single plaintext segment into encrypted wire format
+///
+/// Unkeyed digests only: `Blake3Keyed` fails with `DigestError::KeyRequired`,
+/// see [`process_encrypt_segment_with_key`].
 pub fn process_encrypt_segment_2(
     input: &EncryptSegmentInput,
     frame_size: usize,
     digest_alg: DigestAlg,
-    frame_tx: &Sender<FrameInput>,
+    frame_tx: &impl FrameDispatch<FrameInput>,
+    out_rx: &Receiver<Result<EncryptedFrame, FrameWorkerError>>,
+) -> Result<EncryptedSegment, SegmentWorkerError> {
+    process_encrypt_segment_with_key(input, frame_size, digest_alg, None, frame_tx, out_rx)
+}
+
+/// [`process_encrypt_segment_2`] with the session key, which keyed digests
+/// (`DigestAlg::Blake3Keyed`) are bound to.
+pub fn process_encrypt_segment_with_key(
+    input: &EncryptSegmentInput,
+    frame_size: usize,
+    digest_alg: DigestAlg,
+    session_key: Option<&[u8; KEY_LEN_32]>,
+    frame_tx: &impl FrameDispatch<FrameInput>,
+    out_rx: &Receiver<Result<EncryptedFrame, FrameWorkerError>>,
+) -> Result<EncryptedSegment, SegmentWorkerError> {
+    process_encrypt_segment_padded(input, frame_size, digest_alg, session_key, None, frame_tx, out_rx)
+}
+
+/// [`process_encrypt_segment_with_key`], padding the segment wire to exactly
+/// `pad_to` bytes (see `EncryptParams::pad_segments_to`).
+///
+/// Padding frames hold zeros and sit between the data frames and the digest
+/// frame, which takes the next free `frame_index`. A segment that cannot reach
+/// `pad_to` exactly fails with `SegmentWorkerError::InvalidSegment`.
+pub fn process_encrypt_segment_padded(
+    input: &EncryptSegmentInput,
+    frame_size: usize,
+    digest_alg: DigestAlg,
+    session_key: Option<&[u8; KEY_LEN_32]>,
+    pad_to: Option<usize>,
+    frame_tx: &impl FrameDispatch<FrameInput>,
+    out_rx: &Receiver<Result<EncryptedFrame, FrameWorkerError>>,
+) -> Result<EncryptedSegment, SegmentWorkerError> {
+    encrypt_segment(input, frame_size, digest_alg, session_key, pad_to, FrameLayout::LEGACY, frame_tx, out_rx)
+}
+
+/// Encrypt one segment with every setting taken from `crypto`: frame size,
+/// digest algorithm and key, padding, and the closing frames the stream
+/// header declares (`HeaderV1::frame_layout`).
+pub fn process_encrypt_segment_with_context(
+    input: &EncryptSegmentInput,
+    crypto: &EncryptContext,
+    frame_tx: &impl FrameDispatch<FrameInput>,
+    out_rx: &Receiver<Result<EncryptedFrame, FrameWorkerError>>,
+) -> Result<EncryptedSegment, SegmentWorkerError> {
+    encrypt_segment(
+        input,
+        crypto.base.frame_size,
+        crypto.base.digest_alg,
+        Some(&*crypto.base.session_key),
+        crypto.pad_to,
+        crypto.header.frame_layout(),
+        frame_tx,
+        out_rx,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn encrypt_segment(
+    input: &EncryptSegmentInput,
+    frame_size: usize,
+    digest_alg: DigestAlg,
+    session_key: Option<&[u8; KEY_LEN_32]>,
+    pad_to: Option<usize>,
+    layout: FrameLayout,
+    frame_tx: &impl FrameDispatch<FrameInput>,
     out_rx: &Receiver<Result<EncryptedFrame, FrameWorkerError>>,
 ) -> Result<EncryptedSegment, SegmentWorkerError> {
     let mut counters = TelemetryCounters::default();
     let mut stage_times = StageTimes::default();
 
-    eprintln!("[ENCRYPT] Entering process_encrypt_segment_v2 for segment {}", input.segment_index);
+    trace!(len = input.bytes.len(), "encrypting segment");
 
     // Validation
     let start = Instant::now();
     // ✅ Empty final segment case
     if input.bytes.is_empty() && input.flags.contains(SegmentFlags::FINAL_SEGMENT) {
-        eprintln!("[ENCRYPT] Empty FINAL_SEGMENT detected at index {}", input.segment_index);
+        debug!("empty final segment");
+        // Its header is still written
+        counters.add_header(SegmentHeader::LEN);
         let header = SegmentHeader::new(
             &Bytes::new(),
             input.segment_index,
@@ -121,58 +248,96 @@ pub fn process_encrypt_segment_2(
 
     // 1️⃣ Split plaintext into frame-sized chunks
     let bytes_len: usize = input.bytes.len();
-    let frame_count: usize = (bytes_len + frame_size - 1) / frame_size;
+    let ranges = frame_ranges(bytes_len, frame_size);
+    let frame_count: usize = ranges.len();
     if frame_count == 0 {
         return Err(SegmentWorkerError::InvalidSegment("Empty segment".into()));
     }
+    let padding = match pad_to {
+        Some(target) => {
+            padding_frame_lens(target, unpadded_wire_len(bytes_len, frame_count, digest_alg, layout), frame_size)?
+        }
+        None => Vec::new(),
+    };
+    // Data frames, padding frames, then digest and terminator as the layout has them
+    check_frame_budget((frame_count + padding.len()) as u64 + u64::from(layout.closing_frames()))?;
+    let wire_bytes_len = len_to_u32("bytes_len", bytes_len).map_err(SegmentWorkerError::SegmentError)?;
+    let data_frame_count = len_to_u32("frame_count", frame_count).map_err(SegmentWorkerError::SegmentError)?;
+    let body_frame_count = len_to_u32("frame_count", frame_count + padding.len()).map_err(SegmentWorkerError::SegmentError)?;
+    // Digest frame follows the data and padding frames, then the terminator
+    let digest_index = body_frame_count;
+    let terminator_index = digest_index
+        .checked_add(u32::from(layout.segment_digest))
+        .ok_or_else(|| SegmentWorkerError::InvalidSegment("frame index overflow".into()))?;
     stage_times.add(Stage::Validate, start.elapsed());
 
     // 2️⃣ Dispatch plaintext frames for parallel encryption
-    // Read / chunking
+    // Read / chunking: frames are zero-copy views into the segment buffer
     let start_encrypt = Instant::now();
-    for (frame_index, chunk) in input.bytes.chunks(frame_size).enumerate() {
-        eprintln!("[ENCRYPT] Chunking frames from bytes, len={}", input.bytes.len());
-        frame_tx.send(FrameInput {
+    for (frame_index, range) in (0..data_frame_count).zip(ranges) {
+        frame_tx.dispatch(FrameInput {
             segment_index: input.segment_index,
-            frame_index: frame_index as u32,
+            frame_index,
             frame_type: FrameType::Data,
-            plaintext: Bytes::copy_from_slice(chunk),
-        })
-        .map_err(|_| SegmentWorkerError::FrameWorkerError(FrameWorkerError::WorkerDisconnected))?;
+            plaintext: input.bytes.slice(range),
+            segment_flags: input.flags,
+        })?;
+    }
+    // Zero-filled padding, sliced from one buffer
+    let zeros = Bytes::from(vec![0u8; padding.iter().copied().max().unwrap_or(0)]);
+    for (frame_index, &len) in (data_frame_count..body_frame_count).zip(&padding) {
+        frame_tx.dispatch(FrameInput {
+            segment_index: input.segment_index,
+            frame_index,
+            frame_type: FrameType::Padding,
+            plaintext: zeros.slice(..len),
+            segment_flags: input.flags,
+        })?;
     }
     stage_times.add(Stage::Read, start_encrypt.elapsed());
 
-    // 3️⃣ Collect encrypted frames
-    let mut data_frames = Vec::with_capacity(frame_count);
+    // 3️⃣ Collect encrypted frames, placed by frame_index
+    let body_frames = frame_count + padding.len();
+    let mut slots: Vec<Option<EncryptedFrame>> = (0..body_frames).map(|_| None).collect();
     let mut data_wire_len = 0;
-    let mut received = 0;
-    eprintln!("[ENCRYPT] Collecting {} encrypted frames", frame_count);
-
-    while received < frame_count {
-        match out_rx.recv() {
-            Ok(Ok(frame)) => {
-                received += 1;
-                eprintln!("[ENCRYPT] Received frame type {:?}, index {}", frame.frame_type, frame.frame_index);
-                // Encryption
-                println!("{}", &frame.stage_times.summary());
-                stage_times.merge(&frame.stage_times);
-
-                data_frames.push(frame);
+    trace!(frame_count, padding_frames = padding.len(), "collecting encrypted frames");
+
+    for _ in 0..body_frames {
+        let frame = recv_segment_frame(frame_tx, out_rx, input.segment_index)?;
+        trace!(frame_index = frame.frame_index, "frame encrypted");
+        let slot = match frame.frame_type {
+            FrameType::Data if frame.frame_index < data_frame_count => {
+                slots.get_mut(frame.frame_index as usize).filter(|slot| slot.is_none())
             }
-            Ok(Err(e)) => return Err(e.into()),
-            Err(_) => return Err(SegmentWorkerError::FrameWorkerError(FrameWorkerError::WorkerDisconnected)),
-        }
-    }
-    if data_frames.len() != frame_count {
-        return Err(SegmentWorkerError::InvalidSegment("Invalid number of frames received".into()));
+            FrameType::Padding if frame.frame_index >= data_frame_count => {
+                slots.get_mut(frame.frame_index as usize).filter(|slot| slot.is_none())
+            }
+            _ => None,
+        };
+        let Some(slot) = slot else {
+            return Err(SegmentWorkerError::InvalidSegment(format!(
+                "unexpected {:?} frame {} while collecting data frames",
+                frame.frame_type, frame.frame_index
+            )));
+        };
+        // Encryption
+        stage_times.merge(&frame.stage_times);
+        *slot = Some(frame);
     }
-    data_frames.sort_unstable_by_key(|f| f.frame_index);
-    eprintln!("[ENCRYPT] Sorted {} data frames", data_frames.len());
+    let mut frames = slots.into_iter().flatten();
+    let data_frames: Vec<EncryptedFrame> = frames.by_ref().take(frame_count).collect();
+    let padding_frames: Vec<EncryptedFrame> = frames.collect();
+    let padding_wire_len: usize = padding_frames.iter().map(|frame| frame.wire.len()).sum();
+    // Padding is pure overhead: header, zeros and tag
+    counters.bytes_overhead += padding_wire_len as u64;
 
-    // 4️⃣ Initialize digest calculator
+    // 4️⃣ Initialize digest calculator (only when the layout has segment digests)
     // Digesting
     let start = Instant::now();
-    let mut digest_builder = SegmentDigestBuilder::new(digest_alg, input.segment_index, frame_count as u32);
+    let mut digest_builder = layout
+        .segment_digest
+        .then(|| SegmentDigestBuilder::for_alg(digest_alg, session_key, input.segment_index, data_frame_count))
+        .transpose()?;
 
     for frame in &data_frames {
         data_wire_len += frame.wire.len();
@@ -182,71 +347,85 @@ pub fn process_encrypt_segment_2(
         // Calculate len of ciphertext
         counters.bytes_ciphertext += frame.ciphertext().len() as u64;
 
-        digest_builder.update_frame(frame.frame_index, frame.ciphertext());
+        if let Some(builder) = digest_builder.as_mut() {
+            builder.update_frame(frame.frame_index, frame.ciphertext())?;
+        }
     }
     // Many frames for each segment data
     counters.frames_data = frame_count as u64;
 
     // 5️⃣ Digest frame
-    let digest = digest_builder.finalize();
-    let digest_payload = Bytes::from(DigestFrame::new(digest_alg, digest).encode());
-    frame_tx.send(FrameInput {
-        segment_index: input.segment_index,
-        frame_index: frame_count as u32,
-        frame_type: FrameType::Digest,
-        plaintext: digest_payload,
-    }).map_err(|_| SegmentWorkerError::FrameWorkerError(FrameWorkerError::WorkerDisconnected))?;
-    
-    let digest_frame_result = out_rx.recv()
-        .map_err(|e| SegmentWorkerError::StateError(e.to_string()))?;
-    let digest_frame = digest_frame_result?;
-    eprintln!("[ENCRYPT] Digest frame encoded, for segment {}", input.segment_index);
-    
-    counters.add_digest(digest_frame.ciphertext().len());
+    let digest_frame = match digest_builder {
+        Some(builder) => {
+            let digest_payload = Bytes::from(DigestFrame::new(digest_alg, builder.finalize()).encode());
+            frame_tx.dispatch(FrameInput {
+                segment_index: input.segment_index,
+                frame_index: digest_index,
+                frame_type: FrameType::Digest,
+                plaintext: digest_payload,
+                segment_flags: input.flags,
+            })?;
+
+            let frame = recv_control_frame(frame_tx, out_rx, input.segment_index, FrameType::Digest, digest_index)?;
+            trace!("digest frame encoded");
+            counters.add_digest(frame.wire.len());
+            Some(frame)
+        }
+        None => None,
+    };
     stage_times.add(Stage::Digest, start.elapsed());
 
     // 6️⃣ Terminator frame
     // Finalizing
     let start = Instant::now();
-    frame_tx.send(FrameInput {
-        segment_index: input.segment_index,
-        frame_index: frame_count as u32 + 1,
-        frame_type: FrameType::Terminator,
-        plaintext: Bytes::new(),
-    }).map_err(|_| SegmentWorkerError::FrameWorkerError(FrameWorkerError::WorkerDisconnected))?;
-    
-    let terminator_frame_result = out_rx.recv()
-        .map_err(|e| SegmentWorkerError::StateError(e.to_string()))?;
-    let terminator_frame = terminator_frame_result?;
-
-    eprintln!("[ENCRYPT] Terminator frame encoded for segment {}", input.segment_index);
-    counters.add_terminator(terminator_frame.ciphertext().len());
+    let terminator_frame = if layout.terminator {
+        frame_tx.dispatch(FrameInput {
+            segment_index: input.segment_index,
+            frame_index: terminator_index,
+            frame_type: FrameType::Terminator,
+            plaintext: Bytes::new(),
+            segment_flags: input.flags,
+        })?;
+
+        let frame = recv_control_frame(frame_tx, out_rx, input.segment_index, FrameType::Terminator, terminator_index)?;
+        trace!("terminator frame encoded");
+        counters.add_terminator(frame.wire.len());
+        Some(frame)
+    } else {
+        None
+    };
     stage_times.add(Stage::Validate, start.elapsed());
 
     // 7️⃣ Serialize frames
     // Writing / wiring
     let start = Instant::now();
-    let total_len = data_wire_len + digest_frame.wire.len() + terminator_frame.wire.len();
+    let closing: Vec<&EncryptedFrame> = digest_frame.iter().chain(&terminator_frame).collect();
+    let total_len = data_wire_len + padding_wire_len + closing.iter().map(|frame| frame.wire.len()).sum::<usize>();
     let mut wire_bytes = Vec::with_capacity(total_len);
 
-    for frame in data_frames {
+    for frame in data_frames.iter().chain(&padding_frames).chain(closing) {
         wire_bytes.extend_from_slice(&frame.wire);
     }
-    wire_bytes.extend_from_slice(&digest_frame.wire);
-    wire_bytes.extend_from_slice(&terminator_frame.wire);
+
+    if let Some(target) = pad_to.filter(|&target| target != wire_bytes.len()) {
+        return Err(SegmentWorkerError::InvalidSegment(format!(
+            "padded segment is {} wire bytes, expected {}", wire_bytes.len(), target
+        )));
+    }
 
     let wire = Bytes::from(wire_bytes);
-    let header = SegmentHeader::new(
+    let header = SegmentHeader::try_new(
         &wire,
         input.segment_index,
-        bytes_len as u32,
-        frame_count as u32,
+        wire_bytes_len,
+        body_frame_count,
         digest_alg as u16,
         input.flags,
-    );
+    )
+    .map_err(SegmentWorkerError::SegmentError)?;
     stage_times.add(Stage::Write, start.elapsed());
 
-    eprintln!("[ENCRYPT] Returning encrypted segment {}", input.segment_index);
+    trace!(wire_len = header.wire_len, "segment encrypted");
     Ok(EncryptedSegment {
         header,
         wire,
@@ -254,3 +433,80 @@ pub fn process_encrypt_segment_2(
         stage_times,
     })
 }
+
+/// Wire bytes of a segment without padding: data frames, then the digest
+/// frame and the (unsealed) terminator frame if `layout` has them.
+fn unpadded_wire_len(bytes_len: usize, frame_count: usize, digest_alg: DigestAlg, layout: FrameLayout) -> usize {
+    let sealed = FrameHeader::LEN + TAG_LEN;
+    // Digest payload: [ alg_id: u16 ][ digest_len: u16 ][ digest ]
+    let digest = if layout.segment_digest { sealed + 4 + digest_alg.digest_len() } else { 0 };
+    let terminator = if layout.terminator { FrameHeader::LEN } else { 0 };
+    bytes_len + frame_count * sealed + digest + terminator
+}
+
+/// Plaintext lengths of the padding frames that bring `unpadded` wire bytes
+/// to exactly `target`.
+///
+/// As few frames as `frame_size` allows, the zeros spread evenly over them.
+/// Each frame holds at least one zero: the AEAD refuses empty plaintext.
+fn padding_frame_lens(target: usize, unpadded: usize, frame_size: usize) -> Result<Vec<usize>, SegmentWorkerError> {
+    let gap = target.checked_sub(unpadded).ok_or_else(|| {
+        SegmentWorkerError::InvalidSegment(format!("segment needs {unpadded} wire bytes, above the padding target {target}"))
+    })?;
+    if gap == 0 {
+        return Ok(Vec::new());
+    }
+    let count = gap.div_ceil(frame_size + PADDING_FRAME_OVERHEAD);
+    let zeros = gap.checked_sub(count * PADDING_FRAME_OVERHEAD).filter(|&zeros| zeros >= count).ok_or_else(|| {
+        SegmentWorkerError::InvalidSegment(format!(
+            "segment is {gap} wire bytes short of the padding target {target}, less than a padding frame ({})",
+            PADDING_FRAME_OVERHEAD + 1
+        ))
+    })?;
+    let (base, extra) = (zeros / count, zeros % count);
+    Ok((0..count).map(|i| base + usize::from(i < extra)).collect())
+}
+
+/// Next frame response of `segment_index`.
+///
+/// Responses tagged with another segment (left over from a segment that
+/// failed half-way) are dropped; untagged errors are assumed to be ours.
+/// Fails instead of blocking once the frame workers are gone.
+fn recv_segment_frame(
+    frame_tx: &impl FrameDispatch<FrameInput>,
+    out_rx: &Receiver<Result<EncryptedFrame, FrameWorkerError>>,
+    segment_index: u32,
+) -> Result<EncryptedFrame, SegmentWorkerError> {
+    loop {
+        match recv_frame(out_rx, frame_tx)? {
+            Ok(frame) if frame.segment_index == segment_index => return Ok(frame),
+            Ok(frame) => {
+                debug!(stray_segment = frame.segment_index, frame_index = frame.frame_index, "dropping frame of another segment");
+            }
+            Err(e) => match e.frame_tag() {
+                Some((stray_segment, _)) if stray_segment != segment_index => {
+                    debug!(stray_segment, error = %e, "dropping frame error of another segment");
+                }
+                _ => return Err(e.into()),
+            },
+        }
+    }
+}
+
+/// Receive the digest or terminator frame, which must carry `frame_index`.
+fn recv_control_frame(
+    frame_tx: &impl FrameDispatch<FrameInput>,
+    out_rx: &Receiver<Result<EncryptedFrame, FrameWorkerError>>,
+    segment_index: u32,
+    frame_type: FrameType,
+    frame_index: u32,
+) -> Result<EncryptedFrame, SegmentWorkerError> {
+    let frame = recv_segment_frame(frame_tx, out_rx, segment_index)?;
+    if frame.frame_type != frame_type || frame.frame_index != frame_index {
+        return Err(SegmentWorkerError::InvalidSegment(format!(
+            "expected {:?} frame {}, got {:?} frame {}",
+            frame_type, frame_index, frame.frame_type, frame.frame_index
+        )));
+    }
+    Ok(frame)
+}
@@ -23,6 +23,8 @@ pub use types::{
     EncryptContext,
     DecryptContext,
     SegmentWorkerError,
+    SegmentHook,
+    SegmentWorkerHandle,
 };
 
 pub use encrypt::EncryptSegmentWorker;
@@ -1,10 +1,14 @@
 use std::fmt;
 use std::convert::{From};
+use std::ops::Range;
+use std::thread::JoinHandle;
 use bytes::Bytes;
+use crossbeam::channel::{Receiver, Sender, bounded};
+use zeroize::Zeroizing;
 
-use crate::crypto::{CryptoError, DigestAlg, DigestError, KEY_LEN_32};
+use crate::crypto::{CryptoError, DigestAlg, DigestError, KEY_LEN_32, ct_eq, derive_key_check};
 use crate::headers::types::HeaderV1;
-use crate::stream_v2::framing::{FrameError};
+use crate::stream_v2::framing::{FrameError, FrameType};
 use crate::stream_v2::parallelism::HybridParallelismProfile;
 use crate::stream_v2::segmenting::SegmentHeader;
 use crate::stream_v2::segmenting::types::{SegmentError, SegmentFlags};
@@ -25,18 +29,18 @@ pub const DEFAULT_FRAME_SIZE: Option<usize> = None; // Auto-calculate
 pub const MIN_FRAME_SIZE: usize = 4 * 1024;      // 4 KiB
 pub const MAX_FRAME_SIZE: usize = 64 * 1024;     // 64 KiB
 
-/// Frame size mapping table (precomputed for common segment sizes)
-pub const FRAME_SIZE_TABLE: &[(usize, usize)] = &[
-    // (segment_size, optimal_frame_size)
-    (16 * 1024,    4 * 1024),   // 16 KiB segment → 4 KiB frames (4 frames)
-    (32 * 1024,    8 * 1024),   // 32 KiB segment → 8 KiB frames (4 frames)
-    (64 * 1024,    16 * 1024),  // 64 KiB segment → 16 KiB frames (4 frames)
-    (128 * 1024,   16 * 1024),  // 128 KiB segment → 16 KiB frames (8 frames)
-    (256 * 1024,   16 * 1024),  // 256 KiB segment → 16 KiB frames (16 frames)
-    (1024 * 1024,  32 * 1024),  // 1 MiB segment → 32 KiB frames (32 frames)
-    (2048 * 1024,  64 * 1024),  // 2 MiB segment → 64 KiB frames (32 frames)
-    (4096 * 1024,  64 * 1024),  // 4 MiB segment → 64 KiB frames (64 frames)
-];
+/// Preferred frame size: one TLS record.
+pub const PREFERRED_FRAME_SIZE: usize = 16 * 1024;
+
+/// Fewest data frames a segment is split into, for frame-level parallelism.
+/// Segments smaller than `MIN_FRAMES_PER_SEGMENT * MIN_FRAME_SIZE` get as many
+/// `MIN_FRAME_SIZE` frames as they hold.
+pub const MIN_FRAMES_PER_SEGMENT: usize = 4;
+
+/// Most data frames a segment is split into (a `MAX_CHUNK_SIZE` segment in
+/// `MAX_FRAME_SIZE` frames). Sizing target only; the nonce budget is
+/// `crypto::MAX_FRAMES_PER_SEGMENT`.
+pub const MAX_FRAMES_PER_SEGMENT: usize = crate::constants::MAX_CHUNK_SIZE / MAX_FRAME_SIZE;
 
 /// `SegmentInput` is the “raw” form: just plaintext frames.
 /// Input from reader stage (plaintext)
@@ -59,6 +63,11 @@ pub struct EncryptedSegment {
     pub stage_times: StageTimes,
 }
 
+/// Called by a decrypt segment worker with each segment header before processing it.
+///
+/// Meant for tests and diagnostics, e.g. delaying one segment to force reordering.
+pub type SegmentHook = std::sync::Arc<dyn Fn(&SegmentHeader) + Send + Sync>;
+
 #[derive(Debug)]
 pub struct DecryptSegmentInput {
     pub header: SegmentHeader,
@@ -77,14 +86,38 @@ impl From<EncryptedSegment> for DecryptSegmentInput {
 }
 
 /// Output of decryption
+///
+/// Plaintext stays split per frame (in `frame_index` order) so a segment is
+/// never copied into one `bytes_len`-sized buffer; writers emit the frames
+/// back to back.
 #[derive(Debug, Clone)]
 pub struct DecryptedSegment {
     pub header: SegmentHeader,
-    pub bytes: Bytes, // plaintext frames
+    pub frames: Vec<Bytes>, // ordered plaintext frames
     pub counters: TelemetryCounters,
     pub stage_times: StageTimes,
 }
 
+impl DecryptedSegment {
+    /// Total plaintext length across all frames.
+    pub fn len(&self) -> usize {
+        self.frames.iter().map(Bytes::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.iter().all(Bytes::is_empty)
+    }
+
+    /// Contiguous plaintext. Zero-copy for zero or one frame, concatenates otherwise.
+    pub fn to_bytes(&self) -> Bytes {
+        match self.frames.as_slice() {
+            [] => Bytes::new(),
+            [single] => single.clone(),
+            frames => Bytes::from(frames.concat()),
+        }
+    }
+}
+
 /// Immutable crypto context shared across workers
 // #[derive(Debug, Clone)]
 // pub struct SegmentCryptoContext {
@@ -131,10 +164,14 @@ pub struct DecryptedSegment {
 //     }
 // }
 
-#[derive(Debug, Clone)]
+/// Shared per-stream crypto state.
+///
+/// The session key is wiped when the context (and every clone handed to a
+/// worker) is dropped; `Debug` never prints it.
+#[derive(Clone)]
 pub struct CryptoContextBase {
     pub profile: HybridParallelismProfile,
-    pub session_key: [u8; KEY_LEN_32],
+    pub session_key: Zeroizing<[u8; KEY_LEN_32]>,
     pub digest_alg: DigestAlg,
     pub segment_size: usize,
     pub frame_size: usize,
@@ -146,6 +183,7 @@ impl CryptoContextBase {
         session_key: &[u8],
         digest_alg: DigestAlg,
         segment_size: usize,
+        frame_size: Option<usize>,
     ) -> Result<Self, SegmentWorkerError> {
         if session_key.len() != KEY_LEN_32 {
             return Err(SegmentWorkerError::CryptoError(
@@ -153,10 +191,20 @@ impl CryptoContextBase {
             ));
         }
 
-        let mut arr = [0u8; KEY_LEN_32];
+        let mut arr = Zeroizing::new([0u8; KEY_LEN_32]);
         arr.copy_from_slice(session_key);
 
-        let frame_size = get_frame_size(segment_size);
+        // Explicit frame size must be one of `ALLOWED_FRAME_SIZES`; otherwise derive one
+        let frame_size = match frame_size {
+            Some(size) if is_allowed_frame_size(size) => size,
+            Some(size) => {
+                return Err(SegmentWorkerError::StateError(format!(
+                    "invalid frame size: {size}, must be one of {:?}",
+                    ALLOWED_FRAME_SIZES
+                )));
+            }
+            None => get_frame_size(segment_size),
+        };
 
         Ok(Self {
             profile,
@@ -168,10 +216,26 @@ impl CryptoContextBase {
     }
 }
 
+impl fmt::Debug for CryptoContextBase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CryptoContextBase")
+            .field("profile", &self.profile)
+            .field("session_key", &"<redacted>")
+            .field("digest_alg", &self.digest_alg)
+            .field("segment_size", &self.segment_size)
+            .field("frame_size", &self.frame_size)
+            .finish()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct EncryptContext {
     pub header: HeaderV1,
     pub base: CryptoContextBase,
+    /// Segment wire length every non-empty segment is padded to (see `EncryptParams::pad_segments_to`).
+    pub pad_to: Option<usize>,
+    /// Raw header extension block written after the header (empty if none).
+    pub extensions: Bytes,
 }
 
 impl EncryptContext {
@@ -180,11 +244,45 @@ impl EncryptContext {
         profile: HybridParallelismProfile,
         session_key: &[u8],
         digest_alg: DigestAlg,
+    ) -> Result<Self, SegmentWorkerError> {
+        Self::with_frame_size(header, profile, session_key, digest_alg, None)
+    }
+
+    /// Like [`EncryptContext::new`], with an explicit frame size
+    /// (`None` picks one with `optimal_frame_size`).
+    ///
+    /// Decrypt needs no counterpart: every frame header carries its own length.
+    ///
+    /// With `HAS_KEY_CHECK` set, the key check value of `session_key` is
+    /// written into the header.
+    pub fn with_frame_size(
+        mut header: HeaderV1,
+        profile: HybridParallelismProfile,
+        session_key: &[u8],
+        digest_alg: DigestAlg,
+        frame_size: Option<usize>,
     ) -> Result<Self, SegmentWorkerError> {
         // Validate segment size in HeaderV1
         let segment_size = header.chunk_size as usize;
-        let base = CryptoContextBase::new(profile, session_key, digest_alg, segment_size)?;
-        Ok(Self { header, base })
+        let base = CryptoContextBase::new(profile, session_key, digest_alg, segment_size, frame_size)?;
+        if header.key_check().is_some() {
+            header.set_key_check(derive_key_check(session_key).map_err(SegmentWorkerError::CryptoError)?);
+        }
+        Ok(Self { header, base, pad_to: None, extensions: Bytes::new() })
+    }
+
+    /// Pad every non-empty segment to `pad_to` wire bytes with padding frames.
+    pub fn with_padding(mut self, pad_to: Option<usize>) -> Self {
+        self.pad_to = pad_to;
+        self
+    }
+
+    /// Write `block` after the header (length recorded in `ext_len`) and bind
+    /// it into the AAD under `AAD_STRICT`. Encode it with `HeaderExtensions::encode`.
+    pub fn with_extensions(mut self, block: Bytes) -> Self {
+        self.header.set_ext_len(block.len() as u16);
+        self.extensions = block;
+        self
     }
 }
 
@@ -192,9 +290,15 @@ impl EncryptContext {
 pub struct DecryptContext {
     pub base: CryptoContextBase,
     pub header: HeaderV1,
+    /// Raw header extension block read after the header (empty if none).
+    pub extensions: Bytes,
 }
 
 impl DecryptContext {
+    /// Fails with `CryptoError::KeyConfirmationFailed` when the header carries
+    /// a key check value (`HAS_KEY_CHECK`) that `session_key` does not match.
+    /// Headers without one are accepted as before; a wrong key then surfaces
+    /// as a tag mismatch in the first segment.
     pub fn from_stream_header(
         header: HeaderV1,
         profile: HybridParallelismProfile,
@@ -202,8 +306,19 @@ impl DecryptContext {
         digest_alg: DigestAlg,
     ) -> Result<Self, SegmentWorkerError> {
         let segment_size = header.chunk_size as usize;
-        let base = CryptoContextBase::new(profile, session_key, digest_alg, segment_size)?;
-        Ok(Self { base, header })
+        let base = CryptoContextBase::new(profile, session_key, digest_alg, segment_size, None)?;
+        if let Some(expected) = header.key_check() {
+            if !ct_eq(&derive_key_check(session_key).map_err(SegmentWorkerError::CryptoError)?, &expected) {
+                return Err(SegmentWorkerError::CryptoError(CryptoError::KeyConfirmationFailed));
+            }
+        }
+        Ok(Self { base, header, extensions: Bytes::new() })
+    }
+
+    /// The extension block read with the header (`PayloadReader::extensions`).
+    pub fn with_extensions(mut self, block: Bytes) -> Self {
+        self.extensions = block;
+        self
     }
 }
 
@@ -215,6 +330,10 @@ pub enum SegmentWorkerError {
     CheckpointRestoreFailed(String),
     MissingDigestFrame,
     MissingTerminatorFrame,
+    /// A digest or terminator frame in a stream whose `FrameLayout` has none.
+    UnexpectedFrame { frame_type: FrameType, frame_index: u32 },
+    /// A segment or frame worker panicked; carries the panic message.
+    WorkerPanicked(String),
 
     FrameWorkerError(FrameWorkerError),
     SegmentError(SegmentError),
@@ -264,6 +383,10 @@ impl fmt::Display for SegmentWorkerError {
             SegmentWorkerError::CheckpointRestoreFailed(msg) => write!(f, "checkpoint restore failed: {}", msg),
             SegmentWorkerError::MissingDigestFrame => write!(f, "invalid segment: {}", "Missing mandatory digest frame"),
             SegmentWorkerError::MissingTerminatorFrame => write!(f, "invalid segment: {}", "Missing mandatory terminator frame"),
+            SegmentWorkerError::UnexpectedFrame { frame_type, frame_index } => write!(
+                f, "invalid segment: unexpected {:?} frame {} (not in the stream's frame layout)", frame_type, frame_index
+            ),
+            SegmentWorkerError::WorkerPanicked(msg) => write!(f, "worker panicked: {}", msg),
 
             SegmentWorkerError::FrameWorkerError(e) => write!(f, "frame worker error: {}", e),
             SegmentWorkerError::SegmentError(e) => write!(f, "segment error: {}", e),
@@ -274,7 +397,90 @@ impl fmt::Display for SegmentWorkerError {
     }
 }
 
-impl std::error::Error for SegmentWorkerError {}
+impl std::error::Error for SegmentWorkerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SegmentWorkerError::FrameWorkerError(e) => Some(e),
+            SegmentWorkerError::SegmentError(e) => Some(e),
+            SegmentWorkerError::DigestError(e) => Some(e),
+            SegmentWorkerError::FramingError(e) => Some(e),
+            SegmentWorkerError::CryptoError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Run one segment's processing, turning a panic into `WorkerPanicked`.
+pub(crate) fn catch_segment_panic<T>(
+    process: impl FnOnce() -> Result<T, SegmentWorkerError>,
+) -> Result<T, SegmentWorkerError> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(process)).unwrap_or_else(|payload| {
+        let msg = crate::utils::panic_message(&*payload);
+        tracing::debug!(panic = %msg, "segment worker panicked");
+        Err(SegmentWorkerError::WorkerPanicked(msg))
+    })
+}
+
+/// A running segment worker, returned by `run_v2`.
+///
+/// The worker reports once its frame pool is up ([`wait_ready`](Self::wait_ready)),
+/// and exits once its input channel closes or its output receiver is gone;
+/// [`join`](Self::join) then waits for the thread, releasing what it held
+/// (private frame pool, audit log handle).
+pub struct SegmentWorkerHandle {
+    thread: JoinHandle<()>,
+    ready: Receiver<Result<(), SegmentWorkerError>>,
+    started: bool,
+}
+
+impl SegmentWorkerHandle {
+    /// Run `worker` on its own thread, handing it the sender of the readiness signal.
+    pub(crate) fn spawn<F>(worker: F) -> Self
+    where
+        F: FnOnce(Sender<Result<(), SegmentWorkerError>>) + Send + 'static,
+    {
+        let (ready_tx, ready) = bounded(1);
+        let thread = std::thread::spawn(move || worker(ready_tx));
+        Self { thread, ready, started: false }
+    }
+
+    /// Block until the worker's frame pool is up.
+    ///
+    /// # Errors
+    /// - The frame pool error when the pool could not start; the worker has exited.
+    /// - `SegmentWorkerError::WorkerPanicked` when the thread died before reporting.
+    pub fn wait_ready(&mut self) -> Result<(), SegmentWorkerError> {
+        if self.started {
+            return Ok(());
+        }
+        match self.ready.recv() {
+            Ok(Ok(())) => {
+                self.started = true;
+                Ok(())
+            }
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err(SegmentWorkerError::WorkerPanicked("segment worker exited before starting".into())),
+        }
+    }
+
+    /// Wait for the worker thread to exit.
+    ///
+    /// Only returns once the worker's input channel is closed (or its output
+    /// receiver dropped). A startup failure not yet taken by
+    /// [`wait_ready`](Self::wait_ready) is returned here.
+    ///
+    /// # Errors
+    /// - `SegmentWorkerError::WorkerPanicked` when the thread panicked.
+    pub fn join(self) -> Result<(), SegmentWorkerError> {
+        self.thread
+            .join()
+            .map_err(|payload| SegmentWorkerError::WorkerPanicked(crate::utils::panic_message(&*payload)))?;
+        match self.ready.try_recv() {
+            Ok(Err(e)) => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
 
 impl From<DigestError> for SegmentWorkerError {
     fn from(e: DigestError) -> Self {
@@ -283,6 +489,15 @@ impl From<DigestError> for SegmentWorkerError {
 }
 impl From<FrameWorkerError> for SegmentWorkerError {
     fn from(e: FrameWorkerError) -> Self {
+        // A frame worker panic is surfaced as such, whichever frame hit it
+        if let FrameWorkerError::WorkerPanicked(msg) = e.untagged() {
+            return match e.frame_tag() {
+                Some((segment_index, frame_index)) => SegmentWorkerError::WorkerPanicked(format!(
+                    "segment {} frame {}: {}", segment_index, frame_index, msg
+                )),
+                None => SegmentWorkerError::WorkerPanicked(msg.clone()),
+            };
+        }
         SegmentWorkerError::FrameWorkerError(e)
     }
 }
@@ -298,36 +513,55 @@ impl From<CryptoError> for SegmentWorkerError {
 }
 
 
-/// Calculate optimal frame size for a given segment size
+/// Frame size for a given segment size, always one of `ALLOWED_FRAME_SIZES`.
+///
+/// - Among the sizes giving `MIN_FRAMES_PER_SEGMENT..=MAX_FRAMES_PER_SEGMENT`
+///   frames, `PREFERRED_FRAME_SIZE` if it qualifies, otherwise the qualifying
+///   size closest to it.
+/// - Segments too small for `MIN_FRAMES_PER_SEGMENT` frames use `MIN_FRAME_SIZE`;
+///   segments beyond `MAX_CHUNK_SIZE` use `MAX_FRAME_SIZE`.
+/// - The last frame of a segment may be short (see [`frame_ranges`]).
 pub fn optimal_frame_size(segment_size: usize) -> usize {
-
-    const MIN_FRAMES_PER_SEGMENT: usize = 4;  // Minimum parallelization
-    const _MAX_FRAMES_PER_SEGMENT: usize = 64; // Don't over-fragment
-    
-    // Calculate frame size to get reasonable frame count
-    let ideal_frame_size = segment_size / 16; // Target ~16 frames
-    
-    // Clamp to allowed range
-    let frame_size = ideal_frame_size
-        .max(MIN_FRAME_SIZE)
-        .min(MAX_FRAME_SIZE);
-    
-    // Ensure we get at least MIN_FRAMES_PER_SEGMENT
-    let frames_per_segment = segment_size / frame_size;
-    if frames_per_segment < MIN_FRAMES_PER_SEGMENT {
-        return segment_size / MIN_FRAMES_PER_SEGMENT;
+    let frames = |frame_size: usize| segment_size.div_ceil(frame_size);
+    let fitting = || {
+        ALLOWED_FRAME_SIZES
+            .iter()
+            .copied()
+            .filter(|&size| (MIN_FRAMES_PER_SEGMENT..=MAX_FRAMES_PER_SEGMENT).contains(&frames(size)))
+    };
+
+    if fitting().any(|size| size == PREFERRED_FRAME_SIZE) {
+        return PREFERRED_FRAME_SIZE;
     }
-    
-    frame_size
+    // Sizes are ascending: above the preferred size take the smallest fit, below it the largest
+    match (fitting().find(|&size| size > PREFERRED_FRAME_SIZE), fitting().rev().find(|&size| size < PREFERRED_FRAME_SIZE)) {
+        (Some(size), _) | (None, Some(size)) => size,
+        (None, None) if frames(MAX_FRAME_SIZE) > MAX_FRAMES_PER_SEGMENT => MAX_FRAME_SIZE,
+        (None, None) => MIN_FRAME_SIZE,
+    }
+}
+
+/// Byte ranges of the data frames of a `segment_len`-byte segment.
+///
+/// Every frame holds `frame_size` bytes except the last, which holds the
+/// remainder when `frame_size` does not divide `segment_len`. Empty for an
+/// empty segment.
+pub fn frame_ranges(segment_len: usize, frame_size: usize) -> impl ExactSizeIterator<Item = Range<usize>> {
+    let full = segment_len / frame_size;
+    let short = segment_len % frame_size;
+    let count = full + usize::from(short > 0);
+    (0..count).map(move |i| {
+        let offset = i * frame_size;
+        offset..(offset + frame_size).min(segment_len)
+    })
+}
+
+/// Whether `frame_size` may be requested explicitly
+pub fn is_allowed_frame_size(frame_size: usize) -> bool {
+    ALLOWED_FRAME_SIZES.contains(&frame_size)
 }
 
-// Auto-calculate optimal frame size
-// Calculate or validate frame size
-/// Get optimal frame size from lookup table
+/// Default frame size of a segment size (see [`optimal_frame_size`]).
 pub fn get_frame_size(segment_size: usize) -> usize {
-    FRAME_SIZE_TABLE
-        .iter()
-        .find(|(seg_size, _)| *seg_size == segment_size)
-        .map(|(_, frame_size)| *frame_size)
-        .unwrap_or_else(|| optimal_frame_size(segment_size))
+    optimal_frame_size(segment_size)
 }
\ No newline at end of file
@@ -1,13 +1,37 @@
 use byteorder::{LittleEndian, ByteOrder};
 
-use crate::stream_v2::segmenting::{SegmentHeader, types::{SegmentError, SegmentFlags, SegmentView}};
+use crate::stream_v2::segmenting::{SegmentHeader, SegmentIndexEntry, SegmentIndexMap, types::{SegmentError, SegmentFlags, SegmentView, u32_to_len}};
 
 
+/// Parse a segment header, dispatching on its `version` byte.
+///
+/// Unknown versions fail with `SegmentError::UnsupportedVersion`, unknown
+/// digest algorithm ids with `SegmentError::UnknownDigestAlg`. A v2 header is
+/// checked against its CRC before any field is interpreted
+/// (`SegmentError::HeaderChecksumMismatch`).
 #[inline]
 pub fn parse_segment_header(wire: &[u8]) -> Result<SegmentHeader, SegmentError> {
     if wire.len() < SegmentHeader::LEN {
         return Err(SegmentError::Truncated);
     }
+    match wire[SegmentHeader::VERSION_OFFSET] {
+        SegmentHeader::VERSION_1 => parse_segment_header_v1(wire),
+        SegmentHeader::VERSION_2 => {
+            let encoded: &[u8; SegmentHeader::LEN] = wire[..SegmentHeader::LEN].try_into().expect("length checked above");
+            let expected = SegmentHeader::header_crc(encoded);
+            let actual = encoded[SegmentHeader::HEADER_CRC_OFFSET];
+            if expected != actual {
+                return Err(SegmentError::HeaderChecksumMismatch { expected, actual });
+            }
+            // Same fields as v1; `reserved` keeps the CRC
+            parse_segment_header_v1(wire)
+        }
+        version => Err(SegmentError::UnsupportedVersion { version }),
+    }
+}
+
+/// Layout v1 (and v2, once its CRC is checked); `wire` holds at least `SegmentHeader::LEN` bytes.
+fn parse_segment_header_v1(wire: &[u8]) -> Result<SegmentHeader, SegmentError> {
 
     // --- fixed offsets ---
     let mut off = 0;
@@ -33,12 +57,15 @@ pub fn parse_segment_header(wire: &[u8]) -> Result<SegmentHeader, SegmentError>
     let flags_raw = LittleEndian::read_u16(&wire[off..off + 2]);
     off += 2;
 
-    let reserved = LittleEndian::read_u16(&wire[off..off + 2]);
+    let version = wire[off];
+    off += 1;
+
+    let reserved = wire[off];
 
     let flags = SegmentFlags::from_bits(flags_raw)
         .ok_or(SegmentError::InvalidFlags{raw: flags_raw})?;
 
-    Ok(SegmentHeader {
+    let header = SegmentHeader {
         segment_index,
         bytes_len,
         wire_len,
@@ -46,8 +73,12 @@ pub fn parse_segment_header(wire: &[u8]) -> Result<SegmentHeader, SegmentError>
         frame_count,
         digest_alg,
         flags,
+        version,
         reserved,
-    })
+    };
+    // Streams are self-describing: reject ids no verifier exists for before any frame is read
+    header.digest_algorithm()?;
+    Ok(header)
 }
 
 // ✅ **This is segmenting-only**
@@ -66,7 +97,7 @@ pub fn decode_segment_header(buf: &[u8]) -> Result<SegmentHeader, SegmentError>
 pub fn decode_segment(wire: &[u8]) -> Result<SegmentView<'_>, SegmentError> {
     let header = parse_segment_header(wire)?;
 
-    let expected_len = SegmentHeader::LEN + header.wire_len as usize;
+    let expected_len = header.total_len()?;
     if wire.len() != expected_len {
         return Err(SegmentError::LengthMismatch {
             expected: expected_len,
@@ -87,3 +118,43 @@ pub fn decode_segment(wire: &[u8]) -> Result<SegmentView<'_>, SegmentError> {
     // ✔ constant time
     // ✔ cache-friendly
 }
+
+/// Decode the payload of a trailing index segment (see `encode_segment_index`).
+///
+/// `payload_offset` is where the stream's first segment starts: the header
+/// plus its extension block.
+pub fn decode_segment_index(buf: &[u8], payload_offset: u64) -> Result<SegmentIndexMap, SegmentError> {
+    if buf.len() < 4 {
+        return Err(SegmentError::Truncated);
+    }
+    let count = u32_to_len("index count", LittleEndian::read_u32(&buf[..4]))?;
+    let expected = count
+        .checked_mul(SegmentIndexMap::ENTRY_LEN)
+        .and_then(|n| n.checked_add(4))
+        .ok_or_else(|| SegmentError::Malformed(format!("segment index count too large: {count}")))?;
+    if buf.len() != expected {
+        return Err(SegmentError::LengthMismatch { expected, actual: buf.len() });
+    }
+
+    let mut entries = Vec::with_capacity(count);
+    for raw in buf[4..].chunks_exact(SegmentIndexMap::ENTRY_LEN) {
+        let flags_raw = LittleEndian::read_u16(&raw[20..22]);
+        entries.push(SegmentIndexEntry {
+            segment_index: LittleEndian::read_u32(&raw[0..4]),
+            offset: LittleEndian::read_u64(&raw[4..12]),
+            wire_len: LittleEndian::read_u32(&raw[12..16]),
+            bytes_len: LittleEndian::read_u32(&raw[16..20]),
+            flags: SegmentFlags::from_bits(flags_raw).ok_or(SegmentError::InvalidFlags { raw: flags_raw })?,
+        });
+    }
+
+    SegmentIndexMap::from_entries(&entries, payload_offset)
+}
+
+/// Decode the stream footer; `None` when the tail is not an index footer.
+pub fn decode_index_footer(buf: &[u8]) -> Option<u64> {
+    if buf.len() != SegmentIndexMap::FOOTER_LEN || buf[..4] != SegmentIndexMap::FOOTER_MAGIC {
+        return None;
+    }
+    Some(LittleEndian::read_u64(&buf[4..]))
+}
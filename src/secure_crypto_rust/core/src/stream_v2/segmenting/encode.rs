@@ -1,29 +1,54 @@
 use byteorder::{LittleEndian, WriteBytesExt};
 use bytes::Bytes;
 
-use crate::stream_v2::segmenting::{SegmentHeader, types::SegmentError};
+use crate::headers::layout::{Endianness, FieldSpec, contiguous};
+use crate::stream_v2::segmenting::{SegmentHeader, SegmentIndexMap, types::SegmentError};
+
+/// Fields of an encoded `SegmentHeader` (`SegmentHeader::LEN` bytes), in wire order.
+pub fn layout() -> Vec<FieldSpec> {
+    use Endianness::*;
+    contiguous(&[
+        ("segment_index", 4, Little, "segment number, from 0"),
+        ("bytes_len", 4, Little, "payload bytes before encryption (compressed or stored)"),
+        ("wire_len", 4, Little, "encoded frame bytes following this header"),
+        ("wire_crc32", 4, Little, "CRC32 of the frame bytes"),
+        ("frame_count", 4, Little, "frames: data, digest and terminator"),
+        ("digest_alg", 2, Little, "segment digest algorithm id"),
+        ("flags", 2, Little, "SegmentFlags bitmask"),
+        ("version", 1, Little, "header layout, 0 = v1, 1 = v2"),
+        ("reserved", 1, Little, "v1: zero; v2: CRC-8 of bytes 0..26 with this byte zeroed"),
+    ])
+}
 
 /// Encode a segment record into canonical wire format.
 ///
-/// Layout:
+/// The layout follows `header.version`; unknown versions fail with
+/// `SegmentError::UnsupportedVersion`. Layout v1:
 ///
 /// ```text
 /// [ segment_index (4) ]
 /// [ bytes_len     (4) ]
 /// [ wire_len      (4) ]
 /// [ wire_crc32    (4) ]
-/// [ frame_count   (2) ]
+/// [ frame_count   (4) ]
 /// [ digest_alg    (2) ]
 /// [ flags         (2) ]
-/// [ reserved      (2) ]
+/// [ version       (1) ]
+/// [ reserved      (1) ]
 /// ```
+///
+/// Layout v2 is identical, with `reserved` replaced by the header CRC-8
+/// (computed here; `header.reserved` is ignored).
 pub fn encode_segment(
     header: &SegmentHeader,
     segment_wire: &Bytes,
 ) -> Result<Vec<u8>, SegmentError> {
-    let expected = SegmentHeader::LEN + header.wire_len as usize;
+    if !matches!(header.version, SegmentHeader::VERSION_1 | SegmentHeader::VERSION_2) {
+        return Err(SegmentError::UnsupportedVersion { version: header.version });
+    }
+    let expected = header.total_len()?;
 
-    if segment_wire.len() != header.wire_len as usize {
+    if segment_wire.len() != header.wire_len_usize()? {
         return Err(SegmentError::LengthMismatch {
             expected,
             actual: segment_wire.len(),
@@ -40,7 +65,13 @@ pub fn encode_segment(
     wire.write_u32::<LittleEndian>(header.frame_count).unwrap();
     wire.write_u16::<LittleEndian>(header.digest_alg).unwrap();
     wire.write_u16::<LittleEndian>(header.flags.bits()).unwrap();
-    wire.write_u16::<LittleEndian>(header.reserved).unwrap();
+    wire.push(header.version);
+    wire.push(header.reserved);
+    if header.version == SegmentHeader::VERSION_2 {
+        let encoded: &[u8; SegmentHeader::LEN] = wire[..].try_into().expect("header fully written");
+        let crc = SegmentHeader::header_crc(encoded);
+        wire[SegmentHeader::HEADER_CRC_OFFSET] = crc;
+    }
 
     // --- Body ---
     wire.extend_from_slice(segment_wire);
@@ -60,3 +91,42 @@ pub fn encode_segment(
     // * slice from another buffer
     // * Header + body are **logically separated**
 }
+
+/// Encode a segment index as the payload of the trailing index segment.
+///
+/// Layout (little endian):
+///
+/// ```text
+/// [ entry_count     (4) ]
+/// [ segment_index   (4) ] ┐
+/// [ offset          (8) ] │
+/// [ wire_len        (4) ] │ × entry_count
+/// [ bytes_len       (4) ] │
+/// [ flags           (2) ] ┘
+/// ```
+pub fn encode_segment_index(index: &SegmentIndexMap) -> Bytes {
+    let mut buf = Vec::with_capacity(4 + index.len() * SegmentIndexMap::ENTRY_LEN);
+    // Entries are appended one per segment, and segment indices are u32
+    buf.write_u32::<LittleEndian>(u32::try_from(index.len()).expect("segment count fits u32")).unwrap();
+    for entry in index.entries() {
+        buf.write_u32::<LittleEndian>(entry.segment_index).unwrap();
+        buf.write_u64::<LittleEndian>(entry.offset).unwrap();
+        buf.write_u32::<LittleEndian>(entry.wire_len).unwrap();
+        buf.write_u32::<LittleEndian>(entry.bytes_len).unwrap();
+        buf.write_u16::<LittleEndian>(entry.flags.bits()).unwrap();
+    }
+    Bytes::from(buf)
+}
+
+/// Encode the stream footer pointing at the index segment header.
+///
+/// ```text
+/// [ magic "RSIX" (4) ]
+/// [ offset       (8) ]
+/// ```
+pub fn encode_index_footer(offset: u64) -> [u8; SegmentIndexMap::FOOTER_LEN] {
+    let mut footer = [0u8; SegmentIndexMap::FOOTER_LEN];
+    footer[..4].copy_from_slice(&SegmentIndexMap::FOOTER_MAGIC);
+    footer[4..].copy_from_slice(&offset.to_le_bytes());
+    footer
+}
@@ -0,0 +1,134 @@
+// # 📂 `src/stream_v2/segmenting/index.rs`
+
+use crate::headers::HeaderV1;
+use crate::stream_v2::segmenting::{SegmentHeader, types::{SegmentError, SegmentFlags}};
+
+/// Location of one segment inside an encrypted stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SegmentIndexEntry {
+    pub segment_index: u32,
+    /// Absolute byte offset (stream header included) of the `SegmentHeader`.
+    pub offset: u64,
+    /// Encoded frame bytes following the segment header.
+    pub wire_len: u32,
+    /// Payload bytes carried by the segment (after compression, before encryption).
+    pub bytes_len: u32,
+    pub flags: SegmentFlags,
+}
+
+impl SegmentIndexEntry {
+    /// Segment header plus wire.
+    pub fn encoded_len(&self) -> u64 {
+        SegmentHeader::LEN as u64 + self.wire_len as u64
+    }
+}
+
+/// 🗺️ `segment_index` → byte offset map of a stream.
+///
+/// Entries are contiguous: entry `i` describes segment `i`. Built either by
+/// recording segments as they are written, or by scanning an existing stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentIndexMap {
+    entries: Vec<SegmentIndexEntry>,
+    next_offset: u64,
+}
+
+impl Default for SegmentIndexMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SegmentIndexMap {
+    /// Encoded entry: segment_index, offset, wire_len, bytes_len, flags.
+    pub const ENTRY_LEN: usize = 4 + 8 + 4 + 4 + 2;
+
+    /// Stream tail locating the index segment: magic + its absolute offset.
+    pub const FOOTER_LEN: usize = 4 + 8;
+    pub const FOOTER_MAGIC: [u8; 4] = *b"RSIX";
+
+    /// Empty map; the first segment is expected right after the stream header.
+    pub fn new() -> Self {
+        Self::starting_at(HeaderV1::LEN as u64)
+    }
+
+    /// Empty map whose first segment starts at `offset` (after a header extension block).
+    pub fn starting_at(offset: u64) -> Self {
+        Self { entries: Vec::new(), next_offset: offset }
+    }
+
+    /// Rebuild a map from decoded entries, checking they describe contiguous
+    /// segments starting at `payload_offset` (`HeaderV1::encoded_len`).
+    pub fn from_entries(entries: &[SegmentIndexEntry], payload_offset: u64) -> Result<Self, SegmentError> {
+        let mut index = Self::starting_at(payload_offset);
+        for entry in entries {
+            if entry.offset != index.next_offset {
+                return Err(SegmentError::Malformed(format!(
+                    "segment {} offset {} does not follow previous segment (expected {})",
+                    entry.segment_index, entry.offset, index.next_offset
+                )));
+            }
+            if index.is_complete() || entry.segment_index as usize != index.entries.len() {
+                return Err(SegmentError::Malformed(format!(
+                    "unexpected segment {} in index",
+                    entry.segment_index
+                )));
+            }
+            index.next_offset += entry.encoded_len();
+            index.entries.push(*entry);
+        }
+        Ok(index)
+    }
+
+    /// Record the next segment of the stream, in write order.
+    pub fn push(&mut self, header: &SegmentHeader) -> Result<(), SegmentError> {
+        if header.segment_index as usize != self.entries.len() {
+            return Err(SegmentError::Malformed(format!(
+                "segment index gap: expected {}, got {}",
+                self.entries.len(),
+                header.segment_index
+            )));
+        }
+        if self.is_complete() {
+            return Err(SegmentError::Malformed("segment after final segment".into()));
+        }
+
+        let entry = SegmentIndexEntry {
+            segment_index: header.segment_index,
+            offset: self.next_offset,
+            wire_len: header.wire_len,
+            bytes_len: header.bytes_len,
+            flags: header.flags,
+        };
+        self.next_offset += entry.encoded_len();
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    pub fn get(&self, segment_index: u32) -> Option<&SegmentIndexEntry> {
+        self.entries.get(segment_index as usize)
+    }
+
+    pub fn entries(&self) -> &[SegmentIndexEntry] {
+        &self.entries
+    }
+
+    /// Number of segments, final segment included.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Whether the final segment has been recorded.
+    pub fn is_complete(&self) -> bool {
+        self.entries.last().is_some_and(|e| e.flags.contains(SegmentFlags::FINAL_SEGMENT))
+    }
+
+    /// Offset just past the last recorded segment.
+    pub fn end_offset(&self) -> u64 {
+        self.next_offset
+    }
+}
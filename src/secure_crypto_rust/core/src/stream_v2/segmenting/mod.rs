@@ -14,14 +14,25 @@
 pub mod types;
 pub mod encode;
 pub mod decode;
+pub mod index;
 
 pub use types::{
     SegmentHeader,
+    SegmentLimits,
 };
 pub use encode::{
+    layout,
     encode_segment,
+    encode_segment_index,
+    encode_index_footer,
 };
 pub use decode::{
     decode_segment_header,
     decode_segment,
+    decode_segment_index,
+    decode_index_footer,
+};
+pub use index::{
+    SegmentIndexEntry,
+    SegmentIndexMap,
 };
\ No newline at end of file
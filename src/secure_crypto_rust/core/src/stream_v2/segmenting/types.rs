@@ -1,13 +1,43 @@
 use std::fmt;
 use bytes::Bytes;
 
-use crate::utils::{ChecksumAlg, compute_checksum};
+use crate::constants::{MAX_CHUNK_SIZE, flags};
+use crate::crypto::{DigestAlg, TAG_LEN};
+use crate::stream_v2::framing::{FrameHeader, MAX_FRAME_CIPHERTEXT_LEN};
+use crate::stream_v2::framing::types::FRAME_LEN_SLACK;
+use crate::stream_v2::segment_worker::types::MIN_FRAME_SIZE;
+use crate::utils::{Checksum, crc32, enum_name_or_hex};
 
 bitflags::bitflags! {
     /// ## 🚩 Segment flags (explicit, extensible)
+    ///
+    /// Stored as a little-endian `u16` at bytes 22..24 of the segment header.
+    /// Unknown bits are rejected on decode (`SegmentError::InvalidFlags`).
+    /// They are not covered by the wire CRC32 but, in streams with
+    /// `SEGMENT_FLAGS_AAD`, by the AAD of every frame of the segment, so a
+    /// flipped bit fails authentication.
+    ///
+    /// | Bit    | Flag            | Meaning                                         |
+    /// |--------|-----------------|-------------------------------------------------|
+    /// | `0x01` | `FINAL_SEGMENT` | empty end-of-stream marker                      |
+    /// | `0x02` | `COMPRESSED`    | payload compressed with the header codec        |
+    /// | `0x04` | `RESUMED`       | written after a resume                          |
+    /// | `0x08` | `UNCOMPRESSED`  | payload stored raw                              |
+    /// | `0x10` | `SEGMENT_INDEX` | trailing segment index (`HAS_SEGMENT_INDEX`)    |
+    /// | `0x20` | `CHECKSUM`      | plaintext trailer with CRC32 (`HAS_CRC32`)      |
+    /// | `0x40` | `TOTAL_LEN`     | plaintext trailer with length (`HAS_TOTAL_LEN`) |
+    /// | `0x80` | `RESERVED`      | reserved                                        |
+    /// | `0x100`| `STREAM_DIGEST` | plaintext trailer with BLAKE3 (`HAS_FINAL_DIGEST`) |
+    /// | `0x200`| `DICT_OVERRIDE` | payload compressed with its own dictionary (`SEGMENT_DICTS`) |
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub struct SegmentFlags: u16 {
-        /// Final segment of the stream
+        /// Final segment of the stream.
+        ///
+        /// Empty, so it has no frames and nothing keyed covers it: a stream
+        /// cut at a segment boundary and closed with a forged final segment
+        /// reads as a shorter stream. Truncation is only detected with a
+        /// plaintext trailer (`HAS_TOTAL_LEN`, `HAS_CRC32` or a whole-stream
+        /// digest), whose authenticated segment precedes this one.
         const FINAL_SEGMENT = 0b0000_0001;
 
         /// Segment contains compressed frames
@@ -16,14 +46,53 @@ bitflags::bitflags! {
         /// Segment written after resume
         const RESUMED = 0b0000_0100;
 
+        /// Segment payload stored raw: compression did not pay off,
+        /// the decrypt path must skip decompression
+        const UNCOMPRESSED = 0b0000_1000;
+
+        /// Trailing segment carrying the encoded `SegmentIndexMap`,
+        /// written after the final segment (header flag `HAS_SEGMENT_INDEX`)
+        const SEGMENT_INDEX = 0b0001_0000;
+
+        /// Plaintext trailer carrying the CRC32 of the whole plaintext,
+        /// written just before the final segment (header flag `HAS_CRC32`)
+        const CHECKSUM = 0b0010_0000;
+
+        /// Plaintext trailer carrying the total plaintext length
+        /// (header flag `HAS_TOTAL_LEN`); may be combined with `CHECKSUM`
+        const TOTAL_LEN = 0b0100_0000;
+
         /// Reserved for future use
         const RESERVED = 0b1000_0000;
+
+        /// Plaintext trailer carrying a BLAKE3 of the whole plaintext,
+        /// in place of per-segment digests (`FrameLayout::final_digest`)
+        const STREAM_DIGEST = 0b0001_0000_0000;
+
+        /// Payload compressed with the dictionary named by its first
+        /// [`DICT_ID_LEN`] bytes (u32 LE) instead of the stream's (header
+        /// flag `SEGMENT_DICTS`). The id is encrypted with the payload, so
+        /// it is authenticated and the segment header keeps its size
+        const DICT_OVERRIDE = 0b0010_0000_0000;
     }
 
     // > Using `bitflags` here is **intentional**:
     // > it prevents accidental semantic drift and gives us cheap validation.
 }
 
+/// Length of the dictionary id leading a `DICT_OVERRIDE` payload.
+pub const DICT_ID_LEN: usize = 4;
+
+impl fmt::Display for SegmentFlags {
+    /// `FINAL_SEGMENT | UNCOMPRESSED`, or `-` when no flag is set.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("-");
+        }
+        bitflags::parser::to_writer(self, f)
+    }
+}
+
 /// Segmetn type identifiers for the envelope.
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -40,7 +109,7 @@ pub struct SegmentHeader {
     /// Optional integrity check of the segment wire (0 if unused)
     pub wire_crc32: u32,
 
-    /// Number of frames in this segment (data + digest + terminator)
+    /// Number of body frames in this segment (data + padding)
     pub frame_count: u32,
 
     /// Digest algorithm used (binds verifier)
@@ -49,8 +118,11 @@ pub struct SegmentHeader {
     /// Segment-level flags (LAST, CHECKPOINT, etc.)
     pub flags: SegmentFlags, // ✅ NOT u16
 
-    /// Reserved for future use; must be zero
-    pub reserved: u16,
+    /// Wire layout of this header (see [`SegmentHeader::VERSION_1`])
+    pub version: u8,
+
+    /// v1: reserved, zero. v2: CRC-8 of the encoded header (see [`SegmentHeader::VERSION_2`])
+    pub reserved: u8,
 }
 
 impl SegmentHeader {
@@ -61,7 +133,28 @@ impl SegmentHeader {
         + 4                  // frame_count
         + 2                  // digest_alg
         + 2                  // flags
-        + 2;                 // reserved
+        + 1                  // version
+        + 1;                 // reserved
+
+    /// Byte offset of `version`; every layout keeps it here so decoders can dispatch.
+    pub const VERSION_OFFSET: usize = 24;
+
+    /// Original layout. The version byte was the low half of a zero `reserved`
+    /// field, so v1 is encoded as `0` and older streams decode unchanged.
+    pub const VERSION_1: u8 = 0;
+
+    /// v1 layout with a CRC-8 of the header itself in the last byte.
+    ///
+    /// Written for streams with `flags::SEGMENT_HEADER_CRC`; a corrupted field
+    /// fails in `decode_segment_header` instead of surfacing later as a frame
+    /// or digest error.
+    pub const VERSION_2: u8 = 1;
+
+    /// Layout written by [`SegmentHeader::new`].
+    pub const VERSION: u8 = Self::VERSION_1;
+
+    /// Byte offset of the v2 header CRC (the v1 `reserved` byte).
+    pub const HEADER_CRC_OFFSET: usize = 25;
 
     /// Construct a fully-validated SegmentHeader.
     ///
@@ -71,6 +164,9 @@ impl SegmentHeader {
     /// - freezes segment metadata
     ///
     /// Callers must NOT mutate fields afterward.
+    ///
+    /// # Panics
+    /// If `wire` is longer than `u32::MAX`; see [`SegmentHeader::try_new`].
     pub fn new(
         wire: &Bytes,
         segment_index: u32,
@@ -79,42 +175,111 @@ impl SegmentHeader {
         digest_alg: u16,
         flags: SegmentFlags,
     ) -> Self {
+        Self::try_new(wire, segment_index, bytes_len, frame_count, digest_alg, flags)
+            .expect("segment wire too large")
+    }
+
+    /// [`SegmentHeader::new`], failing with `LengthOverflow` when `wire` exceeds `u32::MAX` bytes.
+    pub fn try_new(
+        wire: &Bytes,
+        segment_index: u32,
+        bytes_len: u32,
+        frame_count: u32,
+        digest_alg: u16,
+        flags: SegmentFlags,
+    ) -> Result<Self, SegmentError> {
         // --- length ---
-        let wire_len = wire.len();
-        assert!(
-            wire_len <= u32::MAX as usize,
-            "segment wire too large"
-        );
+        let wire_len = len_to_u32("wire_len", wire.len())?;
 
         // --- CRC32 ---
-        let wire_crc32 = compute_checksum(wire, Some(ChecksumAlg::Crc32));
-        
-        SegmentHeader {
+        let wire_crc32 = crc32(wire);
+
+        Ok(SegmentHeader {
             segment_index,
-            wire_len: wire_len as u32,
-            bytes_len: bytes_len as u32,
-            wire_crc32: wire_crc32,
+            wire_len,
+            bytes_len,
+            wire_crc32,
             frame_count,
             digest_alg,
-            flags: flags,
-            reserved: 0u16,
+            flags,
+            version: Self::VERSION,
+            reserved: 0u8,
+        })
+    }
+
+    /// Switch to layout v2, whose encoding carries a CRC-8 of the header fields.
+    pub fn with_header_crc(mut self) -> Self {
+        self.version = Self::VERSION_2;
+        self
+    }
+
+    /// CRC-8 of an encoded header, computed with the CRC byte taken as zero.
+    pub fn header_crc(encoded: &[u8; Self::LEN]) -> u8 {
+        let mut bytes = *encoded;
+        bytes[Self::HEADER_CRC_OFFSET] = 0;
+        crc8(&bytes)
+    }
+
+    /// Fail with `HeaderChecksumMissing` when the stream requires header CRCs
+    /// (`flags::SEGMENT_HEADER_CRC`) and this header is not v2.
+    ///
+    /// Stops a header from dodging the check by flipping its version byte.
+    pub fn require_header_crc(&self, stream_flags: u16) -> Result<(), SegmentError> {
+        if stream_flags & flags::SEGMENT_HEADER_CRC != 0 && self.version != Self::VERSION_2 {
+            return Err(SegmentError::HeaderChecksumMissing { segment_index: self.segment_index, version: self.version });
         }
+        Ok(())
+    }
+
+    /// `wire_len` as an in-memory length.
+    pub fn wire_len_usize(&self) -> Result<usize, SegmentError> {
+        u32_to_len("wire_len", self.wire_len)
+    }
+
+    /// Segment header plus wire bytes, checked against `usize` overflow.
+    pub fn total_len(&self) -> Result<usize, SegmentError> {
+        checked_len_add("segment length", SegmentHeader::LEN, self.wire_len_usize()?)
     }
 
+    /// Check `wire` against this header: lengths, frame count and CRC32.
+    ///
+    /// Done once per segment, before any frame is parsed; an empty final
+    /// segment is checked like any other (`frame_count` and `bytes_len` must
+    /// be zero, its CRC32 that of no bytes).
     pub fn validate(&self, wire: &Bytes) -> Result<(), SegmentError> {
-        // --- CRC32 ---
-        let wire_crc32 = compute_checksum(wire, Some(ChecksumAlg::Crc32));
+        // --- Lengths ---
+        let wire_len = self.wire_len_usize()?;
+        if wire.len() != wire_len {
+            return Err(SegmentError::LengthMismatch { expected: wire_len, actual: wire.len() });
+        }
+        if (self.frame_count == 0) != wire.is_empty() {
+            return Err(SegmentError::FrameCountMismatch { frame_count: self.frame_count, wire_len: self.wire_len });
+        }
+        if wire.is_empty() && self.bytes_len != 0 {
+            return Err(SegmentError::Malformed(format!("empty segment declares {} payload bytes", self.bytes_len)));
+        }
 
-        if self.wire_crc32 != wire_crc32 {
+        // --- CRC32 ---
+        if !Checksum::Crc32(self.wire_crc32).verify(wire) {
             return Err(SegmentError::Malformed("Wire checksum failed".into()));
         }
         Ok(())
     }
+    /// The digest algorithm recorded in this header.
+    ///
+    /// Decryption verifies segment digests with this algorithm, whatever the
+    /// context was configured with; `UnknownDigestAlg` for ids this build lacks.
+    pub fn digest_algorithm(&self) -> Result<DigestAlg, SegmentError> {
+        DigestAlg::try_from(self.digest_alg).map_err(|_| SegmentError::UnknownDigestAlg { raw: self.digest_alg })
+    }
+
     /// Produce a concise debug summary of the segment header
+    ///
+    /// Lists every field; [`fmt::Display`] gives the one-line form used in logs.
     pub fn summary(&self) -> String {
         format!(
             "SegmentHeader {{ index: {}, bytes_len: {}, wire_len: {}, crc32: {}, \
-             frame_count: {}, digest_alg: {}, flags: {:?}, reserved: {} }}",
+             frame_count: {}, digest_alg: {}, flags: {:?}, version: {}, reserved: {} }}",
             self.segment_index,
             self.bytes_len,
             self.wire_len,
@@ -122,11 +287,108 @@ impl SegmentHeader {
             self.frame_count,
             self.digest_alg,
             self.flags,
+            self.version,
             self.reserved,
         )
     }
 }
 
+impl fmt::Display for SegmentHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "segment {}: {} bytes, {} wire bytes, {} frames, digest 0x{:04x}, crc32 {:08x}, flags {}",
+            self.segment_index,
+            self.bytes_len,
+            self.wire_len,
+            self.frame_count,
+            self.digest_alg,
+            self.wire_crc32,
+            self.flags,
+        )
+    }
+}
+
+
+/// Smallest frame on the wire: a bare header (the terminator has no ciphertext).
+const MIN_FRAME_WIRE_LEN: usize = FrameHeader::LEN;
+
+/// Largest frame on the wire.
+const MAX_FRAME_WIRE_LEN: usize = FrameHeader::LEN + MAX_FRAME_CIPHERTEXT_LEN;
+
+/// Bounds on the lengths a segment header may declare.
+///
+/// Segment headers are read before anything is authenticated, so
+/// `read_segment` checks them before allocating the wire buffer. Regular
+/// segments carry at most one chunk; the trailing index segment
+/// (`SEGMENT_INDEX`) grows with the stream and is capped at `MAX_CHUNK_SIZE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SegmentLimits {
+    /// Largest `bytes_len` of a regular segment.
+    pub max_bytes_len: usize,
+    /// Largest `wire_len` of a regular segment.
+    pub max_wire_len: usize,
+}
+
+impl SegmentLimits {
+    /// Limits for a stream whose header declares `chunk_size`.
+    pub fn for_chunk_size(chunk_size: u32) -> Self {
+        let max_bytes_len = usize::try_from(chunk_size).map_or(MAX_CHUNK_SIZE, |c| c.min(MAX_CHUNK_SIZE));
+        Self { max_bytes_len, max_wire_len: Self::wire_len_bound(max_bytes_len) }
+    }
+
+    /// Largest wire a segment of `bytes_len` payload bytes can produce:
+    /// the payload split into the smallest allowed frames, plus digest and
+    /// terminator frames.
+    pub fn wire_len_bound(bytes_len: usize) -> usize {
+        let frames = bytes_len.div_ceil(MIN_FRAME_SIZE) + 2;
+        bytes_len + frames * (FrameHeader::LEN + TAG_LEN) + 2 * FRAME_LEN_SLACK
+    }
+
+    /// Check the lengths declared by `header`.
+    ///
+    /// # Errors
+    /// - `BytesLenTooLarge` / `WireLenTooLarge` beyond the limits
+    /// - `FrameCountMismatch` when `frame_count` frames cannot fill `wire_len` bytes
+    pub fn check(&self, header: &SegmentHeader) -> Result<(), SegmentError> {
+        let (max_bytes_len, max_wire_len) = if header.flags.contains(SegmentFlags::SEGMENT_INDEX) {
+            (MAX_CHUNK_SIZE, Self::wire_len_bound(MAX_CHUNK_SIZE))
+        } else {
+            (self.max_bytes_len, self.max_wire_len)
+        };
+
+        // A length that does not fit `usize` is beyond any limit
+        let bytes_len = u32_to_len("bytes_len", header.bytes_len).unwrap_or(usize::MAX);
+        if bytes_len > max_bytes_len {
+            return Err(SegmentError::BytesLenTooLarge { have: header.bytes_len, max: max_bytes_len });
+        }
+        let wire_len = u32_to_len("wire_len", header.wire_len).unwrap_or(usize::MAX);
+        if wire_len > max_wire_len {
+            return Err(SegmentError::WireLenTooLarge { have: header.wire_len, max: max_wire_len });
+        }
+
+        let frame_count = u32_to_len("frame_count", header.frame_count)?;
+        let consistent = if frame_count == 0 {
+            wire_len == 0
+        } else {
+            frame_count
+                .checked_mul(MIN_FRAME_WIRE_LEN)
+                .is_some_and(|min| min <= wire_len)
+                && frame_count.saturating_mul(MAX_FRAME_WIRE_LEN) >= wire_len
+        };
+        if !consistent {
+            return Err(SegmentError::FrameCountMismatch { frame_count: header.frame_count, wire_len: header.wire_len });
+        }
+        Ok(())
+    }
+}
+
+impl Default for SegmentLimits {
+    /// Limits for the largest supported chunk size.
+    fn default() -> Self {
+        Self::for_chunk_size(MAX_CHUNK_SIZE as u32)
+    }
+}
 
 #[derive(Debug, Clone, Copy)]
 pub struct SegmentView<'a> {
@@ -148,7 +410,22 @@ pub enum SegmentError {
     Truncated,
     Malformed(String),
     InvalidFlags { raw: u16 },
-
+    /// Declared `bytes_len` beyond `SegmentLimits`
+    BytesLenTooLarge { have: u32, max: usize },
+    /// Declared `wire_len` beyond `SegmentLimits`
+    WireLenTooLarge { have: u32, max: usize },
+    /// `frame_count` frames cannot add up to `wire_len` bytes
+    FrameCountMismatch { frame_count: u32, wire_len: u32 },
+    /// A length does not fit the integer type it is converted to
+    LengthOverflow { field: &'static str, value: u64 },
+    /// Segment header `version` byte this build cannot decode or encode
+    UnsupportedVersion { version: u8 },
+    /// Segment header `digest_alg` is not a known `DigestAlg` id
+    UnknownDigestAlg { raw: u16 },
+    /// v2 header whose CRC-8 does not match its fields
+    HeaderChecksumMismatch { expected: u8, actual: u8 },
+    /// Header without a CRC in a stream with `SEGMENT_HEADER_CRC`
+    HeaderChecksumMissing { segment_index: u32, version: u8 },
 }
 
 impl fmt::Display for SegmentError {
@@ -159,8 +436,43 @@ impl fmt::Display for SegmentError {
             Truncated => write!(f, "truncated segment"),
             InvalidFlags { raw } => write!(f, "unknown cipher suite: {}", *raw),
             Malformed(msg) => write!(f, "malformed segment: {}", msg),
+            BytesLenTooLarge { have, max } => write!(f, "segment bytes_len too large: {} (max {})", have, max),
+            WireLenTooLarge { have, max } => write!(f, "segment wire_len too large: {} (max {})", have, max),
+            FrameCountMismatch { frame_count, wire_len } =>
+                write!(f, "{} frames cannot span {} wire bytes", frame_count, wire_len),
+            LengthOverflow { field, value } => write!(f, "segment {} overflows: {}", field, value),
+            UnsupportedVersion { version } => write!(f, "unsupported segment header version: {}", version),
+            UnknownDigestAlg { raw } => write!(f, "unknown segment digest algorithm: {}", enum_name_or_hex::<DigestAlg>(*raw)),
+            HeaderChecksumMismatch { expected, actual } =>
+                write!(f, "segment header checksum mismatch: expected {:02x}, got {:02x}", expected, actual),
+            HeaderChecksumMissing { segment_index, version } =>
+                write!(f, "segment {} header (version {}) lacks the checksum the stream requires", segment_index, version),
         }
     }
 }
 
 impl std::error::Error for SegmentError {}
+
+/// CRC-8 (polynomial 0x07, init 0): catches every error burst up to 8 bits,
+/// so any single corrupted header byte.
+fn crc8(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |crc, &byte| {
+        (0..8).fold(crc ^ byte, |crc, _| if crc & 0x80 != 0 { (crc << 1) ^ 0x07 } else { crc << 1 })
+    })
+}
+
+/// `len` as a `u32` wire field; `LengthOverflow` beyond `u32::MAX`.
+pub fn len_to_u32(field: &'static str, len: usize) -> Result<u32, SegmentError> {
+    u32::try_from(len).map_err(|_| SegmentError::LengthOverflow { field, value: len as u64 })
+}
+
+/// A `u32` wire field as an in-memory length; `LengthOverflow` where `usize` is narrower.
+pub fn u32_to_len(field: &'static str, value: u32) -> Result<usize, SegmentError> {
+    usize::try_from(value).map_err(|_| SegmentError::LengthOverflow { field, value: u64::from(value) })
+}
+
+/// `a + b`; `LengthOverflow` instead of wrapping.
+pub fn checked_len_add(field: &'static str, a: usize, b: usize) -> Result<usize, SegmentError> {
+    a.checked_add(b)
+        .ok_or(SegmentError::LengthOverflow { field, value: (a as u64).saturating_add(b as u64) })
+}
@@ -0,0 +1,132 @@
+// # 📂 `src/stream_v2/sink.rs`
+
+//! Hand-off between pipeline stages.
+//!
+//! Workers deliver their results through a [`StageSink`]. A plain
+//! `Sender<Result<T, E>>` is one; [`ForwardSink`] and [`RouteSink`] wire a
+//! stage straight into the next one, so no adapter thread has to unwrap
+//! results in between. [`ForwardSink`] also takes the telemetry off each
+//! segment; with [`RouteSink`] it travels on to the writer.
+
+use std::sync::{Arc, Mutex};
+use crossbeam::channel::Sender;
+
+use crate::stream_v2::segment_worker::EncryptSegmentInput;
+use crate::telemetry::{StageTimes, TelemetryCounters};
+
+/// Where a stage worker sends each result.
+pub trait StageSink<T, E>: Send {
+    /// Pass `result` on; `false` once nobody downstream is listening.
+    fn send(&mut self, result: Result<T, E>) -> bool;
+}
+
+impl<T: Send, E, F: From<E> + Send> StageSink<T, E> for Sender<Result<T, F>> {
+    fn send(&mut self, result: Result<T, E>) -> bool {
+        Sender::send(self, result.map_err(F::from)).is_ok()
+    }
+}
+
+/// Stage times and counters taken off forwarded segments.
+#[derive(Debug, Default, Clone)]
+pub struct StageTelemetry {
+    pub stage_times: StageTimes,
+    pub counters: TelemetryCounters,
+}
+
+impl StageTelemetry {
+    pub fn merge(&mut self, other: &StageTelemetry) {
+        self.stage_times.merge(&other.stage_times);
+        self.counters.merge(&other.counters);
+    }
+}
+
+/// A segment whose telemetry is accounted when it leaves its stage.
+pub trait StageOutput {
+    /// Move this stage's telemetry into `into`, leaving the segment's own empty.
+    fn take_telemetry(&mut self, into: &mut StageTelemetry);
+}
+
+impl StageOutput for EncryptSegmentInput {
+    /// Compression times, and the payload as `bytes_compressed`.
+    fn take_telemetry(&mut self, into: &mut StageTelemetry) {
+        into.stage_times.merge(&std::mem::take(&mut self.stage_times));
+        into.counters.bytes_compressed += self.bytes.len() as u64;
+    }
+}
+
+/// Sends segments to the next stage and errors straight to the writer.
+///
+/// Every clone keeps its own telemetry and merges it into the shared totals
+/// once, when dropped: give each worker its own clone, and read the totals
+/// after the writer's channel has closed.
+pub struct ForwardSink<T, R> {
+    next: Sender<T>,
+    errors: Sender<R>,
+    telemetry: StageTelemetry,
+    totals: Arc<Mutex<StageTelemetry>>,
+}
+
+impl<T, R> ForwardSink<T, R> {
+    pub fn new(next: Sender<T>, errors: Sender<R>, totals: Arc<Mutex<StageTelemetry>>) -> Self {
+        Self { next, errors, telemetry: StageTelemetry::default(), totals }
+    }
+}
+
+impl<T, R> Clone for ForwardSink<T, R> {
+    fn clone(&self) -> Self {
+        Self::new(self.next.clone(), self.errors.clone(), Arc::clone(&self.totals))
+    }
+}
+
+impl<T, R> Drop for ForwardSink<T, R> {
+    fn drop(&mut self) {
+        // Runs before the senders close, so a closed channel means merged totals
+        if let Ok(mut totals) = self.totals.lock() {
+            totals.merge(&self.telemetry);
+        }
+    }
+}
+
+impl<T, E, O, F> StageSink<T, E> for ForwardSink<T, Result<O, F>>
+where
+    T: StageOutput + Send,
+    O: Send,
+    F: From<E> + Send,
+{
+    fn send(&mut self, result: Result<T, E>) -> bool {
+        match result {
+            Ok(mut segment) => {
+                segment.take_telemetry(&mut self.telemetry);
+                self.next.send(segment).is_ok()
+            }
+            Err(e) => self.errors.send(Err(F::from(e))).is_ok(),
+        }
+    }
+}
+
+/// Sends segments to the next stage as they are and errors straight to the writer.
+#[derive(Clone)]
+pub struct RouteSink<T, R> {
+    next: Sender<T>,
+    errors: Sender<R>,
+}
+
+impl<T, R> RouteSink<T, R> {
+    pub fn new(next: Sender<T>, errors: Sender<R>) -> Self {
+        Self { next, errors }
+    }
+}
+
+impl<T, E, O, F> StageSink<T, E> for RouteSink<T, Result<O, F>>
+where
+    T: Send,
+    O: Send,
+    F: From<E> + Send,
+{
+    fn send(&mut self, result: Result<T, E>) -> bool {
+        match result {
+            Ok(segment) => self.next.send(segment).is_ok(),
+            Err(e) => self.errors.send(Err(F::from(e))).is_ok(),
+        }
+    }
+}
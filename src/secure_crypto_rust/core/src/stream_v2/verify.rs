@@ -0,0 +1,253 @@
+// # 📂 src/stream_v2/verify.rs
+
+// ## 📂 File: `src/stream_v2/verify.rs`
+// ## Authenticate a stream without producing plaintext
+//
+// Scrubbing runs the decrypt side of the pipeline (segment CRC, per-frame AEAD,
+// digest, terminator, decompression) and drops each segment's plaintext as soon
+// as it is checked:
+//
+// - segment workers share one frame pool, as in `run_decrypt_pipeline`
+// - there is no `OrderedPlaintextWriter`: only segment indices are kept, to
+//   check the stream is gap-free and ends with its final segment
+// - the first failure stops the reader; segments already in flight still
+//   finish, so the lowest failing index is the first failure in stream order
+// - trailer segments (`HAS_CRC32` / `HAS_TOTAL_LEN`) are authenticated, but the
+//   plaintext CRC is not recomputed since plaintext is never reassembled
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use bytes::Bytes;
+use crossbeam::channel::{Receiver, bounded};
+use tracing::debug;
+
+use crate::crypto::{DigestAlg, KEY_LEN_32, derive_session_key_32};
+use crate::headers::FrameLayout;
+use crate::stream_v2::compression_worker::{CodecInfo, CompressionBackend, decompress_frames, make_backend};
+use crate::stream_v2::core::{DecryptParams, dec_context_error, validate_decrypt_params};
+use crate::stream_v2::frame_worker::{DecryptFrameInput, DecryptFramePool, DecryptedFrame, FrameDispatch, FrameWorkerError};
+use crate::stream_v2::io::{InputSource, PayloadReader, PlaintextTrailer, open_input, read_segment};
+use crate::stream_v2::parallelism::{HybridParallelismProfile, WorkerTarget};
+use crate::stream_v2::segment_worker::{DecryptContext, DecryptSegmentInput, SegmentWorkerError, decrypt::process_decrypt_segment_with_layout};
+use crate::stream_v2::segmenting::types::SegmentFlags;
+use crate::types::StreamError;
+
+/// Where verification first failed.
+#[derive(Debug)]
+pub struct VerifyFailure {
+    pub segment_index: u32,
+    /// Set when a single frame failed (AEAD, nonce or AAD check).
+    pub frame_index: Option<u32>,
+    pub error: StreamError,
+}
+
+/// Outcome of [`verify_stream_v2`].
+#[derive(Debug, Default)]
+pub struct VerifyReport {
+    /// Segments that authenticated, the final segment included.
+    pub segments_verified: u64,
+    /// Data, digest and terminator frames of the verified segments.
+    pub frames_verified: u64,
+    /// Plaintext bytes of the verified segments, after decompression (trailers excluded).
+    pub bytes_plaintext: u64,
+    /// First failure in stream order; `None` for an intact stream.
+    pub failure: Option<VerifyFailure>,
+}
+
+impl VerifyReport {
+    pub fn is_ok(&self) -> bool {
+        self.failure.is_none()
+    }
+
+    /// Keep `failure` if it comes before the one already recorded.
+    fn record(&mut self, failure: VerifyFailure) {
+        match &self.failure {
+            Some(first) if first.segment_index <= failure.segment_index => {}
+            _ => self.failure = Some(failure),
+        }
+    }
+}
+
+/// Per-segment result sent back by a verify worker.
+struct SegmentCheck {
+    final_segment: bool,
+    frames: u64,
+    bytes_plaintext: u64,
+}
+
+/// 🔍 Authenticate every segment of an encrypted stream, discarding plaintext
+///
+/// Returns `Err` only when verification cannot start (unreadable stream
+/// header, invalid header, failed key confirmation, unsupported codec); anything wrong with the payload
+/// is reported in [`VerifyReport::failure`].
+pub fn verify_stream_v2(input: InputSource, master_key: &[u8]) -> Result<VerifyReport, StreamError> {
+    validate_decrypt_params(master_key, &DecryptParams::new(), None, None)?;
+
+    let (header, mut reader) = PayloadReader::with_header(open_input(input)?)?;
+    header.validate().map_err(StreamError::Header)?;
+
+    let profile = HybridParallelismProfile::from_header(&header);
+    let layout = header.frame_layout();
+    let session_key = derive_session_key_32(master_key, &header).map_err(StreamError::Crypto)?;
+    let crypto = DecryptContext::from_stream_header(header, profile.clone(), session_key.as_slice(), DigestAlg::Blake3)
+        .map_err(dec_context_error)?
+        .with_extensions(reader.extensions().clone());
+    let session_key: &[u8; KEY_LEN_32] = &session_key;
+
+    // Build every backend first so a bad codec fails before any thread starts
+    let codec_info = CodecInfo::from_header(&header, None).map_err(StreamError::Compression)?;
+    let backends = (0..profile.cpu_workers().max(1))
+        .map(|i| make_backend(WorkerTarget::Cpu(i), codec_info.clone()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(StreamError::Compression)?;
+    let frame_pool = DecryptFramePool::decrypt_with_extensions(&header, &crypto.extensions, session_key.as_slice(), profile.cpu_workers())
+        .map_err(|e| StreamError::SegmentWorker(e.into()))?;
+
+    let (seg_tx, seg_rx) = bounded::<DecryptSegmentInput>(profile.inflight_segments());
+    let (out_tx, out_rx) = bounded::<(u32, Result<SegmentCheck, StreamError>)>(profile.inflight_segments());
+    let stop = AtomicBool::new(false);
+    let mut report = VerifyReport::default();
+    let mut seen = HashSet::new();
+    let mut final_index = None;
+
+    thread::scope(|scope| {
+        // ---- Reader thread ----
+        let stop = &stop;
+        let reader_handle = scope.spawn(move || -> Result<(), VerifyFailure> {
+            let mut next_index = 0u32;
+            while !stop.load(Ordering::Relaxed) {
+                let segment = read_segment(&mut reader)
+                    .map_err(|error| VerifyFailure { segment_index: next_index, frame_index: None, error })?;
+                let Some((header, wire)) = segment else { break };
+                next_index = header.segment_index.saturating_add(1);
+
+                let is_final = header.flags.contains(SegmentFlags::FINAL_SEGMENT);
+                if seg_tx.send(DecryptSegmentInput { header, wire }).is_err() || is_final {
+                    break;
+                }
+            }
+            Ok(())
+        });
+
+        // ---- Verify workers ----
+        for mut backend in backends {
+            let (frame_tx, frame_rx) = frame_pool.handle().connect();
+            let seg_rx = seg_rx.clone();
+            let out_tx = out_tx.clone();
+
+            scope.spawn(move || {
+                for input in seg_rx.iter() {
+                    let segment_index = input.header.segment_index;
+                    let result = verify_segment(&input, session_key, layout, &frame_tx, &frame_rx, backend.as_mut());
+                    if out_tx.send((segment_index, result)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(seg_rx);
+        drop(out_tx);
+
+        // ---- Collect: counts and indices only ----
+        for (segment_index, result) in out_rx.iter() {
+            match result {
+                Ok(check) if seen.insert(segment_index) => {
+                    report.segments_verified += 1;
+                    report.frames_verified += check.frames;
+                    report.bytes_plaintext += check.bytes_plaintext;
+                    if check.final_segment {
+                        final_index = Some(segment_index);
+                    }
+                }
+                Ok(_) => report.record(VerifyFailure {
+                    segment_index,
+                    frame_index: None,
+                    error: StreamError::Validation(format!("duplicate segment {}", segment_index)),
+                }),
+                Err(error) => {
+                    debug!(segment_index, error = %error, "segment failed verification");
+                    stop.store(true, Ordering::Relaxed);
+                    report.record(VerifyFailure { segment_index, frame_index: failing_frame(&error), error });
+                }
+            }
+        }
+
+        match reader_handle.join() {
+            Ok(Ok(())) => {}
+            Ok(Err(failure)) => report.record(failure),
+            Err(_) => report.record(VerifyFailure {
+                segment_index: 0,
+                frame_index: None,
+                error: StreamError::PipelineError("verify reader thread panicked"),
+            }),
+        }
+    });
+
+    // Every worker has exited with the scope
+    frame_pool.shutdown();
+
+    if report.is_ok() {
+        if let Some(failure) = check_layout(&seen, final_index) {
+            report.record(failure);
+        }
+    }
+    Ok(report)
+}
+
+/// Decrypt, authenticate and decompress one segment, keeping only its sizes.
+fn verify_segment(
+    input: &DecryptSegmentInput,
+    session_key: &[u8; KEY_LEN_32],
+    layout: FrameLayout,
+    frame_tx: &impl FrameDispatch<DecryptFrameInput>,
+    frame_rx: &Receiver<Result<DecryptedFrame, FrameWorkerError>>,
+    backend: &mut dyn CompressionBackend,
+) -> Result<SegmentCheck, StreamError> {
+    input.header.validate(&input.wire).map_err(|e| StreamError::SegmentWorker(SegmentWorkerError::SegmentError(e)))?;
+    let segment = process_decrypt_segment_with_layout(input, Some(session_key), layout, frame_tx, frame_rx)
+        .map_err(StreamError::SegmentWorker)?;
+    let flags = segment.header.flags;
+    let counters = &segment.counters;
+    let frames = counters.frames_data + counters.frames_digest + counters.frames_terminator;
+
+    let final_segment = flags.contains(SegmentFlags::FINAL_SEGMENT);
+    if final_segment && segment.is_empty() {
+        return Ok(SegmentCheck { final_segment, frames, bytes_plaintext: 0 });
+    }
+    let plaintext = decompress_frames(backend, segment.frames, flags).map_err(StreamError::Compression)?;
+    let bytes_plaintext = match PlaintextTrailer::is_trailer(flags) {
+        true => 0,
+        false => plaintext.iter().map(Bytes::len).sum::<usize>() as u64,
+    };
+    Ok(SegmentCheck { final_segment, frames, bytes_plaintext })
+}
+
+/// Frame index carried by a tagged frame worker error.
+fn failing_frame(error: &StreamError) -> Option<u32> {
+    match error {
+        StreamError::SegmentWorker(SegmentWorkerError::FrameWorkerError(e)) => e.frame_tag().map(|(_, frame_index)| frame_index),
+        _ => None,
+    }
+}
+
+/// Segments `0..=final` each verified once, and nothing past the final segment.
+fn check_layout(seen: &HashSet<u32>, final_index: Option<u32>) -> Option<VerifyFailure> {
+    let layout_failure = |segment_index, msg: String| VerifyFailure {
+        segment_index,
+        frame_index: None,
+        error: StreamError::Validation(msg),
+    };
+    let Some(final_index) = final_index else {
+        let next = seen.iter().max().map_or(0, |max| max.saturating_add(1));
+        return Some(layout_failure(next, "stream ended without a final segment".into()));
+    };
+    if let Some(missing) = (0..=final_index).find(|i| !seen.contains(i)) {
+        return Some(layout_failure(missing, format!("segment {} is missing", missing)));
+    }
+    seen.iter()
+        .filter(|&&i| i > final_index)
+        .min()
+        .map(|&i| layout_failure(i, format!("segment {} follows the final segment {}", i, final_index)))
+}
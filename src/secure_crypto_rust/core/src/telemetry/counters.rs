@@ -5,6 +5,27 @@
 //!
 //! Summary: Collects frame counts and byte counts during encrypt/decrypt.
 //! Converted into immutable TelemetrySnapshot at pipeline end.
+//!
+//! Accumulation contract: every counter has exactly one owning stage, so the
+//! pipeline totals are the `merge` of the per-stage counters and no byte is
+//! counted twice.
+//!
+//! | Counter                          | Encrypt owner           | Decrypt owner            |
+//! |----------------------------------|-------------------------|--------------------------|
+//! | `bytes_plaintext`                | reader (input chunks)   | writer (output segments) |
+//! | `bytes_compressed`               | compression workers     | segment worker           |
+//! | `bytes_ciphertext`               | segment worker          | segment worker           |
+//! | `frames_*`, per-segment overhead | segment worker          | segment worker           |
+//! | stream header / index overhead   | pipeline                | pipeline                 |
+//!
+//! - `bytes_plaintext` excludes trailer segments (`HAS_CRC32` / `HAS_TOTAL_LEN`).
+//! - `bytes_compressed` is the payload of every encrypted segment (the sum of
+//!   `SegmentHeader::bytes_len`), trailer included, segment index excluded.
+//! - `bytes_ciphertext` is the AEAD output of the data frames, tags included,
+//!   headers excluded; frame headers, segment headers and the digest and
+//!   terminator frames are `bytes_overhead`.
+//! - Encrypt and decrypt of the same stream report the same byte counters,
+//!   except the segment index trailer, which only the encryptor writes.
 use bincode::{Encode, Decode};
 use std::ops::AddAssign;
 
@@ -27,7 +48,7 @@ impl TelemetryCounters {
         counters.clone()
     }
 
-    /// Record the stream header as overhead.
+    /// Record a segment header as overhead.
     pub fn add_header(&mut self, header_len: usize) {
         self.frames_header += 1;           // optional: count headers if we track them
         self.bytes_overhead += header_len as u64;
@@ -47,10 +68,13 @@ impl TelemetryCounters {
         self.bytes_overhead += frame_overhead_len as u64;
     }
 
-    // This avoids:
-    // * locks inside workers
-    // * atomics
-    // * false sharing
+    /// Add every counter of `other` into `self`.
+    ///
+    /// Merging is plain addition: callers merge counters from disjoint work
+    /// (different segments, or different owners per the module contract),
+    /// never a stage's counters into a total that already holds them.
+    ///
+    /// This avoids locks and atomics inside workers.
     pub fn merge(&mut self, other: &TelemetryCounters) {
         self.frames_header += other.frames_header;
         self.frames_data += other.frames_data;
@@ -34,7 +34,9 @@
 pub mod counters;
 pub mod timers;
 pub mod snapshot;
+pub mod segments;
 
 pub use counters::*;
 pub use timers::*;
 pub use snapshot::*;
+pub use segments::*;
@@ -0,0 +1,157 @@
+// ## src/telemetry/segments.rs
+
+//! telemetry/segments.rs
+//! Per-segment timings: which crypto worker spent how long on which segment.
+//!
+//! Collected only with `ApiConfig::collect_metrics == Some(true)`. Retained
+//! records are capped at [`MAX_SEGMENT_RECORDS`]; past that a reservoir sample
+//! is kept, while the per-worker totals still count every segment.
+
+use std::sync::Mutex;
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+
+use crate::telemetry::snapshot::duration_ns;
+
+/// Segment records kept per run before reservoir sampling takes over.
+pub const MAX_SEGMENT_RECORDS: usize = 10_000;
+
+/// One segment as processed by a crypto worker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SegmentRecord {
+    pub segment_index: u32,
+    /// Encoded frame bytes of the segment (written on encrypt, read on decrypt).
+    pub wire_len: u64,
+    /// Time the worker spent on the segment, frame round trips included.
+    #[serde(rename = "duration_ns", with = "duration_ns")]
+    pub duration: Duration,
+    /// Index of the crypto worker in the pool.
+    pub worker_id: usize,
+}
+
+/// Work done by one crypto worker, over every segment it processed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkerUsage {
+    pub segments: u64,
+    #[serde(rename = "busy_ns", with = "duration_ns")]
+    pub busy: Duration,
+}
+
+/// Distribution of segment durations plus the per-worker load.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SegmentStats {
+    /// Segments processed by all workers.
+    pub segments: u64,
+    /// Records the duration statistics are computed from (`<= segments`).
+    pub sampled: usize,
+    pub min: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+    /// Nearest-rank 95th percentile.
+    pub p95: Duration,
+    /// Indexed by worker id.
+    pub workers: Vec<WorkerUsage>,
+    /// Busy time of each worker over the run's elapsed time.
+    pub utilization: Vec<f64>,
+}
+
+impl SegmentStats {
+    /// Duration statistics over `records`; `None` when there are none.
+    pub fn compute(records: &[SegmentRecord], workers: &[WorkerUsage], elapsed: Duration) -> Option<Self> {
+        let mut durations: Vec<Duration> = records.iter().map(|r| r.duration).collect();
+        durations.sort_unstable();
+        let (&min, &max) = (durations.first()?, durations.last()?);
+        let total: Duration = durations.iter().sum();
+        let p95_rank = (durations.len() * 95).div_ceil(100).max(1);
+
+        let utilization = workers
+            .iter()
+            .map(|w| if elapsed.is_zero() { 0.0 } else { w.busy.as_secs_f64() / elapsed.as_secs_f64() })
+            .collect();
+        Some(Self {
+            segments: workers.iter().map(|w| w.segments).sum(),
+            sampled: durations.len(),
+            min,
+            max,
+            mean: total / durations.len() as u32,
+            p95: durations[p95_rank - 1],
+            workers: workers.to_vec(),
+            utilization,
+        })
+    }
+}
+
+/// Shared sink the crypto workers report their segments to.
+#[derive(Debug)]
+pub struct SegmentRecorder {
+    capacity: usize,
+    state: Mutex<RecorderState>,
+}
+
+#[derive(Debug, Default)]
+struct RecorderState {
+    records: Vec<SegmentRecord>,
+    workers: Vec<WorkerUsage>,
+    seen: u64,
+    rng: u64,
+}
+
+impl Default for SegmentRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SegmentRecorder {
+    /// Recorder keeping up to [`MAX_SEGMENT_RECORDS`] records.
+    pub fn new() -> Self {
+        Self::with_capacity(MAX_SEGMENT_RECORDS)
+    }
+
+    /// Recorder keeping up to `capacity` records (at least one).
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { capacity: capacity.max(1), state: Mutex::new(RecorderState::default()) }
+    }
+
+    pub fn record(&self, record: SegmentRecord) {
+        let mut state = self.state.lock().unwrap();
+        if state.workers.len() <= record.worker_id {
+            state.workers.resize(record.worker_id + 1, WorkerUsage::default());
+        }
+        let usage = &mut state.workers[record.worker_id];
+        usage.segments += 1;
+        usage.busy += record.duration;
+
+        // Reservoir sampling (Algorithm R): every segment is kept with equal probability
+        state.seen += 1;
+        if state.records.len() < self.capacity {
+            state.records.push(record);
+        } else {
+            let slot = state.next_random() % state.seen;
+            if let Some(kept) = state.records.get_mut(slot as usize) {
+                *kept = record;
+            }
+        }
+    }
+
+    /// Take the records (sorted by segment index) and per-worker totals.
+    pub fn drain(&self) -> (Vec<SegmentRecord>, Vec<WorkerUsage>) {
+        let mut state = self.state.lock().unwrap();
+        let mut records = std::mem::take(&mut state.records);
+        records.sort_by_key(|r| r.segment_index);
+        let workers = std::mem::take(&mut state.workers);
+        state.seen = 0;
+        (records, workers)
+    }
+}
+
+impl RecorderState {
+    /// SplitMix64: sampling only needs to be unbiased, not unpredictable.
+    fn next_random(&mut self) -> u64 {
+        self.rng = self.rng.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
@@ -9,14 +9,31 @@
 // //! - Stage times are flattened into fixed fields for ABI stability.
 // //! - Conversions ensure elapsed time is represented in milliseconds for cross-language parity.
 
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 use serde::{Serialize, Deserialize};
 
 use crate::telemetry::counters::TelemetryCounters;
+use crate::telemetry::segments::{SegmentRecord, SegmentStats, WorkerUsage};
 use crate::telemetry::timers::{TelemetryTimer, StageTimes, Stage};
 
+/// How a pipeline actually ran, whatever strategy the header asked for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EffectiveStrategy {
+    /// One crypto worker.
+    Sequential,
+    /// More than one crypto worker from the first segment.
+    Parallel,
+    /// Ran one segment at a time for `after_segments` segments, then parallel.
+    Promoted { after_segments: u32 },
+}
+
 /// Core telemetry snapshot.
 /// Captures counters, ratios, throughput, stage timings, and elapsed duration.
+///
+/// JSON schema (see [`TelemetrySnapshot::to_json`]): durations are integer
+/// nanoseconds (`elapsed_ns`, `stage_times.<stage>`), stage keys are the
+/// lowercase `Display` names, and `output` is never serialized.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TelemetrySnapshot {
     pub segments_processed: u64,
@@ -29,8 +46,47 @@ pub struct TelemetrySnapshot {
     pub bytes_overhead: u64,
     pub compression_ratio: f64,
     pub throughput_plaintext_bytes_per_sec: f64,
+    #[serde(rename = "elapsed_ns", with = "duration_ns")]
     pub elapsed: Duration,
+    #[serde(with = "stage_times_ns")]
     pub stage_times: StageTimes, // HashMap<Stage, Duration>
+    /// Worker layout the pipeline ran with; `None` when not recorded.
+    #[serde(default)]
+    pub strategy_effective: Option<EffectiveStrategy>,
+    /// Size of the crypto worker pool; `0` when not recorded.
+    #[serde(default)]
+    pub cpu_workers: usize,
+    /// Frame worker threads shared by all crypto workers; `0` when not recorded.
+    #[serde(default)]
+    pub frame_workers: usize,
+    /// Largest plaintext read buffer the encrypt reader held, in bytes; `0` when not recorded.
+    #[serde(default)]
+    pub read_buffer_peak: u64,
+    /// GPU compression backends replaced by CPU ones at startup (see `verify_or_fallback`).
+    #[serde(default)]
+    pub gpu_fallbacks: u64,
+    /// Segments the encrypt pipeline routed to CPU compression workers.
+    #[serde(default)]
+    pub segments_cpu: u64,
+    /// Segments the encrypt pipeline routed to GPU compression workers.
+    #[serde(default)]
+    pub segments_gpu: u64,
+    /// Per-segment timings, sorted by segment index; empty unless metrics were collected.
+    ///
+    /// A uniform sample once the run exceeds `MAX_SEGMENT_RECORDS` segments.
+    #[serde(default)]
+    pub segment_records: Vec<SegmentRecord>,
+    /// Load of each crypto worker, indexed by worker id; empty unless metrics were collected.
+    #[serde(default)]
+    pub worker_usage: Vec<WorkerUsage>,
+    /// Encryption time recorded in the stream header (`enc_time_ns`); decrypt only,
+    /// `None` when the header carries none.
+    #[serde(default, rename = "stream_created_at_ns", with = "system_time_ns")]
+    pub stream_created_at: Option<SystemTime>,
+    /// Non-fatal issues noticed during the run, e.g. a non-strict stream in an
+    /// unexpected AAD domain (see `DecryptParams::aad_domain_warning`).
+    #[serde(default)]
+    pub warnings: Vec<String>,
     /// The final encrypted stream bytes, if the output sink was memory-backed.
     /// 
     /// - `None` if the output was written directly to a file or external sink.
@@ -39,6 +95,7 @@ pub struct TelemetrySnapshot {
     /// This field is primarily useful in tests, benchmarks, or integrations
     /// where we want to inspect the produced ciphertext alongside telemetry
     /// counters and stage timings.
+    #[serde(skip)]
     pub output: Option<Vec<u8>>,
 }
 
@@ -72,6 +129,17 @@ impl TelemetrySnapshot {
             throughput_plaintext_bytes_per_sec: throughput,
             elapsed: elapsed,
             stage_times: timer.stage_times.clone(),
+            strategy_effective: None,
+            cpu_workers: 0,
+            frame_workers: 0,
+            read_buffer_peak: 0,
+            gpu_fallbacks: 0,
+            segments_cpu: 0,
+            segments_gpu: 0,
+            segment_records: Vec::new(),
+            worker_usage: Vec::new(),
+            stream_created_at: None,
+            warnings: Vec::new(),
             output: None, // 🔧 initialize empty
         }
     }
@@ -94,14 +162,31 @@ impl TelemetrySnapshot {
     // Provide a method that validates internal invariants:  
     // - `bytes_ciphertext >= bytes_compressed`  
     // - `compression_ratio <= 1.0`  
-    // - `total_stage_time() <= elapsed`  
+    // - `total_stage_time() <= elapsed` per timed thread  
 
     pub fn sanity_check(&self) -> bool {
         self.bytes_ciphertext >= self.bytes_compressed &&
         self.compression_ratio <= 1.0 &&
-        self.total_stage_time() <= self.elapsed
+        self.total_stage_time() <= self.elapsed * self.timed_threads()
+    }
+
+    /// Threads whose stage times are summed into `stage_times`, so the total
+    /// may reach `elapsed` once per thread: reader, compression adapter and
+    /// writer, a compression and a crypto worker per `cpu_workers`, and the
+    /// frame workers. `1` when the worker layout was not recorded.
+    pub fn timed_threads(&self) -> u32 {
+        if self.cpu_workers == 0 && self.frame_workers == 0 {
+            return 1;
+        }
+        u32::try_from(3 + 2 * self.cpu_workers + self.frame_workers).unwrap_or(u32::MAX)
     }
     
+    /// Segment duration min/max/mean/p95 and per-worker load, or `None` when
+    /// no segment records were collected.
+    pub fn segment_stats(&self) -> Option<SegmentStats> {
+        SegmentStats::compute(&self.segment_records, &self.worker_usage, self.elapsed)
+    }
+
     pub fn output_bytes(&self) -> u64 {
         self.bytes_ciphertext
     }
@@ -110,5 +195,138 @@ impl TelemetrySnapshot {
     pub fn attach_output(&mut self, buf: Vec<u8>) {
         self.output = Some(buf);
     }
+
+    /// `bytes_compressed / bytes_plaintext`, or `None` for an empty stream.
+    ///
+    /// Unlike the `compression_ratio` field this is not capped at `1.0`.
+    pub fn compression_ratio(&self) -> Option<f64> {
+        (self.bytes_plaintext > 0).then(|| self.bytes_compressed as f64 / self.bytes_plaintext as f64)
+    }
+
+    /// Plaintext throughput in MB/s (10^6 bytes), or `None` if no time elapsed.
+    pub fn throughput_mbps(&self) -> Option<f64> {
+        let secs = self.elapsed.as_secs_f64();
+        (secs > 0.0).then(|| self.bytes_plaintext as f64 / secs / 1_000_000.0)
+    }
+
+    /// Share of the produced bytes spent on headers, digests and terminators.
+    pub fn overhead_fraction(&self) -> Option<f64> {
+        let total = self.bytes_ciphertext + self.bytes_overhead;
+        (total > 0).then(|| self.bytes_overhead as f64 / total as f64)
+    }
+
+    /// Share of the total stage time spent in `stage` (`0.0` if it never ran).
+    ///
+    /// `None` when no stage time was recorded at all.
+    pub fn stage_fraction(&self, stage: Stage) -> Option<f64> {
+        let total = self.total_stage_time();
+        (total > Duration::ZERO).then(|| self.stage_times.get(stage).as_secs_f64() / total.as_secs_f64())
+    }
+
+    /// One-line run summary for logging.
+    ///
+    /// Metrics that cannot be computed (empty input, zero elapsed time) are shown as `n/a`.
+    pub fn summary(&self) -> String {
+        fn or_na(v: Option<f64>, fmt: impl Fn(f64) -> String) -> String {
+            v.map(fmt).unwrap_or_else(|| "n/a".into())
+        }
+
+        let mut stages: Vec<(String, Stage)> = self.stage_times.iter().map(|(s, _)| (s.to_string(), *s)).collect();
+        stages.sort_by(|a, b| a.0.cmp(&b.0));
+        let stages = stages
+            .iter()
+            .map(|(name, stage)| format!("{name}={}", or_na(self.stage_fraction(*stage), |f| format!("{:.1}%", f * 100.0))))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        format!(
+            "segments={} plaintext={}B compressed={}B ciphertext={}B overhead={}B ratio={} overhead_frac={} throughput={} elapsed={:?} stages=[{}]",
+            self.segments_processed,
+            self.bytes_plaintext,
+            self.bytes_compressed,
+            self.bytes_ciphertext,
+            self.bytes_overhead,
+            or_na(self.compression_ratio(), |r| format!("{r:.3}")),
+            or_na(self.overhead_fraction(), |f| format!("{f:.3}")),
+            or_na(self.throughput_mbps(), |t| format!("{t:.2}MB/s")),
+            self.elapsed,
+            stages,
+        )
+    }
+
+    /// Compact JSON report (one line), suitable for dashboards and archiving.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("TelemetrySnapshot is always serializable")
+    }
+
+    /// Indented JSON report.
+    pub fn to_json_pretty(&self) -> String {
+        serde_json::to_string_pretty(self).expect("TelemetrySnapshot is always serializable")
+    }
+
+    /// Parse a report produced by [`TelemetrySnapshot::to_json`].
+    ///
+    /// `output` is not part of the schema and is always `None`.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// `Duration` as integer nanoseconds.
+pub(crate) mod duration_ns {
+    use std::time::Duration;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(dur: &Duration, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_u64(dur.as_nanos() as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Duration, D::Error> {
+        Ok(Duration::from_nanos(u64::deserialize(d)?))
+    }
+}
+
+/// `Option<SystemTime>` as integer nanoseconds since the UNIX epoch, or `null`.
+mod system_time_ns {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(time: &Option<SystemTime>, s: S) -> Result<S::Ok, S::Error> {
+        match time.and_then(|t| t.duration_since(UNIX_EPOCH).ok()) {
+            Some(d) => s.serialize_some(&(d.as_nanos() as u64)),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<SystemTime>, D::Error> {
+        Ok(Option::<u64>::deserialize(d)?.map(|ns| UNIX_EPOCH + Duration::from_nanos(ns)))
+    }
+}
+
+/// `StageTimes` as `{ "<stage>": nanoseconds }`, keys sorted for stable output.
+mod stage_times_ns {
+    use std::collections::BTreeMap;
+    use std::time::Duration;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    use crate::telemetry::timers::{Stage, StageTimes};
+
+    pub fn serialize<S: Serializer>(times: &StageTimes, s: S) -> Result<S::Ok, S::Error> {
+        let map: BTreeMap<String, u64> = times
+            .iter()
+            .map(|(stage, dur)| (stage.to_string(), dur.as_nanos() as u64))
+            .collect();
+        s.collect_map(map)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<StageTimes, D::Error> {
+        let map = BTreeMap::<String, u64>::deserialize(d)?;
+        let mut times = StageTimes::default();
+        for (name, ns) in map {
+            let stage: Stage = name.parse().map_err(D::Error::custom)?;
+            times.times.insert(stage, Duration::from_nanos(ns));
+        }
+        Ok(times)
+    }
 }
 
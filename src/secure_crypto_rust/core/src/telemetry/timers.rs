@@ -3,7 +3,8 @@
 //! telemetry/timers.rs
 //! Stage timers for streaming pipelines.
 //!
-//! Summary: Records durations for read, compress, encrypt/decrypt, and write stages.
+//! Summary: Records durations per pipeline [`Stage`]: read, compress, encrypt/decrypt,
+//! framing, validation, digest and write.
 //! Industry notes: TLS/QUIC libraries track per-record timings for performance analysis.
 
 // ### `Stage` enum with `Display`
@@ -28,6 +29,22 @@ pub enum Stage {
     Digest,
 }
 
+impl Stage {
+    /// Every stage, in declaration order (the order summaries print them in).
+    pub const ALL: [Stage; 10] = [
+        Stage::Read,
+        Stage::Write,
+        Stage::Encode,
+        Stage::Decode,
+        Stage::Compress,
+        Stage::Decompress,
+        Stage::Encrypt,
+        Stage::Decrypt,
+        Stage::Validate,
+        Stage::Digest,
+    ];
+}
+
 impl fmt::Display for Stage {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let name = match self {
@@ -46,6 +63,27 @@ impl fmt::Display for Stage {
     }
 }
 
+/// Parses the lowercase names produced by `Display`.
+impl std::str::FromStr for Stage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "read"       => Ok(Stage::Read),
+            "write"      => Ok(Stage::Write),
+            "encode"     => Ok(Stage::Encode),
+            "decode"     => Ok(Stage::Decode),
+            "compress"   => Ok(Stage::Compress),
+            "decompress" => Ok(Stage::Decompress),
+            "encrypt"    => Ok(Stage::Encrypt),
+            "decrypt"    => Ok(Stage::Decrypt),
+            "validate"   => Ok(Stage::Validate),
+            "digest"     => Ok(Stage::Digest),
+            other        => Err(format!("unknown stage: {other}")),
+        }
+    }
+}
+
 // ### Benefits
 // - **Type safety**: we call `Stage::Encrypt` instead of `"encrypt"`.  
 // - **Human‑readable output**: `format!("{}", Stage::Encrypt)` → `"encrypt"`.  
@@ -75,7 +113,7 @@ impl StageTimes {
     //     *self.times.entry(stage).or_insert(Duration::ZERO) += dur;
     // }
     pub fn add(&mut self, stage: Stage, dur: Duration) {
-        self.times.insert(stage, dur);
+        *self.times.entry(stage).or_insert(Duration::ZERO) += dur;
     }
 
 
@@ -149,13 +187,9 @@ impl StageTimes {
         }
     }
 
+    /// One line per stage in [`Stage::ALL`] order; stages never timed show as zero.
     pub fn summary(&self) -> String {
-        let mut out = String::new();
-        out.push_str("=== Stage Times Summary ===\n");
-        for (stage, dur) in &self.times {
-            out.push_str(&format!("{:?}: {:?}\n", stage, dur));
-        }
-        out
+        self.to_string()
     }
 
     // println!("{}", st.summary());
@@ -168,8 +202,8 @@ impl StageTimes {
 impl fmt::Display for StageTimes {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "=== Stage Times Summary ===")?;
-        for (stage, dur) in &self.times {
-            writeln!(f, "{:?}: {:?}", stage, dur)?;
+        for stage in Stage::ALL {
+            writeln!(f, "{:?}: {:?}", stage, self.get(stage))?;
         }
         Ok(())
     }
@@ -0,0 +1,364 @@
+// ## 📂 File: `src/testvectors.rs`
+//! Canonical test vectors for cross-implementation conformance.
+//!
+//! Encryption is deterministic per (key, salt, segment_index, frame_index),
+//! so a fixed key, header and plaintext always produce the same stream. A
+//! vector records those inputs together with what they must produce:
+//!
+//! - the complete encrypted stream (hex)
+//! - the nonce of every sealed frame
+//! - the ciphertext digest of every segment (as in a `DigestManifest`)
+//!
+//! [`TestVectorWriter`] builds vectors; [`verify_test_vector`] re-runs the
+//! pipeline from a vector file's inputs and byte-compares. Vectors serialize
+//! as JSON with integers for algorithm ids and hex for byte strings, so a
+//! mirror implementation can read them without this crate.
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{HEADER_V1, MAGIC_RSE1};
+use crate::crypto::{derive_nonce_12_tls_style, frame_nonce_counter};
+use crate::headers::HeaderV1;
+use crate::stream_v2::core::{ApiConfig, DecryptParams, EncryptParams, decrypt_stream_v2, encrypt_stream_v2};
+use crate::stream_v2::framing::{FrameType, decode::parse_frame_header};
+use crate::stream_v2::io::{InputSource, OutputSink};
+use crate::stream_v2::manifest::{SegmentDigestEntry, export_manifest};
+use crate::stream_v2::segmenting::{SegmentHeader, decode_segment_header, types::SegmentFlags};
+use crate::types::StreamError;
+
+/// Vector file format version.
+pub const TEST_VECTOR_VERSION: u16 = 1;
+
+/// Header fields a vector fixes; the rest are zero (magic and version implied).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VectorHeader {
+    pub alg_profile: u16,
+    pub cipher: u16,
+    pub hkdf_prf: u16,
+    pub compression: u16,
+    pub strategy: u16,
+    pub aad_domain: u16,
+    pub flags: u16,
+    pub chunk_size: u32,
+    pub plaintext_size: u64,
+    pub dict_id: u32,
+    /// 16 bytes, hex.
+    pub salt: String,
+    pub key_id: u32,
+}
+
+impl VectorHeader {
+    pub fn from_header(h: &HeaderV1) -> Self {
+        Self {
+            alg_profile: h.alg_profile,
+            cipher: h.cipher,
+            hkdf_prf: h.hkdf_prf,
+            compression: h.compression,
+            strategy: h.strategy,
+            aad_domain: h.aad_domain,
+            flags: h.flags,
+            chunk_size: h.chunk_size,
+            plaintext_size: h.plaintext_size,
+            dict_id: h.dict_id,
+            salt: hex::encode(h.salt),
+            key_id: h.key_id,
+        }
+    }
+
+    pub fn to_header(&self) -> Result<HeaderV1, StreamError> {
+        let salt: [u8; 16] = decode_hex("salt", &self.salt)?
+            .try_into()
+            .map_err(|_| StreamError::Validation("test vector salt must be 16 bytes".into()))?;
+        Ok(HeaderV1 {
+            magic: MAGIC_RSE1,
+            version: HEADER_V1,
+            alg_profile: self.alg_profile,
+            cipher: self.cipher,
+            hkdf_prf: self.hkdf_prf,
+            compression: self.compression,
+            strategy: self.strategy,
+            aad_domain: self.aad_domain,
+            flags: self.flags,
+            chunk_size: self.chunk_size,
+            plaintext_size: self.plaintext_size,
+            crc32: 0,
+            dict_id: self.dict_id,
+            salt,
+            key_id: self.key_id,
+            parallel_hint: 0,
+            enc_time_ns: 0,
+            reserved: [0u8; 8],
+        })
+    }
+}
+
+/// Nonce of one sealed frame (terminators are not sealed and have none).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrameNonce {
+    pub segment_index: u32,
+    pub frame_index: u32,
+    /// Raw `FrameType` id.
+    pub frame_type: u16,
+    /// 12 bytes, hex.
+    pub nonce: String,
+}
+
+/// What a vector's inputs must produce.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VectorOutput {
+    pub frames: Vec<FrameNonce>,
+    pub segment_digests: Vec<SegmentDigestEntry>,
+    /// Complete encrypted stream, hex.
+    pub stream: String,
+}
+
+/// One conformance vector: inputs plus, once generated, the expected output.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestVector {
+    pub version: u16,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Hex.
+    pub master_key: String,
+    pub header: VectorHeader,
+    /// Explicit frame size; `null` lets the encoder pick one from `chunk_size`.
+    #[serde(default)]
+    pub frame_size: Option<usize>,
+    /// Plaintext segments, hex. All but the last are exactly `chunk_size` bytes.
+    pub segments: Vec<String>,
+    /// `null` in a vector that only fixes inputs (filled in by [`TestVectorWriter::write`]).
+    #[serde(default)]
+    pub expected: Option<VectorOutput>,
+}
+
+impl TestVector {
+    pub fn to_json(&self) -> Result<String, StreamError> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| StreamError::Validation(format!("test vector encode failed: {}", e)))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, StreamError> {
+        let vector: Self = serde_json::from_str(json)
+            .map_err(|e| StreamError::Validation(format!("test vector decode failed: {}", e)))?;
+        if vector.version != TEST_VECTOR_VERSION {
+            return Err(StreamError::Validation(format!("unsupported test vector version {}", vector.version)));
+        }
+        Ok(vector)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, StreamError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Writer holding this vector's inputs.
+    pub fn writer(&self) -> Result<TestVectorWriter, StreamError> {
+        let mut writer = TestVectorWriter::new(&self.name, &decode_hex("master_key", &self.master_key)?, self.header.to_header()?)
+            .with_description(&self.description);
+        writer.frame_size = self.frame_size;
+        for segment in &self.segments {
+            writer = writer.segment(decode_hex("segment", segment)?);
+        }
+        Ok(writer)
+    }
+}
+
+/// Builds canonical vectors from a fixed key, header and plaintext segments.
+#[derive(Debug, Clone)]
+pub struct TestVectorWriter {
+    name: String,
+    description: String,
+    master_key: Vec<u8>,
+    header: HeaderV1,
+    frame_size: Option<usize>,
+    segments: Vec<Vec<u8>>,
+}
+
+impl TestVectorWriter {
+    /// `header.salt` is used as is, degenerate or not: vectors are not for real keys.
+    pub fn new(name: impl Into<String>, master_key: &[u8], header: HeaderV1) -> Self {
+        Self {
+            name: name.into(),
+            description: String::new(),
+            master_key: master_key.to_vec(),
+            header,
+            frame_size: None,
+            segments: Vec::new(),
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Pin the frame size instead of deriving it from `chunk_size`.
+    pub fn with_frame_size(mut self, frame_size: usize) -> Self {
+        self.frame_size = Some(frame_size);
+        self
+    }
+
+    /// Append one plaintext segment.
+    pub fn segment(mut self, plaintext: impl Into<Vec<u8>>) -> Self {
+        self.segments.push(plaintext.into());
+        self
+    }
+
+    /// Encrypt the segments and record the expected output.
+    ///
+    /// Fails when a segment other than the last is not exactly `chunk_size`
+    /// bytes (the stream would be cut differently), or the last one is empty.
+    pub fn build(&self) -> Result<TestVector, StreamError> {
+        let chunk_size = self.header.chunk_size as usize;
+        if let Some((last, full)) = self.segments.split_last() {
+            if let Some(i) = full.iter().position(|s| s.len() != chunk_size) {
+                return Err(StreamError::Validation(format!(
+                    "test vector segment {i} has {} bytes, expected chunk_size {chunk_size}",
+                    full[i].len()
+                )));
+            }
+            if last.is_empty() || last.len() > chunk_size {
+                return Err(StreamError::Validation(format!(
+                    "last test vector segment has {} bytes, expected 1..={chunk_size}",
+                    last.len()
+                )));
+            }
+        }
+
+        let stream = self.encrypt()?;
+        Ok(TestVector {
+            version: TEST_VECTOR_VERSION,
+            name: self.name.clone(),
+            description: self.description.clone(),
+            master_key: hex::encode(&self.master_key),
+            header: VectorHeader::from_header(&self.header),
+            frame_size: self.frame_size,
+            segments: self.segments.iter().map(hex::encode).collect(),
+            expected: Some(VectorOutput {
+                frames: frame_nonces(&self.header, &stream)?,
+                segment_digests: export_manifest(InputSource::Memory(stream.clone()))?.segments,
+                stream: hex::encode(&stream),
+            }),
+        })
+    }
+
+    /// [`TestVectorWriter::build`], then write the vector as JSON to `path`.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<TestVector, StreamError> {
+        let vector = self.build()?;
+        fs::write(path, vector.to_json()? + "\n")?;
+        Ok(vector)
+    }
+
+    fn plaintext(&self) -> Vec<u8> {
+        self.segments.concat()
+    }
+
+    fn encrypt(&self) -> Result<Vec<u8>, StreamError> {
+        let mut params = EncryptParams::new(self.header).with_deterministic_salt();
+        params.frame_size = self.frame_size;
+        let snapshot = encrypt_stream_v2(
+            InputSource::Memory(self.plaintext()),
+            OutputSink::Memory,
+            &self.master_key,
+            params,
+            ApiConfig::with_buf_enabled(),
+        )?;
+        snapshot.output.ok_or(StreamError::PipelineError("memory sink captured no output"))
+    }
+}
+
+/// 🧪 Re-run a vector file's inputs and compare with its recorded output
+///
+/// Fails with `StreamError::Validation` naming the first difference, or when
+/// the file records no output. The recorded stream must also decrypt back to
+/// the segments.
+pub fn verify_test_vector(path: impl AsRef<Path>) -> Result<(), StreamError> {
+    let recorded = TestVector::load(path)?;
+    let expected = recorded
+        .expected
+        .as_ref()
+        .ok_or_else(|| StreamError::Validation(format!("test vector {} records no output", recorded.name)))?;
+    let writer = recorded.writer()?;
+    let actual = writer.build()?.expected.expect("build records output");
+
+    let expected_stream = decode_hex("stream", &expected.stream)?;
+    let actual_stream = decode_hex("stream", &actual.stream)?;
+    if let Some(at) = expected_stream.iter().zip(&actual_stream).position(|(a, b)| a != b) {
+        return Err(StreamError::Validation(format!("test vector {}: stream differs at byte {at}", recorded.name)));
+    }
+    if expected_stream.len() != actual_stream.len() {
+        return Err(StreamError::Validation(format!(
+            "test vector {}: stream is {} bytes, expected {}",
+            recorded.name,
+            actual_stream.len(),
+            expected_stream.len()
+        )));
+    }
+    if expected.frames != actual.frames {
+        return Err(StreamError::Validation(format!("test vector {}: frame nonces differ", recorded.name)));
+    }
+    if expected.segment_digests != actual.segment_digests {
+        return Err(StreamError::Validation(format!("test vector {}: segment digests differ", recorded.name)));
+    }
+
+    let decrypted = decrypt_stream_v2(
+        InputSource::Memory(expected_stream),
+        OutputSink::Memory,
+        &writer.master_key,
+        DecryptParams::new(),
+        ApiConfig::with_buf_enabled(),
+    )?
+    .output
+    .unwrap_or_default();
+    if decrypted != writer.plaintext() {
+        return Err(StreamError::Validation(format!("test vector {}: stream does not decrypt to the segments", recorded.name)));
+    }
+    Ok(())
+}
+
+/// Nonce of every sealed frame, in stream order up to the final segment.
+fn frame_nonces(header: &HeaderV1, stream: &[u8]) -> Result<Vec<FrameNonce>, StreamError> {
+    let mut frames = Vec::new();
+    let mut offset = header.encoded_len();
+    loop {
+        let segment = stream
+            .get(offset..)
+            .ok_or_else(|| StreamError::Validation("stream ends before the final segment".into()))?;
+        let segment_header = decode_segment_header(segment).map_err(StreamError::Segment)?;
+        let wire_end = segment_header.total_len().map_err(StreamError::Segment)?;
+        let wire = segment
+            .get(SegmentHeader::LEN..wire_end)
+            .ok_or_else(|| StreamError::Validation("segment wire is truncated".into()))?;
+
+        let mut frame_offset = 0;
+        while frame_offset < wire.len() {
+            let frame = parse_frame_header(&wire[frame_offset..]).map_err(StreamError::Frame)?;
+            if frame.frame_type != FrameType::Terminator {
+                let nonce = derive_nonce_12_tls_style(
+                    &header.salt,
+                    frame_nonce_counter(frame.segment_index, frame.frame_index),
+                )
+                .map_err(StreamError::Nonce)?;
+                frames.push(FrameNonce {
+                    segment_index: frame.segment_index,
+                    frame_index: frame.frame_index,
+                    frame_type: frame.frame_type as u16,
+                    nonce: hex::encode(nonce),
+                });
+            }
+            frame_offset += frame.total_len().map_err(StreamError::Frame)?;
+        }
+
+        offset += wire_end;
+        if segment_header.flags.contains(SegmentFlags::FINAL_SEGMENT) {
+            return Ok(frames);
+        }
+    }
+}
+
+fn decode_hex(field: &str, text: &str) -> Result<Vec<u8>, StreamError> {
+    hex::decode(text).map_err(|e| StreamError::Validation(format!("test vector {field} is not hex: {e}")))
+}
@@ -1,9 +1,9 @@
 use std::io;
 use crate::{
     compression::CompressionError, 
-    crypto::{AadError, CryptoError, NonceError}, 
+    crypto::{AadError, CryptoError, DigestError, NonceError}, 
     headers::HeaderError, 
-    stream_v2::{compression_worker::CompressionWorkerError, framing::FrameError, segment_worker::SegmentWorkerError, segmenting::types::SegmentError}
+    stream_v2::{compression_worker::CompressionWorkerError, frame_worker::FrameWorkerError, framing::FrameError, segment_worker::SegmentWorkerError, segmenting::types::SegmentError}
 };
 
 
@@ -42,12 +42,33 @@ pub enum StreamError {
     /// Nonce derivation error (policy or calculation failure).
     Nonce(NonceError),
 
+    /// No master key registered for the stream header's `key_id`.
+    UnknownKeyId { key_id: u32 },
+
+    /// CRC32 of the decrypted plaintext differs from the one recorded at encryption (`HAS_CRC32`).
+    ChecksumMismatch { expected: u32, actual: u32 },
+
+    /// Decrypted plaintext is shorter (or longer) than the authenticated total length (`HAS_TOTAL_LEN`).
+    TruncatedStream { expected: u64, got: u64 },
+
+    /// BLAKE3 of the decrypted plaintext differs from the whole-stream digest (`HAS_FINAL_DIGEST`).
+    StreamDigestMismatch,
+
+    /// More out-of-order segments waited behind segment `waiting_for` than an ordered writer buffers.
+    ReorderBufferOverflow { waiting_for: u32, pending: usize, limit: usize },
 
     /// Pipeline error for pipelining Segment
     PipelineError(&'static str),
 
+    /// The progress callback asked the pipeline to stop (`ControlFlow::Break`).
+    Cancelled,
+
     /// Generic high-level validation with a descriptive message.
     Validation(String),
+
+    /// `source` was raised while reading the stream at byte `offset`: the start
+    /// of the stream header or segment header being read (`PayloadReader::position`).
+    At { offset: u64, source: Box<StreamError> },
 }
 
 impl std::fmt::Display for StreamError {
@@ -55,6 +76,7 @@ impl std::fmt::Display for StreamError {
         match self {
             StreamError::Io(e) => write!(f, "I/O error: {}", e),
             StreamError::PipelineError(msg) => write!(f, "pipeline error: {}", msg),
+            StreamError::Cancelled => write!(f, "cancelled by the progress callback"),
             
             StreamError::Aad(e) => write!(f, "aad error: {}", e),
             StreamError::Header(e) => write!(f, "header error: {}", e),
@@ -65,13 +87,325 @@ impl std::fmt::Display for StreamError {
             StreamError::Crypto(e) => write!(f, "crypto error: {}", e),
             StreamError::Compression(e) => write!(f, "compression error: {}", e),
             StreamError::Nonce(e) => write!(f, "nonce error: {}", e),
+            StreamError::UnknownKeyId { key_id } => write!(f, "no master key for key_id {}", key_id),
+            StreamError::ChecksumMismatch { expected, actual } => {
+                write!(f, "plaintext checksum mismatch: expected {:08x}, got {:08x}", expected, actual)
+            }
+            StreamError::TruncatedStream { expected, got } => {
+                write!(f, "truncated stream: expected {} plaintext bytes, got {}", expected, got)
+            }
+            StreamError::StreamDigestMismatch => write!(f, "whole-stream digest mismatch"),
+            StreamError::ReorderBufferOverflow { waiting_for, pending, limit } => write!(
+                f,
+                "reorder buffer overflow: {} segments pending behind segment {} (limit {})",
+                pending, waiting_for, limit
+            ),
 
             StreamError::Validation(msg) => write!(f, "validation error: {}", msg),
+            StreamError::At { offset, source } => write!(f, "at byte {}: {}", offset, source),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StreamError::Io(e) => Some(e),
+            StreamError::Aad(e) => Some(e),
+            StreamError::Header(e) => Some(e),
+            StreamError::SegmentWorker(e) => Some(e),
+            StreamError::CompressionWorker(e) => Some(e),
+            StreamError::Segment(e) => Some(e),
+            StreamError::Frame(e) => Some(e),
+            StreamError::Crypto(e) => Some(e),
+            StreamError::Compression(e) => Some(e),
+            StreamError::Nonce(e) => Some(e),
+            StreamError::At { source, .. } => Some(source.as_ref()),
+            StreamError::UnknownKeyId { .. }
+            | StreamError::ChecksumMismatch { .. }
+            | StreamError::TruncatedStream { .. }
+            | StreamError::StreamDigestMismatch
+            | StreamError::ReorderBufferOverflow { .. }
+            | StreamError::PipelineError(_)
+            | StreamError::Cancelled
+            | StreamError::Validation(_) => None,
+        }
+    }
+}
+
+/// Stable, machine-readable class of a [`StreamError`] (see [`StreamError::code`]).
+///
+/// - The `ERR_*` names from [`ErrorCode::as_str`] never change once released;
+///   metrics and retry policies may key on them.
+/// - New codes may be added, so matches need a wildcard arm.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    Io,
+    HeaderTruncated,
+    HeaderMagic,
+    HeaderCrc,
+    HeaderVersion,
+    HeaderAlgorithm,
+    HeaderSalt,
+    HeaderChunkSize,
+    HeaderReserved,
+    HeaderInvalid,
+    Aad,
+    KeyLen,
+    WrongKey,
+    UnknownKeyId,
+    CryptoAlgorithm,
+    Nonce,
+    Crypto,
+    AuthTag,
+    DigestMismatch,
+    Digest,
+    ChecksumMismatch,
+    Truncated,
+    SegmentMalformed,
+    SegmentTooLarge,
+    FrameMalformed,
+    FrameTooLarge,
+    FrameMissing,
+    CodecUnsupported,
+    Dictionary,
+    CompressionLimit,
+    Compression,
+    Checkpoint,
+    ReorderOverflow,
+    Pipeline,
+    Cancelled,
+    Validation,
+}
+
+impl ErrorCode {
+    /// The stable `ERR_*` name.
+    pub fn as_str(&self) -> &'static str {
+        use ErrorCode::*;
+        match self {
+            Io => "ERR_IO",
+            HeaderTruncated => "ERR_HEADER_TRUNCATED",
+            HeaderMagic => "ERR_HEADER_MAGIC",
+            HeaderCrc => "ERR_HEADER_CRC",
+            HeaderVersion => "ERR_HEADER_VERSION",
+            HeaderAlgorithm => "ERR_HEADER_ALGORITHM",
+            HeaderSalt => "ERR_HEADER_SALT",
+            HeaderChunkSize => "ERR_HEADER_CHUNK_SIZE",
+            HeaderReserved => "ERR_HEADER_RESERVED",
+            HeaderInvalid => "ERR_HEADER_INVALID",
+            Aad => "ERR_AAD",
+            KeyLen => "ERR_KEY_LEN",
+            WrongKey => "ERR_WRONG_KEY",
+            UnknownKeyId => "ERR_UNKNOWN_KEY_ID",
+            CryptoAlgorithm => "ERR_CRYPTO_ALGORITHM",
+            Nonce => "ERR_NONCE",
+            Crypto => "ERR_CRYPTO",
+            AuthTag => "ERR_AUTH_TAG",
+            DigestMismatch => "ERR_DIGEST_MISMATCH",
+            Digest => "ERR_DIGEST",
+            ChecksumMismatch => "ERR_CHECKSUM_MISMATCH",
+            Truncated => "ERR_TRUNCATED",
+            SegmentMalformed => "ERR_SEGMENT_MALFORMED",
+            SegmentTooLarge => "ERR_SEGMENT_TOO_LARGE",
+            FrameMalformed => "ERR_FRAME_MALFORMED",
+            FrameTooLarge => "ERR_FRAME_TOO_LARGE",
+            FrameMissing => "ERR_FRAME_MISSING",
+            CodecUnsupported => "ERR_CODEC_UNSUPPORTED",
+            Dictionary => "ERR_DICTIONARY",
+            CompressionLimit => "ERR_COMPRESSION_LIMIT",
+            Compression => "ERR_COMPRESSION",
+            Checkpoint => "ERR_CHECKPOINT",
+            ReorderOverflow => "ERR_REORDER_OVERFLOW",
+            Pipeline => "ERR_PIPELINE",
+            Cancelled => "ERR_CANCELLED",
+            Validation => "ERR_VALIDATION",
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl StreamError {
+    /// Attach the stream `offset` the error was raised at; an error that
+    /// already carries one keeps it.
+    pub fn at(self, offset: u64) -> Self {
+        match self {
+            located @ StreamError::At { .. } => located,
+            source => StreamError::At { offset, source: Box::new(source) },
+        }
+    }
+
+    /// Stream offset attached with [`StreamError::at`], if any.
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            StreamError::At { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+
+    /// The error without its `At` location.
+    pub fn root(&self) -> &StreamError {
+        match self {
+            StreamError::At { source, .. } => source.root(),
+            e => e,
+        }
+    }
+
+    /// Owned [`StreamError::root`].
+    pub fn into_root(self) -> StreamError {
+        match self {
+            StreamError::At { source, .. } => source.into_root(),
+            e => e,
+        }
+    }
+
+    /// The frame worker failure behind this error, without its frame tag:
+    /// tells an authentication failure from a malformed or truncated frame.
+    pub fn frame_failure(&self) -> Option<&FrameWorkerError> {
+        match self.root() {
+            StreamError::SegmentWorker(SegmentWorkerError::FrameWorkerError(e)) => Some(e.untagged()),
+            _ => None,
+        }
+    }
+
+    /// Stable code of this error, looking through wrapper variants
+    /// (`SegmentWorker`, `CompressionWorker`, frame tags, `At`) to the cause.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            StreamError::Io(_) => ErrorCode::Io,
+            StreamError::Aad(_) => ErrorCode::Aad,
+            StreamError::Header(e) => header_code(e),
+            StreamError::SegmentWorker(e) => segment_worker_code(e),
+            StreamError::CompressionWorker(e) => compression_worker_code(e),
+            StreamError::Segment(e) => segment_code(e),
+            StreamError::Frame(e) => frame_code(e),
+            StreamError::Crypto(e) => crypto_code(e),
+            StreamError::Compression(e) => compression_code(e),
+            StreamError::Nonce(_) => ErrorCode::Nonce,
+            StreamError::UnknownKeyId { .. } => ErrorCode::UnknownKeyId,
+            StreamError::ChecksumMismatch { .. } => ErrorCode::ChecksumMismatch,
+            StreamError::TruncatedStream { .. } => ErrorCode::Truncated,
+            StreamError::StreamDigestMismatch => ErrorCode::DigestMismatch,
+            StreamError::ReorderBufferOverflow { .. } => ErrorCode::ReorderOverflow,
+            StreamError::PipelineError(_) => ErrorCode::Pipeline,
+            StreamError::Cancelled => ErrorCode::Cancelled,
+            StreamError::Validation(_) => ErrorCode::Validation,
+            StreamError::At { source, .. } => source.code(),
+        }
+    }
+}
+
+fn header_code(e: &HeaderError) -> ErrorCode {
+    use HeaderError::*;
+    match e {
+        BufferTooShort { .. } => ErrorCode::HeaderTruncated,
+        InvalidMagic { .. } => ErrorCode::HeaderMagic,
+        InvalidCrc32 { .. } => ErrorCode::HeaderCrc,
+        InvalidVersion { .. } | UnsupportedVersion { .. } => ErrorCode::HeaderVersion,
+        UnknownCipherSuite { .. }
+        | UnknownHkdfPrf { .. }
+        | UnknownCompression { .. }
+        | UnknownStrategy { .. }
+        | UnknownAlgProfile { .. }
+        | UnknownAadDomain { .. }
+        | AlgProfileCipherMismatch { .. }
+        | AlgProfilePrfMismatch { .. } => ErrorCode::HeaderAlgorithm,
+        InvalidSalt { .. } => ErrorCode::HeaderSalt,
+        InvalidChunkSizeZero | InvalidChunkSizeTooLarge { .. } => ErrorCode::HeaderChunkSize,
+        ReservedBytesNonZero { .. } | UnknownFlags { .. } | UnknownExtensionFlags { .. } => ErrorCode::HeaderReserved,
+        DictUsedButMissingId | MalformedExtensions(_) | Validation(_) => ErrorCode::HeaderInvalid,
+    }
+}
+
+fn crypto_code(e: &CryptoError) -> ErrorCode {
+    use CryptoError::*;
+    match e {
+        UnsupportedCipher { .. } | UnsupportedPrf { .. } | PrfMismatch { .. } => ErrorCode::CryptoAlgorithm,
+        InvalidKeyLen { .. } => ErrorCode::KeyLen,
+        InvalidNonceLen { .. } | NonceSpaceExhausted { .. } => ErrorCode::Nonce,
+        TagMismatch => ErrorCode::AuthTag,
+        KeyConfirmationFailed => ErrorCode::WrongKey,
+        Failure(_) | Format(_) => ErrorCode::Crypto,
+    }
+}
+
+fn digest_code(e: &DigestError) -> ErrorCode {
+    match e {
+        DigestError::DigestMismatch => ErrorCode::DigestMismatch,
+        _ => ErrorCode::Digest,
+    }
+}
+
+fn segment_code(e: &SegmentError) -> ErrorCode {
+    match e {
+        SegmentError::BytesLenTooLarge { .. } | SegmentError::WireLenTooLarge { .. } => ErrorCode::SegmentTooLarge,
+        _ => ErrorCode::SegmentMalformed,
+    }
+}
+
+fn frame_code(e: &FrameError) -> ErrorCode {
+    match e {
+        FrameError::CiphertextTooLarge { .. } => ErrorCode::FrameTooLarge,
+        _ => ErrorCode::FrameMalformed,
+    }
+}
+
+fn frame_worker_code(e: &FrameWorkerError) -> ErrorCode {
+    match e {
+        FrameWorkerError::AtFrame { source, .. } => frame_worker_code(source),
+        FrameWorkerError::Crypto(e) => crypto_code(e),
+        FrameWorkerError::Nonce(_) => ErrorCode::Nonce,
+        FrameWorkerError::Aad(_) => ErrorCode::Aad,
+        FrameWorkerError::Framing(e) => frame_code(e),
+        FrameWorkerError::AuthenticationFailed { .. } => ErrorCode::AuthTag,
+        FrameWorkerError::MalformedFrameHeader(e) => frame_code(e),
+        FrameWorkerError::Truncated { .. } => ErrorCode::Truncated,
+        FrameWorkerError::CryptoFailure(_) => ErrorCode::Crypto,
+        FrameWorkerError::InvalidInput(_) | FrameWorkerError::InvalidHeader => ErrorCode::FrameMalformed,
+        FrameWorkerError::WorkerDisconnected | FrameWorkerError::WorkerMissing | FrameWorkerError::WorkerPanicked(_) => {
+            ErrorCode::Pipeline
         }
     }
 }
 
-impl std::error::Error for StreamError {}
+fn segment_worker_code(e: &SegmentWorkerError) -> ErrorCode {
+    match e {
+        SegmentWorkerError::FrameWorkerError(e) => frame_worker_code(e),
+        SegmentWorkerError::SegmentError(e) => segment_code(e),
+        SegmentWorkerError::DigestError(e) => digest_code(e),
+        SegmentWorkerError::FramingError(e) => frame_code(e),
+        SegmentWorkerError::CryptoError(e) => crypto_code(e),
+        SegmentWorkerError::MissingDigestFrame | SegmentWorkerError::MissingTerminatorFrame => ErrorCode::FrameMissing,
+        SegmentWorkerError::InvalidSegment(_) | SegmentWorkerError::UnexpectedFrame { .. } => ErrorCode::SegmentMalformed,
+        SegmentWorkerError::CheckpointError(_) | SegmentWorkerError::CheckpointRestoreFailed(_) => ErrorCode::Checkpoint,
+        SegmentWorkerError::StateError(_) | SegmentWorkerError::WorkerPanicked(_) => ErrorCode::Pipeline,
+    }
+}
+
+fn compression_code(e: &CompressionError) -> ErrorCode {
+    use CompressionError::*;
+    match e {
+        UnsupportedCodec { .. } => ErrorCode::CodecUnsupported,
+        InvalidDictionary { .. } => ErrorCode::Dictionary,
+        ChunkTooLarge { .. } => ErrorCode::CompressionLimit,
+        CodecInitFailed { .. } | CodecProcessFailed { .. } | TrailingData { .. } | StateError(_) => ErrorCode::Compression,
+    }
+}
+
+fn compression_worker_code(e: &CompressionWorkerError) -> ErrorCode {
+    match e {
+        CompressionWorkerError::Compression(e) => compression_code(e),
+        CompressionWorkerError::DictionaryRequired { .. } | CompressionWorkerError::UnexpectedDictionary => {
+            ErrorCode::Dictionary
+        }
+        CompressionWorkerError::StateError(_) => ErrorCode::Compression,
+    }
+}
 
 impl From<io::Error> for StreamError {
     fn from(e: io::Error) -> Self {
@@ -79,3 +413,15 @@ impl From<io::Error> for StreamError {
         StreamError::Io(e)
     }
 }
+
+impl From<SegmentWorkerError> for StreamError {
+    fn from(e: SegmentWorkerError) -> Self {
+        StreamError::SegmentWorker(e)
+    }
+}
+
+impl From<CompressionWorkerError> for StreamError {
+    fn from(e: CompressionWorkerError) -> Self {
+        StreamError::CompressionWorker(e)
+    }
+}
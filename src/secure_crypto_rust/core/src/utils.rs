@@ -1,35 +1,107 @@
+use std::any::Any;
 use std::fmt;
 use num_enum::TryFromPrimitive;
 
 use crate::{constants::{ALLOWED_CHUNK_SIZES, ChunkPolicy, DEFAULT_CHUNK_SIZE, MAX_CHUNK_SIZE, RoundingBase}};
 
+/// Checksum algorithm ids.
 #[repr(u16)]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, TryFromPrimitive)]
 pub enum ChecksumAlg {
     Crc32   = 0x0001,
     Blake3   = 0x0201, // UN-KEYED Blake3
 }
-pub fn compute_checksum(data: &[u8], alg: Option<ChecksumAlg>) -> u32 {
+
+impl ChecksumAlg {
+    /// Length of the canonical byte encoding of a checksum of this algorithm.
+    pub const fn encoded_len(self) -> usize {
+        match self {
+            ChecksumAlg::Crc32 => 4,
+            ChecksumAlg::Blake3 => 32,
+        }
+    }
+}
+
+/// A computed checksum, tagged with its algorithm.
+///
+/// Values of different algorithms never compare equal, so a CRC32 can't be
+/// checked against a truncated Blake3 (or the other way around).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Checksum {
+    Crc32(u32),
+    Blake3([u8; 32]),
+}
+
+impl Checksum {
+    pub fn alg(&self) -> ChecksumAlg {
+        match self {
+            Checksum::Crc32(_) => ChecksumAlg::Crc32,
+            Checksum::Blake3(_) => ChecksumAlg::Blake3,
+        }
+    }
+
+    /// Recompute over `data` with the same algorithm and compare.
+    pub fn verify(&self, data: &[u8]) -> bool {
+        compute_checksum(data, self.alg()) == *self
+    }
+
+    /// Canonical wire encoding: CRC32 as 4 bytes LE, Blake3 as its 32 raw bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Checksum::Crc32(crc) => crc.to_le_bytes().to_vec(),
+            Checksum::Blake3(hash) => hash.to_vec(),
+        }
+    }
+
+    /// Inverse of [`Checksum::to_bytes`]; `None` if `bytes` is not `alg.encoded_len()` long.
+    pub fn from_bytes(alg: ChecksumAlg, bytes: &[u8]) -> Option<Self> {
+        match alg {
+            ChecksumAlg::Crc32 => bytes.try_into().ok().map(|b| Checksum::Crc32(u32::from_le_bytes(b))),
+            ChecksumAlg::Blake3 => bytes.try_into().ok().map(Checksum::Blake3),
+        }
+    }
+}
+
+impl fmt::Display for Checksum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Checksum::Crc32(crc) => write!(f, "crc32:{crc:08x}"),
+            Checksum::Blake3(hash) => {
+                f.write_str("blake3:")?;
+                hash.iter().try_for_each(|b| write!(f, "{b:02x}"))
+            }
+        }
+    }
+}
+
+pub fn compute_checksum(data: &[u8], alg: ChecksumAlg) -> Checksum {
     match alg {
-        Some(ChecksumAlg::Crc32)  => compute_crc32(data),
-        // Some(ChecksumAlg::Blake3) => compute_blake3(data), // Its return 32-bytes
-        _                         => compute_crc32(data)
+        ChecksumAlg::Crc32  => Checksum::Crc32(crc32(data)),
+        ChecksumAlg::Blake3 => Checksum::Blake3(*blake3::hash(data).as_bytes()),
     }
 }
 
-fn compute_crc32(data: &[u8]) -> u32 {
+/// Plain CRC32 (IEEE), for fixed 4-byte wire fields.
+pub fn crc32(data: &[u8]) -> u32 {
     use crc32fast::Hasher;
     let mut hasher = Hasher::new();
     hasher.update(data);
     hasher.finalize()
 }
 
-// fn compute_blake3(data: &[u8]) -> [u8; 32] {
-//     use blake3::Hasher;
-//     let mut hasher = Hasher::new();
-//     hasher.update(data);
-//     *hasher.finalize().as_bytes()
-// }
+/// Text of a `catch_unwind` / `JoinHandle::join` panic payload.
+///
+/// `panic!` payloads are a `&str` or a `String`; anything else is reported
+/// as opaque.
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        (*msg).to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "non-string panic payload".into()
+    }
+}
 
 pub fn enum_name_or_hex<T>(raw: T::Primitive) -> String
 where
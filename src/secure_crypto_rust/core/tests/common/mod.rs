@@ -0,0 +1,49 @@
+// # 📂 `tests/common/mod.rs`
+//! Fixtures shared by the integration tests. Each test binary pulls this in
+//! with `mod common;` and uses only what it needs.
+#![allow(dead_code)]
+
+use crypto_core::{
+    stream_v2::{
+        InputSource, OutputSink,
+        core::{ApiConfig, DecryptParams, EncryptParams, decrypt_stream_v2, encrypt_stream_v2},
+    },
+    types::StreamError,
+};
+
+/// Master key every test stream is encrypted under.
+pub fn master_key() -> Vec<u8> {
+    vec![0x11u8; 32]
+}
+
+/// `len` bytes of a repeating byte ramp.
+pub fn plaintext(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i % 251) as u8).collect()
+}
+
+/// Deterministic, single-threaded API config.
+pub fn config() -> ApiConfig {
+    ApiConfig::new(Some(true), None)
+}
+
+/// Encrypts `data` in memory under [`master_key`].
+pub fn try_encrypt(data: &[u8], params: EncryptParams, config: ApiConfig) -> Result<Vec<u8>, StreamError> {
+    let snapshot = encrypt_stream_v2(InputSource::Memory(data.to_vec()), OutputSink::Memory, &master_key(), params, config)?;
+    Ok(snapshot.output.unwrap_or_default())
+}
+
+/// [`try_encrypt`] with the default [`config`], panicking on failure.
+pub fn encrypt(data: &[u8], params: EncryptParams) -> Vec<u8> {
+    try_encrypt(data, params, config()).unwrap()
+}
+
+/// Decrypts an in-memory `stream` under `key`.
+pub fn try_decrypt(stream: Vec<u8>, key: &[u8], params: DecryptParams, config: ApiConfig) -> Result<Vec<u8>, StreamError> {
+    let snapshot = decrypt_stream_v2(InputSource::Memory(stream), OutputSink::Memory, key, params, config)?;
+    Ok(snapshot.output.unwrap_or_default())
+}
+
+/// Decrypts an in-memory `stream` under [`master_key`] with the default [`config`].
+pub fn decrypt(stream: Vec<u8>, params: DecryptParams) -> Result<Vec<u8>, StreamError> {
+    try_decrypt(stream, &master_key(), params, config())
+}
@@ -0,0 +1,134 @@
+// # 📂 `tests/test_aad_binding.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use crypto_core::{
+        compression::CompressionCodec,
+        crypto::{AAD_V2, AAD_V3, AadHeader, CryptoError, aad_version, build_aad},
+        headers::{AadDomain, HeaderV1},
+        stream_v2::{
+            core::{DecryptParams, EncryptParams},
+            frame_worker::FrameWorkerError,
+            segment_worker::SegmentWorkerError,
+            segmenting::{SegmentHeader, decode_segment_header, types::SegmentFlags},
+        },
+        types::StreamError,
+    };
+
+    use crate::common;
+
+    const CHUNK: u32 = 16 * 1024;
+
+    fn header(strict: bool) -> HeaderV1 {
+        let mut header = HeaderV1 { chunk_size: CHUNK, ..HeaderV1::test_header() };
+        header.compression = CompressionCodec::Zstd as u16;
+        header.enable_segment_flags_aad();
+        if strict {
+            header.enable_aad_strict();
+        }
+        header
+    }
+
+    fn encrypt(strict: bool) -> Vec<u8> {
+        common::encrypt(&common::plaintext(3 * CHUNK as usize), EncryptParams::new(header(strict)))
+    }
+
+    fn decrypt(ciphertext: Vec<u8>) -> Result<Vec<u8>, StreamError> {
+        common::decrypt(ciphertext, DecryptParams::new().expect_aad_domain(AadDomain::Generic))
+    }
+
+    /// Overwrites `range` of the stream header, keeping the header CRC consistent.
+    fn patch(ciphertext: &[u8], range: Range<usize>, value: &[u8]) -> Vec<u8> {
+        let mut buf = ciphertext.to_vec();
+        buf[range].copy_from_slice(value);
+        let crc = crc32fast::hash(&buf[0..32]);
+        buf[32..36].copy_from_slice(&crc.to_le_bytes());
+        buf
+    }
+
+    fn assert_auth_failure(result: Result<Vec<u8>, StreamError>, field: &str) {
+        match result {
+            Err(StreamError::SegmentWorker(SegmentWorkerError::FrameWorkerError(e))) => {
+                assert!(matches!(e.untagged(), FrameWorkerError::AuthenticationFailed { .. }), "{field}: {e:?}");
+            }
+            other => panic!("{field}: expected a frame authentication failure, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn codec_fields_are_authenticated() {
+        let ciphertext = encrypt(false);
+        assert!(decrypt(ciphertext.clone()).is_ok());
+
+        // Also part of the HKDF info: the key check sees a different session key first
+        let keyed: [(&str, Range<usize>, Vec<u8>); 3] = [
+            ("compression", 12..14, (CompressionCodec::Lz4 as u16).to_le_bytes().to_vec()),
+            ("aad_domain", 16..18, (AadDomain::FileEnvelope as u16).to_le_bytes().to_vec()),
+            ("chunk_size", 20..24, (2 * CHUNK).to_le_bytes().to_vec()),
+        ];
+        for (field, range, value) in keyed {
+            let result = decrypt(patch(&ciphertext, range, &value));
+            assert!(matches!(result, Err(StreamError::Crypto(CryptoError::KeyConfirmationFailed))), "{field}: {result:?}");
+        }
+        assert_auth_failure(decrypt(patch(&ciphertext, 36..40, &7u32.to_le_bytes())), "dict_id");
+    }
+
+    #[test]
+    fn strict_mode_binds_the_whole_header() {
+        let hint = 9u32.to_le_bytes();
+        let time = 12345u64.to_le_bytes();
+
+        // Advisory fields are outside the default binding
+        let relaxed = encrypt(false);
+        assert!(decrypt(patch(&relaxed, 60..64, &hint)).is_ok());
+        assert!(decrypt(patch(&relaxed, 64..72, &time)).is_ok());
+
+        let strict = encrypt(true);
+        assert!(decrypt(strict.clone()).is_ok());
+        assert_auth_failure(decrypt(patch(&strict, 60..64, &hint)), "parallel_hint");
+        assert_auth_failure(decrypt(patch(&strict, 64..72, &time)), "enc_time_ns");
+    }
+
+    #[test]
+    fn segment_flags_are_authenticated() {
+        let mut ciphertext = encrypt(false);
+        let first = HeaderV1::LEN..HeaderV1::LEN + SegmentHeader::LEN;
+        let header = decode_segment_header(&ciphertext[first.clone()]).unwrap();
+        assert!(!header.flags.contains(SegmentFlags::UNCOMPRESSED));
+
+        // Claim the compressed payload is stored raw; the wire CRC32 still matches
+        let flags = header.flags | SegmentFlags::UNCOMPRESSED;
+        ciphertext[first.start + 22..first.start + 24].copy_from_slice(&flags.bits().to_le_bytes());
+        assert_auth_failure(decrypt(ciphertext), "segment flags");
+    }
+
+    #[test]
+    fn aad_is_versioned() {
+        let frame = AadHeader { frame_type: 0, segment_index: 1, frame_index: 2, plaintext_len: 3, segment_flags: 4 };
+
+        let aad = build_aad(&header(false), &frame).unwrap();
+        assert_eq!(aad.len(), AadHeader::LEN_V3);
+        assert_eq!(aad[0], AAD_V3);
+
+        let strict = build_aad(&header(true), &frame).unwrap();
+        assert_eq!(strict.len(), AadHeader::LEN_V3_STRICT);
+        assert_eq!(strict[0], AAD_V3);
+        assert_ne!(aad, strict[..aad.len()]);
+
+        // Streams without SEGMENT_FLAGS_AAD keep the v2 layout and still open
+        let legacy = HeaderV1 { chunk_size: CHUNK, ..HeaderV1::test_header() };
+        assert_eq!(aad_version(&legacy), AAD_V2);
+        let v2 = build_aad(&legacy, &frame).unwrap();
+        assert_eq!(v2.len(), AadHeader::LEN_V2);
+        assert_eq!(v2[0], AAD_V2);
+        assert_eq!(v2[AadHeader::STREAM_LEN..], aad[AadHeader::STREAM_LEN..AadHeader::LEN_V2]);
+
+        let data = common::plaintext(3 * CHUNK as usize);
+        let ciphertext = common::encrypt(&data, EncryptParams::new(legacy));
+        assert_eq!(decrypt(ciphertext).unwrap(), data);
+    }
+}
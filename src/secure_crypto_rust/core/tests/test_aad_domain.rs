@@ -0,0 +1,83 @@
+// # 📂 `tests/test_aad_domain.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crypto_core::{
+        headers::{AadDomain, HeaderV1, decode_header_le},
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{DecryptParams, EncryptParams, decrypt_stream_v2},
+        },
+        telemetry::TelemetrySnapshot,
+        types::StreamError,
+    };
+
+    use crate::common::{self, master_key};
+
+    const CHUNK: usize = 16 * 1024;
+
+    fn plaintext() -> Vec<u8> {
+        (0..2 * CHUNK + 99).map(|i| (i % 211) as u8).collect()
+    }
+
+    /// A `FileEnvelope` stream, optionally `AAD_STRICT`.
+    fn encrypt(strict: bool) -> Vec<u8> {
+        let mut header = HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() };
+        if strict {
+            header.enable_aad_strict();
+        }
+        common::encrypt(&plaintext(), EncryptParams::new(header).with_aad_domain(AadDomain::FileEnvelope))
+    }
+
+    fn decrypt(stream: Vec<u8>, params: DecryptParams) -> Result<TelemetrySnapshot, StreamError> {
+        decrypt_stream_v2(InputSource::Memory(stream), OutputSink::Memory, &master_key(), params, common::config())
+    }
+
+    #[test]
+    fn the_selected_domain_is_recorded() {
+        let stream = encrypt(false);
+        let header = decode_header_le(&stream[..HeaderV1::LEN]).unwrap();
+        assert_eq!(header.aad_domain, AadDomain::FileEnvelope as u16);
+    }
+
+    #[test]
+    fn strict_streams_reject_another_domain() {
+        let params = DecryptParams::new().expect_aad_domain(AadDomain::PipeEnvelope);
+        match decrypt(encrypt(true), params) {
+            Err(StreamError::Validation(msg)) => {
+                assert!(msg.contains("header aad_domain mismatch: expected PipeEnvelope"), "{msg}");
+            }
+            other => panic!("expected an aad_domain mismatch, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn strict_streams_require_an_expected_domain() {
+        match decrypt(encrypt(true), DecryptParams::new()) {
+            Err(StreamError::Validation(msg)) => {
+                assert!(msg.contains("FileEnvelope") && msg.contains("expect_aad_domain"), "{msg}");
+            }
+            other => panic!("expected a missing-domain error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn strict_streams_in_the_expected_domain_decrypt() {
+        let snapshot = decrypt(encrypt(true), DecryptParams::new().expect_aad_domain(AadDomain::FileEnvelope)).unwrap();
+        assert_eq!(snapshot.output.as_deref(), Some(&plaintext()[..]));
+        assert!(snapshot.warnings.is_empty(), "{:?}", snapshot.warnings);
+    }
+
+    #[test]
+    fn lenient_streams_in_another_domain_decrypt_with_a_warning() {
+        let params = DecryptParams::new().expect_aad_domain(AadDomain::PipeEnvelope);
+        let snapshot = decrypt(encrypt(false), params).unwrap();
+        assert_eq!(snapshot.output.as_deref(), Some(&plaintext()[..]));
+        assert_eq!(snapshot.warnings, ["stream AAD domain is FileEnvelope, expected PipeEnvelope"]);
+
+        let snapshot = decrypt(encrypt(false), DecryptParams::new().expect_aad_domain(AadDomain::FileEnvelope)).unwrap();
+        assert!(snapshot.warnings.is_empty(), "{:?}", snapshot.warnings);
+    }
+}
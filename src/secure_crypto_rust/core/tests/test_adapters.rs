@@ -0,0 +1,106 @@
+// # 📂 `tests/test_adapters.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+
+    use crypto_core::{
+        headers::HeaderV1,
+        stream_v2::{
+            DecryptingReader, EncryptingWriter,
+            segmenting::SegmentHeader,
+            core::{DecryptParams, EncryptParams},
+        },
+    };
+
+    use crate::common::{self, master_key, plaintext};
+
+    fn header() -> HeaderV1 {
+        HeaderV1 {
+            chunk_size: 16 * 1024,
+            ..HeaderV1::test_header()
+        }
+    }
+
+    fn encrypt_with_writer(data: &[u8], write_size: usize) -> Vec<u8> {
+        let params = EncryptParams::new(header());
+        let mut writer = EncryptingWriter::new(Vec::new(), &master_key(), params).unwrap();
+        for chunk in data.chunks(write_size) {
+            writer.write_all(chunk).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn writer_output_decrypts_with_pipeline() {
+        let data = plaintext(100_000);
+        let ciphertext = encrypt_with_writer(&data, 7_000);
+
+        let out = common::decrypt(ciphertext, DecryptParams::new()).expect("pipeline should decrypt writer output");
+        assert_eq!(out, data);
+    }
+
+    #[test]
+    fn pipeline_output_decrypts_with_reader() {
+        let data = plaintext(70_000);
+        let ciphertext = common::encrypt(&data, EncryptParams::new(header()));
+        let mut reader = DecryptingReader::new(ciphertext.as_slice(), &master_key(), DecryptParams::new()).unwrap();
+        assert_eq!(reader.header().unwrap().chunk_size, header().chunk_size);
+
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+        assert_eq!(out, data);
+    }
+
+    #[test]
+    fn writer_reader_roundtrip_small_reads() {
+        let data = plaintext(40_000);
+        let ciphertext = encrypt_with_writer(&data, 1_000);
+
+        let mut reader = DecryptingReader::new(ciphertext.as_slice(), &master_key(), DecryptParams::new()).unwrap();
+        let mut out = Vec::new();
+        let mut buf = [0u8; 333];
+        loop {
+            let n = reader.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            out.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(out, data);
+    }
+
+    #[test]
+    fn writer_matches_pipeline_segment_layout() {
+        // Exactly two full segments: both sides must emit the trailing empty final segment
+        let data = plaintext(2 * header().chunk_size as usize);
+        let ciphertext = encrypt_with_writer(&data, 4096);
+
+        let pipelined = common::encrypt(&data, EncryptParams::new(header()));
+        assert_eq!(ciphertext.len(), pipelined.len());
+    }
+
+    #[test]
+    fn reader_rejects_stream_without_final_segment() {
+        let data = plaintext(20_000);
+        let mut ciphertext = encrypt_with_writer(&data, 20_000);
+        // Drop the trailing empty final segment (header only, no wire)
+        ciphertext.truncate(ciphertext.len() - SegmentHeader::LEN);
+
+        let mut reader = DecryptingReader::new(ciphertext.as_slice(), &master_key(), DecryptParams::new()).unwrap();
+        let mut out = Vec::new();
+        assert!(reader.read_to_end(&mut out).is_err(), "missing final segment must be an error");
+    }
+
+    #[test]
+    fn reader_rejects_wrong_key() {
+        let ciphertext = encrypt_with_writer(&plaintext(5_000), 5_000);
+        let wrong = vec![0x22u8; 32];
+
+        let mut reader = DecryptingReader::new(ciphertext.as_slice(), &wrong, DecryptParams::new()).unwrap();
+        let mut out = Vec::new();
+        assert!(reader.read_to_end(&mut out).is_err());
+    }
+}
@@ -0,0 +1,106 @@
+// # 📂 `tests/test_aead_profiles.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use crypto_core::{
+        crypto::KEY_LEN_32,
+        headers::{AlgProfile, CipherSuite, HeaderV1, HkdfPrf, decode_header_le, encode_header_le},
+        stream_v2::{
+            core::{DecryptParams, EncryptParams},
+            frame_worker::{DecryptFrameInput, FrameInput, decrypt::DecryptFrameWorker, encrypt::EncryptFrameWorker},
+            framing::FrameType,
+            segmenting::types::SegmentFlags,
+        },
+    };
+
+    use crate::common::{self, plaintext};
+
+    fn header_for(profile: AlgProfile, cipher: CipherSuite, prf: HkdfPrf) -> HeaderV1 {
+        HeaderV1 {
+            alg_profile: profile as u16,
+            cipher: cipher as u16,
+            hkdf_prf: prf as u16,
+            chunk_size: 16 * 1024,
+            ..HeaderV1::test_header()
+        }
+    }
+
+    fn encrypt(header: HeaderV1, plaintext: &[u8]) -> Vec<u8> {
+        common::encrypt(plaintext, EncryptParams::new(header))
+    }
+
+    fn decrypt(ciphertext: Vec<u8>) -> Result<Vec<u8>, String> {
+        common::decrypt(ciphertext, DecryptParams::new()).map_err(|e| e.to_string())
+    }
+
+    fn roundtrip(profile: AlgProfile, prf: HkdfPrf) {
+        let plaintext = plaintext(50_000);
+        let ciphertext = encrypt(header_for(profile, CipherSuite::Aes256Gcm, prf), &plaintext);
+
+        let decoded = decode_header_le(&ciphertext[..HeaderV1::LEN]).unwrap();
+        assert_eq!(decoded.cipher, CipherSuite::Aes256Gcm as u16);
+
+        assert_eq!(decrypt(ciphertext).expect("AES stream should decrypt"), plaintext);
+    }
+
+    #[test]
+    fn aes256gcm_hkdf_sha256_roundtrip() {
+        roundtrip(AlgProfile::Aes256GcmHkdfSha256, HkdfPrf::Sha256);
+    }
+
+    #[test]
+    fn aes256gcm_hkdf_sha512_roundtrip() {
+        roundtrip(AlgProfile::Aes256GcmHkdfSha512, HkdfPrf::Sha512);
+    }
+
+    #[test]
+    fn aes_and_chacha_frames_differ_for_same_input() {
+        let key = [0x42u8; KEY_LEN_32];
+        let input = FrameInput {
+            segment_index: 0,
+            frame_index: 0,
+            frame_type: FrameType::Data,
+            plaintext: Bytes::from_static(b"same plaintext, same nonce, same aad"),
+            segment_flags: SegmentFlags::empty(),
+        };
+
+        let aes = header_for(AlgProfile::Aes256GcmHkdfSha256, CipherSuite::Aes256Gcm, HkdfPrf::Sha256);
+        let chacha = header_for(AlgProfile::Chacha20Poly1305HkdfSha256, CipherSuite::Chacha20Poly1305, HkdfPrf::Sha256);
+
+        let aes_frame = EncryptFrameWorker::new(aes, &key).unwrap().encrypt_frame(&input).unwrap();
+        let chacha_frame = EncryptFrameWorker::new(chacha, &key).unwrap().encrypt_frame(&input).unwrap();
+        assert_ne!(aes_frame.ciphertext(), chacha_frame.ciphertext());
+
+        // Each frame only opens under the cipher that sealed it
+        let sealed = DecryptFrameInput { wire: aes_frame.wire, segment_flags: input.segment_flags };
+        assert!(DecryptFrameWorker::new(aes, &key).unwrap().decrypt_frame(sealed.clone()).is_ok());
+        assert!(DecryptFrameWorker::new(chacha, &key).unwrap().decrypt_frame(sealed).is_err());
+    }
+
+    #[test]
+    fn chacha_stream_relabelled_as_aes_fails_authentication() {
+        let plaintext = vec![0x5Au8; 20_000];
+        let mut ciphertext = encrypt(
+            header_for(AlgProfile::Chacha20Poly1305HkdfSha256, CipherSuite::Chacha20Poly1305, HkdfPrf::Sha256),
+            &plaintext,
+        );
+
+        // Rewrite the stream header to claim AES-256-GCM (valid CRC, same payload)
+        let mut header = decode_header_le(&ciphertext[..HeaderV1::LEN]).unwrap();
+        header.alg_profile = AlgProfile::Aes256GcmHkdfSha256 as u16;
+        header.cipher = CipherSuite::Aes256Gcm as u16;
+        ciphertext[..HeaderV1::LEN].copy_from_slice(&encode_header_le(&header).unwrap());
+
+        assert!(decrypt(ciphertext).is_err(), "relabelled stream must fail authentication");
+    }
+
+    #[test]
+    fn header_rejects_profile_cipher_mismatch() {
+        let header = header_for(AlgProfile::Aes256GcmHkdfSha256, CipherSuite::Chacha20Poly1305, HkdfPrf::Sha256);
+        assert!(header.validate().is_err());
+        assert!(encode_header_le(&header).is_err());
+    }
+}
@@ -0,0 +1,103 @@
+// # 📂 `tests/test_async.rs`
+#![cfg(feature = "async")]
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{duplex, AsyncReadExt, AsyncWriteExt};
+
+    use crypto_core::{
+        headers::HeaderV1,
+        stream_v2::{
+            ApiConfig, DecryptParams, EncryptParams, InputSource, OutputSink, decrypt_stream_v2_async,
+            encrypt_stream_v2, encrypt_stream_v2_async,
+        },
+    };
+
+    use crate::common::{master_key, plaintext};
+
+    const CHUNK: usize = 64 * 1024;
+
+    fn params() -> EncryptParams<'static> {
+        EncryptParams::new(HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() })
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn duplex_roundtrip() {
+        let data = plaintext(5 * CHUNK + 123);
+
+        // Small duplex buffers force backpressure on every hop
+        let (mut src_w, src_r) = duplex(8 * 1024);
+        let (enc_w, enc_r) = duplex(8 * 1024);
+        let (dec_w, mut dec_r) = duplex(8 * 1024);
+
+        let feed = {
+            let data = data.clone();
+            tokio::spawn(async move {
+                src_w.write_all(&data).await.unwrap();
+                src_w.shutdown().await.unwrap();
+            })
+        };
+        let collect = tokio::spawn(async move {
+            let mut out = Vec::new();
+            dec_r.read_to_end(&mut out).await.unwrap();
+            out
+        });
+
+        let key = master_key();
+        let (enc, dec) = tokio::join!(
+            encrypt_stream_v2_async(src_r, enc_w, &key, params(), ApiConfig::default()),
+            decrypt_stream_v2_async(enc_r, dec_w, &key, DecryptParams::new(), ApiConfig::default()),
+        );
+        let enc = enc.unwrap();
+        let dec = dec.unwrap();
+        feed.await.unwrap();
+
+        assert_eq!(collect.await.unwrap(), data);
+        assert_eq!(enc.bytes_plaintext, data.len() as u64);
+        assert_eq!(dec.bytes_plaintext, data.len() as u64);
+        assert_eq!(enc.segments_processed, dec.segments_processed);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn telemetry_matches_sync_path() {
+        let data = plaintext(3 * CHUNK + 7);
+
+        let sync = encrypt_stream_v2(
+            InputSource::Memory(data.clone()),
+            OutputSink::Memory,
+            &master_key(),
+            params(),
+            ApiConfig::new(Some(true), None),
+        )
+        .unwrap();
+
+        let mut ciphertext = Vec::new();
+        let asynchronous =
+            encrypt_stream_v2_async(data.as_slice(), &mut ciphertext, &master_key(), params(), ApiConfig::default())
+                .await
+                .unwrap();
+
+        assert_eq!(ciphertext.len(), sync.output.as_ref().unwrap().len());
+        assert_eq!(asynchronous.segments_processed, sync.segments_processed);
+        assert_eq!(asynchronous.bytes_plaintext, sync.bytes_plaintext);
+        assert_eq!(asynchronous.bytes_ciphertext, sync.bytes_ciphertext);
+        assert_eq!(asynchronous.bytes_overhead, sync.bytes_overhead);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn truncated_input_fails() {
+        let mut ciphertext = Vec::new();
+        encrypt_stream_v2_async(&plaintext(2 * CHUNK)[..], &mut ciphertext, &master_key(), params(), ApiConfig::default())
+            .await
+            .unwrap();
+        ciphertext.truncate(ciphertext.len() - 1);
+
+        let mut out = Vec::new();
+        let result =
+            decrypt_stream_v2_async(ciphertext.as_slice(), &mut out, &master_key(), DecryptParams::new(), ApiConfig::default())
+                .await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,105 @@
+// # 📂 `tests/test_auto_strategy.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crypto_core::{
+        headers::{HeaderV1, Strategy},
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{ApiConfig, DecryptParams, EncryptParams, decrypt_stream_v2, encrypt_stream_v2},
+            parallelism::{AUTO_PARALLEL_THRESHOLD, AutoPlan},
+        },
+        telemetry::{EffectiveStrategy, TelemetrySnapshot},
+    };
+
+    use crate::common::{self, master_key, plaintext as data};
+
+    const CHUNK: u32 = 16 * 1024;
+    const THRESHOLD: u64 = 4 * CHUNK as u64;
+
+    fn config() -> ApiConfig {
+        common::config().with_auto_parallel_threshold(THRESHOLD)
+    }
+
+    fn encrypt(input: InputSource) -> TelemetrySnapshot {
+        let header = HeaderV1 { strategy: Strategy::Auto as u16, chunk_size: CHUNK, ..HeaderV1::test_header() };
+        encrypt_stream_v2(input, OutputSink::Memory, &master_key(), EncryptParams::new(header), config()).unwrap()
+    }
+
+    fn roundtrip(snapshot: &TelemetrySnapshot, expected: &[u8]) -> TelemetrySnapshot {
+        let ciphertext = snapshot.output.clone().unwrap();
+        let decrypted = decrypt_stream_v2(
+            InputSource::Memory(ciphertext),
+            OutputSink::Memory,
+            &master_key(),
+            DecryptParams::new(),
+            config(),
+        )
+        .unwrap();
+        assert_eq!(decrypted.output.as_deref(), Some(expected));
+        decrypted
+    }
+
+    fn many_cores() -> bool {
+        std::thread::available_parallelism().is_ok_and(|n| n.get() > 2)
+    }
+
+    #[test]
+    fn plan_follows_size_hint() {
+        assert_eq!(ApiConfig::default().auto_parallel_threshold, AUTO_PARALLEL_THRESHOLD);
+        assert_eq!(AutoPlan::for_size(Some(THRESHOLD - 1), THRESHOLD, CHUNK), AutoPlan::Sequential);
+        assert_eq!(AutoPlan::for_size(Some(THRESHOLD), THRESHOLD, CHUNK), AutoPlan::Parallel);
+        assert_eq!(AutoPlan::for_size(None, THRESHOLD, CHUNK), AutoPlan::Warmup { segments: 4 });
+        assert_eq!(AutoPlan::for_size(None, 1, CHUNK), AutoPlan::Warmup { segments: 1 });
+    }
+
+    #[test]
+    fn small_known_input_runs_single_threaded() {
+        let plaintext = data(CHUNK as usize / 2);
+        let snapshot = encrypt(InputSource::Memory(plaintext.clone()));
+        assert_eq!(snapshot.strategy_effective, Some(EffectiveStrategy::Sequential));
+        assert_eq!(snapshot.cpu_workers, 1);
+
+        let decrypted = roundtrip(&snapshot, &plaintext);
+        assert_eq!(decrypted.cpu_workers, 1);
+    }
+
+    #[test]
+    fn large_known_input_runs_parallel() {
+        let plaintext = data(4 * THRESHOLD as usize);
+        let snapshot = encrypt(InputSource::Memory(plaintext.clone()));
+        if many_cores() {
+            assert_eq!(snapshot.strategy_effective, Some(EffectiveStrategy::Parallel));
+            assert!(snapshot.cpu_workers > 1, "{}", snapshot.cpu_workers);
+        }
+        roundtrip(&snapshot, &plaintext);
+    }
+
+    #[test]
+    fn unknown_size_input_is_promoted_after_warmup() {
+        let plaintext = data(4 * THRESHOLD as usize);
+        let snapshot = encrypt(InputSource::Reader(Box::new(Cursor::new(plaintext.clone()))));
+        assert_eq!(snapshot.strategy_effective, Some(EffectiveStrategy::Promoted { after_segments: 4 }));
+        roundtrip(&snapshot, &plaintext);
+
+        // A short stream never leaves the warm-up
+        let plaintext = data(CHUNK as usize);
+        let snapshot = encrypt(InputSource::Reader(Box::new(Cursor::new(plaintext.clone()))));
+        assert_eq!(snapshot.strategy_effective, Some(EffectiveStrategy::Sequential));
+        roundtrip(&snapshot, &plaintext);
+    }
+
+    #[test]
+    fn effective_strategy_is_serialized() {
+        let plaintext = data(4 * THRESHOLD as usize);
+        let snapshot = encrypt(InputSource::Reader(Box::new(Cursor::new(plaintext))));
+
+        let json: serde_json::Value = serde_json::from_str(&snapshot.to_json()).unwrap();
+        assert_eq!(json["strategy_effective"]["promoted"]["after_segments"], 4);
+        assert_eq!(TelemetrySnapshot::from_json(&snapshot.to_json()).unwrap().strategy_effective, snapshot.strategy_effective);
+    }
+}
@@ -0,0 +1,110 @@
+// # 📂 `tests/test_bounded_decrypt.rs`
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::sync::Arc;
+
+    use bytes::Bytes;
+    use crossbeam::channel::unbounded;
+
+    use crypto_core::{
+        crypto::{DigestAlg, KEY_LEN_32},
+        headers::HeaderV1,
+        recovery::persist::AsyncLogManager,
+        stream_v2::{
+            DecryptingReader, EncryptingWriter,
+            core::{DecryptParams, EncryptParams},
+            parallelism::HybridParallelismProfile,
+            segment_worker::{
+                DecryptContext, DecryptSegmentInput, DecryptSegmentWorker, EncryptContext, EncryptSegmentInput,
+                EncryptSegmentWorker,
+            },
+            segmenting::types::SegmentFlags,
+        },
+        telemetry::StageTimes,
+    };
+
+    const SEGMENT_LEN: usize = 4 * 1024 * 1024;
+
+    fn header() -> HeaderV1 {
+        HeaderV1 { chunk_size: SEGMENT_LEN as u32, ..HeaderV1::test_header() }
+    }
+
+    fn contexts() -> (EncryptContext, DecryptContext) {
+        let profile = HybridParallelismProfile::dynamic(SEGMENT_LEN as u32, 0.50, 64);
+        let session_key = [0x42u8; KEY_LEN_32];
+        let enc = EncryptContext::new(header(), profile.clone(), &session_key, DigestAlg::Blake3).unwrap();
+        let dec = DecryptContext::from_stream_header(header(), profile, &session_key, DigestAlg::Blake3).unwrap();
+        (enc, dec)
+    }
+
+    fn data() -> Vec<u8> {
+        (0..SEGMENT_LEN).map(|i| (i % 241) as u8).collect()
+    }
+
+    #[test]
+    fn large_segment_keeps_frames_separate() {
+        let (enc, dec) = contexts();
+        let frame_size = enc.base.frame_size;
+        let log_manager = Arc::new(AsyncLogManager::disabled());
+
+        let (enc_tx, enc_rx) = unbounded();
+        let (mid_tx, mid_rx) = unbounded();
+        let (dec_tx, dec_rx) = unbounded();
+        let (out_tx, out_rx) = unbounded();
+        let enc_worker = EncryptSegmentWorker::new(enc, log_manager.clone()).run_v2(enc_rx, mid_tx);
+        let dec_worker = DecryptSegmentWorker::new(dec, log_manager).run_v2(dec_rx, out_tx);
+
+        let data = data();
+        enc_tx
+            .send(EncryptSegmentInput {
+                segment_index: 0,
+                bytes: Bytes::from(data.clone()),
+                flags: SegmentFlags::empty(),
+                stage_times: StageTimes::default(),
+            })
+            .unwrap();
+        let encrypted = mid_rx.recv().unwrap().unwrap();
+        let frame_count = encrypted.header.frame_count as usize;
+        dec_tx.send(DecryptSegmentInput::from(encrypted)).unwrap();
+
+        let decrypted = out_rx.recv().unwrap().unwrap();
+        assert!(frame_count > 1);
+        assert_eq!(decrypted.frames.len(), frame_count);
+        // No buffer spans the whole segment: each one is a single frame
+        assert!(decrypted.frames.iter().all(|f| !f.is_empty() && f.len() <= frame_size));
+        assert_eq!(decrypted.len(), SEGMENT_LEN);
+        assert_eq!(decrypted.to_bytes(), data);
+
+        drop((enc_tx, dec_tx));
+        enc_worker.join().unwrap();
+        dec_worker.join().unwrap();
+    }
+
+    #[test]
+    fn reader_serves_frames_across_small_reads() {
+        let data = data();
+        let master_key = vec![0x11u8; 32];
+
+        let mut writer = EncryptingWriter::new(Vec::new(), &master_key, EncryptParams::new(header())).unwrap();
+        writer.write_all(&data).unwrap();
+        writer.write_all(&data[..12_345]).unwrap();
+        let ciphertext = writer.finish().unwrap();
+
+        let mut reader = DecryptingReader::new(ciphertext.as_slice(), &master_key, DecryptParams::new()).unwrap();
+        let mut out = Vec::new();
+        let mut buf = [0u8; 1000]; // not a divisor of the frame size
+        loop {
+            let n = reader.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            out.extend_from_slice(&buf[..n]);
+        }
+
+        assert_eq!(out.len(), data.len() + 12_345);
+        assert!(out[..SEGMENT_LEN] == data[..]);
+        assert_eq!(&out[SEGMENT_LEN..], &data[..12_345]);
+    }
+}
@@ -0,0 +1,67 @@
+// # 📂 `tests/test_bytes_api.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crypto_core::{
+        headers::{HeaderV1, decode_header_le},
+        stream_v2::{DecryptParams, EncryptParams, decrypt_bytes, encrypt_bytes},
+        types::StreamError,
+    };
+
+    use crate::common::master_key;
+
+    /// Stream header, data segment framing (data, digest and terminator
+    /// frames) and the empty final segment stay well below this.
+    const SMALL_PAYLOAD_OVERHEAD: usize = 512;
+
+    fn payload(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 31 % 251) as u8).collect()
+    }
+
+    #[test]
+    fn roundtrip_small_and_empty_payloads() {
+        for len in [0, 1, 200, 4096, 70_000] {
+            let plaintext = payload(len);
+            let ciphertext = encrypt_bytes(&plaintext, &master_key(), EncryptParams::new(HeaderV1::test_header())).unwrap();
+            let decrypted = decrypt_bytes(&ciphertext, &master_key(), DecryptParams::new()).unwrap();
+            assert_eq!(decrypted, plaintext, "len {len}");
+        }
+    }
+
+    #[test]
+    fn small_payloads_have_bounded_overhead() {
+        for len in [0, 16, 200, 1024] {
+            let plaintext = payload(len);
+            let ciphertext = encrypt_bytes(&plaintext, &master_key(), EncryptParams::new(HeaderV1::test_header())).unwrap();
+            assert!(
+                ciphertext.len() < plaintext.len() + SMALL_PAYLOAD_OVERHEAD,
+                "{len} bytes encrypted to {}",
+                ciphertext.len()
+            );
+        }
+    }
+
+    #[test]
+    fn chunk_size_is_fitted_to_the_payload() {
+        let ciphertext = encrypt_bytes(&payload(200), &master_key(), EncryptParams::new(HeaderV1::test_header())).unwrap();
+        let header = decode_header_le(&ciphertext[..HeaderV1::LEN]).unwrap();
+        assert_eq!(header.chunk_size, 16 * 1024);
+
+        // Larger payloads keep the caller's chunk size
+        let ciphertext = encrypt_bytes(&payload(300_000), &master_key(), EncryptParams::new(HeaderV1::test_header())).unwrap();
+        let header = decode_header_le(&ciphertext[..HeaderV1::LEN]).unwrap();
+        assert_eq!(header.chunk_size, HeaderV1::test_header().chunk_size);
+    }
+
+    #[test]
+    fn wrong_key_is_an_error() {
+        let ciphertext = encrypt_bytes(b"token", &master_key(), EncryptParams::new(HeaderV1::test_header())).unwrap();
+        assert!(decrypt_bytes(&ciphertext, &[0x22u8; 32], DecryptParams::new()).is_err());
+        assert!(matches!(
+            encrypt_bytes(b"token", &[0u8; 7], EncryptParams::new(HeaderV1::test_header())),
+            Err(StreamError::Crypto(_))
+        ));
+    }
+}
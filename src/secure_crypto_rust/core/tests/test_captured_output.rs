@@ -0,0 +1,91 @@
+// # 📂 `tests/test_captured_output.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+
+    use crypto_core::{
+        headers::HeaderV1,
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{ApiConfig, DecryptParams, EncryptParams, decrypt_stream_v2, encrypt_stream_v2},
+        },
+        telemetry::TelemetrySnapshot,
+        types::StreamError,
+    };
+
+    use crate::common::master_key;
+
+    const CHUNK: usize = 16 * 1024;
+    const MARKER: &str = "captured-output-marker";
+    const CHILD_ENV: &str = "RSE1_CAPTURED_OUTPUT_CHILD";
+
+    fn plaintext() -> Vec<u8> {
+        MARKER.as_bytes().repeat(3 * CHUNK / MARKER.len())
+    }
+
+    fn encrypt(config: ApiConfig) -> Result<TelemetrySnapshot, StreamError> {
+        let header = HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() };
+        encrypt_stream_v2(InputSource::Memory(plaintext()), OutputSink::Memory, &master_key(), EncryptParams::new(header), config)
+    }
+
+    fn decrypt(ciphertext: Vec<u8>, config: ApiConfig) -> Result<TelemetrySnapshot, StreamError> {
+        decrypt_stream_v2(InputSource::Memory(ciphertext), OutputSink::Memory, &master_key(), DecryptParams::new(), config)
+    }
+
+    #[test]
+    fn snapshot_holds_the_output_when_requested() {
+        let ciphertext = encrypt(ApiConfig::with_buf_enabled()).unwrap().output.unwrap();
+        // The repeated marker compresses well: only the stream framing is certain
+        assert!(ciphertext.len() > HeaderV1::LEN);
+
+        let decrypted = decrypt(ciphertext.clone(), ApiConfig::with_buf_enabled()).unwrap();
+        assert_eq!(decrypted.output.unwrap(), plaintext());
+
+        assert!(encrypt(ApiConfig::default()).unwrap().output.is_none());
+        assert!(decrypt(ciphertext, ApiConfig::default()).unwrap().output.is_none());
+    }
+
+    #[test]
+    fn captured_output_is_capped() {
+        let ciphertext = encrypt(ApiConfig::with_buf_enabled()).unwrap().output.unwrap();
+
+        match encrypt(ApiConfig::with_buf_enabled().with_max_captured_bytes(ciphertext.len() - 1)) {
+            Err(StreamError::Io(e)) => assert_eq!(e.kind(), std::io::ErrorKind::OutOfMemory),
+            other => panic!("expected an I/O error, got {other:?}"),
+        }
+        assert!(matches!(
+            decrypt(ciphertext.clone(), ApiConfig::with_buf_enabled().with_max_captured_bytes(CHUNK)),
+            Err(StreamError::Io(_))
+        ));
+
+        let exact = ApiConfig::with_buf_enabled().with_max_captured_bytes(ciphertext.len());
+        assert_eq!(encrypt(exact).unwrap().output.unwrap().len(), ciphertext.len());
+    }
+
+    /// Runs a full roundtrip; only does work inside the child process below.
+    #[test]
+    fn roundtrip_child() {
+        if std::env::var_os(CHILD_ENV).is_none() {
+            return;
+        }
+        let ciphertext = encrypt(ApiConfig::with_buf_enabled()).unwrap().output.unwrap();
+        decrypt(ciphertext, ApiConfig::with_buf_enabled()).unwrap();
+    }
+
+    #[test]
+    fn captured_buffers_are_not_printed() {
+        let output = Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "tests::roundtrip_child", "--nocapture", "--test-threads=1"])
+            .env(CHILD_ENV, "1")
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("1 passed"), "{stdout}");
+        assert!(!stdout.contains(MARKER), "plaintext leaked to stdout");
+    }
+}
@@ -0,0 +1,108 @@
+// # 📂 `tests/test_checkpoint.rs`
+
+#[cfg(test)]
+mod tests {
+    use crypto_core::{
+        crypto::{DigestAlg, DigestError, DigestState, SegmentDigestBuilder, SegmentDigestVerifier},
+        recovery::{AsyncLogManager, Checkpoint, UnifiedEntry},
+    };
+
+    const FRAMES: u32 = 8;
+    const FRAME_LEN: usize = 4096;
+
+    /// Stand-in for the ciphertext of DATA frame `i`.
+    fn frame(i: u32) -> Vec<u8> {
+        (0..FRAME_LEN).map(|j| (j as u32 ^ i.wrapping_mul(31)) as u8).collect()
+    }
+
+    fn uninterrupted(alg: DigestAlg) -> Vec<u8> {
+        let mut builder = SegmentDigestBuilder::new(alg, 3, FRAMES);
+        for i in 0..FRAMES {
+            builder.update_frame(i, &frame(i)).unwrap();
+        }
+        builder.finalize()
+    }
+
+    #[test]
+    fn resumed_builder_matches_uninterrupted_digest() {
+        for alg in [DigestAlg::Sha256, DigestAlg::Sha512, DigestAlg::Sha3_256, DigestAlg::Sha3_512] {
+            // First half of the segment, then a checkpoint through the audit log
+            let mut builder = SegmentDigestBuilder::new(alg, 3, FRAMES);
+            for i in 0..FRAMES / 2 {
+                builder.update_frame(i, &frame(i)).unwrap();
+            }
+            let checkpoint = Checkpoint::from_builder(&builder, FRAMES / 2, 123_456).unwrap();
+            drop(builder);
+
+            let log = AsyncLogManager::memory();
+            checkpoint.persist(&log).unwrap();
+            let entries = log.entries().unwrap();
+            let [UnifiedEntry::Scheduler(line)] = entries.as_slice() else {
+                panic!("expected one scheduler entry, got {entries:?}");
+            };
+
+            // "Restart": rebuild from the log line only
+            let restored = Checkpoint::from_log_line(line).unwrap();
+            assert_eq!(restored, checkpoint);
+            assert_eq!(restored.stream_offset, 123_456);
+
+            let mut builder = restored.to_builder().unwrap();
+            // Frames before the checkpoint are already in the state
+            assert!(matches!(
+                builder.update_frame(0, &frame(0)),
+                Err(DigestError::FrameOrderViolation { expected: 4, got: 0 })
+            ));
+            for i in restored.frame_index..FRAMES {
+                builder.update_frame(i, &frame(i)).unwrap();
+            }
+            assert_eq!(builder.finalize(), uninterrupted(alg), "{alg}");
+        }
+    }
+
+    #[test]
+    fn resumed_verifier_accepts_the_digest() {
+        let expected = uninterrupted(DigestAlg::Sha256);
+
+        let mut verifier = SegmentDigestVerifier::new(DigestAlg::Sha256, 3, FRAMES, expected.clone());
+        for i in 0..3 {
+            verifier.update_frame(i, &frame(i)).unwrap();
+        }
+        let line = Checkpoint::from_verifier(&verifier, 3, 0).unwrap().to_log_line();
+
+        let mut verifier = Checkpoint::from_log_line(&line).unwrap().to_verifier(expected).unwrap();
+        for i in 3..FRAMES {
+            verifier.update_frame(i, &frame(i)).unwrap();
+        }
+        verifier.finalize().unwrap();
+    }
+
+    #[test]
+    fn blake3_is_not_resumable() {
+        assert!(!DigestAlg::Blake3.can_resume());
+
+        let builder = SegmentDigestBuilder::new(DigestAlg::Blake3, 0, 1);
+        assert!(matches!(
+            Checkpoint::from_builder(&builder, 0, 0),
+            Err(DigestError::NotResumable { alg: DigestAlg::Blake3 })
+        ));
+        assert!(matches!(
+            DigestState::deserialize(DigestAlg::Blake3, &[]),
+            Err(DigestError::NotResumable { .. })
+        ));
+    }
+
+    #[test]
+    fn corrupt_state_and_lines_are_rejected() {
+        let builder = SegmentDigestBuilder::new(DigestAlg::Sha256, 0, 2);
+        let state = builder.state.serialize().unwrap();
+        assert!(matches!(
+            DigestState::deserialize(DigestAlg::Sha256, &state[..state.len() - 1]),
+            Err(DigestError::InvalidLength { .. })
+        ));
+
+        let line = Checkpoint::from_builder(&builder, 0, 80).unwrap().to_log_line();
+        let torn = line.replacen("|0|", "|1|", 1);
+        assert!(Checkpoint::from_log_line(&torn).is_none());
+        assert!(Checkpoint::from_log_line("SCHEDULER: RESUME|0|0|Sha256|RESTART|00000000").is_none());
+    }
+}
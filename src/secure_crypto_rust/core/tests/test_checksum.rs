@@ -0,0 +1,61 @@
+// # 📂 `tests/test_checksum.rs`
+
+#[cfg(test)]
+mod tests {
+    use crypto_core::{
+        compression::{CompressionError, verify_checksum},
+        utils::{Checksum, ChecksumAlg, compute_checksum},
+    };
+
+    const DATA: &[u8] = b"123456789";
+
+    #[test]
+    fn crc32_matches_the_reference_value() {
+        let crc = compute_checksum(DATA, ChecksumAlg::Crc32);
+        assert_eq!(crc, Checksum::Crc32(0xCBF4_3926));
+        assert_eq!(crc.to_bytes(), 0xCBF4_3926u32.to_le_bytes());
+        assert!(crc.verify(DATA));
+        assert!(!crc.verify(b"123456780"));
+    }
+
+    #[test]
+    fn blake3_is_computed_not_downgraded() {
+        let hash = compute_checksum(DATA, ChecksumAlg::Blake3);
+        assert_eq!(hash, Checksum::Blake3(*blake3::hash(DATA).as_bytes()));
+        assert_eq!(hash.alg(), ChecksumAlg::Blake3);
+        assert_eq!(hash.to_bytes().len(), ChecksumAlg::Blake3.encoded_len());
+        assert!(hash.verify(DATA));
+        assert!(!hash.verify(b"123456780"));
+    }
+
+    #[test]
+    fn canonical_bytes_round_trip() {
+        for alg in [ChecksumAlg::Crc32, ChecksumAlg::Blake3] {
+            let sum = compute_checksum(DATA, alg);
+            let bytes = sum.to_bytes();
+            assert_eq!(bytes.len(), alg.encoded_len());
+            assert_eq!(Checksum::from_bytes(alg, &bytes), Some(sum));
+        }
+        assert_eq!(Checksum::from_bytes(ChecksumAlg::Blake3, &[0u8; 4]), None);
+        assert_eq!(Checksum::from_bytes(ChecksumAlg::Crc32, &[0u8; 32]), None);
+    }
+
+    #[test]
+    fn cross_algorithm_comparison_is_rejected() {
+        let crc = compute_checksum(DATA, ChecksumAlg::Crc32);
+        let hash = compute_checksum(DATA, ChecksumAlg::Blake3);
+        assert_ne!(crc, hash);
+
+        // A Blake3 prefix carrying the CRC's bytes still doesn't match
+        let mut forged = [0u8; 32];
+        forged[..4].copy_from_slice(&crc.to_bytes());
+        assert_ne!(Checksum::Blake3(forged), crc);
+
+        assert!(matches!(
+            verify_checksum(&crc, &hash, "test".into()),
+            Err(CompressionError::CodecProcessFailed { msg, .. }) if msg.contains("algorithm mismatch")
+        ));
+        verify_checksum(&hash, &compute_checksum(DATA, ChecksumAlg::Blake3), "test".into()).unwrap();
+        assert!(verify_checksum(&crc, &compute_checksum(b"other", ChecksumAlg::Crc32), "test".into()).is_err());
+    }
+}
@@ -0,0 +1,90 @@
+// # 📂 `tests/test_chunk_reader.rs`
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read};
+
+    use crypto_core::{
+        constants::MAX_CHUNK_SIZE,
+        headers::HeaderV1,
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{ApiConfig, DecryptParams, EncryptParams, decrypt_stream_v2, encrypt_stream_v2},
+            io::{ChunkReader, INITIAL_READ_BUF, read_exact_or_eof},
+        },
+    };
+
+    /// Hands out at most `step` bytes per `read`, like a pipe or socket.
+    struct Trickle {
+        inner: Cursor<Vec<u8>>,
+        step: usize,
+    }
+
+    impl Read for Trickle {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = buf.len().min(self.step);
+            self.inner.read(&mut buf[..len])
+        }
+    }
+
+    fn data(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn preserves_segment_boundaries() {
+        let chunk = 3 * INITIAL_READ_BUF + 17;
+        let input = data(3 * chunk + 5);
+        let mut reader = ChunkReader::new(Trickle { inner: Cursor::new(input.clone()), step: 4093 }, chunk);
+
+        let mut lens = Vec::new();
+        let mut joined = Vec::new();
+        loop {
+            let chunk = reader.read_chunk().unwrap();
+            if chunk.is_empty() {
+                break;
+            }
+            lens.push(chunk.len());
+            joined.extend_from_slice(&chunk);
+        }
+        assert_eq!(lens, [chunk, chunk, chunk, 5]);
+        assert_eq!(joined, input);
+        assert_eq!(reader.stats().chunks, 4);
+        assert!(reader.read_chunk().unwrap().is_empty());
+    }
+
+    #[test]
+    fn short_input_does_not_reserve_a_full_chunk() {
+        let mut reader = ChunkReader::new(Cursor::new(vec![7u8]), MAX_CHUNK_SIZE);
+        assert_eq!(&reader.read_chunk().unwrap()[..], [7u8]);
+        assert!(reader.read_chunk().unwrap().is_empty());
+        assert!(reader.stats().peak_capacity <= 2 * INITIAL_READ_BUF, "{:?}", reader.stats());
+
+        assert_eq!(&read_exact_or_eof(&mut Cursor::new(vec![1u8, 2, 3]), MAX_CHUNK_SIZE).unwrap()[..], [1u8, 2, 3]);
+    }
+
+    #[test]
+    fn one_byte_stream_with_max_chunk_size() {
+        let header = HeaderV1 { chunk_size: MAX_CHUNK_SIZE as u32, ..HeaderV1::test_header() };
+        let snapshot = encrypt_stream_v2(
+            InputSource::Memory(vec![0x5A]),
+            OutputSink::Memory,
+            &[0x11u8; 32],
+            EncryptParams::new(header),
+            ApiConfig::new(Some(true), None),
+        )
+        .unwrap();
+        assert!(snapshot.read_buffer_peak > 0);
+        assert!(snapshot.read_buffer_peak <= 2 * INITIAL_READ_BUF as u64, "{}", snapshot.read_buffer_peak);
+
+        let decrypted = decrypt_stream_v2(
+            InputSource::Memory(snapshot.output.unwrap()),
+            OutputSink::Memory,
+            &[0x11u8; 32],
+            DecryptParams::new(),
+            ApiConfig::new(Some(true), None),
+        )
+        .unwrap();
+        assert_eq!(decrypted.output.unwrap(), [0x5A]);
+    }
+}
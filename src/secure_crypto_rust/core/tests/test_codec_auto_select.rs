@@ -0,0 +1,73 @@
+// # 📂 `tests/test_codec_auto_select.rs`
+
+#[cfg(test)]
+mod tests {
+    use crypto_core::compression::{AutoSelectHints, CodecLevel, codec_ids};
+
+    const MB: usize = 1_000_000;
+
+    fn hints(stream_size: usize) -> AutoSelectHints {
+        AutoSelectHints::new(stream_size)
+    }
+
+    #[test]
+    fn selection_table() {
+        let slow_link = AutoSelectHints { bandwidth_mbps: Some(5), ..hints(50 * MB) };
+        let fast_link = AutoSelectHints { bandwidth_mbps: Some(100), ..hints(50 * MB) };
+
+        let cases: Vec<(u16, AutoSelectHints, CodecLevel)> = vec![
+            // Offline defaults
+            (codec_ids::ZSTD, hints(64 * 1024), CodecLevel::ZstdFast),
+            (codec_ids::ZSTD, hints(50 * MB), CodecLevel::ZstdBalanced),
+            (codec_ids::ZSTD, hints(500 * MB), CodecLevel::ZstdMax),
+            (codec_ids::ZSTD, hints(2_000 * MB), CodecLevel::ZstdMax),
+            (codec_ids::LZ4, hints(50 * MB), CodecLevel::Lz4DecSpeed),
+            (codec_ids::DEFLATE, hints(64 * 1024), CodecLevel::FlateFast),
+            (codec_ids::DEFLATE, hints(50 * MB), CodecLevel::FlateDefault),
+            (codec_ids::DEFLATE, hints(500 * MB), CodecLevel::FlateBest),
+            (codec_ids::AUTO, hints(64 * 1024), CodecLevel::Lz4Fast),
+            (codec_ids::AUTO, hints(50 * MB), CodecLevel::ZstdBalanced),
+            (0xFFFF, hints(50 * MB), CodecLevel::ZstdBalanced),
+            // Explicit hints
+            (codec_ids::ZSTD, AutoSelectHints { has_dict: true, ..hints(50 * MB) }, CodecLevel::ZstdFast),
+            (codec_ids::ZSTD, AutoSelectHints { gpu_available: Some(true), ..hints(500 * MB) }, CodecLevel::ZstdBalanced),
+            (codec_ids::ZSTD, AutoSelectHints { archival: Some(true), ..hints(64 * 1024) }, CodecLevel::ZstdMax),
+            (codec_ids::ZSTD, AutoSelectHints { archival: Some(false), ..hints(2_000 * MB) }, CodecLevel::ZstdMax),
+            (codec_ids::LZ4, AutoSelectHints { favor_decompression: Some(false), ..hints(64 * 1024) }, CodecLevel::Lz4Fast),
+            (codec_ids::LZ4, AutoSelectHints { favor_decompression: Some(false), ..hints(500 * MB) }, CodecLevel::Lz4HighAccel),
+            // Caller-provided bandwidth
+            (codec_ids::ZSTD, slow_link, CodecLevel::ZstdMax),
+            (codec_ids::ZSTD, fast_link, CodecLevel::ZstdBalanced),
+            (codec_ids::DEFLATE, slow_link, CodecLevel::FlateBest),
+            (codec_ids::AUTO, slow_link, CodecLevel::ZstdMax),
+            (codec_ids::LZ4, AutoSelectHints { favor_decompression: Some(false), ..slow_link }, CodecLevel::Lz4HighAccel),
+        ];
+
+        for (codec_id, hints, expected) in cases {
+            assert_eq!(CodecLevel::auto_select(codec_id, &hints), expected, "codec {codec_id:#x}, {hints:?}");
+        }
+    }
+
+    #[test]
+    fn selection_is_deterministic() {
+        for stream_size in [0, 1, 999_999, 1_000_000, 99_999_999, 100_000_000, 1_000_000_001] {
+            for codec_id in [codec_ids::AUTO, codec_ids::ZSTD, codec_ids::LZ4, codec_ids::DEFLATE] {
+                let hints = hints(stream_size);
+                let first = CodecLevel::auto_select(codec_id, &hints);
+                for _ in 0..100 {
+                    assert_eq!(CodecLevel::auto_select(codec_id, &hints), first);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_bandwidth_is_not_treated_as_slow() {
+        let offline = hints(50 * MB);
+        assert_eq!(offline.bandwidth_mbps, None);
+        let at_threshold = AutoSelectHints { bandwidth_mbps: Some(AutoSelectHints::LOW_BANDWIDTH_MBPS), ..offline };
+        for codec_id in [codec_ids::AUTO, codec_ids::ZSTD, codec_ids::DEFLATE] {
+            assert_eq!(CodecLevel::auto_select(codec_id, &offline), CodecLevel::auto_select(codec_id, &at_threshold));
+        }
+    }
+}
@@ -0,0 +1,123 @@
+// # 📂 `tests/test_codec_frames.rs`
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use crypto_core::{
+        compression::{CompressionCodec, CompressionError, create_decompressor},
+        headers::HeaderV1,
+        stream_v2::compression_worker::{CodecInfo, CompressionBackend, CpuCompressionBackend},
+    };
+
+    /// Segment payload layout: `[orig_len u32 LE][codec frame][crc32 u32 LE]`
+    /// (lz4 carries its own size prefix inside the block).
+    const PREFIX: usize = 4;
+    const SUFFIX: usize = 4;
+
+    fn sample() -> Vec<u8> {
+        b"segment payloads are independent codec frames; "
+            .iter()
+            .cycle()
+            .take(40_000)
+            .copied()
+            .collect()
+    }
+
+    fn segment(codec: CompressionCodec, plaintext: &[u8]) -> Vec<u8> {
+        let header = HeaderV1 { compression: codec as u16, ..HeaderV1::test_header() };
+        let mut backend = CpuCompressionBackend::new(CodecInfo::from_header(&header, None).unwrap()).unwrap();
+        backend.compress_chunk(plaintext).unwrap()
+    }
+
+    fn codec_frame(payload: &[u8]) -> &[u8] {
+        &payload[PREFIX..payload.len() - SUFFIX]
+    }
+
+    /// Insert `extra` between the codec frame and the CRC; the CRC covers the
+    /// plaintext, so only the frame check can catch it.
+    fn with_trailing(payload: &[u8], extra: &[u8]) -> Vec<u8> {
+        let split = payload.len() - SUFFIX;
+        [&payload[..split], extra, &payload[split..]].concat()
+    }
+
+    fn decompress(codec: CompressionCodec, payload: &[u8]) -> Result<Vec<u8>, CompressionError> {
+        let mut decompressor = create_decompressor(codec as u16, None).unwrap();
+        let mut out = Vec::new();
+        decompressor.decompress_chunk(payload, &mut out)?;
+        Ok(out)
+    }
+
+    #[test]
+    fn zstd_segment_is_a_standalone_frame() {
+        let plaintext = sample();
+        let payload = segment(CompressionCodec::Zstd, &plaintext);
+        assert!(payload.len() < plaintext.len());
+        // Reference streaming decoder: no context beyond the frame itself
+        assert_eq!(zstd::stream::decode_all(codec_frame(&payload)).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn deflate_segment_is_a_standalone_zlib_stream() {
+        let plaintext = sample();
+        let payload = segment(CompressionCodec::Deflate, &plaintext);
+        let mut decoded = Vec::new();
+        let mut decoder = flate2::read::ZlibDecoder::new(codec_frame(&payload));
+        decoder.read_to_end(&mut decoded).unwrap();
+        assert_eq!(decoded, plaintext);
+        assert_eq!(decoder.total_in() as usize, codec_frame(&payload).len());
+    }
+
+    #[test]
+    fn lz4_segment_is_a_standalone_block() {
+        let plaintext = sample();
+        let payload = segment(CompressionCodec::Lz4, &plaintext);
+        let block = &payload[..payload.len() - SUFFIX];
+        assert_eq!(lz4_flex::block::decompress_size_prepended(block).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn segments_do_not_share_state() {
+        let plaintext = sample();
+        for codec in [CompressionCodec::Zstd, CompressionCodec::Deflate, CompressionCodec::Lz4] {
+            let header = HeaderV1 { compression: codec as u16, ..HeaderV1::test_header() };
+            let mut backend = CpuCompressionBackend::new(CodecInfo::from_header(&header, None).unwrap()).unwrap();
+            let first = backend.compress_chunk(&plaintext).unwrap();
+            let second = backend.compress_chunk(&plaintext).unwrap();
+            // Same input, same frame: nothing carried over from the first segment
+            assert_eq!(first, second, "{codec:?}");
+            // Each decodes with a fresh decompressor
+            assert_eq!(decompress(codec, &second).unwrap(), plaintext, "{codec:?}");
+        }
+    }
+
+    #[test]
+    fn zstd_rejects_trailing_data() {
+        let plaintext = sample();
+        let payload = segment(CompressionCodec::Zstd, &plaintext);
+
+        // A second, empty frame keeps the decoded size unchanged
+        let empty_frame = zstd::bulk::compress(b"", 3).unwrap();
+        let err = decompress(CompressionCodec::Zstd, &with_trailing(&payload, &empty_frame)).unwrap_err();
+        assert!(matches!(err, CompressionError::TrailingData { extra, .. } if extra == empty_frame.len()), "{err:?}");
+
+        assert!(decompress(CompressionCodec::Zstd, &with_trailing(&payload, &[0xAB; 5])).is_err());
+    }
+
+    #[test]
+    fn deflate_rejects_trailing_data() {
+        let plaintext = sample();
+        let payload = segment(CompressionCodec::Deflate, &plaintext);
+        assert_eq!(decompress(CompressionCodec::Deflate, &payload).unwrap(), plaintext);
+
+        let err = decompress(CompressionCodec::Deflate, &with_trailing(&payload, &[0xAB; 5])).unwrap_err();
+        assert!(matches!(err, CompressionError::TrailingData { extra: 5, .. }), "{err:?}");
+    }
+
+    #[test]
+    fn auto_rejects_trailing_data() {
+        let plaintext = sample();
+        let payload = segment(CompressionCodec::Auto, &plaintext);
+        assert!(decompress(CompressionCodec::Auto, &with_trailing(&payload, &[0xAB; 5])).is_err());
+    }
+}
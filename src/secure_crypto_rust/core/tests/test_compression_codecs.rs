@@ -0,0 +1,103 @@
+// # 📂 `tests/test_compression_codecs.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crypto_core::{
+        compression::{CodecLevel, CompressionCodec, CompressionError},
+        headers::{HeaderV1, decode_header_le},
+        stream_v2::{
+            InputSource, OutputSink,
+            compression_worker::{CodecInfo, make_backend},
+            core::{DecryptParams, EncryptParams, encrypt_stream_v2},
+            parallelism::WorkerTarget,
+        },
+    };
+
+    use crate::common::{self, master_key};
+
+    const CHUNK: usize = 16 * 1024;
+
+    /// Compressible, multi-segment plaintext (≈ 4.5 segments).
+    fn plaintext() -> Vec<u8> {
+        let line = b"segment pipeline compression roundtrip 0123456789 abcdefghijklmnopqrstuvwxyz\n";
+        line.iter().cycle().take(4 * CHUNK + CHUNK / 2).copied().collect()
+    }
+
+    /// Encrypt + decrypt with `codec`; returns `bytes_compressed` from the encrypt snapshot.
+    fn roundtrip(codec: CompressionCodec, level: Option<CodecLevel>) -> u64 {
+        let data = plaintext();
+        let header = HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() };
+        let params = EncryptParams::new(header).with_compression(codec, level);
+
+        let enc = encrypt_stream_v2(
+            InputSource::Memory(data.clone()),
+            OutputSink::Memory,
+            &master_key(),
+            params,
+            common::config(),
+        )
+        .expect("encryption should succeed");
+        let ciphertext = enc.output.clone().unwrap();
+
+        // Codec is recorded in the stream header
+        let decoded = decode_header_le(&ciphertext[..HeaderV1::LEN]).unwrap();
+        assert_eq!(decoded.compression, codec as u16);
+
+        let decrypted = common::decrypt(ciphertext, DecryptParams::new()).expect("decryption should succeed");
+        assert_eq!(decrypted, data, "{codec:?} roundtrip mismatch");
+
+        enc.bytes_compressed
+    }
+
+    #[test]
+    fn zstd_roundtrip_multi_segment() {
+        roundtrip(CompressionCodec::Zstd, None);
+    }
+
+    #[test]
+    fn lz4_roundtrip_multi_segment() {
+        roundtrip(CompressionCodec::Lz4, None);
+    }
+
+    #[test]
+    fn deflate_roundtrip_multi_segment() {
+        roundtrip(CompressionCodec::Deflate, None);
+    }
+
+    #[test]
+    fn explicit_level_roundtrip() {
+        roundtrip(CompressionCodec::Zstd, Some(CodecLevel::ZstdMax));
+        roundtrip(CompressionCodec::Deflate, Some(CodecLevel::FlateBest));
+    }
+
+    #[test]
+    fn bytes_compressed_differs_per_codec() {
+        let zstd = roundtrip(CompressionCodec::Zstd, None);
+        let lz4 = roundtrip(CompressionCodec::Lz4, None);
+        let deflate = roundtrip(CompressionCodec::Deflate, None);
+
+        assert!(zstd < plaintext().len() as u64 && lz4 < plaintext().len() as u64 && deflate < plaintext().len() as u64);
+        assert_ne!(zstd, lz4);
+        assert_ne!(zstd, deflate);
+        assert_ne!(lz4, deflate);
+    }
+
+    #[test]
+    fn unknown_codec_is_unsupported_not_auto() {
+        let info = CodecInfo {
+            codec_id: 0x7777,
+            level: CodecLevel::Custom(1),
+            dict: None,
+            gpu: None,
+            max_output: None,
+            segment_dicts: None,
+        };
+        match make_backend(WorkerTarget::Cpu(0), info) {
+            Err(CompressionError::UnsupportedCodec { codec_id }) => assert_eq!(codec_id, 0x7777),
+            Err(e) => panic!("unexpected error: {e}"),
+            Ok(_) => panic!("unknown codec must not fall back to Auto"),
+        }
+    }
+}
@@ -13,6 +13,8 @@ mod tests {
             level: CodecLevel::ZstdBalanced,
             dict: None,
             gpu: None,
+            max_output: None,
+            segment_dicts: None,
         }
     }
 
@@ -0,0 +1,110 @@
+// # 📂 `tests/test_compression_stream.rs`
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read};
+
+    use crypto_core::compression::{
+        Checksum, ChecksumAlg, CompressStream, CompressionCodec, CompressionError, DecompressStream, compute_checksum,
+        stream::CHUNK_PREFIX_LEN,
+    };
+
+    const CHUNK: usize = 4 * 1024;
+    const CODECS: [CompressionCodec; 4] =
+        [CompressionCodec::Auto, CompressionCodec::Zstd, CompressionCodec::Lz4, CompressionCodec::Deflate];
+
+    /// Compressible text, 3.5 chunks.
+    fn plaintext() -> Vec<u8> {
+        b"log line: archived without encryption 0123456789\n".iter().cycle().take(3 * CHUNK + CHUNK / 2).copied().collect()
+    }
+
+    /// Reader returning at most 100 bytes per call.
+    struct Trickle(Cursor<Vec<u8>>);
+    impl Read for Trickle {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = buf.len().min(100);
+            self.0.read(&mut buf[..n])
+        }
+    }
+
+    fn compress<R: Read>(reader: R, codec: CompressionCodec) -> Vec<Vec<u8>> {
+        CompressStream::new(reader, codec as u16, CHUNK, None)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    fn decompress(framed: Vec<u8>, codec: CompressionCodec) -> Result<Vec<Vec<u8>>, CompressionError> {
+        DecompressStream::new(Cursor::new(framed), codec as u16, None).unwrap().collect()
+    }
+
+    #[test]
+    fn every_codec_roundtrips() {
+        for codec in CODECS {
+            let frames = compress(Cursor::new(plaintext()), codec);
+            assert_eq!(frames.len(), 4, "{codec:?}");
+
+            let chunks = decompress(frames.concat(), codec).unwrap();
+            assert!(chunks[..3].iter().all(|c| c.len() == CHUNK), "{codec:?}");
+            assert_eq!(chunks.concat(), plaintext(), "{codec:?}");
+        }
+    }
+
+    #[test]
+    fn frames_carry_length_and_crc() {
+        for frame in compress(Cursor::new(plaintext()), CompressionCodec::Zstd) {
+            let len = u32::from_le_bytes(frame[..4].try_into().unwrap()) as usize;
+            let crc = u32::from_le_bytes(frame[4..8].try_into().unwrap());
+            assert_eq!(frame.len(), CHUNK_PREFIX_LEN + len);
+            assert_eq!(Checksum::Crc32(crc), compute_checksum(&frame[CHUNK_PREFIX_LEN..], ChecksumAlg::Crc32));
+        }
+    }
+
+    #[test]
+    fn partial_final_chunk_and_short_reads() {
+        let data = plaintext();
+        let frames = compress(Trickle(Cursor::new(data.clone())), CompressionCodec::Lz4);
+
+        // Short reads are refilled: same boundaries as a single-shot reader
+        assert_eq!(frames, compress(Cursor::new(data.clone()), CompressionCodec::Lz4));
+
+        let chunks = decompress(frames.concat(), CompressionCodec::Lz4).unwrap();
+        assert_eq!(chunks.last().unwrap().len(), CHUNK / 2);
+        assert_eq!(chunks.concat(), data);
+
+        assert!(compress(Cursor::new(Vec::new()), CompressionCodec::Zstd).is_empty());
+    }
+
+    #[test]
+    fn corrupted_crc_is_rejected() {
+        for codec in CODECS {
+            let mut framed = compress(Cursor::new(plaintext()), codec).concat();
+            framed[4] ^= 0x01; // stored CRC of the first frame
+
+            let mut stream = DecompressStream::new(Cursor::new(framed), codec as u16, None).unwrap();
+            assert!(matches!(stream.next(), Some(Err(CompressionError::CodecProcessFailed { .. }))), "{codec:?}");
+            assert!(stream.next().is_none(), "stream must stop after an error");
+        }
+    }
+
+    #[test]
+    fn truncated_input_is_rejected() {
+        let framed = compress(Cursor::new(plaintext()), CompressionCodec::Deflate).concat();
+        for cut in [3, CHUNK_PREFIX_LEN + 5, framed.len() - 1] {
+            let result = decompress(framed[..cut].to_vec(), CompressionCodec::Deflate);
+            assert!(matches!(result, Err(CompressionError::StateError(_))), "cut at {cut}");
+        }
+    }
+
+    #[test]
+    fn invalid_arguments_are_rejected() {
+        assert!(matches!(
+            CompressStream::new(Cursor::new(Vec::new()), CompressionCodec::Zstd as u16, 0, None),
+            Err(CompressionError::ChunkTooLarge { .. })
+        ));
+        assert!(matches!(
+            DecompressStream::new(Cursor::new(Vec::new()), 0xFF, None),
+            Err(CompressionError::UnsupportedCodec { codec_id: 0xFF })
+        ));
+    }
+}
@@ -2,7 +2,7 @@
 mod tests {
     use crypto_core::{
         constants::{MAGIC_DICT, MIN_DICT_LEN}, 
-        headers::HeaderV1, 
+        headers::{HeaderV1, decode_header_le}, 
         stream_v2::{
             InputSource, OutputSink, 
             core::{ApiConfig, DecryptParams, EncryptParams, decrypt_stream_v2, encrypt_stream_v2, validate_decrypt_params, validate_dictionary, validate_encrypt_params}, 
@@ -27,6 +27,7 @@ mod tests {
         let params = EncryptParams {
             header: dummy_header(),
             dict: None,
+            ..Default::default()
         };
         let result = validate_encrypt_params(&dummy_master_key(), &params, None, None);
         assert!(result.is_ok(), "Expected valid params to pass");
@@ -37,6 +38,7 @@ mod tests {
         let params = EncryptParams {
             header: dummy_header(),
             dict: None,
+            ..Default::default()
         };
         let bad_key = vec![0x22u8; 15]; // invalid length
         let result = validate_encrypt_params(&bad_key, &params, None, None);
@@ -45,7 +47,7 @@ mod tests {
 
     #[test]
     fn validate_decrypt_params_with_valid_key_and_defaults() {
-        let params = DecryptParams;
+        let params = DecryptParams::new();
         let result = validate_decrypt_params(&dummy_master_key(), &params, None, None);
         assert!(result.is_ok(), "Expected valid decrypt params to pass");
     }
@@ -74,7 +76,7 @@ mod tests {
     fn encrypt_and_decrypt_roundtrip_minimal() {
         let master_key = dummy_master_key();
         let header = dummy_header();
-        let params = EncryptParams { header: header.clone(), dict: None };
+        let params = EncryptParams { header, dict: None, ..Default::default() };
         let config = ApiConfig::new(Some(true), None);
 
         let plaintext = vec![0x55u8; 1024];
@@ -98,9 +100,9 @@ mod tests {
         println!("Ciphertext LEN: {}", ciphertext.len());
         assert!(ciphertext.len() >= HeaderV1::LEN, "Ciphertext missing stream header");
         eprintln!(
-            "[TEST] Ciphertext length = {}, header prefix = {:?}",
+            "[TEST] Ciphertext length = {}, header = {}",
             ciphertext.len(),
-            &ciphertext[..HeaderV1::LEN]
+            decode_header_le(&ciphertext).unwrap().summary()
         );
 
         // Decrypt
@@ -109,7 +111,7 @@ mod tests {
             input_dec, 
             OutputSink::Memory, 
             &master_key, 
-            DecryptParams, 
+            DecryptParams::new(), 
             config
         )
         .expect("decryption should succeed");
@@ -121,7 +123,7 @@ mod tests {
     fn encrypt_and_decrypt_roundtrip() {
         let master_key = dummy_master_key();
         let header = dummy_header();
-        let params = EncryptParams { header: header.clone(), dict: None };
+        let params = EncryptParams { header, dict: None, ..Default::default() };
         let config = ApiConfig::new(Some(true), None);
 
         let plaintext = vec![0x55u8; 1024]; // 1 KiB of data
@@ -137,7 +139,7 @@ mod tests {
         // Decrypt
         let input_dec = InputSource::Memory(snapshot_enc.output.clone().unwrap());
         let output_dec = OutputSink::Memory;
-        let snapshot_dec = decrypt_stream_v2(input_dec, output_dec, &master_key, DecryptParams, config)
+        let snapshot_dec = decrypt_stream_v2(input_dec, output_dec, &master_key, DecryptParams::new(), config)
             .expect("decryption should succeed");
 
         assert_eq!(snapshot_dec.bytes_plaintext, 1024);
@@ -147,7 +149,7 @@ mod tests {
     fn encrypt_stream_with_invalid_key_should_fail() {
         let bad_key = vec![0x33u8; 15]; // invalid length
         let header = dummy_header();
-        let params = EncryptParams { header, dict: None };
+        let params = EncryptParams { header, dict: None, ..Default::default() };
         let config = ApiConfig::new(Some(true), None);
 
         let plaintext = vec![0x44u8; 512];
@@ -165,7 +167,7 @@ mod tests {
         let output = OutputSink::Memory;
         let config = ApiConfig::new(Some(true), None);
 
-        let result = decrypt_stream_v2(input, output, &bad_key, DecryptParams, config);
+        let result = decrypt_stream_v2(input, output, &bad_key, DecryptParams::new(), config);
         assert!(result.is_err(), "Expected decryption to fail with invalid key");
     }
 }
@@ -0,0 +1,169 @@
+// # 📂 `tests/test_counter_accounting.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crypto_core::{
+        compression::CompressionCodec,
+        constants::flags,
+        headers::{HeaderV1, Strategy},
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{DecryptParams, EncryptParams, decrypt_stream_v2, encrypt_stream_v2},
+            framing::{FrameHeader, FrameType, decode::parse_frame_header},
+            segmenting::{SegmentHeader, decode_segment_header, types::SegmentFlags},
+        },
+        telemetry::{TelemetryCounters, TelemetrySnapshot},
+    };
+
+    use crate::common::{self, master_key};
+
+    const CHUNK: usize = 16 * 1024;
+
+    /// Compressible, ≈ 4.5 segments.
+    fn plaintext() -> Vec<u8> {
+        b"counted exactly once, whatever the merge path 0123456789\n"
+            .iter()
+            .cycle()
+            .take(4 * CHUNK + CHUNK / 2)
+            .copied()
+            .collect()
+    }
+
+    /// Byte totals read back from the wire, independent of telemetry.
+    #[derive(Debug, Default)]
+    struct Measured {
+        /// Sum of `bytes_len` over the encrypted segments (index excluded).
+        payload: u64,
+        /// Sum of data-frame `ciphertext_len`.
+        ciphertext: u64,
+        /// Offset just past the final segment.
+        end_of_final: usize,
+    }
+
+    fn measure(stream: &[u8]) -> Measured {
+        let mut measured = Measured::default();
+        let mut offset = HeaderV1::LEN;
+        loop {
+            let header = decode_segment_header(&stream[offset..offset + SegmentHeader::LEN]).unwrap();
+            let wire_start = offset + SegmentHeader::LEN;
+            let wire_end = wire_start + header.wire_len as usize;
+            measured.payload += header.bytes_len as u64;
+
+            let mut frame_offset = wire_start;
+            while frame_offset < wire_end {
+                let frame = parse_frame_header(&stream[frame_offset..wire_end]).unwrap();
+                if frame.frame_type == FrameType::Data {
+                    measured.ciphertext += frame.ciphertext_len as u64;
+                }
+                frame_offset += FrameHeader::LEN + frame.ciphertext_len as usize;
+            }
+
+            offset = wire_end;
+            if header.flags.contains(SegmentFlags::FINAL_SEGMENT) {
+                measured.end_of_final = offset;
+                return measured;
+            }
+        }
+    }
+
+    fn roundtrip(header: HeaderV1, params: impl Fn(EncryptParams<'static>) -> EncryptParams<'static>) -> (Vec<u8>, TelemetrySnapshot, TelemetrySnapshot) {
+        let encrypted = encrypt_stream_v2(
+            InputSource::Memory(plaintext()),
+            OutputSink::Memory,
+            &master_key(),
+            params(EncryptParams::new(header).with_compression(CompressionCodec::Zstd, None)),
+            common::config(),
+        )
+        .unwrap();
+        let stream = encrypted.output.clone().unwrap();
+
+        let decrypted = decrypt_stream_v2(
+            InputSource::Memory(stream.clone()),
+            OutputSink::Memory,
+            &master_key(),
+            DecryptParams::new(),
+            common::config(),
+        )
+        .unwrap();
+        assert_eq!(decrypted.output.as_deref(), Some(&plaintext()[..]));
+        (stream, encrypted, decrypted)
+    }
+
+    fn header(strategy: Strategy, extra_flags: u16) -> HeaderV1 {
+        let base = HeaderV1::test_header();
+        HeaderV1 { chunk_size: CHUNK as u32, strategy: strategy as u16, flags: base.flags | extra_flags, ..base }
+    }
+
+    #[test]
+    fn every_byte_category_is_counted_once() {
+        for strategy in [Strategy::Sequential, Strategy::Parallel] {
+            for extra in [0, flags::HAS_CRC32 | flags::HAS_TOTAL_LEN] {
+                let (stream, enc, dec) = roundtrip(header(strategy, extra), |p| p);
+                let measured = measure(&stream);
+                let case = format!("{strategy:?} flags {extra:#x}");
+
+                assert_eq!(enc.bytes_plaintext, plaintext().len() as u64, "{case}");
+                assert_eq!(dec.bytes_plaintext, plaintext().len() as u64, "{case}");
+
+                assert_eq!(enc.bytes_compressed, measured.payload, "{case}");
+                assert_eq!(dec.bytes_compressed, measured.payload, "{case}");
+                assert!(measured.payload < plaintext().len() as u64, "{case}: zstd should shrink the text");
+
+                assert_eq!(enc.bytes_ciphertext, measured.ciphertext, "{case}");
+                assert_eq!(dec.bytes_ciphertext, measured.ciphertext, "{case}");
+
+                // Ciphertext and overhead partition the stream
+                assert_eq!(enc.bytes_ciphertext + enc.bytes_overhead, stream.len() as u64, "{case}");
+                assert_eq!(dec.bytes_overhead, enc.bytes_overhead, "{case}");
+                assert!(enc.sanity_check() && dec.sanity_check(), "{case}");
+            }
+        }
+    }
+
+    #[test]
+    fn index_trailer_is_encrypt_only_overhead() {
+        let (stream, enc, dec) = roundtrip(header(Strategy::Sequential, 0), |p| p.with_segment_index());
+        let measured = measure(&stream);
+
+        assert_eq!(enc.bytes_ciphertext + enc.bytes_overhead, stream.len() as u64);
+        assert_eq!(enc.bytes_overhead - dec.bytes_overhead, (stream.len() - measured.end_of_final) as u64);
+        assert_eq!(enc.bytes_compressed, dec.bytes_compressed);
+    }
+
+    #[test]
+    fn merge_adds_disjoint_stage_counters() {
+        let reader = TelemetryCounters { bytes_plaintext: 100, ..Default::default() };
+        let compression = TelemetryCounters { bytes_compressed: 60, ..Default::default() };
+        let segment = TelemetryCounters {
+            frames_header: 1,
+            frames_data: 2,
+            frames_digest: 1,
+            frames_terminator: 1,
+            bytes_ciphertext: 92,
+            bytes_overhead: 140,
+            ..Default::default()
+        };
+
+        let mut total = TelemetryCounters::default();
+        for stage in [&reader, &compression, &segment] {
+            total.merge(stage);
+        }
+        assert_eq!(total.bytes_plaintext, 100);
+        assert_eq!(total.bytes_compressed, 60);
+        assert_eq!(total.bytes_ciphertext, 92);
+        assert_eq!(total.bytes_overhead, 140);
+        assert_eq!((total.frames_header, total.frames_data), (1, 2));
+
+        // Merging is addition: `+=` agrees, and merging twice doubles
+        let mut added = TelemetryCounters::default();
+        added += reader.clone();
+        added += compression.clone();
+        added += segment.clone();
+        assert_eq!(added, total);
+
+        total.merge(&segment);
+        assert_eq!(total.bytes_ciphertext, 2 * 92);
+    }
+}
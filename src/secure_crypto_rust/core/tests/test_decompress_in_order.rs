@@ -0,0 +1,148 @@
+// # 📂 `tests/test_decompress_in_order.rs`
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    use crypto_core::{
+        compression::CompressionCodec,
+        crypto::{DigestAlg, KEY_LEN_32},
+        headers::HeaderV1,
+        recovery::AsyncLogManager,
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{ApiConfig, DecryptParams, EncryptParams, decrypt_stream_v2, encrypt_stream_v2},
+            io::PayloadReader,
+            parallelism::HybridParallelismProfile,
+            pipeline::{PipelineConfig, run_decrypt_pipeline, run_encrypt_pipeline},
+            segment_worker::{DecryptContext, EncryptContext, SegmentHook},
+            segmenting::SegmentHeader,
+        },
+        telemetry::{Stage, TelemetrySnapshot},
+    };
+
+    const CHUNK: usize = 16 * 1024;
+    /// Full segments; with the partial and final ones, everything after
+    /// segment 0 still fits the reorder buffer of `profile()` (8).
+    const SEGMENTS: usize = 6;
+
+    fn profile() -> HybridParallelismProfile {
+        HybridParallelismProfile::new(4, 0, 8)
+    }
+
+    fn plaintext() -> Vec<u8> {
+        b"segments wait compressed until their turn comes. "
+            .iter()
+            .cycle()
+            .take(SEGMENTS * CHUNK + 777)
+            .copied()
+            .collect()
+    }
+
+    fn encrypt(data: &[u8]) -> Vec<u8> {
+        let header = HeaderV1 {
+            compression: CompressionCodec::Zstd as u16,
+            chunk_size: CHUNK as u32,
+            ..HeaderV1::test_header()
+        };
+        let mut crypto = EncryptContext::new(header, profile(), &[0x42u8; KEY_LEN_32], DigestAlg::Blake3).unwrap();
+        let mut out = Cursor::new(Vec::new());
+        run_encrypt_pipeline(
+            &mut PayloadReader::new(Cursor::new(data.to_vec())),
+            &mut out,
+            &mut crypto,
+            &PipelineConfig::new(profile(), None),
+            Arc::new(AsyncLogManager::disabled()),
+        )
+        .unwrap();
+        out.into_inner()
+    }
+
+    /// Segment 0 finishes last, so every later segment waits in the reorder buffer.
+    fn delay_first_segment() -> SegmentHook {
+        Arc::new(|header: &SegmentHeader| {
+            if header.segment_index == 0 {
+                thread::sleep(Duration::from_millis(100));
+            }
+        })
+    }
+
+    fn decrypt(stream: Vec<u8>, in_order: bool) -> (TelemetrySnapshot, Vec<u8>) {
+        let (header, mut reader) = PayloadReader::with_header(Cursor::new(stream)).unwrap();
+        let mut crypto = DecryptContext::from_stream_header(header, profile(), &[0x42u8; KEY_LEN_32], DigestAlg::Blake3).unwrap();
+        let config = PipelineConfig::new(profile(), None)
+            .with_decompress_in_order(in_order)
+            .with_segment_hook(Some(delay_first_segment()));
+        let mut out = Cursor::new(Vec::new());
+        let snapshot = run_decrypt_pipeline(
+            &mut reader,
+            &mut out,
+            &mut crypto,
+            &config,
+            Arc::new(AsyncLogManager::disabled()),
+        )
+        .unwrap();
+        (snapshot, out.into_inner())
+    }
+
+    #[test]
+    fn delayed_first_segment_decrypts_in_both_modes() {
+        let data = plaintext();
+        let stream = encrypt(&data);
+
+        for in_order in [false, true] {
+            let (snapshot, out) = decrypt(stream.clone(), in_order);
+            assert_eq!(out, data, "decompress_in_order = {in_order}");
+            assert_eq!(snapshot.bytes_plaintext, data.len() as u64, "decompress_in_order = {in_order}");
+            assert!(snapshot.bytes_compressed < snapshot.bytes_plaintext);
+            assert_eq!(snapshot.segments_processed, SEGMENTS as u64 + 2);
+        }
+    }
+
+    #[test]
+    fn decompress_stage_is_attributed_in_both_modes() {
+        let stream = encrypt(&plaintext());
+
+        for in_order in [false, true] {
+            let (snapshot, _) = decrypt(stream.clone(), in_order);
+            let times = &snapshot.stage_times;
+            assert!(times.has_all(&[Stage::Decrypt, Stage::Decompress, Stage::Write]), "{times}");
+            assert!(times.get(Stage::Decompress) > Duration::ZERO, "{times}");
+            assert!(snapshot.sanity_check(), "decompress_in_order = {in_order}: {times}");
+        }
+    }
+
+    #[test]
+    fn api_roundtrip_decompresses_in_order() {
+        let data = plaintext();
+        for codec in [CompressionCodec::Zstd, CompressionCodec::Deflate, CompressionCodec::Lz4, CompressionCodec::Auto] {
+            let header = HeaderV1 { compression: codec as u16, chunk_size: CHUNK as u32, ..HeaderV1::test_header() };
+            // A plaintext trailer is checked against decompressed output too
+            let params = EncryptParams::new(header).with_plaintext_size(data.len() as u64);
+            let stream = encrypt_stream_v2(
+                InputSource::Memory(data.clone()),
+                OutputSink::Memory,
+                &[0x11u8; 32],
+                params,
+                ApiConfig::new(Some(true), None).with_profile(profile()),
+            )
+            .unwrap()
+            .output
+            .unwrap();
+
+            let dec = decrypt_stream_v2(
+                InputSource::Memory(stream),
+                OutputSink::Memory,
+                &[0x11u8; 32],
+                DecryptParams::new(),
+                ApiConfig::new(Some(true), None).with_profile(profile()).with_decompress_in_order(true),
+            )
+            .unwrap();
+            assert_eq!(dec.output.as_deref(), Some(data.as_slice()), "{codec:?}");
+            assert_eq!(dec.bytes_plaintext, data.len() as u64, "{codec:?}");
+        }
+    }
+}
@@ -0,0 +1,135 @@
+// # 📂 `tests/test_decompression_bomb.rs`
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use crossbeam::channel::unbounded;
+    use crypto_core::{
+        compression::{CodecLevel, CompressionError, DECOMPRESS_SLACK, codec_ids, create_compressor, create_decompressor},
+        headers::HeaderV1,
+        stream_v2::{
+            compression_worker::{CodecInfo, CompressionWorkerError, CpuCompressionBackend, run_decompression_worker},
+            segment_worker::DecryptedSegment,
+            segmenting::{SegmentHeader, types::SegmentFlags},
+        },
+        telemetry::{StageTimes, TelemetryCounters},
+    };
+
+    const CHUNK: usize = 64 * 1024;
+    const LIMIT: usize = CHUNK + DECOMPRESS_SLACK;
+
+    fn compress(codec_id: u16, plaintext: &[u8]) -> Vec<u8> {
+        let mut compressor = create_compressor(codec_id, None).unwrap();
+        let mut out = Vec::new();
+        compressor.compress_chunk(plaintext, &mut out).unwrap();
+        out
+    }
+
+    fn assert_too_large(codec_id: u16, plaintext_len: usize) {
+        let bomb = compress(codec_id, &vec![0u8; plaintext_len]);
+        let mut decompressor = create_decompressor(codec_id, None).unwrap();
+        decompressor.set_max_output(LIMIT);
+
+        let mut out = Vec::new();
+        match decompressor.decompress_chunk(&bomb, &mut out) {
+            Err(CompressionError::ChunkTooLarge { have, max }) => {
+                assert_eq!(max, LIMIT);
+                assert!(have > LIMIT, "codec {codec_id:#06x} reported {have}");
+            }
+            other => panic!("codec {codec_id:#06x}: expected ChunkTooLarge, got {other:?}"),
+        }
+        assert!(out.is_empty(), "no plaintext may be emitted past the cap");
+    }
+
+    #[test]
+    fn zstd_bomb_is_rejected_before_decompression() {
+        // 100 MiB of zeros compresses to a few KiB
+        let bomb = compress(codec_ids::ZSTD, &vec![0u8; 100 * 1024 * 1024]);
+        assert!(bomb.len() < CHUNK);
+
+        let mut decompressor = create_decompressor(codec_ids::ZSTD, None).unwrap();
+        decompressor.set_max_output(LIMIT);
+        let mut out = Vec::new();
+        let err = decompressor.decompress_chunk(&bomb, &mut out).unwrap_err();
+        assert!(matches!(err, CompressionError::ChunkTooLarge { have, max } if have == 100 * 1024 * 1024 && max == LIMIT));
+    }
+
+    #[test]
+    fn every_codec_enforces_the_cap() {
+        for codec_id in [codec_ids::LZ4, codec_ids::DEFLATE, codec_ids::AUTO] {
+            assert_too_large(codec_id, 1024 * 1024);
+        }
+    }
+
+    #[test]
+    fn output_within_slack_is_accepted() {
+        for codec_id in [codec_ids::ZSTD, codec_ids::LZ4, codec_ids::DEFLATE, codec_ids::AUTO] {
+            let plaintext = vec![7u8; LIMIT];
+            let mut decompressor = create_decompressor(codec_id, None).unwrap();
+            decompressor.set_max_output(LIMIT);
+            let mut out = Vec::new();
+            decompressor.decompress_chunk(&compress(codec_id, &plaintext), &mut out).unwrap();
+            assert_eq!(out, plaintext);
+        }
+    }
+
+    #[test]
+    fn codec_info_caps_output_at_header_chunk_size() {
+        let header = HeaderV1 { compression: codec_ids::ZSTD, ..HeaderV1::test_header() };
+        assert_eq!(header.chunk_size as usize, CHUNK);
+        let info = CodecInfo::from_header(&header, None).unwrap();
+        assert_eq!(info.max_output, Some(LIMIT));
+        assert_eq!(info.output_limit(), LIMIT);
+    }
+
+    fn bomb_segment(codec_id: u16, plaintext_len: usize) -> DecryptedSegment {
+        let wire = Bytes::from(compress(codec_id, &vec![0u8; plaintext_len]));
+        DecryptedSegment {
+            header: SegmentHeader::new(&wire, 0, wire.len() as u32, 1, 0, SegmentFlags::empty()),
+            frames: vec![wire],
+            counters: TelemetryCounters::default(),
+            stage_times: StageTimes::default(),
+        }
+    }
+
+    fn run_worker(codec_info: CodecInfo<'static>, max_output: usize, seg: DecryptedSegment) -> CompressionWorkerError {
+        let backend = CpuCompressionBackend::new(codec_info).unwrap();
+        let (in_tx, in_rx) = unbounded();
+        let (out_tx, out_rx) = unbounded();
+        in_tx.send(seg).unwrap();
+        drop(in_tx);
+
+        run_decompression_worker(in_rx, out_tx, Box::new(backend), max_output);
+        let results: Vec<_> = out_rx.iter().collect();
+        assert_eq!(results.len(), 1);
+        results.into_iter().next().unwrap().unwrap_err()
+    }
+
+    #[test]
+    fn worker_rejects_segment_larger_than_header_chunk_size() {
+        // Forged stream: 64 KiB chunk size, one segment decompressing to 100 MiB
+        let header = HeaderV1 { compression: codec_ids::ZSTD, ..HeaderV1::test_header() };
+        let info = CodecInfo::from_header(&header, None).unwrap();
+        let limit = info.output_limit();
+
+        let err = run_worker(info, limit, bomb_segment(codec_ids::ZSTD, 100 * 1024 * 1024));
+        assert!(matches!(err, CompressionWorkerError::Compression(CompressionError::ChunkTooLarge { max, .. }) if max == LIMIT));
+    }
+
+    #[test]
+    fn worker_checks_total_output_even_with_uncapped_backend() {
+        let info = CodecInfo {
+            codec_id: codec_ids::LZ4,
+            level: CodecLevel::Lz4Fast,
+            dict: None,
+            gpu: None,
+            max_output: None,
+            segment_dicts: None,
+        };
+        let err = run_worker(info, LIMIT, bomb_segment(codec_ids::LZ4, 1024 * 1024));
+        assert!(matches!(
+            err,
+            CompressionWorkerError::Compression(CompressionError::ChunkTooLarge { have, max }) if have == 1024 * 1024 && max == LIMIT
+        ));
+    }
+}
@@ -0,0 +1,96 @@
+// # 📂 `tests/test_decrypt_pins.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read};
+
+    use crypto_core::{
+        compression::CompressionCodec,
+        headers::{AadDomain, CipherSuite, HeaderV1},
+        stream_v2::{
+            DecryptingReader,
+            core::{DecryptParams, EncryptParams},
+        },
+        types::StreamError,
+    };
+
+    use crate::common::{self, master_key};
+
+    const CHUNK: usize = 16 * 1024;
+
+    fn plaintext() -> Vec<u8> {
+        b"pinned header fields\n".repeat(2 * CHUNK / 21)
+    }
+
+    /// ChaCha20-Poly1305, Zstd, key id 7, `FileEnvelope`.
+    fn encrypt() -> Vec<u8> {
+        let header = HeaderV1 {
+            chunk_size: CHUNK as u32,
+            aad_domain: AadDomain::FileEnvelope as u16,
+            ..HeaderV1::test_header()
+        };
+        let params = EncryptParams::new(header).with_compression(CompressionCodec::Zstd, None).with_key_id(7);
+        common::encrypt(&plaintext(), params)
+    }
+
+    fn decrypt(params: DecryptParams, key: &[u8]) -> Result<Vec<u8>, StreamError> {
+        common::try_decrypt(encrypt(), key, params, common::config())
+    }
+
+    fn assert_mismatch(params: DecryptParams, field: &str) {
+        match decrypt(params, &master_key()) {
+            Err(StreamError::Validation(msg)) => assert!(msg.contains(&format!("header {field} mismatch")), "{msg}"),
+            other => panic!("expected a {field} mismatch, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn unpinned_params_accept_any_header() {
+        assert_eq!(decrypt(DecryptParams::new(), &master_key()).unwrap(), plaintext());
+    }
+
+    #[test]
+    fn matching_pins_pass() {
+        let params = DecryptParams::new()
+            .expect_cipher(CipherSuite::Chacha20Poly1305)
+            .expect_compression(CompressionCodec::Zstd)
+            .expect_key_id(7)
+            .expect_aad_domain(AadDomain::FileEnvelope);
+        assert_eq!(decrypt(params, &master_key()).unwrap(), plaintext());
+    }
+
+    #[test]
+    fn each_pin_rejects_a_mismatch() {
+        assert_mismatch(DecryptParams::new().expect_cipher(CipherSuite::Aes256Gcm), "cipher");
+        assert_mismatch(DecryptParams::new().expect_compression(CompressionCodec::Lz4), "compression");
+        assert_mismatch(DecryptParams::new().expect_key_id(8), "key_id");
+    }
+
+    #[test]
+    fn combined_pins_report_the_mismatched_field() {
+        let params = DecryptParams::new()
+            .expect_cipher(CipherSuite::Chacha20Poly1305)
+            .expect_compression(CompressionCodec::Zstd)
+            .expect_key_id(9)
+            .expect_aad_domain(AadDomain::FileEnvelope);
+        assert_mismatch(params, "key_id");
+    }
+
+    #[test]
+    fn pins_are_checked_before_key_derivation() {
+        // A wrong key would fail key confirmation; the pin must fail first
+        let wrong_key = vec![0x22u8; 32];
+        let result = decrypt(DecryptParams::new().expect_key_id(8), &wrong_key);
+        assert!(matches!(result, Err(StreamError::Validation(_))), "{result:?}");
+    }
+
+    #[test]
+    fn streaming_reader_honours_pins() {
+        let params = DecryptParams::new().expect_cipher(CipherSuite::Aes256Gcm);
+        let mut reader = DecryptingReader::new(Cursor::new(encrypt()), &master_key(), params).unwrap();
+        let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        assert!(err.to_string().contains("header cipher mismatch"), "{err}");
+    }
+}
@@ -0,0 +1,97 @@
+// # 📂 `tests/test_dict_binding.rs`
+
+#[cfg(test)]
+mod tests {
+    use crossbeam::channel::bounded;
+    use crypto_core::{
+        compression::{CodecLevel, CompressionCodec},
+        headers::HeaderV1,
+        stream_v2::{
+            compression_pipeline::{spawn_compression_workers, spawn_decompression_workers},
+            compression_worker::{CodecInfo, CompressionWorkerError},
+            parallelism::HybridParallelismProfile,
+        },
+    };
+
+    const DICT_ID: u32 = 7;
+    const DICT: &[u8] = b"shared dictionary bytes for the binding checks";
+
+    fn header(codec: CompressionCodec, dict_used: bool) -> HeaderV1 {
+        let mut header = HeaderV1 { compression: codec as u16, ..HeaderV1::test_header() };
+        if dict_used {
+            header.set_dict_id(DICT_ID);
+        }
+        header
+    }
+
+    fn codec_info(codec: CompressionCodec, dict: Option<&'static [u8]>) -> CodecInfo<'static> {
+        let level = match codec {
+            CompressionCodec::Lz4 => CodecLevel::Lz4Fast,
+            _ => CodecLevel::ZstdBalanced,
+        };
+        CodecInfo { codec_id: codec as u16, level, dict, gpu: None, max_output: None, segment_dicts: None }
+    }
+
+    fn check(codec: CompressionCodec, dict_used: bool, dict: Option<&'static [u8]>) -> Result<(), CompressionWorkerError> {
+        codec_info(codec, dict).check_dict_binding(&header(codec, dict_used))
+    }
+
+    #[test]
+    fn flag_and_dictionary_must_agree() {
+        for codec in [CompressionCodec::Zstd, CompressionCodec::Lz4] {
+            assert!(check(codec, true, Some(DICT)).is_ok(), "{codec:?}");
+            assert!(check(codec, false, None).is_ok(), "{codec:?}");
+            assert!(
+                matches!(check(codec, true, None), Err(CompressionWorkerError::DictionaryRequired { dict_id: DICT_ID })),
+                "{codec:?}"
+            );
+            assert!(matches!(check(codec, false, Some(DICT)), Err(CompressionWorkerError::UnexpectedDictionary)), "{codec:?}");
+        }
+    }
+
+    #[test]
+    fn workers_refuse_to_start_on_mismatch() {
+        for codec in [CompressionCodec::Zstd, CompressionCodec::Lz4] {
+            for (dict_used, dict) in [(true, None), (false, Some(DICT))] {
+                let header = header(codec, dict_used);
+
+                let (_in_tx, in_rx) = bounded(1);
+                let (out_tx, _out_rx) = bounded(1);
+                let err = spawn_compression_workers(
+                    HybridParallelismProfile::single_threaded(),
+                    codec_info(codec, dict),
+                    in_rx,
+                    out_tx,
+                    &header,
+                )
+                .unwrap_err();
+                assert!(
+                    matches!(err, CompressionWorkerError::DictionaryRequired { .. } | CompressionWorkerError::UnexpectedDictionary),
+                    "{codec:?}: {err}"
+                );
+
+                let (_in_tx, in_rx) = bounded(1);
+                let (out_tx, _out_rx) = bounded(1);
+                let err = spawn_decompression_workers(
+                    HybridParallelismProfile::single_threaded(),
+                    codec_info(codec, dict),
+                    in_rx,
+                    out_tx,
+                    &header,
+                )
+                .unwrap_err();
+                assert!(
+                    matches!(err, CompressionWorkerError::DictionaryRequired { .. } | CompressionWorkerError::UnexpectedDictionary),
+                    "{codec:?}: {err}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn error_messages_name_the_mismatch() {
+        let required = CompressionWorkerError::DictionaryRequired { dict_id: DICT_ID };
+        assert!(required.to_string().contains("dictionary 7 required"), "{required}");
+        assert!(CompressionWorkerError::UnexpectedDictionary.to_string().contains("DICT_USED"));
+    }
+}
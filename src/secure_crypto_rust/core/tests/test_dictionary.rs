@@ -0,0 +1,147 @@
+// # 📂 `tests/test_dictionary.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crypto_core::{
+        compression::{CompressionCodec, CompressionError, DictionaryRegistry, train_dictionary},
+        constants::MAGIC_DICT,
+        headers::{HeaderV1, decode_header_le},
+        stream_v2::core::{DecryptParams, EncryptParams},
+        types::StreamError,
+    };
+
+    use crate::common::{self, decrypt};
+
+    const DICT_ID: u32 = 7;
+
+    fn record(i: usize) -> String {
+        format!(
+            "{{\"id\":{},\"user\":\"user-{}\",\"role\":\"{}\",\"active\":{},\"region\":\"eu-west-{}\"}}\n",
+            i,
+            i % 97,
+            if i.is_multiple_of(3) { "admin" } else { "member" },
+            i.is_multiple_of(2),
+            i % 4
+        )
+    }
+
+    fn samples() -> Vec<Vec<u8>> {
+        (0..1000).map(|i| record(i).into_bytes()).collect()
+    }
+
+    fn plaintext() -> Vec<u8> {
+        (5000..5600).flat_map(|i| record(i).into_bytes()).collect()
+    }
+
+    fn trained_registry() -> Arc<DictionaryRegistry> {
+        let samples = samples();
+        let refs: Vec<&[u8]> = samples.iter().map(|s| s.as_slice()).collect();
+        let dict = train_dictionary(&refs, 4096).expect("training should succeed");
+
+        let mut registry = DictionaryRegistry::new();
+        registry.register(DICT_ID, dict).unwrap();
+        Arc::new(registry)
+    }
+
+    fn raw_registry() -> Arc<DictionaryRegistry> {
+        let mut dict = MAGIC_DICT.to_vec();
+        dict.extend(samples().concat().into_iter().take(8 * 1024));
+
+        let mut registry = DictionaryRegistry::new();
+        registry.register(DICT_ID, dict).unwrap();
+        Arc::new(registry)
+    }
+
+    fn encrypt(codec: CompressionCodec, registry: Arc<DictionaryRegistry>, dict_id: u32) -> Result<Vec<u8>, StreamError> {
+        let header = HeaderV1 { chunk_size: 16 * 1024, ..HeaderV1::test_header() };
+        let params = EncryptParams::new(header)
+            .with_compression(codec, None)
+            .with_dictionary(registry, dict_id);
+        common::try_encrypt(&plaintext(), params, common::config())
+    }
+
+    #[test]
+    fn register_validates_framing_once() {
+        let mut registry = DictionaryRegistry::new();
+
+        // Missing MAGIC_DICT prefix
+        assert!(registry.register(1, vec![0xAB; 64]).is_err());
+        // Too short
+        assert!(registry.register(1, MAGIC_DICT.to_vec()).is_err());
+        // Id 0 means "no dictionary"
+        let mut dict = MAGIC_DICT.to_vec();
+        dict.extend_from_slice(&[0x5A; 60]);
+        assert!(registry.register(0, dict.clone()).is_err());
+
+        registry.register(1, dict.clone()).unwrap();
+        assert!(matches!(registry.register(1, dict), Err(CompressionError::InvalidDictionary { dict_id: 1 })));
+
+        // Lookups return the codec payload without the framing
+        assert_eq!(registry.get(1).unwrap(), &[0x5A; 60][..]);
+        assert!(registry.get(2).is_none());
+    }
+
+    #[test]
+    fn trained_dictionary_is_framed() {
+        let samples = samples();
+        let refs: Vec<&[u8]> = samples.iter().map(|s| s.as_slice()).collect();
+        let dict = train_dictionary(&refs, 4096).unwrap();
+
+        assert!(dict.starts_with(MAGIC_DICT));
+        assert!(dict.len() <= 4096);
+    }
+
+    #[test]
+    fn zstd_roundtrip_with_trained_dictionary() {
+        let registry = trained_registry();
+        let ciphertext = encrypt(CompressionCodec::Zstd, registry.clone(), DICT_ID).expect("encryption should succeed");
+
+        let header = decode_header_le(&ciphertext[..HeaderV1::LEN]).unwrap();
+        assert_eq!(header.dict_id, DICT_ID);
+
+        let out = decrypt(ciphertext, DecryptParams::new().with_dictionaries(registry)).expect("decryption should succeed");
+        assert_eq!(out, plaintext());
+    }
+
+    #[test]
+    fn lz4_roundtrip_with_raw_dictionary() {
+        let registry = raw_registry();
+        let ciphertext = encrypt(CompressionCodec::Lz4, registry.clone(), DICT_ID).unwrap();
+
+        let out = decrypt(ciphertext, DecryptParams::new().with_dictionaries(registry)).unwrap();
+        assert_eq!(out, plaintext());
+    }
+
+    #[test]
+    fn decrypt_fails_for_unknown_dict_id() {
+        let ciphertext = encrypt(CompressionCodec::Zstd, trained_registry(), DICT_ID).unwrap();
+
+        // Registry without the stream's dictionary
+        let err = decrypt(ciphertext.clone(), DecryptParams::new().with_dictionaries(raw_registry_with_id(DICT_ID + 1)))
+            .unwrap_err();
+        assert!(matches!(err, StreamError::Compression(CompressionError::InvalidDictionary { dict_id: DICT_ID })), "unexpected error: {err}");
+
+        // No registry at all
+        let err = decrypt(ciphertext, DecryptParams::new()).unwrap_err();
+        assert!(matches!(err, StreamError::Compression(CompressionError::InvalidDictionary { dict_id: DICT_ID })), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn encrypt_fails_fast_for_unknown_dict_id() {
+        let err = encrypt(CompressionCodec::Zstd, trained_registry(), DICT_ID + 1).unwrap_err();
+        assert!(matches!(err, StreamError::Compression(CompressionError::InvalidDictionary { .. })), "unexpected error: {err}");
+    }
+
+    fn raw_registry_with_id(dict_id: u32) -> Arc<DictionaryRegistry> {
+        let mut dict = MAGIC_DICT.to_vec();
+        dict.extend_from_slice(&[0x20; 256]);
+
+        let mut registry = DictionaryRegistry::new();
+        registry.register(dict_id, dict).unwrap();
+        Arc::new(registry)
+    }
+}
@@ -13,7 +13,7 @@
 #[cfg(test)]
 mod tests {
     use proptest::prelude::*;
-    use crypto_core::crypto::{DigestAlg, SegmentDigestBuilder, DigestError, DigestFrame, SegmentDigestVerifier};
+    use crypto_core::crypto::{DigestAlg, SegmentDigestBuilder, DigestError, DigestFrame, SegmentDigestVerifier, ct_eq};
 
 
     fn run_roundtrip(alg: DigestAlg) {
@@ -28,7 +28,7 @@ mod tests {
         // Build digest
         let mut builder = SegmentDigestBuilder::new(alg, segment_index, frame_count);
         for (idx, ct) in &frames {
-            builder.update_frame(*idx, ct);
+            builder.update_frame(*idx, ct).unwrap();
         }
         let digest_bytes = builder.finalize();
 
@@ -42,7 +42,7 @@ mod tests {
         // Verify with SegmentDigestVerifier
         let mut verifier = SegmentDigestVerifier::new(alg, segment_index, frame_count, digest_bytes);
         for (idx, ct) in &frames {
-            verifier.update_frame(*idx, ct);
+            verifier.update_frame(*idx, ct).unwrap();
         }
         assert!(verifier.finalize().is_ok(), "digest mismatch for {:?}", alg);
     }
@@ -94,7 +94,7 @@ mod tests {
 
         // Build digest
         let mut builder = SegmentDigestBuilder::new(DigestAlg::Sha256, segment_index, frame_count);
-        builder.update_frame(0, &ciphertext);
+        builder.update_frame(0, &ciphertext).unwrap();
         let digest_bytes = builder.finalize();
 
         // Tamper ciphertext
@@ -102,7 +102,7 @@ mod tests {
 
         // Verifier should fail
         let mut verifier = SegmentDigestVerifier::new(DigestAlg::Sha256, segment_index, frame_count, digest_bytes);
-        verifier.update_frame(0, &tampered);
+        verifier.update_frame(0, &tampered).unwrap();
         let result = verifier.finalize();
         assert!(matches!(result, Err(DigestError::DigestMismatch)));
     }
@@ -170,7 +170,7 @@ mod tests {
             // Build digest
             let mut builder = SegmentDigestBuilder::new(alg, segment_index, frame_count);
             for (i, ct) in frames.iter().enumerate() {
-                builder.update_frame(i as u32, ct);
+                builder.update_frame(i as u32, ct).unwrap();
             }
             let digest_bytes = builder.finalize();
 
@@ -184,7 +184,7 @@ mod tests {
             // Verify
             let mut verifier = SegmentDigestVerifier::new(alg, segment_index, frame_count, digest_bytes);
             for (i, ct) in frames.iter().enumerate() {
-                verifier.update_frame(i as u32, ct);
+                verifier.update_frame(i as u32, ct).unwrap();
             }
             prop_assert!(verifier.finalize().is_ok());
         }
@@ -199,7 +199,7 @@ mod tests {
         ) {
             let frame_count = 1u32;
             let mut builder = SegmentDigestBuilder::new(DigestAlg::Sha256, segment_index, frame_count);
-            builder.update_frame(0, &ciphertext);
+            builder.update_frame(0, &ciphertext).unwrap();
             let digest_bytes = builder.finalize();
 
             // Tamper ciphertext by flipping a bit
@@ -207,7 +207,7 @@ mod tests {
             tampered[0] ^= 0xFF;
 
             let mut verifier = SegmentDigestVerifier::new(DigestAlg::Sha256, segment_index, frame_count, digest_bytes);
-            verifier.update_frame(0, &tampered);
+            verifier.update_frame(0, &tampered).unwrap();
             let result = verifier.finalize();
             prop_assert!(matches!(result, Err(DigestError::DigestMismatch)));
         }
@@ -254,7 +254,7 @@ mod tests {
         let mut builder = SegmentDigestBuilder::new(DigestAlg::Sha256, 7, frames.len() as u32);
 
         for (i, data) in &frames {
-            builder.update_frame(*i, data);
+            builder.update_frame(*i, data).unwrap();
         }
 
         let digest = builder.finalize();
@@ -267,7 +267,7 @@ mod tests {
         );
 
         for (i, data) in frames {
-            verifier.update_frame(i, &data);
+            verifier.update_frame(i, &data).unwrap();
         }
 
         assert!(verifier.finalize().is_ok());
@@ -278,7 +278,7 @@ mod tests {
     #[test]
     fn digest_mismatch_detected() {
         let mut builder = SegmentDigestBuilder::new(DigestAlg::Sha256, 1, 1);
-        builder.update_frame(0, b"correct");
+        builder.update_frame(0, b"correct").unwrap();
         let digest = builder.finalize();
 
         let mut verifier = SegmentDigestVerifier::new(
@@ -288,7 +288,7 @@ mod tests {
             digest,
         );
 
-        verifier.update_frame(0, b"tampered");
+        verifier.update_frame(0, b"tampered").unwrap();
         assert!(verifier.finalize().is_err());
     }
 
@@ -297,7 +297,7 @@ mod tests {
     #[test]
     fn digest_sha512_works() {
         let mut builder = SegmentDigestBuilder::new(DigestAlg::Sha512, 42, 1);
-        builder.update_frame(0, b"data");
+        builder.update_frame(0, b"data").unwrap();
         let digest = builder.finalize();
 
         let mut verifier = SegmentDigestVerifier::new(
@@ -307,7 +307,7 @@ mod tests {
             digest,
         );
 
-        verifier.update_frame(0, b"data");
+        verifier.update_frame(0, b"data").unwrap();
         assert!(verifier.finalize().is_ok());
     }
 
@@ -316,8 +316,8 @@ mod tests {
     #[test]
     fn digest_blake3_works() {
         let mut builder = SegmentDigestBuilder::new(DigestAlg::Blake3, 99, 2);
-        builder.update_frame(0, b"a");
-        builder.update_frame(1, b"b");
+        builder.update_frame(0, b"a").unwrap();
+        builder.update_frame(1, b"b").unwrap();
         let digest = builder.finalize();
 
         let mut verifier = SegmentDigestVerifier::new(
@@ -327,8 +327,8 @@ mod tests {
             digest,
         );
 
-        verifier.update_frame(0, b"a");
-        verifier.update_frame(1, b"b");
+        verifier.update_frame(0, b"a").unwrap();
+        verifier.update_frame(1, b"b").unwrap();
         assert!(verifier.finalize().is_ok());
     }
 
@@ -365,12 +365,100 @@ mod tests {
         let mut a = SegmentDigestBuilder::new(DigestAlg::Sha256, 1, 1);
         let mut b = SegmentDigestBuilder::new(DigestAlg::Sha256, 1, 1);
 
-        a.update_frame(0, b"x");
-        b.update_frame(0, b"x");
+        a.update_frame(0, b"x").unwrap();
+        b.update_frame(0, b"x").unwrap();
 
         assert_eq!(a.finalize(), b.finalize());
     }
 
+    // ## 8️⃣ Canonical frame order
+
+    #[test]
+    fn out_of_order_frame_rejected() {
+        let mut builder = SegmentDigestBuilder::new(DigestAlg::Sha256, 1, 3);
+        builder.update_frame(0, b"a").unwrap();
+        assert!(matches!(
+            builder.update_frame(2, b"c"),
+            Err(DigestError::FrameOrderViolation { expected: 1, got: 2 })
+        ));
+        // Nothing was hashed: the right frame is still accepted
+        builder.update_frame(1, b"b").unwrap();
+        builder.update_frame(2, b"c").unwrap();
+
+        let mut verifier = SegmentDigestVerifier::new(DigestAlg::Sha256, 1, 3, builder.finalize());
+        assert!(matches!(
+            verifier.update_frame(1, b"b"),
+            Err(DigestError::FrameOrderViolation { expected: 0, got: 1 })
+        ));
+        assert_eq!(verifier.next_frame_index(), 0);
+    }
+
+    #[test]
+    fn duplicate_frame_rejected() {
+        let mut builder = SegmentDigestBuilder::new(DigestAlg::Blake3, 5, 2);
+        builder.update_frame(0, b"x").unwrap();
+        assert!(matches!(
+            builder.update_frame(0, b"x"),
+            Err(DigestError::FrameOrderViolation { expected: 1, got: 0 })
+        ));
+
+        let mut verifier = SegmentDigestVerifier::new(DigestAlg::Blake3, 5, 2, Vec::new());
+        verifier.update_frame(0, b"x").unwrap();
+        assert!(matches!(
+            verifier.update_frame(0, b"x"),
+            Err(DigestError::FrameOrderViolation { expected: 1, got: 0 })
+        ));
+    }
+
+    #[test]
+    fn resumed_state_continues_at_its_frame() {
+        let mut builder = SegmentDigestBuilder::new(DigestAlg::Sha256, 2, 3);
+        builder.update_frame(0, b"a").unwrap();
+        builder.update_frame(1, b"b").unwrap();
+
+        let mut resumed = SegmentDigestBuilder::with_state(builder.state(), 2, 3, builder.next_frame_index);
+        assert!(matches!(
+            resumed.update_frame(1, b"b"),
+            Err(DigestError::FrameOrderViolation { expected: 2, got: 1 })
+        ));
+        resumed.update_frame(2, b"c").unwrap();
+        builder.update_frame(2, b"c").unwrap();
+        assert_eq!(resumed.finalize(), builder.finalize());
+    }
+
+    #[test]
+    fn verifier_rejects_single_byte_flips() {
+        let mut builder = SegmentDigestBuilder::new(DigestAlg::Sha256, 9, 1);
+        builder.update_frame(0, b"payload").unwrap();
+        let digest = builder.finalize();
+
+        let verify = |expected: Vec<u8>| {
+            let mut verifier = SegmentDigestVerifier::new(DigestAlg::Sha256, 9, 1, expected);
+            verifier.update_frame(0, b"payload").unwrap();
+            verifier.finalize()
+        };
+        assert!(verify(digest.clone()).is_ok());
+
+        // Equal length, differing only in the first or the last byte
+        for pos in [0, digest.len() - 1] {
+            let mut tampered = digest.clone();
+            tampered[pos] ^= 0x01;
+            assert!(!ct_eq(&digest, &tampered));
+            assert!(matches!(verify(tampered), Err(DigestError::DigestMismatch)), "byte {pos}");
+        }
+        // A truncated digest never matches
+        assert!(matches!(verify(digest[..digest.len() - 1].to_vec()), Err(DigestError::DigestMismatch)));
+    }
+
+    #[test]
+    fn ct_eq_matches_slice_equality() {
+        assert!(ct_eq(b"", b""));
+        assert!(ct_eq(b"abc", b"abc"));
+        assert!(!ct_eq(b"abc", b"abd"));
+        assert!(!ct_eq(b"abc", b"xbc"));
+        assert!(!ct_eq(b"abc", b"ab"));
+    }
+
     // # 🏁 Final assessment
 
     // ✅ Spec-safe digest framing
@@ -5,7 +5,7 @@
 #[cfg(test)]
 mod tests {
     use bytes::Bytes;
-    use crypto_core::{crypto::{DigestAlg, DigestError, DigestFrame}, stream_v2::{frame_worker::{FrameInput, FrameWorkerError}, framing::FrameType}};
+    use crypto_core::{crypto::{DigestAlg, DigestError, DigestFrame}, stream_v2::{frame_worker::{FrameInput, FrameWorkerError}, framing::FrameType, segmenting::types::SegmentFlags}};
 
     fn make_digest_frame(alg: DigestAlg, digest: &[u8]) -> FrameInput {
         // let mut buf = Vec::new();
@@ -23,6 +23,7 @@ mod tests {
             segment_index: 0,
             frame_index: 0,
             plaintext: Bytes::from(digest_plaintext),
+            segment_flags: SegmentFlags::empty(),
         }
     }
     #[test]
@@ -64,6 +65,7 @@ mod tests {
             segment_index: 0,
             frame_index: 0,
             plaintext: Bytes::from(vec![0x01, 0x02]), // only 2 bytes
+            segment_flags: SegmentFlags::empty(),
         };
         let err = frame.validate().unwrap_err();
         assert!(matches!(err, FrameWorkerError::InvalidInput(msg) if msg.contains("too short")));
@@ -81,6 +83,7 @@ mod tests {
             segment_index: 0,
             frame_index: 0,
             plaintext: Bytes::from(buf),
+            segment_flags: SegmentFlags::empty(),
         };
         let err = DigestFrame::decode(&frame.plaintext).unwrap_err();
         assert!(matches!(
@@ -101,6 +104,7 @@ mod tests {
             segment_index: 0,
             frame_index: 0,
             plaintext: Bytes::from(buf),
+            segment_flags: SegmentFlags::empty(),
         };
         let err = DigestFrame::decode(&frame.plaintext).unwrap_err();
          assert!(matches!(
@@ -0,0 +1,198 @@
+// # 📂 `tests/test_digest_keyed.rs`
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use crossbeam::channel::{Receiver, Sender, bounded, unbounded};
+    use crypto_core::{
+        crypto::{DigestAlg, DigestError, DigestState, SegmentDigestBuilder, SegmentDigestVerifier},
+        headers::HeaderV1,
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{ApiConfig, DecryptParams, EncryptParams, decrypt_stream_v2, encrypt_stream_v2},
+            frame_worker::{
+                DecryptFrameInput, DecryptedFrame, EncryptedFrame, FrameInput, FrameWorkerError,
+                decrypt::DecryptFrameWorker, encrypt::EncryptFrameWorker,
+            },
+            manifest::export_manifest,
+            segment_worker::{
+                DecryptSegmentInput, EncryptSegmentInput, SegmentWorkerError,
+                decrypt::{process_decrypt_segment_v2, process_decrypt_segment_with_key},
+                encrypt::process_encrypt_segment_with_key,
+            },
+            segmenting::{SegmentHeader, decode_segment_header, types::SegmentFlags},
+            verify_stream_v2,
+        },
+        telemetry::StageTimes,
+    };
+
+    const SESSION_KEY: [u8; 32] = [0x5Au8; 32];
+    const OTHER_SESSION_KEY: [u8; 32] = [0xA5u8; 32];
+    const FRAME: usize = 64;
+    const CHUNK: usize = 16 * 1024;
+
+    fn frames() -> Vec<(u32, Vec<u8>)> {
+        vec![(0, b"hello".to_vec()), (1, b"world".to_vec()), (2, vec![0xEE; 300])]
+    }
+
+    fn keyed_digest(key: &[u8; 32]) -> Vec<u8> {
+        let mut builder = SegmentDigestBuilder::new_keyed(key, 7, frames().len() as u32);
+        for (frame_index, ciphertext) in frames() {
+            builder.update_frame(frame_index, &ciphertext).unwrap();
+        }
+        builder.finalize()
+    }
+
+    fn verify_keyed(key: &[u8; 32], expected: Vec<u8>) -> Result<(), DigestError> {
+        let mut verifier = SegmentDigestVerifier::new_keyed(key, 7, frames().len() as u32, expected);
+        for (frame_index, ciphertext) in frames() {
+            verifier.update_frame(frame_index, &ciphertext).unwrap();
+        }
+        verifier.finalize()
+    }
+
+    #[test]
+    fn keyed_roundtrip() {
+        let digest = keyed_digest(&SESSION_KEY);
+        assert_eq!(digest.len(), 32);
+        assert_eq!(keyed_digest(&SESSION_KEY), digest);
+        assert!(verify_keyed(&SESSION_KEY, digest).is_ok());
+    }
+
+    #[test]
+    fn keyed_digest_differs_from_unkeyed() {
+        let mut unkeyed = SegmentDigestBuilder::new(DigestAlg::Blake3, 7, frames().len() as u32);
+        for (frame_index, ciphertext) in frames() {
+            unkeyed.update_frame(frame_index, &ciphertext).unwrap();
+        }
+        assert_ne!(unkeyed.finalize(), keyed_digest(&SESSION_KEY));
+        // Keyed by a derived key, never by the session key itself
+        let mut raw = blake3::Hasher::new_keyed(&SESSION_KEY);
+        raw.update(&7u32.to_le_bytes());
+        raw.update(&(frames().len() as u32).to_le_bytes());
+        for (frame_index, ciphertext) in frames() {
+            raw.update(&frame_index.to_le_bytes());
+            raw.update(&(ciphertext.len() as u32).to_le_bytes());
+            raw.update(&ciphertext);
+        }
+        assert_ne!(raw.finalize().as_bytes().to_vec(), keyed_digest(&SESSION_KEY));
+    }
+
+    #[test]
+    fn other_session_key_fails_verification() {
+        let digest = keyed_digest(&SESSION_KEY);
+        assert!(matches!(verify_keyed(&OTHER_SESSION_KEY, digest), Err(DigestError::DigestMismatch)));
+    }
+
+    #[test]
+    fn keyed_alg_needs_the_session_key() {
+        assert!(DigestAlg::Blake3Keyed.is_keyed());
+        assert!(!DigestAlg::Blake3Keyed.can_resume());
+        assert_eq!(DigestAlg::try_from(0x0202u16).unwrap(), DigestAlg::Blake3Keyed);
+
+        assert!(matches!(
+            SegmentDigestBuilder::for_alg(DigestAlg::Blake3Keyed, None, 0, 1),
+            Err(DigestError::KeyRequired { alg: DigestAlg::Blake3Keyed })
+        ));
+        let builder = SegmentDigestBuilder::for_alg(DigestAlg::Blake3Keyed, Some(&SESSION_KEY), 0, 1).unwrap();
+        assert_eq!(builder.alg, DigestAlg::Blake3Keyed);
+        assert!(matches!(builder.state().serialize(), Err(DigestError::NotResumable { alg: DigestAlg::Blake3Keyed })));
+        assert!(matches!(
+            DigestState::deserialize(DigestAlg::Blake3Keyed, &[]),
+            Err(DigestError::NotResumable { .. })
+        ));
+    }
+
+    // --- Segment workers: same ciphertext, different session key ---
+
+    fn enc_pool() -> (Sender<FrameInput>, Receiver<Result<EncryptedFrame, FrameWorkerError>>) {
+        let (frame_tx, frame_rx) = bounded::<FrameInput>(8);
+        let (out_tx, out_rx) = unbounded();
+        EncryptFrameWorker::new(HeaderV1::test_header(), &SESSION_KEY).unwrap().run(frame_rx, out_tx);
+        (frame_tx, out_rx)
+    }
+
+    fn dec_pool() -> (Sender<DecryptFrameInput>, Receiver<Result<DecryptedFrame, FrameWorkerError>>) {
+        let (frame_tx, frame_rx) = bounded::<DecryptFrameInput>(8);
+        let (out_tx, out_rx) = unbounded();
+        DecryptFrameWorker::new(HeaderV1::test_header(), &SESSION_KEY).unwrap().run(frame_rx, out_tx);
+        (frame_tx, out_rx)
+    }
+
+    fn encrypted_segment() -> DecryptSegmentInput {
+        let (frame_tx, out_rx) = enc_pool();
+        let input = EncryptSegmentInput {
+            segment_index: 3,
+            bytes: Bytes::from(vec![0x33u8; 5 * FRAME + 7]),
+            flags: SegmentFlags::empty(),
+            stage_times: StageTimes::default(),
+        };
+        let segment =
+            process_encrypt_segment_with_key(&input, FRAME, DigestAlg::Blake3Keyed, Some(&SESSION_KEY), &frame_tx, &out_rx)
+                .unwrap();
+        assert_eq!(segment.header.digest_alg, DigestAlg::Blake3Keyed as u16);
+        DecryptSegmentInput { header: segment.header, wire: segment.wire }
+    }
+
+    #[test]
+    fn segment_roundtrip_with_keyed_digest() {
+        let input = encrypted_segment();
+        let (frame_tx, out_rx) = dec_pool();
+        // The header selects keyed verification, whatever the context's algorithm
+        let segment =
+            process_decrypt_segment_with_key(&input, Some(&SESSION_KEY), &frame_tx, &out_rx).unwrap();
+        assert_eq!(segment.frames.concat(), vec![0x33u8; 5 * FRAME + 7]);
+    }
+
+    #[test]
+    fn segment_digest_is_bound_to_the_session_key() {
+        let input = encrypted_segment();
+        let (frame_tx, out_rx) = dec_pool();
+        // Frames still authenticate; only the digest key differs
+        let err = process_decrypt_segment_with_key(&input, Some(&OTHER_SESSION_KEY), &frame_tx, &out_rx)
+            .unwrap_err();
+        assert!(matches!(err, SegmentWorkerError::DigestError(DigestError::DigestMismatch)), "{err:?}");
+
+        let (frame_tx, out_rx) = dec_pool();
+        let err = process_decrypt_segment_v2(&input, &frame_tx, &out_rx).unwrap_err();
+        assert!(matches!(err, SegmentWorkerError::DigestError(DigestError::KeyRequired { .. })), "{err:?}");
+    }
+
+    // --- Whole stream ---
+
+    #[test]
+    fn stream_roundtrip_with_keyed_digests() {
+        let master_key = [0x11u8; 32];
+        let plaintext: Vec<u8> = (0..3 * CHUNK + 100).map(|i| (i % 251) as u8).collect();
+        let header = HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() };
+        let stream = encrypt_stream_v2(
+            InputSource::Memory(plaintext.clone()),
+            OutputSink::Memory,
+            &master_key,
+            EncryptParams::new(header).with_digest_alg(DigestAlg::Blake3Keyed),
+            ApiConfig::new(Some(true), None),
+        )
+        .unwrap()
+        .output
+        .unwrap();
+
+        let first = decode_segment_header(&stream[HeaderV1::LEN..HeaderV1::LEN + SegmentHeader::LEN]).unwrap();
+        assert_eq!(first.digest_alg, DigestAlg::Blake3Keyed as u16);
+
+        let decrypted = decrypt_stream_v2(
+            InputSource::Memory(stream.clone()),
+            OutputSink::Memory,
+            &master_key,
+            DecryptParams::new(),
+            ApiConfig::new(Some(true), None),
+        )
+        .unwrap();
+        assert_eq!(decrypted.output.unwrap(), plaintext);
+
+        let report = verify_stream_v2(InputSource::Memory(stream.clone()), &master_key).unwrap();
+        assert!(report.is_ok(), "{report:?}");
+
+        // Without the key, keyed digests cannot be recomputed
+        assert!(export_manifest(InputSource::Memory(stream)).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+// # 📂 `tests/test_digest_manifest.rs`
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use crypto_core::{
+        headers::HeaderV1,
+        stream_v2::{
+            DigestManifest, InputSource, OutputSink, SegmentDigestStatus,
+            core::{ApiConfig, EncryptParams, encrypt_stream_v2},
+            export_manifest,
+            framing::FrameHeader,
+            segmenting::{SegmentHeader, decode_segment_header},
+            verify_against_manifest,
+        },
+    };
+
+    const CHUNK: usize = 16 * 1024;
+
+    fn scratch_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("crypto_core_{name}_{}.enc", std::process::id()))
+    }
+
+    /// Five data segments and the final one, written to `path`.
+    fn encrypt_to(path: &Path) {
+        let data: Vec<u8> = (0..5 * CHUNK - 10).map(|i| (i * 7 % 256) as u8).collect();
+        encrypt_stream_v2(
+            InputSource::Memory(data),
+            OutputSink::File(path.to_path_buf()),
+            &[0x11u8; 32],
+            EncryptParams::new(HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() }),
+            ApiConfig::new(Some(false), None),
+        )
+        .unwrap();
+    }
+
+    fn segment_offset(ciphertext: &[u8], index: u32) -> usize {
+        let mut offset = HeaderV1::LEN;
+        for _ in 0..index {
+            let header = decode_segment_header(&ciphertext[offset..offset + SegmentHeader::LEN]).unwrap();
+            offset += SegmentHeader::LEN + header.wire_len as usize;
+        }
+        offset
+    }
+
+    #[test]
+    fn export_covers_every_segment() {
+        let path = scratch_file("manifest_export");
+        encrypt_to(&path);
+
+        let manifest = export_manifest(InputSource::File(path.clone())).unwrap();
+        assert_eq!(manifest.segments.len(), 6);
+        for (i, entry) in manifest.segments.iter().enumerate() {
+            assert_eq!(entry.segment_index, i as u32);
+        }
+        // Data segments carry a digest, the empty final segment does not
+        assert!(manifest.segments[..5].iter().all(|entry| !entry.digest.is_empty() && entry.frame_count >= 1));
+        assert!(manifest.segments[5].digest.is_empty());
+
+        let report = verify_against_manifest(InputSource::File(path.clone()), &manifest).unwrap();
+        assert!(report.is_ok(), "{:?}", report);
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn corrupted_segment_is_the_only_mismatch() {
+        let path = scratch_file("manifest_corrupt");
+        encrypt_to(&path);
+        let manifest = export_manifest(InputSource::File(path.clone())).unwrap();
+
+        // Flip one ciphertext byte of segment 2 on disk
+        let mut ciphertext = fs::read(&path).unwrap();
+        let offset = segment_offset(&ciphertext, 2) + SegmentHeader::LEN + FrameHeader::LEN + 17;
+        ciphertext[offset] ^= 0x40;
+        fs::write(&path, &ciphertext).unwrap();
+
+        let report = verify_against_manifest(InputSource::File(path.clone()), &manifest).unwrap();
+        assert_eq!(report.failed_segments(), vec![2]);
+        // The stale wire CRC gives the change away before any digest is computed
+        assert!(matches!(report.segments[2], (2, SegmentDigestStatus::Malformed(_))));
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn truncated_stream_reports_missing_segments() {
+        let path = scratch_file("manifest_truncated");
+        encrypt_to(&path);
+        let manifest = export_manifest(InputSource::File(path.clone())).unwrap();
+
+        let ciphertext = fs::read(&path).unwrap();
+        let cut = segment_offset(&ciphertext, 4);
+        let report = verify_against_manifest(InputSource::Memory(ciphertext[..cut].to_vec()), &manifest).unwrap();
+        assert_eq!(report.failed_segments(), vec![4, 5]);
+        assert_eq!(report.segments[4].1, SegmentDigestStatus::Missing);
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn manifest_roundtrips_through_json_and_binary() {
+        let path = scratch_file("manifest_serde");
+        encrypt_to(&path);
+        let manifest = export_manifest(InputSource::File(path.clone())).unwrap();
+
+        let json = manifest.to_json().unwrap();
+        assert!(json.contains(&hex_of(&manifest.segments[0].digest)), "digests are hex in JSON");
+        assert_eq!(DigestManifest::from_json(&json).unwrap(), manifest);
+
+        let bytes = manifest.to_bytes().unwrap();
+        assert!(bytes.len() < json.len());
+        assert_eq!(DigestManifest::from_bytes(&bytes).unwrap(), manifest);
+        assert!(DigestManifest::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn digest_mismatch_without_crc_change_is_detected() {
+        let path = scratch_file("manifest_forged");
+        encrypt_to(&path);
+        let mut manifest = export_manifest(InputSource::File(path.clone())).unwrap();
+        manifest.segments[1].digest[0] ^= 0xFF;
+
+        let report = verify_against_manifest(InputSource::File(path.clone()), &manifest).unwrap();
+        assert_eq!(report.failed_segments(), vec![1]);
+        assert_eq!(report.segments[1].1, SegmentDigestStatus::Mismatch);
+        let _ = fs::remove_file(path);
+    }
+
+    fn hex_of(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{b:02x}")).collect()
+    }
+}
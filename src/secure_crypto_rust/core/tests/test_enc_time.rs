@@ -0,0 +1,103 @@
+// # 📂 `tests/test_enc_time.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use crypto_core::{
+        headers::{HeaderV1, decode_header_le},
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{DecryptParams, EncryptParams, decrypt_stream_v2},
+        },
+        telemetry::TelemetrySnapshot,
+    };
+
+    use crate::common::{self, master_key};
+
+    const CHUNK: usize = 16 * 1024;
+
+    fn header() -> HeaderV1 {
+        HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() }
+    }
+
+    fn encrypt(params: EncryptParams) -> Vec<u8> {
+        common::encrypt(&[0x5Au8; 2 * CHUNK], params)
+    }
+
+    fn decrypt(ciphertext: Vec<u8>) -> TelemetrySnapshot {
+        decrypt_stream_v2(
+            InputSource::Memory(ciphertext),
+            OutputSink::Memory,
+            &master_key(),
+            DecryptParams::new(),
+            common::config(),
+        )
+        .unwrap()
+    }
+
+    fn unix_ns(t: SystemTime) -> u64 {
+        t.duration_since(UNIX_EPOCH).unwrap().as_nanos() as u64
+    }
+
+    #[test]
+    fn encryption_time_is_recorded_by_default() {
+        let before = unix_ns(SystemTime::now());
+        let ciphertext = encrypt(EncryptParams::new(header()));
+        let after = unix_ns(SystemTime::now());
+
+        let written = decode_header_le(&ciphertext).unwrap();
+        assert!((before..=after).contains(&written.enc_time_ns), "{}", written.enc_time_ns);
+        assert!(!written.created_in_future());
+        assert!(written.created_at_rfc3339().unwrap().ends_with('Z'));
+
+        let snapshot = decrypt(ciphertext);
+        assert_eq!(snapshot.stream_created_at, written.created_at());
+
+        let restored = TelemetrySnapshot::from_json(&snapshot.to_json()).unwrap();
+        assert_eq!(restored.stream_created_at, written.created_at());
+    }
+
+    #[test]
+    fn zero_means_unset() {
+        let ciphertext = encrypt(EncryptParams::new(header()).without_timestamp());
+        let written = decode_header_le(&ciphertext).unwrap();
+
+        assert_eq!(written.enc_time_ns, 0);
+        assert_eq!(written.created_at(), None);
+        // Never rendered as 1970-01-01
+        assert_eq!(written.created_at_rfc3339(), None);
+        assert!(!written.created_in_future());
+        assert_eq!(decrypt(ciphertext).stream_created_at, None);
+    }
+
+    #[test]
+    fn deterministic_salt_keeps_the_given_time() {
+        let fixed = HeaderV1 { enc_time_ns: 1_700_000_000_123_456_789, ..header() };
+        let ciphertext = encrypt(EncryptParams::new(fixed).with_deterministic_salt());
+        let written = decode_header_le(&ciphertext).unwrap();
+
+        assert_eq!(written.enc_time_ns, fixed.enc_time_ns);
+        assert_eq!(written.created_at_rfc3339().unwrap(), "2023-11-14T22:13:20.123456789Z");
+    }
+
+    #[test]
+    fn absurd_times_render_and_are_flagged() {
+        let far = HeaderV1 { enc_time_ns: u64::MAX, ..header() };
+        assert!(far.created_at_rfc3339().unwrap().starts_with("2554-07-21T"));
+        assert!(far.created_in_future());
+
+        let tomorrow = unix_ns(SystemTime::now() + Duration::from_secs(86_400));
+        assert!(HeaderV1 { enc_time_ns: tomorrow, ..header() }.created_in_future());
+
+        // One nanosecond after the epoch is set, not "unset"
+        let epoch = HeaderV1 { enc_time_ns: 1, ..header() };
+        assert_eq!(epoch.created_at_rfc3339().unwrap(), "1970-01-01T00:00:00.000000001Z");
+        assert!(!epoch.created_in_future());
+
+        let ciphertext = encrypt(EncryptParams::new(far).without_timestamp());
+        assert_eq!(decrypt(ciphertext).stream_created_at, far.created_at());
+    }
+}
@@ -0,0 +1,143 @@
+// # 📂 `tests/test_error_codes.rs`
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+    use std::io;
+
+    use crypto_core::{
+        compression::CompressionError,
+        crypto::{AadError, CryptoError, DigestError, NonceError},
+        headers::HeaderError,
+        stream_v2::{
+            compression_worker::CompressionWorkerError,
+            frame_worker::FrameWorkerError,
+            framing::FrameError,
+            segment_worker::SegmentWorkerError,
+            segmenting::types::SegmentError,
+        },
+        types::{ErrorCode, StreamError},
+    };
+
+    /// One instance of every `StreamError` variant (and the interesting inner ones).
+    fn representatives() -> Vec<(StreamError, &'static str)> {
+        vec![
+            (StreamError::Io(io::Error::new(io::ErrorKind::NotFound, "gone")), "ERR_IO"),
+            (StreamError::Aad(AadError::UnknownDomain { raw: 9 }), "ERR_AAD"),
+            (StreamError::Header(HeaderError::InvalidMagic { have: *b"XXXX", need: *b"RSE1" }), "ERR_HEADER_MAGIC"),
+            (StreamError::Header(HeaderError::BufferTooShort { have: 3, need: 80 }), "ERR_HEADER_TRUNCATED"),
+            (StreamError::Header(HeaderError::InvalidSalt { salt: [0u8; 16] }), "ERR_HEADER_SALT"),
+            (StreamError::Header(HeaderError::UnknownCipherSuite { raw: 0xFF }), "ERR_HEADER_ALGORITHM"),
+            (StreamError::Crypto(CryptoError::InvalidKeyLen { expected: 32, actual: 7 }), "ERR_KEY_LEN"),
+            (StreamError::Crypto(CryptoError::TagMismatch), "ERR_AUTH_TAG"),
+            (StreamError::Crypto(CryptoError::KeyConfirmationFailed), "ERR_WRONG_KEY"),
+            (StreamError::Nonce(NonceError::InvalidSalt), "ERR_NONCE"),
+            (StreamError::Segment(SegmentError::WireLenTooLarge { have: u32::MAX, max: 1 }), "ERR_SEGMENT_TOO_LARGE"),
+            (StreamError::Segment(SegmentError::Truncated), "ERR_SEGMENT_MALFORMED"),
+            (StreamError::Frame(FrameError::Truncated), "ERR_FRAME_MALFORMED"),
+            (StreamError::SegmentWorker(SegmentWorkerError::DigestError(DigestError::DigestMismatch)), "ERR_DIGEST_MISMATCH"),
+            (StreamError::SegmentWorker(SegmentWorkerError::MissingTerminatorFrame), "ERR_FRAME_MISSING"),
+            (
+                StreamError::SegmentWorker(SegmentWorkerError::FrameWorkerError(
+                    FrameWorkerError::Crypto(CryptoError::TagMismatch).at(3, 1),
+                )),
+                "ERR_AUTH_TAG",
+            ),
+            (
+                StreamError::SegmentWorker(SegmentWorkerError::FrameWorkerError(
+                    FrameWorkerError::AuthenticationFailed { segment_index: 3, frame_index: 1 }.at(3, 1),
+                )),
+                "ERR_AUTH_TAG",
+            ),
+            (
+                StreamError::SegmentWorker(SegmentWorkerError::FrameWorkerError(FrameWorkerError::MalformedFrameHeader(
+                    FrameError::InvalidMagic(*b"XXXX"),
+                ))),
+                "ERR_FRAME_MALFORMED",
+            ),
+            (
+                StreamError::SegmentWorker(SegmentWorkerError::FrameWorkerError(
+                    FrameWorkerError::Truncated { needed: 64, have: 10 }.at(0, 2),
+                )),
+                "ERR_TRUNCATED",
+            ),
+            (
+                StreamError::CompressionWorker(CompressionWorkerError::Compression(CompressionError::ChunkTooLarge {
+                    have: 2,
+                    max: 1,
+                })),
+                "ERR_COMPRESSION_LIMIT",
+            ),
+            (StreamError::CompressionWorker(CompressionWorkerError::DictionaryRequired { dict_id: 4 }), "ERR_DICTIONARY"),
+            (StreamError::Compression(CompressionError::UnsupportedCodec { codec_id: 0xFF }), "ERR_CODEC_UNSUPPORTED"),
+            (StreamError::UnknownKeyId { key_id: 5 }, "ERR_UNKNOWN_KEY_ID"),
+            (StreamError::ChecksumMismatch { expected: 1, actual: 2 }, "ERR_CHECKSUM_MISMATCH"),
+            (StreamError::TruncatedStream { expected: 10, got: 5 }, "ERR_TRUNCATED"),
+            (StreamError::ReorderBufferOverflow { waiting_for: 0, pending: 9, limit: 8 }, "ERR_REORDER_OVERFLOW"),
+            (StreamError::PipelineError("worker panicked"), "ERR_PIPELINE"),
+            (StreamError::Cancelled, "ERR_CANCELLED"),
+            (StreamError::Validation("bad params".into()), "ERR_VALIDATION"),
+        ]
+    }
+
+    #[test]
+    fn every_variant_has_a_stable_code() {
+        for (error, name) in representatives() {
+            assert_eq!(error.code().as_str(), name, "{error}");
+            assert_eq!(error.code().to_string(), name);
+        }
+    }
+
+    #[test]
+    fn source_chains_terminate() {
+        for (error, _) in representatives() {
+            let mut depth = 0;
+            let mut current: Option<&dyn Error> = Some(&error);
+            while let Some(e) = current {
+                depth += 1;
+                assert!(depth <= 8, "source chain of {error} does not terminate");
+                current = e.source();
+            }
+        }
+    }
+
+    #[test]
+    fn wrapping_variants_expose_their_cause() {
+        let error = StreamError::SegmentWorker(SegmentWorkerError::FrameWorkerError(
+            FrameWorkerError::Crypto(CryptoError::TagMismatch).at(3, 1),
+        ));
+        let chain: Vec<String> = std::iter::successors(error.source(), |e| (*e).source()).map(|e| e.to_string()).collect();
+        assert_eq!(chain.last().unwrap(), &CryptoError::TagMismatch.to_string());
+        assert_eq!(chain.len(), 4); // segment worker, frame worker (tagged), frame worker, crypto
+
+        assert!(StreamError::Validation("x".into()).source().is_none());
+        assert!(StreamError::PipelineError("x").source().is_none());
+    }
+
+    #[test]
+    fn frame_failures_keep_their_kind_and_indices() {
+        let error = StreamError::SegmentWorker(SegmentWorkerError::FrameWorkerError(
+            FrameWorkerError::AuthenticationFailed { segment_index: 4, frame_index: 2 }.at(4, 2),
+        ));
+        assert!(matches!(
+            error.frame_failure(),
+            Some(FrameWorkerError::AuthenticationFailed { segment_index: 4, frame_index: 2 })
+        ));
+        assert!(error.to_string().contains("authentication failed for segment 4 frame 2"), "{error}");
+
+        let error = StreamError::SegmentWorker(SegmentWorkerError::FrameWorkerError(FrameWorkerError::Truncated {
+            needed: 64,
+            have: 10,
+        }));
+        assert!(matches!(error.frame_failure(), Some(FrameWorkerError::Truncated { needed: 64, have: 10 })));
+        assert!(StreamError::Frame(FrameError::Truncated).frame_failure().is_none());
+    }
+
+    #[test]
+    fn display_never_prints_salt_bytes() {
+        let error = StreamError::Header(HeaderError::InvalidSalt { salt: [0u8; 16] });
+        let text = error.to_string();
+        assert!(!text.contains("0x0000"), "{text}");
+        assert_eq!(error.code(), ErrorCode::HeaderSalt);
+    }
+}
@@ -0,0 +1,129 @@
+// # 📂 `tests/test_final_segment.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+
+    use bytes::Bytes;
+    use crypto_core::{
+        headers::HeaderV1,
+        stream_v2::{
+            DecryptingReader, EncryptingWriter,
+            core::{DecryptParams, EncryptParams},
+            segmenting::{SegmentHeader, decode_segment_header, encode_segment, types::SegmentFlags},
+        },
+    };
+
+    use crate::common::{self, master_key};
+
+    const CHUNK: usize = 16 * 1024;
+
+    fn params() -> EncryptParams<'static> {
+        EncryptParams::new(HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() })
+    }
+
+    fn sizes() -> [usize; 4] {
+        [0, 1, CHUNK, CHUNK + 1]
+    }
+
+    fn plaintext(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 239) as u8).collect()
+    }
+
+    /// (segment_index, flags) of every segment in the stream.
+    fn segments(ciphertext: &[u8]) -> Vec<(u32, SegmentFlags)> {
+        let mut out = Vec::new();
+        let mut offset = HeaderV1::LEN;
+        while offset < ciphertext.len() {
+            let header = decode_segment_header(&ciphertext[offset..offset + SegmentHeader::LEN]).unwrap();
+            offset += SegmentHeader::LEN + header.wire_len as usize;
+            out.push((header.segment_index, header.flags));
+        }
+        assert_eq!(offset, ciphertext.len());
+        out
+    }
+
+    fn assert_single_trailing_final(ciphertext: &[u8], data_segments: usize) {
+        let segs = segments(ciphertext);
+        assert_eq!(segs.len(), data_segments + 1);
+        let (last_index, last_flags) = *segs.last().unwrap();
+        assert_eq!(last_index as usize, data_segments);
+        assert!(last_flags.contains(SegmentFlags::FINAL_SEGMENT));
+        assert_eq!(segs.iter().filter(|(_, f)| f.contains(SegmentFlags::FINAL_SEGMENT)).count(), 1);
+    }
+
+    #[test]
+    fn pipeline_roundtrips_boundary_sizes() {
+        for len in sizes() {
+            let data = plaintext(len);
+            let ciphertext = common::try_encrypt(&data, params(), common::config())
+                .unwrap_or_else(|e| panic!("{len} bytes: encryption failed: {e}"));
+
+            assert_single_trailing_final(&ciphertext, len.div_ceil(CHUNK));
+
+            let out = common::decrypt(ciphertext, DecryptParams::new())
+                .unwrap_or_else(|e| panic!("{len} bytes: decryption failed: {e}"));
+            assert_eq!(out, data, "{len} bytes roundtrip mismatch");
+        }
+    }
+
+    #[test]
+    fn adapters_roundtrip_boundary_sizes() {
+        for len in sizes() {
+            let data = plaintext(len);
+            let mut writer = EncryptingWriter::new(Vec::new(), &master_key(), params()).unwrap();
+            writer.write_all(&data).unwrap();
+            let ciphertext = writer.finish().unwrap_or_else(|e| panic!("{len} bytes: finish failed: {e}"));
+
+            assert_single_trailing_final(&ciphertext, len.div_ceil(CHUNK));
+
+            let mut reader = DecryptingReader::new(ciphertext.as_slice(), &master_key(), DecryptParams::new()).unwrap();
+            let mut out = Vec::new();
+            reader.read_to_end(&mut out).unwrap();
+            assert_eq!(out, data, "{len} bytes roundtrip mismatch");
+        }
+    }
+
+    #[test]
+    fn pipeline_and_adapter_agree_on_empty_stream() {
+        let from_pipeline = common::encrypt(&[], params());
+        let from_writer = EncryptingWriter::new(Vec::new(), &master_key(), params()).unwrap().finish().unwrap();
+
+        assert_eq!(from_pipeline.len(), HeaderV1::LEN + SegmentHeader::LEN);
+        assert_eq!(from_writer.len(), from_pipeline.len());
+    }
+
+    /// `ciphertext` cut after its first `keep` segments, closed by a final
+    /// marker forged from the real one.
+    fn cut_and_close(ciphertext: &[u8], keep: usize) -> Vec<u8> {
+        let mut offset = HeaderV1::LEN;
+        for _ in 0..keep {
+            let header = decode_segment_header(&ciphertext[offset..offset + SegmentHeader::LEN]).unwrap();
+            offset += SegmentHeader::LEN + header.wire_len as usize;
+        }
+        let last = ciphertext.len() - SegmentHeader::LEN;
+        let mut marker = decode_segment_header(&ciphertext[last..]).unwrap();
+        assert!(marker.flags.contains(SegmentFlags::FINAL_SEGMENT));
+        marker.segment_index = keep as u32;
+
+        let mut out = ciphertext[..offset].to_vec();
+        out.extend_from_slice(&encode_segment(&marker, &Bytes::new()).unwrap());
+        out
+    }
+
+    #[test]
+    fn forged_final_marker_is_only_caught_by_a_trailer() {
+        let data = plaintext(3 * CHUNK);
+
+        // No keyed data covers the empty final segment: the cut stream reads as a prefix
+        let ciphertext = common::encrypt(&data, params());
+        let out = common::decrypt(cut_and_close(&ciphertext, 2), DecryptParams::new()).unwrap();
+        assert_eq!(out, data[..2 * CHUNK]);
+
+        // The authenticated length trailer is missing from the cut stream
+        let ciphertext = common::encrypt(&data, params().with_length_trailer());
+        assert!(common::decrypt(cut_and_close(&ciphertext, 2), DecryptParams::new()).is_err());
+    }
+}
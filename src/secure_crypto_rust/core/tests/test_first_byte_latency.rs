@@ -0,0 +1,136 @@
+// # 📂 `tests/test_first_byte_latency.rs`
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Cursor, Write};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use crypto_core::{
+        compression::CompressionCodec,
+        crypto::{DigestAlg, KEY_LEN_32},
+        headers::HeaderV1,
+        recovery::AsyncLogManager,
+        stream_v2::{
+            io::PayloadReader,
+            parallelism::HybridParallelismProfile,
+            pipeline::{PipelineConfig, run_decrypt_pipeline, run_encrypt_pipeline},
+            segment_worker::{DecryptContext, EncryptContext, SegmentHook},
+            segmenting::SegmentHeader,
+        },
+        telemetry::{Stage, TelemetrySnapshot},
+    };
+
+    const CHUNK: usize = 16 * 1024;
+    const SEGMENTS: usize = 8;
+    /// How long every segment after the first keeps its worker busy.
+    const LATER_SEGMENT_DELAY: Duration = Duration::from_millis(300);
+
+    fn profile() -> HybridParallelismProfile {
+        HybridParallelismProfile::new(4, 0, 8)
+    }
+
+    fn plaintext() -> Vec<u8> {
+        b"the first segment should not wait for the others. ".iter().cycle().take(SEGMENTS * CHUNK).copied().collect()
+    }
+
+    fn encrypt(data: &[u8]) -> Vec<u8> {
+        let header = HeaderV1 {
+            compression: CompressionCodec::Zstd as u16,
+            chunk_size: CHUNK as u32,
+            ..HeaderV1::test_header()
+        };
+        let mut crypto = EncryptContext::new(header, profile(), &[0x42u8; KEY_LEN_32], DigestAlg::Blake3).unwrap();
+        let mut out = Cursor::new(Vec::new());
+        run_encrypt_pipeline(
+            &mut PayloadReader::new(Cursor::new(data.to_vec())),
+            &mut out,
+            &mut crypto,
+            &PipelineConfig::new(profile(), None),
+            Arc::new(AsyncLogManager::disabled()),
+        )
+        .unwrap();
+        out.into_inner()
+    }
+
+    /// Records when the first plaintext byte arrives.
+    struct FirstByteWriter {
+        started: Instant,
+        first_byte: Arc<Mutex<Option<Duration>>>,
+        out: Arc<Mutex<Vec<u8>>>,
+    }
+
+    impl Write for FirstByteWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if !buf.is_empty() {
+                self.first_byte.lock().unwrap().get_or_insert(self.started.elapsed());
+            }
+            self.out.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    struct Run {
+        snapshot: TelemetrySnapshot,
+        plaintext: Vec<u8>,
+        first_byte: Duration,
+        total: Duration,
+    }
+
+    fn decrypt(stream: Vec<u8>, in_order: bool) -> Run {
+        let slow_later_segments: SegmentHook = Arc::new(|header: &SegmentHeader| {
+            if header.segment_index > 0 {
+                thread::sleep(LATER_SEGMENT_DELAY);
+            }
+        });
+        let (header, mut reader) = PayloadReader::with_header(Cursor::new(stream)).unwrap();
+        let mut crypto = DecryptContext::from_stream_header(header, profile(), &[0x42u8; KEY_LEN_32], DigestAlg::Blake3).unwrap();
+        let config = PipelineConfig::new(profile(), None)
+            .with_decompress_in_order(in_order)
+            .with_segment_hook(Some(slow_later_segments));
+
+        let first_byte = Arc::new(Mutex::new(None));
+        let out = Arc::new(Mutex::new(Vec::new()));
+        let started = Instant::now();
+        let writer = FirstByteWriter { started, first_byte: Arc::clone(&first_byte), out: Arc::clone(&out) };
+        let snapshot = run_decrypt_pipeline(&mut reader, writer, &mut crypto, &config, Arc::new(AsyncLogManager::disabled())).unwrap();
+        let total = started.elapsed();
+
+        let first_byte = first_byte.lock().unwrap().expect("plaintext was written");
+        let plaintext = std::mem::take(&mut *out.lock().unwrap());
+        Run { snapshot, plaintext, first_byte, total }
+    }
+
+    #[test]
+    fn first_segment_is_written_while_later_ones_are_in_flight() {
+        let stream = encrypt(&plaintext());
+        for in_order in [false, true] {
+            let run = decrypt(stream.clone(), in_order);
+            assert_eq!(run.plaintext, plaintext(), "in_order {in_order}");
+            assert!(run.total >= LATER_SEGMENT_DELAY, "in_order {in_order}: run took {:?}", run.total);
+            assert!(
+                run.first_byte < LATER_SEGMENT_DELAY,
+                "in_order {in_order}: first byte after {:?}, behind segments still being decrypted",
+                run.first_byte
+            );
+        }
+    }
+
+    #[test]
+    fn segment_telemetry_is_still_merged() {
+        let stream = encrypt(&plaintext());
+        for in_order in [false, true] {
+            let snapshot = decrypt(stream.clone(), in_order).snapshot;
+            assert_eq!(snapshot.bytes_plaintext, (SEGMENTS * CHUNK) as u64, "in_order {in_order}");
+            assert!(snapshot.bytes_ciphertext > 0, "in_order {in_order}");
+            assert!(snapshot.bytes_compressed > 0, "in_order {in_order}");
+            assert!(snapshot.stage_times.get(Stage::Decrypt) > Duration::ZERO, "in_order {in_order}");
+            assert!(snapshot.stage_times.get(Stage::Decompress) > Duration::ZERO, "in_order {in_order}");
+        }
+    }
+}
@@ -0,0 +1,206 @@
+// # 📂 `tests/test_frame_layout.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use crypto_core::{
+        compression::CompressionCodec,
+        constants::flags,
+        crypto::DigestAlg,
+        headers::{FrameLayout, HeaderV1},
+        stream_v2::{
+            core::{DecryptParams, EncryptParams},
+            estimate_ciphertext_len,
+            frame_worker::{DecryptFramePool, EncryptFramePool},
+            framing::{FrameHeader, FrameType, decode::parse_frame_header},
+            io::PlaintextTrailer,
+            parallelism::HybridParallelismProfile,
+            segment_worker::{
+                DecryptSegmentInput, EncryptContext, EncryptSegmentInput, EncryptedSegment, SegmentWorkerError,
+                decrypt::process_decrypt_segment_with_layout, encrypt::process_encrypt_segment_with_context,
+            },
+            segmenting::types::SegmentFlags,
+        },
+        telemetry::StageTimes,
+        types::{ErrorCode, StreamError},
+    };
+
+    use crate::common;
+
+    const CHUNK: usize = 16 * 1024;
+
+    const LAYOUTS: [FrameLayout; 4] = [
+        FrameLayout { segment_digest: true, terminator: true },
+        FrameLayout { segment_digest: true, terminator: false },
+        FrameLayout { segment_digest: false, terminator: true },
+        FrameLayout { segment_digest: false, terminator: false },
+    ];
+
+    fn session_key() -> Vec<u8> {
+        vec![0x42u8; 32]
+    }
+
+    fn plaintext() -> Vec<u8> {
+        // Incompressible, so the size estimate is exact
+        (0..3 * CHUNK + 1234).map(|i| (i.wrapping_mul(2_654_435_761) >> 7) as u8).collect()
+    }
+
+    fn header(layout: Option<FrameLayout>) -> HeaderV1 {
+        let mut header = HeaderV1 {
+            compression: CompressionCodec::Auto as u16,
+            chunk_size: CHUNK as u32,
+            ..HeaderV1::test_header()
+        };
+        if let Some(layout) = layout {
+            header.set_frame_layout(layout);
+        }
+        header
+    }
+
+    fn encrypt(header: HeaderV1) -> Vec<u8> {
+        common::encrypt(&plaintext(), EncryptParams::new(header))
+    }
+
+    fn decrypt(stream: Vec<u8>) -> Result<Vec<u8>, StreamError> {
+        common::decrypt(stream, DecryptParams::new())
+    }
+
+    /// One full-chunk segment encrypted under `layout`, through a real frame pool.
+    fn encrypt_segment(layout: FrameLayout) -> (HeaderV1, usize, EncryptedSegment) {
+        let header = header(Some(layout));
+        let crypto =
+            EncryptContext::new(header, HybridParallelismProfile::single_threaded(), &session_key(), DigestAlg::Sha256)
+                .unwrap();
+        let pool = EncryptFramePool::encrypt(&header, &session_key(), 1).unwrap();
+        let (frame_tx, out_rx) = pool.handle().connect();
+        let input = EncryptSegmentInput {
+            segment_index: 0,
+            bytes: Bytes::from(plaintext()[..CHUNK].to_vec()),
+            flags: SegmentFlags::empty(),
+            stage_times: StageTimes::default(),
+        };
+        let segment = process_encrypt_segment_with_context(&input, &crypto, &frame_tx, &out_rx).unwrap();
+        drop(frame_tx);
+        pool.shutdown();
+        (header, crypto.base.frame_size, segment)
+    }
+
+    /// Decrypt `segment` (frames sealed under `header`) expecting `layout`.
+    fn decrypt_segment(header: &HeaderV1, segment: &EncryptedSegment, layout: FrameLayout) -> Result<Vec<u8>, SegmentWorkerError> {
+        let pool = DecryptFramePool::decrypt(header, &session_key(), 1).unwrap();
+        let (frame_tx, out_rx) = pool.handle().connect();
+        let input = DecryptSegmentInput { header: segment.header, wire: segment.wire.clone() };
+        let key: [u8; 32] = session_key().try_into().unwrap();
+        let result = process_decrypt_segment_with_layout(&input, Some(&key), layout, &frame_tx, &out_rx)
+            .map(|decrypted| decrypted.frames.concat());
+        drop(frame_tx);
+        pool.shutdown();
+        result
+    }
+
+    fn wire_frame_count(wire: &[u8]) -> u32 {
+        let mut offset = 0;
+        let mut count = 0;
+        while offset < wire.len() {
+            let frame = parse_frame_header(&wire[offset..]).unwrap();
+            offset += FrameHeader::LEN + frame.ciphertext_len as usize;
+            count += 1;
+        }
+        count
+    }
+
+    #[test]
+    fn header_flags_select_the_layout() {
+        assert_eq!(HeaderV1::test_header().frame_layout(), FrameLayout::LEGACY);
+
+        // Without EXPLICIT_FRAMES the policy bits are not read
+        let legacy = HeaderV1 { flags: flags::HAS_FINAL_DIGEST, ..HeaderV1::test_header() };
+        assert_eq!(legacy.frame_layout(), FrameLayout::LEGACY);
+
+        for layout in LAYOUTS {
+            let header = header(Some(layout));
+            assert_ne!(header.flags & flags::EXPLICIT_FRAMES, 0);
+            assert_eq!(header.frame_layout(), layout);
+            assert_eq!(header.flags & flags::HAS_TERMINATOR != 0, layout.terminator);
+            assert_eq!(header.flags & flags::HAS_FINAL_DIGEST != 0, layout.final_digest());
+        }
+    }
+
+    #[test]
+    fn every_layout_round_trips() {
+        let legacy = encrypt(header(None));
+        assert_eq!(decrypt(legacy.clone()).unwrap(), plaintext());
+
+        for layout in LAYOUTS {
+            let header = header(Some(layout));
+            let stream = encrypt(header);
+            assert_eq!(decrypt(stream.clone()).unwrap(), plaintext(), "{layout:?}");
+            assert_eq!(estimate_ciphertext_len(plaintext().len() as u64, &header), stream.len() as u64, "{layout:?}");
+            if layout != FrameLayout::LEGACY {
+                assert_ne!(stream.len(), legacy.len(), "{layout:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn segments_carry_only_the_layouts_closing_frames() {
+        for layout in LAYOUTS {
+            let (header, frame_size, segment) = encrypt_segment(layout);
+            let data_frames = CHUNK.div_ceil(frame_size) as u32;
+            // The segment header counts data frames; the closing frames follow them on the wire
+            assert_eq!(segment.header.frame_count, data_frames, "{layout:?}");
+            assert_eq!(wire_frame_count(&segment.wire), data_frames + layout.closing_frames(), "{layout:?}");
+            assert_eq!(decrypt_segment(&header, &segment, layout).unwrap(), plaintext()[..CHUNK], "{layout:?}");
+        }
+    }
+
+    #[test]
+    fn missing_closing_frames_are_named() {
+        let no_terminator = FrameLayout { segment_digest: true, terminator: false };
+        let (header, _, segment) = encrypt_segment(no_terminator);
+        let err = decrypt_segment(&header, &segment, FrameLayout::LEGACY).unwrap_err();
+        assert!(matches!(err, SegmentWorkerError::MissingTerminatorFrame), "{err:?}");
+
+        let no_digest = FrameLayout { segment_digest: false, terminator: true };
+        let (header, _, segment) = encrypt_segment(no_digest);
+        let err = decrypt_segment(&header, &segment, FrameLayout::LEGACY).unwrap_err();
+        assert!(matches!(err, SegmentWorkerError::MissingDigestFrame), "{err:?}");
+    }
+
+    #[test]
+    fn unexpected_closing_frames_are_named() {
+        let (header, _, segment) = encrypt_segment(FrameLayout::LEGACY);
+
+        let err = decrypt_segment(&header, &segment, FrameLayout { segment_digest: true, terminator: false }).unwrap_err();
+        assert!(
+            matches!(err, SegmentWorkerError::UnexpectedFrame { frame_type: FrameType::Terminator, .. }),
+            "{err:?}"
+        );
+        assert!(err.to_string().contains("Terminator"), "{err}");
+
+        let err = decrypt_segment(&header, &segment, FrameLayout { segment_digest: false, terminator: true }).unwrap_err();
+        assert!(matches!(err, SegmentWorkerError::UnexpectedFrame { frame_type: FrameType::Digest, .. }), "{err:?}");
+        assert_eq!(StreamError::SegmentWorker(err).code(), ErrorCode::SegmentMalformed);
+    }
+
+    #[test]
+    fn whole_stream_digest_is_checked() {
+        let header = header(Some(FrameLayout { segment_digest: false, terminator: true }));
+        let mut trailer = PlaintextTrailer::for_header(&header);
+        assert!(trailer.flags().contains(SegmentFlags::STREAM_DIGEST));
+        trailer.update(b"the plaintext");
+        let payload = trailer.payload();
+        assert_eq!(payload.len(), 32);
+
+        let mut tampered = payload.to_vec();
+        tampered[0] ^= 0x01;
+        let err = trailer.verify(&tampered).unwrap_err();
+        assert!(matches!(err, StreamError::StreamDigestMismatch), "{err:?}");
+        assert_eq!(err.code(), ErrorCode::DigestMismatch);
+
+        trailer.verify(&payload).unwrap();
+        trailer.finish().unwrap();
+    }
+}
@@ -0,0 +1,156 @@
+// # 📂 `tests/test_frame_pool.rs`
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use bytes::Bytes;
+    use crossbeam::channel::unbounded;
+    use crypto_core::{
+        crypto::{DigestAlg, KEY_LEN_32},
+        headers::HeaderV1,
+        recovery::persist::AsyncLogManager,
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{ApiConfig, DecryptParams, EncryptParams, decrypt_stream_v2, encrypt_stream_v2},
+            frame_worker::{DecryptFramePool, EncryptFramePool},
+            parallelism::HybridParallelismProfile,
+            segment_worker::{
+                DecryptContext, DecryptSegmentInput, DecryptSegmentWorker, EncryptContext, EncryptSegmentInput,
+                EncryptSegmentWorker, encrypt::process_encrypt_segment_2,
+            },
+            segmenting::types::SegmentFlags,
+        },
+        telemetry::StageTimes,
+    };
+
+    const FRAME_THREADS: usize = 2;
+    const SEGMENT_WORKERS: usize = 4;
+    const SEGMENTS: u32 = 12;
+
+    fn session_key() -> Vec<u8> {
+        vec![0x42u8; KEY_LEN_32]
+    }
+
+    fn plaintext(segment_index: u32) -> Bytes {
+        (0..40_000u32).map(|i| (i ^ segment_index.wrapping_mul(131)) as u8).collect::<Vec<_>>().into()
+    }
+
+    fn segment(segment_index: u32) -> EncryptSegmentInput {
+        EncryptSegmentInput {
+            segment_index,
+            bytes: plaintext(segment_index),
+            flags: SegmentFlags::empty(),
+            stage_times: StageTimes::default(),
+        }
+    }
+
+    #[test]
+    fn segment_workers_share_one_frame_pool() {
+        let header = HeaderV1::test_header();
+        let profile = HybridParallelismProfile::single_threaded();
+        let enc = EncryptContext::new(header, profile.clone(), &session_key(), DigestAlg::Sha256).unwrap();
+        let dec = DecryptContext::from_stream_header(header, profile, &session_key(), DigestAlg::Sha256).unwrap();
+        let log_manager = Arc::new(AsyncLogManager::disabled());
+
+        let enc_pool = EncryptFramePool::encrypt(&header, &session_key(), FRAME_THREADS).unwrap();
+        let dec_pool = DecryptFramePool::decrypt(&header, &session_key(), FRAME_THREADS).unwrap();
+        assert_eq!(enc_pool.threads(), FRAME_THREADS);
+        assert_eq!(dec_pool.threads(), FRAME_THREADS);
+
+        let (enc_tx, enc_rx) = unbounded();
+        let (mid_tx, mid_rx) = unbounded();
+        let (dec_tx, dec_rx) = unbounded();
+        let (out_tx, out_rx) = unbounded();
+        let mut workers = Vec::new();
+        for _ in 0..SEGMENT_WORKERS {
+            workers.push(
+                EncryptSegmentWorker::new(enc.clone(), log_manager.clone())
+                    .with_frame_pool(enc_pool.handle())
+                    .run_v2(enc_rx.clone(), mid_tx.clone()),
+            );
+            workers.push(
+                DecryptSegmentWorker::new(dec.clone(), log_manager.clone())
+                    .with_frame_pool(dec_pool.handle())
+                    .run_v2(dec_rx.clone(), out_tx.clone()),
+            );
+        }
+        drop((enc_rx, mid_tx, dec_rx, out_tx));
+
+        for i in 0..SEGMENTS {
+            enc_tx.send(segment(i)).unwrap();
+        }
+        drop(enc_tx);
+        for encrypted in mid_rx.iter() {
+            dec_tx.send(DecryptSegmentInput::from(encrypted.unwrap())).unwrap();
+        }
+        drop(dec_tx);
+
+        let mut decrypted: Vec<_> = out_rx.iter().map(|res| res.unwrap()).collect();
+        decrypted.sort_by_key(|seg| seg.header.segment_index);
+        assert_eq!(decrypted.len(), SEGMENTS as usize);
+        for seg in &decrypted {
+            assert_eq!(seg.to_bytes(), plaintext(seg.header.segment_index));
+        }
+
+        // Every segment worker has exited: shutting down must not block
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        enc_pool.shutdown();
+        dec_pool.shutdown();
+    }
+
+    #[test]
+    fn interleaved_segments_get_their_own_frames() {
+        let header = HeaderV1::test_header();
+        let pool = EncryptFramePool::encrypt(&header, &session_key(), FRAME_THREADS).unwrap();
+
+        let handles: Vec<_> = (0..SEGMENT_WORKERS as u32)
+            .map(|worker| {
+                let frames = pool.handle();
+                std::thread::spawn(move || {
+                    let (frame_tx, out_rx) = frames.connect();
+                    for i in (worker..SEGMENTS).step_by(SEGMENT_WORKERS) {
+                        let seg = process_encrypt_segment_2(&segment(i), 4096, DigestAlg::Sha256, &frame_tx, &out_rx).unwrap();
+                        assert_eq!(seg.header.segment_index, i);
+                        assert_eq!(seg.header.bytes_len as usize, plaintext(i).len());
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        pool.shutdown();
+    }
+
+    #[test]
+    fn pipeline_spawns_one_frame_pool_per_run() {
+        let header = HeaderV1 { chunk_size: 16 * 1024, ..HeaderV1::test_header() };
+        let input = vec![9u8; 256 * 1024];
+        let master_key = [0x11u8; 32];
+
+        let snapshot = encrypt_stream_v2(
+            InputSource::Memory(input.clone()),
+            OutputSink::Memory,
+            &master_key,
+            EncryptParams::new(header),
+            ApiConfig::new(Some(true), None),
+        )
+        .unwrap();
+        // Before pooling each crypto worker started its own `cpu_workers` frame workers
+        assert_eq!(snapshot.frame_workers, snapshot.cpu_workers.max(1));
+
+        let decrypted = decrypt_stream_v2(
+            InputSource::Memory(snapshot.output.unwrap()),
+            OutputSink::Memory,
+            &master_key,
+            DecryptParams::new(),
+            ApiConfig::new(Some(true), None),
+        )
+        .unwrap();
+        assert_eq!(decrypted.frame_workers, decrypted.cpu_workers.max(1));
+        assert_eq!(decrypted.output.unwrap(), input);
+    }
+}
@@ -0,0 +1,181 @@
+// # 📂 `tests/test_frame_routing.rs`
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use crossbeam::channel::{Receiver, Sender, bounded, unbounded};
+    use crypto_core::{
+        crypto::{DigestAlg, TAG_LEN},
+        headers::{HeaderV1, Strategy},
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{ApiConfig, DecryptParams, EncryptParams, decrypt_stream_v2, encrypt_stream_v2},
+            frame_worker::{
+                DecryptFrameInput, DecryptedFrame, EncryptedFrame, FrameInput, FrameWorkerError, decrypt::DecryptFrameWorker,
+                encrypt::EncryptFrameWorker,
+            },
+            framing::{FrameHeader, FrameType},
+            segment_worker::{
+                DecryptSegmentInput, EncryptSegmentInput, EncryptedSegment, decrypt::process_decrypt_segment_v2,
+                encrypt::process_encrypt_segment_2,
+            },
+            segmenting::{SegmentHeader, types::SegmentFlags},
+        },
+        telemetry::StageTimes,
+    };
+
+    const FRAME: usize = 64;
+    const POOL: usize = 3;
+
+    fn key() -> Vec<u8> {
+        vec![0x42u8; 32]
+    }
+
+    struct EncPool {
+        frame_tx: Sender<FrameInput>,
+        out_tx: Sender<Result<EncryptedFrame, FrameWorkerError>>,
+        out_rx: Receiver<Result<EncryptedFrame, FrameWorkerError>>,
+    }
+
+    struct DecPool {
+        frame_tx: Sender<DecryptFrameInput>,
+        out_tx: Sender<Result<DecryptedFrame, FrameWorkerError>>,
+        out_rx: Receiver<Result<DecryptedFrame, FrameWorkerError>>,
+    }
+
+    fn enc_pool() -> EncPool {
+        let (frame_tx, frame_rx) = bounded::<FrameInput>(POOL * 4);
+        let (out_tx, out_rx) = unbounded();
+        for _ in 0..POOL {
+            EncryptFrameWorker::new(HeaderV1::test_header(), &key()).unwrap().run(frame_rx.clone(), out_tx.clone());
+        }
+        EncPool { frame_tx, out_tx, out_rx }
+    }
+
+    fn dec_pool() -> DecPool {
+        let (frame_tx, frame_rx) = bounded::<DecryptFrameInput>(POOL * 4);
+        let (out_tx, out_rx) = unbounded();
+        for _ in 0..POOL {
+            DecryptFrameWorker::new(HeaderV1::test_header(), &key()).unwrap().run(frame_rx.clone(), out_tx.clone());
+        }
+        DecPool { frame_tx, out_tx, out_rx }
+    }
+
+    fn plaintext(segment_index: u32, len: usize) -> Bytes {
+        (0..len).map(|i| (i as u32 ^ segment_index.wrapping_mul(97)) as u8).collect::<Vec<_>>().into()
+    }
+
+    fn encrypt(pool: &EncPool, segment_index: u32, data: Bytes) -> EncryptedSegment {
+        let input = EncryptSegmentInput { segment_index, bytes: data, flags: SegmentFlags::empty(), stage_times: StageTimes::default() };
+        process_encrypt_segment_2(&input, FRAME, DigestAlg::Sha256, &pool.frame_tx, &pool.out_rx).unwrap()
+    }
+
+    fn decrypt(pool: &DecPool, segment: &EncryptedSegment) -> Result<Vec<u8>, String> {
+        let input = DecryptSegmentInput { header: segment.header, wire: segment.wire.clone() };
+        process_decrypt_segment_v2(&input, &pool.frame_tx, &pool.out_rx)
+            .map(|seg| seg.frames.concat())
+            .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn stray_encrypt_responses_are_skipped() {
+        let pool = enc_pool();
+        let worker = EncryptFrameWorker::new(HeaderV1::test_header(), &key()).unwrap();
+
+        // Leftovers of segment 0, queued before segment 1 starts
+        let stray = FrameInput {
+            segment_index: 0,
+            frame_index: 1,
+            frame_type: FrameType::Data,
+            plaintext: plaintext(0, FRAME),
+            segment_flags: SegmentFlags::empty(),
+        };
+        pool.out_tx.send(Ok(worker.encrypt_frame(&stray).unwrap())).unwrap();
+        pool.out_tx.send(Err(FrameWorkerError::InvalidInput("stale".into()).at(0, 2))).unwrap();
+
+        let data = plaintext(1, 5 * FRAME + 3);
+        let segment = encrypt(&pool, 1, data.clone());
+        assert_eq!(segment.header.segment_index, 1);
+
+        assert_eq!(decrypt(&dec_pool(), &segment).unwrap(), data);
+    }
+
+    #[test]
+    fn stray_decrypt_responses_are_skipped() {
+        let enc = enc_pool();
+        let pool = dec_pool();
+        let other = encrypt(&enc, 7, plaintext(7, 2 * FRAME));
+        let segment = encrypt(&enc, 8, plaintext(8, 4 * FRAME));
+
+        let worker = DecryptFrameWorker::new(HeaderV1::test_header(), &key()).unwrap();
+        let first_frame = other.wire.slice(..FrameHeader::LEN + FRAME + TAG_LEN);
+        pool.out_tx.send(Ok(worker.decrypt_frame(DecryptFrameInput { wire: first_frame, segment_flags: other.header.flags }).unwrap())).unwrap();
+        pool.out_tx.send(Err(FrameWorkerError::InvalidInput("stale".into()).at(7, 0))).unwrap();
+
+        assert_eq!(decrypt(&pool, &segment).unwrap(), plaintext(8, 4 * FRAME));
+    }
+
+    #[test]
+    fn failed_segment_does_not_poison_the_next_one() {
+        let enc = enc_pool();
+        let pool = dec_pool();
+
+        for round in 0..50u32 {
+            let mut bad = encrypt(&enc, 2 * round, plaintext(round, 8 * FRAME));
+            let mut wire = bad.wire.to_vec();
+            wire[FrameHeader::LEN + 4] ^= 0x01; // inside the first data frame's ciphertext
+            bad.wire = Bytes::from(wire);
+            bad.header = SegmentHeader::new(
+                &bad.wire,
+                bad.header.segment_index,
+                bad.header.bytes_len,
+                bad.header.frame_count,
+                bad.header.digest_alg,
+                bad.header.flags,
+            );
+            assert!(decrypt(&pool, &bad).is_err(), "round {round}");
+
+            // Frames of the failed segment may still be in flight
+            let good_data = plaintext(round + 1, 6 * FRAME + 1);
+            let good = encrypt(&enc, 2 * round + 1, good_data.clone());
+            assert_eq!(decrypt(&pool, &good).unwrap(), good_data, "round {round}");
+        }
+    }
+
+    #[test]
+    fn many_small_segments_roundtrip_with_parallel_workers() {
+        let master_key = vec![0x11u8; 32];
+        let header = HeaderV1 {
+            chunk_size: 1024,
+            strategy: Strategy::Parallel as u16,
+            parallel_hint: 4,
+            ..HeaderV1::test_header()
+        };
+
+        for round in 0..5usize {
+            let data: Vec<u8> = (0..300 * 1024 + round * 37).map(|i| (i * 7 + round) as u8).collect();
+            let ciphertext = encrypt_stream_v2(
+                InputSource::Memory(data.clone()),
+                OutputSink::Memory,
+                &master_key,
+                EncryptParams::new(header),
+                ApiConfig::new(Some(true), None),
+            )
+            .unwrap()
+            .output
+            .unwrap();
+
+            let decrypted = decrypt_stream_v2(
+                InputSource::Memory(ciphertext),
+                OutputSink::Memory,
+                &master_key,
+                DecryptParams::new(),
+                ApiConfig::new(Some(true), None),
+            )
+            .unwrap()
+            .output
+            .unwrap();
+            assert_eq!(decrypted, data, "round {round}");
+        }
+    }
+}
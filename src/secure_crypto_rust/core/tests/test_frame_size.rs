@@ -0,0 +1,147 @@
+// # 📂 `tests/test_frame_size.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use crypto_core::{
+        constants::MAX_CHUNK_SIZE,
+        headers::HeaderV1,
+        stream_v2::{
+            core::{DecryptParams, EncryptParams},
+            segment_worker::types::{
+                ALLOWED_FRAME_SIZES, MAX_FRAMES_PER_SEGMENT, MIN_FRAME_SIZE, MIN_FRAMES_PER_SEGMENT, PREFERRED_FRAME_SIZE,
+                frame_ranges, optimal_frame_size,
+            },
+            segmenting::{SegmentHeader, decode_segment_header, types::SegmentFlags},
+        },
+        types::StreamError,
+    };
+
+    use crate::common;
+
+    const CHUNK: usize = 64 * 1024;
+
+    /// Two full segments of incompressible bytes, so segments keep their size.
+    fn plaintext() -> Vec<u8> {
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        (0..2 * CHUNK)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                (state >> 32) as u8
+            })
+            .collect()
+    }
+
+    fn encrypt(frame_size: Option<usize>) -> Result<Vec<u8>, StreamError> {
+        let header = HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() };
+        let mut params = EncryptParams::new(header);
+        params.frame_size = frame_size;
+
+        common::try_encrypt(&plaintext(), params, common::config())
+    }
+
+    fn decrypt(ciphertext: Vec<u8>) -> Vec<u8> {
+        common::decrypt(ciphertext, DecryptParams::new()).expect("decryption should succeed")
+    }
+
+    /// Data frame counts of every non-final segment.
+    fn frames_per_segment(ciphertext: &[u8]) -> Vec<u32> {
+        let mut counts = Vec::new();
+        let mut offset = HeaderV1::LEN;
+        while offset < ciphertext.len() {
+            let header = decode_segment_header(&ciphertext[offset..offset + SegmentHeader::LEN]).unwrap();
+            offset += SegmentHeader::LEN + header.wire_len as usize;
+            if !header.flags.contains(SegmentFlags::FINAL_SEGMENT) {
+                counts.push(header.frame_count);
+            }
+        }
+        counts
+    }
+
+    #[test]
+    fn frame_count_follows_requested_frame_size() {
+        for (frame_size, expected) in [(4 * 1024, 16), (16 * 1024, 4), (64 * 1024, 1)] {
+            let ciphertext = encrypt(Some(frame_size)).expect("encryption should succeed");
+            assert_eq!(frames_per_segment(&ciphertext), vec![expected, expected], "frame size {frame_size}");
+            assert_eq!(decrypt(ciphertext), plaintext(), "frame size {frame_size} roundtrip mismatch");
+        }
+    }
+
+    #[test]
+    fn default_prefers_16_kib_frames() {
+        // 64 KiB segments → 16 KiB frames
+        let ciphertext = encrypt(None).unwrap();
+        assert_eq!(frames_per_segment(&ciphertext), vec![4, 4]);
+    }
+
+    #[test]
+    fn optimal_frame_size_at_the_edges() {
+        for (segment_size, expected) in [
+            (4 * 1024, 4 * 1024),         // one frame, nothing smaller allowed
+            (16 * 1024, 4 * 1024),        // four frames
+            (32 * 1024, 8 * 1024),
+            (64 * 1024, PREFERRED_FRAME_SIZE),
+            (8 * 1024 * 1024, PREFERRED_FRAME_SIZE), // 512 frames
+            (8 * 1024 * 1024 + 1, 32 * 1024),
+            (3 * 1024 * 1024, PREFERRED_FRAME_SIZE),
+            (MAX_CHUNK_SIZE, 64 * 1024),
+        ] {
+            assert_eq!(optimal_frame_size(segment_size), expected, "segment size {segment_size}");
+        }
+    }
+
+    #[test]
+    fn final_frame_holds_the_remainder() {
+        let ranges: Vec<_> = frame_ranges(10_000, 4096).collect();
+        assert_eq!(ranges, vec![0..4096, 4096..8192, 8192..10_000]);
+        assert_eq!(frame_ranges(8192, 4096).len(), 2);
+        assert_eq!(frame_ranges(0, 4096).len(), 0);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_frame_sizing_stays_in_bounds(segment_size in (4 * 1024usize)..=MAX_CHUNK_SIZE) {
+            let frame_size = optimal_frame_size(segment_size);
+            prop_assert!(ALLOWED_FRAME_SIZES.contains(&frame_size));
+
+            let ranges: Vec<_> = frame_ranges(segment_size, frame_size).collect();
+            let min_frames = MIN_FRAMES_PER_SEGMENT.min(segment_size.div_ceil(MIN_FRAME_SIZE));
+            prop_assert!((min_frames..=MAX_FRAMES_PER_SEGMENT).contains(&ranges.len()), "{} frames", ranges.len());
+
+            // Contiguous, full frames except the last, covering the segment
+            prop_assert_eq!(ranges.iter().map(|r| r.len()).sum::<usize>(), segment_size);
+            for (i, range) in ranges.iter().enumerate() {
+                prop_assert_eq!(range.start, i * frame_size);
+                prop_assert!(range.len() == frame_size || (i == ranges.len() - 1 && !range.is_empty()));
+            }
+        }
+    }
+
+    #[test]
+    fn smaller_frames_cost_more_overhead() {
+        let small = encrypt(Some(4 * 1024)).unwrap();
+        let large = encrypt(Some(64 * 1024)).unwrap();
+        assert!(small.len() > large.len());
+    }
+
+    #[test]
+    fn rejects_frame_size_outside_allowed_set() {
+        for frame_size in [0, 1000, 12 * 1024, 128 * 1024] {
+            match encrypt(Some(frame_size)) {
+                Err(StreamError::Validation(msg)) => assert!(msg.contains("invalid frame size"), "{msg}"),
+                Err(e) => panic!("unexpected error: {e}"),
+                Ok(_) => panic!("frame size {frame_size} must be rejected"),
+            }
+        }
+    }
+
+    #[test]
+    fn builder_sets_frame_size() {
+        let params = EncryptParams::new(HeaderV1::test_header()).with_frame_size(8 * 1024);
+        assert_eq!(params.frame_size, Some(8 * 1024));
+    }
+}
@@ -17,11 +17,12 @@ mod tests {
     use crypto_core::crypto::KEY_LEN_32;
     use crypto_core::headers::types::HeaderV1;
     use crypto_core::stream_v2::frame_worker::{
-        DecryptedFrame, EncryptedFrame, FrameInput, FrameWorkerError,
+        DecryptFrameInput, DecryptedFrame, EncryptedFrame, FrameInput, FrameWorkerError,
     };
     use crypto_core::stream_v2::frame_worker::decrypt::DecryptFrameWorker;
     use crypto_core::stream_v2::frame_worker::encrypt::EncryptFrameWorker;
-    use crypto_core::stream_v2::framing::FrameType;
+    use crypto_core::stream_v2::framing::{FrameError, FrameHeader, FrameType};
+    use crypto_core::stream_v2::segmenting::types::SegmentFlags;
 
     fn test_key() -> Vec<u8> {
         vec![0x42u8; KEY_LEN_32]
@@ -33,9 +34,14 @@ mod tests {
             segment_index: 1,
             frame_index,
             plaintext: Bytes::copy_from_slice(data),
+            segment_flags: SegmentFlags::empty(),
         }
     }
 
+    fn sealed(wire: Bytes) -> DecryptFrameInput {
+        DecryptFrameInput { wire, segment_flags: SegmentFlags::empty() }
+    }
+
     // ✅ 1. Encrypt → decrypt round-trip
     #[test]
     fn encrypt_decrypt_roundtrip() {
@@ -48,7 +54,7 @@ mod tests {
         let input = sample_input(0, b"hello world");
 
         let encrypted = enc.encrypt_frame(&input).unwrap();
-        let decrypted = dec.decrypt_frame(encrypted.wire.clone()).unwrap();
+        let decrypted = dec.decrypt_frame(sealed(encrypted.wire.clone())).unwrap();
 
         assert_eq!(decrypted.frame_index, 0);
         assert_eq!(&decrypted.plaintext[..], b"hello world");
@@ -86,7 +92,10 @@ mod tests {
         let last = wire.len() - 1;
         wire[last] ^= 0xFF;
 
-        assert!(dec.decrypt_frame(wire.freeze()).is_err());
+        assert!(matches!(
+            dec.decrypt_frame(sealed(wire.freeze())),
+            Err(FrameWorkerError::AuthenticationFailed { segment_index: 1, frame_index: 3 })
+        ));
     }
 
     // ❌ 4. Wrong key fails
@@ -100,7 +109,10 @@ mod tests {
         let input = sample_input(0, b"secret");
         let encrypted = enc.encrypt_frame(&input).unwrap();
 
-        assert!(dec.decrypt_frame(encrypted.wire).is_err());
+        assert!(matches!(
+            dec.decrypt_frame(sealed(encrypted.wire)),
+            Err(FrameWorkerError::AuthenticationFailed { segment_index: 1, frame_index: 0 })
+        ));
     }
 
     // ❌ 5. Wrong header (salt)
@@ -115,7 +127,10 @@ mod tests {
         let input = sample_input(1, b"oops");
         let encrypted = enc.encrypt_frame(&input).unwrap();
 
-        assert!(dec.decrypt_frame(encrypted.wire).is_err());
+        assert!(matches!(
+            dec.decrypt_frame(sealed(encrypted.wire)),
+            Err(FrameWorkerError::AuthenticationFailed { segment_index: 1, frame_index: 1 })
+        ));
     }
 
     // ✅ 6. DATA frame cannot be empty
@@ -183,7 +198,7 @@ mod tests {
         let enc = EncryptFrameWorker::new(header.clone(), &key).unwrap();
         let dec = DecryptFrameWorker::new(header, &key).unwrap();
 
-        let (frame_tx, frame_rx) = crossbeam::channel::unbounded::<Bytes>();
+        let (frame_tx, frame_rx) = crossbeam::channel::unbounded::<DecryptFrameInput>();
         let (out_tx, out_rx) =
             crossbeam::channel::unbounded::<Result<DecryptedFrame, FrameWorkerError>>();
 
@@ -192,8 +207,8 @@ mod tests {
         let e1 = enc.encrypt_frame(&sample_input(0, b"x")).unwrap();
         let e2 = enc.encrypt_frame(&sample_input(1, b"y")).unwrap();
 
-        frame_tx.send(e1.wire).unwrap();
-        frame_tx.send(e2.wire).unwrap();
+        frame_tx.send(sealed(e1.wire)).unwrap();
+        frame_tx.send(sealed(e2.wire)).unwrap();
 
         let d1 = out_rx.recv().unwrap().unwrap();
         let d2 = out_rx.recv().unwrap().unwrap();
@@ -216,10 +231,11 @@ mod tests {
             segment_index: 9,
             frame_index: 99,
             plaintext: Bytes::from_static(b"done"),
+            segment_flags: SegmentFlags::empty(),
         };
 
         let encrypted = enc.encrypt_frame(&input).unwrap();
-        let decrypted = dec.decrypt_frame(encrypted.wire).unwrap();
+        let decrypted = dec.decrypt_frame(sealed(encrypted.wire)).unwrap();
 
         assert_eq!(decrypted.frame_type, FrameType::Digest);
         assert_eq!(&decrypted.plaintext[..], b"done");
@@ -238,6 +254,7 @@ mod tests {
             segment_index: 9,
             frame_index: 99,
             plaintext: Bytes::from_static(b"oops"),
+            segment_flags: SegmentFlags::empty(),
         };
 
         let result = enc.encrypt_frame(&input);
@@ -248,6 +265,47 @@ mod tests {
                 if msg.contains("TERMINATOR frame must be empty")
         ));
     }
+
+    // ❌ 12. Truncated frame reports how much is missing
+    #[test]
+    fn truncated_frame_reports_needed_bytes() {
+        let header = HeaderV1::test_header();
+        let key = test_key();
+
+        let enc = EncryptFrameWorker::new(header, &key).unwrap();
+        let dec = DecryptFrameWorker::new(header, &key).unwrap();
+
+        let encrypted = enc.encrypt_frame(&sample_input(2, b"cut short")).unwrap();
+        let full = encrypted.wire.len();
+
+        assert!(matches!(
+            dec.decrypt_frame(sealed(encrypted.wire.slice(..full - 4))),
+            Err(FrameWorkerError::Truncated { needed, have }) if needed == full && have == full - 4
+        ));
+        assert!(matches!(
+            dec.decrypt_frame(sealed(encrypted.wire.slice(..FrameHeader::LEN - 1))),
+            Err(FrameWorkerError::Truncated { needed: FrameHeader::LEN, have }) if have == FrameHeader::LEN - 1
+        ));
+    }
+
+    // ❌ 13. Corrupt header is malformed, not an auth failure
+    #[test]
+    fn corrupt_magic_is_a_malformed_header() {
+        let header = HeaderV1::test_header();
+        let key = test_key();
+
+        let enc = EncryptFrameWorker::new(header, &key).unwrap();
+        let dec = DecryptFrameWorker::new(header, &key).unwrap();
+
+        let encrypted = enc.encrypt_frame(&sample_input(0, b"magic")).unwrap();
+        let mut wire = bytes::BytesMut::from(&encrypted.wire[..]);
+        wire[0] ^= 0xFF;
+
+        assert!(matches!(
+            dec.decrypt_frame(sealed(wire.freeze())),
+            Err(FrameWorkerError::MalformedFrameHeader(FrameError::InvalidMagic(_)))
+        ));
+    }
 }
 
 // ## 🧠 Coverage Summary
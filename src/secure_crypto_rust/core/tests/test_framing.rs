@@ -165,7 +165,7 @@ mod tests {
 
         assert!(matches!(
             decode_frame(&wire),
-            Err(FrameError::InvalidFrameType(_))
+            Err(FrameError::UnknownFrameType { raw: 0xFF })
         ));
     }
 
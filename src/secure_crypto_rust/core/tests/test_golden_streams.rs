@@ -0,0 +1,116 @@
+// # 📂 `tests/test_golden_streams.rs`
+//
+// Byte-exact fixtures of complete encrypted streams. Any change to the wire
+// format, key schedule, nonce derivation or codec framing shows up here.
+//
+// Regenerate after an intentional format change with
+// `RSE1_BLESS_FIXTURES=1 cargo test --test test_golden_streams`, then commit
+// the rewritten files under `tests/fixtures/`.
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf};
+
+    use crypto_core::{
+        compression::CompressionCodec,
+        headers::{HeaderV1, decode_header_le},
+        stream_v2::core::{DecryptParams, EncryptParams},
+    };
+
+    use crate::common;
+
+    const CHUNK: usize = 16 * 1024;
+    const BLESS_ENV: &str = "RSE1_BLESS_FIXTURES";
+
+    /// Compressible text, ≈ 2.5 segments.
+    fn text_plaintext() -> Vec<u8> {
+        b"golden stream fixture: fixed key, fixed salt, fixed plaintext\n".repeat(5 * CHUNK / 2 / 62)
+    }
+
+    /// Incompressible bytes (xorshift64), ≈ 1.5 segments.
+    fn random_plaintext() -> Vec<u8> {
+        let mut state = 0x9E37_79B9_7F4A_7C15u64;
+        (0..CHUNK + CHUNK / 2)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                (state >> 24) as u8
+            })
+            .collect()
+    }
+
+    /// Fixed salt and key id from `test_header`; sequential so `parallel_hint` is stable, no timestamp.
+    /// Frame AAD v3, as written by default headers.
+    fn encrypt(codec: CompressionCodec, plaintext: &[u8]) -> Vec<u8> {
+        let mut header = HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() };
+        header.enable_segment_flags_aad();
+        let params = EncryptParams::new(header).with_compression(codec, None).without_timestamp();
+        common::try_encrypt(plaintext, params, common::config()).expect("encryption should succeed")
+    }
+
+    fn decrypt(ciphertext: Vec<u8>) -> Vec<u8> {
+        common::decrypt(ciphertext, DecryptParams::new()).expect("decryption should succeed")
+    }
+
+    fn fixture_path(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(format!("{name}.hex"))
+    }
+
+    /// Hex dump, 32 bytes per line.
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.chunks(32).map(|line| hex::encode(line) + "\n").collect()
+    }
+
+    fn from_hex(text: &str) -> Vec<u8> {
+        let joined: String = text.split_whitespace().collect();
+        hex::decode(joined).expect("fixture is not valid hex")
+    }
+
+    /// Byte-compares against the fixture; rewrites it only when blessing.
+    fn check_golden(name: &str, actual: &[u8]) -> Vec<u8> {
+        let path = fixture_path(name);
+        if env::var_os(BLESS_ENV).is_some() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, to_hex(actual)).unwrap();
+            eprintln!("wrote {}; commit it to pin the format", path.display());
+            return actual.to_vec();
+        }
+        assert!(path.exists(), "{name}: missing fixture {}; run with {BLESS_ENV}=1 and commit it", path.display());
+
+        let expected = from_hex(&fs::read_to_string(&path).unwrap());
+        if let Some(at) = expected.iter().zip(actual).position(|(a, b)| a != b) {
+            panic!("{name}: first difference at byte {at} (stream header is {} bytes)", HeaderV1::LEN);
+        }
+        assert_eq!(expected.len(), actual.len(), "{name}: length changed");
+        expected
+    }
+
+    #[test]
+    fn zstd_stream_matches_fixture() {
+        let plaintext = text_plaintext();
+        let golden = check_golden("zstd_text", &encrypt(CompressionCodec::Zstd, &plaintext));
+
+        let header = decode_header_le(&golden[..HeaderV1::LEN]).unwrap();
+        assert_eq!(header.compression, CompressionCodec::Zstd as u16);
+        assert!(golden.len() < plaintext.len());
+        assert_eq!(decrypt(golden), plaintext);
+    }
+
+    #[test]
+    fn auto_stream_matches_fixture() {
+        let plaintext = random_plaintext();
+        let golden = check_golden("auto_random", &encrypt(CompressionCodec::Auto, &plaintext));
+
+        assert!(golden.len() > plaintext.len());
+        assert_eq!(decrypt(golden), plaintext);
+    }
+
+    #[test]
+    fn encryption_is_deterministic_for_fixed_inputs() {
+        let plaintext = text_plaintext();
+        assert_eq!(encrypt(CompressionCodec::Zstd, &plaintext), encrypt(CompressionCodec::Zstd, &plaintext));
+    }
+}
@@ -0,0 +1,107 @@
+// # 📂 `tests/test_gpu_fallback.rs`
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use crossbeam::channel;
+    use crypto_core::{
+        compression::{CodecLevel, CompressionError, codec_ids},
+        stream_v2::{
+            compression_worker::{
+                BackendOrigin, CodecInfo, CompressionBackend, CpuCompressionBackend, make_backend_checked,
+                run_compression_worker, verify_or_fallback,
+            },
+            parallelism::WorkerTarget,
+            segment_worker::EncryptSegmentInput,
+            segmenting::types::SegmentFlags,
+        },
+        telemetry::StageTimes,
+    };
+
+    fn codec_info() -> CodecInfo<'static> {
+        CodecInfo { codec_id: codec_ids::ZSTD, level: CodecLevel::ZstdBalanced, dict: None, gpu: None, max_output: None, segment_dicts: None }
+    }
+
+    /// Emits a short digest in place of compressed data, like the GPU hash sketches.
+    struct PlaceholderHash;
+
+    impl CompressionBackend for PlaceholderHash {
+        fn compress_chunk(&mut self, input: &[u8]) -> Result<Vec<u8>, CompressionError> {
+            Ok(crc32fast::hash(input).to_le_bytes().to_vec())
+        }
+        fn decompress_chunk(&mut self, input: &[u8]) -> Result<Vec<u8>, CompressionError> {
+            Ok(input.to_vec())
+        }
+    }
+
+    /// Round-trips through itself, but in a format no CPU decoder understands.
+    struct PrivateFormat;
+
+    impl CompressionBackend for PrivateFormat {
+        fn compress_chunk(&mut self, input: &[u8]) -> Result<Vec<u8>, CompressionError> {
+            Ok(input.iter().map(|b| b ^ 0x5A).collect())
+        }
+        fn decompress_chunk(&mut self, input: &[u8]) -> Result<Vec<u8>, CompressionError> {
+            self.compress_chunk(input)
+        }
+    }
+
+    fn fallback_for(candidate: Result<Box<dyn CompressionBackend>, CompressionError>) -> Box<dyn CompressionBackend> {
+        let (backend, origin) = verify_or_fallback(candidate, codec_info()).unwrap();
+        assert_eq!(origin, BackendOrigin::CpuFallback);
+        backend
+    }
+
+    #[test]
+    fn failing_backends_are_replaced() {
+        fallback_for(Ok(Box::new(PlaceholderHash)));
+        fallback_for(Ok(Box::new(PrivateFormat)));
+        fallback_for(Err(CompressionError::CodecInitFailed { codec: "wgpu".into(), msg: "no adapter".into() }));
+    }
+
+    #[test]
+    fn conforming_gpu_backend_is_kept() {
+        let (_, origin) = make_backend_checked(WorkerTarget::Gpu(0), codec_info()).unwrap();
+        assert_eq!(origin, BackendOrigin::Gpu);
+        let (_, origin) = make_backend_checked(WorkerTarget::Cpu(0), codec_info()).unwrap();
+        assert_eq!(origin, BackendOrigin::Cpu);
+    }
+
+    #[test]
+    fn fallback_cannot_hide_codec_errors() {
+        let info = CodecInfo { codec_id: 0x7777, ..codec_info() };
+        let result = verify_or_fallback(Ok(Box::new(PlaceholderHash)), info);
+        assert!(matches!(result, Err(CompressionError::UnsupportedCodec { codec_id: 0x7777 })));
+    }
+
+    #[test]
+    fn worker_output_stays_decodable_after_fallback() {
+        let backend = fallback_for(Ok(Box::new(PlaceholderHash)));
+        let (tx_in, rx_in) = channel::unbounded();
+        let (tx_out, rx_out) = channel::unbounded();
+        let worker = std::thread::spawn(move || run_compression_worker(rx_in, tx_out, backend));
+
+        let segments: Vec<Vec<u8>> = (0..4u8).map(|i| vec![b'a' + i; 32 * 1024]).collect();
+        for (i, bytes) in segments.iter().enumerate() {
+            tx_in
+                .send(EncryptSegmentInput {
+                    segment_index: i as u32,
+                    bytes: Bytes::from(bytes.clone()),
+                    flags: SegmentFlags::empty(),
+                    stage_times: StageTimes::default(),
+                })
+                .unwrap();
+        }
+        drop(tx_in);
+        worker.join().unwrap();
+
+        let mut decoder = CpuCompressionBackend::new(codec_info()).unwrap();
+        let mut outputs: Vec<EncryptSegmentInput> = rx_out.iter().map(|res| res.unwrap()).collect();
+        outputs.sort_by_key(|seg| seg.segment_index);
+        assert_eq!(outputs.len(), segments.len());
+        for (seg, expected) in outputs.iter().zip(&segments) {
+            assert!(!seg.flags.contains(SegmentFlags::UNCOMPRESSED));
+            assert_eq!(&decoder.decompress_chunk(&seg.bytes).unwrap(), expected);
+        }
+    }
+}
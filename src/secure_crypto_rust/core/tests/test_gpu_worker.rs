@@ -1,13 +1,12 @@
 #[cfg(test)]
 mod gpu_worker_tests {
-    use std::sync::{Arc, Mutex};
     use bytes::Bytes;
     use crossbeam::channel;
 
     use crypto_core::{compression::{CodecLevel, codec_ids}, 
         stream_v2::{
             compression_worker::{CodecInfo, GpuCompressionBackend, run_compression_worker}, 
-            parallelism::Scheduler, segment_worker::EncryptSegmentInput, segmenting::types::SegmentFlags
+            segment_worker::EncryptSegmentInput, segmenting::types::SegmentFlags
         }, telemetry::StageTimes
     };
 
@@ -18,17 +17,16 @@ mod gpu_worker_tests {
             level: CodecLevel::Custom(0),
             dict: None,
             gpu: None,
+            max_output: None,
+            segment_dicts: None,
         };
 
         let backend = GpuCompressionBackend::new(codec_info).expect("gpu backend init");
         let (tx_in, rx_in) = channel::unbounded();
         let (tx_out, rx_out) = channel::unbounded();
 
-        let scheduler = Arc::new(Mutex::new(Scheduler::new(0, 1, 4 * 1024 * 1024)));
-
         // spawn worker
-        let sched_clone = scheduler.clone();
-        std::thread::spawn(move || run_compression_worker(rx_in, tx_out, Box::new(backend), sched_clone));
+        std::thread::spawn(move || run_compression_worker(rx_in, tx_out, Box::new(backend)));
 
         // send a large segment (>= 4 MB)
         let big_buf = vec![42u8; 32 * 1024 * 1024]; // 5 MB
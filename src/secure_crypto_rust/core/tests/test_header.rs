@@ -9,7 +9,28 @@
 #[cfg(test)]
 mod tests {
 
-use crypto_core::{compression::enum_name_or_hex, constants::{MAX_CHUNK_SIZE, flags}, headers::{AadDomain, AlgProfile, CipherSuite, HeaderError, HeaderV1, HkdfPrf, Strategy, fmt_bytes}};
+use std::io::{Cursor, Read};
+use std::sync::Arc;
+
+use crypto_core::{
+    compression::enum_name_or_hex,
+    constants::{ALLOWED_CHUNK_SIZES, ChunkSize, DEFAULT_CHUNK_SIZE, MAX_CHUNK_SIZE, flags},
+    headers::{
+        AadDomain, AlgProfile, CipherSuite, HeaderError, HeaderV1, HkdfPrf, Strategy, UnknownFlagPolicy, decode_any_with,
+        decode_header_le, decode_header_le_with, encode_header_le, fmt_bytes,
+    },
+    crypto::{DigestAlg, derive_session_key_32},
+    recovery::AsyncLogManager,
+    stream_v2::{
+        DecryptingReader, InputSource, OutputSink,
+        core::{ApiConfig, DecryptParams, decrypt_stream_v2},
+        io::PayloadReader,
+        parallelism::HybridParallelismProfile,
+        pipeline::{PipelineConfig, run_encrypt_pipeline},
+        segment_worker::EncryptContext,
+    },
+    types::StreamError,
+};
 
 // ## 1️⃣ Enum registry verification tests
 
@@ -79,7 +100,7 @@ use crypto_core::{compression::enum_name_or_hex, constants::{MAX_CHUNK_SIZE, fla
     fn header_with_optional_fields_valid() {
         let mut h = HeaderV1::test_header();
         h.set_plaintext_size(123456);
-        h.set_crc32(0xDEADBEEF);
+        h.enable_crc32();
         h.set_dict_id(42);
         h.enable_terminator();
         h.enable_final_digest();
@@ -152,6 +173,97 @@ use crypto_core::{compression::enum_name_or_hex, constants::{MAX_CHUNK_SIZE, fla
         );
     }
 
+    #[test]
+    fn unknown_flag_bits_are_rejected_unless_tolerated() {
+        assert_eq!(flags::KNOWN_MASK & 0x8000, 0);
+        let mut h = HeaderV1::test_header();
+        h.flags = flags::HAS_TOTAL_LEN | 0x8000;
+
+        assert!(matches!(h.validate(), Err(HeaderError::UnknownFlags { bits: 0x8000 })));
+        assert!(matches!(flags::validate(h.flags), Err(HeaderError::UnknownFlags { bits: 0x8000 })));
+        h.validate_with(UnknownFlagPolicy::Tolerate).unwrap();
+
+        // The encoder validates strictly: patch the flags into a valid encoding
+        let mut bytes = encode_header_le(&HeaderV1::test_header()).unwrap();
+        bytes[18..20].copy_from_slice(&h.flags.to_le_bytes());
+        let crc = crc32fast::hash(&bytes[0..32]);
+        bytes[32..36].copy_from_slice(&crc.to_le_bytes());
+
+        let err = decode_header_le(&bytes).unwrap_err();
+        assert!(matches!(err, HeaderError::UnknownFlags { bits: 0x8000 }), "{err:?}");
+        assert_eq!(err.to_string(), "unknown header flags 0x8000");
+        let decoded = decode_header_le_with(&bytes, UnknownFlagPolicy::Tolerate).unwrap();
+        assert_eq!(decoded.flags, h.flags);
+        decode_any_with(&bytes, UnknownFlagPolicy::Tolerate).unwrap();
+    }
+
+    /// `data` as a newer writer would encrypt it with flag bit `0x8000` set.
+    ///
+    /// This build refuses to write an unknown flag, so the stream is written
+    /// without it under a session key derived with it, then the bit is patched in.
+    fn newer_writer_stream(key: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut newer = HeaderV1 { chunk_size: 16 * 1024, ..HeaderV1::test_header() };
+        newer.enable_key_check();
+        newer.flags |= 0x8000;
+        let session_key = derive_session_key_32(key, &newer).unwrap();
+
+        let written = HeaderV1 { flags: newer.flags & !0x8000, ..newer };
+        let profile = HybridParallelismProfile::single_threaded();
+        let mut crypto = EncryptContext::new(written, profile.clone(), session_key.as_slice(), DigestAlg::Blake3).unwrap();
+        let mut reader = PayloadReader::new(Cursor::new(data.to_vec()));
+        let mut stream = Vec::new();
+        run_encrypt_pipeline(&mut reader, &mut stream, &mut crypto, &PipelineConfig::new(profile, None), Arc::new(AsyncLogManager::disabled()))
+            .unwrap();
+
+        // Flags sit outside the frame AAD of non-strict streams
+        let patched = u16::from_le_bytes([stream[18], stream[19]]) | 0x8000;
+        stream[18..20].copy_from_slice(&patched.to_le_bytes());
+        let crc = crc32fast::hash(&stream[0..32]);
+        stream[32..36].copy_from_slice(&crc.to_le_bytes());
+        stream
+    }
+
+    #[test]
+    fn tolerant_decryption_reads_streams_with_unknown_flags() {
+        let key = [0x11u8; 32];
+        let data = vec![0x5Au8; 40_000];
+        let stream = newer_writer_stream(&key, &data);
+
+        let decrypt = |params: DecryptParams| {
+            decrypt_stream_v2(InputSource::Memory(stream.clone()), OutputSink::Memory, &key, params, ApiConfig::new(Some(true), None))
+                .map(|snapshot| snapshot.output.unwrap_or_default())
+        };
+        let err = decrypt(DecryptParams::new()).unwrap_err();
+        assert!(matches!(err.root(), StreamError::Header(HeaderError::UnknownFlags { bits: 0x8000 })), "{err:?}");
+
+        let tolerant = DecryptParams::new().with_unknown_flags(UnknownFlagPolicy::Tolerate);
+        assert_eq!(decrypt(tolerant.clone()).unwrap(), data);
+
+        let mut reader = DecryptingReader::new(stream.as_slice(), &key, tolerant).unwrap();
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+        assert_eq!(out, data);
+    }
+
+    #[test]
+    fn flag_registry_covers_the_known_mask() {
+        let names = flags::all();
+        assert_eq!(names.iter().fold(0u16, |acc, (bit, _)| acc | bit), flags::KNOWN_MASK);
+        assert!(names.iter().all(|(bit, _)| bit.count_ones() == 1));
+        assert!(names.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        flags::validate(flags::KNOWN_MASK).unwrap();
+    }
+
+    #[test]
+    fn chunk_size_only_holds_allowed_sizes() {
+        assert_eq!(ChunkSize::default().get(), DEFAULT_CHUNK_SIZE as u32);
+        assert_eq!(ChunkSize::all().map(usize::from).collect::<Vec<_>>(), ALLOWED_CHUNK_SIZES);
+        assert_eq!(ChunkSize::new(128 * 1024).map(u32::from), Some(128 * 1024));
+        assert_eq!(ChunkSize::new(100 * 1024), None);
+        let err = ChunkSize::try_from(3usize).unwrap_err();
+        assert!(err.to_string().contains("chunk size 3"), "{err}");
+    }
+
 // ## 4️⃣ Formatting & diagnostics tests
 
     // These **lock error message quality**.
@@ -180,6 +292,42 @@ use crypto_core::{compression::enum_name_or_hex, constants::{MAX_CHUNK_SIZE, fla
         assert_eq!(s, "0x00ff01");
     }
 
+    #[test]
+    fn display_prints_names_and_redacts_the_salt() {
+        let mut h = HeaderV1::test_header();
+        h.flags = flags::HAS_TOTAL_LEN | flags::AAD_STRICT | 0x8000;
+        let s = h.to_string();
+        assert_eq!(
+            s,
+            "RSE1 v1 profile=Chacha20Poly1305HkdfSha256 cipher=Chacha20Poly1305 prf=Sha256 codec=Deflate \
+             strategy=Sequential aad=Generic chunk=64 KiB flags=[HAS_TOTAL_LEN, AAD_STRICT, 0x8000] key_id=1 salt=5253…"
+        );
+        assert_eq!(h.summary(), s);
+        assert_eq!(h.flags_names(), ["HAS_TOTAL_LEN", "AAD_STRICT"]);
+    }
+
+    #[test]
+    fn display_never_contains_the_full_salt() {
+        for _ in 0..16 {
+            let h = HeaderV1::default();
+            let s = h.to_string();
+            let salt = hex::encode(h.salt);
+            assert!(!s.contains(&salt), "{s}");
+            assert!(!s.contains(&salt[4..]), "{s}");
+            assert!(s.ends_with(&format!("salt={}…", &salt[..4])), "{s}");
+        }
+    }
+
+    #[test]
+    fn display_formats_chunk_size_units_and_empty_flags() {
+        let h = HeaderV1 { chunk_size: 4 * 1024 * 1024, flags: 0, ..HeaderV1::test_header() };
+        assert!(h.to_string().contains("chunk=4 MiB flags=[] "));
+        let h = HeaderV1 { chunk_size: 1000, strategy: 0x0BAD, ..HeaderV1::test_header() };
+        assert!(h.to_string().contains("strategy=0xbad"));
+        assert!(h.to_string().contains("chunk=1000 B"));
+        assert!(h.flags_names().is_empty());
+    }
+
 // ## 5️⃣ ABI & layout invariants (CRITICAL)
 
     // These catch accidental struct changes.
@@ -0,0 +1,126 @@
+// # 📂 `tests/test_header_builder.rs`
+
+#[cfg(test)]
+mod tests {
+    use crypto_core::{
+        compression::CompressionCodec,
+        constants::{DEFAULT_CHUNK_SIZE, MAX_CHUNK_SIZE, flags},
+        headers::{AlgProfile, CipherSuite, HeaderError, HeaderV1, HkdfPrf, decode_header_le, encode_header_le},
+    };
+
+    #[test]
+    fn profile_determines_cipher_and_prf() {
+        let header = HeaderV1::builder()
+            .alg_profile(AlgProfile::Chacha20Poly1305HkdfBlake3K)
+            .compression(CompressionCodec::Zstd)
+            .chunk_size(1 << 20)
+            .key_id(7)
+            .build()
+            .unwrap();
+
+        assert_eq!(header.alg_profile, AlgProfile::Chacha20Poly1305HkdfBlake3K as u16);
+        assert_eq!(header.cipher, CipherSuite::Chacha20Poly1305 as u16);
+        assert_eq!(header.hkdf_prf, HkdfPrf::Blake3K as u16);
+        assert_eq!(header.compression, CompressionCodec::Zstd as u16);
+        assert_eq!(header.chunk_size, 1 << 20);
+        assert_eq!(header.key_id, 7);
+
+        // Decoding fills in the header CRC the encoder computed
+        let decoded = decode_header_le(&encode_header_le(&header).unwrap()).unwrap();
+        assert_eq!(HeaderV1 { crc32: 0, ..decoded }, header);
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        let header = HeaderV1::builder().build().unwrap();
+        assert_eq!(header.chunk_size, DEFAULT_CHUNK_SIZE as u32);
+        assert_eq!(header.alg_profile, AlgProfile::Chacha20Poly1305HkdfSha256 as u16);
+        assert_ne!(header.salt, [0u8; 16]);
+    }
+
+    #[test]
+    fn salt_is_random_unless_supplied() {
+        let a = HeaderV1::builder().build().unwrap();
+        let b = HeaderV1::builder().build().unwrap();
+        assert_ne!(a.salt, b.salt);
+
+        let salt = [0x5Au8; 16];
+        assert_eq!(HeaderV1::builder().salt(salt).build().unwrap().salt, salt);
+    }
+
+    #[test]
+    fn chunk_size_is_rounded_up() {
+        let header = HeaderV1::builder().chunk_size(100_000).build().unwrap();
+        assert_eq!(header.chunk_size, 128 * 1024);
+    }
+
+    #[test]
+    fn dict_id_and_plaintext_size_set_their_flags() {
+        let header = HeaderV1::builder().dict_id(42).plaintext_size(1234).build().unwrap();
+        assert_eq!(header.dict_id, 42);
+        assert_eq!(header.plaintext_size, 1234);
+        assert_ne!(header.flags & flags::DICT_USED, 0);
+        assert_ne!(header.flags & flags::HAS_TOTAL_LEN, 0);
+    }
+
+    #[test]
+    fn contradicting_cipher_is_rejected() {
+        let err = HeaderV1::builder()
+            .alg_profile(AlgProfile::Chacha20Poly1305HkdfSha256)
+            .cipher(CipherSuite::Aes256Gcm)
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, HeaderError::AlgProfileCipherMismatch { .. }));
+    }
+
+    #[test]
+    fn contradicting_prf_is_rejected() {
+        let err = HeaderV1::builder()
+            .alg_profile(AlgProfile::Aes256GcmHkdfSha256)
+            .hkdf_prf(HkdfPrf::Sha512)
+            .build()
+            .unwrap_err();
+        match err {
+            HeaderError::AlgProfilePrfMismatch { profile, prf } => {
+                assert_eq!(profile, AlgProfile::Aes256GcmHkdfSha256 as u16);
+                assert_eq!(prf, HkdfPrf::Sha512 as u16);
+            }
+            other => panic!("expected AlgProfilePrfMismatch, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn matching_explicit_cipher_and_prf_are_accepted() {
+        let header = HeaderV1::builder()
+            .alg_profile(AlgProfile::Aes256GcmHkdfSha512)
+            .cipher(CipherSuite::Aes256Gcm)
+            .hkdf_prf(HkdfPrf::Sha512)
+            .build()
+            .unwrap();
+        assert_eq!(header.cipher, CipherSuite::Aes256Gcm as u16);
+    }
+
+    #[test]
+    fn zero_chunk_size_is_rejected() {
+        let err = HeaderV1::builder().chunk_size(0).build().unwrap_err();
+        assert!(matches!(err, HeaderError::InvalidChunkSizeZero));
+    }
+
+    #[test]
+    fn oversized_chunk_size_is_rejected() {
+        let err = HeaderV1::builder().chunk_size(MAX_CHUNK_SIZE as u32 + 1).build().unwrap_err();
+        assert!(matches!(err, HeaderError::InvalidChunkSizeTooLarge { .. }));
+    }
+
+    #[test]
+    fn zero_salt_is_rejected() {
+        let err = HeaderV1::builder().salt([0u8; 16]).build().unwrap_err();
+        assert!(matches!(err, HeaderError::InvalidSalt { .. }));
+    }
+
+    #[test]
+    fn dict_flag_without_dict_id_is_rejected() {
+        let err = HeaderV1::builder().flags(flags::DICT_USED).build().unwrap_err();
+        assert!(matches!(err, HeaderError::DictUsedButMissingId));
+    }
+}
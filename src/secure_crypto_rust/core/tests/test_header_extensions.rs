@@ -0,0 +1,144 @@
+// # 📂 `tests/test_header_extensions.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crypto_core::{
+        constants::{KNOWN_EXT_FLAGS, flags},
+        headers::{AadDomain, HeaderError, HeaderExtensions, HeaderV1, decode_header_le, encode_header_le},
+        stream_v2::{
+            core::{DecryptParams, EncryptParams},
+            io::{PayloadReader, iter_segment_headers},
+        },
+        types::StreamError,
+    };
+
+    use crate::common;
+
+    const CHUNK: usize = 16 * 1024;
+    const UNKNOWN_BIT: u16 = 0x8000;
+
+    fn header() -> HeaderV1 {
+        HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() }
+    }
+
+    fn plaintext() -> Vec<u8> {
+        (0..3 * CHUNK + 77).map(|i| (i % 241) as u8).collect()
+    }
+
+    fn extensions() -> HeaderExtensions {
+        let mut ext = HeaderExtensions::new();
+        ext.push(0x0001, b"created-by: test".to_vec()).push(0x0a0b, vec![]).push(0x0002, vec![7u8; 300]);
+        ext
+    }
+
+    fn encrypt(header: HeaderV1, ext: Option<HeaderExtensions>) -> Vec<u8> {
+        let mut params = EncryptParams::new(header);
+        if let Some(ext) = ext {
+            params = params.with_extensions(ext);
+        }
+        common::encrypt(&plaintext(), params)
+    }
+
+    fn decrypt(ciphertext: Vec<u8>) -> Result<Vec<u8>, StreamError> {
+        common::decrypt(ciphertext, DecryptParams::new().expect_aad_domain(AadDomain::Generic))
+    }
+
+    #[test]
+    fn unknown_ext_flags_are_tolerated_without_strict_extensions() {
+        assert_eq!(KNOWN_EXT_FLAGS & UNKNOWN_BIT, 0);
+        let mut h = header();
+        h.set_ext_flags(UNKNOWN_BIT);
+        h.validate().unwrap();
+
+        let decoded = decode_header_le(&encode_header_le(&h).unwrap()).unwrap();
+        assert_eq!(decoded.ext_flags(), UNKNOWN_BIT);
+
+        // A third-party writer setting the bit does not break interop
+        assert_eq!(decrypt(encrypt(h, None)).unwrap(), plaintext());
+    }
+
+    #[test]
+    fn strict_extensions_reject_unknown_ext_flags() {
+        let mut h = header();
+        h.set_ext_flags(UNKNOWN_BIT);
+        h.enable_strict_extensions();
+        assert!(matches!(h.validate(), Err(HeaderError::UnknownExtensionFlags { bits: UNKNOWN_BIT })));
+
+        // Same bytes on the wire, decoded by a strict reader
+        let mut lenient = header();
+        lenient.set_ext_flags(UNKNOWN_BIT);
+        let mut bytes = encode_header_le(&lenient).unwrap();
+        bytes[18..20].copy_from_slice(&(lenient.flags | flags::STRICT_EXTENSIONS).to_le_bytes());
+        let crc = crc32fast::hash(&bytes[..32]);
+        bytes[32..36].copy_from_slice(&crc.to_le_bytes());
+        assert!(matches!(decode_header_le(&bytes), Err(HeaderError::UnknownExtensionFlags { .. })));
+    }
+
+    #[test]
+    fn key_check_slot_stays_reserved() {
+        let mut h = header();
+        h.reserved[0] = 1;
+        assert!(matches!(h.validate(), Err(HeaderError::ReservedBytesNonZero { .. })));
+    }
+
+    #[test]
+    fn tlv_block_round_trips() {
+        let ext = extensions();
+        let block = ext.encode().unwrap();
+        assert_eq!(block.len(), ext.encoded_len());
+        assert_eq!(HeaderExtensions::decode(&block).unwrap(), ext);
+        assert_eq!(ext.get(0x0002), Some(&[7u8; 300][..]));
+        assert!(HeaderExtensions::decode(&block[..block.len() - 1]).is_err());
+
+        let mut too_big = HeaderExtensions::new();
+        too_big.push(1, vec![0u8; u16::MAX as usize]);
+        assert!(matches!(too_big.encode(), Err(HeaderError::MalformedExtensions(_))));
+    }
+
+    #[test]
+    fn stream_with_extensions_decrypts_and_exposes_the_block() {
+        let ciphertext = encrypt(header(), Some(extensions()));
+
+        let (h, reader) = PayloadReader::with_header(Cursor::new(ciphertext.clone())).unwrap();
+        assert_eq!(h.ext_len() as usize, extensions().encoded_len());
+        assert_eq!(HeaderExtensions::decode(reader.extensions()).unwrap(), extensions());
+
+        let first = iter_segment_headers(Cursor::new(ciphertext.clone())).next().unwrap().unwrap();
+        assert_eq!(first.offset, h.encoded_len() as u64);
+
+        assert_eq!(decrypt(ciphertext).unwrap(), plaintext());
+    }
+
+    #[test]
+    fn extensions_are_authenticated_only_under_aad_strict() {
+        let value_at = HeaderV1::LEN + HeaderExtensions::ENTRY_HEADER_LEN;
+
+        let mut lenient = encrypt(header(), Some(extensions()));
+        lenient[value_at] ^= 0x01;
+        assert_eq!(decrypt(lenient).unwrap(), plaintext());
+
+        let mut strict_header = header();
+        strict_header.enable_aad_strict();
+        let mut strict = encrypt(strict_header, Some(extensions()));
+        assert_eq!(decrypt(strict.clone()).unwrap(), plaintext());
+        strict[value_at] ^= 0x01;
+        assert!(decrypt(strict).is_err());
+    }
+
+    #[test]
+    fn strict_extensions_reject_malformed_blocks() {
+        let mut h = header();
+        h.enable_strict_extensions();
+        let mut ciphertext = encrypt(h, Some(extensions()));
+        // Inflate the first entry's length past the end of the block
+        ciphertext[HeaderV1::LEN + 2..HeaderV1::LEN + 4].copy_from_slice(&u16::MAX.to_le_bytes());
+        assert!(matches!(
+            decrypt(ciphertext).map_err(StreamError::into_root),
+            Err(StreamError::Header(HeaderError::MalformedExtensions(_)))
+        ));
+    }
+}
@@ -0,0 +1,110 @@
+// # 📂 `tests/test_header_versions.rs`
+
+#[cfg(test)]
+mod tests {
+    use crypto_core::{
+        constants::{HEADER_V1, SUPPORTED_HEADER_VERSIONS},
+        headers::{HEADER_PREFIX_LEN, HeaderError, HeaderV1, VersionedHeader, decode_any, encode_header_le},
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{ApiConfig, DecryptParams, EncryptParams, decrypt_stream_v2, encrypt_stream_v2},
+        },
+        types::StreamError,
+    };
+
+    /// Valid v1 header bytes with the version field patched (CRC kept consistent).
+    fn fixture(version: u16) -> Vec<u8> {
+        let mut buf = encode_header_le(&HeaderV1::test_header()).unwrap().to_vec();
+        patch_version(&mut buf, version);
+        buf
+    }
+
+    fn patch_version(buf: &mut [u8], version: u16) {
+        buf[4..6].copy_from_slice(&version.to_le_bytes());
+        let crc = crc32fast::hash(&buf[0..32]);
+        buf[32..36].copy_from_slice(&crc.to_le_bytes());
+    }
+
+    #[test]
+    fn version_0_is_invalid() {
+        match decode_any(&fixture(0)) {
+            Err(HeaderError::InvalidVersion { have: 0 }) => {}
+            other => panic!("expected InvalidVersion, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn version_1_decodes() {
+        let header = decode_any(&fixture(1)).unwrap();
+        assert_eq!(header.version(), HEADER_V1);
+        assert_eq!(header.encoded_len(), HeaderV1::LEN);
+        assert_eq!(header, VersionedHeader::V1(HeaderV1 { crc32: header_crc(&fixture(1)), ..HeaderV1::test_header() }));
+    }
+
+    #[test]
+    fn newer_versions_are_unsupported() {
+        for version in [2u16, 0xFFFF] {
+            match decode_any(&fixture(version)) {
+                Err(HeaderError::UnsupportedVersion { have, supported }) => {
+                    assert_eq!(have, version);
+                    assert_eq!(supported, SUPPORTED_HEADER_VERSIONS);
+                }
+                other => panic!("version {version:#x}: expected UnsupportedVersion, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn validate_accepts_only_known_versions() {
+        let mut header = HeaderV1::test_header();
+        header.validate().unwrap();
+
+        header.version = 2;
+        assert!(matches!(header.validate(), Err(HeaderError::UnsupportedVersion { have: 2, .. })));
+        header.version = 0;
+        assert!(matches!(header.validate(), Err(HeaderError::InvalidVersion { have: 0 })));
+    }
+
+    #[test]
+    fn prefix_is_checked_before_dispatch() {
+        assert!(matches!(
+            decode_any(&fixture(1)[..HEADER_PREFIX_LEN - 1]),
+            Err(HeaderError::BufferTooShort { need: HEADER_PREFIX_LEN, .. })
+        ));
+
+        let mut bad_magic = fixture(2);
+        bad_magic[0..4].copy_from_slice(b"RSE9");
+        assert!(matches!(decode_any(&bad_magic), Err(HeaderError::InvalidMagic { .. })));
+    }
+
+    #[test]
+    fn stream_with_newer_header_is_rejected() {
+        let master_key = vec![0x11u8; 32];
+        let mut ciphertext = encrypt_stream_v2(
+            InputSource::Memory(vec![7u8; 1000]),
+            OutputSink::Memory,
+            &master_key,
+            EncryptParams::new(HeaderV1::test_header()),
+            ApiConfig::new(Some(true), None),
+        )
+        .unwrap()
+        .output
+        .unwrap();
+        patch_version(&mut ciphertext[..HeaderV1::LEN], 2);
+
+        let result = decrypt_stream_v2(
+            InputSource::Memory(ciphertext),
+            OutputSink::Memory,
+            &master_key,
+            DecryptParams::new(),
+            ApiConfig::default(),
+        );
+        let err = result.unwrap_err();
+        assert_eq!(err.offset(), Some(0));
+        assert!(matches!(err.root(), StreamError::Header(HeaderError::UnsupportedVersion { have: 2, .. })));
+    }
+
+    fn header_crc(buf: &[u8]) -> u32 {
+        u32::from_le_bytes(buf[32..36].try_into().unwrap())
+    }
+}
@@ -0,0 +1,97 @@
+// # 📂 `tests/test_inspect.rs`
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crypto_core::{
+        crypto::DigestAlg,
+        headers::HeaderV1,
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{ApiConfig, EncryptParams, encrypt_stream_v2},
+            io::{SegmentInfo, iter_segment_headers, scan_segment_index},
+            segmenting::{SegmentHeader, types::SegmentFlags},
+        },
+        types::StreamError,
+    };
+
+    const CHUNK: usize = 16 * 1024;
+
+    fn encrypt(data: &[u8]) -> Vec<u8> {
+        encrypt_stream_v2(
+            InputSource::Memory(data.to_vec()),
+            OutputSink::Memory,
+            &[0x11u8; 32],
+            EncryptParams::new(HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() }),
+            ApiConfig::new(Some(true), None),
+        )
+        .unwrap()
+        .output
+        .unwrap()
+    }
+
+    #[test]
+    fn lists_every_segment_without_the_key() {
+        let ciphertext = encrypt(&vec![0x5Au8; 3 * CHUNK]);
+
+        let mut iter = iter_segment_headers(ciphertext.as_slice());
+        let infos: Vec<SegmentInfo> = iter.by_ref().collect::<Result<_, _>>().unwrap();
+        assert_eq!(iter.header().unwrap().chunk_size, CHUNK as u32);
+
+        assert_eq!(infos.len(), 4);
+        let mut offset = HeaderV1::LEN as u64;
+        for (i, info) in infos.iter().enumerate() {
+            assert_eq!(info.segment_index, i as u32);
+            assert_eq!(info.offset, offset);
+            offset += info.encoded_len();
+        }
+        assert_eq!(offset, ciphertext.len() as u64);
+
+        for info in &infos[..3] {
+            assert!(!info.is_final());
+            assert!(info.wire_len > 0 && info.frame_count > 0);
+            assert_eq!(info.digest_alg, DigestAlg::Blake3 as u16);
+        }
+        let last = infos[3];
+        assert!(last.is_final());
+        assert_eq!((last.wire_len, last.frame_count), (0, 0));
+
+        // Same layout as the seek-based scan
+        let scanned = scan_segment_index(&mut Cursor::new(&ciphertext)).unwrap();
+        for (info, entry) in infos.iter().zip(scanned.entries()) {
+            assert_eq!((info.offset, info.wire_len, info.flags), (entry.offset, entry.wire_len, entry.flags));
+        }
+    }
+
+    #[test]
+    fn truncated_stream_ends_with_an_error() {
+        let ciphertext = encrypt(&vec![1u8; 2 * CHUNK]);
+
+        let cut = &ciphertext[..ciphertext.len() - SegmentHeader::LEN - 10];
+        let items: Vec<_> = iter_segment_headers(cut).collect();
+        assert_eq!(items.len(), 2);
+        assert!(items[0].is_ok());
+        assert!(matches!(items[1], Err(StreamError::Segment(_))));
+
+        let no_final = &ciphertext[..ciphertext.len() - SegmentHeader::LEN];
+        let items: Vec<_> = iter_segment_headers(no_final).collect();
+        assert!(matches!(items.last(), Some(Err(StreamError::Validation(msg))) if msg.contains("final")));
+
+        let items: Vec<_> = iter_segment_headers(&ciphertext[..10]).collect();
+        assert!(matches!(items.as_slice(), [Err(_)]));
+    }
+
+    #[test]
+    fn flags_and_headers_display() {
+        assert_eq!(SegmentFlags::empty().to_string(), "-");
+        assert_eq!(SegmentFlags::FINAL_SEGMENT.to_string(), "FINAL_SEGMENT");
+        assert_eq!((SegmentFlags::COMPRESSED | SegmentFlags::CHECKSUM).to_string(), "COMPRESSED | CHECKSUM");
+
+        let wire = bytes::Bytes::from_static(b"frames");
+        let header = SegmentHeader::new(&wire, 7, 4, 3, 0x0002, SegmentFlags::UNCOMPRESSED);
+        let text = header.to_string();
+        assert!(text.starts_with("segment 7: 4 bytes, 6 wire bytes, 3 frames"), "{text}");
+        assert!(text.ends_with("flags UNCOMPRESSED"), "{text}");
+    }
+}
@@ -1,7 +1,18 @@
 #[cfg(test)]
 mod tests {
     use proptest::prelude::*;
-    use crypto_core::{constants::prf_ids, crypto::derive_session_key_32, headers::types::HeaderV1};
+    use crypto_core::{
+        constants::prf_ids,
+        crypto::{CryptoError, derive_session_key_32, derive_session_key_32_with},
+        headers::{HkdfPrf, decode_header_le, encode_header_le, types::HeaderV1},
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{ApiConfig, DecryptParams, EncryptParams, decrypt_stream_v2, encrypt_stream_v2},
+        },
+        types::StreamError,
+    };
+
+    const ALL_PRFS: [HkdfPrf; 5] = [HkdfPrf::Sha256, HkdfPrf::Sha512, HkdfPrf::Sha3_256, HkdfPrf::Sha3_512, HkdfPrf::Blake3K];
 
     fn dummy_header(prf: u16, salt: [u8; 16]) -> HeaderV1 { 
         let mut header = HeaderV1::test_header();
@@ -52,6 +63,75 @@ mod tests {
         assert_ne!(k1, k2);
     }
 
+    #[test]
+    fn every_prf_gives_a_distinct_key() {
+        let master = [0x11u8; 32];
+        let keys: Vec<[u8; 32]> = ALL_PRFS
+            .iter()
+            .map(|&prf| *derive_session_key_32(&master, &dummy_header(prf as u16, [7; 16])).unwrap())
+            .collect();
+        for i in 0..keys.len() {
+            for j in i + 1..keys.len() {
+                assert_ne!(keys[i], keys[j], "{:?} vs {:?}", ALL_PRFS[i], ALL_PRFS[j]);
+            }
+        }
+    }
+
+    #[test]
+    fn explicit_prf_must_match_the_header() {
+        let master = [0x11u8; 32];
+        let header = dummy_header(prf_ids::SHA512, [7; 16]);
+        let k1 = derive_session_key_32_with(HkdfPrf::Sha512, &master, &header).unwrap();
+        assert_eq!(*k1, *derive_session_key_32(&master, &header).unwrap());
+
+        match derive_session_key_32_with(HkdfPrf::Sha256, &master, &header) {
+            Err(CryptoError::PrfMismatch { requested, header }) => {
+                assert_eq!((requested, header), (prf_ids::SHA256, prf_ids::SHA512));
+            }
+            other => panic!("expected PrfMismatch, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn unknown_prf_is_rejected() {
+        let header = dummy_header(0x00FF, [7; 16]);
+        assert!(matches!(derive_session_key_32(b"masterkey", &header), Err(CryptoError::UnsupportedPrf { prf_id: 0x00FF })));
+    }
+
+    #[test]
+    fn altered_prf_field_fails_authentication() {
+        let master = [0x11u8; 32];
+        let data = b"prf bound stream ".repeat(4096);
+        let ciphertext = encrypt_stream_v2(
+            InputSource::Memory(data.clone()),
+            OutputSink::Memory,
+            &master,
+            EncryptParams::new(HeaderV1::test_header()),
+            ApiConfig::new(Some(true), None),
+        )
+        .unwrap()
+        .output
+        .unwrap();
+
+        for prf in [HkdfPrf::Sha512, HkdfPrf::Sha3_256, HkdfPrf::Blake3K] {
+            // Re-encode the header with another PRF: a well-formed header, valid CRC
+            let mut header = decode_header_le(&ciphertext[..HeaderV1::LEN]).unwrap();
+            header.hkdf_prf = prf as u16;
+            let mut tampered = ciphertext.clone();
+            tampered[..HeaderV1::LEN].copy_from_slice(&encode_header_le(&header).unwrap());
+
+            let result = decrypt_stream_v2(
+                InputSource::Memory(tampered),
+                OutputSink::Memory,
+                &master,
+                DecryptParams::new(),
+                ApiConfig::new(Some(true), None),
+            );
+            // The PRF id is part of the HKDF info: the key check sees another session key
+            assert!(matches!(result, Err(StreamError::Crypto(CryptoError::KeyConfirmationFailed))), "{prf:?}: {result:?}");
+        }
+    }
+
     // Property-based fuzzing: arbitrary salts produce deterministic keys
     proptest! {
         #[test]
@@ -0,0 +1,120 @@
+// # 📂 `tests/test_key_confirmation.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crypto_core::{
+        constants::flags,
+        crypto::{CryptoError, DigestAlg, KEY_CHECK_LEN, derive_key_check, derive_session_key_32},
+        headers::{HeaderError, HeaderV1, decode_header_le},
+        stream_v2::{
+            InputSource,
+            core::{DecryptParams, EncryptParams},
+            parallelism::HybridParallelismProfile,
+            segment_worker::{DecryptContext, SegmentWorkerError},
+            verify_stream_v2,
+        },
+        types::StreamError,
+    };
+
+    use crate::common::{self, master_key};
+
+    const CHUNK: usize = 16 * 1024;
+
+    fn wrong_key() -> Vec<u8> {
+        vec![0x22u8; 32]
+    }
+
+    fn header() -> HeaderV1 {
+        HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() }
+    }
+
+    fn profile() -> HybridParallelismProfile {
+        HybridParallelismProfile::dynamic(CHUNK as u32, 0.50, 64)
+    }
+
+    fn plaintext() -> Vec<u8> {
+        common::plaintext(4 * CHUNK + 123)
+    }
+
+    fn encrypt() -> Vec<u8> {
+        common::encrypt(&plaintext(), EncryptParams::new(header()))
+    }
+
+    fn decrypt(ciphertext: Vec<u8>, key: &[u8]) -> Result<Vec<u8>, StreamError> {
+        common::try_decrypt(ciphertext, key, DecryptParams::new(), common::config())
+    }
+
+    #[test]
+    fn encrypted_header_carries_the_key_check() {
+        let ciphertext = encrypt();
+        let written = decode_header_le(&ciphertext[..HeaderV1::LEN]).unwrap();
+        assert_ne!(written.flags & flags::HAS_KEY_CHECK, 0);
+
+        let session_key = derive_session_key_32(&master_key(), &written).unwrap();
+        assert_eq!(written.key_check(), Some(derive_key_check(session_key.as_slice()).unwrap()));
+        assert!(written.reserved[KEY_CHECK_LEN..].iter().all(|&b| b == 0));
+
+        assert_eq!(decrypt(ciphertext, &master_key()).unwrap(), plaintext());
+    }
+
+    #[test]
+    fn wrong_key_fails_before_any_segment() {
+        // Header only: the right key would fail on the missing segments,
+        // so a key confirmation error proves no segment was read
+        let header_only = encrypt()[..HeaderV1::LEN].to_vec();
+        assert!(matches!(
+            decrypt(header_only.clone(), &wrong_key()),
+            Err(StreamError::Crypto(CryptoError::KeyConfirmationFailed))
+        ));
+        assert!(!matches!(
+            decrypt(header_only, &master_key()),
+            Err(StreamError::Crypto(CryptoError::KeyConfirmationFailed))
+        ));
+
+        assert!(matches!(
+            verify_stream_v2(InputSource::Memory(encrypt()), &wrong_key()),
+            Err(StreamError::Crypto(CryptoError::KeyConfirmationFailed))
+        ));
+    }
+
+    #[test]
+    fn headers_without_the_flag_skip_the_check() {
+        let wrong = derive_session_key_32(&wrong_key(), &header()).unwrap();
+        assert!(DecryptContext::from_stream_header(header(), profile(), wrong.as_slice(), DigestAlg::Blake3).is_ok());
+
+        let right = derive_session_key_32(&master_key(), &header()).unwrap();
+        let mut checked = header();
+        checked.set_key_check(derive_key_check(right.as_slice()).unwrap());
+        assert!(DecryptContext::from_stream_header(checked, profile(), right.as_slice(), DigestAlg::Blake3).is_ok());
+        assert!(matches!(
+            DecryptContext::from_stream_header(checked, profile(), wrong.as_slice(), DigestAlg::Blake3),
+            Err(SegmentWorkerError::CryptoError(CryptoError::KeyConfirmationFailed))
+        ));
+    }
+
+    #[test]
+    fn key_check_depends_on_the_session_key() {
+        let a = derive_key_check(&[0x01u8; 32]).unwrap();
+        let b = derive_key_check(&[0x02u8; 32]).unwrap();
+        assert_ne!(a, b);
+        assert_eq!(a, derive_key_check(&[0x01u8; 32]).unwrap());
+        assert!(matches!(derive_key_check(&[0u8; 16]), Err(CryptoError::InvalidKeyLen { .. })));
+    }
+
+    #[test]
+    fn only_the_key_check_bytes_may_be_set() {
+        let mut h = header();
+        h.reserved[0] = 1;
+        assert!(matches!(h.validate(), Err(HeaderError::ReservedBytesNonZero { .. })));
+
+        h.flags |= flags::HAS_KEY_CHECK;
+        assert!(h.validate().is_ok());
+
+        // Bytes past the key check value are the extension fields (ext_flags, ext_len)
+        h.reserved[KEY_CHECK_LEN] = 1;
+        assert_eq!(h.ext_flags(), 1);
+        assert!(h.validate().is_ok());
+    }
+}
@@ -0,0 +1,110 @@
+// # 📂 `tests/test_keyring.rs`
+
+#[cfg(test)]
+mod tests {
+    use crypto_core::{
+        crypto::KeyRing,
+        headers::{HeaderV1, decode_header_le},
+        stream_v2::{
+            InputSource, OutputSink, decrypt_stream_v2_with_keyring,
+            core::{ApiConfig, DecryptParams, EncryptParams, encrypt_stream_v2},
+        },
+        types::StreamError,
+    };
+
+    fn key(id: u32) -> Vec<u8> {
+        vec![id as u8 ^ 0xA5; 32]
+    }
+
+    fn plaintext() -> Vec<u8> {
+        (0..40_000).map(|i| (i % 233) as u8).collect()
+    }
+
+    fn encrypt_with_key_id(key_id: u32) -> Vec<u8> {
+        let header = HeaderV1 { chunk_size: 16 * 1024, ..HeaderV1::test_header() };
+        encrypt_stream_v2(
+            InputSource::Memory(plaintext()),
+            OutputSink::Memory,
+            &key(key_id),
+            EncryptParams::new(header).with_key_id(key_id),
+            ApiConfig::new(Some(true), None),
+        )
+        .expect("encryption should succeed")
+        .output
+        .unwrap()
+    }
+
+    fn decrypt(ciphertext: Vec<u8>, keyring: &KeyRing) -> Result<Vec<u8>, StreamError> {
+        decrypt_stream_v2_with_keyring(
+            InputSource::Memory(ciphertext),
+            OutputSink::Memory,
+            keyring,
+            DecryptParams::new(),
+            ApiConfig::new(Some(true), None),
+        )
+        .map(|s| s.output.unwrap_or_default())
+    }
+
+    fn ring(ids: &[u32]) -> KeyRing {
+        let mut keyring = KeyRing::new();
+        for &id in ids {
+            keyring.insert(id, &key(id)).unwrap();
+        }
+        keyring
+    }
+
+    #[test]
+    fn key_id_is_written_to_header() {
+        let ciphertext = encrypt_with_key_id(7);
+        assert_eq!(decode_header_le(&ciphertext[..HeaderV1::LEN]).unwrap().key_id, 7);
+    }
+
+    #[test]
+    fn selects_key_by_header_key_id() {
+        let ciphertext = encrypt_with_key_id(7);
+        let out = decrypt(ciphertext, &ring(&[3, 7, 9])).expect("key 7 is in the ring");
+        assert_eq!(out, plaintext());
+    }
+
+    #[test]
+    fn missing_key_id_is_unknown_key_id() {
+        let ciphertext = encrypt_with_key_id(7);
+        match decrypt(ciphertext, &ring(&[3, 9])) {
+            Err(StreamError::UnknownKeyId { key_id }) => assert_eq!(key_id, 7),
+            Err(e) => panic!("unexpected error: {e}"),
+            Ok(_) => panic!("decryption must fail without key 7"),
+        }
+    }
+
+    #[test]
+    fn default_key_is_used_as_fallback() {
+        let ciphertext = encrypt_with_key_id(7);
+        let mut keyring = ring(&[3]);
+        keyring.set_default(&key(7)).unwrap();
+        assert_eq!(decrypt(ciphertext, &keyring).unwrap(), plaintext());
+    }
+
+    #[test]
+    fn wrong_key_under_matching_id_fails() {
+        let ciphertext = encrypt_with_key_id(7);
+        let mut keyring = KeyRing::new();
+        keyring.insert(7, &key(8)).unwrap();
+        assert!(decrypt(ciphertext, &keyring).is_err());
+    }
+
+    #[test]
+    fn insert_rejects_invalid_key_length() {
+        let mut keyring = KeyRing::new();
+        assert!(keyring.insert(1, &[0u8; 31]).is_err());
+        assert!(keyring.set_default(&[0u8; 7]).is_err());
+        assert!(keyring.is_empty());
+    }
+
+    #[test]
+    fn debug_does_not_leak_key_bytes() {
+        let keyring = ring(&[9, 3]);
+        let dbg = format!("{keyring:?}");
+        assert!(dbg.contains("[3, 9]"));
+        assert!(!dbg.contains(&format!("{}", key(3)[0])));
+    }
+}
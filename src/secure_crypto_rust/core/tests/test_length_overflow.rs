@@ -0,0 +1,117 @@
+// # 📂 `tests/test_length_overflow.rs`
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use crypto_core::stream_v2::{
+        framing::{self, FrameError, FrameHeader, FrameType, decode::parse_frame_header, encode::encode_frame},
+        segmenting::{self, SegmentHeader, SegmentLimits, encode_segment, types::{SegmentError, SegmentFlags}},
+    };
+
+    /// Frame header bytes declaring `ciphertext_len`.
+    fn frame_header_bytes(ciphertext_len: u32) -> Vec<u8> {
+        let header = FrameHeader {
+            segment_index: 0,
+            frame_index: 0,
+            frame_type: FrameType::Data,
+            plaintext_len: 1,
+            ciphertext_len: 0,
+        };
+        let mut bytes = encode_frame(&header, &[]).unwrap();
+        bytes[FrameHeader::LEN - 4..].copy_from_slice(&ciphertext_len.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn u32_conversions_at_the_boundaries() {
+        for len in [0usize, 1, u16::MAX as usize, u32::MAX as usize - 1, u32::MAX as usize] {
+            assert_eq!(segmenting::types::len_to_u32("len", len).unwrap() as usize, len);
+            assert_eq!(framing::types::len_to_u32("len", len).unwrap() as usize, len);
+        }
+        for value in [0u32, 1, u32::MAX - 1, u32::MAX] {
+            // usize is at least 32 bits on every supported target
+            assert_eq!(segmenting::types::u32_to_len("v", value).unwrap() as u64, value as u64);
+            assert_eq!(framing::types::u32_to_len("v", value).unwrap() as u64, value as u64);
+        }
+    }
+
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn lengths_beyond_u32_are_rejected() {
+        for len in [u32::MAX as usize + 1, usize::MAX] {
+            assert!(matches!(
+                segmenting::types::len_to_u32("wire_len", len),
+                Err(SegmentError::LengthOverflow { field: "wire_len", value }) if value == len as u64
+            ));
+            assert!(matches!(
+                framing::types::len_to_u32("ciphertext_len", len),
+                Err(FrameError::LengthOverflow { field: "ciphertext_len", .. })
+            ));
+        }
+    }
+
+    #[cfg(target_pointer_width = "32")]
+    #[test]
+    fn header_lengths_near_u32_max_overflow_usize() {
+        let header = FrameHeader { ciphertext_len: u32::MAX, ..FrameHeader::zero() };
+        assert!(matches!(header.total_len(), Err(FrameError::LengthOverflow { .. })));
+
+        let segment = SegmentHeader { wire_len: u32::MAX, ..SegmentHeader::new(&Bytes::new(), 0, 0, 0, 0, SegmentFlags::empty()) };
+        assert!(matches!(segment.total_len(), Err(SegmentError::LengthOverflow { .. })));
+    }
+
+    #[test]
+    fn additions_never_wrap() {
+        assert_eq!(segmenting::types::checked_len_add("end", usize::MAX - 1, 1).unwrap(), usize::MAX);
+        assert!(matches!(
+            segmenting::types::checked_len_add("end", usize::MAX, 1),
+            Err(SegmentError::LengthOverflow { field: "end", .. })
+        ));
+        assert!(matches!(
+            framing::types::checked_len_add("frame end offset", usize::MAX - 5, FrameHeader::LEN),
+            Err(FrameError::LengthOverflow { field: "frame end offset", .. })
+        ));
+    }
+
+    #[test]
+    fn frame_lengths_straddling_u32_max() {
+        for ciphertext_len in [u32::MAX - 1, u32::MAX] {
+            assert!(matches!(
+                parse_frame_header(&frame_header_bytes(ciphertext_len)),
+                Err(FrameError::CiphertextTooLarge { have, .. }) if have == ciphertext_len
+            ));
+        }
+
+        let header = FrameHeader { ciphertext_len: u32::MAX, ..FrameHeader::zero() };
+        if let Ok(total) = header.total_len() {
+            assert_eq!(total as u64, FrameHeader::LEN as u64 + u32::MAX as u64);
+        }
+        assert!(encode_frame(&header, &[0u8; 4]).is_err());
+    }
+
+    #[test]
+    fn segment_lengths_straddling_u32_max() {
+        let base = SegmentHeader::new(&Bytes::new(), 0, 0, 0, 0, SegmentFlags::empty());
+        let limits = SegmentLimits::default();
+
+        for wire_len in [u32::MAX - 1, u32::MAX] {
+            let header = SegmentHeader { wire_len, frame_count: 1, ..base };
+            assert!(matches!(limits.check(&header), Err(SegmentError::WireLenTooLarge { .. })));
+            assert!(encode_segment(&header, &Bytes::from_static(b"short")).is_err());
+        }
+        for bytes_len in [u32::MAX - 1, u32::MAX] {
+            let header = SegmentHeader { bytes_len, ..base };
+            assert!(matches!(limits.check(&header), Err(SegmentError::BytesLenTooLarge { .. })));
+        }
+        let header = SegmentHeader { frame_count: u32::MAX, wire_len: 64, ..base };
+        assert!(matches!(limits.check(&header), Err(SegmentError::FrameCountMismatch { .. })));
+    }
+
+    #[test]
+    fn checked_segment_header_construction() {
+        let wire = Bytes::from_static(b"frames");
+        let header = SegmentHeader::try_new(&wire, 3, 6, 1, 0, SegmentFlags::empty()).unwrap();
+        assert_eq!(header, SegmentHeader::new(&wire, 3, 6, 1, 0, SegmentFlags::empty()));
+        assert_eq!(header.total_len().unwrap(), SegmentHeader::LEN + wire.len());
+    }
+}
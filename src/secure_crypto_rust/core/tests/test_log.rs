@@ -1,13 +1,78 @@
 #[cfg(test)]
 mod tests {
-    use crypto_core::recovery::{UnifiedEntry, compact_unified_log};
+    use std::collections::BTreeSet;
+    use std::fs;
+    use std::io::{self, Write};
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, Instant};
+
+    use crypto_core::{
+        headers::HeaderV1,
+        recovery::{AsyncLogManager, Direction, FrameRecord, LogConfig, UnifiedEntry, compact_unified_log},
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{ApiConfig, DecryptParams, EncryptParams, decrypt_stream_v2, encrypt_stream_v2},
+            framing::FrameType,
+        },
+    };
+
+    fn frame(direction: Direction, frame_index: u32) -> UnifiedEntry {
+        UnifiedEntry::Frame(FrameRecord {
+            direction,
+            segment_index: 0,
+            frame_index,
+            frame_type: FrameType::Data,
+            wire_len: 64,
+            wire_crc32: 0xDEAD_BEEF,
+            timestamp_ns: 42,
+            payload: None,
+        })
+    }
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("crypto_core_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn file_names(dir: &Path) -> BTreeSet<String> {
+        fs::read_dir(dir)
+            .map(|entries| entries.flatten().map(|e| e.file_name().to_string_lossy().into_owned()).collect())
+            .unwrap_or_default()
+    }
+
+    fn roundtrip(config: ApiConfig) {
+        let master_key = vec![0x11u8; 32];
+        let data = vec![0x5Au8; 10_000];
+        let ciphertext = encrypt_stream_v2(
+            InputSource::Memory(data.clone()),
+            OutputSink::Memory,
+            &master_key,
+            EncryptParams::new(HeaderV1::test_header()),
+            ApiConfig { with_buf: Some(true), ..config.clone() },
+        )
+        .unwrap()
+        .output
+        .unwrap();
+        let plaintext = decrypt_stream_v2(
+            InputSource::Memory(ciphertext),
+            OutputSink::Memory,
+            &master_key,
+            DecryptParams::new(),
+            ApiConfig { with_buf: Some(true), ..config },
+        )
+        .unwrap()
+        .output
+        .unwrap();
+        assert_eq!(plaintext, data);
+    }
 
 
     // #[test]
     // fn test_append_and_replay() {
     //     let mut log = LogManager::new("test_audit.log", 10);
     //     log.append(UnifiedEntry::Scheduler("cycle-start".into())).expect("REASON");
-    //     log.append(UnifiedEntry::Encrypt(vec![1,2,3]));
+    //     log.append(frame(Direction::Encrypt, 0));
     //     assert_eq!(log.replay().len(), 2);
     // }
 
@@ -16,9 +81,144 @@ mod tests {
         let mut entries = vec![
             UnifiedEntry::Scheduler("cycle".into()),
             UnifiedEntry::Scheduler("cycle".into()),
-            UnifiedEntry::Encrypt(vec![1]),
+            frame(Direction::Encrypt, 0),
         ];
         compact_unified_log(&mut entries);
         assert_eq!(entries.len(), 2);
     }
+
+    #[test]
+    fn compaction_drops_repeated_frame_records() {
+        let retried = match frame(Direction::Encrypt, 1) {
+            UnifiedEntry::Frame(record) => UnifiedEntry::Frame(FrameRecord { timestamp_ns: 99, ..record }),
+            other => other,
+        };
+        let mut entries = vec![
+            frame(Direction::Encrypt, 0),
+            frame(Direction::Encrypt, 1),
+            retried,
+            frame(Direction::Decrypt, 1),
+        ];
+        compact_unified_log(&mut entries);
+        assert_eq!(entries, [frame(Direction::Encrypt, 0), frame(Direction::Encrypt, 1), frame(Direction::Decrypt, 1)]);
+    }
+
+    #[test]
+    fn disabled_log_creates_no_files() {
+        let cwd = std::env::current_dir().unwrap();
+        let before = file_names(&cwd);
+
+        let log = AsyncLogManager::disabled();
+        assert!(!log.is_enabled());
+        log.append(UnifiedEntry::Scheduler("dropped".into())).unwrap();
+        assert!(log.entries().is_none());
+
+        roundtrip(ApiConfig::default());
+        assert_eq!(ApiConfig::default().log, LogConfig::Disabled);
+        assert_eq!(file_names(&cwd), before);
+    }
+
+    #[test]
+    fn custom_directory_is_honored() {
+        let dir = scratch_dir("log_dir");
+        roundtrip(ApiConfig::default().with_log(LogConfig::Directory(dir.clone())));
+
+        let names = file_names(&dir);
+        assert!(names.contains("stream_v2_enc.log"), "{names:?}");
+        assert!(names.contains("stream_v2_dec.log"), "{names:?}");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn explicit_path_is_honored() {
+        let dir = scratch_dir("log_path");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("audit.log");
+
+        let log = AsyncLogManager::from_config(&LogConfig::Path(path.clone()), "unused.log", 100).unwrap();
+        assert!(log.is_enabled());
+        assert!(path.exists());
+        assert_eq!(file_names(&dir), BTreeSet::from(["audit.log".to_string()]));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn unwritable_path_fails_at_construction() {
+        let dir = scratch_dir("log_missing");
+        assert!(AsyncLogManager::with_path(dir.join("no/such/dir.log"), 100).is_err());
+    }
+
+    #[test]
+    fn memory_log_keeps_entries() {
+        let log = AsyncLogManager::memory();
+        log.append(UnifiedEntry::Scheduler("resume".into())).unwrap();
+        log.append(frame(Direction::Encrypt, 3)).unwrap();
+
+        let entries = log.entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(matches!(&entries[0], UnifiedEntry::Scheduler(msg) if msg == "resume"));
+        assert_eq!(entries[1], frame(Direction::Encrypt, 3));
+
+        roundtrip(ApiConfig::default().with_log(LogConfig::Memory));
+    }
+
+    /// Writer that accepts nothing, like a full disk.
+    struct FullDisk;
+
+    impl Write for FullDisk {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("no space left on device"))
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn shutdown_flushes_entries_to_file() {
+        let dir = scratch_dir("log_shutdown");
+        let log = AsyncLogManager::in_dir(&dir, "audit.log", 100).unwrap();
+        log.append(UnifiedEntry::Scheduler("first".into())).unwrap();
+        log.append(frame(Direction::Decrypt, 9)).unwrap();
+        log.shutdown().unwrap();
+
+        let contents = fs::read_to_string(dir.join("audit.log")).unwrap();
+        let lines: Vec<_> = contents.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], "SCHEDULER: first");
+        assert!(lines[1].starts_with("FRAME: DEC|0|9|1|64|deadbeef|42|"), "{}", lines[1]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn write_failure_surfaces_on_shutdown() {
+        let log = AsyncLogManager::with_writer(FullDisk);
+        log.append(UnifiedEntry::Scheduler("lost".into())).unwrap();
+
+        let err = log.shutdown().unwrap_err();
+        assert!(err.to_string().contains("no space left"), "{err}");
+    }
+
+    #[test]
+    fn append_fails_after_writer_error() {
+        let log = AsyncLogManager::with_writer(FullDisk);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while log.append(UnifiedEntry::Scheduler("retry".into())).is_ok() {
+            assert!(Instant::now() < deadline, "append kept succeeding after a write error");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(log.close().is_err());
+
+        // Closed: further appends fail and a second close has nothing to report
+        assert!(log.append(UnifiedEntry::Scheduler("late".into())).is_err());
+        assert!(log.close().is_ok());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn full_device_error_is_not_swallowed() {
+        let log = AsyncLogManager::with_path("/dev/full", 100).unwrap();
+        log.append(frame(Direction::Encrypt, 0)).unwrap();
+        assert!(log.shutdown().is_err());
+    }
 }
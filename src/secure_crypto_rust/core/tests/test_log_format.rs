@@ -0,0 +1,140 @@
+// # 📂 `tests/test_log_format.rs`
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use crypto_core::{
+        headers::HeaderV1,
+        recovery::{
+            Direction, FrameRecord, LogConfig, LogDetail, LogFormat, LogOptions, UnifiedEntry, bootstrap_from_log,
+            read_log_entries,
+        },
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{ApiConfig, EncryptParams, encrypt_stream_v2},
+            framing::FrameType,
+        },
+    };
+
+    const MIB: usize = 1024 * 1024;
+
+    /// Incompressible bytes (xorshift64), so every segment keeps all its frames.
+    fn plaintext() -> Vec<u8> {
+        let mut state = 0x9E37_79B9_7F4A_7C15u64;
+        (0..MIB)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                (state >> 24) as u8
+            })
+            .collect()
+    }
+
+    fn log_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rse1-log-format-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    /// Encrypt 1 MiB with the audit log at `path`; returns the stream.
+    fn encrypt_logged(path: &Path, format: LogFormat, detail: LogDetail) -> Vec<u8> {
+        let config = ApiConfig::new(Some(true), None)
+            .with_log(LogConfig::Path(path.to_path_buf()))
+            .with_log_options(LogOptions { format, detail });
+        encrypt_stream_v2(
+            InputSource::Memory(plaintext()),
+            OutputSink::Memory,
+            &[0x11u8; 32],
+            EncryptParams::new(HeaderV1::test_header()),
+            config,
+        )
+        .unwrap()
+        .output
+        .unwrap()
+    }
+
+    fn frames(path: &Path) -> Vec<FrameRecord> {
+        read_log_entries(path.to_str().unwrap())
+            .unwrap()
+            .map(|entry| match entry.unwrap() {
+                UnifiedEntry::Frame(record) => record,
+                other => panic!("unexpected entry {other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn metadata_log_stays_small() {
+        let text = log_path("meta.log");
+        let binary = log_path("meta.bin");
+        let full = log_path("full.log");
+        encrypt_logged(&text, LogFormat::Text, LogDetail::Metadata);
+        encrypt_logged(&binary, LogFormat::Binary, LogDetail::Metadata);
+        encrypt_logged(&full, LogFormat::Text, LogDetail::FullPayload);
+
+        let size = |p: &Path| fs::metadata(p).unwrap().len();
+        assert!(size(&binary) < 8 * 1024, "binary metadata log is {} bytes", size(&binary));
+        assert!(size(&text) < 12 * 1024, "text metadata log is {} bytes", size(&text));
+        assert!(size(&binary) < size(&text));
+        // Base64 payloads: more than the stream itself
+        assert!(size(&full) > MIB as u64, "full payload log is {} bytes", size(&full));
+    }
+
+    #[test]
+    fn both_formats_read_back_the_same_frames() {
+        let text = log_path("same.log");
+        let binary = log_path("same.bin");
+        let stream = encrypt_logged(&text, LogFormat::Text, LogDetail::Metadata);
+        encrypt_logged(&binary, LogFormat::Binary, LogDetail::Metadata);
+
+        // Workers finish out of order and time differs: compare sorted, without timestamps
+        let key = |r: &FrameRecord| (r.segment_index, r.frame_index, r.frame_type as u16, r.wire_len, r.wire_crc32);
+        let sorted = |path: &Path| {
+            let mut keys: Vec<_> = frames(path).iter().map(key).collect();
+            keys.sort();
+            keys
+        };
+        let text_frames = frames(&text);
+        assert!(text_frames.iter().all(|r| r.direction == Direction::Encrypt && r.payload.is_none()));
+        assert_eq!(sorted(&text), sorted(&binary));
+
+        // Frame bytes of all segments, stream header and segment headers aside
+        let logged: u64 = text_frames.iter().map(|r| u64::from(r.wire_len)).sum();
+        assert!(logged < (stream.len() - HeaderV1::LEN) as u64);
+        assert!(text_frames.iter().filter(|r| r.frame_type == FrameType::Terminator).count() >= 16);
+    }
+
+    #[test]
+    fn full_payload_matches_the_recorded_crc() {
+        let path = log_path("payload.bin");
+        encrypt_logged(&path, LogFormat::Binary, LogDetail::FullPayload);
+
+        for record in frames(&path) {
+            let payload = record.payload.as_ref().expect("payload kept");
+            assert_eq!(payload.len() as u32, record.wire_len);
+            assert_eq!(crc32fast::hash(payload), record.wire_crc32);
+        }
+    }
+
+    #[test]
+    fn bootstrap_reads_a_torn_binary_log() {
+        let path = log_path("torn.bin");
+        encrypt_logged(&path, LogFormat::Binary, LogDetail::Metadata);
+        let complete = bootstrap_from_log(path.to_str().unwrap()).unwrap();
+        assert_eq!(complete.direction, Some(Direction::Encrypt));
+        assert!(complete.next_segment_index >= 16);
+
+        // Cut the last record short
+        let mut bytes = fs::read(&path).unwrap();
+        bytes.truncate(bytes.len() - 3);
+        fs::write(&path, &bytes).unwrap();
+        let torn = bootstrap_from_log(path.to_str().unwrap()).unwrap();
+        assert!(torn.bytes_processed < complete.bytes_processed);
+        assert!(torn.next_segment_index <= complete.next_segment_index);
+    }
+}
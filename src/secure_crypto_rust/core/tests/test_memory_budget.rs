@@ -0,0 +1,51 @@
+// # 📂 `tests/test_memory_budget.rs`
+
+#[cfg(test)]
+mod tests {
+    use crypto_core::stream_v2::parallelism::{HybridParallelismProfile, MemoryBudget, inflight_for_budget};
+
+    const KIB: u64 = 1024;
+    const MIB: u64 = 1024 * KIB;
+    const GIB: u64 = 1024 * MIB;
+
+    #[test]
+    fn representative_machines() {
+        let cases: [(&str, u64, u32, usize); 6] = [
+            // (machine, available bytes, segment size, expected in-flight)
+            ("workstation, 16 GiB free, 32 MiB segments", 16 * GIB, 32 * MIB as u32, 64),
+            ("container, 512 MiB limit, 32 MiB segments", 512 * MIB, 32 * MIB as u32, 8),
+            ("container, 512 MiB limit, 4 MiB segments", 512 * MIB, 4 * MIB as u32, 64),
+            ("small VM, 96 MiB free, 8 MiB segments", 96 * MIB, 8 * MIB as u32, 6),
+            ("embedded, 100 MiB free, 64 KiB segments", 100 * MIB, 64 * KIB as u32, 64),
+            ("starved, 16 MiB free, 32 MiB segments", 16 * MIB, 32 * MIB as u32, 1),
+        ];
+        for (machine, available, segment, expected) in cases {
+            assert_eq!(inflight_for_budget(available, segment, 0.50, 64), expected, "{machine}");
+        }
+    }
+
+    #[test]
+    fn budget_is_not_truncated_to_u32() {
+        // 64 GiB * 0.5 does not fit a u32; 32 GiB / 32 MiB = 1024 segments
+        assert_eq!(inflight_for_budget(64 * GIB, 32 * MIB as u32, 0.50, usize::MAX), 1024);
+    }
+
+    #[test]
+    fn degenerate_inputs_are_clamped() {
+        assert_eq!(inflight_for_budget(GIB, 0, 0.50, 64), 64);
+        assert_eq!(inflight_for_budget(0, 64 * KIB as u32, 0.50, 64), 1);
+        assert_eq!(inflight_for_budget(512 * MIB, 32 * MIB as u32, 4.0, 64), 16);
+        assert_eq!(inflight_for_budget(512 * MIB, 32 * MIB as u32, -1.0, 64), 1);
+    }
+
+    #[test]
+    fn explicit_budget_overrides_detection() {
+        assert_eq!(MemoryBudget::Bytes(123).available_bytes(), 123);
+
+        let profile = HybridParallelismProfile::dynamic_with_budget(32 * MIB as u32, MemoryBudget::Bytes(512 * MIB), 0.50, 64);
+        assert_eq!(profile.inflight_segments(), 8);
+
+        let profile = HybridParallelismProfile::dynamic_with_budget(0, MemoryBudget::Bytes(GIB), 0.50, 64);
+        assert_eq!(profile.inflight_segments(), 64);
+    }
+}
@@ -0,0 +1,86 @@
+// # 📂 `tests/test_nonce_budget.rs`
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use bytes::Bytes;
+    use crypto_core::{
+        crypto::{
+            CryptoError, MAX_FRAMES_PER_SEGMENT, MAX_SEGMENTS_PER_STREAM, TAG_LEN, check_frame_budget,
+            check_segment_budget, derive_nonce_12_tls_style, frame_nonce_counter,
+        },
+        headers::HeaderV1,
+        stream_v2::{
+            frame_worker::{FrameInput, encrypt::EncryptFrameWorker},
+            framing::FrameType,
+            segmenting::types::SegmentFlags,
+        },
+    };
+
+    #[test]
+    fn segment_budget_boundary() {
+        check_segment_budget(0).unwrap();
+        check_segment_budget(MAX_SEGMENTS_PER_STREAM - 1).unwrap();
+
+        for index in [MAX_SEGMENTS_PER_STREAM, MAX_SEGMENTS_PER_STREAM + 1, u32::MAX] {
+            match check_segment_budget(index) {
+                Err(CryptoError::NonceSpaceExhausted { counter: "segment", requested, limit }) => {
+                    assert_eq!(requested, u64::from(index) + 1);
+                    assert_eq!(limit, u64::from(MAX_SEGMENTS_PER_STREAM));
+                }
+                other => panic!("segment {index}: expected NonceSpaceExhausted, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn frame_budget_boundary() {
+        check_frame_budget(1).unwrap();
+        check_frame_budget(u64::from(MAX_FRAMES_PER_SEGMENT)).unwrap();
+
+        let err = check_frame_budget(u64::from(MAX_FRAMES_PER_SEGMENT) + 1).unwrap_err();
+        assert!(matches!(err, CryptoError::NonceSpaceExhausted { counter: "frame", .. }));
+        assert!(err.to_string().contains("nonce space exhausted"), "{err}");
+    }
+
+    #[test]
+    fn limits_stay_clear_of_wraparound() {
+        // The last usable indices still fit their u32 half with room to spare
+        let last = frame_nonce_counter(MAX_SEGMENTS_PER_STREAM - 1, MAX_FRAMES_PER_SEGMENT - 1);
+        assert!(last < u64::MAX / 2);
+        assert_eq!(last >> 32, u64::from(MAX_SEGMENTS_PER_STREAM - 1));
+        assert_eq!(last & 0xFFFF_FFFF, u64::from(MAX_FRAMES_PER_SEGMENT - 1));
+    }
+
+    #[test]
+    fn counters_are_distinct_across_segments() {
+        let salt = HeaderV1::test_header().salt;
+        let mut nonces = HashSet::new();
+        for segment_index in [0u32, 1, 2, MAX_SEGMENTS_PER_STREAM - 1] {
+            for frame_index in [0u32, 1, 2, MAX_FRAMES_PER_SEGMENT - 1] {
+                let counter = frame_nonce_counter(segment_index, frame_index);
+                assert!(nonces.insert(derive_nonce_12_tls_style(&salt, counter).unwrap()));
+            }
+        }
+    }
+
+    #[test]
+    fn same_frame_index_in_two_segments_uses_two_keystreams() {
+        let worker = EncryptFrameWorker::new(HeaderV1::test_header(), &[0x42u8; 32]).unwrap();
+        let plaintext = Bytes::from(vec![0u8; 256]);
+        let seal = |segment_index| {
+            let input = FrameInput {
+                segment_index,
+                frame_index: 0,
+                frame_type: FrameType::Data,
+                plaintext: plaintext.clone(),
+                segment_flags: SegmentFlags::empty(),
+            };
+            let frame = worker.encrypt_frame(&input).unwrap();
+            let ct = frame.ciphertext();
+            ct[..ct.len() - TAG_LEN].to_vec()
+        };
+        assert_ne!(seal(0), seal(1));
+    }
+}
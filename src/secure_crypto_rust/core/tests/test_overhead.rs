@@ -0,0 +1,103 @@
+// # 📂 `tests/test_overhead.rs`
+
+#[cfg(test)]
+mod tests {
+    use crypto_core::{
+        compression::CompressionCodec,
+        crypto::DigestAlg,
+        headers::HeaderV1,
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{ApiConfig, EncryptParams, encrypt_stream_v2},
+            estimate_ciphertext_len, estimate_ciphertext_len_with, estimate_max_plaintext_for,
+        },
+    };
+
+    const CHUNK_SIZES: [usize; 3] = [16 * 1024, 64 * 1024, 256 * 1024];
+
+    fn header(chunk_size: usize) -> HeaderV1 {
+        // Stored payloads: the estimate is exact
+        HeaderV1 {
+            compression: CompressionCodec::Auto as u16,
+            chunk_size: chunk_size as u32,
+            ..HeaderV1::test_header()
+        }
+    }
+
+    fn lengths(chunk_size: usize) -> [usize; 7] {
+        [0, 1, chunk_size - 1, chunk_size, chunk_size + 1, 3 * chunk_size, 3 * chunk_size + 4099]
+    }
+
+    fn encrypted_len(params: EncryptParams, len: usize) -> u64 {
+        let data: Vec<u8> = (0..len).map(|i| (i.wrapping_mul(2_654_435_761) >> 7) as u8).collect();
+        encrypt_stream_v2(
+            InputSource::Memory(data),
+            OutputSink::Memory,
+            &[0x11u8; 32],
+            params,
+            ApiConfig::new(Some(true), None),
+        )
+        .unwrap()
+        .output
+        .unwrap()
+        .len() as u64
+    }
+
+    #[test]
+    fn estimate_matches_pipeline_output() {
+        for chunk_size in CHUNK_SIZES {
+            for len in lengths(chunk_size) {
+                let header = header(chunk_size);
+                let actual = encrypted_len(EncryptParams::new(header), len);
+                assert_eq!(estimate_ciphertext_len(len as u64, &header), actual, "chunk {chunk_size}, len {len}");
+            }
+        }
+    }
+
+    #[test]
+    fn estimate_matches_with_trailer_and_index() {
+        let chunk_size = 16 * 1024;
+        for len in lengths(chunk_size) {
+            let mut header = header(chunk_size);
+            header.enable_crc32();
+            let params = EncryptParams::new(header).with_plaintext_size(len as u64).with_segment_index();
+            let header = params.header;
+            let actual = encrypted_len(params, len);
+            assert_eq!(estimate_ciphertext_len(len as u64, &header), actual, "len {len}");
+        }
+    }
+
+    #[test]
+    fn estimate_matches_explicit_frame_size_and_digest() {
+        let chunk_size = 64 * 1024;
+        for len in lengths(chunk_size) {
+            let params = EncryptParams::new(header(chunk_size)).with_frame_size(4096).with_digest_alg(DigestAlg::Sha512);
+            let header = params.header;
+            let actual = encrypted_len(params, len);
+            assert_eq!(estimate_ciphertext_len_with(len as u64, &header, 4096, DigestAlg::Sha512), actual, "len {len}");
+        }
+    }
+
+    #[test]
+    fn max_plaintext_is_the_inverse() {
+        for chunk_size in CHUNK_SIZES {
+            let header = header(chunk_size);
+            for len in lengths(chunk_size) {
+                let budget = estimate_ciphertext_len(len as u64, &header);
+                let max = estimate_max_plaintext_for(budget, &header).unwrap();
+                assert!(max >= len as u64);
+                assert!(estimate_ciphertext_len(max, &header) <= budget);
+                assert!(estimate_ciphertext_len(max + 1, &header) > budget, "chunk {chunk_size}, len {len}");
+            }
+        }
+    }
+
+    #[test]
+    fn budget_below_empty_stream_fits_nothing() {
+        let header = header(64 * 1024);
+        let empty = estimate_ciphertext_len(0, &header);
+        assert_eq!(empty, (HeaderV1::LEN + crypto_core::stream_v2::segmenting::SegmentHeader::LEN) as u64);
+        assert_eq!(estimate_max_plaintext_for(empty - 1, &header), None);
+        assert_eq!(estimate_max_plaintext_for(empty, &header), Some(0));
+    }
+}
@@ -0,0 +1,122 @@
+// # 📂 `tests/test_padding_frames.rs`
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crypto_core::{
+        compression::CompressionCodec,
+        crypto::{DigestAlg, TAG_LEN},
+        headers::HeaderV1,
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{ApiConfig, DecryptParams, EncryptParams, decrypt_stream_v2, encrypt_stream_v2},
+            framing::FrameHeader,
+            io::{PayloadReader, read_segment},
+            min_segment_pad_target,
+            segment_worker::types::get_frame_size,
+            segmenting::types::SegmentFlags,
+        },
+        telemetry::TelemetrySnapshot,
+        types::StreamError,
+    };
+
+    const CHUNK: usize = 16 * 1024;
+    const KEY: [u8; 32] = [0x5au8; 32];
+
+    fn header(codec: CompressionCodec) -> HeaderV1 {
+        HeaderV1 { compression: codec as u16, chunk_size: CHUNK as u32, ..HeaderV1::test_header() }
+    }
+
+    fn pad_target() -> usize {
+        min_segment_pad_target(CHUNK as u32, get_frame_size(CHUNK), DigestAlg::Blake3) as usize + 1000
+    }
+
+    /// Compressible text followed by noise, ending in a partial chunk.
+    fn plaintext() -> Vec<u8> {
+        let mut data: Vec<u8> = b"padding hides how well this compresses. ".iter().cycle().take(3 * CHUNK).copied().collect();
+        data.extend((0..2 * CHUNK + 321).map(|i: usize| (i.wrapping_mul(2_654_435_761) >> 11) as u8));
+        data
+    }
+
+    fn encrypt(data: &[u8], params: EncryptParams) -> Result<Vec<u8>, StreamError> {
+        Ok(encrypt_stream_v2(InputSource::Memory(data.to_vec()), OutputSink::Memory, &KEY, params, ApiConfig::new(Some(true), None))?
+            .output
+            .unwrap())
+    }
+
+    fn decrypt(stream: Vec<u8>) -> TelemetrySnapshot {
+        decrypt_stream_v2(InputSource::Memory(stream), OutputSink::Memory, &KEY, DecryptParams::new(), ApiConfig::new(Some(true), None)).unwrap()
+    }
+
+    #[test]
+    fn padded_and_unpadded_roundtrips_match() {
+        let data = plaintext();
+        for codec in [CompressionCodec::Auto, CompressionCodec::Zstd, CompressionCodec::Lz4] {
+            let plain = encrypt(&data, EncryptParams::new(header(codec))).unwrap();
+            let padded = encrypt(&data, EncryptParams::new(header(codec)).pad_segments_to(Some(pad_target()))).unwrap();
+            assert!(padded.len() > plain.len(), "{codec:?}");
+
+            let plain_dec = decrypt(plain);
+            let padded_dec = decrypt(padded);
+            assert_eq!(plain_dec.output.as_deref(), Some(data.as_slice()), "{codec:?}");
+            assert_eq!(padded_dec.output, plain_dec.output, "{codec:?}");
+            assert_eq!(padded_dec.bytes_plaintext, plain_dec.bytes_plaintext, "{codec:?}");
+            // Padding is counted as overhead, never as payload
+            assert_eq!(padded_dec.bytes_compressed, plain_dec.bytes_compressed, "{codec:?}");
+            assert!(padded_dec.bytes_overhead > plain_dec.bytes_overhead, "{codec:?}");
+        }
+    }
+
+    #[test]
+    fn padded_segments_have_equal_wire_len() {
+        let data = plaintext();
+        let stream = encrypt(&data, EncryptParams::new(header(CompressionCodec::Zstd)).pad_segments_to(Some(pad_target()))).unwrap();
+
+        let (_, mut reader) = PayloadReader::with_header(Cursor::new(stream)).unwrap();
+        let mut padded = 0;
+        while let Some((header, _)) = read_segment(&mut reader).unwrap() {
+            if header.wire_len == 0 || header.flags.contains(SegmentFlags::SEGMENT_INDEX) {
+                continue;
+            }
+            assert_eq!(header.wire_len as usize, pad_target(), "segment {}", header.segment_index);
+            padded += 1;
+        }
+        assert_eq!(padded, data.len().div_ceil(CHUNK));
+    }
+
+    #[test]
+    fn exact_minimum_target_pads_full_chunks() {
+        let data = plaintext();
+        let target = min_segment_pad_target(CHUNK as u32, get_frame_size(CHUNK), DigestAlg::Blake3) as usize;
+        let stream = encrypt(&data, EncryptParams::new(header(CompressionCodec::Auto)).pad_segments_to(Some(target))).unwrap();
+        assert_eq!(decrypt(stream).output.as_deref(), Some(data.as_slice()));
+    }
+
+    #[test]
+    fn rejects_out_of_range_targets() {
+        let min = min_segment_pad_target(CHUNK as u32, get_frame_size(CHUNK), DigestAlg::Blake3) as usize;
+        for target in [0, min - 1, usize::MAX] {
+            match encrypt(b"short", EncryptParams::new(header(CompressionCodec::Auto)).pad_segments_to(Some(target))) {
+                Err(StreamError::Validation(msg)) => assert!(msg.contains("padding target"), "{msg}"),
+                Err(e) => panic!("unexpected error: {e}"),
+                Ok(_) => panic!("padding target {target} must be rejected"),
+            }
+        }
+    }
+
+    #[test]
+    fn tampered_padding_fails_decryption() {
+        let data = plaintext();
+        let mut stream = encrypt(&data, EncryptParams::new(header(CompressionCodec::Auto)).pad_segments_to(Some(pad_target()))).unwrap();
+        let (_, mut reader) = PayloadReader::with_header(Cursor::new(stream.clone())).unwrap();
+        let (_, wire) = read_segment(&mut reader).unwrap().unwrap();
+        let start = stream.windows(64).position(|w| w == &wire[..64]).unwrap();
+        // Last byte of the first segment's padding: just before its digest and terminator frames
+        let digest_and_terminator = 2 * FrameHeader::LEN + TAG_LEN + 4 + DigestAlg::Blake3.digest_len();
+        stream[start + wire.len() - digest_and_terminator - 1] ^= 0x01;
+
+        let result = decrypt_stream_v2(InputSource::Memory(stream), OutputSink::Memory, &KEY, DecryptParams::new(), ApiConfig::new(Some(true), None));
+        assert!(result.is_err());
+    }
+}
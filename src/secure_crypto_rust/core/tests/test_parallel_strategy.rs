@@ -0,0 +1,114 @@
+// # 📂 `tests/test_parallel_strategy.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crypto_core::{
+        headers::{HeaderV1, Strategy, decode_header_le},
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{ApiConfig, DecryptParams, EncryptParams, decrypt_stream_v2, encrypt_stream_v2},
+            parallelism::HybridParallelismProfile,
+        },
+    };
+
+    use crate::common::{self, master_key};
+
+    const CHUNK: usize = 16 * 1024;
+
+    fn plaintext(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 241) as u8).collect()
+    }
+
+    fn header(strategy: Strategy, parallel_hint: u32) -> HeaderV1 {
+        HeaderV1 { chunk_size: CHUNK as u32, strategy: strategy as u16, parallel_hint, ..HeaderV1::test_header() }
+    }
+
+    fn encrypt(data: &[u8], header: HeaderV1, config: ApiConfig) -> Vec<u8> {
+        encrypt_stream_v2(InputSource::Memory(data.to_vec()), OutputSink::Memory, &master_key(), EncryptParams::new(header), config)
+            .unwrap()
+            .output
+            .unwrap()
+    }
+
+    fn decrypt(ciphertext: Vec<u8>, config: ApiConfig) -> Vec<u8> {
+        decrypt_stream_v2(InputSource::Memory(ciphertext), OutputSink::Memory, &master_key(), DecryptParams::new(), config)
+            .unwrap()
+            .output
+            .unwrap_or_default()
+    }
+
+    fn written_header(ciphertext: &[u8]) -> HeaderV1 {
+        decode_header_le(&ciphertext[..HeaderV1::LEN]).unwrap()
+    }
+
+    #[test]
+    fn profile_follows_header_strategy() {
+        let sequential = HybridParallelismProfile::from_header(&header(Strategy::Sequential, 8));
+        assert_eq!(sequential.cpu_workers(), 1);
+        assert_eq!(sequential.inflight_segments(), 1);
+
+        let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        let parallel = HybridParallelismProfile::from_header(&header(Strategy::Parallel, 2));
+        assert_eq!(parallel.cpu_workers(), 2.min(cores));
+
+        // A zero hint means "no preference": same sizing as Auto
+        let unhinted = HybridParallelismProfile::from_header(&header(Strategy::Parallel, 0));
+        let auto = HybridParallelismProfile::from_header(&header(Strategy::Auto, 0));
+        assert_eq!(unhinted.cpu_workers(), auto.cpu_workers());
+    }
+
+    #[test]
+    fn sequential_header_decrypts_with_one_worker() {
+        let data = plaintext(4 * CHUNK + 17);
+        let ciphertext = encrypt(&data, header(Strategy::Sequential, 0), common::config());
+
+        let written = written_header(&ciphertext);
+        assert_eq!(written.parallel_hint, 1);
+        assert_eq!(HybridParallelismProfile::from_header(&written).cpu_workers(), 1);
+
+        assert_eq!(decrypt(ciphertext, common::config()), data);
+    }
+
+    #[test]
+    fn chosen_worker_count_is_recorded_in_header() {
+        let data = plaintext(2 * CHUNK);
+
+        let parallel = encrypt(&data, header(Strategy::Parallel, 2), common::config());
+        let expected = HybridParallelismProfile::from_header(&header(Strategy::Parallel, 2)).cpu_workers();
+        assert_eq!(written_header(&parallel).parallel_hint, expected as u32);
+
+        // An explicit profile wins over the header strategy
+        let config = common::config().with_profile(HybridParallelismProfile::single_threaded());
+        let overridden = encrypt(&data, header(Strategy::Auto, 0), config);
+        assert_eq!(written_header(&overridden).parallel_hint, 1);
+    }
+
+    #[test]
+    fn results_are_identical_across_strategies() {
+        let data = plaintext(6 * CHUNK + 333);
+        let cases = [
+            (header(Strategy::Sequential, 0), common::config()),
+            (header(Strategy::Parallel, 3), common::config()),
+            (header(Strategy::Parallel, 0), common::config()),
+            (header(Strategy::Auto, 0), common::config()),
+            (
+                header(Strategy::Auto, 0),
+                common::config().with_profile(HybridParallelismProfile::single_threaded()),
+            ),
+        ];
+
+        let mut lengths = Vec::new();
+        for (header, config) in cases {
+            let ciphertext = encrypt(&data, header, config);
+            lengths.push(ciphertext.len());
+
+            // Decrypt with the header's own strategy and with a forced single worker
+            assert_eq!(decrypt(ciphertext.clone(), common::config()), data);
+            let single = common::config().with_profile(HybridParallelismProfile::single_threaded());
+            assert_eq!(decrypt(ciphertext, single), data);
+        }
+        assert!(lengths.windows(2).all(|w| w[0] == w[1]), "{lengths:?}");
+    }
+}
@@ -0,0 +1,93 @@
+// # 📂 `tests/test_parallelism_profile.rs`
+
+#[cfg(test)]
+mod tests {
+    use crypto_core::{
+        constants::{DEFAULT_QUEUE_CAP, MAX_QUEUE_CAP},
+        stream_v2::{
+            HybridParallelismProfile, ParallelismProfile, ProfileWarning,
+            core::validate_worker_settings,
+        },
+        types::StreamError,
+    };
+
+    fn cores() -> usize {
+        num_cpus::get().max(1)
+    }
+
+    #[test]
+    fn in_range_values_are_kept_without_warnings() {
+        for workers in [1, cores()] {
+            for inflight in [1, 16, MAX_QUEUE_CAP] {
+                let profile = ParallelismProfile::builder()
+                    .cpu_workers(workers)
+                    .inflight_segments(inflight)
+                    .build()
+                    .unwrap();
+                assert_eq!((profile.cpu_workers(), profile.inflight_segments()), (workers, inflight));
+                assert!(profile.warnings().is_empty(), "{:?}", profile.warnings());
+            }
+        }
+    }
+
+    #[test]
+    fn values_above_the_limit_are_clamped_and_reported() {
+        let profile = ParallelismProfile::builder()
+            .cpu_workers(cores() + 1)
+            .inflight_segments(MAX_QUEUE_CAP + 1)
+            .build()
+            .unwrap();
+        assert_eq!(profile.cpu_workers(), cores());
+        assert_eq!(profile.inflight_segments(), MAX_QUEUE_CAP);
+        assert_eq!(
+            profile.warnings(),
+            [
+                ProfileWarning::Clamped { field: "cpu_workers", requested: cores() + 1, applied: cores() },
+                ProfileWarning::Clamped { field: "inflight_segments", requested: MAX_QUEUE_CAP + 1, applied: MAX_QUEUE_CAP },
+            ]
+        );
+        assert!(profile.warnings()[1].to_string().contains("inflight_segments clamped from 1025 to 1024"));
+    }
+
+    #[test]
+    fn zero_is_rejected() {
+        let err = ParallelismProfile::builder().cpu_workers(0).build().unwrap_err();
+        assert!(matches!(err, StreamError::Validation(ref msg) if msg.contains("cpu_workers")), "{err:?}");
+        let err = ParallelismProfile::builder().inflight_segments(0).build().unwrap_err();
+        assert!(matches!(err, StreamError::Validation(ref msg) if msg.contains("inflight_segments")), "{err:?}");
+    }
+
+    #[test]
+    fn unset_values_use_defaults() {
+        let profile = ParallelismProfile::builder().build().unwrap();
+        assert!((1..=cores()).contains(&profile.cpu_workers()));
+        assert_eq!(profile.inflight_segments(), DEFAULT_QUEUE_CAP);
+        assert!(profile.warnings().is_empty());
+    }
+
+    #[test]
+    fn converts_to_a_cpu_only_hybrid_profile() {
+        let profile = ParallelismProfile::builder().cpu_workers(1).inflight_segments(12).build().unwrap();
+        let hybrid = HybridParallelismProfile::from(profile);
+        assert_eq!(hybrid.cpu_workers(), 1);
+        assert_eq!(hybrid.gpu_workers(), 0);
+        assert_eq!(hybrid.inflight_segments(), 12);
+        assert!(hybrid.gpu().is_none());
+    }
+
+    #[test]
+    fn worker_settings_validation_uses_ranges() {
+        assert!(validate_worker_settings(None, None).is_ok());
+        for ok in [(1, 1), (cores(), MAX_QUEUE_CAP), (1, 3), (cores(), 12)] {
+            assert!(validate_worker_settings(Some(ok.0), Some(ok.1)).is_ok(), "{ok:?}");
+        }
+        for bad in [(0, 4), (cores() + 1, 4), (1, 0), (1, MAX_QUEUE_CAP + 1)] {
+            assert!(
+                matches!(validate_worker_settings(Some(bad.0), Some(bad.1)), Err(StreamError::Validation(_))),
+                "{bad:?}"
+            );
+        }
+        let err = validate_worker_settings(Some(cores() + 1), None).unwrap_err();
+        assert!(err.to_string().contains(&format!("must be in 1..={}", cores())), "{err}");
+    }
+}
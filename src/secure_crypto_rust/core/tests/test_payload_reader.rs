@@ -0,0 +1,120 @@
+// # 📂 `tests/test_payload_reader.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Cursor, Read, Seek, SeekFrom};
+
+    use crypto_core::{
+        headers::HeaderV1,
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{DecryptParams, EncryptParams, decrypt_stream_v2},
+            io::{PAYLOAD_READ_BUF, PayloadReader, SegmentInfo, iter_segment_headers, read_segment},
+        },
+        types::StreamError,
+    };
+
+    use crate::common::{self, master_key};
+
+    const CHUNK: usize = 16 * 1024;
+
+    fn plaintext() -> Vec<u8> {
+        (0..3 * CHUNK + 321).map(|i| (i % 239) as u8).collect()
+    }
+
+    fn encrypt() -> Vec<u8> {
+        let header = HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() };
+        common::encrypt(&plaintext(), EncryptParams::new(header))
+    }
+
+    fn decrypt(input: InputSource) -> Result<Vec<u8>, StreamError> {
+        decrypt_stream_v2(input, OutputSink::Memory, &master_key(), DecryptParams::new(), common::config())
+            .map(|snapshot| snapshot.output.unwrap_or_default())
+    }
+
+    fn segments(stream: &[u8]) -> Vec<SegmentInfo> {
+        iter_segment_headers(Cursor::new(stream.to_vec())).map(Result::unwrap).collect()
+    }
+
+    /// Hands out at most one byte per `read` call, like a slow socket.
+    struct OneByte<R>(R);
+
+    impl<R: Read> Read for OneByte<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = buf.len().min(1);
+            self.0.read(&mut buf[..len])
+        }
+    }
+
+    #[test]
+    fn one_byte_reads_still_decrypt() {
+        let stream = encrypt();
+        let input = InputSource::Reader(Box::new(OneByte(Cursor::new(stream))));
+        assert_eq!(decrypt(input).unwrap(), plaintext());
+    }
+
+    #[test]
+    fn position_follows_every_segment() {
+        let stream = encrypt();
+        let (header, mut reader) = PayloadReader::with_header_and_capacity(64, OneByte(Cursor::new(stream.clone()))).unwrap();
+        assert_eq!(reader.capacity(), 64);
+        assert_eq!(reader.position(), header.encoded_len() as u64);
+
+        for info in segments(&stream) {
+            assert_eq!(reader.position(), info.offset);
+            let (segment, _) = read_segment(&mut reader).unwrap().unwrap();
+            assert_eq!(segment.segment_index, info.segment_index);
+            assert_eq!(reader.position(), info.offset + info.encoded_len());
+        }
+        assert!(read_segment(&mut reader).unwrap().is_none());
+        assert_eq!(reader.position(), stream.len() as u64);
+    }
+
+    #[test]
+    fn truncated_stream_reports_the_segment_offset() {
+        let stream = encrypt();
+        let second = segments(&stream)[1];
+        let cut = stream[..second.offset as usize + second.encoded_len() as usize - 7].to_vec();
+
+        let (_, mut reader) = PayloadReader::with_header(Cursor::new(cut.clone())).unwrap();
+        assert_eq!(reader.capacity(), PAYLOAD_READ_BUF);
+        read_segment(&mut reader).unwrap().unwrap();
+        let err = read_segment(&mut reader).unwrap_err();
+        assert_eq!(err.offset(), Some(second.offset));
+        assert!(matches!(err.root(), StreamError::Io(e) if e.kind() == io::ErrorKind::UnexpectedEof), "{err:?}");
+        assert!(err.to_string().starts_with(&format!("at byte {}:", second.offset)), "{err}");
+
+        let err = decrypt(InputSource::Reader(Box::new(OneByte(Cursor::new(cut))))).unwrap_err();
+        assert_eq!(err.offset(), Some(second.offset), "{err:?}");
+    }
+
+    #[test]
+    fn seeking_discards_the_buffer() {
+        let stream = encrypt();
+        let infos = segments(&stream);
+        let (_, mut reader) = PayloadReader::with_header(Cursor::new(stream)).unwrap();
+
+        // The first read buffered the whole stream; the seek must not serve stale bytes
+        read_segment(&mut reader).unwrap().unwrap();
+        assert_eq!(reader.stream_position().unwrap(), infos[1].offset);
+        assert_eq!(reader.seek(SeekFrom::Start(infos[2].offset)).unwrap(), infos[2].offset);
+        assert_eq!(reader.position(), infos[2].offset);
+        assert_eq!(read_segment(&mut reader).unwrap().unwrap().0.segment_index, infos[2].segment_index);
+
+        // Relative seeks are taken from the logical position, not the inner reader's
+        let back = -((infos[2].encoded_len() + infos[1].encoded_len()) as i64);
+        assert_eq!(reader.seek(SeekFrom::Current(back)).unwrap(), infos[1].offset);
+        assert_eq!(read_segment(&mut reader).unwrap().unwrap().0.segment_index, infos[1].segment_index);
+    }
+
+    #[test]
+    fn header_errors_are_located_at_the_start() {
+        let mut stream = encrypt();
+        stream[0] ^= 0xFF;
+        let err = PayloadReader::with_header(Cursor::new(stream)).unwrap_err();
+        assert_eq!(err.offset(), Some(0));
+        assert!(matches!(err.root(), StreamError::Header(_)), "{err:?}");
+    }
+}
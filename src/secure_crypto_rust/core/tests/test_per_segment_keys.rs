@@ -0,0 +1,105 @@
+// # 📂 `tests/test_per_segment_keys.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use bytes::Bytes;
+    use crypto_core::{
+        constants::flags,
+        crypto::{
+            AadHeader, AeadImpl, CryptoError, KEY_LEN_32, build_aad_with_extensions, derive_nonce_12_tls_style,
+            derive_segment_key, frame_nonce_counter,
+        },
+        headers::HeaderV1,
+        stream_v2::{
+            core::{DecryptParams, EncryptParams},
+            frame_worker::{FrameInput, encrypt::EncryptFrameWorker},
+            framing::FrameType,
+            io::PayloadReader,
+            segmenting::types::SegmentFlags,
+        },
+    };
+
+    use crate::common;
+
+    const CHUNK: usize = 16 * 1024;
+    const SESSION_KEY: [u8; KEY_LEN_32] = [0x42u8; KEY_LEN_32];
+
+    fn plaintext() -> Vec<u8> {
+        (0..4 * CHUNK + 555).map(|i| (i % 233) as u8).collect()
+    }
+
+    fn encrypt(params: EncryptParams) -> Vec<u8> {
+        common::encrypt(&plaintext(), params)
+    }
+
+    fn decrypt(stream: Vec<u8>) -> Vec<u8> {
+        common::decrypt(stream, DecryptParams::new()).unwrap()
+    }
+
+    fn header() -> HeaderV1 {
+        HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() }
+    }
+
+    #[test]
+    fn subkeys_are_distinct_and_deterministic() {
+        let first = derive_segment_key(&SESSION_KEY, 0);
+        assert_eq!(first, derive_segment_key(&SESSION_KEY, 0));
+        assert_ne!(first, derive_segment_key(&SESSION_KEY, 1));
+        assert_ne!(*first, SESSION_KEY);
+        assert_ne!(first, derive_segment_key(&[0x43u8; KEY_LEN_32], 0));
+    }
+
+    #[test]
+    fn round_trips_with_and_without_the_flag() {
+        for per_segment_keys in [false, true] {
+            let mut params = EncryptParams::new(header());
+            if per_segment_keys {
+                params = params.with_per_segment_keys();
+            }
+            let stream = encrypt(params);
+
+            let (written, _) = PayloadReader::with_header(Cursor::new(stream.clone())).unwrap();
+            assert_eq!(written.per_segment_keys(), per_segment_keys);
+            assert_eq!(written.flags & flags::PER_SEGMENT_KEYS != 0, per_segment_keys);
+            assert_eq!(decrypt(stream), plaintext(), "per_segment_keys {per_segment_keys}");
+        }
+    }
+
+    #[test]
+    fn a_segment_only_opens_under_its_own_subkey() {
+        let mut header = header();
+        header.enable_per_segment_keys();
+        let worker = EncryptFrameWorker::new(header, &SESSION_KEY).unwrap();
+
+        let input = FrameInput {
+            segment_index: 1,
+            frame_index: 0,
+            frame_type: FrameType::Data,
+            plaintext: Bytes::from_static(b"segment one"),
+            segment_flags: SegmentFlags::empty(),
+        };
+        let frame = worker.encrypt_frame(&input).unwrap();
+
+        let aad_header = AadHeader {
+            frame_type: FrameType::Data.try_to_u8().unwrap(),
+            segment_index: 1,
+            frame_index: 0,
+            plaintext_len: input.plaintext.len() as u32,
+            segment_flags: input.segment_flags.bits(),
+        };
+        let aad = build_aad_with_extensions(&header, &[], &aad_header).unwrap();
+        let nonce = derive_nonce_12_tls_style(&header.salt, frame_nonce_counter(1, 0)).unwrap();
+        let open = |key: &[u8]| AeadImpl::from_header_and_key(&header, key).unwrap().open(&nonce, &aad, frame.ciphertext());
+
+        assert_eq!(open(&derive_segment_key(&SESSION_KEY, 1)[..]).unwrap(), b"segment one");
+        for neighbour in [0, 2] {
+            let err = open(&derive_segment_key(&SESSION_KEY, neighbour)[..]).unwrap_err();
+            assert!(matches!(err, CryptoError::TagMismatch), "segment {neighbour}: {err:?}");
+        }
+        assert!(matches!(open(&SESSION_KEY).unwrap_err(), CryptoError::TagMismatch));
+    }
+}
@@ -21,7 +21,7 @@ mod tests {
     use crypto_core::stream_v2::io::{PayloadReader};
     use crypto_core::stream_v2::parallelism::HybridParallelismProfile;
     use crypto_core::stream_v2::pipeline::{PipelineConfig, run_decrypt_pipeline, run_encrypt_pipeline};
-    use crypto_core::stream_v2::segment_worker::{EncryptContext, DecryptContext, SegmentWorkerError};
+    use crypto_core::stream_v2::segment_worker::{EncryptContext, DecryptContext};
     use crypto_core::stream_v2::segmenting::SegmentHeader;
     use crypto_core::telemetry::TelemetrySnapshot;
     use crypto_core::types::StreamError;
@@ -34,7 +34,7 @@ mod tests {
         let profile = HybridParallelismProfile::dynamic(header.chunk_size as u32, 0.50, 64);
        // Create a Vec of 32 bytes
         let session_key = vec![0x42u8; KEY_LEN_32];
-        let log_manager = Arc::new(AsyncLogManager::new("test_audit.log", 100).unwrap());
+        let log_manager = Arc::new(AsyncLogManager::disabled());
         
         let context = EncryptContext::new(
             header,
@@ -49,7 +49,7 @@ mod tests {
         let profile = HybridParallelismProfile::dynamic(header.chunk_size as u32, 0.50, 64);
        // Create a Vec of 32 bytes
         let session_key = vec![0x42u8; KEY_LEN_32];
-        let log_manager = Arc::new(AsyncLogManager::new("test_audit.log", 100).unwrap());
+        let log_manager = Arc::new(AsyncLogManager::disabled());
         
         let context = DecryptContext::from_stream_header(
             header,
@@ -336,16 +336,18 @@ mod tests {
     }
 
     #[test]
-    fn empty_input_produces_error() {
+    fn empty_input_roundtrips() {
         let data = [];
 
-        let err = run_encrypt_decrypt(
+        let (out, snapshot) = run_encrypt_decrypt(
             &data,
             HybridParallelismProfile::single_threaded(),
         )
-        .unwrap_err();
+        .expect("empty input must produce a valid stream");
 
-        matches!(err, StreamError::SegmentWorker(SegmentWorkerError::InvalidSegment(_)));
+        assert!(out.is_empty());
+        // Only the final segment
+        assert_eq!(snapshot.segments_processed, 1);
     }
 
     #[test]
@@ -0,0 +1,158 @@
+// # 📂 `tests/test_pipeline_errors.rs`
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Cursor, Read};
+    use std::sync::Arc;
+
+    use crypto_core::{
+        crypto::{DigestAlg, KEY_LEN_32},
+        headers::HeaderV1,
+        recovery::AsyncLogManager,
+        stream_v2::{
+            io::PayloadReader,
+            parallelism::HybridParallelismProfile,
+            pipeline::{PipelineConfig, run_decrypt_pipeline, run_decrypt_pipeline_with_outcome, run_encrypt_pipeline, run_encrypt_pipeline_with_outcome},
+            segment_worker::{DecryptContext, EncryptContext},
+            segmenting::{SegmentHeader, decode_segment_header, types::SegmentFlags},
+        },
+        types::StreamError,
+    };
+
+    /// Yields `limit` bytes of plaintext, then fails every read.
+    struct FailingReader {
+        remaining: usize,
+    }
+
+    impl Read for FailingReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.remaining == 0 {
+                return Err(io::Error::new(io::ErrorKind::BrokenPipe, "source went away"));
+            }
+            let n = buf.len().min(self.remaining);
+            buf[..n].fill(0x5A);
+            self.remaining -= n;
+            Ok(n)
+        }
+    }
+
+    fn profile() -> HybridParallelismProfile {
+        HybridParallelismProfile::new(2, 2, 4)
+    }
+
+    fn log_manager() -> Arc<AsyncLogManager> {
+        Arc::new(AsyncLogManager::disabled())
+    }
+
+    fn encrypt_failing(limit: usize) -> (Result<(), StreamError>, Vec<u8>) {
+        let header = HeaderV1::test_header();
+        let mut crypto = EncryptContext::new(header, profile(), &[0x42u8; KEY_LEN_32], DigestAlg::Blake3).unwrap();
+        let config = PipelineConfig::new(profile(), None);
+
+        let mut output = Vec::new();
+        let mut reader = PayloadReader::new(FailingReader { remaining: limit });
+        let result = run_encrypt_pipeline(&mut reader, Cursor::new(&mut output), &mut crypto, &config, log_manager());
+        (result.map(|_| ()), output)
+    }
+
+    fn has_final_segment(stream: &[u8]) -> bool {
+        let mut offset = HeaderV1::LEN;
+        while offset + SegmentHeader::LEN <= stream.len() {
+            let header = decode_segment_header(&stream[offset..offset + SegmentHeader::LEN]).unwrap();
+            if header.flags.contains(SegmentFlags::FINAL_SEGMENT) {
+                return true;
+            }
+            offset += SegmentHeader::LEN + header.wire_len as usize;
+        }
+        false
+    }
+
+    #[test]
+    fn reader_io_error_fails_encrypt_pipeline() {
+        let chunk = HeaderV1::test_header().chunk_size as usize;
+        for limit in [0, 10, chunk, 3 * chunk + 17] {
+            let (result, output) = encrypt_failing(limit);
+            match result {
+                Err(StreamError::Io(e)) => assert_eq!(e.kind(), io::ErrorKind::BrokenPipe),
+                Err(e) => panic!("limit {limit}: expected StreamError::Io, got {e}"),
+                Ok(()) => panic!("limit {limit}: pipeline must not succeed on a failing source"),
+            }
+            assert!(!has_final_segment(&output), "limit {limit}: output looks like a complete stream");
+        }
+    }
+
+    #[test]
+    fn truncated_output_does_not_decrypt() {
+        let chunk = HeaderV1::test_header().chunk_size as usize;
+        let (result, output) = encrypt_failing(2 * chunk + 5);
+        assert!(result.is_err());
+
+        let header = HeaderV1::test_header();
+        let mut crypto = DecryptContext::from_stream_header(header, profile(), &[0x42u8; KEY_LEN_32], DigestAlg::Blake3).unwrap();
+        let config = PipelineConfig::new(profile(), None);
+        let (_header, mut reader) = PayloadReader::with_header(Cursor::new(output)).unwrap();
+
+        let mut plaintext = Vec::new();
+        let decrypted = run_decrypt_pipeline(&mut reader, Cursor::new(&mut plaintext), &mut crypto, &config, log_manager());
+        assert!(decrypted.is_err());
+    }
+
+    #[test]
+    fn failed_encrypt_keeps_the_telemetry_of_written_segments() {
+        let chunk = HeaderV1::test_header().chunk_size as usize;
+        let mut crypto = EncryptContext::new(HeaderV1::test_header(), profile(), &[0x42u8; KEY_LEN_32], DigestAlg::Blake3).unwrap();
+        let config = PipelineConfig::new(profile(), None);
+        let mut reader = PayloadReader::new(FailingReader { remaining: 3 * chunk + 17 });
+
+        let mut output = Vec::new();
+        let outcome = run_encrypt_pipeline_with_outcome(&mut reader, Cursor::new(&mut output), &mut crypto, &config, log_manager());
+        assert!(matches!(&outcome.result, Err(StreamError::Io(e)) if e.kind() == io::ErrorKind::BrokenPipe));
+        assert_eq!(outcome.snapshot.segments_processed, 3);
+        assert_eq!(outcome.snapshot.bytes_plaintext, 3 * chunk as u64);
+        assert!(outcome.snapshot.bytes_overhead >= HeaderV1::LEN as u64);
+    }
+
+    #[test]
+    fn failed_decrypt_reports_segments_before_the_corrupt_one() {
+        let header = HeaderV1::test_header();
+        let chunk = header.chunk_size as usize;
+        let profile = HybridParallelismProfile::new(1, 0, 4);
+        let key = [0x42u8; KEY_LEN_32];
+
+        let mut crypto = EncryptContext::new(header, profile.clone(), &key, DigestAlg::Blake3).unwrap();
+        let plaintext: Vec<u8> = (0..5 * chunk).map(|i| (i % 253) as u8).collect();
+        let mut stream = Vec::new();
+        run_encrypt_pipeline(
+            &mut PayloadReader::new(Cursor::new(plaintext)),
+            Cursor::new(&mut stream),
+            &mut crypto,
+            &PipelineConfig::new(profile.clone(), None),
+            log_manager(),
+        )
+        .unwrap();
+
+        // Corrupt one wire byte of the third of five data segments
+        let mut offset = HeaderV1::LEN;
+        for _ in 0..2 {
+            let segment = decode_segment_header(&stream[offset..offset + SegmentHeader::LEN]).unwrap();
+            offset += SegmentHeader::LEN + segment.wire_len as usize;
+        }
+        let third = decode_segment_header(&stream[offset..offset + SegmentHeader::LEN]).unwrap();
+        assert_eq!(third.segment_index, 2);
+        stream[offset + SegmentHeader::LEN + 40] ^= 0x01;
+
+        // One worker, decompression in the writer: segments reach it strictly in order
+        let mut crypto = DecryptContext::from_stream_header(header, profile.clone(), &key, DigestAlg::Blake3).unwrap();
+        let config = PipelineConfig::new(profile, None).with_decompress_in_order(true);
+        let (_header, mut reader) = PayloadReader::with_header(Cursor::new(stream)).unwrap();
+        let mut output = Vec::new();
+        let outcome = run_decrypt_pipeline_with_outcome(&mut reader, Cursor::new(&mut output), &mut crypto, &config, log_manager());
+
+        assert!(!outcome.is_ok());
+        assert!(matches!(outcome.result, Err(StreamError::SegmentWorker(_))), "{:?}", outcome.result);
+        assert_eq!(outcome.snapshot.segments_processed, 2);
+        assert_eq!(outcome.snapshot.bytes_plaintext, 2 * chunk as u64);
+        assert_eq!(output.len(), 2 * chunk);
+        assert!(outcome.snapshot.elapsed > std::time::Duration::ZERO);
+    }
+}
@@ -0,0 +1,230 @@
+// # 📂 `tests/test_plaintext_crc.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::{Cursor, Read, Write};
+
+    use crypto_core::{
+        headers::{AadDomain, HeaderV1, decode_header_le},
+        stream_v2::{
+            DecryptingReader, EncryptingWriter, InputSource, OutputSink,
+            core::{DecryptParams, EncryptParams, encrypt_stream_v2},
+            io::scan_segment_index,
+            segmenting::{SegmentHeader, SegmentIndexEntry, decode_segment_header, encode_segment, types::SegmentFlags},
+        },
+        types::StreamError,
+    };
+
+    use crate::common::{self, master_key};
+
+    const CHUNK: usize = 16 * 1024;
+
+    fn header(crc: bool) -> HeaderV1 {
+        let mut header = HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() };
+        if crc {
+            header.enable_crc32();
+        }
+        header
+    }
+
+    fn plaintext(len: usize, seed: u8) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8 ^ seed).collect()
+    }
+
+    fn encrypt(data: &[u8], crc: bool) -> Vec<u8> {
+        common::encrypt(data, EncryptParams::new(header(crc)))
+    }
+
+    fn decrypt(ciphertext: Vec<u8>) -> Result<Vec<u8>, StreamError> {
+        common::decrypt(ciphertext, DecryptParams::new())
+    }
+
+    fn read_all(ciphertext: Vec<u8>) -> std::io::Result<Vec<u8>> {
+        let mut reader = DecryptingReader::new(Cursor::new(ciphertext), &master_key(), DecryptParams::new()).unwrap();
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).map(|_| out)
+    }
+
+    fn segments(ciphertext: &[u8]) -> Vec<SegmentIndexEntry> {
+        scan_segment_index(&mut Cursor::new(ciphertext)).unwrap().entries().to_vec()
+    }
+
+    fn checksum_segment(ciphertext: &[u8]) -> SegmentIndexEntry {
+        let entries = segments(ciphertext);
+        let [.., checksum, last] = entries.as_slice() else { panic!("too few segments") };
+        assert!(last.flags.contains(SegmentFlags::FINAL_SEGMENT));
+        assert!(checksum.flags.contains(SegmentFlags::CHECKSUM), "{:?}", checksum.flags);
+        *checksum
+    }
+
+    fn range(entry: &SegmentIndexEntry) -> std::ops::Range<usize> {
+        entry.offset as usize..(entry.offset + entry.encoded_len()) as usize
+    }
+
+    #[test]
+    fn checksum_segment_precedes_the_final_segment() {
+        let data = plaintext(3 * CHUNK + 5, 0);
+        let ciphertext = encrypt(&data, true);
+
+        let entries = segments(&ciphertext);
+        assert_eq!(entries.len(), 4 + 1 + 1);
+        assert_eq!(checksum_segment(&ciphertext).segment_index, 4);
+        assert_eq!(decrypt(ciphertext.clone()).unwrap(), data);
+        assert_eq!(read_all(ciphertext).unwrap(), data);
+
+        // Without the flag the layout is unchanged
+        let plain = encrypt(&data, false);
+        let entries = segments(&plain);
+        assert_eq!(entries.len(), 4 + 1);
+        assert!(entries.iter().all(|e| !e.flags.contains(SegmentFlags::CHECKSUM)));
+    }
+
+    #[test]
+    fn empty_input_still_carries_a_checksum() {
+        let ciphertext = encrypt(&[], true);
+        assert_eq!(segments(&ciphertext).len(), 2);
+        assert_eq!(decrypt(ciphertext).unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn adapters_write_the_same_layout() {
+        let data = plaintext(2 * CHUNK + 100, 3);
+        let mut writer = EncryptingWriter::new(Vec::new(), &master_key(), EncryptParams::new(header(true))).unwrap();
+        writer.write_all(&data).unwrap();
+        let ciphertext = writer.finish().unwrap();
+
+        assert_eq!(ciphertext.len(), encrypt(&data, true).len());
+        assert_eq!(checksum_segment(&ciphertext).segment_index, 3);
+        assert_eq!(read_all(ciphertext).unwrap(), data);
+    }
+
+    #[test]
+    fn mismatched_checksum_is_caught() {
+        // Same key, header and length: the other stream's checksum segment authenticates here.
+        // Incompressible, so both streams store their segments at the same offsets
+        let noise = |mut state: u64| -> Vec<u8> {
+            (0..2 * CHUNK + 7)
+                .map(|_| {
+                    // xorshift64
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    state as u8
+                })
+                .collect()
+        };
+        let data = noise(1);
+        let ciphertext = encrypt(&data, true);
+        let other = encrypt(&noise(2), true);
+
+        let ours = checksum_segment(&ciphertext);
+        let theirs = checksum_segment(&other);
+        assert_eq!(ours.offset, theirs.offset);
+        assert_eq!(ours.encoded_len(), theirs.encoded_len());
+
+        let mut spliced = ciphertext.clone();
+        spliced[range(&ours)].copy_from_slice(&other[range(&theirs)]);
+        assert_ne!(spliced, ciphertext);
+
+        match decrypt(spliced.clone()) {
+            Err(StreamError::ChecksumMismatch { expected, actual }) => {
+                assert_eq!(actual, crc32fast::hash(&data));
+                assert_ne!(expected, actual);
+            }
+            other => panic!("expected ChecksumMismatch, got {other:?}"),
+        }
+        let err = read_all(spliced).unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"), "{err}");
+    }
+
+    #[test]
+    fn dropped_checksum_segment_is_caught() {
+        let data = plaintext(CHUNK + 1, 0);
+        let ciphertext = encrypt(&data, true);
+        let checksum = checksum_segment(&ciphertext);
+
+        // Cut the checksum segment and renumber the (unauthenticated) final marker
+        let final_start = range(&checksum).end;
+        let mut final_header = decode_segment_header(&ciphertext[final_start..][..SegmentHeader::LEN]).unwrap();
+        final_header.segment_index = checksum.segment_index;
+        let mut forged = ciphertext[..checksum.offset as usize].to_vec();
+        forged.extend_from_slice(&encode_segment(&final_header, &bytes::Bytes::new()).unwrap());
+
+        assert!(matches!(decrypt(forged.clone()), Err(StreamError::Validation(msg)) if msg.contains("trailer")));
+        assert!(read_all(forged).is_err());
+    }
+    /// A CRC + length stream whose size is only known once the input is drained.
+    fn sized_header(strict: bool) -> HeaderV1 {
+        let mut header = header(true);
+        header.enable_total_len();
+        if strict {
+            header.enable_aad_strict();
+        }
+        header
+    }
+
+    fn plaintext_size(stream: &[u8]) -> u64 {
+        decode_header_le(&stream[..HeaderV1::LEN]).unwrap().plaintext_size
+    }
+
+    #[test]
+    fn seekable_sinks_get_the_size_patched_into_the_header() {
+        let data = plaintext(2 * CHUNK + 77, 3);
+
+        let in_memory = encrypt_stream_v2(
+            InputSource::Memory(data.clone()),
+            OutputSink::Memory,
+            &master_key(),
+            EncryptParams::new(sized_header(false)),
+            common::config(),
+        )
+        .unwrap()
+        .output
+        .unwrap();
+        assert_eq!(plaintext_size(&in_memory), data.len() as u64);
+        assert_eq!(decrypt(in_memory.clone()).unwrap(), data);
+        assert_eq!(read_all(in_memory).unwrap(), data);
+
+        let path = std::env::temp_dir().join(format!("rse1-crc-patch-{}.bin", std::process::id()));
+        encrypt_stream_v2(
+            InputSource::Memory(data.clone()),
+            OutputSink::File(path.clone()),
+            &master_key(),
+            EncryptParams::new(sized_header(false)),
+            common::config(),
+        )
+        .unwrap();
+        let on_disk = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(plaintext_size(&on_disk), data.len() as u64);
+        assert_eq!(decrypt(on_disk).unwrap(), data);
+    }
+
+    #[test]
+    fn unseekable_and_strict_streams_keep_the_header_as_written() {
+        let data = plaintext(CHUNK + 5, 4);
+
+        // A file behind `Writer` is only known to be `Write`
+        let path = std::env::temp_dir().join(format!("rse1-crc-piped-{}.bin", std::process::id()));
+        encrypt_stream_v2(
+            InputSource::Memory(data.clone()),
+            OutputSink::Writer(Box::new(fs::File::create(&path).unwrap())),
+            &master_key(),
+            EncryptParams::new(sized_header(false)),
+            common::config(),
+        )
+        .unwrap();
+        let piped = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(plaintext_size(&piped), 0);
+        assert_eq!(decrypt(piped).unwrap(), data);
+
+        // The header is part of every frame's AAD: patching it would break them all
+        let strict = common::encrypt(&data, EncryptParams::new(sized_header(true)));
+        assert_eq!(plaintext_size(&strict), 0);
+        assert_eq!(common::decrypt(strict, DecryptParams::new().expect_aad_domain(AadDomain::Generic)).unwrap(), data);
+    }
+}
@@ -0,0 +1,113 @@
+// # 📂 `tests/test_progress_cancel.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::ops::ControlFlow;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use crypto_core::{
+        headers::{HeaderV1, Strategy},
+        stream_v2::{
+            InputSource, OutputSink, ProgressCallback, ProgressEvent,
+            core::{ApiConfig, DecryptParams, EncryptParams, decrypt_stream_v2, encrypt_stream_v2},
+        },
+        types::{ErrorCode, StreamError},
+    };
+
+    use crate::common::{self, master_key};
+
+    const CHUNK: usize = 16 * 1024;
+    const SEGMENTS: usize = 128;
+
+    fn plaintext() -> Vec<u8> {
+        common::plaintext(SEGMENTS * CHUNK)
+    }
+
+    fn header() -> HeaderV1 {
+        HeaderV1 { chunk_size: CHUNK as u32, strategy: Strategy::Parallel as u16, ..HeaderV1::test_header() }
+    }
+
+    /// Callback recording every event; breaks once `cancel_after` segments are written.
+    fn recorder(cancel_after: Option<u64>) -> (ProgressCallback, Arc<Mutex<Vec<ProgressEvent>>>) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let callback: ProgressCallback = Arc::new(move |event: ProgressEvent| {
+            sink.lock().unwrap().push(event);
+            match cancel_after {
+                Some(n) if event.segments_completed >= n => ControlFlow::Break(()),
+                _ => ControlFlow::Continue(()),
+            }
+        });
+        (callback, events)
+    }
+
+    fn encrypt(config: ApiConfig) -> Result<Vec<u8>, StreamError> {
+        let snapshot = encrypt_stream_v2(
+            InputSource::Memory(plaintext()),
+            OutputSink::Memory,
+            &master_key(),
+            EncryptParams::new(header()),
+            config,
+        )?;
+        Ok(snapshot.output.unwrap())
+    }
+
+    fn decrypt(stream: Vec<u8>, config: ApiConfig) -> Result<Vec<u8>, StreamError> {
+        let snapshot =
+            decrypt_stream_v2(InputSource::Memory(stream), OutputSink::Memory, &master_key(), DecryptParams::new(), config)?;
+        Ok(snapshot.output.unwrap())
+    }
+
+    /// Once the call returned, no pipeline thread may still report.
+    fn assert_quiescent(events: &Mutex<Vec<ProgressEvent>>) {
+        let seen = events.lock().unwrap().len();
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(events.lock().unwrap().len(), seen, "progress reported after the pipeline returned");
+    }
+
+    #[test]
+    fn progress_reaches_the_totals() {
+        let (callback, events) = recorder(None);
+        let stream = encrypt(common::config().with_progress(callback)).unwrap();
+
+        let events = events.lock().unwrap();
+        let last = events.iter().max_by_key(|e| (e.segments_completed, e.bytes_read)).unwrap();
+        assert_eq!(events.iter().map(|e| e.bytes_read).max(), Some(plaintext().len() as u64));
+        assert_eq!(last.bytes_written, (stream.len() - HeaderV1::LEN) as u64);
+        // Data segments plus the empty final segment
+        assert_eq!(last.segments_completed, SEGMENTS as u64 + 1);
+    }
+
+    #[test]
+    fn encrypt_cancels_after_two_segments() {
+        let (callback, events) = recorder(Some(2));
+        let result = encrypt(common::config().with_progress(callback));
+        assert!(matches!(result, Err(StreamError::Cancelled)), "{result:?}");
+        assert_eq!(result.unwrap_err().code(), ErrorCode::Cancelled);
+
+        // The reader stopped well before the end of the input
+        let read = events.lock().unwrap().iter().map(|e| e.bytes_read).max().unwrap();
+        assert!(read < plaintext().len() as u64, "read {read} bytes after cancelling");
+        assert_quiescent(&events);
+
+        // Nothing is left running that would disturb the next run
+        assert!(encrypt(common::config()).is_ok());
+    }
+
+    #[test]
+    fn decrypt_cancels_after_two_segments() {
+        let stream = encrypt(common::config()).unwrap();
+        let (callback, events) = recorder(Some(2));
+        let result = decrypt(stream.clone(), common::config().with_progress(callback));
+        assert!(matches!(result, Err(StreamError::Cancelled)), "{result:?}");
+
+        let read = events.lock().unwrap().iter().map(|e| e.bytes_read).max().unwrap();
+        assert!(read < (stream.len() - HeaderV1::LEN) as u64, "read {read} bytes after cancelling");
+        assert_quiescent(&events);
+
+        assert_eq!(decrypt(stream, common::config()).unwrap(), plaintext());
+    }
+}
@@ -0,0 +1,103 @@
+// # 📂 `tests/test_quiet_output.rs`
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        path::{Path, PathBuf},
+        process::Command,
+    };
+
+    use crypto_core::{
+        headers::HeaderV1,
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{ApiConfig, DecryptParams, EncryptParams, decrypt_stream_v2, encrypt_stream_v2},
+        },
+    };
+
+    const CHILD_ENV: &str = "CRYPTO_CORE_QUIET_CHILD";
+    const STREAM_LEN: usize = 100 * 1024 * 1024;
+
+    /// Encrypts and decrypts 100 MiB. Only meaningful when spawned by
+    /// `streams_produce_no_output_without_subscriber`.
+    #[test]
+    #[ignore]
+    fn quiet_child_roundtrip() {
+        if std::env::var_os(CHILD_ENV).is_none() {
+            return;
+        }
+        let master_key = vec![0x11u8; 32];
+        let plaintext: Vec<u8> = (0..STREAM_LEN).map(|i| (i % 251) as u8).collect();
+
+        let snapshot = encrypt_stream_v2(
+            InputSource::Memory(plaintext.clone()),
+            OutputSink::Memory,
+            &master_key,
+            EncryptParams::new(HeaderV1::test_header()),
+            ApiConfig::new(Some(true), None),
+        )
+        .expect("encryption should succeed");
+        assert_eq!(snapshot.bytes_plaintext, STREAM_LEN as u64);
+
+        let decrypted = decrypt_stream_v2(
+            InputSource::Memory(snapshot.output.unwrap()),
+            OutputSink::Memory,
+            &master_key,
+            DecryptParams::new(),
+            ApiConfig::new(Some(true), None),
+        )
+        .expect("decryption should succeed");
+        assert!(decrypted.output.unwrap() == plaintext);
+    }
+
+    #[test]
+    fn streams_produce_no_output_without_subscriber() {
+        let output = Command::new(std::env::current_exe().unwrap())
+            .args(["--ignored", "--exact", "tests::quiet_child_roundtrip", "--nocapture", "--test-threads=1", "-q"])
+            .env(CHILD_ENV, "1")
+            .output()
+            .expect("failed to spawn test binary");
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(output.status.success(), "child failed:\n{stdout}\n{stderr}");
+        assert!(stderr.is_empty(), "unexpected stderr output:\n{stderr}");
+
+        // Everything left on stdout must be the harness's own summary
+        for line in stdout.lines() {
+            let harness = line.is_empty()
+                || line.starts_with("running ")
+                || line.starts_with("test result:")
+                || line.chars().all(|c| c == '.');
+            assert!(harness, "unexpected stdout line: {line:?}");
+        }
+    }
+
+    /// Every `.rs` file under `src/`, sketches included.
+    fn library_sources(dir: &Path, out: &mut Vec<PathBuf>) {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                library_sources(&path, out);
+            } else if path.extension().is_some_and(|ext| ext == "rs") {
+                out.push(path);
+            }
+        }
+    }
+
+    #[test]
+    fn library_sources_have_no_print_macros() {
+        let mut sources = Vec::new();
+        library_sources(&Path::new(env!("CARGO_MANIFEST_DIR")).join("src"), &mut sources);
+        assert!(!sources.is_empty(), "no library sources found");
+
+        for path in sources {
+            let source = std::fs::read_to_string(&path).unwrap();
+            for line in source.lines().map(str::trim_start).filter(|l| !l.starts_with("//")) {
+                // `print!(` also matches `eprint!(`; `println!(` also matches `eprintln!(`
+                let printing = line.contains("print!(") || line.contains("println!(") || line.contains("dbg!(");
+                assert!(!printing, "print macro left in {}: {line}", path.display());
+            }
+        }
+    }
+}
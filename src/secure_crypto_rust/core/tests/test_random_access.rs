@@ -0,0 +1,118 @@
+// # 📂 `tests/test_random_access.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use crypto_core::{
+        headers::HeaderV1,
+        stream_v2::{
+            EncryptingWriter, RandomAccessDecryptor,
+            core::{DecryptParams, EncryptParams},
+            io::scan_segment_index,
+            segmenting::{SegmentHeader, SegmentIndexMap, types::SegmentFlags},
+        },
+        types::StreamError,
+    };
+
+    use crate::common::master_key;
+
+    const CHUNK: usize = 16 * 1024;
+
+    fn plaintext(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 239) as u8).collect()
+    }
+
+    fn encrypt(data: &[u8]) -> (Vec<u8>, SegmentIndexMap) {
+        let params = EncryptParams::new(HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() });
+        let mut writer = EncryptingWriter::new(Vec::new(), &master_key(), params).unwrap();
+        writer.write_all(data).unwrap();
+        writer.finish_with_index().unwrap()
+    }
+
+    fn decryptor(ciphertext: Vec<u8>) -> RandomAccessDecryptor<Cursor<Vec<u8>>> {
+        RandomAccessDecryptor::new(Cursor::new(ciphertext), &master_key(), DecryptParams::new()).unwrap()
+    }
+
+    #[test]
+    fn scanned_index_matches_recorded_index() {
+        let (ciphertext, recorded) = encrypt(&plaintext(5 * CHUNK + 100));
+        let scanned = scan_segment_index(&mut Cursor::new(&ciphertext)).unwrap();
+
+        assert_eq!(scanned, recorded);
+        assert_eq!(scanned.len(), 7); // 6 data segments + final marker
+        assert!(scanned.is_complete());
+        assert_eq!(scanned.end_offset(), ciphertext.len() as u64);
+        assert_eq!(scanned.get(0).unwrap().offset, HeaderV1::LEN as u64);
+    }
+
+    #[test]
+    fn reads_segments_in_any_order() {
+        let data = plaintext(5 * CHUNK + 100);
+        let (ciphertext, _) = encrypt(&data);
+        let mut reader = decryptor(ciphertext);
+
+        let count = reader.segment_count().unwrap();
+        for index in (0..count - 1).rev() {
+            let start = index * CHUNK;
+            let end = (start + CHUNK).min(data.len());
+            let segment = reader.read_segment(index as u32).unwrap();
+            assert_eq!(&segment[..], &data[start..end], "segment {index}");
+        }
+    }
+
+    #[test]
+    fn final_segment_is_empty_and_past_it_is_out_of_range() {
+        let (ciphertext, index) = encrypt(&plaintext(2 * CHUNK));
+        let mut reader = decryptor(ciphertext).with_index(index);
+
+        let last = reader.segment_count().unwrap() as u32 - 1;
+        assert!(reader.index().unwrap().get(last).unwrap().flags.contains(SegmentFlags::FINAL_SEGMENT));
+        assert!(reader.read_segment(last).unwrap().is_empty());
+
+        match reader.read_segment(last + 1) {
+            Err(StreamError::Validation(msg)) => assert!(msg.contains("out of range"), "{msg}"),
+            other => panic!("expected out-of-range error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn empty_stream_has_only_the_final_segment() {
+        let (ciphertext, _) = encrypt(&[]);
+        let mut reader = decryptor(ciphertext);
+        assert_eq!(reader.segment_count().unwrap(), 1);
+        assert!(reader.read_segment(0).unwrap().is_empty());
+        assert!(reader.read_segment(1).is_err());
+    }
+
+    #[test]
+    fn corruption_is_confined_to_its_segment() {
+        let data = plaintext(4 * CHUNK);
+        let (mut ciphertext, index) = encrypt(&data);
+        let target = index.get(2).unwrap();
+        ciphertext[(target.offset + SegmentHeader::LEN as u64 + 10) as usize] ^= 0x01;
+
+        let mut reader = decryptor(ciphertext).with_index(index);
+        assert!(reader.read_segment(2).is_err());
+        assert_eq!(&reader.read_segment(3).unwrap()[..], &data[3 * CHUNK..]);
+        assert_eq!(&reader.read_segment(1).unwrap()[..], &data[CHUNK..2 * CHUNK]);
+    }
+
+    #[test]
+    fn scan_rejects_stream_without_final_segment() {
+        let (ciphertext, index) = encrypt(&plaintext(3 * CHUNK));
+        let final_offset = index.get(index.len() as u32 - 1).unwrap().offset as usize;
+
+        let mut reader = decryptor(ciphertext[..final_offset].to_vec());
+        assert!(matches!(reader.segment_count(), Err(StreamError::Validation(_))));
+
+        // A wire cut short is reported as truncation
+        let cut = index.get(1).unwrap().offset as usize + SegmentHeader::LEN + 5;
+        assert!(matches!(
+            scan_segment_index(&mut Cursor::new(&ciphertext[..cut])),
+            Err(StreamError::Segment(_))
+        ));
+    }
+}
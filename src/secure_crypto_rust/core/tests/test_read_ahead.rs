@@ -0,0 +1,89 @@
+// # 📂 `tests/test_read_ahead.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Cursor, thread};
+
+    use crypto_core::{
+        headers::HeaderV1,
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{DecryptParams, EncryptParams, decrypt_stream_v2},
+            io::{DEFAULT_READ_AHEAD, PayloadReader, SegmentPrefetcher, iter_segment_headers, read_segment},
+        },
+        types::StreamError,
+    };
+
+    use crate::common::{self, master_key};
+
+    const CHUNK: usize = 16 * 1024;
+
+    fn plaintext() -> Vec<u8> {
+        common::plaintext(6 * CHUNK + 123)
+    }
+
+    fn encrypt() -> Vec<u8> {
+        let header = HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() };
+        common::encrypt(&plaintext(), EncryptParams::new(header))
+    }
+
+    fn decrypt(stream: Vec<u8>, read_ahead: usize) -> Result<Vec<u8>, StreamError> {
+        let config = common::config().with_read_ahead(read_ahead);
+        decrypt_stream_v2(InputSource::Memory(stream), OutputSink::Memory, &master_key(), DecryptParams::new(), config)
+            .map(|snapshot| snapshot.output.unwrap_or_default())
+    }
+
+    #[test]
+    fn every_depth_decrypts_the_same() {
+        let stream = encrypt();
+        for read_ahead in [0, DEFAULT_READ_AHEAD, 4] {
+            assert_eq!(decrypt(stream.clone(), read_ahead).unwrap(), plaintext(), "read_ahead {read_ahead}");
+        }
+    }
+
+    #[test]
+    fn prefetcher_yields_what_read_segment_does() {
+        let stream = encrypt();
+        let (_, mut direct) = PayloadReader::with_header(Cursor::new(stream.clone())).unwrap();
+        let (_, mut prefetched) = PayloadReader::with_header(Cursor::new(stream)).unwrap();
+
+        thread::scope(|scope| {
+            let prefetcher = SegmentPrefetcher::spawn(scope, &mut prefetched, DEFAULT_READ_AHEAD);
+            while let Some(expected) = read_segment(&mut direct).unwrap() {
+                assert_eq!(prefetcher.next_segment().unwrap(), Some(expected));
+            }
+            assert_eq!(prefetcher.next_segment().unwrap(), None);
+        });
+    }
+
+    #[test]
+    fn prefetcher_stops_after_the_final_segment() {
+        let mut stream = encrypt();
+        let end = stream.len() as u64;
+        // Not a segment header: reading on would fail
+        stream.extend_from_slice(&[0xEEu8; 64]);
+
+        let (_, mut reader) = PayloadReader::with_header(Cursor::new(stream.clone())).unwrap();
+        thread::scope(|scope| {
+            let prefetcher = SegmentPrefetcher::spawn(scope, &mut reader, 2);
+            while prefetcher.next_segment().unwrap().is_some() {}
+        });
+        assert_eq!(reader.position(), end);
+        assert_eq!(decrypt(stream, DEFAULT_READ_AHEAD).unwrap(), plaintext());
+    }
+
+    #[test]
+    fn truncation_surfaces_on_the_same_segment() {
+        let stream = encrypt();
+        let third = iter_segment_headers(Cursor::new(stream.clone())).nth(2).unwrap().unwrap();
+        let cut = stream[..(third.offset + third.encoded_len()) as usize - 7].to_vec();
+
+        let serial = decrypt(cut.clone(), 0).unwrap_err();
+        let prefetched = decrypt(cut, DEFAULT_READ_AHEAD).unwrap_err();
+        assert_eq!(serial.offset(), Some(third.offset), "{serial:?}");
+        assert_eq!(prefetched.offset(), serial.offset(), "{prefetched:?}");
+        assert_eq!(prefetched.to_string(), serial.to_string());
+    }
+}
@@ -0,0 +1,142 @@
+// # 📂 `tests/test_recovery_bootstrap.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::ErrorKind;
+    use std::path::PathBuf;
+
+    use base64::{Engine, engine::general_purpose::STANDARD};
+    use crypto_core::{
+        crypto::DigestAlg,
+        headers::HeaderV1,
+        recovery::{Checkpoint, Direction, ResumePoint, bootstrap_from_log},
+        stream_v2::{
+            InputSource, OutputSink, decrypt_stream_v2_from,
+            core::{DecryptParams, EncryptParams},
+            framing::decode::parse_frame_header,
+            segmenting::{SegmentHeader, decode_segment_header},
+        },
+    };
+
+    use crate::common::{self, master_key};
+
+    const CHUNK: usize = 16 * 1024;
+
+    /// Four data segments, the last one short.
+    fn plaintext() -> Vec<u8> {
+        common::plaintext(3 * CHUNK + CHUNK / 2)
+    }
+
+    fn encrypt() -> Vec<u8> {
+        let header = HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() };
+        common::encrypt(&plaintext(), EncryptParams::new(header))
+    }
+
+    /// `(segment_index, segment_offset, frame wires)` of every data segment.
+    fn segments(stream: &[u8]) -> Vec<(u32, u64, Vec<Vec<u8>>)> {
+        let mut out = Vec::new();
+        let mut offset = HeaderV1::LEN;
+        while offset < stream.len() {
+            let header = decode_segment_header(&stream[offset..]).unwrap();
+            let end = offset + header.total_len().unwrap();
+            let mut frames = Vec::new();
+            let mut at = offset + SegmentHeader::LEN;
+            while at < end {
+                let len = parse_frame_header(&stream[at..]).unwrap().total_len().unwrap();
+                frames.push(stream[at..at + len].to_vec());
+                at += len;
+            }
+            if !frames.is_empty() {
+                out.push((header.segment_index, offset as u64, frames));
+            }
+            offset = end;
+        }
+        out
+    }
+
+    fn log_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rse1-bootstrap-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    fn entry(frame: &[u8]) -> String {
+        format!("ENCRYPT: {}\n", STANDARD.encode(frame))
+    }
+
+    #[test]
+    fn torn_tail_is_dropped_and_decrypt_resumes() {
+        let stream = encrypt();
+        let segs = segments(&stream);
+        assert_eq!(segs.len(), 4);
+
+        // Segments 0, 1 and 3 finished; segment 2 crashed before its terminator
+        let mut log = String::new();
+        for (seg, _, frames) in &segs {
+            let frames = if *seg == 2 { &frames[..frames.len() - 1] } else { &frames[..] };
+            log.extend(frames.iter().map(|f| entry(f)));
+        }
+        let torn = entry(&segs[2].2[0]);
+        log.push_str(&torn[..torn.len() / 2]);
+        let path = log_path("torn.log");
+        fs::write(&path, &log).unwrap();
+
+        let state = bootstrap_from_log(path.to_str().unwrap()).unwrap();
+        let last_frames = &segs[3].2;
+        assert_eq!(state.direction, Some(Direction::Encrypt));
+        assert_eq!(state.last_segment_index, Some(3));
+        assert_eq!(state.last_frame_index, Some(last_frames.len() as u32 - 1));
+        assert_eq!(state.next_segment_index, 2);
+        assert_eq!(state.resume_offset, segs[2].1);
+        let logged: usize = segs.iter().flat_map(|(_, _, f)| f).map(Vec::len).sum::<usize>() - segs[2].2.last().unwrap().len();
+        assert_eq!(state.bytes_processed, logged as u64);
+
+        let resume: ResumePoint = state.into();
+        let snapshot = decrypt_stream_v2_from(
+            InputSource::Memory(stream),
+            OutputSink::Memory,
+            &master_key(),
+            DecryptParams::new(),
+            common::config(),
+            resume,
+        )
+        .unwrap();
+        assert_eq!(snapshot.output.unwrap(), plaintext()[2 * CHUNK..]);
+    }
+
+    #[test]
+    fn corrupt_line_before_the_end_is_an_error() {
+        let segs = segments(&encrypt());
+        let torn = entry(&segs[0].2[0]);
+        let log = format!("{}\n{}", &torn[..torn.len() / 2], entry(&segs[0].2[1]));
+        let path = log_path("corrupt.log");
+        fs::write(&path, log).unwrap();
+
+        let err = bootstrap_from_log(path.to_str().unwrap()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn scheduler_positions_without_frames() {
+        let checkpoint = Checkpoint {
+            segment_index: 5,
+            frame_index: 3,
+            frame_count: 8,
+            stream_offset: 4096,
+            alg: DigestAlg::Sha256,
+            digest_state: Vec::new(),
+        };
+        let log = format!("SCHEDULER: COMPACTED\nSCHEDULER: {}\n", checkpoint.to_log_line());
+        let path = log_path("scheduler.log");
+        fs::write(&path, log).unwrap();
+
+        let state = bootstrap_from_log(path.to_str().unwrap()).unwrap();
+        assert_eq!((state.last_segment_index, state.last_frame_index), (Some(5), Some(2)));
+        assert_eq!(state.direction, None);
+        // No frame was logged: resume from the first segment
+        assert_eq!(ResumePoint::from(state), ResumePoint::new(HeaderV1::LEN as u64, 0));
+    }
+}
@@ -0,0 +1,116 @@
+// # 📂 `tests/test_reencrypt.rs`
+
+#[cfg(test)]
+mod tests {
+    use crypto_core::{
+        compression::CompressionCodec,
+        crypto::KeyRing,
+        headers::{HeaderV1, decode_header_le},
+        stream_v2::{
+            InputSource, OutputSink, reencrypt_stream_v2,
+            core::{ApiConfig, DecryptParams, EncryptParams, decrypt_stream_v2, encrypt_stream_v2},
+        },
+        types::StreamError,
+    };
+
+    const KEY_A: [u8; 32] = [0xA1; 32];
+    const KEY_B: [u8; 32] = [0xB2; 32];
+    const CHUNK: usize = 16 * 1024;
+
+    /// Four full segments and a partial one.
+    fn plaintext() -> Vec<u8> {
+        (0..4 * CHUNK + 1000).map(|i| ((i / 7) % 251) as u8).collect()
+    }
+
+    fn archive(data: &[u8]) -> Vec<u8> {
+        let header = HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() };
+        encrypt_stream_v2(
+            InputSource::Memory(data.to_vec()),
+            OutputSink::Memory,
+            &KEY_A,
+            EncryptParams::new(header).with_key_id(1),
+            ApiConfig::new(Some(true), None),
+        )
+        .unwrap()
+        .output
+        .unwrap()
+    }
+
+    fn old_keys() -> KeyRing {
+        KeyRing::new().with_key(1, &KEY_A).unwrap()
+    }
+
+    /// New key id, salt, codec and chunk size: the plaintext is re-segmented.
+    fn new_params() -> EncryptParams<'static> {
+        let header = HeaderV1 {
+            compression: CompressionCodec::Zstd as u16,
+            chunk_size: 64 * 1024,
+            ..HeaderV1::new_random()
+        };
+        EncryptParams::new(header).with_key_id(2)
+    }
+
+    fn decrypt(stream: Vec<u8>, key: &[u8]) -> Result<Vec<u8>, StreamError> {
+        decrypt_stream_v2(InputSource::Memory(stream), OutputSink::Memory, key, DecryptParams::new(), ApiConfig::new(Some(true), None))
+            .map(|snapshot| snapshot.output.unwrap_or_default())
+    }
+
+    #[test]
+    fn rotates_archive_from_key_a_to_key_b() {
+        let data = plaintext();
+        let report = reencrypt_stream_v2(
+            InputSource::Memory(archive(&data)),
+            OutputSink::Memory,
+            &old_keys(),
+            &KEY_B,
+            new_params(),
+            ApiConfig::new(Some(true), None),
+        )
+        .unwrap();
+
+        // Both legs report their own telemetry
+        assert_eq!(report.decrypt.bytes_plaintext, data.len() as u64);
+        assert_eq!(report.encrypt.bytes_plaintext, data.len() as u64);
+        assert!(report.decrypt.segments_processed > report.encrypt.segments_processed);
+        assert!(report.decrypt.output.is_none());
+
+        let rotated = report.encrypt.output.unwrap();
+        let header = decode_header_le(&rotated[..HeaderV1::LEN]).unwrap();
+        assert_eq!(header.key_id, 2);
+        assert_eq!(header.chunk_size, 64 * 1024);
+
+        assert_eq!(decrypt(rotated.clone(), &KEY_B).unwrap(), data);
+        assert!(decrypt(rotated, &KEY_A).is_err());
+    }
+
+    #[test]
+    fn corrupt_archive_reports_the_decrypt_error() {
+        let mut stream = archive(&plaintext());
+        let mid = stream.len() / 2;
+        stream[mid] ^= 0x40;
+
+        let result = reencrypt_stream_v2(
+            InputSource::Memory(stream),
+            OutputSink::Memory,
+            &old_keys(),
+            &KEY_B,
+            new_params(),
+            ApiConfig::new(Some(true), None),
+        );
+        let err = result.unwrap_err();
+        assert!(!err.to_string().contains("re-encryption source failed"), "{err}");
+    }
+
+    #[test]
+    fn unknown_old_key_id_is_reported() {
+        let result = reencrypt_stream_v2(
+            InputSource::Memory(archive(&plaintext())),
+            OutputSink::Memory,
+            &KeyRing::new().with_key(9, &KEY_A).unwrap(),
+            &KEY_B,
+            new_params(),
+            ApiConfig::new(Some(true), None),
+        );
+        assert!(matches!(result, Err(StreamError::UnknownKeyId { key_id: 1 })));
+    }
+}
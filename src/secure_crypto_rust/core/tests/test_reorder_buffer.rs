@@ -0,0 +1,169 @@
+// # 📂 `tests/test_reorder_buffer.rs`
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use crypto_core::{
+        stream_v2::{
+            io::{OrderedEncryptedWriter, OrderedPlaintextWriter},
+            segment_worker::{DecryptedSegment, EncryptedSegment},
+            segmenting::{SegmentHeader, types::{SegmentError, SegmentFlags}},
+        },
+        telemetry::{StageTimes, TelemetryCounters},
+        types::StreamError,
+    };
+
+    const LIMIT: usize = 4;
+
+    fn plaintext(segment_index: u32) -> Bytes {
+        Bytes::from(vec![segment_index as u8; 8])
+    }
+
+    fn decrypted(segment_index: u32, flags: SegmentFlags) -> DecryptedSegment {
+        let frames = if flags.contains(SegmentFlags::FINAL_SEGMENT) { Vec::new() } else { vec![plaintext(segment_index)] };
+        DecryptedSegment {
+            header: SegmentHeader::new(&Bytes::new(), segment_index, 8, 0, 0, flags),
+            frames,
+            counters: TelemetryCounters::default(),
+            stage_times: StageTimes::default(),
+        }
+    }
+
+    fn encrypted(segment_index: u32) -> EncryptedSegment {
+        encrypted_with(segment_index, plaintext(segment_index), SegmentFlags::empty())
+    }
+
+    fn encrypted_final(segment_index: u32) -> EncryptedSegment {
+        encrypted_with(segment_index, Bytes::new(), SegmentFlags::FINAL_SEGMENT)
+    }
+
+    fn encrypted_with(segment_index: u32, wire: Bytes, flags: SegmentFlags) -> EncryptedSegment {
+        EncryptedSegment {
+            header: SegmentHeader::new(&wire, segment_index, 8, 0, 0, flags),
+            wire,
+            counters: TelemetryCounters::default(),
+            stage_times: StageTimes::default(),
+        }
+    }
+
+    #[test]
+    fn stalled_segment_overflows_the_plaintext_writer() {
+        let mut out = Vec::new();
+        let mut writer = OrderedPlaintextWriter::new(&mut out).with_max_pending(LIMIT);
+
+        // Segment 0 stalls; later segments keep arriving
+        for i in 1..=LIMIT as u32 {
+            writer.push(decrypted(i, SegmentFlags::empty())).unwrap();
+        }
+        match writer.push(decrypted(LIMIT as u32 + 1, SegmentFlags::empty())) {
+            Err(StreamError::ReorderBufferOverflow { waiting_for, pending, limit }) => {
+                assert_eq!(waiting_for, 0);
+                assert_eq!(pending, LIMIT + 1);
+                assert_eq!(limit, LIMIT);
+            }
+            other => panic!("expected ReorderBufferOverflow, got {other:?}"),
+        }
+        drop(writer);
+        assert!(out.is_empty(), "nothing may be written past the stalled segment");
+    }
+
+    #[test]
+    fn late_segment_within_the_bound_drains_in_order() {
+        let mut out = Vec::new();
+        let mut writer = OrderedPlaintextWriter::new(&mut out).with_max_pending(LIMIT);
+
+        for i in (1..=LIMIT as u32).rev() {
+            writer.push(decrypted(i, SegmentFlags::empty())).unwrap();
+        }
+        writer.push(decrypted(0, SegmentFlags::empty())).unwrap();
+        writer.push(decrypted(LIMIT as u32 + 1, SegmentFlags::FINAL_SEGMENT)).unwrap();
+        writer.finish().unwrap();
+
+        let expected: Vec<u8> = (0..=LIMIT as u32).flat_map(|i| plaintext(i).to_vec()).collect();
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn unbounded_by_default() {
+        let mut out = Vec::new();
+        let mut writer = OrderedPlaintextWriter::new(&mut out);
+        for i in 1..200 {
+            writer.push(decrypted(i, SegmentFlags::empty())).unwrap();
+        }
+    }
+
+    #[test]
+    fn stalled_segment_overflows_the_encrypted_writer() {
+        let mut out = Vec::new();
+        let mut writer = OrderedEncryptedWriter::new(&mut out).with_max_pending(LIMIT);
+
+        for i in 1..=LIMIT as u32 {
+            writer.push(encrypted(i)).unwrap();
+        }
+        let err = writer.push(encrypted(LIMIT as u32 + 1)).unwrap_err();
+        assert!(matches!(err, StreamError::ReorderBufferOverflow { waiting_for: 0, .. }));
+        assert!(err.to_string().contains("behind segment 0"));
+    }
+
+    fn assert_validation(err: StreamError, expected: &str) {
+        match err {
+            StreamError::Validation(msg) => assert_eq!(msg, expected),
+            other => panic!("expected Validation({expected:?}), got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn plaintext_writer_rejects_segments_stranded_behind_a_gap() {
+        let mut out = Vec::new();
+        let mut writer = OrderedPlaintextWriter::new(&mut out);
+        for i in [0, 1, 3] {
+            writer.push(decrypted(i, SegmentFlags::empty())).unwrap();
+        }
+        writer.push(decrypted(4, SegmentFlags::FINAL_SEGMENT)).unwrap();
+
+        assert_validation(writer.finish().unwrap_err(), "missing segment 2, 2 segments stranded");
+        let expected: Vec<u8> = [0, 1].into_iter().flat_map(|i| plaintext(i).to_vec()).collect();
+        assert_eq!(out, expected, "nothing past the gap may be written");
+    }
+
+    #[test]
+    fn encrypted_writer_rejects_segments_stranded_behind_a_gap() {
+        let mut out = Vec::new();
+        let mut writer = OrderedEncryptedWriter::new(&mut out);
+        for i in [0, 1, 3] {
+            writer.push(encrypted(i)).unwrap();
+        }
+        writer.push(encrypted_final(4)).unwrap();
+
+        assert_validation(writer.finish().unwrap_err(), "missing segment 2, 2 segments stranded");
+        assert_eq!(writer.index().len(), 2);
+    }
+
+    #[test]
+    fn final_segment_must_be_the_last_one_written() {
+        let mut out = Vec::new();
+        let mut writer = OrderedEncryptedWriter::new(&mut out);
+        writer.push(encrypted(0)).unwrap();
+        writer.push(encrypted_final(1)).unwrap();
+        // The segment index refuses it as soon as it is written
+        match writer.push(encrypted(2)) {
+            Err(StreamError::Segment(SegmentError::Malformed(msg))) => assert_eq!(msg, "segment after final segment"),
+            other => panic!("expected the index to reject segment 2, got {other:?}"),
+        }
+
+        let mut out = Vec::new();
+        let mut writer = OrderedPlaintextWriter::new(&mut out);
+        writer.push(decrypted(0, SegmentFlags::empty())).unwrap();
+        writer.push(decrypted(1, SegmentFlags::FINAL_SEGMENT)).unwrap();
+        writer.push(decrypted(2, SegmentFlags::empty())).unwrap();
+        assert_validation(writer.finish().unwrap_err(), "final segment 1 is not the last segment written (2)");
+    }
+
+    #[test]
+    fn missing_final_segment_is_still_named() {
+        let mut out = Vec::new();
+        let mut writer = OrderedEncryptedWriter::new(&mut out);
+        writer.push(encrypted(0)).unwrap();
+        assert_validation(writer.finish().unwrap_err(), "Missing final segment");
+    }
+}
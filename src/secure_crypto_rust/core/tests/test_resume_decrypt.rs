@@ -0,0 +1,107 @@
+// # 📂 `tests/test_resume_decrypt.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crypto_core::{
+        headers::HeaderV1,
+        recovery::ResumePoint,
+        stream_v2::{
+            InputSource, OutputSink, decrypt_stream_v2_from,
+            core::{DecryptParams, EncryptParams, encrypt_stream_v2},
+            segmenting::{SegmentHeader, decode_segment_header},
+        },
+    };
+
+    use crate::common::{self, master_key};
+
+    const CHUNK: usize = 16 * 1024;
+
+    fn encrypt(plaintext: &[u8]) -> Vec<u8> {
+        let header = HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() };
+        let snapshot = encrypt_stream_v2(
+            InputSource::Memory(plaintext.to_vec()),
+            OutputSink::Memory,
+            &master_key(),
+            EncryptParams::new(header),
+            common::config(),
+        )
+        .expect("encryption should succeed");
+        snapshot.output.unwrap()
+    }
+
+    /// Byte offset of the `SegmentHeader` for `segment_index`.
+    fn segment_offset(ciphertext: &[u8], segment_index: u32) -> u64 {
+        let mut offset = HeaderV1::LEN;
+        loop {
+            let header = decode_segment_header(&ciphertext[offset..offset + SegmentHeader::LEN]).unwrap();
+            if header.segment_index == segment_index {
+                return offset as u64;
+            }
+            offset += SegmentHeader::LEN + header.wire_len as usize;
+        }
+    }
+
+    fn decrypt_from(ciphertext: Vec<u8>, resume_point: ResumePoint) -> Result<(Vec<u8>, u64), String> {
+        decrypt_stream_v2_from(
+            InputSource::Memory(ciphertext),
+            OutputSink::Memory,
+            &master_key(),
+            DecryptParams::new(),
+            common::config(),
+            resume_point,
+        )
+        .map(|s| (s.output.clone().unwrap_or_default(), s.segments_processed))
+        .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn resume_from_segment_five_of_ten() {
+        let plaintext: Vec<u8> = (0..10 * CHUNK).map(|i| (i % 253) as u8).collect();
+        let ciphertext = encrypt(&plaintext);
+
+        let offset = segment_offset(&ciphertext, 5);
+        let (out, segments) = decrypt_from(ciphertext, ResumePoint::new(offset, 5)).expect("resume should succeed");
+
+        assert_eq!(out, &plaintext[5 * CHUNK..]);
+        // Segments 5..=9 plus the final empty marker (index 10)
+        assert_eq!(segments, 6);
+    }
+
+    #[test]
+    fn resume_from_segment_zero_matches_full_decrypt() {
+        let plaintext: Vec<u8> = (0..3 * CHUNK + 100).map(|i| (i % 241) as u8).collect();
+        let ciphertext = encrypt(&plaintext);
+
+        let (out, _) = decrypt_from(ciphertext, ResumePoint::new(HeaderV1::LEN as u64, 0)).unwrap();
+        assert_eq!(out, plaintext);
+    }
+
+    #[test]
+    fn resume_rejects_index_mismatch() {
+        let plaintext = vec![0x42u8; 4 * CHUNK];
+        let ciphertext = encrypt(&plaintext);
+
+        let offset = segment_offset(&ciphertext, 2);
+        let err = decrypt_from(ciphertext, ResumePoint::new(offset, 3)).unwrap_err();
+        assert!(err.contains("resume point mismatch"), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn resume_rejects_offset_inside_header() {
+        let ciphertext = encrypt(&[0x01u8; 1024]);
+        assert!(decrypt_from(ciphertext, ResumePoint::new(10, 0)).is_err());
+    }
+
+    #[test]
+    fn resume_still_requires_final_segment() {
+        let plaintext = vec![0x42u8; 4 * CHUNK];
+        let mut ciphertext = encrypt(&plaintext);
+        let offset = segment_offset(&ciphertext, 2);
+
+        // Strip the trailing empty final segment
+        ciphertext.truncate(ciphertext.len() - SegmentHeader::LEN);
+        assert!(decrypt_from(ciphertext, ResumePoint::new(offset, 2)).is_err());
+    }
+}
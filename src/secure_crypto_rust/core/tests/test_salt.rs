@@ -0,0 +1,72 @@
+// # 📂 `tests/test_salt.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crypto_core::{
+        crypto::{generate_salt, is_degenerate_salt},
+        headers::HeaderV1,
+        stream_v2::{
+            EncryptingWriter, SaltPolicy,
+            core::{DecryptParams, EncryptParams},
+        },
+        types::StreamError,
+    };
+
+    use crate::common::{self, master_key};
+
+    fn plaintext() -> Vec<u8> {
+        b"same plaintext, same key\n".repeat(2048)
+    }
+
+    fn encrypt(params: EncryptParams) -> Result<Vec<u8>, StreamError> {
+        common::try_encrypt(&plaintext(), params, common::config())
+    }
+
+    fn decrypt(ciphertext: Vec<u8>) -> Vec<u8> {
+        common::decrypt(ciphertext, DecryptParams::new()).unwrap()
+    }
+
+    #[test]
+    fn default_headers_get_fresh_salts() {
+        let a = HeaderV1::default();
+        let b = HeaderV1::new_random();
+        assert_ne!(a.salt, b.salt);
+        assert!(!is_degenerate_salt(&a.salt) && !is_degenerate_salt(&b.salt));
+        assert_ne!(generate_salt(), generate_salt());
+    }
+
+    #[test]
+    fn default_params_never_repeat_ciphertext() {
+        let first = encrypt(EncryptParams::default()).unwrap();
+        let second = encrypt(EncryptParams::default()).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(decrypt(first), plaintext());
+        assert_eq!(decrypt(second), plaintext());
+    }
+
+    #[test]
+    fn degenerate_salts_are_rejected() {
+        for byte in [0x00u8, 0x01, 0x42, 0xFF] {
+            let header = HeaderV1::new_with_salt([byte; 16]);
+            let result = encrypt(EncryptParams::new(header));
+            assert!(matches!(result, Err(StreamError::Validation(ref msg)) if msg.contains("salt")), "{byte:#x}: {result:?}");
+
+            let writer = EncryptingWriter::new(Vec::new(), &master_key(), EncryptParams::new(header));
+            assert!(writer.is_err());
+        }
+    }
+
+    #[test]
+    fn deterministic_policy_reproduces_ciphertext() {
+        let header = HeaderV1::new_with_salt([0x42; 16]);
+        let params = EncryptParams::new(header).with_deterministic_salt();
+        assert_eq!(params.salt_policy, SaltPolicy::Deterministic);
+
+        let first = encrypt(params.clone()).unwrap();
+        let second = encrypt(params).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(decrypt(first), plaintext());
+    }
+}
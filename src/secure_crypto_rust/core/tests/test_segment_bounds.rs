@@ -0,0 +1,156 @@
+// # 📂 `tests/test_segment_bounds.rs`
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use proptest::prelude::*;
+    use crypto_core::{
+        constants::{DEFAULT_CHUNK_SIZE, MAX_CHUNK_SIZE},
+        crypto::DigestAlg,
+        stream_v2::{
+            framing::{FrameError, FrameHeader, MAX_FRAME_CIPHERTEXT_LEN, decode::parse_frame_header},
+            io::{PayloadReader, read_segment},
+            segmenting::{SegmentHeader, SegmentLimits, types::{SegmentError, SegmentFlags}},
+        },
+        types::StreamError,
+    };
+
+    /// Raw segment header bytes (little-endian), CRC left at zero.
+    fn raw_segment_header(bytes_len: u32, wire_len: u32, frame_count: u32, flags: SegmentFlags) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(SegmentHeader::LEN);
+        buf.extend_from_slice(&0u32.to_le_bytes()); // segment_index
+        buf.extend_from_slice(&bytes_len.to_le_bytes());
+        buf.extend_from_slice(&wire_len.to_le_bytes());
+        buf.extend_from_slice(&0u32.to_le_bytes()); // wire_crc32
+        buf.extend_from_slice(&frame_count.to_le_bytes());
+        buf.extend_from_slice(&(DigestAlg::Blake3 as u16).to_le_bytes()); // digest_alg
+        buf.extend_from_slice(&flags.bits().to_le_bytes());
+        buf.extend_from_slice(&0u16.to_le_bytes()); // reserved
+        buf
+    }
+
+    fn read(bytes: Vec<u8>) -> Result<Option<(SegmentHeader, bytes::Bytes)>, StreamError> {
+        let limits = SegmentLimits::for_chunk_size(DEFAULT_CHUNK_SIZE as u32);
+        read_segment(&mut PayloadReader::new(Cursor::new(bytes)).with_limits(limits)).map_err(StreamError::into_root)
+    }
+
+    #[test]
+    fn huge_wire_len_is_rejected_before_allocation() {
+        match read(raw_segment_header(1024, u32::MAX, 3, SegmentFlags::empty())) {
+            Err(StreamError::Segment(SegmentError::WireLenTooLarge { have, max })) => {
+                assert_eq!(have, u32::MAX);
+                assert_eq!(max, SegmentLimits::for_chunk_size(DEFAULT_CHUNK_SIZE as u32).max_wire_len);
+            }
+            other => panic!("expected WireLenTooLarge, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn bytes_len_above_chunk_size_is_rejected() {
+        let bytes_len = DEFAULT_CHUNK_SIZE as u32 + 1;
+        assert!(matches!(
+            read(raw_segment_header(bytes_len, 1024, 3, SegmentFlags::empty())),
+            Err(StreamError::Segment(SegmentError::BytesLenTooLarge { .. }))
+        ));
+    }
+
+    #[test]
+    fn frame_count_must_fit_wire_len() {
+        // Frames without wire, wire without frames, too many frames, too few frames
+        for (wire_len, frame_count) in [(0, 3), (100, 0), (100, 1000), ((FrameHeader::LEN + MAX_FRAME_CIPHERTEXT_LEN) as u32 + 1, 1)] {
+            let mut bytes = raw_segment_header(1024, wire_len, frame_count, SegmentFlags::empty());
+            bytes.resize(bytes.len() + wire_len as usize, 0);
+            assert!(
+                matches!(read(bytes), Err(StreamError::Segment(SegmentError::FrameCountMismatch { .. }))),
+                "wire_len {wire_len}, frame_count {frame_count}"
+            );
+        }
+    }
+
+    #[test]
+    fn empty_final_segment_is_accepted() {
+        let (header, wire) = read(raw_segment_header(0, 0, 0, SegmentFlags::FINAL_SEGMENT)).unwrap().unwrap();
+        assert!(header.flags.contains(SegmentFlags::FINAL_SEGMENT));
+        assert!(wire.is_empty());
+    }
+
+    #[test]
+    fn index_segment_may_exceed_the_chunk_size() {
+        let limits = SegmentLimits::for_chunk_size(DEFAULT_CHUNK_SIZE as u32);
+        let bytes_len = 4 * DEFAULT_CHUNK_SIZE as u32;
+        let wire_len = bytes_len + 64 * 1024;
+        let mut bytes = raw_segment_header(bytes_len, wire_len, 100, SegmentFlags::SEGMENT_INDEX);
+        bytes.resize(bytes.len() + wire_len as usize, 0);
+        assert!(read(bytes).is_ok());
+
+        // ...but not the largest supported chunk size
+        let header = SegmentHeader {
+            bytes_len: MAX_CHUNK_SIZE as u32 + 1,
+            ..SegmentHeader::new(&bytes::Bytes::new(), 0, 0, 0, 0, SegmentFlags::SEGMENT_INDEX)
+        };
+        assert!(matches!(limits.check(&header), Err(SegmentError::BytesLenTooLarge { .. })));
+    }
+
+    #[test]
+    fn huge_frame_ciphertext_len_is_rejected() {
+        let mut frame = vec![0u8; FrameHeader::LEN];
+        frame[0..4].copy_from_slice(b"SV2F");
+        frame[4] = 1; // version
+        frame[5] = 1; // data frame
+        frame[18..22].copy_from_slice(&u32::MAX.to_le_bytes());
+        match parse_frame_header(&frame) {
+            Err(FrameError::CiphertextTooLarge { have, max }) => {
+                assert_eq!(have, u32::MAX);
+                assert_eq!(max, MAX_FRAME_CIPHERTEXT_LEN);
+            }
+            other => panic!("expected CiphertextTooLarge, got {other:?}"),
+        }
+    }
+
+    proptest! {
+        /// Random header bytes never panic, and an accepted header never
+        /// yields a wire beyond the limits.
+        #[test]
+        fn prop_random_segment_headers_stay_bounded(
+            header in proptest::collection::vec(any::<u8>(), SegmentHeader::LEN),
+            tail in proptest::collection::vec(any::<u8>(), 0..256),
+        ) {
+            let limits = SegmentLimits::for_chunk_size(DEFAULT_CHUNK_SIZE as u32);
+            let mut bytes = header;
+            bytes.extend_from_slice(&tail);
+
+            if let Ok(Some((header, wire))) = read(bytes) {
+                prop_assert!(limits.check(&header).is_ok());
+                prop_assert_eq!(wire.len(), header.wire_len as usize);
+                prop_assert!(wire.len() <= tail.len());
+            }
+        }
+
+        /// Plausible but forged lengths: anything beyond the limits fails
+        /// with a `SegmentError` before the wire is read.
+        #[test]
+        fn prop_forged_lengths_fail_before_allocation(
+            bytes_len in 0u32..(2 * DEFAULT_CHUNK_SIZE as u32),
+            wire_len in prop_oneof![0u32..(2 * DEFAULT_CHUNK_SIZE as u32), any::<u32>()],
+            frame_count in prop_oneof![0u32..64, any::<u32>()],
+        ) {
+            let limits = SegmentLimits::for_chunk_size(DEFAULT_CHUNK_SIZE as u32);
+            let bytes = raw_segment_header(bytes_len, wire_len, frame_count, SegmentFlags::empty());
+
+            // No wire follows the header: only a rejected header avoids the read
+            match read(bytes) {
+                Err(StreamError::Segment(_)) => {}
+                Err(StreamError::Io(_)) => prop_assert!(wire_len as usize <= limits.max_wire_len),
+                other => prop_assert!(wire_len == 0, "unexpected {:?}", other),
+            }
+        }
+
+        #[test]
+        fn prop_random_frame_headers_stay_bounded(bytes in proptest::collection::vec(any::<u8>(), 0..64)) {
+            if let Ok(header) = parse_frame_header(&bytes) {
+                prop_assert!(header.ciphertext_len as usize <= MAX_FRAME_CIPHERTEXT_LEN);
+            }
+        }
+    }
+}
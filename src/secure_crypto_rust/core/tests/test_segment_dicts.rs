@@ -0,0 +1,181 @@
+// # 📂 `tests/test_segment_dicts.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+    use std::sync::Arc;
+
+    use crypto_core::{
+        compression::{CompressionCodec, CompressionError, DictSelector, DictionaryRegistry, train_dictionary},
+        constants::flags,
+        headers::{HeaderV1, decode_header_le},
+        stream_v2::{
+            EncryptingWriter,
+            compression_worker::CompressionWorkerError,
+            core::{ApiConfig, DecryptParams, EncryptParams},
+            io::iter_segment_headers,
+            segmenting::types::SegmentFlags,
+        },
+        types::StreamError,
+    };
+
+    use crate::common::{self, master_key};
+
+    const CHUNK: usize = 16 * 1024;
+    const JSON_DICT: u32 = 1;
+    const BLOB_DICT: u32 = 2;
+
+    fn json_record(i: usize) -> Vec<u8> {
+        format!(
+            "{{\"id\":{},\"user\":\"user-{}\",\"level\":\"{}\",\"msg\":\"request served\",\"region\":\"eu-west-{}\"}}\n",
+            i,
+            i % 97,
+            if i.is_multiple_of(3) { "warn" } else { "info" },
+            i % 4
+        )
+        .into_bytes()
+    }
+
+    fn blob_record(i: usize) -> Vec<u8> {
+        let mut record = b"BLB1".to_vec();
+        record.extend_from_slice(&(i as u32).to_le_bytes());
+        record.extend_from_slice(&((i % 5) as u16).to_le_bytes());
+        record.extend_from_slice(format!("sensor-{:08}", i % 300).as_bytes());
+        record.extend_from_slice(&(i as f64 * 0.5).to_le_bytes());
+        record.extend_from_slice(&[0u8; 8]);
+        record
+    }
+
+    fn trained(record: fn(usize) -> Vec<u8>) -> Vec<u8> {
+        let samples: Vec<Vec<u8>> = (0..1000).map(record).collect();
+        let refs: Vec<&[u8]> = samples.iter().map(|s| s.as_slice()).collect();
+        train_dictionary(&refs, 4096).expect("training should succeed")
+    }
+
+    fn registry() -> Arc<DictionaryRegistry> {
+        let mut registry = DictionaryRegistry::new();
+        registry.register(JSON_DICT, trained(json_record)).unwrap();
+        registry.register(BLOB_DICT, trained(blob_record)).unwrap();
+        Arc::new(registry)
+    }
+
+    /// JSON logs, then binary blobs: several segments of each.
+    fn plaintext() -> Vec<u8> {
+        let logs = (5000..5800).flat_map(json_record);
+        let blobs = (5000..7000).flat_map(blob_record);
+        logs.chain(blobs).collect()
+    }
+
+    /// JSON segments to the log dictionary, everything else to the blob one.
+    fn by_content() -> DictSelector {
+        DictSelector::new([JSON_DICT, BLOB_DICT], |_, bytes| Some(if bytes.first() == Some(&b'{') { JSON_DICT } else { BLOB_DICT }))
+    }
+
+    fn params(selector: DictSelector) -> EncryptParams<'static> {
+        let header = HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() };
+        EncryptParams::new(header)
+            .with_compression(CompressionCodec::Zstd, None)
+            .with_dict_selector(registry(), selector)
+    }
+
+    fn encrypt(params: EncryptParams) -> Result<Vec<u8>, StreamError> {
+        common::try_encrypt(&plaintext(), params, common::config())
+    }
+
+    fn decrypt(stream: Vec<u8>, params: DecryptParams, config: ApiConfig) -> Result<Vec<u8>, StreamError> {
+        common::try_decrypt(stream, &master_key(), params, config)
+    }
+
+    fn overridden(stream: &[u8]) -> Vec<bool> {
+        iter_segment_headers(Cursor::new(stream))
+            .map(|info| info.unwrap())
+            .filter(|info| info.bytes_len > 0)
+            .map(|info| info.flags.contains(SegmentFlags::DICT_OVERRIDE))
+            .collect()
+    }
+
+    #[test]
+    fn each_half_round_trips_with_its_own_dictionary() {
+        let stream = encrypt(params(by_content())).unwrap();
+        let header = decode_header_le(&stream[..HeaderV1::LEN]).unwrap();
+        assert_ne!(header.flags & flags::SEGMENT_DICTS, 0);
+        assert_eq!(header.flags & flags::DICT_USED, 0);
+        let overridden = overridden(&stream);
+        assert!(overridden.len() > 4 && overridden.iter().all(|&o| o), "{overridden:?}");
+
+        for in_order in [false, true] {
+            let config = common::config().with_decompress_in_order(in_order);
+            let out = decrypt(stream.clone(), DecryptParams::new().with_dictionaries(registry()), config).unwrap();
+            assert_eq!(out, plaintext(), "decompress_in_order = {in_order}");
+        }
+    }
+
+    #[test]
+    fn segments_the_selector_passes_on_keep_the_stream_dictionary() {
+        let only_blobs = DictSelector::new([BLOB_DICT], |_, bytes| (bytes.first() != Some(&b'{')).then_some(BLOB_DICT));
+        let stream = encrypt(params(only_blobs).with_dictionary(registry(), JSON_DICT)).unwrap();
+        let header = decode_header_le(&stream[..HeaderV1::LEN]).unwrap();
+        assert_eq!(header.dict_id, JSON_DICT);
+        let overridden = overridden(&stream);
+        assert!(overridden.contains(&true) && overridden.contains(&false), "{overridden:?}");
+
+        let out = decrypt(stream, DecryptParams::new().with_dictionaries(registry()), common::config()).unwrap();
+        assert_eq!(out, plaintext());
+    }
+
+    #[test]
+    fn encrypting_writer_selects_dictionaries_too() {
+        let mut writer = EncryptingWriter::new(Vec::new(), &master_key(), params(by_content())).unwrap();
+        writer.write_all(&plaintext()).unwrap();
+        let stream = writer.finish().unwrap();
+        assert!(overridden(&stream).iter().all(|&o| o));
+
+        let out = decrypt(stream, DecryptParams::new().with_dictionaries(registry()), common::config()).unwrap();
+        assert_eq!(out, plaintext());
+    }
+
+    #[test]
+    fn an_unregistered_id_fails_before_encryption() {
+        let unregistered = DictSelector::new([JSON_DICT, 9], |_, _| Some(JSON_DICT));
+        let err = params(unregistered).validate().unwrap_err();
+        assert!(matches!(err, StreamError::Compression(CompressionError::InvalidDictionary { dict_id: 9 })), "{err:?}");
+
+        // Nothing is selected yet: the writer refuses to start
+        let Err(err) = EncryptingWriter::new(Vec::new(), &master_key(), params(DictSelector::new([9], |_, _| None))) else {
+            panic!("writer accepted an unregistered dictionary id");
+        };
+        assert!(matches!(err, StreamError::Compression(CompressionError::InvalidDictionary { dict_id: 9 })), "{err:?}");
+    }
+
+    #[test]
+    fn an_undeclared_id_fails_encryption() {
+        let undeclared = DictSelector::new([JSON_DICT, BLOB_DICT], |segment_index, _| Some(if segment_index == 3 { 9 } else { JSON_DICT }));
+        let err = encrypt(params(undeclared)).unwrap_err();
+        assert!(matches!(err, StreamError::Compression(CompressionError::InvalidDictionary { dict_id: 9 })), "{err:?}");
+    }
+
+    #[test]
+    fn a_selector_needs_a_registry() {
+        let mut params = params(by_content());
+        params.dictionaries = Some(Arc::new(DictionaryRegistry::new()));
+        let err = encrypt(params).unwrap_err();
+        assert!(matches!(&err, StreamError::Validation(msg) if msg.contains("dict_selector")), "{err:?}");
+    }
+
+    #[test]
+    fn decryption_needs_the_selected_dictionaries() {
+        let stream = encrypt(params(by_content())).unwrap();
+        let err = decrypt(stream, DecryptParams::new(), common::config()).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                StreamError::CompressionWorker(CompressionWorkerError::Compression(CompressionError::InvalidDictionary {
+                    dict_id: JSON_DICT | BLOB_DICT
+                }))
+            ),
+            "{err:?}"
+        );
+    }
+}
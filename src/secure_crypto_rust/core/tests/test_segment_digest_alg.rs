@@ -0,0 +1,115 @@
+// # 📂 `tests/test_segment_digest_alg.rs`
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::sync::Arc;
+
+    use crypto_core::{
+        crypto::{DigestAlg, KEY_LEN_32},
+        headers::HeaderV1,
+        recovery::AsyncLogManager,
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{ApiConfig, DecryptParams, EncryptParams, decrypt_stream_v2, encrypt_stream_v2},
+            io::PayloadReader,
+            parallelism::HybridParallelismProfile,
+            pipeline::{PipelineConfig, run_decrypt_pipeline, run_encrypt_pipeline},
+            segment_worker::{DecryptContext, EncryptContext},
+            segmenting::{SegmentHeader, decode_segment_header, types::SegmentError},
+        },
+    };
+
+    const KEY: [u8; KEY_LEN_32] = [0x42u8; KEY_LEN_32];
+    /// Offset of `digest_alg` inside a segment header.
+    const DIGEST_ALG_OFFSET: usize = 20;
+
+    fn profile() -> HybridParallelismProfile {
+        HybridParallelismProfile::new(2, 0, 4)
+    }
+
+    fn plaintext() -> Vec<u8> {
+        b"streams name their own digest. ".repeat(2048)
+    }
+
+    /// Pipeline encryption keyed directly with `KEY` as the session key.
+    fn encrypt_with(digest_alg: DigestAlg, data: &[u8]) -> Vec<u8> {
+        let header = HeaderV1 { chunk_size: 16 * 1024, ..HeaderV1::test_header() };
+        let mut crypto = EncryptContext::new(header, profile(), &KEY, digest_alg).unwrap();
+        let mut out = Cursor::new(Vec::new());
+        run_encrypt_pipeline(
+            &mut PayloadReader::new(Cursor::new(data.to_vec())),
+            &mut out,
+            &mut crypto,
+            &PipelineConfig::new(profile(), None),
+            Arc::new(AsyncLogManager::disabled()),
+        )
+        .unwrap();
+        out.into_inner()
+    }
+
+    fn decrypt_with(digest_alg: DigestAlg, stream: Vec<u8>) -> Vec<u8> {
+        let (header, mut reader) = PayloadReader::with_header(Cursor::new(stream)).unwrap();
+        let mut crypto = DecryptContext::from_stream_header(header, profile(), &KEY, digest_alg).unwrap();
+        let mut out = Cursor::new(Vec::new());
+        run_decrypt_pipeline(
+            &mut reader,
+            &mut out,
+            &mut crypto,
+            &PipelineConfig::new(profile(), None),
+            Arc::new(AsyncLogManager::disabled()),
+        )
+        .unwrap();
+        out.into_inner()
+    }
+
+    fn api_stream(data: &[u8]) -> Vec<u8> {
+        let header = HeaderV1 { chunk_size: 16 * 1024, ..HeaderV1::test_header() };
+        encrypt_stream_v2(InputSource::Memory(data.to_vec()), OutputSink::Memory, &KEY, EncryptParams::new(header), ApiConfig::new(Some(true), None))
+            .unwrap()
+            .output
+            .unwrap()
+    }
+
+    fn api_decrypt(stream: Vec<u8>) -> Result<Vec<u8>, String> {
+        decrypt_stream_v2(InputSource::Memory(stream), OutputSink::Memory, &KEY, DecryptParams::new(), ApiConfig::new(Some(true), None))
+            .map(|snapshot| snapshot.output.unwrap_or_default())
+            .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn digest_alg_comes_from_the_segment_header() {
+        let data = plaintext();
+        for written in [DigestAlg::Sha512, DigestAlg::Sha3_256, DigestAlg::Blake3] {
+            let stream = encrypt_with(written, &data);
+            let first = decode_segment_header(&stream[HeaderV1::LEN..]).unwrap();
+            assert_eq!(first.digest_algorithm().unwrap(), written);
+
+            // The context's algorithm only applies to encryption
+            assert_eq!(decrypt_with(DigestAlg::Blake3, stream), data, "{written:?}");
+        }
+    }
+
+    #[test]
+    fn unknown_digest_alg_is_rejected_on_decode() {
+        let mut stream = api_stream(&plaintext());
+        let at = HeaderV1::LEN + DIGEST_ALG_OFFSET;
+        stream[at..at + 2].copy_from_slice(&0xBEEFu16.to_le_bytes());
+
+        assert!(matches!(
+            decode_segment_header(&stream[HeaderV1::LEN..HeaderV1::LEN + SegmentHeader::LEN]),
+            Err(SegmentError::UnknownDigestAlg { raw: 0xBEEF })
+        ));
+        let err = api_decrypt(stream).unwrap_err();
+        assert!(err.contains("unknown segment digest algorithm"), "{err}");
+    }
+
+    #[test]
+    fn header_alg_must_match_the_digest_frame() {
+        let mut stream = api_stream(&plaintext());
+        // Known id, but not the one the digest frame was written with
+        let at = HeaderV1::LEN + DIGEST_ALG_OFFSET;
+        stream[at..at + 2].copy_from_slice(&(DigestAlg::Sha256 as u16).to_le_bytes());
+        assert!(api_decrypt(stream).is_err());
+    }
+}
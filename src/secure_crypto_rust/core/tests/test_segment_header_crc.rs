@@ -0,0 +1,130 @@
+// # 📂 `tests/test_segment_header_crc.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use bytes::Bytes;
+    use crypto_core::{
+        constants::flags,
+        headers::HeaderV1,
+        stream_v2::{
+            core::{DecryptParams, EncryptParams},
+            io::iter_segment_headers,
+            segmenting::{SegmentHeader, decode_segment_header, encode_segment, layout, types::{SegmentError, SegmentFlags}},
+        },
+        types::{ErrorCode, StreamError},
+    };
+
+    use crate::common;
+
+    const CHUNK: usize = 16 * 1024;
+
+    fn plaintext() -> Vec<u8> {
+        common::plaintext(2 * CHUNK + 99)
+    }
+
+    fn encrypt(flags: u16) -> Vec<u8> {
+        let header = HeaderV1 { chunk_size: CHUNK as u32, flags, ..HeaderV1::test_header() };
+        common::encrypt(&plaintext(), EncryptParams::new(header))
+    }
+
+    fn decrypt(stream: Vec<u8>) -> Result<Vec<u8>, StreamError> {
+        common::decrypt(stream, DecryptParams::new())
+    }
+
+    fn v2_header() -> (SegmentHeader, [u8; SegmentHeader::LEN]) {
+        let wire = Bytes::from_static(b"frames");
+        let header = SegmentHeader::new(&wire, 0x0102_0304, 0x0A0B, 3, 2, SegmentFlags::COMPRESSED).with_header_crc();
+        let encoded = encode_segment(&header, &wire).unwrap();
+        (header, encoded[..SegmentHeader::LEN].try_into().unwrap())
+    }
+
+    #[test]
+    fn v2_header_round_trips_with_its_crc() {
+        let (header, bytes) = v2_header();
+        assert_eq!(bytes[SegmentHeader::VERSION_OFFSET], SegmentHeader::VERSION_2);
+        assert_eq!(bytes[SegmentHeader::HEADER_CRC_OFFSET], SegmentHeader::header_crc(&bytes));
+
+        let decoded = decode_segment_header(&bytes).unwrap();
+        assert_eq!(decoded.reserved, bytes[SegmentHeader::HEADER_CRC_OFFSET]);
+        assert_eq!(SegmentHeader { reserved: 0, ..decoded }, header);
+        decoded.require_header_crc(flags::SEGMENT_HEADER_CRC).unwrap();
+    }
+
+    #[test]
+    fn every_corrupted_field_byte_fails_the_header_crc() {
+        let (_, bytes) = v2_header();
+        for field in layout().iter().filter(|f| f.name != "version") {
+            for i in field.offset..field.offset + field.len {
+                for mask in [0x01u8, 0x80, 0xFF] {
+                    let mut corrupt = bytes;
+                    corrupt[i] ^= mask;
+                    assert!(
+                        matches!(decode_segment_header(&corrupt), Err(SegmentError::HeaderChecksumMismatch { .. })),
+                        "{} byte {i} ^ {mask:#04x}",
+                        field.name
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn a_downgraded_version_byte_is_caught_by_the_stream_flag() {
+        let (_, bytes) = v2_header();
+        let mut v1 = bytes;
+        v1[SegmentHeader::VERSION_OFFSET] = SegmentHeader::VERSION_1;
+        let decoded = decode_segment_header(&v1).unwrap();
+
+        decoded.require_header_crc(0).unwrap();
+        assert!(matches!(
+            decoded.require_header_crc(flags::SEGMENT_HEADER_CRC),
+            Err(SegmentError::HeaderChecksumMissing { segment_index: 0x0102_0304, version: 0 })
+        ));
+    }
+
+    #[test]
+    fn flagged_streams_write_v2_headers_and_fail_fast_on_corruption() {
+        let stream = encrypt(flags::SEGMENT_HEADER_CRC);
+        let first = HeaderV1::LEN;
+        assert_eq!(stream[first + SegmentHeader::VERSION_OFFSET], SegmentHeader::VERSION_2);
+        assert_eq!(decrypt(stream.clone()).unwrap(), plaintext());
+
+        // frame_count of the first segment
+        let mut corrupt = stream.clone();
+        corrupt[first + 16] ^= 0x01;
+        let err = decrypt(corrupt.clone()).unwrap_err();
+        assert!(matches!(err.root(), StreamError::Segment(SegmentError::HeaderChecksumMismatch { .. })), "{err:?}");
+        assert_eq!(err.offset(), Some(first as u64));
+        assert_eq!(err.code(), ErrorCode::SegmentMalformed);
+
+        let listed = iter_segment_headers(Cursor::new(corrupt)).next().unwrap();
+        assert!(matches!(listed, Err(StreamError::Segment(SegmentError::HeaderChecksumMismatch { .. }))));
+
+        let mut downgraded = stream;
+        downgraded[first + SegmentHeader::VERSION_OFFSET] = SegmentHeader::VERSION_1;
+        let err = decrypt(downgraded).unwrap_err();
+        assert!(matches!(err.root(), StreamError::Segment(SegmentError::HeaderChecksumMissing { .. })), "{err:?}");
+    }
+
+    #[test]
+    fn streams_without_the_flag_keep_v1_headers() {
+        let stream = encrypt(0);
+        for info in iter_segment_headers(Cursor::new(stream.clone())) {
+            let offset = info.unwrap().offset as usize;
+            assert_eq!(stream[offset + SegmentHeader::VERSION_OFFSET], SegmentHeader::VERSION_1);
+            assert_eq!(stream[offset + SegmentHeader::HEADER_CRC_OFFSET], 0);
+        }
+        assert_eq!(decrypt(stream).unwrap(), plaintext());
+    }
+
+    #[test]
+    fn new_headers_request_segment_crcs() {
+        assert_ne!(HeaderV1::default().flags & flags::SEGMENT_HEADER_CRC, 0);
+        assert_ne!(HeaderV1::builder().build().unwrap().flags & flags::SEGMENT_HEADER_CRC, 0);
+        assert_eq!(HeaderV1::test_header().flags & flags::SEGMENT_HEADER_CRC, 0);
+    }
+}
@@ -0,0 +1,168 @@
+// # 📂 `tests/test_segment_index_trailer.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read, Write};
+
+    use crypto_core::{
+        constants::flags,
+        headers::{HeaderExtensions, HeaderV1, decode_header_le},
+        stream_v2::{
+            DecryptingReader, EncryptingWriter, RandomAccessDecryptor,
+            core::{DecryptParams, EncryptParams},
+            io::{locate_segment_index, scan_segment_index},
+            segmenting::{
+                SegmentHeader, SegmentIndexEntry, SegmentIndexMap, decode_segment_header, decode_segment_index,
+                encode_segment_index, types::SegmentFlags,
+            },
+        },
+    };
+
+    use crate::common::{self, master_key};
+
+    const CHUNK: usize = 16 * 1024;
+
+    fn params(indexed: bool) -> EncryptParams<'static> {
+        let params = EncryptParams::new(HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() });
+        if indexed { params.with_segment_index() } else { params }
+    }
+
+    fn plaintext(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 239) as u8).collect()
+    }
+
+    fn encrypt(data: &[u8], indexed: bool) -> Vec<u8> {
+        common::encrypt(data, params(indexed))
+    }
+
+    fn decrypt(ciphertext: Vec<u8>) -> Vec<u8> {
+        common::decrypt(ciphertext, DecryptParams::new()).unwrap()
+    }
+
+    #[test]
+    fn flagged_stream_ends_with_index_trailer() {
+        let data = plaintext(4 * CHUNK + 9);
+        let ciphertext = encrypt(&data, true);
+
+        let scanned = scan_segment_index(&mut Cursor::new(&ciphertext)).unwrap();
+        let offset = locate_segment_index(&mut Cursor::new(&ciphertext)).unwrap().unwrap();
+        assert_eq!(offset, scanned.end_offset());
+
+        let trailer = decode_segment_header(&ciphertext[offset as usize..][..SegmentHeader::LEN]).unwrap();
+        assert!(trailer.flags.contains(SegmentFlags::SEGMENT_INDEX));
+        assert_eq!(trailer.segment_index as usize, scanned.len());
+        assert_eq!(
+            offset as usize + SegmentHeader::LEN + trailer.wire_len as usize + SegmentIndexMap::FOOTER_LEN,
+            ciphertext.len()
+        );
+
+        // Decoders stop at the final segment and ignore the trailer
+        assert_eq!(decrypt(ciphertext), data);
+    }
+
+    #[test]
+    fn unflagged_stream_has_no_trailer() {
+        let data = plaintext(3 * CHUNK);
+        let ciphertext = encrypt(&data, false);
+
+        let header = decode_header_le(&ciphertext[..HeaderV1::LEN]).unwrap();
+        assert_eq!(header.flags & flags::HAS_SEGMENT_INDEX, 0);
+        assert_eq!(locate_segment_index(&mut Cursor::new(&ciphertext)).unwrap(), None);
+        assert_eq!(scan_segment_index(&mut Cursor::new(&ciphertext)).unwrap().end_offset(), ciphertext.len() as u64);
+        assert_eq!(decrypt(ciphertext), data);
+    }
+
+    #[test]
+    fn random_access_uses_the_trailer() {
+        let data = plaintext(5 * CHUNK + 1);
+        let ciphertext = encrypt(&data, true);
+        let scanned = scan_segment_index(&mut Cursor::new(&ciphertext)).unwrap();
+
+        let mut reader = RandomAccessDecryptor::new(Cursor::new(ciphertext), &master_key(), DecryptParams::new()).unwrap();
+        assert_eq!(reader.index().unwrap(), &scanned);
+        assert_eq!(&reader.read_segment(5).unwrap()[..], &data[5 * CHUNK..]);
+        assert_eq!(&reader.read_segment(2).unwrap()[..], &data[2 * CHUNK..3 * CHUNK]);
+    }
+
+    #[test]
+    fn tampered_index_is_rejected_but_stream_still_decrypts() {
+        let data = plaintext(2 * CHUNK);
+        let mut ciphertext = encrypt(&data, true);
+        let offset = locate_segment_index(&mut Cursor::new(&ciphertext)).unwrap().unwrap() as usize;
+        ciphertext[offset + SegmentHeader::LEN + 12] ^= 0x80;
+
+        let mut reader =
+            RandomAccessDecryptor::new(Cursor::new(ciphertext.clone()), &master_key(), DecryptParams::new()).unwrap();
+        assert!(reader.index().is_err());
+
+        assert_eq!(decrypt(ciphertext), data);
+    }
+
+    #[test]
+    fn index_offsets_account_for_header_extensions() {
+        let data = plaintext(3 * CHUNK + 5);
+        let mut extensions = HeaderExtensions::new();
+        extensions.push(0x0001, vec![0xABu8; 37]);
+
+        let piped = common::encrypt(&data, params(true).with_extensions(extensions.clone()));
+        let mut writer =
+            EncryptingWriter::new(Vec::new(), &master_key(), params(true).with_extensions(extensions)).unwrap();
+        writer.write_all(&data).unwrap();
+        let written = writer.finish().unwrap();
+
+        for ciphertext in [piped, written] {
+            let header = decode_header_le(&ciphertext[..HeaderV1::LEN]).unwrap();
+            assert!(header.encoded_len() > HeaderV1::LEN);
+            let scanned = scan_segment_index(&mut Cursor::new(&ciphertext)).unwrap();
+            assert_eq!(scanned.get(0).unwrap().offset, header.encoded_len() as u64);
+            assert_eq!(locate_segment_index(&mut Cursor::new(&ciphertext)).unwrap(), Some(scanned.end_offset()));
+
+            let mut reader =
+                RandomAccessDecryptor::new(Cursor::new(ciphertext.clone()), &master_key(), DecryptParams::new()).unwrap();
+            assert_eq!(reader.index().unwrap(), &scanned);
+            assert_eq!(&reader.read_segment(1).unwrap()[..], &data[CHUNK..2 * CHUNK]);
+            assert_eq!(decrypt(ciphertext), data);
+        }
+    }
+
+    #[test]
+    fn adapters_write_and_skip_the_trailer() {
+        let data = plaintext(3 * CHUNK + 100);
+        let mut writer = EncryptingWriter::new(Vec::new(), &master_key(), params(true)).unwrap();
+        writer.write_all(&data).unwrap();
+        let ciphertext = writer.finish().unwrap();
+
+        assert_eq!(ciphertext.len(), encrypt(&data, true).len());
+        assert!(locate_segment_index(&mut Cursor::new(&ciphertext)).unwrap().is_some());
+
+        let mut reader = DecryptingReader::new(ciphertext.as_slice(), &master_key(), DecryptParams::new()).unwrap();
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+        assert_eq!(out, data);
+    }
+
+    #[test]
+    fn index_payload_roundtrip_and_validation() {
+        let mut index = SegmentIndexMap::new();
+        for (i, wire_len) in [(0u32, 100u32), (1, 250), (2, 0)] {
+            let flags = if i == 2 { SegmentFlags::FINAL_SEGMENT } else { SegmentFlags::empty() };
+            let wire = bytes::Bytes::from(vec![0u8; wire_len as usize]);
+            index.push(&SegmentHeader::new(&wire, i, wire_len, 0, 0, flags)).unwrap();
+        }
+
+        let encoded = encode_segment_index(&index);
+        assert_eq!(encoded.len(), 4 + 3 * SegmentIndexMap::ENTRY_LEN);
+        assert_eq!(decode_segment_index(&encoded, HeaderV1::LEN as u64).unwrap(), index);
+
+        assert!(decode_segment_index(&encoded[..encoded.len() - 1], HeaderV1::LEN as u64).is_err());
+
+        // Entries must describe contiguous segments
+        let mut entries: Vec<SegmentIndexEntry> = index.entries().to_vec();
+        entries[1].offset += 1;
+        assert!(SegmentIndexMap::from_entries(&entries, HeaderV1::LEN as u64).is_err());
+        // ...from the payload offset the caller gives
+        assert!(SegmentIndexMap::from_entries(index.entries(), HeaderV1::LEN as u64 + 8).is_err());
+    }
+}
@@ -0,0 +1,141 @@
+// # 📂 `tests/test_segment_metrics.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crypto_core::{
+        headers::{HeaderV1, Strategy},
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{ApiConfig, DecryptParams, EncryptParams, decrypt_stream_v2, encrypt_stream_v2},
+        },
+        telemetry::{SegmentRecord, SegmentRecorder, SegmentStats, TelemetrySnapshot, WorkerUsage},
+    };
+
+    use crate::common::{self, master_key};
+
+    const CHUNK: usize = 16 * 1024;
+    const SEGMENTS: usize = 50;
+
+    fn encrypt(collect_metrics: Option<bool>) -> TelemetrySnapshot {
+        let header = HeaderV1 {
+            chunk_size: CHUNK as u32,
+            strategy: Strategy::Parallel as u16,
+            ..HeaderV1::test_header()
+        };
+        let data = common::plaintext(SEGMENTS * CHUNK);
+        encrypt_stream_v2(
+            InputSource::Memory(data),
+            OutputSink::Memory,
+            &master_key(),
+            EncryptParams::new(header),
+            ApiConfig::new(Some(true), collect_metrics),
+        )
+        .unwrap()
+    }
+
+    fn assert_covers_run(snapshot: &TelemetrySnapshot) {
+        // 50 data segments + the empty final segment
+        assert_eq!(snapshot.segments_processed, SEGMENTS as u64 + 1);
+        assert_eq!(snapshot.segment_records.len() as u64, snapshot.segments_processed);
+        let indices: Vec<u32> = snapshot.segment_records.iter().map(|r| r.segment_index).collect();
+        assert_eq!(indices, (0..=SEGMENTS as u32).collect::<Vec<_>>());
+        assert!(snapshot.segment_records.iter().all(|r| r.worker_id < snapshot.cpu_workers));
+
+        let stats = snapshot.segment_stats().expect("segment stats");
+        assert_eq!(stats.segments, snapshot.segments_processed);
+        assert_eq!(stats.workers.iter().map(|w| w.segments).sum::<u64>(), snapshot.segments_processed);
+        assert_eq!(stats.sampled, snapshot.segment_records.len());
+        assert!(stats.min <= stats.mean && stats.mean <= stats.max);
+        assert!(stats.min <= stats.p95 && stats.p95 <= stats.max);
+        assert!(stats.max > Duration::ZERO);
+        assert_eq!(stats.utilization.len(), stats.workers.len());
+        assert!(stats.utilization.iter().all(|u| *u >= 0.0));
+    }
+
+    #[test]
+    fn parallel_encrypt_records_every_segment() {
+        let snapshot = encrypt(Some(true));
+        assert_covers_run(&snapshot);
+        let (data, last) = snapshot.segment_records.split_at(SEGMENTS);
+        assert!(data.iter().all(|r| r.wire_len > 0));
+        assert_eq!(last[0].wire_len, 0);
+    }
+
+    #[test]
+    fn decrypt_records_every_segment() {
+        let stream = encrypt(None).output.unwrap();
+        let snapshot = decrypt_stream_v2(
+            InputSource::Memory(stream),
+            OutputSink::Memory,
+            &master_key(),
+            DecryptParams::new(),
+            ApiConfig::new(Some(false), Some(true)),
+        )
+        .unwrap();
+        assert_covers_run(&snapshot);
+    }
+
+    #[test]
+    fn disabled_metrics_leave_records_empty() {
+        for collect_metrics in [None, Some(false)] {
+            let snapshot = encrypt(collect_metrics);
+            assert!(snapshot.segment_records.is_empty());
+            assert!(snapshot.worker_usage.is_empty());
+            assert_eq!(snapshot.segment_stats(), None);
+        }
+    }
+
+    #[test]
+    fn records_survive_json() {
+        let snapshot = encrypt(Some(true));
+        let back = TelemetrySnapshot::from_json(&snapshot.to_json()).unwrap();
+        assert_eq!(back.segment_records, snapshot.segment_records);
+        assert_eq!(back.worker_usage, snapshot.worker_usage);
+    }
+
+    // --- Recorder ---
+
+    fn record(segment_index: u32, worker_id: usize, millis: u64) -> SegmentRecord {
+        SegmentRecord { segment_index, wire_len: 100, duration: Duration::from_millis(millis), worker_id }
+    }
+
+    #[test]
+    fn recorder_samples_beyond_capacity() {
+        let recorder = SegmentRecorder::with_capacity(10);
+        for i in 0..1000 {
+            recorder.record(record(i, (i % 3) as usize, 1));
+        }
+        let (records, workers) = recorder.drain();
+
+        // Retention is capped, the counts are not
+        assert_eq!(records.len(), 10);
+        assert_eq!(workers.iter().map(|w| w.segments).sum::<u64>(), 1000);
+        assert_eq!(workers[0], WorkerUsage { segments: 334, busy: Duration::from_millis(334) });
+        // Sorted, distinct, and not simply the first ten segments
+        assert!(records.windows(2).all(|w| w[0].segment_index < w[1].segment_index));
+        assert!(records.iter().any(|r| r.segment_index >= 10));
+
+        // Drained: the next run starts empty
+        assert_eq!(recorder.drain(), (Vec::new(), Vec::new()));
+    }
+
+    #[test]
+    fn stats_over_known_durations() {
+        let records: Vec<SegmentRecord> = (1..=100).map(|i| record(i, 0, u64::from(i))).collect();
+        let workers = [WorkerUsage { segments: 100, busy: Duration::from_millis(5050) }];
+        let stats = SegmentStats::compute(&records, &workers, Duration::from_millis(10_100)).unwrap();
+
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.max, Duration::from_millis(100));
+        assert_eq!(stats.mean, Duration::from_micros(50_500));
+        assert_eq!(stats.p95, Duration::from_millis(95));
+        assert_eq!(stats.utilization.len(), 1);
+        assert!((stats.utilization[0] - 0.5).abs() < 1e-9);
+
+        assert_eq!(SegmentStats::compute(&[], &workers, Duration::from_secs(1)), None);
+    }
+}
@@ -0,0 +1,118 @@
+// # 📂 `tests/test_segment_routing.rs`
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use bytes::Bytes;
+    use crossbeam::channel;
+    use crypto_core::{
+        compression::CompressionError,
+        headers::HeaderV1,
+        stream_v2::{
+            InputSource, OutputSink,
+            compression_pipeline::spawn_routed_compression_workers,
+            compression_worker::CompressionBackend,
+            core::{ApiConfig, EncryptParams, encrypt_stream_v2},
+            parallelism::{Scheduler, WorkerTarget},
+            segment_worker::EncryptSegmentInput,
+            segmenting::types::SegmentFlags,
+        },
+        telemetry::StageTimes,
+    };
+
+    const THRESHOLD: usize = 64 * 1024;
+
+    /// Passes data through and records the size of every segment it saw.
+    struct Recorder(Arc<Mutex<Vec<usize>>>);
+
+    impl CompressionBackend for Recorder {
+        fn compress_chunk(&mut self, input: &[u8]) -> Result<Vec<u8>, CompressionError> {
+            self.0.lock().unwrap().push(input.len());
+            Ok(input.to_vec())
+        }
+        fn decompress_chunk(&mut self, input: &[u8]) -> Result<Vec<u8>, CompressionError> {
+            Ok(input.to_vec())
+        }
+    }
+
+    fn segment(segment_index: u32, len: usize) -> EncryptSegmentInput {
+        EncryptSegmentInput {
+            segment_index,
+            bytes: Bytes::from(vec![0xA5u8; len]),
+            flags: SegmentFlags::empty(),
+            stage_times: StageTimes::default(),
+        }
+    }
+
+    #[test]
+    fn large_segments_go_to_gpu_small_stay_on_cpu() {
+        let cpu_seen = Arc::new(Mutex::new(Vec::new()));
+        let gpu_seen = Arc::new(Mutex::new(Vec::new()));
+        let (tx_in, rx_in) = channel::unbounded();
+        let (tx_out, rx_out) = channel::unbounded();
+
+        let scheduler = spawn_routed_compression_workers(
+            vec![Box::new(Recorder(cpu_seen.clone()))],
+            vec![Box::new(Recorder(gpu_seen.clone()))],
+            THRESHOLD,
+            4,
+            rx_in,
+            tx_out,
+        );
+
+        let sizes = [1024, 2 * THRESHOLD, 2048, THRESHOLD, 4 * THRESHOLD];
+        for (i, len) in sizes.iter().enumerate() {
+            tx_in.send(segment(i as u32, *len)).unwrap();
+        }
+        drop(tx_in);
+
+        let outputs: Vec<EncryptSegmentInput> = rx_out.iter().map(|res| res.unwrap()).collect();
+        assert_eq!(outputs.len(), sizes.len());
+
+        let mut cpu = cpu_seen.lock().unwrap().clone();
+        let mut gpu = gpu_seen.lock().unwrap().clone();
+        cpu.sort();
+        gpu.sort();
+        assert_eq!(cpu, [1024, 2048]);
+        assert_eq!(gpu, [THRESHOLD, 2 * THRESHOLD, 4 * THRESHOLD]);
+
+        // Load stays booked until the consumer releases each segment
+        let mut sched = scheduler.lock().unwrap();
+        assert_eq!((sched.segments_cpu(), sched.segments_gpu()), (2, 3));
+        assert_eq!(sched.load(&WorkerTarget::Gpu(0)), Some(3));
+        for seg in &outputs {
+            assert!(sched.release(seg.segment_index).is_some());
+        }
+        assert_eq!(sched.load(&WorkerTarget::Cpu(0)), Some(0));
+        assert_eq!(sched.load(&WorkerTarget::Gpu(0)), Some(0));
+        assert!(sched.release(0).is_none());
+    }
+
+    #[test]
+    fn assign_and_release_track_targets() {
+        let mut sched = Scheduler::new(2, 0, THRESHOLD);
+        let first = sched.assign(0, 10);
+        let second = sched.assign(1, 4 * THRESHOLD);
+        assert_ne!(first, second, "two CPU workers should share the load");
+        assert!(matches!(second, WorkerTarget::Cpu(_)), "no GPU: everything stays on CPU");
+        assert_eq!(sched.release(1), Some(second));
+        assert_eq!(sched.release(0), Some(first));
+        assert_eq!(sched.segments_gpu(), 0);
+    }
+
+    #[test]
+    fn small_chunk_streams_never_touch_a_gpu() {
+        let header = HeaderV1 { chunk_size: 16 * 1024, ..HeaderV1::test_header() };
+        let snapshot = encrypt_stream_v2(
+            InputSource::Memory(vec![7u8; 100 * 1024]),
+            OutputSink::Memory,
+            &[0x11u8; 32],
+            EncryptParams::new(header),
+            ApiConfig::new(Some(true), None),
+        )
+        .unwrap();
+        assert_eq!(snapshot.segments_gpu, 0);
+        assert_eq!(snapshot.segments_cpu, snapshot.segments_processed);
+    }
+}
@@ -0,0 +1,119 @@
+// # 📂 `tests/test_segment_validation.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use bytes::Bytes;
+    use crypto_core::{
+        headers::HeaderV1,
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{ApiConfig, DecryptParams, EncryptParams, decrypt_stream_v2},
+            segmenting::{
+                SegmentHeader, decode_segment_header,
+                types::{SegmentError, SegmentFlags},
+            },
+        },
+        telemetry::{Stage, TelemetrySnapshot},
+        types::{ErrorCode, StreamError},
+    };
+
+    use crate::common::{self, master_key};
+
+    const CHUNK: usize = 64 * 1024;
+    /// `wire_crc32` offset in the segment header.
+    const CRC_OFFSET: usize = 12;
+
+    fn encrypt(len: usize) -> Vec<u8> {
+        let header = HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() };
+        common::encrypt(&common::plaintext(len), EncryptParams::new(header))
+    }
+
+    fn decrypt(stream: Vec<u8>) -> Result<TelemetrySnapshot, StreamError> {
+        decrypt_stream_v2(
+            InputSource::Memory(stream),
+            OutputSink::Memory,
+            &master_key(),
+            DecryptParams::new(),
+            ApiConfig::new(Some(true), Some(true)),
+        )
+    }
+
+    /// Offset of the last segment header (the empty final segment).
+    fn final_segment_offset(stream: &[u8]) -> usize {
+        let header = decode_segment_header(&stream[stream.len() - SegmentHeader::LEN..]).unwrap();
+        assert!(header.flags.contains(SegmentFlags::FINAL_SEGMENT) && header.wire_len == 0);
+        stream.len() - SegmentHeader::LEN
+    }
+
+    #[test]
+    fn corrupt_crc_of_final_segment_fails() {
+        let mut stream = encrypt(3 * CHUNK);
+        let offset = final_segment_offset(&stream);
+        stream[offset + CRC_OFFSET] ^= 0x01;
+
+        let err = decrypt(stream).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::SegmentMalformed, "{err}");
+    }
+
+    #[test]
+    fn empty_final_segment_must_be_consistent() {
+        let empty = Bytes::new();
+        let header = SegmentHeader::new(&empty, 4, 0, 0, 0, SegmentFlags::FINAL_SEGMENT);
+        assert!(header.validate(&empty).is_ok());
+
+        let forged = SegmentHeader { frame_count: 7, ..header };
+        assert!(matches!(forged.validate(&empty), Err(SegmentError::FrameCountMismatch { frame_count: 7, .. })));
+
+        let forged = SegmentHeader { bytes_len: 100, ..header };
+        assert!(matches!(forged.validate(&empty), Err(SegmentError::Malformed(_))));
+
+        let forged = SegmentHeader { wire_crc32: 0xDEAD_BEEF, ..header };
+        assert!(matches!(forged.validate(&empty), Err(SegmentError::Malformed(_))));
+
+        // Declared wire bytes that are not there
+        let forged = SegmentHeader { wire_len: 32, ..header };
+        assert!(matches!(forged.validate(&empty), Err(SegmentError::LengthMismatch { expected: 32, actual: 0 })));
+    }
+
+    #[test]
+    fn validate_stage_is_timed() {
+        let snapshot = decrypt(encrypt(4 * CHUNK)).unwrap();
+        assert!(snapshot.stage_times.get(Stage::Validate) > Duration::ZERO);
+    }
+
+    /// One CRC pass over every segment wire, timed here; the pipeline's
+    /// Validate stage should cost about one such pass, not two.
+    ///
+    /// Timing-based: run with `cargo test --test test_segment_validation -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn validate_stage_costs_one_crc_pass() {
+        let stream = encrypt(64 * CHUNK);
+        let mut segments = Vec::new();
+        let mut offset = HeaderV1::LEN;
+        while offset < stream.len() {
+            let header = decode_segment_header(&stream[offset..]).unwrap();
+            let end = offset + header.total_len().unwrap();
+            segments.push((header, Bytes::copy_from_slice(&stream[offset + SegmentHeader::LEN..end])));
+            offset = end;
+        }
+
+        let best = |run: &dyn Fn() -> Duration| (0..5).map(|_| run()).min().unwrap();
+        let one_pass = best(&|| {
+            let start = Instant::now();
+            for (header, wire) in &segments {
+                header.validate(wire).unwrap();
+            }
+            start.elapsed()
+        });
+        let stage = best(&|| decrypt(stream.clone()).unwrap().stage_times.get(Stage::Validate));
+
+        let ratio = stage.as_secs_f64() / one_pass.as_secs_f64();
+        println!("validate stage {stage:?}, one CRC pass {one_pass:?}, ratio {ratio:.2}");
+        assert!(ratio < 1.5, "Validate stage costs {ratio:.2} CRC passes");
+    }
+}
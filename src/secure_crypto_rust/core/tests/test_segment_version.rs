@@ -0,0 +1,109 @@
+// # 📂 `tests/test_segment_version.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use crypto_core::{
+        headers::HeaderV1,
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{DecryptParams, EncryptParams, decrypt_stream_v2},
+            segmenting::{SegmentHeader, decode_segment, decode_segment_header, encode_segment, types::{SegmentError, SegmentFlags}},
+        },
+        types::{ErrorCode, StreamError},
+    };
+
+    use crate::common::{self, master_key};
+
+    /// A v1 segment header as written before the version byte existed.
+    const V1_HEADER: [u8; SegmentHeader::LEN] = [
+        0x07, 0x00, 0x00, 0x00, // segment_index 7
+        0x00, 0x10, 0x00, 0x00, // bytes_len 4096
+        0x05, 0x00, 0x00, 0x00, // wire_len 5
+        0x78, 0x56, 0x34, 0x12, // wire_crc32
+        0x03, 0x00, 0x00, 0x00, // frame_count 3
+        0x02, 0x00,             // digest_alg: Sha256
+        0x02, 0x00,             // flags: COMPRESSED
+        0x00, 0x00,             // version 0 (v1), reserved
+    ];
+
+    fn encrypt() -> Vec<u8> {
+        let header = HeaderV1 { chunk_size: 16 * 1024, ..HeaderV1::test_header() };
+        common::encrypt(&b"segment versions\n".repeat(2048), EncryptParams::new(header))
+    }
+
+    fn decrypt(stream: Vec<u8>) -> Result<Vec<u8>, StreamError> {
+        let snapshot = decrypt_stream_v2(
+            InputSource::Memory(stream),
+            OutputSink::Memory,
+            &master_key(),
+            DecryptParams::new(),
+            common::config(),
+        )?;
+        Ok(snapshot.output.unwrap())
+    }
+
+    #[test]
+    fn v1_fixture_decodes_and_reencodes_byte_identically() {
+        let header = decode_segment_header(&V1_HEADER).unwrap();
+        assert_eq!(header.version, SegmentHeader::VERSION_1);
+        assert_eq!(header.segment_index, 7);
+        assert_eq!(header.bytes_len, 4096);
+        assert_eq!(header.wire_len, 5);
+        assert_eq!(header.wire_crc32, 0x1234_5678);
+        assert_eq!(header.flags, SegmentFlags::COMPRESSED);
+
+        let encoded = encode_segment(&header, &Bytes::from_static(b"abcde")).unwrap();
+        assert_eq!(&encoded[..SegmentHeader::LEN], &V1_HEADER);
+    }
+
+    #[test]
+    fn new_headers_are_v1() {
+        let wire = Bytes::from_static(b"frames");
+        let header = SegmentHeader::new(&wire, 0, 6, 1, 0, SegmentFlags::empty());
+        assert_eq!(header.version, SegmentHeader::VERSION);
+        assert_eq!(SegmentHeader::VERSION, SegmentHeader::VERSION_1);
+        let encoded = encode_segment(&header, &wire).unwrap();
+        assert_eq!(encoded[SegmentHeader::VERSION_OFFSET], 0);
+    }
+
+    #[test]
+    fn future_version_is_rejected_on_decode() {
+        let mut bytes = V1_HEADER;
+        bytes[SegmentHeader::VERSION_OFFSET] = 2;
+        assert!(matches!(decode_segment_header(&bytes), Err(SegmentError::UnsupportedVersion { version: 2 })));
+
+        let mut segment = bytes.to_vec();
+        segment.extend_from_slice(b"abcde");
+        assert!(matches!(decode_segment(&segment), Err(SegmentError::UnsupportedVersion { version: 2 })));
+        assert_eq!(
+            SegmentError::UnsupportedVersion { version: 2 }.to_string(),
+            "unsupported segment header version: 2"
+        );
+    }
+
+    #[test]
+    fn future_version_is_rejected_on_encode() {
+        let wire = Bytes::from_static(b"frames");
+        let header = SegmentHeader { version: 2, ..SegmentHeader::new(&wire, 0, 6, 1, 0, SegmentFlags::empty()) };
+        assert!(matches!(encode_segment(&header, &wire), Err(SegmentError::UnsupportedVersion { version: 2 })));
+    }
+
+    #[test]
+    fn v1_stream_roundtrips_and_future_segment_fails_the_stream() {
+        let stream = encrypt();
+        assert_eq!(stream[HeaderV1::LEN + SegmentHeader::VERSION_OFFSET], SegmentHeader::VERSION_1);
+        assert_eq!(decrypt(stream.clone()).unwrap(), b"segment versions\n".repeat(2048));
+
+        let mut future = stream;
+        future[HeaderV1::LEN + SegmentHeader::VERSION_OFFSET] = 0x7F;
+        let err = decrypt(future).unwrap_err();
+        assert!(
+            matches!(err.root(), StreamError::Segment(SegmentError::UnsupportedVersion { version: 0x7F })),
+            "{err:?}"
+        );
+        assert_eq!(err.code(), ErrorCode::SegmentMalformed);
+    }
+}
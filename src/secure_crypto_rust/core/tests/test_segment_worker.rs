@@ -14,15 +14,18 @@ use crypto_core::stream_v2::segment_worker::{
     DecryptSegmentInput, DecryptSegmentWorker, EncryptSegmentInput, EncryptSegmentWorker, EncryptedSegment, EncryptContext, DecryptContext, SegmentWorkerError
 };
 use crypto_core::recovery::persist::AsyncLogManager;
-use crypto_core::stream_v2::segmenting::types::SegmentFlags;
+use crypto_core::stream_v2::frame_worker::FrameWorkerError;
+use crypto_core::stream_v2::framing::FrameHeader;
+use crypto_core::stream_v2::segmenting::types::{SegmentError, SegmentFlags, SegmentHeader};
 use crypto_core::telemetry::StageTimes;
+use crypto_core::types::{ErrorCode, StreamError};
 
     fn setup_enc_context(alg: DigestAlg) -> (EncryptContext, Arc<AsyncLogManager>) {
         let header = HeaderV1::test_header(); // Mock header
         let profile = HybridParallelismProfile::dynamic(header.chunk_size as u32, 0.50, 64);
        // Create a Vec of 32 bytes
         let session_key = vec![0x42u8; KEY_LEN_32];
-        let log_manager = Arc::new(AsyncLogManager::new("test_audit.log", 100).unwrap());
+        let log_manager = Arc::new(AsyncLogManager::disabled());
         
         let context = EncryptContext::new(
             header,
@@ -38,7 +41,7 @@ use crypto_core::telemetry::StageTimes;
         let profile = HybridParallelismProfile::dynamic(header.chunk_size as u32, 0.50, 64);
        // Create a Vec of 32 bytes
         let session_key = vec![0x42u8; KEY_LEN_32];
-        let log_manager = Arc::new(AsyncLogManager::new("test_audit.log", 100).unwrap());
+        let log_manager = Arc::new(AsyncLogManager::disabled());
         
         let context = DecryptContext::from_stream_header(
             header,
@@ -87,11 +90,11 @@ use crypto_core::telemetry::StageTimes;
         let (bridge_tx, bridge_rx) = unbounded();
         let (dec_tx, dec_rx) = unbounded();
 
-        enc.run_v2(enc_rx, mid_tx);
+        let enc_worker = enc.run_v2(enc_rx, mid_tx);
         // bridge converts EncryptedSegment → DecryptSegmentInput
         forward_encrypted_to_decrypt(mid_rx, bridge_tx);
         //
-        dec.run_v2(bridge_rx, dec_tx);
+        let dec_worker = dec.run_v2(bridge_rx, dec_tx);
 
         let plaintext = Bytes::from_static(b"hello segmented crypto world");
 
@@ -103,10 +106,15 @@ use crypto_core::telemetry::StageTimes;
         }).unwrap();
 
         let encrypted = dec_rx.recv().unwrap().unwrap();
-        let reassembled = encrypted.bytes;
+        let reassembled = encrypted.to_bytes();
 
         assert_eq!(reassembled, plaintext);
         assert_eq!(encrypted.header.segment_index, 7);
+
+        // Closing the input lets the workers exit
+        drop(enc_tx);
+        enc_worker.join().unwrap();
+        dec_worker.join().unwrap();
     }
 
     // ## ✅ 2. Large segment (multi-frame, parallelism)
@@ -124,11 +132,11 @@ use crypto_core::telemetry::StageTimes;
         let (bridge_tx, bridge_rx) = unbounded();
         let (dec_tx, dec_rx) = unbounded();
 
-        enc.run_v2(enc_rx, mid_tx);
+        let enc_worker = enc.run_v2(enc_rx, mid_tx);
         // bridge converts EncryptedSegment → DecryptSegmentInput
         forward_encrypted_to_decrypt(mid_rx, bridge_tx);
         //
-        dec.run_v2(bridge_rx, dec_tx);
+        let dec_worker = dec.run_v2(bridge_rx, dec_tx);
 
         let data = vec![0xAB; 2 * 1024 * 1024];
         let plaintext = Bytes::from(data.clone());
@@ -141,9 +149,14 @@ use crypto_core::telemetry::StageTimes;
         }).unwrap();
 
         let decrypted = dec_rx.recv().unwrap().unwrap();
-        let out = decrypted.bytes;
+        let out = decrypted.to_bytes();
 
         assert_eq!(out, data);
+
+        // Closing the input lets the workers exit
+        drop(enc_tx);
+        enc_worker.join().unwrap();
+        dec_worker.join().unwrap();
     }
 
     // ## ❌ 3. Corrupted ciphertext → digest failure
@@ -162,7 +175,7 @@ use crypto_core::telemetry::StageTimes;
         let (dec_tx, dec_rx) = unbounded();
         
         // give one clone to the encrypt worker
-        enc.run_v2(enc_rx, mid_tx.clone());
+        let enc_worker = enc.run_v2(enc_rx, mid_tx.clone());
 
         // produce a segment
         enc_tx
@@ -187,10 +200,17 @@ use crypto_core::telemetry::StageTimes;
         // bridge converts EncryptedSegment → DecryptSegmentInput
         forward_encrypted_to_decrypt(mid_rx, bridge_tx);
 
-        dec.run_v2(bridge_rx, dec_tx);
+        let dec_worker = dec.run_v2(bridge_rx, dec_tx);
 
         // now the decrypt worker should fail verification
-        assert!(dec_rx.recv().unwrap().is_err());
+        let err = dec_rx.recv().unwrap().unwrap_err();
+        assert!(matches!(&err, SegmentWorkerError::SegmentError(SegmentError::Malformed(_))), "{err:?}");
+
+        // Closing the input lets the workers exit
+        drop(enc_tx);
+        drop(mid_tx);
+        enc_worker.join().unwrap();
+        dec_worker.join().unwrap();
     }
 
     // ## ❌ 4. Wrong crypto context (wrong key)
@@ -216,11 +236,11 @@ use crypto_core::telemetry::StageTimes;
         let (bridge_tx, bridge_rx) = unbounded();
         let (dec_tx, dec_rx) = unbounded();
 
-        enc.run_v2(enc_rx, mid_tx);
+        let enc_worker = enc.run_v2(enc_rx, mid_tx);
         // bridge converts EncryptedSegment → DecryptSegmentInput
         forward_encrypted_to_decrypt(mid_rx, bridge_tx);
         //
-        dec.run_v2(bridge_rx, dec_tx);
+        let dec_worker = dec.run_v2(bridge_rx, dec_tx);
 
         enc_tx.send(EncryptSegmentInput {
             segment_index: 3,
@@ -229,7 +249,19 @@ use crypto_core::telemetry::StageTimes;
             stage_times: StageTimes::default(),
         }).unwrap();
 
-        assert!(dec_rx.recv().unwrap().is_err());
+        // Every frame fails to authenticate; whichever is reported names segment 3
+        match dec_rx.recv().unwrap().unwrap_err() {
+            SegmentWorkerError::FrameWorkerError(e) => assert!(
+                matches!(e.untagged(), FrameWorkerError::AuthenticationFailed { segment_index: 3, .. }),
+                "{e:?}"
+            ),
+            other => panic!("expected an authentication failure, got {other:?}"),
+        }
+
+        // Closing the input lets the workers exit
+        drop(enc_tx);
+        enc_worker.join().unwrap();
+        dec_worker.join().unwrap();
     }
 
     // ## ❌ 5. Truncated segment wire
@@ -248,7 +280,7 @@ use crypto_core::telemetry::StageTimes;
         let (dec_tx, dec_rx) = unbounded();
 
         // give one clone to the encrypt worker
-        enc.run_v2(enc_rx, mid_tx.clone());
+        let enc_worker = enc.run_v2(enc_rx, mid_tx.clone());
 
         // produce a segment
         enc_tx.send(EncryptSegmentInput {
@@ -268,9 +300,16 @@ use crypto_core::telemetry::StageTimes;
         // bridge converts EncryptedSegment → DecryptSegmentInput
         forward_encrypted_to_decrypt(mid_rx, bridge_tx);
 
-        dec.run_v2(bridge_rx, dec_tx);
+        let dec_worker = dec.run_v2(bridge_rx, dec_tx);
+
+        let err = dec_rx.recv().unwrap().unwrap_err();
+        assert!(matches!(&err, SegmentWorkerError::SegmentError(SegmentError::LengthMismatch { .. })), "{err:?}");
 
-        assert!(dec_rx.recv().unwrap().is_err());
+        // Closing the input lets the workers exit
+        drop(enc_tx);
+        drop(mid_tx);
+        enc_worker.join().unwrap();
+        dec_worker.join().unwrap();
     }
 
     // ## ❌ 6. Missing terminator frame
@@ -289,7 +328,7 @@ use crypto_core::telemetry::StageTimes;
         let (dec_tx, dec_rx) = unbounded();
 
         // give one clone to the encrypt worker
-        enc.run_v2(enc_rx, mid_tx.clone());
+        let enc_worker = enc.run_v2(enc_rx, mid_tx.clone());
 
         // produce a segment
         enc_tx.send(EncryptSegmentInput {
@@ -315,9 +354,16 @@ use crypto_core::telemetry::StageTimes;
         // bridge converts EncryptedSegment → DecryptSegmentInput
         forward_encrypted_to_decrypt(mid_rx, bridge_tx);
 
-        dec.run_v2(bridge_rx, dec_tx);
+        let dec_worker = dec.run_v2(bridge_rx, dec_tx);
 
-        assert!(dec_rx.recv().unwrap().is_err());
+        let err = dec_rx.recv().unwrap().unwrap_err();
+        assert!(matches!(&err, SegmentWorkerError::SegmentError(SegmentError::LengthMismatch { .. })), "{err:?}");
+
+        // Closing the input lets the workers exit
+        drop(enc_tx);
+        drop(mid_tx);
+        enc_worker.join().unwrap();
+        dec_worker.join().unwrap();
     }
 
     // ## ✅ 7. Deterministic encryption (same input → same wire)
@@ -333,7 +379,7 @@ use crypto_core::telemetry::StageTimes;
         let (tx, rx) = unbounded();
         let (out_tx, out_rx) = unbounded();
 
-        enc.run_v2(rx, out_tx);
+        let enc_worker = enc.run_v2(rx, out_tx);
 
         let payload = Bytes::from_static(b"deterministic segment");
 
@@ -354,6 +400,10 @@ use crypto_core::telemetry::StageTimes;
         let b = out_rx.recv().unwrap().unwrap();
 
         assert_eq!(a.wire, b.wire);
+
+        // Closing the input lets the workers exit
+        drop(tx);
+        enc_worker.join().unwrap();
     }
 
     // ## ✅ 8. Telemetry sanity checks
@@ -371,11 +421,11 @@ use crypto_core::telemetry::StageTimes;
         let (bridge_tx, bridge_rx) = unbounded();
         let (dec_tx, dec_rx) = unbounded();
 
-        enc.run_v2(enc_rx, mid_tx);
+        let enc_worker = enc.run_v2(enc_rx, mid_tx);
         // bridge converts EncryptedSegment → DecryptSegmentInput
         forward_encrypted_to_decrypt(mid_rx, bridge_tx);
         //
-        dec.run_v2(bridge_rx, dec_tx);
+        let dec_worker = dec.run_v2(bridge_rx, dec_tx);
 
         let plaintext = Bytes::from_static(b"telemetry test");
 
@@ -394,17 +444,146 @@ use crypto_core::telemetry::StageTimes;
         assert!(decrypted.counters.bytes_compressed > 0);
 
         // Compare against raw byte slice
-        assert_eq!(decrypted.bytes.as_ref(), b"telemetry test");
+        assert_eq!(decrypted.to_bytes().as_ref(), b"telemetry test");
 
         // Compare against Vec<u8>
-        assert_eq!(decrypted.bytes.to_vec(), b"telemetry test".to_vec());
+        assert_eq!(decrypted.to_bytes().to_vec(), b"telemetry test".to_vec());
 
         // Compare against &str (requires UTF‑8 conversion)
-        assert_eq!(std::str::from_utf8(decrypted.bytes.as_ref()).unwrap(), "telemetry test");
+        assert_eq!(std::str::from_utf8(decrypted.to_bytes().as_ref()).unwrap(), "telemetry test");
 
         // Compare length explicitly
-        assert_eq!(decrypted.bytes.len(), "telemetry test".len());
+        assert_eq!(decrypted.len(), "telemetry test".len());
 
+        // Closing the input lets the workers exit
+        drop(enc_tx);
+        enc_worker.join().unwrap();
+        dec_worker.join().unwrap();
+    }
+
+    // ## ❌ 9. Frame pool that cannot start
+
+    #[test]
+    fn failing_frame_pool_surfaces_through_the_handle() {
+        let (mut crypto_enc, log_enc) = setup_enc_context(DigestAlg::Sha256);
+        let (mut crypto_dec, log_dec) = setup_dec_context(DigestAlg::Sha256);
+        // No such cipher: neither private frame pool can be built
+        crypto_enc.header.cipher = 0xFFFF;
+        crypto_dec.header.cipher = 0xFFFF;
+
+        let (_enc_tx, enc_rx) = unbounded::<EncryptSegmentInput>();
+        let (mid_tx, mid_rx) = unbounded();
+        let mut enc_worker = EncryptSegmentWorker::new(crypto_enc, log_enc).run_v2(enc_rx, mid_tx);
+        let err = enc_worker.wait_ready().unwrap_err();
+        assert!(matches!(err, SegmentWorkerError::FrameWorkerError(_)), "{err:?}");
+        // The worker exited without producing anything
+        assert!(mid_rx.recv().is_err());
+        enc_worker.join().unwrap();
+
+        // Not waited for: joining reports it instead
+        let (_dec_tx, dec_rx) = unbounded::<DecryptSegmentInput>();
+        let (out_tx, out_rx) = unbounded();
+        let dec_worker = DecryptSegmentWorker::new(crypto_dec, log_dec).run_v2(dec_rx, out_tx);
+        let err = dec_worker.join().unwrap_err();
+        assert!(matches!(err, SegmentWorkerError::FrameWorkerError(_)), "{err:?}");
+        assert!(out_rx.recv().is_err());
+    }
+
+    /// Encrypt one segment and hand back its wire for hand-crafted decrypt inputs.
+    fn encrypt_one(segment_index: u32, bytes: &'static [u8]) -> EncryptedSegment {
+        let (crypto_enc, log_enc) = setup_enc_context(DigestAlg::Sha256);
+        let (enc_tx, enc_rx) = unbounded();
+        let (mid_tx, mid_rx) = unbounded();
+        let enc_worker = EncryptSegmentWorker::new(crypto_enc, log_enc).run_v2(enc_rx, mid_tx);
+        enc_tx.send(EncryptSegmentInput {
+            segment_index,
+            bytes: Bytes::from_static(bytes),
+            flags: SegmentFlags::empty(),
+            stage_times: StageTimes::default(),
+        }).unwrap();
+        let encrypted = mid_rx.recv().unwrap().unwrap();
+        drop(enc_tx);
+        enc_worker.join().unwrap();
+        encrypted
+    }
+
+    /// Decrypt `wire` under a segment header rebuilt for it, so damage reaches the frame layer.
+    fn decrypt_rewired(context: DecryptContext, log: Arc<AsyncLogManager>, original: &SegmentHeader, wire: Bytes) -> SegmentWorkerError {
+        let header = SegmentHeader::new(
+            &wire,
+            original.segment_index,
+            original.bytes_len,
+            original.frame_count,
+            original.digest_alg,
+            original.flags,
+        );
+        let (dec_tx, dec_rx) = unbounded();
+        let (out_tx, out_rx) = unbounded();
+        let dec_worker = DecryptSegmentWorker::new(context, log).run_v2(dec_rx, out_tx);
+        dec_tx.send(DecryptSegmentInput { header, wire }).unwrap();
+        let err = out_rx.recv().unwrap().unwrap_err();
+        drop(dec_tx);
+        dec_worker.join().unwrap();
+        err
+    }
+
+    // ## ❌ 10. Wrong key reports the first frame that failed
+
+    #[test]
+    fn wrong_key_reports_authentication_failure_with_indices() {
+        let encrypted = encrypt_one(6, b"wrong key");
+        // One frame worker: frame 0 is the first to fail
+        let context = DecryptContext::from_stream_header(
+            HeaderV1::test_header(),
+            HybridParallelismProfile::single_threaded(),
+            &[0x99u8; KEY_LEN_32],
+            DigestAlg::Sha256,
+        ).unwrap();
+        let log = Arc::new(AsyncLogManager::disabled());
+
+        let err = decrypt_rewired(context, log, &encrypted.header, encrypted.wire);
+        match &err {
+            SegmentWorkerError::FrameWorkerError(e) => {
+                assert_eq!(e.frame_tag(), Some((6, 0)));
+                assert!(matches!(e.untagged(), FrameWorkerError::AuthenticationFailed { segment_index: 6, frame_index: 0 }), "{e:?}");
+            }
+            other => panic!("expected an authentication failure, got {other:?}"),
+        }
+        let err = StreamError::SegmentWorker(err);
+        assert_eq!(err.code(), ErrorCode::AuthTag);
+        assert!(matches!(err.frame_failure(), Some(FrameWorkerError::AuthenticationFailed { segment_index: 6, frame_index: 0 })));
+    }
+
+    // ## ❌ 11. Damage past the segment checksum is classified per frame
+
+    #[test]
+    fn frame_damage_is_classified() {
+        let encrypted = encrypt_one(2, b"classify me");
+
+        // Flipped ciphertext byte of the first data frame
+        let mut wire = bytes::BytesMut::from(&encrypted.wire[..]);
+        wire[FrameHeader::LEN] ^= 0xFF;
+        let (context, log) = setup_dec_context(DigestAlg::Sha256);
+        let err = StreamError::SegmentWorker(decrypt_rewired(context, log, &encrypted.header, wire.freeze()));
+        assert!(matches!(
+            err.frame_failure(),
+            Some(FrameWorkerError::AuthenticationFailed { segment_index: 2, frame_index: 0 })
+        ), "{err:?}");
+
+        // Corrupt magic of the first frame
+        let mut wire = bytes::BytesMut::from(&encrypted.wire[..]);
+        wire[0] ^= 0xFF;
+        let (context, log) = setup_dec_context(DigestAlg::Sha256);
+        let err = StreamError::SegmentWorker(decrypt_rewired(context, log, &encrypted.header, wire.freeze()));
+        assert!(matches!(err.frame_failure(), Some(FrameWorkerError::MalformedFrameHeader(_))), "{err:?}");
+        assert_eq!(err.code(), ErrorCode::FrameMalformed);
+
+        // Last frame cut short
+        let (context, log) = setup_dec_context(DigestAlg::Sha256);
+        let cut = encrypted.wire.slice(..encrypted.wire.len() - 5);
+        let err = StreamError::SegmentWorker(decrypt_rewired(context, log, &encrypted.header, cut));
+        assert!(matches!(err.frame_failure(), Some(FrameWorkerError::Truncated { .. })), "{err:?}");
+        assert_eq!(err.code(), ErrorCode::Truncated);
     }
 
 }
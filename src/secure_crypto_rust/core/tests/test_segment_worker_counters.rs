@@ -2,7 +2,7 @@
 mod tests {
     use bytes::Bytes;
     use crossbeam::channel::{self, bounded};
-    use crypto_core::{crypto::DigestAlg, stream_v2::{frame_worker::{EncryptedFrame, FrameInput, FrameWorkerError}, segment_worker::{EncryptSegmentInput, encrypt::process_encrypt_segment_2}, segmenting::types::SegmentFlags}, telemetry::{StageTimes, TelemetryCounters}};
+    use crypto_core::{crypto::DigestAlg, stream_v2::{frame_worker::{EncryptedFrame, FrameInput, FrameWorkerError}, segment_worker::{EncryptSegmentInput, encrypt::process_encrypt_segment_2}, segmenting::{SegmentHeader, types::SegmentFlags}}, telemetry::{StageTimes, TelemetryCounters}};
 
     fn setup_channels() -> (
         channel::Sender<FrameInput>,
@@ -44,8 +44,9 @@ mod tests {
         let result = process_encrypt_segment_2(&input, 16, DigestAlg::Sha256, &frame_tx, &out_rx)
             .expect("should succeed");
 
-        // Empty final segment should have all counters = 0
-        assert_eq!(result.counters, TelemetryCounters::default());
+        // Empty final segment only counts its own header
+        let header_only = TelemetryCounters { frames_header: 1, bytes_overhead: SegmentHeader::LEN as u64, ..TelemetryCounters::default() };
+        assert_eq!(result.counters, header_only);
     }
 
     #[test]
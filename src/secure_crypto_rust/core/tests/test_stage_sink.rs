@@ -0,0 +1,138 @@
+// # 📂 `tests/test_stage_sink.rs`
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use bytes::Bytes;
+    use crossbeam::channel::{Receiver, unbounded};
+    use crypto_core::{
+        compression::{CompressionError, codec_ids},
+        headers::HeaderV1,
+        stream_v2::{
+            InputSource, OutputSink,
+            compression_worker::{CompressionBackend, CompressionWorkerError, run_compression_worker_into},
+            core::{ApiConfig, DecryptParams, EncryptParams, decrypt_stream_v2, encrypt_stream_v2},
+            parallelism::HybridParallelismProfile,
+            segment_worker::{EncryptSegmentInput, EncryptedSegment},
+            segmenting::types::SegmentFlags,
+            sink::{ForwardSink, StageSink, StageTelemetry},
+        },
+        telemetry::{Stage, StageTimes},
+        types::StreamError,
+    };
+
+    type Sink = ForwardSink<EncryptSegmentInput, Result<EncryptedSegment, StreamError>>;
+    type Errors = Receiver<Result<EncryptedSegment, StreamError>>;
+
+    fn segment(segment_index: u32, len: usize) -> EncryptSegmentInput {
+        let mut stage_times = StageTimes::default();
+        stage_times.add(Stage::Compress, Duration::from_millis(5));
+        EncryptSegmentInput {
+            segment_index,
+            bytes: Bytes::from(vec![0xA5u8; len]),
+            flags: SegmentFlags::empty(),
+            stage_times,
+        }
+    }
+
+    fn sink() -> (Sink, Receiver<EncryptSegmentInput>, Errors, Arc<Mutex<StageTelemetry>>) {
+        let (next_tx, next_rx) = unbounded();
+        let (err_tx, err_rx) = unbounded();
+        let totals = Arc::new(Mutex::new(StageTelemetry::default()));
+        (ForwardSink::new(next_tx, err_tx, Arc::clone(&totals)), next_rx, err_rx, totals)
+    }
+
+    #[test]
+    fn segments_go_to_the_next_stage_and_errors_to_the_writer() {
+        let (mut sink, next_rx, err_rx, _) = sink();
+
+        assert!(StageSink::<_, CompressionWorkerError>::send(&mut sink, Ok(segment(0, 100))));
+        let forwarded = next_rx.try_recv().unwrap();
+        assert_eq!(forwarded.segment_index, 0);
+        assert_eq!(forwarded.stage_times, StageTimes::default(), "telemetry stays with the sink");
+
+        assert!(sink.send(Err(CompressionWorkerError::StateError("boom".into()))));
+        assert!(next_rx.try_recv().is_err());
+        let err = err_rx.try_recv().unwrap().unwrap_err();
+        assert!(matches!(err, StreamError::CompressionWorker(CompressionWorkerError::StateError(_))), "{err:?}");
+    }
+
+    #[test]
+    fn each_clone_merges_its_telemetry_once_when_dropped() {
+        let (mut first, _next_rx, _err_rx, totals) = sink();
+        let mut second = first.clone();
+
+        assert!(StageSink::<_, CompressionWorkerError>::send(&mut first, Ok(segment(0, 100))));
+        assert!(StageSink::<_, CompressionWorkerError>::send(&mut second, Ok(segment(1, 50))));
+        assert!(StageSink::<_, CompressionWorkerError>::send(&mut second, Ok(segment(2, 25))));
+        assert_eq!(totals.lock().unwrap().counters.bytes_compressed, 0, "nothing merged while workers run");
+
+        drop(first);
+        assert_eq!(totals.lock().unwrap().counters.bytes_compressed, 100);
+        drop(second);
+        let totals = totals.lock().unwrap();
+        assert_eq!(totals.counters.bytes_compressed, 175);
+        assert_eq!(totals.stage_times.get(Stage::Compress), Duration::from_millis(15));
+    }
+
+    struct Identity;
+
+    impl CompressionBackend for Identity {
+        fn compress_chunk(&mut self, input: &[u8]) -> Result<Vec<u8>, CompressionError> {
+            Ok(input.to_vec())
+        }
+        fn decompress_chunk(&mut self, input: &[u8]) -> Result<Vec<u8>, CompressionError> {
+            Ok(input.to_vec())
+        }
+    }
+
+    #[test]
+    fn compression_worker_stops_once_the_next_stage_is_gone() {
+        let (sink, next_rx, _err_rx, _) = sink();
+        let (in_tx, in_rx) = unbounded();
+        for i in 0..4 {
+            in_tx.send(segment(i, 64)).unwrap();
+        }
+        drop(next_rx);
+
+        // The input stays open: only the failed hand-off can end the loop.
+        // A receiver of our own keeps the unread segments queued once the worker drops its one
+        let unread = in_rx.clone();
+        run_compression_worker_into(in_rx, sink, Box::new(Identity));
+        assert_eq!(unread.len(), 3);
+    }
+
+    #[test]
+    fn small_segments_round_trip_through_every_stage() {
+        let plaintext: Vec<u8> = (0..40 * 16 * 1024 + 77).map(|i| (i % 97) as u8).collect();
+        let header = HeaderV1 { compression: codec_ids::ZSTD, chunk_size: 16 * 1024, ..HeaderV1::test_header() };
+        let config = || ApiConfig::new(Some(true), None).with_profile(HybridParallelismProfile::new(4, 0, 8));
+
+        let encrypted = encrypt_stream_v2(
+            InputSource::Memory(plaintext.clone()),
+            OutputSink::Memory,
+            &[0x11u8; 32],
+            EncryptParams::new(header),
+            config(),
+        )
+        .unwrap();
+        assert!(encrypted.bytes_compressed > 0);
+        assert!(encrypted.stage_times.get(Stage::Compress) > Duration::ZERO);
+
+        let decrypted = decrypt_stream_v2(
+            InputSource::Memory(encrypted.output.clone().unwrap()),
+            OutputSink::Memory,
+            &[0x11u8; 32],
+            DecryptParams::new(),
+            config(),
+        )
+        .unwrap();
+        assert_eq!(decrypted.output.as_deref(), Some(&plaintext[..]));
+        assert_eq!(decrypted.bytes_compressed, encrypted.bytes_compressed);
+        assert_eq!(decrypted.bytes_ciphertext, encrypted.bytes_ciphertext);
+    }
+}
@@ -0,0 +1,90 @@
+// # 📂 `tests/test_stdio_sinks.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use crypto_core::{
+        compression::CompressionCodec,
+        headers::HeaderV1,
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{DecryptParams, EncryptParams, decrypt_stream_v2, encrypt_stream_v2},
+            io::{open_input, open_output, open_output_counted},
+        },
+        telemetry::TelemetrySnapshot,
+    };
+
+    use crate::common::{self, master_key};
+
+    const CHUNK: usize = 16 * 1024;
+
+    fn plaintext() -> Vec<u8> {
+        b"discarded but still counted\n".repeat(3 * CHUNK / 28)
+    }
+
+    fn encrypt(output: OutputSink) -> TelemetrySnapshot {
+        let header = HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() };
+        encrypt_stream_v2(
+            InputSource::Memory(plaintext()),
+            output,
+            &master_key(),
+            EncryptParams::new(header).with_compression(CompressionCodec::Zstd, None),
+            common::config(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn discard_counts_what_it_drops() {
+        let (mut writer, buf, counter) = open_output_counted(OutputSink::Discard, Some(true), None).unwrap();
+        assert!(buf.is_none());
+        writer.write_all(b"twelve bytes").unwrap();
+        writer.write_all(&[0u8; 100]).unwrap();
+        writer.flush().unwrap();
+        assert_eq!(counter.unwrap().load(std::sync::atomic::Ordering::Relaxed), 112);
+
+        // Only `Discard` has a counter
+        let (_, buf, counter) = open_output_counted(OutputSink::Memory, Some(true), None).unwrap();
+        assert!(buf.is_some() && counter.is_none());
+    }
+
+    #[test]
+    fn encrypt_to_discard_reports_the_stream_length() {
+        let captured = encrypt(OutputSink::Memory);
+        let stream_len = captured.output.as_ref().unwrap().len() as u64;
+
+        let discarded = encrypt(OutputSink::Discard);
+        assert!(discarded.output.is_none());
+        assert_eq!(discarded.bytes_ciphertext + discarded.bytes_overhead, stream_len);
+        assert_eq!(discarded.bytes_ciphertext, captured.bytes_ciphertext);
+        assert_eq!(discarded.bytes_plaintext, plaintext().len() as u64);
+    }
+
+    #[test]
+    fn decrypt_to_discard_reports_the_plaintext_length() {
+        let stream = encrypt(OutputSink::Memory).output.unwrap();
+        let snapshot = decrypt_stream_v2(
+            InputSource::Memory(stream),
+            OutputSink::Discard,
+            &master_key(),
+            DecryptParams::new(),
+            common::config(),
+        )
+        .unwrap();
+        assert!(snapshot.output.is_none());
+        assert_eq!(snapshot.bytes_plaintext, plaintext().len() as u64);
+    }
+
+    #[test]
+    fn stdio_variants_open() {
+        assert_eq!(InputSource::Stdin.size_hint(), None);
+        // Opening does not read or write; the handles only have to be `Send`
+        let reader = open_input(InputSource::Stdin).unwrap();
+        let (writer, buf) = open_output(OutputSink::Stdout, Some(true), None).unwrap();
+        assert!(buf.is_none());
+        std::thread::spawn(move || drop((reader, writer))).join().unwrap();
+    }
+}
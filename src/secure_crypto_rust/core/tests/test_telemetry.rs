@@ -146,4 +146,55 @@ mod telemetry_snapshot_tests {
 
         assert_eq!(snapshot.output_bytes(), counters.bytes_ciphertext);
     }
+
+    /// Fails to compile when a `Stage` variant is added without updating this
+    /// match, `Stage::ALL` and the name tables.
+    fn stage_name(stage: Stage) -> &'static str {
+        match stage {
+            Stage::Read => "read",
+            Stage::Write => "write",
+            Stage::Encode => "encode",
+            Stage::Decode => "decode",
+            Stage::Compress => "compress",
+            Stage::Decompress => "decompress",
+            Stage::Encrypt => "encrypt",
+            Stage::Decrypt => "decrypt",
+            Stage::Validate => "validate",
+            Stage::Digest => "digest",
+        }
+    }
+
+    #[test]
+    fn every_stage_is_listed_and_named() {
+        assert_eq!(Stage::ALL.len(), 10);
+        for stage in Stage::ALL {
+            assert_eq!(stage.to_string(), stage_name(stage));
+            assert_eq!(stage_name(stage).parse::<Stage>(), Ok(stage));
+        }
+        assert!("finalize".parse::<Stage>().is_err());
+    }
+
+    #[test]
+    fn summary_prints_every_stage_including_digest() {
+        let mut times = StageTimes::default();
+        times.add(Stage::Digest, Duration::from_micros(7));
+        let summary = times.summary();
+        assert!(summary.contains("Digest: 7µs"), "{summary}");
+        // Untimed stages still get a line, in declaration order
+        assert!(summary.contains("Compress: 0ns"), "{summary}");
+        assert!(summary.find("Read:").unwrap() < summary.find("Digest:").unwrap());
+        assert_eq!(summary.lines().count(), 1 + Stage::ALL.len());
+    }
+
+    #[test]
+    fn digest_stage_survives_json() {
+        let mut timer = make_timer();
+        timer.stage_times.add(Stage::Digest, Duration::from_micros(3));
+        let snapshot = TelemetrySnapshot::from(&make_counters(), &timer, Some(1));
+        let json = serde_json::to_value(&snapshot).unwrap();
+        assert_eq!(json["stage_times"]["digest"].as_u64(), Some(3_000));
+
+        let back: TelemetrySnapshot = serde_json::from_value(json).unwrap();
+        assert_eq!(back.stage_times.get(Stage::Digest), Duration::from_micros(3));
+    }
 }
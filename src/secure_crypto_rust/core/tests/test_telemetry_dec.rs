@@ -4,7 +4,7 @@
 mod tests {
     use bytes::Bytes;
     use crossbeam::channel::{Receiver, Sender, bounded, unbounded};
-    use crypto_core::{crypto::DigestAlg, headers::HeaderV1, stream_v2::{frame_worker::{DecryptedFrame, EncryptedFrame, FrameInput, FrameWorkerError, decrypt::DecryptFrameWorker, encrypt::EncryptFrameWorker}, segment_worker::{DecryptSegmentInput, EncryptSegmentInput, SegmentWorkerError, decrypt::process_decrypt_segment_v2, encrypt::process_encrypt_segment_2}, segmenting::{SegmentHeader, types::SegmentFlags}}, telemetry::{Stage, StageTimes, TelemetryCounters}};
+    use crypto_core::{crypto::DigestAlg, headers::HeaderV1, stream_v2::{frame_worker::{DecryptFrameInput, DecryptedFrame, EncryptedFrame, FrameInput, FrameWorkerError, decrypt::DecryptFrameWorker, encrypt::EncryptFrameWorker}, segment_worker::{DecryptSegmentInput, EncryptSegmentInput, SegmentWorkerError, decrypt::process_decrypt_segment_v2, encrypt::process_encrypt_segment_2}, segmenting::{SegmentHeader, types::SegmentFlags}}, telemetry::{Stage, StageTimes, TelemetryCounters}};
 
     /// Build a deterministic encrypted segment fixture for testing.
     /// This uses the real encrypt pipeline to produce a wire payload
@@ -47,10 +47,10 @@ mod tests {
     }
 
     fn make_channels() -> (
-        Sender<Bytes>,
+        Sender<DecryptFrameInput>,
         Receiver<Result<DecryptedFrame, FrameWorkerError>>,
     ) {
-        let (frame_tx, frame_rx) = bounded::<Bytes>(4);
+        let (frame_tx, frame_rx) = bounded::<DecryptFrameInput>(4);
         let (out_tx, out_rx) = unbounded::<Result<DecryptedFrame, FrameWorkerError>>();
 
         // Minimal worker stub (replace with real header/session_key in integration tests)
@@ -78,11 +78,13 @@ mod tests {
             wire: Bytes::new(),
         };
 
-        let result = process_decrypt_segment_v2(&input, &DigestAlg::Sha256, &frame_tx, &out_rx);
+        let result = process_decrypt_segment_v2(&input, &frame_tx, &out_rx);
         assert!(result.is_ok());
         let seg = result.unwrap();
-        assert_eq!(seg.bytes.len(), 0);
-        assert_eq!(seg.counters, TelemetryCounters::default());
+        assert_eq!(seg.len(), 0);
+        // Only its own header was read
+        let header_only = TelemetryCounters { frames_header: 1, bytes_overhead: SegmentHeader::LEN as u64, ..TelemetryCounters::default() };
+        assert_eq!(seg.counters, header_only);
         assert_eq!(seg.stage_times, StageTimes::default());
     }
 
@@ -102,7 +104,7 @@ mod tests {
             wire: Bytes::new(),
         };
 
-        let result = process_decrypt_segment_v2(&input, &DigestAlg::Sha256, &frame_tx, &out_rx);
+        let result = process_decrypt_segment_v2(&input, &frame_tx, &out_rx);
         assert!(matches!(result, Err(SegmentWorkerError::InvalidSegment(_))));
     }
 
@@ -121,7 +123,7 @@ mod tests {
         );
         let input = DecryptSegmentInput { header, wire: bogus_wire };
 
-        let result = process_decrypt_segment_v2(&input, &DigestAlg::Sha256, &frame_tx, &out_rx);
+        let result = process_decrypt_segment_v2(&input, &frame_tx, &out_rx);
         assert!(result.is_err());
         // Telemetry counters should remain default on failure
         if let Err(e) = result {
@@ -150,7 +152,7 @@ mod tests {
         );
         let input = DecryptSegmentInput { header, wire: fake_wire };
 
-        let result = process_decrypt_segment_v2(&input, &DigestAlg::Sha256, &frame_tx, &out_rx);
+        let result = process_decrypt_segment_v2(&input, &frame_tx, &out_rx);
         assert!(result.is_ok());
         let seg = result.unwrap();
 
@@ -0,0 +1,106 @@
+// # 📂 `tests/test_telemetry_json.rs`
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    use serde_json::Value;
+
+    use crypto_core::telemetry::{Stage, StageTimes, TelemetryCounters, TelemetrySnapshot, TelemetryTimer};
+
+    fn make_snapshot() -> TelemetrySnapshot {
+        let counters = TelemetryCounters {
+            frames_header: 1,
+            frames_data: 4,
+            frames_digest: 1,
+            frames_terminator: 1,
+            bytes_plaintext: 4096,
+            bytes_compressed: 1024,
+            bytes_ciphertext: 1100,
+            bytes_overhead: 200,
+        };
+        let mut timer = TelemetryTimer::new();
+        timer.stage_times = StageTimes {
+            times: HashMap::from([
+                (Stage::Read, Duration::from_nanos(1_500)),
+                (Stage::Encrypt, Duration::from_micros(42)),
+                (Stage::Write, Duration::from_millis(3)),
+            ]),
+        };
+        timer.finish();
+        TelemetrySnapshot::from(&counters, &timer, Some(2))
+    }
+
+    #[test]
+    fn json_has_stable_key_names() {
+        let json: Value = serde_json::from_str(&make_snapshot().to_json()).unwrap();
+        let obj = json.as_object().unwrap();
+
+        for key in [
+            "bytes_plaintext",
+            "bytes_ciphertext",
+            "bytes_overhead",
+            "segments_processed",
+            "elapsed_ns",
+            "stage_times",
+        ] {
+            assert!(obj.contains_key(key), "missing key {key}");
+        }
+        assert!(!obj.contains_key("elapsed"));
+        assert!(!obj.contains_key("output"));
+        assert_eq!(obj["bytes_plaintext"], 4096);
+        assert_eq!(obj["segments_processed"], 2);
+    }
+
+    #[test]
+    fn durations_are_integer_nanoseconds() {
+        let snapshot = make_snapshot();
+        let json: Value = serde_json::from_str(&snapshot.to_json()).unwrap();
+
+        assert_eq!(json["elapsed_ns"].as_u64().unwrap(), snapshot.elapsed.as_nanos() as u64);
+
+        let stages = json["stage_times"].as_object().unwrap();
+        assert_eq!(stages.len(), 3);
+        assert_eq!(stages["read"], 1_500);
+        assert_eq!(stages["encrypt"], 42_000);
+        assert_eq!(stages["write"], 3_000_000);
+    }
+
+    #[test]
+    fn roundtrips_through_json() {
+        let snapshot = make_snapshot();
+        for json in [snapshot.to_json(), snapshot.to_json_pretty()] {
+            let mut restored = TelemetrySnapshot::from_json(&json).unwrap();
+            // Floats may differ in the last ulp after a text roundtrip
+            let rel = (restored.throughput_plaintext_bytes_per_sec - snapshot.throughput_plaintext_bytes_per_sec).abs()
+                / snapshot.throughput_plaintext_bytes_per_sec;
+            assert!(rel < 1e-12);
+            restored.throughput_plaintext_bytes_per_sec = snapshot.throughput_plaintext_bytes_per_sec;
+            assert_eq!(restored, snapshot);
+        }
+    }
+
+    #[test]
+    fn output_buffer_is_not_serialized() {
+        let mut snapshot = make_snapshot();
+        snapshot.attach_output(vec![0xAA; 64]);
+
+        let restored = TelemetrySnapshot::from_json(&snapshot.to_json()).unwrap();
+        assert!(restored.output.is_none());
+    }
+
+    #[test]
+    fn rejects_unknown_stage_name() {
+        let mut json: Value = serde_json::from_str(&make_snapshot().to_json()).unwrap();
+        json["stage_times"]["warp"] = Value::from(1);
+        assert!(TelemetrySnapshot::from_json(&json.to_string()).is_err());
+    }
+
+    #[test]
+    fn stage_names_parse_from_display() {
+        for stage in [Stage::Read, Stage::Compress, Stage::Decrypt, Stage::Digest] {
+            assert_eq!(stage.to_string().parse::<Stage>().unwrap(), stage);
+        }
+    }
+}
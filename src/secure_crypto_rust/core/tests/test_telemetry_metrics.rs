@@ -0,0 +1,90 @@
+// # 📂 `tests/test_telemetry_metrics.rs`
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    use crypto_core::telemetry::{Stage, StageTimes, TelemetryCounters, TelemetrySnapshot, TelemetryTimer};
+
+    fn snapshot(plaintext: u64, compressed: u64, stages: &[(Stage, Duration)], elapsed: Duration) -> TelemetrySnapshot {
+        let counters = TelemetryCounters {
+            bytes_plaintext: plaintext,
+            bytes_compressed: compressed,
+            bytes_ciphertext: compressed,
+            bytes_overhead: 100,
+            ..Default::default()
+        };
+        let mut timer = TelemetryTimer::new();
+        timer.stage_times = StageTimes { times: stages.iter().copied().collect::<HashMap<_, _>>() };
+        timer.finish();
+
+        let mut snapshot = TelemetrySnapshot::from(&counters, &timer, Some(1));
+        snapshot.elapsed = elapsed;
+        snapshot
+    }
+
+    #[test]
+    fn compression_ratio_and_overhead() {
+        let s = snapshot(1000, 250, &[], Duration::from_secs(1));
+        assert_eq!(s.compression_ratio(), Some(0.25));
+        assert_eq!(s.overhead_fraction(), Some(100.0 / 350.0));
+    }
+
+    #[test]
+    fn throughput_in_megabytes_per_second() {
+        let s = snapshot(4_000_000, 4_000_000, &[], Duration::from_secs(2));
+        assert_eq!(s.throughput_mbps(), Some(2.0));
+    }
+
+    #[test]
+    fn stage_fractions_sum_to_one() {
+        let stages = [
+            (Stage::Read, Duration::from_millis(25)),
+            (Stage::Encrypt, Duration::from_millis(50)),
+            (Stage::Write, Duration::from_millis(25)),
+        ];
+        let s = snapshot(1000, 1000, &stages, Duration::from_secs(1));
+
+        assert_eq!(s.stage_fraction(Stage::Encrypt), Some(0.5));
+        assert_eq!(s.stage_fraction(Stage::Compress), Some(0.0)); // never ran
+        let sum: f64 = stages.iter().map(|(st, _)| s.stage_fraction(*st).unwrap()).sum();
+        assert!((sum - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn empty_input_has_no_ratio() {
+        // Empty stream: only the final segment went through, nothing timed
+        let s = snapshot(0, 0, &[], Duration::ZERO);
+        assert_eq!(s.compression_ratio(), None);
+        assert_eq!(s.throughput_mbps(), None);
+        assert_eq!(s.stage_fraction(Stage::Read), None);
+        assert_eq!(s.overhead_fraction(), Some(1.0));
+    }
+
+    #[test]
+    fn summary_never_contains_nan() {
+        let cases = [
+            snapshot(0, 0, &[], Duration::ZERO),
+            snapshot(1000, 400, &[], Duration::ZERO),
+            snapshot(1000, 400, &[(Stage::Read, Duration::ZERO)], Duration::from_millis(3)),
+            snapshot(1000, 400, &[(Stage::Read, Duration::from_millis(1))], Duration::from_millis(3)),
+        ];
+        for s in cases {
+            let line = s.summary();
+            assert!(!line.contains("NaN") && !line.contains("inf"), "{line}");
+            assert!(!line.contains('\n'), "{line}");
+        }
+    }
+
+    #[test]
+    fn summary_reports_metrics() {
+        let s = snapshot(1000, 250, &[(Stage::Read, Duration::from_millis(1))], Duration::from_secs(1));
+        let line = s.summary();
+        assert!(line.contains("ratio=0.250"), "{line}");
+        assert!(line.contains("read=100.0%"), "{line}");
+
+        let empty = snapshot(0, 0, &[], Duration::ZERO).summary();
+        assert!(empty.contains("ratio=n/a") && empty.contains("throughput=n/a"), "{empty}");
+    }
+}
@@ -18,7 +18,7 @@ mod telemetry_decrypt_tests {
         let profile = HybridParallelismProfile::dynamic(header.chunk_size as u32, 0.50, 64);
        // Create a Vec of 32 bytes
         let session_key = vec![0x42u8; KEY_LEN_32];
-        let log_manager = Arc::new(AsyncLogManager::new("test_audit.log", 100).unwrap());
+        let log_manager = Arc::new(AsyncLogManager::disabled());
         
         let context = EncryptContext::new(
             header,
@@ -32,7 +32,7 @@ mod telemetry_decrypt_tests {
         let profile = HybridParallelismProfile::dynamic(header.chunk_size as u32, 0.50, 64);
        // Create a Vec of 32 bytes
         let session_key = vec![0x42u8; KEY_LEN_32];
-        let log_manager = Arc::new(AsyncLogManager::new("test_audit.log", 100).unwrap());
+        let log_manager = Arc::new(AsyncLogManager::disabled());
         
         let context = DecryptContext::from_stream_header(
             header.clone(),
@@ -108,7 +108,7 @@ mod telemetry_decrypt_tests {
         let (stream_header, mut reader) = PayloadReader::with_header(cursor)
             .expect("failed to read stream header");
 
-        eprintln!("[TEST] Parsed stream header: {:?}", stream_header);
+        eprintln!("[TEST] Parsed stream header: {}", stream_header.summary());
 
         let mut writer = Cursor::new(Vec::new());
         let (mut dec_ctx, profile, log_manager) = setup_dec_context(DigestAlg::Blake3, &stream_header);
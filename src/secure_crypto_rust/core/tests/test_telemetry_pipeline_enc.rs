@@ -17,7 +17,7 @@ mod telemetry_encrypt_tests {
         let profile = HybridParallelismProfile::dynamic(header.chunk_size as u32, 0.50, 64);
        // Create a Vec of 32 bytes
         let session_key = vec![0x42u8; KEY_LEN_32];
-        let log_manager = Arc::new(AsyncLogManager::new("test_audit.log", 100).unwrap());
+        let log_manager = Arc::new(AsyncLogManager::disabled());
         
         let context = EncryptContext::new(
             header,
@@ -90,11 +90,14 @@ mod telemetry_encrypt_tests {
     }
 
     #[test]
-    fn telemetry_fails_on_empty_input() {
+    fn telemetry_handles_empty_input() {
         let data = b"";
-        let result = std::panic::catch_unwind(|| run_pipeline_with_data(data));
+        let snapshot = run_pipeline_with_data(data);
 
-        assert!(result.is_err(), "Empty input should fail validation");
+        assert_eq!(snapshot.bytes_plaintext, 0);
+        // Stream header + final segment header
+        assert!(snapshot.bytes_overhead > 0);
+        assert_eq!(snapshot.segments_processed, 1);
     }
 
     #[test]
@@ -0,0 +1,112 @@
+// # 📂 `tests/test_telemetry_stages.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crypto_core::{
+        compression::CompressionCodec,
+        headers::HeaderV1,
+        stream_v2::{
+            InputSource, OutputSink,
+            core::{ApiConfig, DecryptParams, EncryptParams, decrypt_stream_v2, encrypt_stream_v2},
+        },
+        telemetry::{Stage, StageTimes, TelemetrySnapshot},
+    };
+
+    use crate::common::master_key;
+
+    const CHUNK: usize = 16 * 1024;
+    const SEGMENTS: usize = 8;
+
+    fn plaintext() -> Vec<u8> {
+        b"stage timings add up across segments and workers. "
+            .iter()
+            .cycle()
+            .take(SEGMENTS * CHUNK + 333)
+            .copied()
+            .collect()
+    }
+
+    fn roundtrip() -> (TelemetrySnapshot, TelemetrySnapshot) {
+        let header = HeaderV1 {
+            compression: CompressionCodec::Zstd as u16,
+            chunk_size: CHUNK as u32,
+            ..HeaderV1::test_header()
+        };
+        let enc = encrypt_stream_v2(
+            InputSource::Memory(plaintext()),
+            OutputSink::Memory,
+            &master_key(),
+            EncryptParams::new(header),
+            ApiConfig::new(Some(true), Some(true)),
+        )
+        .unwrap();
+        let stream = enc.output.clone().unwrap();
+        assert!(enc.bytes_compressed < enc.bytes_plaintext, "zstd should shrink the text");
+
+        let dec = decrypt_stream_v2(
+            InputSource::Memory(stream),
+            OutputSink::Memory,
+            &master_key(),
+            DecryptParams::new(),
+            ApiConfig::new(Some(true), Some(true)),
+        )
+        .unwrap();
+        assert_eq!(dec.output.as_deref(), Some(plaintext().as_slice()));
+        (enc, dec)
+    }
+
+    #[test]
+    fn add_accumulates() {
+        let mut times = StageTimes::default();
+        times.add(Stage::Compress, Duration::from_millis(2));
+        times.add(Stage::Compress, Duration::from_millis(3));
+        assert_eq!(times.get(Stage::Compress), Duration::from_millis(5));
+    }
+
+    #[test]
+    fn encrypt_snapshot_covers_every_stage() {
+        let (enc, _) = roundtrip();
+        let expected = [
+            Stage::Read,
+            Stage::Compress,
+            Stage::Encrypt,
+            Stage::Encode,
+            Stage::Validate,
+            Stage::Digest,
+            Stage::Write,
+        ];
+        assert!(enc.stage_times.has_all(&expected), "{}", enc.stage_times);
+        assert!(enc.sanity_check());
+    }
+
+    #[test]
+    fn decrypt_snapshot_covers_every_stage() {
+        let (_, dec) = roundtrip();
+        let expected = [
+            Stage::Read,
+            Stage::Decode,
+            Stage::Decrypt,
+            Stage::Decompress,
+            Stage::Validate,
+            Stage::Digest,
+            Stage::Write,
+        ];
+        assert!(dec.stage_times.has_all(&expected), "{}", dec.stage_times);
+        assert!(dec.sanity_check());
+    }
+
+    #[test]
+    fn sanity_bound_scales_with_recorded_workers() {
+        let (enc, _) = roundtrip();
+        assert!(enc.timed_threads() as usize >= enc.cpu_workers + enc.frame_workers);
+
+        let mut unrecorded = enc.clone();
+        unrecorded.cpu_workers = 0;
+        unrecorded.frame_workers = 0;
+        assert_eq!(unrecorded.timed_threads(), 1);
+    }
+}
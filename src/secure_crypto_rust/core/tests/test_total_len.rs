@@ -0,0 +1,161 @@
+// # 📂 `tests/test_total_len.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read, Write};
+
+    use bytes::Bytes;
+    use crypto_core::{
+        constants::flags,
+        headers::{HeaderV1, decode_header_le},
+        stream_v2::{
+            DecryptingReader, EncryptingWriter,
+            core::{DecryptParams, EncryptParams},
+            io::scan_segment_index,
+            segmenting::{SegmentHeader, SegmentIndexEntry, decode_segment_header, encode_segment, types::SegmentFlags},
+        },
+        types::StreamError,
+    };
+
+    use crate::common::{self, master_key};
+
+    const CHUNK: usize = 16 * 1024;
+
+    fn plaintext(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 233) as u8).collect()
+    }
+
+    fn params() -> EncryptParams<'static> {
+        let header = HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() };
+        EncryptParams::new(header).with_length_trailer()
+    }
+
+    fn encrypt(data: &[u8], params: EncryptParams) -> Result<Vec<u8>, StreamError> {
+        common::try_encrypt(data, params, common::config())
+    }
+
+    fn decrypt(ciphertext: Vec<u8>) -> Result<Vec<u8>, StreamError> {
+        common::decrypt(ciphertext, DecryptParams::new())
+    }
+
+    fn read_all(ciphertext: Vec<u8>) -> std::io::Result<Vec<u8>> {
+        let mut reader = DecryptingReader::new(Cursor::new(ciphertext), &master_key(), DecryptParams::new()).unwrap();
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).map(|_| out)
+    }
+
+    fn segments(ciphertext: &[u8]) -> Vec<SegmentIndexEntry> {
+        scan_segment_index(&mut Cursor::new(ciphertext)).unwrap().entries().to_vec()
+    }
+
+    fn bytes_of<'a>(ciphertext: &'a [u8], entry: &SegmentIndexEntry) -> &'a [u8] {
+        &ciphertext[entry.offset as usize..(entry.offset + entry.encoded_len()) as usize]
+    }
+
+    /// `segments` of `ciphertext` followed by its final marker renumbered to come right after them.
+    fn reassemble(ciphertext: &[u8], keep: &[SegmentIndexEntry]) -> Vec<u8> {
+        let entries = segments(ciphertext);
+        let last = entries.last().unwrap();
+        let mut final_header = decode_segment_header(&bytes_of(ciphertext, last)[..SegmentHeader::LEN]).unwrap();
+        final_header.segment_index = keep.len() as u32;
+
+        let mut out = ciphertext[..HeaderV1::LEN].to_vec();
+        for entry in keep {
+            out.extend_from_slice(bytes_of(ciphertext, entry));
+        }
+        out.extend_from_slice(&encode_segment(&final_header, &Bytes::new()).unwrap());
+        out
+    }
+
+    #[test]
+    fn declared_size_is_recorded_and_verified() {
+        let data = plaintext(3 * CHUNK + 11);
+        let ciphertext = encrypt(&data, params().with_plaintext_size(data.len() as u64)).unwrap();
+
+        let header = decode_header_le(&ciphertext[..HeaderV1::LEN]).unwrap();
+        assert_eq!(header.plaintext_size, data.len() as u64);
+        assert_ne!(header.flags & flags::HAS_TOTAL_LEN, 0);
+
+        let entries = segments(&ciphertext);
+        let trailer = &entries[entries.len() - 2];
+        assert_eq!(trailer.flags & (SegmentFlags::TOTAL_LEN | SegmentFlags::CHECKSUM), SegmentFlags::TOTAL_LEN);
+
+        assert_eq!(decrypt(ciphertext.clone()).unwrap(), data);
+        assert_eq!(read_all(ciphertext).unwrap(), data);
+    }
+
+    #[test]
+    fn wrong_declared_size_fails_encryption() {
+        let data = plaintext(CHUNK);
+        let result = encrypt(&data, params().with_plaintext_size(data.len() as u64 + 1));
+        assert!(matches!(result, Err(StreamError::Validation(msg)) if msg.contains("declares")));
+    }
+
+    #[test]
+    fn dropping_the_last_data_segment_is_detected() {
+        let data = plaintext(3 * CHUNK);
+        let ciphertext = encrypt(&data, params()).unwrap();
+        let entries = segments(&ciphertext);
+        assert_eq!(entries.len(), 3 + 1 + 1);
+
+        // Every kept segment still authenticates; the final marker is re-indexed
+        let truncated = reassemble(&ciphertext, &entries[..2]);
+        assert!(matches!(decrypt(truncated.clone()), Err(StreamError::Validation(msg)) if msg.contains("trailer")));
+        assert!(read_all(truncated).is_err());
+
+        // Keeping the trailer leaves a hole in front of it
+        let mut holed = ciphertext[..HeaderV1::LEN].to_vec();
+        for entry in [&entries[0], &entries[1], &entries[3], &entries[4]] {
+            holed.extend_from_slice(bytes_of(&ciphertext, entry));
+        }
+        assert!(decrypt(holed.clone()).is_err());
+        assert!(read_all(holed).is_err());
+    }
+
+    #[test]
+    fn streamed_input_needs_no_declared_size() {
+        let data = plaintext(3 * CHUNK + 5);
+        let mut writer = EncryptingWriter::new(Vec::new(), &master_key(), params()).unwrap();
+        writer.write_all(&data).unwrap();
+        let ciphertext = writer.finish().unwrap();
+
+        let header = decode_header_le(&ciphertext[..HeaderV1::LEN]).unwrap();
+        assert_eq!(header.plaintext_size, 0);
+        assert_ne!(header.flags & flags::HAS_TOTAL_LEN, 0);
+        assert_eq!(decrypt(ciphertext.clone()).unwrap(), data);
+
+        let entries = segments(&ciphertext);
+        assert_eq!(entries.len(), 4 + 1 + 1);
+        let truncated = reassemble(&ciphertext, &entries[..3]);
+        assert!(matches!(decrypt(truncated.clone()), Err(StreamError::Validation(msg)) if msg.contains("trailer")));
+        assert!(read_all(truncated).is_err());
+    }
+
+    #[test]
+    fn trailer_of_a_longer_stream_reports_truncation() {
+        // Same segment count, different tail length: the trailer authenticates in either stream
+        let short = plaintext(2 * CHUNK + 10);
+        let long = plaintext(2 * CHUNK + 900);
+        let ciphertext = encrypt(&short, params()).unwrap();
+        let other = encrypt(&long, params()).unwrap();
+
+        let (ours, theirs) = (segments(&ciphertext), segments(&other));
+        let (ours, theirs) = (&ours[ours.len() - 2], &theirs[theirs.len() - 2]);
+        assert_eq!(ours.segment_index, theirs.segment_index);
+        assert_eq!(ours.encoded_len(), theirs.encoded_len());
+
+        let mut spliced = ciphertext.clone();
+        let start = ours.offset as usize;
+        spliced[start..start + ours.encoded_len() as usize].copy_from_slice(bytes_of(&other, theirs));
+
+        match decrypt(spliced) {
+            Err(StreamError::TruncatedStream { expected, got }) => {
+                assert_eq!(expected, long.len() as u64);
+                assert_eq!(got, short.len() as u64);
+            }
+            other => panic!("expected TruncatedStream, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,134 @@
+// # 📂 `tests/test_uncompressed_segments.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+
+    use crypto_core::{
+        compression::CompressionCodec,
+        headers::HeaderV1,
+        stream_v2::{
+            DecryptingReader, EncryptingWriter, InputSource, OutputSink,
+            core::{DecryptParams, EncryptParams, encrypt_stream_v2},
+            segmenting::{SegmentHeader, decode_segment_header, types::SegmentFlags},
+        },
+    };
+
+    use crate::common::{self, master_key};
+
+    const CHUNK: usize = 16 * 1024;
+
+    /// Incompressible bytes (xorshift64), ≈ 4.5 segments.
+    fn random_bytes() -> Vec<u8> {
+        let mut state = 0x9E37_79B9_7F4A_7C15u64;
+        (0..4 * CHUNK + CHUNK / 2)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                (state >> 24) as u8
+            })
+            .collect()
+    }
+
+    fn text_bytes() -> Vec<u8> {
+        b"stored raw only when compression does not pay off\n".repeat(4 * CHUNK / 50)
+    }
+
+    fn params(codec: CompressionCodec) -> EncryptParams<'static> {
+        let header = HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() };
+        EncryptParams::new(header).with_compression(codec, None)
+    }
+
+    fn encrypt(codec: CompressionCodec, data: &[u8]) -> (Vec<u8>, u64) {
+        let snapshot = encrypt_stream_v2(
+            InputSource::Memory(data.to_vec()),
+            OutputSink::Memory,
+            &master_key(),
+            params(codec),
+            common::config(),
+        )
+        .expect("encryption should succeed");
+        (snapshot.output.clone().unwrap(), snapshot.bytes_compressed)
+    }
+
+    fn decrypt(ciphertext: Vec<u8>) -> Vec<u8> {
+        common::decrypt(ciphertext, DecryptParams::new()).expect("decryption should succeed")
+    }
+
+    /// Headers of every non-final segment in the stream.
+    fn data_segment_headers(ciphertext: &[u8]) -> Vec<SegmentHeader> {
+        let mut headers = Vec::new();
+        let mut offset = HeaderV1::LEN;
+        while offset < ciphertext.len() {
+            let header = decode_segment_header(&ciphertext[offset..offset + SegmentHeader::LEN]).unwrap();
+            offset += SegmentHeader::LEN + header.wire_len as usize;
+            if !header.flags.contains(SegmentFlags::FINAL_SEGMENT) {
+                headers.push(header);
+            }
+        }
+        headers
+    }
+
+    #[test]
+    fn random_segments_are_stored_raw() {
+        let data = random_bytes();
+        for codec in [CompressionCodec::Zstd, CompressionCodec::Lz4, CompressionCodec::Deflate] {
+            let (ciphertext, bytes_compressed) = encrypt(codec, &data);
+
+            let headers = data_segment_headers(&ciphertext);
+            assert_eq!(headers.len(), 5);
+            assert!(headers.iter().all(|h| h.flags.contains(SegmentFlags::UNCOMPRESSED)), "{codec:?}");
+
+            // Telemetry reports the stored size, i.e. the plaintext itself
+            assert_eq!(bytes_compressed, data.len() as u64, "{codec:?}");
+            assert_eq!(decrypt(ciphertext), data, "{codec:?} roundtrip mismatch");
+        }
+    }
+
+    #[test]
+    fn random_output_grows_only_by_framing() {
+        let data = random_bytes();
+        // `Auto` is the pass-through codec: its stream is pure framing overhead
+        let (baseline, _) = encrypt(CompressionCodec::Auto, &data);
+        for codec in [CompressionCodec::Zstd, CompressionCodec::Lz4, CompressionCodec::Deflate] {
+            let (ciphertext, _) = encrypt(codec, &data);
+            assert_eq!(ciphertext.len(), baseline.len(), "{codec:?} output grew beyond framing overhead");
+        }
+    }
+
+    #[test]
+    fn compressible_segments_keep_compression() {
+        let data = text_bytes();
+        let (ciphertext, bytes_compressed) = encrypt(CompressionCodec::Zstd, &data);
+
+        assert!(data_segment_headers(&ciphertext).iter().all(|h| !h.flags.contains(SegmentFlags::UNCOMPRESSED)));
+        assert!(bytes_compressed < data.len() as u64);
+        assert_eq!(decrypt(ciphertext), data);
+    }
+
+    #[test]
+    fn mixed_stream_roundtrips_through_adapters() {
+        // Compressible segments followed by incompressible ones
+        let mut data = text_bytes();
+        data.truncate(2 * CHUNK);
+        data.extend_from_slice(&random_bytes()[..2 * CHUNK + 100]);
+
+        let mut writer = EncryptingWriter::new(Vec::new(), &master_key(), params(CompressionCodec::Lz4)).unwrap();
+        writer.write_all(&data).unwrap();
+        let ciphertext = writer.finish().unwrap();
+
+        let flags: Vec<bool> = data_segment_headers(&ciphertext)
+            .iter()
+            .map(|h| h.flags.contains(SegmentFlags::UNCOMPRESSED))
+            .collect();
+        assert_eq!(flags, vec![false, false, true, true, true]);
+
+        let mut reader = DecryptingReader::new(ciphertext.as_slice(), &master_key(), DecryptParams::new()).unwrap();
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+        assert_eq!(out, data);
+    }
+}
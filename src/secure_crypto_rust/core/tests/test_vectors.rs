@@ -0,0 +1,140 @@
+// # 📂 `tests/test_vectors.rs`
+#![cfg(feature = "testvectors")]
+//
+// Conformance vectors under `tests/vectors/`. Each file fixes its inputs;
+// `expected` holds the stream, frame nonces and segment digests they produce.
+//
+// Regenerate after an intentional format change with
+// `RSE1_BLESS_FIXTURES=1 cargo test --features testvectors --test test_vectors`,
+// then commit the rewritten files.
+
+#[cfg(test)]
+mod tests {
+    use std::{env, path::PathBuf};
+
+    use crypto_core::{
+        compression::CompressionCodec,
+        constants::flags,
+        headers::{HeaderV1, decode_header_le},
+        testvectors::{TestVector, TestVectorWriter, verify_test_vector},
+        types::StreamError,
+    };
+
+    const CHUNK: usize = 16 * 1024;
+    const BLESS_ENV: &str = "RSE1_BLESS_FIXTURES";
+
+    fn vector_path(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/vectors").join(format!("{name}.json"))
+    }
+
+    /// Frame AAD v3, as written by default headers.
+    fn header() -> HeaderV1 {
+        HeaderV1 { chunk_size: CHUNK as u32, flags: flags::SEGMENT_FLAGS_AAD, ..HeaderV1::test_header() }
+    }
+
+    fn segment_text(n: usize, len: usize) -> Vec<u8> {
+        format!("rse1 test vector, segment {n}. ").into_bytes().into_iter().cycle().take(len).collect()
+    }
+
+    /// The inputs each shipped vector must hold.
+    fn spec(name: &str) -> TestVectorWriter {
+        let key = [0x11u8; 32];
+        match name {
+            "empty" => TestVectorWriter::new(name, &key, header()),
+            "one_frame" => TestVectorWriter::new(name, &key, header())
+                .with_frame_size(4096)
+                .segment(b"The quick brown fox jumps over the lazy dog.".to_vec()),
+            "multi_segment_zstd" => {
+                TestVectorWriter::new(name, &key, HeaderV1 { compression: CompressionCodec::Zstd as u16, ..header() })
+                    .with_frame_size(4096)
+                    .segment(segment_text(0, CHUNK))
+                    .segment(segment_text(1, CHUNK))
+                    .segment(segment_text(2, 1000))
+            }
+            other => panic!("no spec for vector {other}"),
+        }
+    }
+
+    /// Compare inputs with the spec, then verify the recorded output (or bless it).
+    fn check(name: &str) {
+        let path = vector_path(name);
+        let on_disk = TestVector::load(&path).expect("vector file should parse");
+        let built = spec(name).build().expect("vector should build");
+        let inputs = |v: &TestVector| (v.master_key.clone(), v.header.clone(), v.frame_size, v.segments.clone());
+        assert_eq!(inputs(&on_disk), inputs(&built), "{name}: inputs differ from the spec");
+
+        if env::var_os(BLESS_ENV).is_some() {
+            on_disk.writer().unwrap().write(&path).unwrap();
+            return;
+        }
+        assert!(on_disk.expected.is_some(), "{name}: no recorded output; run with {BLESS_ENV}=1 and commit it");
+        verify_test_vector(&path).unwrap_or_else(|e| panic!("{name}: {e}"));
+        assert_eq!(on_disk.expected, built.expected, "{name}: regenerated output differs");
+    }
+
+    #[test]
+    fn empty_vector() {
+        check("empty");
+    }
+
+    #[test]
+    fn one_frame_vector() {
+        check("one_frame");
+    }
+
+    #[test]
+    fn multi_segment_zstd_vector() {
+        check("multi_segment_zstd");
+    }
+
+    #[test]
+    fn vectors_record_what_they_fix() {
+        let vector = spec("multi_segment_zstd").build().unwrap();
+        let expected = vector.expected.as_ref().unwrap();
+        let stream = hex::decode(&expected.stream).unwrap();
+
+        let header = decode_header_le(&stream[..HeaderV1::LEN]).unwrap();
+        assert_eq!(header.salt, *b"RSE1-test-salt-1");
+        assert_eq!(header.compression, CompressionCodec::Zstd as u16);
+
+        // Three data segments plus the final one, each with its digest
+        assert_eq!(expected.segment_digests.len(), 4);
+        assert!(expected.frames.iter().all(|f| f.nonce.len() == 24));
+        // Nonces are unique within the stream
+        let mut nonces: Vec<_> = expected.frames.iter().map(|f| &f.nonce).collect();
+        nonces.sort();
+        nonces.dedup();
+        assert_eq!(nonces.len(), expected.frames.len());
+
+        // Same inputs, same vector
+        assert_eq!(spec("multi_segment_zstd").build().unwrap(), vector);
+        // JSON round trip
+        assert_eq!(TestVector::from_json(&vector.to_json().unwrap()).unwrap(), vector);
+    }
+
+    #[test]
+    fn mismatch_is_reported() {
+        let dir = env::temp_dir().join(format!("rse1-vector-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("one_frame.json");
+
+        let mut vector = spec("one_frame").build().unwrap();
+        let expected = vector.expected.as_mut().unwrap();
+        let mut stream = hex::decode(&expected.stream).unwrap();
+        stream[HeaderV1::LEN + 40] ^= 0x01;
+        expected.stream = hex::encode(stream);
+        std::fs::write(&path, vector.to_json().unwrap()).unwrap();
+
+        let err = verify_test_vector(&path).unwrap_err();
+        assert!(matches!(&err, StreamError::Validation(msg) if msg.contains("differs at byte")), "{err:?}");
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn uneven_segments_are_rejected() {
+        let short_first = spec("empty").segment(vec![1u8; 10]).segment(vec![2u8; 10]);
+        assert!(matches!(short_first.build(), Err(StreamError::Validation(_))));
+        let empty_last = spec("empty").segment(Vec::new());
+        assert!(matches!(empty_last.build(), Err(StreamError::Validation(_))));
+    }
+}
@@ -0,0 +1,108 @@
+// # 📂 `tests/test_verify_stream.rs`
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use crypto_core::{
+        crypto::CryptoError,
+        headers::HeaderV1,
+        stream_v2::{
+            InputSource,
+            core::EncryptParams,
+            framing::FrameHeader,
+            segmenting::{SegmentHeader, decode_segment_header, encode_segment},
+            verify_stream_v2,
+        },
+        types::StreamError,
+    };
+
+    use crate::common::{self, master_key};
+
+    const CHUNK: usize = 16 * 1024;
+
+    fn plaintext() -> Vec<u8> {
+        common::plaintext(6 * CHUNK + 1000)
+    }
+
+    fn encrypt() -> Vec<u8> {
+        let header = HeaderV1 { chunk_size: CHUNK as u32, ..HeaderV1::test_header() };
+        common::encrypt(&plaintext(), EncryptParams::new(header))
+    }
+
+    /// Offset of segment `index`'s header in `ciphertext`.
+    fn segment_offset(ciphertext: &[u8], index: u32) -> usize {
+        let mut offset = HeaderV1::LEN;
+        for _ in 0..index {
+            let header = decode_segment_header(&ciphertext[offset..offset + SegmentHeader::LEN]).unwrap();
+            offset += SegmentHeader::LEN + header.wire_len as usize;
+        }
+        offset
+    }
+
+    #[test]
+    fn clean_stream_verifies() {
+        let ciphertext = encrypt();
+        let report = verify_stream_v2(InputSource::Memory(ciphertext), &master_key()).unwrap();
+
+        assert!(report.is_ok(), "unexpected failure: {:?}", report.failure);
+        // Six full chunks, one partial, and the empty final segment
+        assert_eq!(report.segments_verified, 8);
+        assert_eq!(report.bytes_plaintext, plaintext().len() as u64);
+        // Every data segment has at least one data frame plus digest and terminator
+        assert!(report.frames_verified >= 7 * 3);
+    }
+
+    #[test]
+    fn flipped_ciphertext_byte_reports_its_segment() {
+        let mut ciphertext = encrypt();
+        let offset = segment_offset(&ciphertext, 3);
+        ciphertext[offset + SegmentHeader::LEN + FrameHeader::LEN + 5] ^= 0x01;
+
+        let report = verify_stream_v2(InputSource::Memory(ciphertext), &master_key()).unwrap();
+        let failure = report.failure.expect("tampered stream must fail");
+        assert_eq!(failure.segment_index, 3);
+    }
+
+    #[test]
+    fn forged_wire_crc_reports_the_failing_frame() {
+        let mut ciphertext = encrypt();
+        let offset = segment_offset(&ciphertext, 2);
+        let header = decode_segment_header(&ciphertext[offset..offset + SegmentHeader::LEN]).unwrap();
+        let end = offset + SegmentHeader::LEN + header.wire_len as usize;
+
+        // Tamper with frame 0's ciphertext and recompute the segment CRC, so only AEAD catches it
+        let mut wire = ciphertext[offset + SegmentHeader::LEN..end].to_vec();
+        wire[FrameHeader::LEN] ^= 0x80;
+        let wire = Bytes::from(wire);
+        let forged = SegmentHeader::new(&wire, header.segment_index, header.bytes_len, header.frame_count, header.digest_alg, header.flags);
+        ciphertext.splice(offset..end, encode_segment(&forged, &wire).unwrap());
+
+        let report = verify_stream_v2(InputSource::Memory(ciphertext), &master_key()).unwrap();
+        let failure = report.failure.expect("tampered stream must fail");
+        assert_eq!(failure.segment_index, 2);
+        assert_eq!(failure.frame_index, Some(0));
+    }
+
+    #[test]
+    fn wrong_key_fails_before_the_first_segment() {
+        assert!(matches!(
+            verify_stream_v2(InputSource::Memory(encrypt()), &[0x22u8; 32]),
+            Err(StreamError::Crypto(CryptoError::KeyConfirmationFailed))
+        ));
+    }
+
+    #[test]
+    fn missing_final_segment_is_reported() {
+        let mut ciphertext = encrypt();
+        let final_offset = segment_offset(&ciphertext, 7);
+        ciphertext.truncate(final_offset);
+
+        let report = verify_stream_v2(InputSource::Memory(ciphertext), &master_key()).unwrap();
+        assert_eq!(report.segments_verified, 7);
+        let failure = report.failure.expect("truncated stream must fail");
+        assert_eq!(failure.segment_index, 7);
+        assert!(matches!(failure.error, StreamError::Validation(_)));
+    }
+}
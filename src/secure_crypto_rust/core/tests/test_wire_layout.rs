@@ -0,0 +1,133 @@
+// # 📂 `tests/test_wire_layout.rs`
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use bytes::Bytes;
+    use crypto_core::{
+        headers::{self, Endianness, FieldSpec, HeaderV1, encode_header_le},
+        stream_v2::{
+            framing::{self, FrameHeader, FrameType, encode::encode_frame},
+            segmenting::{self, SegmentHeader, encode_segment, types::SegmentFlags},
+        },
+    };
+
+    fn field(layout: &[FieldSpec], name: &str) -> Range<usize> {
+        let spec = layout.iter().find(|f| f.name == name).unwrap_or_else(|| panic!("no field {name}"));
+        spec.offset..spec.offset + spec.len
+    }
+
+    fn le(bytes: &[u8]) -> u64 {
+        bytes.iter().rev().fold(0, |acc, &b| (acc << 8) | b as u64)
+    }
+
+    fn assert_contiguous(layout: &[FieldSpec], len: usize) {
+        let mut offset = 0;
+        for spec in layout {
+            assert_eq!(spec.offset, offset, "{} is not contiguous", spec.name);
+            assert!(spec.len > 0);
+            offset += spec.len;
+        }
+        assert_eq!(offset, len);
+    }
+
+    #[test]
+    fn layouts_cover_each_header_exactly() {
+        assert_contiguous(&headers::layout(), HeaderV1::LEN);
+        assert_contiguous(&segmenting::layout(), SegmentHeader::LEN);
+        assert_contiguous(&framing::layout(), FrameHeader::LEN);
+    }
+
+    #[test]
+    fn stream_header_fields_sit_at_their_offsets() {
+        let header = HeaderV1 {
+            chunk_size: 0x0001_2000,
+            plaintext_size: 0x0102_0304_0506_0708,
+            key_id: 0x0A0B_0C0D,
+            parallel_hint: 7,
+            enc_time_ns: 0x1122_3344_5566_7788,
+            ..HeaderV1::test_header()
+        };
+        let bytes = encode_header_le(&header).unwrap();
+        let layout = headers::layout();
+
+        assert_eq!(&bytes[field(&layout, "magic")], b"RSE1");
+        assert_eq!(&bytes[field(&layout, "salt")], &header.salt);
+        for (name, value) in [
+            ("version", header.version as u64),
+            ("compression", header.compression as u64),
+            ("strategy", header.strategy as u64),
+            ("chunk_size", header.chunk_size as u64),
+            ("plaintext_size", header.plaintext_size),
+            ("key_id", header.key_id as u64),
+            ("parallel_hint", header.parallel_hint as u64),
+            ("enc_time_ns", header.enc_time_ns),
+        ] {
+            assert_eq!(le(&bytes[field(&layout, name)]), value, "{name}");
+        }
+        assert_eq!(le(&bytes[field(&layout, "crc32")]), crc32fast::hash(&bytes[..32]) as u64);
+        assert!(layout.iter().all(|f| f.endianness == Endianness::Bytes || f.len <= 8));
+    }
+
+    #[test]
+    fn segment_header_fields_sit_at_their_offsets() {
+        let wire = Bytes::from_static(b"frames");
+        let header = SegmentHeader::new(&wire, 0x0102_0304, 0x0A0B, 3, 2, SegmentFlags::FINAL_SEGMENT);
+        let bytes = encode_segment(&header, &wire).unwrap();
+        let layout = segmenting::layout();
+
+        for (name, value) in [
+            ("segment_index", header.segment_index as u64),
+            ("bytes_len", header.bytes_len as u64),
+            ("wire_len", wire.len() as u64),
+            ("wire_crc32", header.wire_crc32 as u64),
+            ("frame_count", 3),
+            ("digest_alg", 2),
+            ("flags", SegmentFlags::FINAL_SEGMENT.bits() as u64),
+            ("version", SegmentHeader::VERSION_1 as u64),
+            ("reserved", 0),
+        ] {
+            assert_eq!(le(&bytes[field(&layout, name)]), value, "{name}");
+        }
+        assert_eq!(&bytes[SegmentHeader::LEN..], &wire[..]);
+    }
+
+    #[test]
+    fn frame_header_fields_sit_at_their_offsets() {
+        let ciphertext = [0xA5u8; 19];
+        let header = FrameHeader {
+            segment_index: 0x0102_0304,
+            frame_index: 9,
+            frame_type: FrameType::Digest,
+            plaintext_len: 3,
+            ciphertext_len: ciphertext.len() as u32,
+        };
+        let bytes = encode_frame(&header, &ciphertext).unwrap();
+        let layout = framing::layout();
+
+        assert_eq!(&bytes[field(&layout, "magic")], b"SV2F");
+        for (name, value) in [
+            ("version", 1),
+            ("frame_type", FrameType::Digest as u64),
+            ("segment_index", header.segment_index as u64),
+            ("frame_index", 9),
+            ("plaintext_len", 3),
+            ("ciphertext_len", ciphertext.len() as u64),
+        ] {
+            assert_eq!(le(&bytes[field(&layout, name)]), value, "{name}");
+        }
+        assert_eq!(&bytes[FrameHeader::LEN..], &ciphertext);
+    }
+
+    #[test]
+    fn describe_lists_every_field() {
+        let text = headers::describe();
+        for title in ["HeaderV1 (80 bytes)", "SegmentHeader (26 bytes)", "FrameHeader (22 bytes)"] {
+            assert!(text.contains(title), "{text}");
+        }
+        for spec in headers::layout().iter().chain(&segmenting::layout()).chain(&framing::layout()) {
+            assert!(text.contains(spec.name), "{} missing", spec.name);
+        }
+    }
+}
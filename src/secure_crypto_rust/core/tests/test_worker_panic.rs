@@ -0,0 +1,149 @@
+// # 📂 `tests/test_worker_panic.rs`
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use bytes::Bytes;
+    use crossbeam::channel::{Sender, unbounded};
+    use crypto_core::{
+        crypto::{DigestAlg, KEY_LEN_32},
+        headers::HeaderV1,
+        recovery::persist::AsyncLogManager,
+        stream_v2::{
+            frame_worker::{
+                DecryptFrameInput, DecryptedFrame, EncryptFramePool, EncryptedFrame, FrameDispatch, FrameInput,
+                FrameWorkerError, FrameWorkerPool, decrypt::DecryptFrameWorker, encrypt::EncryptFrameWorker, recv_frame,
+            },
+            framing::FrameType,
+            parallelism::HybridParallelismProfile,
+            segment_worker::{
+                DecryptContext, DecryptSegmentInput, DecryptSegmentWorker, EncryptContext, EncryptSegmentInput,
+                EncryptSegmentWorker, SegmentWorkerError,
+            },
+            segmenting::types::SegmentFlags,
+        },
+        telemetry::StageTimes,
+        types::{ErrorCode, StreamError},
+    };
+
+    /// Far above a healthy segment; a hang fails the test instead of the run.
+    const BOUND: Duration = Duration::from_secs(10);
+
+    fn session_key() -> Vec<u8> {
+        vec![0x42u8; KEY_LEN_32]
+    }
+
+    fn segment() -> EncryptSegmentInput {
+        EncryptSegmentInput {
+            segment_index: 0,
+            bytes: Bytes::from(vec![0x5Au8; 40_000]),
+            flags: SegmentFlags::FINAL_SEGMENT,
+            stage_times: StageTimes::default(),
+        }
+    }
+
+    fn encrypt_worker(frame_pool: &FrameWorkerPool<FrameInput, EncryptedFrame>) -> EncryptSegmentWorker {
+        let header = HeaderV1::test_header();
+        let crypto =
+            EncryptContext::new(header, HybridParallelismProfile::single_threaded(), &session_key(), DigestAlg::Sha256)
+                .unwrap();
+        EncryptSegmentWorker::new(crypto, Arc::new(AsyncLogManager::disabled())).with_frame_pool(frame_pool.handle())
+    }
+
+    fn assert_panicked(err: SegmentWorkerError) {
+        match &err {
+            SegmentWorkerError::WorkerPanicked(msg) => assert!(msg.contains("injected frame panic"), "{msg}"),
+            other => panic!("expected WorkerPanicked, got {other:?}"),
+        }
+        assert_eq!(StreamError::SegmentWorker(err).code(), ErrorCode::Pipeline);
+    }
+
+    #[test]
+    fn panicking_encrypt_frame_worker_fails_the_segment() {
+        let header = HeaderV1::test_header();
+        let workers = vec![EncryptFrameWorker::new(header, &session_key()).unwrap()];
+        let pool = FrameWorkerPool::from_workers(workers, |worker: &EncryptFrameWorker, input: FrameInput| {
+            if input.frame_type == FrameType::Data {
+                panic!("injected frame panic");
+            }
+            worker.encrypt_tagged(&input)
+        });
+
+        let (seg_tx, seg_rx) = unbounded();
+        let (out_tx, out_rx) = unbounded();
+        let worker = encrypt_worker(&pool).run_v2(seg_rx, out_tx);
+        seg_tx.send(segment()).unwrap();
+
+        let result = out_rx.recv_timeout(BOUND).expect("segment worker hung on a panicked frame");
+        assert_panicked(result.unwrap_err());
+
+        // The frame panic was caught: the segment worker itself exits cleanly
+        drop(seg_tx);
+        worker.join().unwrap();
+    }
+
+    #[test]
+    fn panicking_decrypt_frame_worker_fails_the_segment() {
+        let header = HeaderV1::test_header();
+        let enc_pool = EncryptFramePool::encrypt(&header, &session_key(), 1).unwrap();
+        let (seg_tx, seg_rx) = unbounded();
+        let (enc_tx, enc_rx) = unbounded();
+        let enc_worker = encrypt_worker(&enc_pool).run_v2(seg_rx, enc_tx);
+        seg_tx.send(segment()).unwrap();
+        let encrypted = enc_rx.recv_timeout(BOUND).unwrap().unwrap();
+        drop(seg_tx);
+        enc_worker.join().unwrap();
+
+        let workers = vec![DecryptFrameWorker::new(header, &session_key()).unwrap()];
+        let dec_pool = FrameWorkerPool::from_workers(workers, |_: &DecryptFrameWorker, _: DecryptFrameInput| -> Result<DecryptedFrame, FrameWorkerError> {
+            panic!("injected frame panic")
+        });
+        let crypto = DecryptContext::from_stream_header(
+            header,
+            HybridParallelismProfile::single_threaded(),
+            &session_key(),
+            DigestAlg::Sha256,
+        )
+        .unwrap();
+        let (in_tx, in_rx) = unbounded();
+        let (out_tx, out_rx) = unbounded();
+        let dec_worker = DecryptSegmentWorker::new(crypto, Arc::new(AsyncLogManager::disabled()))
+            .with_frame_pool(dec_pool.handle())
+            .run_v2(in_rx, out_tx);
+        in_tx.send(DecryptSegmentInput::from(encrypted)).unwrap();
+
+        let result = out_rx.recv_timeout(BOUND).expect("segment worker hung on a panicked frame");
+        assert_panicked(result.unwrap_err());
+
+        // The panic was caught per job: the pool thread is still there to join
+        drop(in_tx);
+        dec_worker.join().unwrap();
+        dec_pool.shutdown();
+    }
+
+    /// A dispatcher whose workers are all gone.
+    struct DeadPool;
+
+    impl FrameDispatch<FrameInput> for DeadPool {
+        fn dispatch(&self, _: FrameInput) -> Result<(), FrameWorkerError> {
+            Err(FrameWorkerError::WorkerDisconnected)
+        }
+
+        fn workers_alive(&self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn collection_stops_waiting_once_workers_are_gone() {
+        // The reply sender stays open, as a segment worker's own clone would
+        let (_reply, out_rx): (Sender<Result<EncryptedFrame, FrameWorkerError>>, _) = unbounded();
+        let (done_tx, done_rx) = unbounded();
+        std::thread::spawn(move || {
+            let _ = done_tx.send(recv_frame(&out_rx, &DeadPool).map(|_| ()));
+        });
+        let result = done_rx.recv_timeout(BOUND).expect("recv_frame blocked on a dead pool");
+        assert!(matches!(result, Err(FrameWorkerError::WorkerDisconnected)));
+    }
+}
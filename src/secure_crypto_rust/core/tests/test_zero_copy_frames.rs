@@ -0,0 +1,81 @@
+// # 📂 `tests/test_zero_copy_frames.rs`
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use bytes::Bytes;
+    use crossbeam::channel::{Sender, unbounded};
+    use crypto_core::{
+        crypto::{DigestAlg, KEY_LEN_32},
+        headers::HeaderV1,
+        stream_v2::{
+            frame_worker::{FrameDispatch, FrameInput, FrameWorkerError, encrypt::EncryptFrameWorker},
+            framing::FrameType,
+            segment_worker::{EncryptSegmentInput, encrypt::process_encrypt_segment_2},
+            segmenting::types::SegmentFlags,
+        },
+        telemetry::StageTimes,
+    };
+
+    const FRAME_SIZE: usize = 4096;
+
+    /// Forwards frames to a worker and keeps the data frame plaintexts.
+    struct Recording {
+        inner: Sender<FrameInput>,
+        seen: Mutex<Vec<Bytes>>,
+    }
+
+    impl FrameDispatch<FrameInput> for Recording {
+        fn dispatch(&self, input: FrameInput) -> Result<(), FrameWorkerError> {
+            if input.frame_type == FrameType::Data {
+                self.seen.lock().unwrap().push(input.plaintext.clone());
+            }
+            self.inner.dispatch(input)
+        }
+    }
+
+    fn dispatched_frames(bytes: Bytes) -> Vec<Bytes> {
+        let header = HeaderV1::test_header();
+        let (frame_tx, frame_rx) = unbounded();
+        let (out_tx, out_rx) = unbounded();
+        EncryptFrameWorker::new(header, &[0x42u8; KEY_LEN_32]).unwrap().run(frame_rx, out_tx);
+
+        let recording = Recording { inner: frame_tx, seen: Mutex::new(Vec::new()) };
+        let input = EncryptSegmentInput {
+            segment_index: 0,
+            bytes,
+            flags: SegmentFlags::empty(),
+            stage_times: StageTimes::default(),
+        };
+        let seg = process_encrypt_segment_2(&input, FRAME_SIZE, DigestAlg::Sha256, &recording, &out_rx).unwrap();
+        assert_eq!(seg.header.bytes_len as usize, input.bytes.len());
+        recording.seen.into_inner().unwrap()
+    }
+
+    #[test]
+    fn data_frames_are_views_into_the_segment_buffer() {
+        let bytes: Bytes = (0..10 * FRAME_SIZE + 123).map(|i| i as u8).collect::<Vec<_>>().into();
+        let base = bytes.as_ptr() as usize;
+        let range = base..base + bytes.len();
+
+        let frames = dispatched_frames(bytes.clone());
+        assert_eq!(frames.len(), 11);
+        for (i, frame) in frames.iter().enumerate() {
+            let ptr = frame.as_ptr() as usize;
+            assert!(range.contains(&ptr), "frame {i} was copied out of the segment");
+            assert_eq!(ptr, base + i * FRAME_SIZE);
+            assert!(ptr + frame.len() <= range.end);
+        }
+        assert_eq!(frames.concat(), bytes);
+    }
+
+    #[test]
+    fn short_last_frame_is_a_view_too() {
+        let bytes = Bytes::from(vec![5u8; FRAME_SIZE + 1]);
+        let frames = dispatched_frames(bytes.clone());
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[1].len(), 1);
+        assert_eq!(frames[1].as_ptr(), bytes[FRAME_SIZE..].as_ptr());
+    }
+}
@@ -0,0 +1,82 @@
+// # 📂 `tests/test_zeroize.rs`
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use zeroize::Zeroizing;
+
+    use crypto_core::{
+        crypto::{DigestAlg, KEY_LEN_32, derive_session_key_32},
+        headers::HeaderV1,
+        stream_v2::{
+            frame_worker::{DecryptFrameInput, FrameInput, decrypt::DecryptFrameWorker, encrypt::EncryptFrameWorker},
+            framing::FrameType,
+            parallelism::HybridParallelismProfile,
+            segment_worker::{DecryptContext, EncryptContext, types::CryptoContextBase},
+            segmenting::types::SegmentFlags,
+        },
+    };
+
+    fn header() -> HeaderV1 {
+        HeaderV1 { chunk_size: 16 * 1024, ..HeaderV1::test_header() }
+    }
+
+    fn profile() -> HybridParallelismProfile {
+        HybridParallelismProfile::dynamic(header().chunk_size, 0.50, 64)
+    }
+
+    #[test]
+    fn session_key_is_zeroizing() {
+        let key: Zeroizing<[u8; KEY_LEN_32]> = derive_session_key_32(&[0x11u8; 32], &header()).unwrap();
+        assert!(key.iter().any(|&b| b != 0));
+    }
+
+    #[test]
+    fn contexts_have_drop_glue() {
+        // The session key is wiped by `Zeroizing`'s Drop impl
+        assert!(std::mem::needs_drop::<Zeroizing<[u8; KEY_LEN_32]>>());
+        assert!(std::mem::needs_drop::<CryptoContextBase>());
+        assert!(std::mem::needs_drop::<EncryptContext>());
+        assert!(std::mem::needs_drop::<DecryptContext>());
+    }
+
+    #[test]
+    fn debug_redacts_session_key() {
+        let ctx = EncryptContext::new(header(), profile(), &[0xABu8; KEY_LEN_32], DigestAlg::Blake3).unwrap();
+        let dbg = format!("{ctx:?}");
+        assert!(dbg.contains("<redacted>"));
+        assert!(!dbg.contains("171, 171")); // 0xAB bytes
+    }
+
+    #[test]
+    fn cloned_contexts_still_roundtrip() {
+        let session_key = derive_session_key_32(&[0x11u8; 32], &header()).unwrap();
+        let enc = EncryptContext::new(header(), profile(), session_key.as_slice(), DigestAlg::Blake3).unwrap();
+        let dec = DecryptContext::from_stream_header(header(), profile(), session_key.as_slice(), DigestAlg::Blake3).unwrap();
+
+        // Clone, then drop the originals: clones must own their key copies
+        let enc_clone = enc.clone();
+        let dec_clone = dec.clone();
+        drop(enc);
+        drop(dec);
+        drop(session_key);
+
+        let input = FrameInput {
+            segment_index: 0,
+            frame_index: 0,
+            frame_type: FrameType::Data,
+            plaintext: Bytes::from_static(b"cloned context roundtrip"),
+            segment_flags: SegmentFlags::empty(),
+        };
+        let frame = EncryptFrameWorker::new(enc_clone.header, enc_clone.base.session_key.as_slice())
+            .unwrap()
+            .encrypt_frame(&input)
+            .unwrap();
+        let decrypted = DecryptFrameWorker::new(dec_clone.header, dec_clone.base.session_key.as_slice())
+            .unwrap()
+            .decrypt_frame(DecryptFrameInput { wire: frame.wire, segment_flags: input.segment_flags })
+            .unwrap();
+
+        assert_eq!(decrypted.plaintext, input.plaintext);
+    }
+}
@@ -0,0 +1,114 @@
+// ## 📂 File: `python/src/ffi/errors.rs`
+//! Python exception hierarchy for `StreamError`.
+//!
+//! ```text
+//! StreamError
+//! ├── CryptoError     key, cipher, PRF or nonce policy errors (bad key length, unknown key id, ...)
+//! ├── HeaderError     malformed or unsupported stream header
+//! ├── IntegrityError  authentication failed: key check, AEAD tag, digest, plaintext checksum/length,
+//! │                   or a corrupted segment/frame
+//! └── IoError         reading the input or writing the output failed
+//! ```
+//!
+//! A failed key check (wrong key) and a tampered ciphertext both raise `IntegrityError`.
+//! Errors outside these groups (compression, pipeline) raise the base `StreamError`.
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+
+use crypto_core::{
+    crypto::CryptoError as CoreCryptoError,
+    stream_v2::{frame_worker::FrameWorkerError, segment_worker::SegmentWorkerError},
+    types::StreamError as CoreStreamError,
+};
+
+create_exception!(rust_crypto, StreamError, PyException, "Base class of every rust_crypto stream error.");
+create_exception!(rust_crypto, CryptoError, StreamError, "Key, cipher, PRF or nonce policy error.");
+create_exception!(rust_crypto, HeaderError, StreamError, "Malformed or unsupported stream header.");
+create_exception!(rust_crypto, IntegrityError, StreamError, "Authentication or integrity check failed.");
+create_exception!(rust_crypto, IoError, StreamError, "I/O error while reading or writing a stream.");
+
+pub fn register(py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("StreamError", py.get_type_bound::<StreamError>())?;
+    m.add("CryptoError", py.get_type_bound::<CryptoError>())?;
+    m.add("HeaderError", py.get_type_bound::<HeaderError>())?;
+    m.add("IntegrityError", py.get_type_bound::<IntegrityError>())?;
+    m.add("IoError", py.get_type_bound::<IoError>())?;
+    Ok(())
+}
+
+/// Exception group of a core error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    Crypto,
+    Header,
+    Integrity,
+    Io,
+    Other,
+}
+
+/// Convert a core error into the matching Python exception.
+pub fn to_py_err(error: CoreStreamError) -> PyErr {
+    let message = error.to_string();
+    match classify(&error) {
+        ErrorKind::Crypto => CryptoError::new_err(message),
+        ErrorKind::Header => HeaderError::new_err(message),
+        ErrorKind::Integrity => IntegrityError::new_err(message),
+        ErrorKind::Io => IoError::new_err(message),
+        ErrorKind::Other => StreamError::new_err(message),
+    }
+}
+
+pub fn classify(error: &CoreStreamError) -> ErrorKind {
+    match error {
+        CoreStreamError::Io(_) => ErrorKind::Io,
+        CoreStreamError::At { source, .. } => classify(source),
+        CoreStreamError::Header(_) => ErrorKind::Header,
+        CoreStreamError::Crypto(CoreCryptoError::TagMismatch | CoreCryptoError::KeyConfirmationFailed) => {
+            ErrorKind::Integrity
+        }
+        CoreStreamError::Crypto(_) | CoreStreamError::Nonce(_) | CoreStreamError::UnknownKeyId { .. } => {
+            ErrorKind::Crypto
+        }
+        CoreStreamError::Segment(_)
+        | CoreStreamError::Frame(_)
+        | CoreStreamError::Aad(_)
+        | CoreStreamError::ChecksumMismatch { .. }
+        | CoreStreamError::TruncatedStream { .. }
+        | CoreStreamError::StreamDigestMismatch => ErrorKind::Integrity,
+        CoreStreamError::SegmentWorker(e) => classify_segment_worker(e),
+        CoreStreamError::CompressionWorker(_)
+        | CoreStreamError::Compression(_)
+        | CoreStreamError::ReorderBufferOverflow { .. }
+        | CoreStreamError::PipelineError(_)
+        | CoreStreamError::Cancelled
+        | CoreStreamError::Validation(_) => ErrorKind::Other,
+    }
+}
+
+fn classify_segment_worker(error: &SegmentWorkerError) -> ErrorKind {
+    match error {
+        SegmentWorkerError::FrameWorkerError(e) => classify_frame_worker(e),
+        SegmentWorkerError::CryptoError(CoreCryptoError::TagMismatch) => ErrorKind::Integrity,
+        SegmentWorkerError::CryptoError(_) => ErrorKind::Crypto,
+        SegmentWorkerError::StateError(_) | SegmentWorkerError::WorkerPanicked(_) => ErrorKind::Other,
+        _ => ErrorKind::Integrity,
+    }
+}
+
+fn classify_frame_worker(error: &FrameWorkerError) -> ErrorKind {
+    match error {
+        FrameWorkerError::AtFrame { source, .. } => classify_frame_worker(source),
+        FrameWorkerError::Crypto(CoreCryptoError::TagMismatch)
+        | FrameWorkerError::CryptoFailure(_)
+        | FrameWorkerError::AuthenticationFailed { .. } => {
+            ErrorKind::Integrity
+        }
+        FrameWorkerError::Crypto(_) | FrameWorkerError::Nonce(_) => ErrorKind::Crypto,
+        FrameWorkerError::WorkerDisconnected | FrameWorkerError::WorkerMissing | FrameWorkerError::WorkerPanicked(_) => {
+            ErrorKind::Other
+        }
+        _ => ErrorKind::Integrity,
+    }
+}
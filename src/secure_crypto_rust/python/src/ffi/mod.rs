@@ -1,8 +1,13 @@
 
 use pyo3::prelude::*;
 
-#[pymodule]
-pub fn register(_py: Python<'_>, _m: &Bound<'_, PyModule>) -> PyResult<()> {
+pub mod errors;
+pub mod stream;
+pub mod types;
 
+/// Add the stream functions and exception types to the `rust_crypto` module.
+pub fn register(py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    errors::register(py, m)?;
+    stream::register(m)?;
     Ok(())
-}
\ No newline at end of file
+}
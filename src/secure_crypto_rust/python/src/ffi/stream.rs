@@ -0,0 +1,205 @@
+// ## 📂 File: `python/src/ffi/stream.rs`
+//! stream_v2 entry points for Python.
+//!
+//! - `encrypt_file` / `decrypt_file` stream between paths and return the
+//!   telemetry as a dict
+//! - `encrypt_bytes` / `decrypt_bytes` work on in-memory buffers
+//! - the GIL is released while the pipeline runs (`py.allow_threads`)
+//! - failures raise the exceptions of `ffi::errors`
+
+use std::path::PathBuf;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+
+use crypto_core::{
+    compression::CompressionCodec,
+    headers::HeaderV1,
+    stream_v2::{self, ApiConfig, DecryptParams, EncryptParams, InputSource, OutputSink},
+};
+
+use super::errors::to_py_err;
+use super::types::snapshot_to_dict;
+
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(encrypt_file, m)?)?;
+    m.add_function(wrap_pyfunction!(decrypt_file, m)?)?;
+    m.add_function(wrap_pyfunction!(encrypt_bytes, m)?)?;
+    m.add_function(wrap_pyfunction!(decrypt_bytes, m)?)?;
+    Ok(())
+}
+
+/// Encrypt `in_path` into `out_path`; returns the telemetry dict.
+#[pyfunction]
+#[pyo3(signature = (in_path, out_path, key, *, codec=None, chunk_size=None))]
+pub fn encrypt_file<'py>(
+    py: Python<'py>,
+    in_path: PathBuf,
+    out_path: PathBuf,
+    key: &[u8],
+    codec: Option<&str>,
+    chunk_size: Option<u32>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let params = encrypt_params(codec, chunk_size)?;
+    let key = key.to_vec();
+    let snapshot = py
+        .allow_threads(move || {
+            stream_v2::encrypt_stream_v2(
+                InputSource::File(in_path),
+                OutputSink::File(out_path),
+                &key,
+                params,
+                ApiConfig::default(),
+            )
+        })
+        .map_err(to_py_err)?;
+    snapshot_to_dict(py, &snapshot)
+}
+
+/// Decrypt `in_path` into `out_path`; returns the telemetry dict.
+#[pyfunction]
+#[pyo3(signature = (in_path, out_path, key))]
+pub fn decrypt_file<'py>(
+    py: Python<'py>,
+    in_path: PathBuf,
+    out_path: PathBuf,
+    key: &[u8],
+) -> PyResult<Bound<'py, PyDict>> {
+    let key = key.to_vec();
+    let snapshot = py
+        .allow_threads(move || {
+            stream_v2::decrypt_stream_v2(
+                InputSource::File(in_path),
+                OutputSink::File(out_path),
+                &key,
+                DecryptParams::new(),
+                ApiConfig::default(),
+            )
+        })
+        .map_err(to_py_err)?;
+    snapshot_to_dict(py, &snapshot)
+}
+
+/// Encrypt `data` into a complete stream.
+#[pyfunction]
+#[pyo3(signature = (data, key, *, codec=None, chunk_size=None))]
+pub fn encrypt_bytes<'py>(
+    py: Python<'py>,
+    data: &[u8],
+    key: &[u8],
+    codec: Option<&str>,
+    chunk_size: Option<u32>,
+) -> PyResult<Bound<'py, PyBytes>> {
+    let params = encrypt_params(codec, chunk_size)?;
+    let ciphertext = py
+        .allow_threads(|| stream_v2::encrypt_bytes(data, key, params))
+        .map_err(to_py_err)?;
+    Ok(PyBytes::new_bound(py, &ciphertext))
+}
+
+/// Decrypt a complete stream held in `data`.
+#[pyfunction]
+#[pyo3(signature = (data, key))]
+pub fn decrypt_bytes<'py>(py: Python<'py>, data: &[u8], key: &[u8]) -> PyResult<Bound<'py, PyBytes>> {
+    let plaintext = py
+        .allow_threads(|| stream_v2::decrypt_bytes(data, key, DecryptParams::new()))
+        .map_err(to_py_err)?;
+    Ok(PyBytes::new_bound(py, &plaintext))
+}
+
+/// Header for the optional `codec` / `chunk_size` keyword arguments.
+///
+/// Invalid arguments raise `ValueError`, before any work starts.
+fn encrypt_params(codec: Option<&str>, chunk_size: Option<u32>) -> PyResult<EncryptParams> {
+    let mut builder = HeaderV1::builder();
+    if let Some(codec) = codec {
+        builder = builder.compression(parse_codec(codec)?);
+    }
+    if let Some(chunk_size) = chunk_size {
+        builder = builder.chunk_size(chunk_size);
+    }
+    let header = builder.build().map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok(EncryptParams::new(header))
+}
+
+fn parse_codec(name: &str) -> PyResult<CompressionCodec> {
+    match name.to_ascii_lowercase().as_str() {
+        "auto" => Ok(CompressionCodec::Auto),
+        "zstd" => Ok(CompressionCodec::Zstd),
+        "lz4" => Ok(CompressionCodec::Lz4),
+        "deflate" => Ok(CompressionCodec::Deflate),
+        _ => Err(PyValueError::new_err(format!(
+            "unknown codec {name:?}: expected one of auto, zstd, lz4, deflate"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::errors::{CryptoError, IntegrityError, IoError};
+
+    const KEY: [u8; 32] = [0x11; 32];
+
+    fn with_py<T>(f: impl FnOnce(Python<'_>) -> T) -> T {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(f)
+    }
+
+    #[test]
+    fn bytes_roundtrip() {
+        with_py(|py| {
+            let data = b"segmented stream".repeat(5_000);
+            let ciphertext = encrypt_bytes(py, &data, &KEY, Some("zstd"), None).unwrap();
+            let plaintext = decrypt_bytes(py, ciphertext.as_bytes(), &KEY).unwrap();
+            assert_eq!(plaintext.as_bytes(), data.as_slice());
+        });
+    }
+
+    #[test]
+    fn wrong_key_raises_integrity_error() {
+        with_py(|py| {
+            let ciphertext = encrypt_bytes(py, b"secret", &KEY, None, None).unwrap();
+            let err = decrypt_bytes(py, ciphertext.as_bytes(), &[0x22; 32]).unwrap_err();
+            assert!(err.is_instance_of::<IntegrityError>(py), "{err}");
+        });
+    }
+
+    #[test]
+    fn bad_key_length_raises_crypto_error() {
+        with_py(|py| {
+            let err = encrypt_bytes(py, b"secret", &[0u8; 7], None, None).unwrap_err();
+            assert!(err.is_instance_of::<CryptoError>(py), "{err}");
+        });
+    }
+
+    #[test]
+    fn bad_arguments_raise_value_error() {
+        with_py(|py| {
+            assert!(encrypt_bytes(py, b"x", &KEY, Some("brotli"), None).unwrap_err().is_instance_of::<PyValueError>(py));
+            assert!(encrypt_bytes(py, b"x", &KEY, None, Some(0)).unwrap_err().is_instance_of::<PyValueError>(py));
+        });
+    }
+
+    #[test]
+    fn file_roundtrip_returns_telemetry() {
+        let dir = std::env::temp_dir().join(format!("rust_crypto_ffi_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (plain, enc, dec) = (dir.join("plain"), dir.join("enc"), dir.join("dec"));
+        std::fs::write(&plain, vec![0x5Au8; 200_000]).unwrap();
+
+        with_py(|py| {
+            let telemetry = encrypt_file(py, plain.clone(), enc.clone(), &KEY, Some("lz4"), Some(64 * 1024)).unwrap();
+            let bytes_plaintext: u64 = telemetry.get_item("bytes_plaintext").unwrap().unwrap().extract().unwrap();
+            assert_eq!(bytes_plaintext, 200_000);
+
+            decrypt_file(py, enc.clone(), dec.clone(), &KEY).unwrap();
+            assert_eq!(std::fs::read(&dec).unwrap(), std::fs::read(&plain).unwrap());
+
+            let err = decrypt_file(py, dir.join("missing"), dec.clone(), &KEY).unwrap_err();
+            assert!(err.is_instance_of::<IoError>(py), "{err}");
+        });
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
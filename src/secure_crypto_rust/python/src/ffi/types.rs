@@ -0,0 +1,49 @@
+// ## 📂 File: `python/src/ffi/types.rs`
+//! Conversions from core types to Python objects.
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde_json::Value;
+
+use crypto_core::telemetry::TelemetrySnapshot;
+
+/// `TelemetrySnapshot` as a Python dict, with the keys of `TelemetrySnapshot::to_json`
+/// (`elapsed_ns`, `stage_times` in nanoseconds, ...).
+pub fn snapshot_to_dict<'py>(py: Python<'py>, snapshot: &TelemetrySnapshot) -> PyResult<Bound<'py, PyDict>> {
+    let value = serde_json::to_value(snapshot)
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("telemetry encode failed: {e}")))?;
+    let dict = PyDict::new_bound(py);
+    if let Value::Object(map) = value {
+        for (key, value) in map {
+            dict.set_item(key, json_to_py(py, value)?)?;
+        }
+    }
+    Ok(dict)
+}
+
+fn json_to_py(py: Python<'_>, value: Value) -> PyResult<PyObject> {
+    Ok(match value {
+        Value::Null => py.None(),
+        Value::Bool(b) => b.into_py(py),
+        Value::Number(n) => match (n.as_u64(), n.as_i64()) {
+            (Some(u), _) => u.into_py(py),
+            (None, Some(i)) => i.into_py(py),
+            (None, None) => n.as_f64().unwrap_or(f64::NAN).into_py(py),
+        },
+        Value::String(s) => s.into_py(py),
+        Value::Array(items) => {
+            let list = PyList::empty_bound(py);
+            for item in items {
+                list.append(json_to_py(py, item)?)?;
+            }
+            list.into_py(py)
+        }
+        Value::Object(map) => {
+            let dict = PyDict::new_bound(py);
+            for (key, value) in map {
+                dict.set_item(key, json_to_py(py, value)?)?;
+            }
+            dict.into_py(py)
+        }
+    })
+}